@@ -39,7 +39,11 @@ def comment_cwe_pre(ghidra_address, text):
 def get_cwe_checker_output():
     ghidra_file = askFile('Select json output file of the cwe_checker', 'Open')
     with open(ghidra_file.getAbsolutePath()) as json_file:
-        return json.load(json_file)
+        report = json.load(json_file)
+    # Reports saved before the report schema was versioned are plain lists of warnings.
+    if isinstance(report, list):
+        return report
+    return report['warnings']
 
 
 def main():
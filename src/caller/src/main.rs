@@ -3,30 +3,110 @@
 
 extern crate cwe_checker_lib; // Needed for the docstring-link to work
 
-use cwe_checker_lib::analysis::graph;
+use cwe_checker_lib::analysis::concrete_interpreter::InterpreterConfig;
+use cwe_checker_lib::analysis::{function_signature, graph};
+use cwe_checker_lib::pcode::CallingConvention;
 use cwe_checker_lib::utils::binary::RuntimeMemoryImage;
-use cwe_checker_lib::utils::log::print_all_messages;
+use cwe_checker_lib::utils::log::{correlate_warnings, print_all_messages, CweWarning};
+use cwe_checker_lib::utils::report::{diff_reports, render_report, ReportFormat};
+use cwe_checker_lib::utils::{
+    analysis_profile, annotations, baseline, bench, corroboration, coverage_map, extern_semantics,
+    fact_export, ipc_contracts, safe_wrappers, scoring, test_case_extract,
+};
 use cwe_checker_lib::utils::{get_ghidra_plugin_path, read_config_file};
 use cwe_checker_lib::AnalysisResults;
-use cwe_checker_lib::{intermediate_representation::Project, utils::log::LogMessage};
+use cwe_checker_lib::{
+    intermediate_representation::{
+        NoReturnConfig, NormalizationConfig, OrdinalImportsConfig, Project, SymbolAliasesConfig,
+        SyscallsConfig, TaskEntryPointsConfig,
+    },
+    utils::log::LogMessage,
+};
 use nix::{sys::stat, unistd};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::thread;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 /// Find vulnerable patterns in binary executables
+///
+/// Without a subcommand the arguments of the `analyze` subcommand are expected.
+#[structopt(
+    setting = AppSettings::SubcommandsNegateReqs,
+    setting = AppSettings::ArgsNegateSubcommands
+)]
 struct CmdlineArgs {
+    #[structopt(flatten)]
+    analyze_args: AnalyzeArgs,
+
+    #[structopt(subcommand)]
+    command: Option<Subcommand>,
+}
+
+#[derive(Debug, StructOpt)]
+#[allow(clippy::large_enum_variant)]
+enum Subcommand {
+    /// Analyze a binary and report the found CWE warnings (the default if no subcommand is given).
+    Analyze(AnalyzeArgs),
+    /// Render a JSON report saved by the `analyze` subcommand in another output format.
+    Report(ReportArgs),
+    /// Export artifacts from an intermediate representation saved by the `analyze` subcommand.
+    Export(ExportArgs),
+    /// Compare two JSON reports saved by the `analyze` subcommand.
+    Evaluate(EvaluateArgs),
+    /// Analyze all binaries of a corpus and compare the reports with baseline reports.
+    Bench(BenchArgs),
+    /// Run the analysis of a test case extract saved by the `analyze` subcommand
+    /// and check whether the expected warning is generated.
+    Reproduce(ReproduceArgs),
+}
+
+#[derive(Debug, StructOpt)]
+struct AnalyzeArgs {
     /// The path to the binary.
-    #[structopt(required_unless("module-versions"), validator(check_file_existence))]
+    #[structopt(
+        required_unless_one(&["module-versions", "dry-run"]),
+        validator(check_file_existence)
+    )]
     binary: Option<String>,
 
     /// Path to a custom configuration file to use instead of the standard one.
     #[structopt(long, short, validator(check_file_existence))]
     config: Option<String>,
 
+    /// Path to a model file with semantics of extern functions (e.g. of the functions of a library).
+    /// The model is layered on top of the bundled models and the models in the configuration directory.
+    /// Can be given several times. Later models override the semantics of functions modelled by earlier models.
+    #[structopt(long, number_of_values = 1, validator(check_file_existence))]
+    extern_semantics: Vec<String>,
+
+    /// Adapt the configuration and the default set of checks to a class of binaries,
+    /// e.g. 'rtos' for firmware running on a real-time operating system like FreeRTOS or Zephyr.
+    /// Either the name of a bundled profile or the path to a profile file.
+    #[structopt(long)]
+    profile: Option<String>,
+
+    /// Path to a file with calling convention definitions
+    /// that replace the calling conventions exported by Ghidra.
+    ///
+    /// The file must contain a JSON object mapping CPU architecture names to lists of calling conventions.
+    #[structopt(long, validator(check_file_existence))]
+    calling_conventions: Option<String>,
+
+    /// Write the inferred register parameters of all functions as JSON to the given path.
+    /// The file can be imported with `--import-signatures` in the analysis of the same or a related binary.
+    #[structopt(long)]
+    export_signatures: Option<String>,
+
+    /// Use the function signatures in the JSON file at the given path (written by `--export-signatures`)
+    /// as initial assumptions of the signature inference.
+    /// The assumptions are verified and functions whose signatures do not match are logged.
+    #[structopt(long, validator(check_file_existence))]
+    import_signatures: Option<String>,
+
     /// Write the results to a file instead of stdout.
     /// This only affects CWE warnings. Log messages are still printed to stdout.
     #[structopt(long, short)]
@@ -46,20 +126,230 @@ struct CmdlineArgs {
     #[structopt(long, short)]
     quiet: bool,
 
+    /// Group CWE warnings of the same check that share a root cause (e.g. the same memory object).
+    /// Only the first warning of each group is printed as a finding, the other warnings are listed as related to it.
+    /// Warnings of checks with different evidence types at the same instruction are merged into findings with high confidence.
+    #[structopt(long)]
+    correlate: bool,
+
+    /// Check the generated CWE warnings against the assertion annotations contained in the binary
+    /// and print a message to stdout for each violated annotation.
+    /// Annotations are calls to marker functions like `__cwe_checker_expect_warning_CWE416()`.
+    #[structopt(long)]
+    check_annotations: bool,
+
+    /// Annotate each CWE warning with a score between 0 and 100 estimating how likely its location is reachable
+    /// by an attacker (using the loop depth and the call graph distances to entry points and input functions)
+    /// and sort the warnings by descending score.
+    #[structopt(long)]
+    score: bool,
+
+    /// Only report CWE warnings with at least the given score. Implies `--score`.
+    #[structopt(long)]
+    min_score: Option<u64>,
+
+    /// Write a JSON file to the given path listing for each function whether it is reachable from input functions,
+    /// its number of calls to dangerous sink functions and the amount of behavior unresolved by the analyses.
+    /// Intended to help with the selection of fuzzing targets.
+    #[structopt(long)]
+    export_coverage_map: Option<String>,
+
+    /// Add the IPC endpoints of the binary to the multi-binary session file at the given path
+    /// (created if it does not exist).
+    /// Data received on channels produced by binaries analyzed before in the same session
+    /// is treated as untrusted by the CWE-129 check, according to the contracts in the `IpcContracts` configuration.
+    #[structopt(long)]
+    ipc_session: Option<String>,
+
+    /// Merge the lifecycle metadata of the findings in the JSON report at the given path into the CWE warnings.
+    /// Findings contained in the report keep their first-seen timestamp and their triage state,
+    /// so a report saved with `--json` can be used as the baseline for the next run.
+    #[structopt(long, validator(check_file_existence))]
+    baseline: Option<String>,
+
+    /// Write the call graph, the intraprocedural dataflow edges and the CWE warnings as relational facts
+    /// into the given directory, one CSV file per table.
+    /// The facts can be imported into external query engines to write custom queries over the analysis results.
+    #[structopt(long)]
+    export_facts: Option<String>,
+
+    /// Write the intermediate representation of the binary after all preprocessing steps
+    /// as JSON to the given path. The file can be used as input for the `export` subcommand.
+    #[structopt(long)]
+    save_ir: Option<String>,
+
+    /// Write a self-contained test case extract for each CWE warning into the given directory, named `<finding ID>.json`.
+    /// An extract contains the intermediate representation of the functions affected by the warning,
+    /// the configuration of the check and the expected warning.
+    /// Use the `reproduce` subcommand to analyze an extract on its own.
+    #[structopt(long)]
+    extract_test_cases: Option<String>,
+
     /// Prints out the version numbers of all known modules.
     #[structopt(long)]
     module_versions: bool,
 
+    /// Print the checks that would be executed together with the analyses they require
+    /// and their effective configuration, and then quit without analyzing the binary.
+    #[structopt(long)]
+    dry_run: bool,
+
     /// Output for debugging purposes.
+    /// Without arguments the results of the pointer inference analysis are printed as JSON.
+    /// With `pi-trace <function>` the states at the start and end of each block of the function
+    /// are printed for each fixpoint iteration as an HTML page.
     /// The current behavior of this flag is unstable and subject to change.
-    #[structopt(long, hidden = true)]
-    debug: bool,
+    #[structopt(long, hidden = true, min_values = 0, max_values = 2)]
+    debug: Option<Vec<String>>,
+}
+
+/// The names of the modules that require the results of the pointer inference analysis.
+const MODULES_DEPENDING_ON_POINTER_INFERENCE: [&str; 49] = [
+    "CWE14",
+    "CWE78",
+    "CWE88",
+    "CWE120",
+    "CWE121",
+    "CWE122",
+    "CWE129",
+    "CWE131",
+    "CWE134",
+    "CWE197",
+    "CWE208",
+    "CWE252",
+    "CWE273",
+    "CWE295",
+    "CWE321",
+    "CWE326",
+    "CWE337",
+    "CWE362",
+    "CWE369",
+    "CWE401",
+    "CWE457",
+    "CWE467",
+    "CWE468",
+    "CWE469",
+    "CWE476",
+    "CWE479",
+    "CWE532",
+    "CWE606",
+    "CWE617",
+    "CWE667",
+    "CWE672",
+    "CWE674",
+    "CWE685",
+    "CWE690",
+    "CWE732",
+    "CWE761",
+    "CWE770",
+    "CWE775",
+    "CWE789",
+    "CWE798",
+    "CWE835",
+    "CWE843",
+    "CWE908",
+    "CWE910",
+    "CWE1335",
+    "CWE1341",
+    "Memory",
+    "SecretHygiene",
+    "StackIntegrity",
+];
+
+#[derive(Debug, StructOpt)]
+struct ReportArgs {
+    /// The path to the JSON report.
+    #[structopt(validator(check_file_existence))]
+    report: String,
+
+    /// The output format: text, json, sarif or html.
+    #[structopt(long, short, default_value = "text")]
+    format: ReportFormat,
+
+    /// Write the rendered report to a file instead of stdout.
+    #[structopt(long, short)]
+    out: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ExportArgs {
+    /// The path to the saved intermediate representation.
+    #[structopt(validator(check_file_existence))]
+    ir: String,
+
+    /// Write the control flow graph of the program in the DOT format of Graphviz to the given path.
+    #[structopt(long)]
+    cfg: String,
+}
+
+#[derive(Debug, StructOpt)]
+struct EvaluateArgs {
+    /// The path to the old JSON report.
+    #[structopt(validator(check_file_existence))]
+    old_report: String,
+
+    /// The path to the new JSON report.
+    #[structopt(validator(check_file_existence))]
+    new_report: String,
+
+    /// Generate JSON output.
+    #[structopt(long, short)]
+    json: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct ReproduceArgs {
+    /// The path to the test case extract.
+    #[structopt(validator(check_file_existence))]
+    extract: String,
+
+    /// Generate JSON output.
+    #[structopt(long, short)]
+    json: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct BenchArgs {
+    /// The directory containing the binaries to analyze.
+    #[structopt(long)]
+    corpus: String,
+
+    /// The directory containing the baseline reports, named `<binary name>.json`,
+    /// e.g. the output directory of an earlier run of the `bench` subcommand.
+    /// Runtime deltas are only shown if the directory also contains the runtimes of the baseline run.
+    #[structopt(long)]
+    baseline: String,
+
+    /// Save the new reports and the runtimes into the given directory,
+    /// so that it can be used as the baseline of later runs.
+    #[structopt(long, short)]
+    out: Option<String>,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    #[structopt(long, short, validator(check_file_existence))]
+    config: Option<String>,
+
+    /// Specify a specific set of checks to be run as a comma separated list, e.g. 'CWE332,CWE476,CWE782'.
+    #[structopt(long, short)]
+    partial: Option<String>,
+
+    /// Generate JSON output.
+    #[structopt(long, short)]
+    json: bool,
 }
 
 fn main() {
     let cmdline_args = CmdlineArgs::from_args();
 
-    run_with_ghidra(cmdline_args);
+    match cmdline_args.command {
+        None => run_with_ghidra(cmdline_args.analyze_args),
+        Some(Subcommand::Analyze(analyze_args)) => run_with_ghidra(analyze_args),
+        Some(Subcommand::Report(report_args)) => run_report(report_args),
+        Some(Subcommand::Export(export_args)) => run_export(export_args),
+        Some(Subcommand::Evaluate(evaluate_args)) => run_evaluate(evaluate_args),
+        Some(Subcommand::Bench(bench_args)) => run_bench(bench_args),
+        Some(Subcommand::Reproduce(reproduce_args)) => run_reproduce(reproduce_args),
+    }
 }
 
 /// Check the existence of a file
@@ -75,7 +365,7 @@ fn check_file_existence(file_path: String) -> Result<(), String> {
 }
 
 /// Run the cwe_checker with Ghidra as its backend.
-fn run_with_ghidra(args: CmdlineArgs) {
+fn run_with_ghidra(args: AnalyzeArgs) {
     let mut modules = cwe_checker_lib::get_modules();
     if args.module_versions {
         // Only print the module versions and then quit.
@@ -87,12 +377,38 @@ fn run_with_ghidra(args: CmdlineArgs) {
     }
 
     // Get the configuration file
-    let config: serde_json::Value = if let Some(config_path) = args.config {
+    let mut config: serde_json::Value = if let Some(config_path) = args.config {
         let file = std::io::BufReader::new(std::fs::File::open(config_path).unwrap());
         serde_json::from_reader(file).expect("Parsing of the configuration file failed")
     } else {
         read_config_file("config.json")
     };
+    // Merge the configuration entries of the analysis profile into the configuration.
+    let profile = args.profile.as_ref().map(|profile| {
+        analysis_profile::AnalysisProfile::load(profile).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid analysis profile: {}", error);
+            std::process::exit(101);
+        })
+    });
+    if let Some(profile) = &profile {
+        profile.apply_to_config(&mut config);
+    }
+    // Add the semantics of extern functions to the configuration.
+    let knowledge_base = extern_semantics::KnowledgeBase::load(&args.extern_semantics)
+        .unwrap_or_else(|error| {
+            eprintln!("Error: Invalid extern function model: {}", error);
+            std::process::exit(101);
+        });
+    knowledge_base.apply_to_config(&mut config);
+    let calling_conventions: BTreeMap<String, Vec<CallingConvention>> =
+        if let Some(calling_conventions_path) = args.calling_conventions {
+            let file =
+                std::io::BufReader::new(std::fs::File::open(calling_conventions_path).unwrap());
+            serde_json::from_reader(file)
+                .expect("Parsing of the calling convention definitions failed")
+        } else {
+            BTreeMap::new()
+        };
 
     // Filter the modules to be executed if the `--partial` parameter is set.
     if let Some(ref partial_module_list) = args.partial {
@@ -100,12 +416,79 @@ fn run_with_ghidra(args: CmdlineArgs) {
     } else {
         // TODO: CWE78 is disabled on a standard run for now,
         // because it uses up huge amounts of RAM and computation time on some binaries.
+        // The analysis profile may disable further checks.
         modules = modules
             .into_iter()
             .filter(|module| module.name != "CWE78")
+            .filter(|module| !matches!(&profile, Some(profile) if profile.is_disabled(module.name)))
             .collect();
     }
 
+    if let Err(error) = validate_module_configs(&modules, &config) {
+        eprintln!("Error: {}", error);
+        std::process::exit(101);
+    }
+    let no_return_config: NoReturnConfig =
+        cwe_checker_lib::utils::parse_config(&config["NoReturn"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of NoReturn: {}", error);
+            std::process::exit(101);
+        });
+    let symbol_aliases_config: SymbolAliasesConfig =
+        cwe_checker_lib::utils::parse_config(&config["SymbolAliases"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of SymbolAliases: {}", error);
+            std::process::exit(101);
+        });
+    let ordinal_imports_config: OrdinalImportsConfig =
+        cwe_checker_lib::utils::parse_config(&config["OrdinalImports"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of OrdinalImports: {}", error);
+            std::process::exit(101);
+        });
+    let normalization_config: NormalizationConfig =
+        cwe_checker_lib::utils::parse_config(&config["Normalization"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of Normalization: {}", error);
+            std::process::exit(101);
+        });
+    let syscalls_config: SyscallsConfig = cwe_checker_lib::utils::parse_config(&config["Syscalls"])
+        .unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of Syscalls: {}", error);
+            std::process::exit(101);
+        });
+    let task_entry_points_config: TaskEntryPointsConfig =
+        cwe_checker_lib::utils::parse_config(&config["TaskEntryPoints"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of TaskEntryPoints: {}", error);
+            std::process::exit(101);
+        });
+    let safe_wrapper_config: safe_wrappers::Config =
+        cwe_checker_lib::utils::parse_config(&config["SafeWrappers"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of SafeWrappers: {}", error);
+            std::process::exit(101);
+        });
+    let ipc_config: ipc_contracts::Config =
+        cwe_checker_lib::utils::parse_config(&config["IpcContracts"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of IpcContracts: {}", error);
+            std::process::exit(101);
+        });
+    let coverage_map_config: coverage_map::Config =
+        cwe_checker_lib::utils::parse_config(&config["CoverageMap"]).unwrap_or_else(|error| {
+            eprintln!("Error: Invalid configuration of CoverageMap: {}", error);
+            std::process::exit(101);
+        });
+    let interpreter_config: InterpreterConfig = cwe_checker_lib::utils::parse_config(
+        &config["GlobalInitialization"],
+    )
+    .unwrap_or_else(|error| {
+        eprintln!(
+            "Error: Invalid configuration of GlobalInitialization: {}",
+            error
+        );
+        std::process::exit(101);
+    });
+
+    if args.dry_run {
+        print_execution_plan(&modules, &config);
+        return;
+    }
+
     let binary_file_path = PathBuf::from(args.binary.unwrap());
     let binary: Vec<u8> = std::fs::read(&binary_file_path).unwrap_or_else(|_| {
         panic!(
@@ -113,9 +496,50 @@ fn run_with_ghidra(args: CmdlineArgs) {
             binary_file_path.display()
         )
     });
-    let (mut project, mut all_logs) = get_project_from_ghidra(&binary_file_path, &binary[..]);
+    let (mut project, mut all_logs) =
+        get_project_from_ghidra(&binary_file_path, &binary[..], &calling_conventions);
+    all_logs.extend(knowledge_base.get_layers().iter().map(|(name, version)| {
+        LogMessage::new_debug(format!(
+            "Applied extern function model {} (version {})",
+            name, version
+        ))
+    }));
     // Normalize the project and gather log messages generated from it.
     all_logs.append(&mut project.normalize());
+    // Rename aliases of standard functions (e.g. internal names of musl or uClibc) to the standard names.
+    all_logs.append(&mut project.canonicalize_symbol_aliases(&symbol_aliases_config.aliases));
+    // Rename extern symbols imported by ordinal (e.g. in Windows binaries) to the names of the imported functions.
+    all_logs.append(&mut project.resolve_ordinal_imports(&ordinal_imports_config.ordinals));
+    // Check the no-return flags of extern symbols for misclassifications,
+    // since they determine which code is reachable in the control flow graph.
+    all_logs.append(
+        &mut project
+            .check_no_return_flags(&no_return_config.symbols, no_return_config.auto_correct),
+    );
+    // Replace direct syscalls by calls to extern symbols representing them.
+    match cwe_checker_lib::utils::get_binary_operating_system(&binary) {
+        Ok(operating_system) => {
+            all_logs.append(
+                &mut project
+                    .replace_syscalls_by_extern_calls(operating_system, &syscalls_config.models),
+            );
+        }
+        Err(err) => all_logs.push(LogMessage::new_info(format!(
+            "Could not determine the operating system of the binary: {}",
+            err
+        ))),
+    }
+    // Infer internal functions that never return, e.g. wrappers around `abort`,
+    // after the no-return flags of extern symbols and syscalls are known.
+    if no_return_config.infer_internal_functions {
+        all_logs.append(&mut project.infer_no_return_functions());
+    }
+    // Add the functions started as tasks or threads to the entry points.
+    all_logs.append(
+        &mut project.add_task_entry_points(&task_entry_points_config.task_creation_symbols),
+    );
+    // Split huge basic blocks to bound the cost of analyzing a single block.
+    all_logs.append(&mut project.split_oversized_blocks(normalization_config.max_defs_per_block));
 
     // Generate the representation of the runtime memory image of the binary
     let mut runtime_memory_image = RuntimeMemoryImage::new(&binary).unwrap_or_else(|err| {
@@ -126,6 +550,20 @@ fn run_with_ghidra(args: CmdlineArgs) {
         // so that other analyses do not have to adjust their addresses.
         runtime_memory_image.add_global_memory_offset(project.program.term.address_base_offset);
     }
+    // Analyze the constructors of the binary to find global values set before `main` is executed.
+    // A maximum of zero steps disables the concrete execution.
+    let interpreter_config =
+        Some(interpreter_config).filter(|interpreter_config| interpreter_config.max_steps > 0);
+    all_logs.append(&mut cwe_checker_lib::analysis::global_initialization::run(
+        &mut project,
+        &binary,
+        &mut runtime_memory_image,
+        interpreter_config,
+    ));
+    if let Some(ir_path) = &args.save_ir {
+        std::fs::write(ir_path, serde_json::to_string(&project).unwrap())
+            .expect("Writing of the intermediate representation failed");
+    }
     // Generate the control flow graph of the program
     let extern_sub_tids = project
         .program
@@ -136,47 +574,210 @@ fn run_with_ghidra(args: CmdlineArgs) {
         .collect();
     let control_flow_graph = graph::get_program_cfg(&project.program, extern_sub_tids);
 
-    let analysis_results = AnalysisResults::new(
+    if args.export_signatures.is_some() || args.import_signatures.is_some() {
+        let imported_signatures: BTreeMap<String, function_signature::FunctionSignature> =
+            match &args.import_signatures {
+                Some(signatures_path) => {
+                    let file =
+                        std::io::BufReader::new(std::fs::File::open(signatures_path).unwrap());
+                    serde_json::from_reader(file)
+                        .expect("Parsing of the function signatures failed")
+                }
+                None => BTreeMap::new(),
+            };
+        let (signatures, mut logs) =
+            function_signature::infer_function_signatures(&project, &imported_signatures);
+        all_logs.append(&mut logs);
+        let signatures = function_signature::export_signatures(&project, &signatures);
+        if let Some(signatures_path) = &args.export_signatures {
+            std::fs::write(
+                signatures_path,
+                serde_json::to_string_pretty(&signatures).unwrap(),
+            )
+            .expect("Writing of the function signatures failed");
+        }
+        // The pointer inference only passes the parameter registers of the signatures to called functions.
+        config["Memory"]["function_signatures"] = serde_json::to_value(&signatures).unwrap();
+    }
+
+    let mut analysis_results = AnalysisResults::new(
         &binary,
         &runtime_memory_image,
         &control_flow_graph,
         &project,
     );
-
-    let modules_depending_on_pointer_inference = vec!["CWE78", "CWE476", "Memory"];
-    let pointer_inference_results = if modules
+    // The contracts of safe wrappers are checked using the pointer inference analysis.
+    if modules
         .iter()
-        .any(|module| modules_depending_on_pointer_inference.contains(&module.name))
+        .any(|module| MODULES_DEPENDING_ON_POINTER_INFERENCE.contains(&module.name))
+        || !safe_wrapper_config.wrappers.is_empty()
     {
-        Some(analysis_results.compute_pointer_inference(&config["Memory"]))
-    } else {
-        None
-    };
-    let analysis_results =
-        analysis_results.set_pointer_inference(pointer_inference_results.as_ref());
+        analysis_results = analysis_results.with_pointer_inference_config(&config["Memory"]);
+    }
 
     // Print debug and then return.
-    // Right now there is only one debug printing function.
-    // When more debug printing modes exist, this behaviour will change!
-    if args.debug {
-        cwe_checker_lib::analysis::pointer_inference::run(
-            &project,
-            &runtime_memory_image,
-            &control_flow_graph,
-            serde_json::from_value(config["Memory"].clone()).unwrap(),
-            true,
-        );
+    if let Some(debug_args) = &args.debug {
+        let mut pi_config: cwe_checker_lib::analysis::pointer_inference::Config =
+            cwe_checker_lib::utils::parse_config(&config["Memory"]).unwrap();
+        pi_config.resolve_memory_pool_addresses(&binary, project.program.term.address_base_offset);
+        match &debug_args[..] {
+            [] => {
+                cwe_checker_lib::analysis::pointer_inference::run(
+                    &project,
+                    &runtime_memory_image,
+                    &control_flow_graph,
+                    pi_config,
+                    true,
+                );
+            }
+            [mode, function_name] if mode == "pi-trace" => {
+                match cwe_checker_lib::analysis::pointer_inference::run_with_trace(
+                    &project,
+                    &runtime_memory_image,
+                    &control_flow_graph,
+                    pi_config,
+                    function_name,
+                ) {
+                    Ok(trace) => print!("{}", trace),
+                    Err(err) => {
+                        eprintln!("Error: {}", err);
+                        std::process::exit(101);
+                    }
+                }
+            }
+            _ => {
+                eprintln!("Error: Unknown debug mode. Supported: --debug [pi-trace <function>]");
+                std::process::exit(101);
+            }
+        }
         return;
     }
 
+    if let Some(session_path) = &args.ipc_session {
+        // The endpoint sites are found using the pointer inference analysis.
+        analysis_results = analysis_results.with_pointer_inference_config(&config["Memory"]);
+        let binary_name = binary_file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut session: ipc_contracts::Session = std::fs::read_to_string(session_path)
+            .ok()
+            .map(|session| {
+                serde_json::from_str(&session).expect("Parsing of the session file failed")
+            })
+            .unwrap_or_default();
+        session.add_binary(
+            &binary_name,
+            ipc_contracts::find_endpoint_sites(&analysis_results, &ipc_config, &binary_name),
+        );
+        std::fs::write(
+            session_path,
+            serde_json::to_string_pretty(&session).unwrap(),
+        )
+        .expect("Writing of the session file failed");
+        config["CWE129"]["ipc_sources"] =
+            serde_json::to_value(session.get_ipc_sources(&binary_name, &ipc_config)).unwrap();
+    }
+
+    if let Some(coverage_map_path) = &args.export_coverage_map {
+        let coverage_map = coverage_map::generate_coverage_map(&project, &coverage_map_config);
+        std::fs::write(
+            coverage_map_path,
+            serde_json::to_string_pretty(&coverage_map).unwrap(),
+        )
+        .expect("Writing of the coverage map failed");
+    }
+
     // Execute the modules and collect their logs and CWE-warnings.
     let mut all_cwes = Vec::new();
+    // The module that generated the warnings of each CWE name, needed for test case extracts.
+    let mut warning_modules: BTreeMap<String, &str> = BTreeMap::new();
     for module in modules {
         let (mut logs, mut cwes) = (module.run)(&analysis_results, &config[&module.name]);
+        for cwe in cwes.iter() {
+            warning_modules
+                .entry(cwe.name.clone())
+                .or_insert(module.name);
+        }
         all_logs.append(&mut logs);
         all_cwes.append(&mut cwes);
     }
 
+    if !safe_wrapper_config.wrappers.is_empty() {
+        all_cwes =
+            safe_wrappers::suppress_warnings_in_wrappers(&project, all_cwes, &safe_wrapper_config);
+        if let Some(pointer_inference) = analysis_results.pointer_inference() {
+            all_cwes.append(&mut safe_wrappers::check_wrapper_contracts(
+                &project,
+                pointer_inference,
+                &runtime_memory_image,
+                &safe_wrapper_config,
+            ));
+        }
+    }
+
+    if args.correlate {
+        let corroboration_config: corroboration::Config =
+            serde_json::from_value(config["Corroboration"].clone()).unwrap_or_default();
+        all_cwes = corroboration::corroborate_warnings(all_cwes, &corroboration_config);
+        all_cwes = correlate_warnings(all_cwes);
+    }
+
+    if args.check_annotations {
+        let annotations = annotations::get_annotations(&project);
+        for message in annotations::check_annotations(&project, &annotations, &all_cwes) {
+            println!("{}", message);
+        }
+    }
+
+    if args.score || args.min_score.is_some() {
+        let scoring_config: scoring::Config =
+            serde_json::from_value(config["Scoring"].clone()).unwrap_or_default();
+        scoring::score_warnings(&project, &mut all_cwes, &scoring_config);
+        scoring::filter_and_sort_by_score(&mut all_cwes, args.min_score.unwrap_or(0));
+    }
+
+    baseline::assign_finding_ids(&mut all_cwes);
+    if let Some(baseline_path) = &args.baseline {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        baseline::merge_with_baseline(&mut all_cwes, &read_report(baseline_path), timestamp);
+    }
+
+    if let Some(extract_path) = &args.extract_test_cases {
+        std::fs::create_dir_all(extract_path).expect("Creating the extract directory failed");
+        for cwe in all_cwes.iter() {
+            let extract = warning_modules.get(&cwe.name).and_then(|module| {
+                test_case_extract::TestCaseExtract::new(
+                    &project,
+                    &runtime_memory_image,
+                    cwe,
+                    module,
+                    &config,
+                )
+            });
+            match (extract, &cwe.finding_id) {
+                (Some(extract), Some(finding_id)) => std::fs::write(
+                    Path::new(extract_path).join(format!("{}.json", finding_id)),
+                    serde_json::to_string(&extract).unwrap(),
+                )
+                .expect("Writing of the test case extract failed"),
+                _ => all_logs.push(LogMessage::new_info(format!(
+                    "No test case extract generated for warning: {}",
+                    cwe.description
+                ))),
+            }
+        }
+    }
+
+    if let Some(facts_path) = &args.export_facts {
+        let facts = fact_export::generate_facts(&project, &all_cwes);
+        fact_export::write_facts(Path::new(facts_path), &facts)
+            .expect("Writing of the fact tables failed");
+    }
+
     // Print the results of the modules.
     if args.quiet {
         all_logs = Vec::new(); // Suppress all log messages since the `--quiet` flag is set.
@@ -184,6 +785,255 @@ fn run_with_ghidra(args: CmdlineArgs) {
     print_all_messages(all_logs, all_cwes, args.out.as_deref(), args.json);
 }
 
+/// Re-render a saved JSON report in the output format given by the `report` subcommand.
+fn run_report(args: ReportArgs) {
+    let cwes = read_report(&args.report);
+    let output = render_report(&cwes, args.format);
+    if let Some(file_path) = args.out {
+        std::fs::write(file_path, output).expect("Writing of the report failed");
+    } else {
+        print!("{}", output);
+    }
+}
+
+/// Export the control flow graph of a saved intermediate representation.
+fn run_export(args: ExportArgs) {
+    let file = std::io::BufReader::new(std::fs::File::open(&args.ir).unwrap());
+    let project: Project =
+        serde_json::from_reader(file).expect("Parsing of the intermediate representation failed");
+    let extern_sub_tids = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .map(|symbol| symbol.tid.clone())
+        .collect();
+    let control_flow_graph = graph::get_program_cfg(&project.program, extern_sub_tids);
+    std::fs::write(&args.cfg, graph::to_dot(&control_flow_graph))
+        .expect("Writing of the control flow graph failed");
+}
+
+/// Print the warnings added and removed between two saved JSON reports.
+fn run_evaluate(args: EvaluateArgs) {
+    let diff = diff_reports(
+        &read_report(&args.old_report),
+        &read_report(&args.new_report),
+    );
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff).unwrap());
+    } else {
+        println!("{}", diff);
+    }
+}
+
+/// Analyze a test case extract and print the generated warnings.
+/// Exit with an error code if the expected warning of the extract is not generated.
+fn run_reproduce(args: ReproduceArgs) {
+    let extract = std::fs::read_to_string(&args.extract).unwrap();
+    let extract = test_case_extract::TestCaseExtract::parse(&extract)
+        .unwrap_or_else(|err| panic!("Parsing of the extract {} failed: {}", args.extract, err));
+    let cwes = extract.run().unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(101);
+    });
+    let expected_id = baseline::get_finding_id(&extract.expected_warning);
+    let is_reproduced = cwes
+        .iter()
+        .any(|cwe| baseline::get_finding_id(cwe) == expected_id);
+    let message = if is_reproduced {
+        LogMessage::new_info("Expected warning reproduced")
+    } else {
+        LogMessage::new_error(format!(
+            "Expected warning not reproduced: {}",
+            extract.expected_warning.description
+        ))
+    };
+    print_all_messages(vec![message], cwes, None, args.json);
+    if !is_reproduced {
+        std::process::exit(1);
+    }
+}
+
+/// Analyze each binary of the corpus in a separate process,
+/// compare the reports with the baseline reports and print a summary of the changes.
+fn run_bench(args: BenchArgs) {
+    let baseline_directory = Path::new(&args.baseline);
+    let report_directory = match &args.out {
+        Some(out) => PathBuf::from(out),
+        None => std::env::temp_dir().join(format!("cwe_checker_bench_{}", std::process::id())),
+    };
+    std::fs::create_dir_all(&report_directory).expect("Creating the report directory failed");
+    let baseline_runtimes = bench::read_runtimes(baseline_directory)
+        .unwrap_or_else(|err| panic!("Parsing of the baseline runtimes failed: {}", err));
+    let mut binaries: Vec<PathBuf> = std::fs::read_dir(&args.corpus)
+        .expect("Reading of the corpus directory failed")
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    binaries.sort();
+    let executable =
+        std::env::current_exe().expect("Could not determine the cwe_checker executable");
+
+    let mut summary = bench::BenchSummary::default();
+    let mut runtimes = BTreeMap::new();
+    for binary_path in binaries {
+        let binary_name = binary_path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let report_path = bench::get_report_path(&report_directory, &binary_name);
+        let mut command = Command::new(&executable);
+        command
+            .arg("analyze")
+            .arg(&binary_path)
+            .args(["--json", "--quiet", "--out"])
+            .arg(&report_path);
+        if let Some(config) = &args.config {
+            command.arg("--config").arg(config);
+        }
+        if let Some(partial) = &args.partial {
+            command.arg("--partial").arg(partial);
+        }
+        let start_time = std::time::Instant::now();
+        let status = command.output().map(|output| output.status);
+        let runtime = start_time.elapsed().as_secs_f64();
+        let baseline_runtime = baseline_runtimes.get(&binary_name).copied();
+        let comparison = match status {
+            Ok(status) if status.success() => {
+                runtimes.insert(binary_name.clone(), runtime);
+                let baseline_path = bench::get_report_path(baseline_directory, &binary_name);
+                let baseline_report = baseline_path
+                    .exists()
+                    .then(|| read_report(&baseline_path.to_string_lossy()));
+                bench::BinaryComparison::new(
+                    &binary_name,
+                    baseline_report.as_deref(),
+                    &read_report(&report_path.to_string_lossy()),
+                    runtime,
+                    baseline_runtime,
+                )
+            }
+            Ok(status) => bench::BinaryComparison::failed(
+                &binary_name,
+                status.to_string(),
+                runtime,
+                baseline_runtime,
+            ),
+            Err(err) => bench::BinaryComparison::failed(
+                &binary_name,
+                err.to_string(),
+                runtime,
+                baseline_runtime,
+            ),
+        };
+        summary.binaries.push(comparison);
+    }
+    bench::write_runtimes(&report_directory, &runtimes).expect("Writing of the runtimes failed");
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+    } else {
+        println!("{}", summary);
+    }
+}
+
+/// Read a JSON report saved by the `analyze` subcommand.
+fn read_report(path: &str) -> Vec<CweWarning> {
+    let json = std::fs::read_to_string(path).unwrap();
+    baseline::parse_report(&json)
+        .unwrap_or_else(|err| panic!("Parsing of the report {} failed: {}", path, err))
+}
+
+/// Print the planned execution of a run with the given modules and configuration:
+/// the analyses that are executed, the checks together with the analyses they depend on,
+/// and the effective configuration of each check.
+fn print_execution_plan(modules: &[&cwe_checker_lib::CweModule], config: &serde_json::Value) {
+    let needs_pointer_inference = modules
+        .iter()
+        .any(|module| MODULES_DEPENDING_ON_POINTER_INFERENCE.contains(&module.name));
+    let global_initialization_steps = config["GlobalInitialization"]["max_steps"]
+        .as_u64()
+        .unwrap_or(0);
+    println!("[cwe_checker] execution plan:");
+    println!("Analyses:");
+    println!("  - Ghidra disassembly and P-Code extraction (heavy)");
+    println!("  - Normalization of the intermediate representation");
+    if global_initialization_steps > 0 {
+        println!(
+            "  - Concrete execution of constructors (at most {} steps each)",
+            global_initialization_steps
+        );
+    } else {
+        println!("  - Global initialization analysis");
+    }
+    println!("  - Control flow graph generation");
+    if needs_pointer_inference {
+        println!("  - Pointer Inference (heavy)");
+    }
+    println!("Checks:");
+    for module in modules {
+        let required_analyses = if MODULES_DEPENDING_ON_POINTER_INFERENCE.contains(&module.name) {
+            "control flow graph, Pointer Inference"
+        } else {
+            "control flow graph"
+        };
+        println!(
+            "  - {} {} (requires: {})",
+            module.name, module.version, required_analyses
+        );
+        let mut effective_config = (module.default_config)();
+        if let (Some(effective_settings), Some(settings)) = (
+            effective_config.as_object_mut(),
+            config[&module.name].as_object(),
+        ) {
+            for (key, value) in settings {
+                if !key.starts_with("_comment") {
+                    effective_settings.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        if effective_config
+            .as_object()
+            .is_some_and(|settings| !settings.is_empty())
+        {
+            println!(
+                "    config: {}",
+                serde_json::to_string(&effective_config).unwrap()
+            );
+        }
+    }
+}
+
+/// Check that the configurations of the given modules can be parsed into their configuration structs.
+/// The configuration of the pointer inference is also checked if one of the modules depends on it.
+fn validate_module_configs(
+    modules: &[&cwe_checker_lib::CweModule],
+    config: &serde_json::Value,
+) -> Result<(), String> {
+    let pointer_inference_module = &cwe_checker_lib::analysis::pointer_inference::CWE_MODULE;
+    let needs_pointer_inference = modules
+        .iter()
+        .any(|module| MODULES_DEPENDING_ON_POINTER_INFERENCE.contains(&module.name));
+    let mut modules_to_validate = modules.to_vec();
+    if needs_pointer_inference
+        && !modules
+            .iter()
+            .any(|module| module.name == pointer_inference_module.name)
+    {
+        modules_to_validate.push(pointer_inference_module);
+    }
+    for module in modules_to_validate {
+        (module.validate_config)(&config[module.name]).map_err(|error| {
+            format!(
+                "Invalid configuration of the {} module: {}",
+                module.name, error
+            )
+        })?;
+    }
+    Ok(())
+}
+
 /// Only keep the modules specified by the `--partial` parameter in the `modules` list.
 /// The parameter is a comma-separated list of module names, e.g. 'CWE332,CWE476,CWE782'.
 fn filter_modules_for_partial_run(
@@ -206,7 +1056,13 @@ fn filter_modules_for_partial_run(
 }
 
 /// Execute the `p_code_extractor` plugin in ghidra and parse its output into the `Project` data structure.
-fn get_project_from_ghidra(file_path: &Path, binary: &[u8]) -> (Project, Vec<LogMessage>) {
+///
+/// The `calling_conventions` given by the user replace the calling conventions exported by Ghidra.
+fn get_project_from_ghidra(
+    file_path: &Path,
+    binary: &[u8],
+    calling_conventions: &BTreeMap<String, Vec<CallingConvention>>,
+) -> (Project, Vec<LogMessage>) {
     let ghidra_path: std::path::PathBuf =
         serde_json::from_value(read_config_file("ghidra.json")["ghidra_path"].clone())
             .expect("Path to Ghidra not configured.");
@@ -299,6 +1155,7 @@ fn get_project_from_ghidra(file_path: &Path, binary: &[u8]) -> (Project, Vec<Log
     let mut project_pcode: cwe_checker_lib::pcode::Project =
         serde_json::from_reader(std::io::BufReader::new(file)).unwrap();
     let mut log_messages = project_pcode.normalize();
+    log_messages.append(&mut project_pcode.override_calling_conventions(calling_conventions));
     let project: Project = match cwe_checker_lib::utils::get_binary_base_address(binary) {
         Ok(binary_base_address) => project_pcode.into_ir_project(binary_base_address),
         Err(_err) => {
@@ -0,0 +1,554 @@
+//! Custom numeric domains that can be plugged into the value analysis of the pointer inference.
+//!
+//! A custom domain is combined with the [`IntervalDomain`] in the [`ExtendedIntervalDomain`],
+//! which the pointer inference uses for absolute values and pointer offsets.
+//! The operations of the extended domain are computed in both domains.
+//! After each operation the custom domain may restrict the interval of the result (a *reduction*),
+//! e.g. a congruence domain can restrict the interval bounds to values with the correct remainder.
+//! This way the checks, which only look at the intervals, profit from the precision of the custom domain.
+//!
+//! Values are extended by a custom domain with [`ExtendedIntervalDomain::extend_by_custom_domain`],
+//! e.g. by the pointer inference for the custom domain given in the `custom_domain` field of its configuration.
+//! Operations on an extended value and a value without custom component
+//! extend the latter by the same custom domain.
+//! Values that were never extended by a custom domain behave exactly like values of the interval domain.
+//!
+//! Independently of the custom domain, the extended domain tags values with the heap objects they were loaded from.
+//! The tags are propagated through all operations on the values,
+//! so that checks can recognize values derived from the contents of objects that were freed afterwards.
+
+use super::{
+    AbstractDomain, AbstractIdentifier, DataDomain, HasTop, IntervalDomain, RegisterDomain,
+    SizedDomain, SpecializeByConditional,
+};
+use super::{Interval, TryToBitvec, TryToInterval, TryToValueSet};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Display};
+use std::sync::Arc;
+
+/// An element of a custom numeric domain.
+///
+/// All operations return `None` if the result is the *Top* element of the custom domain.
+/// The operations only get elements of the same custom domain as operands,
+/// so implementations can downcast them to their own type with [`CustomDomain::as_any`].
+///
+/// The fixpoint computation only terminates if the custom domain has no infinite ascending chains
+/// or if its `merge` operation performs widening.
+pub trait CustomDomain: Debug + Send + Sync {
+    /// Merge two elements of the domain.
+    fn merge(&self, other: &dyn CustomDomain) -> Option<CustomValue>;
+
+    /// Compute the result of a binary operation.
+    fn bin_op(&self, op: BinOpType, rhs: &dyn CustomDomain) -> Option<CustomValue>;
+
+    /// Compute the result of a unary operation.
+    fn un_op(&self, op: UnOpType) -> Option<CustomValue>;
+
+    /// Extract a sub-bitvector.
+    fn subpiece(&self, low_byte: ByteSize, size: ByteSize) -> Option<CustomValue>;
+
+    /// Perform a typecast.
+    fn cast(&self, kind: CastOpType, width: ByteSize) -> Option<CustomValue>;
+
+    /// Create the element of the same custom domain that represents the values of the given interval.
+    fn lift_interval(&self, interval: &Interval) -> Option<CustomValue>;
+
+    /// Restrict the given interval of values to the values represented by `self`.
+    /// Return `None` if the interval cannot be restricted.
+    fn refine_interval(&self, interval: &Interval) -> Option<Interval>;
+
+    /// Return `self` as `Any` to allow downcasting to the concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Return whether `self` and `other` represent the same element of the domain.
+    fn equals(&self, other: &dyn CustomDomain) -> bool;
+
+    /// Return a hash of `self`, which has to be equal for equal elements.
+    fn hash_value(&self) -> u64;
+}
+
+/// A shared element of a custom numeric domain.
+#[derive(Debug, Clone)]
+pub struct CustomValue(Arc<dyn CustomDomain>);
+
+impl CustomValue {
+    /// Wrap an element of a custom domain.
+    pub fn new(value: impl CustomDomain + 'static) -> CustomValue {
+        CustomValue(Arc::new(value))
+    }
+}
+
+impl std::ops::Deref for CustomValue {
+    type Target = dyn CustomDomain;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl PartialEq for CustomValue {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0.equals(other.0.as_ref())
+    }
+}
+
+impl Eq for CustomValue {}
+
+impl std::hash::Hash for CustomValue {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.hash_value())
+    }
+}
+
+/// A function creating the element of a custom domain that represents the values of the given interval.
+/// It returns `None` if the element is the *Top* element of the custom domain.
+pub type CustomDomainConstructor = fn(&Interval) -> Option<CustomValue>;
+
+/// The product of the [`IntervalDomain`] with an optional custom numeric domain,
+/// together with the set of heap objects that the value was loaded from.
+///
+/// See the [module documentation](self) for more information.
+/// The custom component is not serialized.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ExtendedIntervalDomain {
+    /// The interval component.
+    interval: IntervalDomain,
+    /// The component of the custom domain. `None` represents the *Top* element of the custom domain.
+    #[serde(skip)]
+    custom: Option<CustomValue>,
+    /// The heap objects that the value (or a value it was computed from) was loaded from.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    origins: BTreeSet<AbstractIdentifier>,
+}
+
+impl ExtendedIntervalDomain {
+    /// Create a new domain with the given bounds.
+    /// Both `start` and `end` are inclusive.
+    pub fn new(start: Bitvector, end: Bitvector) -> Self {
+        IntervalDomain::new(start, end).into()
+    }
+
+    /// Combine the given components and restrict the interval to the values represented by the custom component.
+    fn from_components(interval: IntervalDomain, custom: Option<CustomValue>) -> Self {
+        let mut domain = ExtendedIntervalDomain {
+            interval,
+            custom,
+            origins: BTreeSet::new(),
+        };
+        if let Some(custom) = &domain.custom {
+            let interval = domain
+                .interval
+                .try_to_interval()
+                .unwrap_or_else(|_| Interval::new_top(domain.interval.bytesize()));
+            if let Some(refined_interval) = custom.refine_interval(&interval) {
+                if let Ok(intersection) = domain.interval.intersect(&refined_interval.into()) {
+                    domain.interval = intersection;
+                }
+            }
+        }
+        domain
+    }
+
+    /// Get the interval component.
+    pub fn get_interval_domain(&self) -> &IntervalDomain {
+        &self.interval
+    }
+
+    /// Get the custom component if it is not *Top*.
+    pub fn get_custom_value(&self) -> Option<&CustomValue> {
+        self.custom.as_ref()
+    }
+
+    /// Get the heap objects that the value was loaded from.
+    pub fn get_origins(&self) -> &BTreeSet<AbstractIdentifier> {
+        &self.origins
+    }
+
+    /// Add the given heap objects to the objects that the value was loaded from.
+    pub fn add_origins(&mut self, origins: &BTreeSet<AbstractIdentifier>) {
+        self.origins.extend(origins.iter().cloned());
+    }
+
+    /// Return `self` with the given set of origins.
+    fn with_origins(mut self, origins: BTreeSet<AbstractIdentifier>) -> Self {
+        self.origins = origins;
+        self
+    }
+
+    /// Get the union of the origins of `self` and `other`.
+    fn union_origins(&self, other: &Self) -> BTreeSet<AbstractIdentifier> {
+        self.origins.union(&other.origins).cloned().collect()
+    }
+
+    /// Compute the intersection of the interval components.
+    /// Return an error if the intersection is empty.
+    pub fn intersect(&self, other: &Self) -> Result<Self, Error> {
+        let interval = self.interval.intersect(&other.interval)?;
+        let custom = self.custom.clone().or_else(|| other.custom.clone());
+        Ok(Self::from_components(interval, custom).with_origins(self.union_origins(other)))
+    }
+
+    /// Check whether all values are representable by bitvectors of the given `size`.
+    pub fn fits_into_size(&self, size: ByteSize) -> bool {
+        self.interval.fits_into_size(size)
+    }
+
+    /// Extend the value by the element of the custom domain created by the given constructor for its interval.
+    /// Values that already have a custom component are returned unchanged.
+    pub fn extend_by_custom_domain(&self, constructor: CustomDomainConstructor) -> Self {
+        if self.custom.is_some() {
+            return self.clone();
+        }
+        let custom = self
+            .interval
+            .try_to_interval()
+            .ok()
+            .and_then(|interval| constructor(&interval));
+        Self::from_components(self.interval.clone(), custom).with_origins(self.origins.clone())
+    }
+
+    /// Apply the given operation to the custom components.
+    ///
+    /// If only one of the values has a custom component,
+    /// the other value is extended by the same custom domain first.
+    /// The result is *Top* if neither value has a custom component.
+    fn combine_custom(
+        &self,
+        other: &Self,
+        operation: impl Fn(&CustomValue, &CustomValue) -> Option<CustomValue>,
+    ) -> Option<CustomValue> {
+        let lift_interval = |custom: &CustomValue, interval: &IntervalDomain| {
+            interval
+                .try_to_interval()
+                .ok()
+                .and_then(|interval| custom.lift_interval(&interval))
+        };
+        match (&self.custom, &other.custom) {
+            (Some(custom), Some(other_custom)) => operation(custom, other_custom),
+            (Some(custom), None) => operation(custom, &lift_interval(custom, &other.interval)?),
+            (None, Some(other_custom)) => {
+                operation(&lift_interval(other_custom, &self.interval)?, other_custom)
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+impl From<IntervalDomain> for ExtendedIntervalDomain {
+    /// Extend the interval by the *Top* element of the custom domain.
+    fn from(interval: IntervalDomain) -> Self {
+        Self::from_components(interval, None)
+    }
+}
+
+impl From<IntervalDomain> for DataDomain<ExtendedIntervalDomain> {
+    fn from(interval: IntervalDomain) -> Self {
+        DataDomain::Value(interval.into())
+    }
+}
+
+impl From<Interval> for ExtendedIntervalDomain {
+    fn from(interval: Interval) -> Self {
+        IntervalDomain::from(interval).into()
+    }
+}
+
+impl From<Bitvector> for ExtendedIntervalDomain {
+    fn from(bitvec: Bitvector) -> Self {
+        IntervalDomain::from(bitvec).into()
+    }
+}
+
+impl AbstractDomain for ExtendedIntervalDomain {
+    /// Merge both components (performing widening on the interval component if necessary)
+    /// and the origins.
+    fn merge(&self, other: &Self) -> Self {
+        let custom =
+            self.combine_custom(other, |custom, other_custom| custom.merge(&**other_custom));
+        Self::from_components(self.interval.merge(&other.interval), custom)
+            .with_origins(self.union_origins(other))
+    }
+
+    /// Return `true` if both components are *Top* and the value is not tagged with any origins.
+    ///
+    /// The origins do not restrict the represented values,
+    /// but values tagged with origins must not be dropped like *Top* values,
+    /// e.g. when writing them to memory regions.
+    fn is_top(&self) -> bool {
+        self.interval.is_top() && self.custom.is_none() && self.origins.is_empty()
+    }
+}
+
+impl SizedDomain for ExtendedIntervalDomain {
+    fn bytesize(&self) -> ByteSize {
+        self.interval.bytesize()
+    }
+
+    fn new_top(bytesize: ByteSize) -> Self {
+        ExtendedIntervalDomain {
+            interval: IntervalDomain::new_top(bytesize),
+            custom: None,
+            origins: BTreeSet::new(),
+        }
+    }
+}
+
+impl HasTop for ExtendedIntervalDomain {
+    fn top(&self) -> Self {
+        Self::new_top(self.bytesize())
+    }
+}
+
+impl RegisterDomain for ExtendedIntervalDomain {
+    fn bin_op(&self, op: BinOpType, rhs: &Self) -> Self {
+        let custom = self.combine_custom(rhs, |custom, other_custom| {
+            custom.bin_op(op, &**other_custom)
+        });
+        Self::from_components(self.interval.bin_op(op, &rhs.interval), custom)
+            .with_origins(self.union_origins(rhs))
+    }
+
+    fn un_op(&self, op: UnOpType) -> Self {
+        let custom = self.custom.as_ref().and_then(|custom| custom.un_op(op));
+        Self::from_components(self.interval.un_op(op), custom).with_origins(self.origins.clone())
+    }
+
+    fn subpiece(&self, low_byte: ByteSize, size: ByteSize) -> Self {
+        let custom = self
+            .custom
+            .as_ref()
+            .and_then(|custom| custom.subpiece(low_byte, size));
+        Self::from_components(self.interval.subpiece(low_byte, size), custom)
+            .with_origins(self.origins.clone())
+    }
+
+    fn cast(&self, kind: CastOpType, width: ByteSize) -> Self {
+        let custom = self
+            .custom
+            .as_ref()
+            .and_then(|custom| custom.cast(kind, width));
+        Self::from_components(self.interval.cast(kind, width), custom)
+            .with_origins(self.origins.clone())
+    }
+}
+
+impl SpecializeByConditional for ExtendedIntervalDomain {
+    fn add_signed_less_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        let interval = self.interval.add_signed_less_equal_bound(bound)?;
+        Ok(Self::from_components(interval, self.custom).with_origins(self.origins))
+    }
+
+    fn add_unsigned_less_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        let interval = self.interval.add_unsigned_less_equal_bound(bound)?;
+        Ok(Self::from_components(interval, self.custom).with_origins(self.origins))
+    }
+
+    fn add_signed_greater_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        let interval = self.interval.add_signed_greater_equal_bound(bound)?;
+        Ok(Self::from_components(interval, self.custom).with_origins(self.origins))
+    }
+
+    fn add_unsigned_greater_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        let interval = self.interval.add_unsigned_greater_equal_bound(bound)?;
+        Ok(Self::from_components(interval, self.custom).with_origins(self.origins))
+    }
+
+    fn add_not_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        let interval = self.interval.add_not_equal_bound(bound)?;
+        Ok(Self::from_components(interval, self.custom).with_origins(self.origins))
+    }
+}
+
+impl std::ops::Add for ExtendedIntervalDomain {
+    type Output = ExtendedIntervalDomain;
+
+    fn add(self, rhs: Self) -> Self {
+        self.bin_op(BinOpType::IntAdd, &rhs)
+    }
+}
+
+impl std::ops::Sub for ExtendedIntervalDomain {
+    type Output = ExtendedIntervalDomain;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.bin_op(BinOpType::IntSub, &rhs)
+    }
+}
+
+impl std::ops::Neg for ExtendedIntervalDomain {
+    type Output = ExtendedIntervalDomain;
+
+    fn neg(self) -> Self {
+        self.un_op(UnOpType::Int2Comp)
+    }
+}
+
+impl TryToBitvec for ExtendedIntervalDomain {
+    fn try_to_bitvec(&self) -> Result<Bitvector, Error> {
+        self.interval.try_to_bitvec()
+    }
+}
+
+impl TryToInterval for ExtendedIntervalDomain {
+    fn try_to_interval(&self) -> Result<Interval, Error> {
+        self.interval.try_to_interval()
+    }
+}
+
+impl TryToValueSet for ExtendedIntervalDomain {
+    fn try_to_value_set(&self, max_cardinality: u64) -> Result<Vec<Bitvector>, Error> {
+        self.interval.try_to_value_set(max_cardinality)
+    }
+}
+
+impl Display for ExtendedIntervalDomain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.custom {
+            Some(custom) => write!(f, "{} ({:?})", self.interval, custom)?,
+            None => write!(f, "{}", self.interval)?,
+        }
+        if !self.origins.is_empty() {
+            let origins: Vec<String> = self.origins.iter().map(|id| id.to_string()).collect();
+            write!(f, " loaded from {}", origins.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A domain tracking whether values are even.
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Even;
+
+    impl Even {
+        fn new_value(interval: &Interval) -> Option<CustomValue> {
+            let value = interval.start.try_to_i64().ok()?;
+            (interval.start == interval.end && value % 2 == 0).then(|| CustomValue::new(Even))
+        }
+    }
+
+    impl CustomDomain for Even {
+        fn merge(&self, _other: &dyn CustomDomain) -> Option<CustomValue> {
+            Some(CustomValue::new(Even))
+        }
+
+        fn bin_op(&self, op: BinOpType, _rhs: &dyn CustomDomain) -> Option<CustomValue> {
+            matches!(
+                op,
+                BinOpType::IntAdd | BinOpType::IntSub | BinOpType::IntMult
+            )
+            .then(|| CustomValue::new(Even))
+        }
+
+        fn un_op(&self, _op: UnOpType) -> Option<CustomValue> {
+            None
+        }
+
+        fn subpiece(&self, _low_byte: ByteSize, _size: ByteSize) -> Option<CustomValue> {
+            None
+        }
+
+        fn cast(&self, _kind: CastOpType, _width: ByteSize) -> Option<CustomValue> {
+            None
+        }
+
+        fn lift_interval(&self, interval: &Interval) -> Option<CustomValue> {
+            Even::new_value(interval)
+        }
+
+        fn refine_interval(&self, interval: &Interval) -> Option<Interval> {
+            let one = Bitvector::one(interval.start.width());
+            let mut refined = interval.clone();
+            if refined.start.try_to_i64().ok()? % 2 != 0 {
+                refined.start += &one;
+            }
+            if refined.end.try_to_i64().ok()? % 2 != 0 {
+                refined.end -= &one;
+            }
+            Some(refined)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn equals(&self, other: &dyn CustomDomain) -> bool {
+            other.as_any().is::<Even>()
+        }
+
+        fn hash_value(&self) -> u64 {
+            0
+        }
+    }
+
+    fn bv(value: i64) -> Bitvector {
+        Bitvector::from_i64(value)
+    }
+
+    #[test]
+    fn custom_domain_extension() {
+        let even = |value: i64| {
+            ExtendedIntervalDomain::from(bv(value)).extend_by_custom_domain(Even::new_value)
+        };
+        let two = even(2);
+        let four = even(4);
+        assert!(two.get_custom_value().is_some());
+        assert!(even(3).get_custom_value().is_none());
+        // The merged interval [2, 6] is widened to Top,
+        // but the custom component restricts it again to even values.
+        // The value 6 without custom component is extended by the custom domain of the merge partner.
+        let merged = two.merge(&four).merge(&ExtendedIntervalDomain::from(bv(6)));
+        assert_eq!(merged.get_custom_value(), two.get_custom_value());
+        let restricted = merged
+            .add_signed_greater_equal_bound(&bv(3))
+            .unwrap()
+            .add_signed_less_equal_bound(&bv(9))
+            .unwrap();
+        assert_eq!(
+            restricted.try_to_interval().unwrap(),
+            Interval::new(bv(4), bv(8))
+        );
+        let sum = restricted.clone() + two.clone();
+        assert!(sum.get_custom_value().is_some());
+        assert!(restricted
+            .bin_op(BinOpType::IntAnd, &two)
+            .get_custom_value()
+            .is_none());
+
+        // Values not extended by a custom domain behave like intervals.
+        let plain_sum = ExtendedIntervalDomain::from(bv(2)) + ExtendedIntervalDomain::from(bv(4));
+        assert!(plain_sum.get_custom_value().is_none());
+        assert_eq!(
+            plain_sum.get_interval_domain(),
+            &IntervalDomain::from(bv(6))
+        );
+    }
+
+    #[test]
+    fn origin_propagation() {
+        let origin = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            crate::abstract_domain::AbstractLocation::Register("RAX".to_string(), ByteSize::new(8)),
+        );
+        let origins: BTreeSet<AbstractIdentifier> = vec![origin].into_iter().collect();
+        let mut loaded = ExtendedIntervalDomain::new_top(ByteSize::new(8));
+        loaded.add_origins(&origins);
+        // Tagged values differ from the Top value and are thus not Top themselves.
+        assert!(!loaded.is_top());
+        assert!(loaded.top().is_top());
+        assert_ne!(loaded, loaded.top());
+        let index = loaded
+            .cast(CastOpType::IntZExt, ByteSize::new(8))
+            .bin_op(BinOpType::IntMult, &ExtendedIntervalDomain::from(bv(4)));
+        assert_eq!(index.get_origins(), &origins);
+        let merged = ExtendedIntervalDomain::from(bv(2)).merge(&index);
+        assert_eq!(merged.get_origins(), &origins);
+        assert!(ExtendedIntervalDomain::from(bv(2)).get_origins().is_empty());
+    }
+}
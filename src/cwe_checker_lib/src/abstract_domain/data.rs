@@ -1,6 +1,6 @@
 use super::{
     AbstractDomain, AbstractIdentifier, HasTop, Interval, PointerDomain, RegisterDomain,
-    SizedDomain, SpecializeByConditional, TryToBitvec, TryToInterval,
+    SizedDomain, SpecializeByConditional, TryToBitvec, TryToInterval, TryToValueSet,
 };
 use crate::intermediate_representation::*;
 use crate::prelude::*;
@@ -289,6 +289,17 @@ impl<T: RegisterDomain + TryToInterval> TryToInterval for DataDomain<T> {
     }
 }
 
+impl<T: RegisterDomain + TryToValueSet> TryToValueSet for DataDomain<T> {
+    /// If the domain represents a small enough set of absolute values, return it.
+    fn try_to_value_set(&self, max_cardinality: u64) -> Result<Vec<Bitvector>, Error> {
+        match self {
+            DataDomain::Value(value) => value.try_to_value_set(max_cardinality),
+            DataDomain::Pointer(_) => Err(anyhow!("Value is a pointer.")),
+            DataDomain::Top(_) => Err(anyhow!("Value is Top")),
+        }
+    }
+}
+
 impl<T: RegisterDomain> std::ops::Add for DataDomain<T> {
     type Output = DataDomain<T>;
 
@@ -42,6 +42,11 @@ impl AbstractIdentifier {
     pub fn new(time: Tid, location: AbstractLocation) -> AbstractIdentifier {
         AbstractIdentifier(Arc::new(AbstractIdentifierData { time, location }))
     }
+
+    /// Get the time identifier of the abstract identifier.
+    pub fn get_tid(&self) -> &Tid {
+        &self.time
+    }
 }
 
 impl std::fmt::Display for AbstractIdentifier {
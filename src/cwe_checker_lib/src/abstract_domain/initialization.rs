@@ -0,0 +1,179 @@
+use super::{AbstractDomain, HasTop};
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// An abstract domain tracking which bytes of a memory region are initialized on all execution paths.
+///
+/// Offsets are saved as signed integers, which allows negative offsets, e.g. for downward growing stack frames.
+/// The initialized bytes are saved as a set of disjoint and non-adjacent half-open intervals.
+/// Bytes not contained in any interval may be uninitialized.
+/// If the domain is marked as completely initialized, all bytes of the region are considered to be initialized,
+/// e.g. after a write to an unknown offset inside the region.
+///
+/// The *Top* element of the domain is the region where every byte may be uninitialized.
+/// Merging two regions yields the bytes initialized in both regions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct InitializationDomain {
+    /// Maps the start offsets of the initialized intervals to their (exclusive) end offsets.
+    initialized: BTreeMap<i64, i64>,
+    /// Whether all bytes of the region are considered to be initialized.
+    completely_initialized: bool,
+}
+
+impl InitializationDomain {
+    /// Create a new region where every byte may be uninitialized.
+    pub fn new_uninitialized() -> InitializationDomain {
+        InitializationDomain::default()
+    }
+
+    /// Create a new region where every byte is initialized.
+    pub fn new_completely_initialized() -> InitializationDomain {
+        InitializationDomain {
+            initialized: BTreeMap::new(),
+            completely_initialized: true,
+        }
+    }
+
+    /// Mark the bytes in the interval from `start` (inclusive) to `end` (exclusive) as initialized.
+    pub fn mark_initialized(&mut self, start: i64, end: i64) {
+        if start >= end || self.completely_initialized {
+            return;
+        }
+        let mut new_start = start;
+        let mut new_end = end;
+        let overlapping: Vec<(i64, i64)> = self
+            .initialized
+            .range(..=end)
+            .rev()
+            .take_while(|(_, interval_end)| **interval_end >= start)
+            .map(|(interval_start, interval_end)| (*interval_start, *interval_end))
+            .collect();
+        for (interval_start, interval_end) in overlapping {
+            new_start = new_start.min(interval_start);
+            new_end = new_end.max(interval_end);
+            self.initialized.remove(&interval_start);
+        }
+        self.initialized.insert(new_start, new_end);
+    }
+
+    /// Mark all bytes of the region as initialized.
+    pub fn mark_completely_initialized(&mut self) {
+        self.initialized.clear();
+        self.completely_initialized = true;
+    }
+
+    /// Return whether all bytes in the interval from `start` (inclusive) to `end` (exclusive) are initialized.
+    pub fn is_initialized(&self, start: i64, end: i64) -> bool {
+        if self.completely_initialized || start >= end {
+            return true;
+        }
+        self.initialized
+            .range(..=start)
+            .next_back()
+            .map(|(_, interval_end)| *interval_end >= end)
+            .unwrap_or(false)
+    }
+
+    /// Return whether no byte in the interval from `start` (inclusive) to `end` (exclusive) is initialized.
+    pub fn is_uninitialized(&self, start: i64, end: i64) -> bool {
+        if self.completely_initialized || start >= end {
+            return false;
+        }
+        !self
+            .initialized
+            .range(..end)
+            .next_back()
+            .map(|(_, interval_end)| *interval_end > start)
+            .unwrap_or(false)
+    }
+}
+
+impl AbstractDomain for InitializationDomain {
+    /// Keep the bytes that are initialized in both regions.
+    fn merge(&self, other: &Self) -> Self {
+        if self.completely_initialized {
+            return other.clone();
+        } else if other.completely_initialized {
+            return self.clone();
+        }
+        let mut merged = InitializationDomain::new_uninitialized();
+        let mut other_intervals = other.initialized.iter().peekable();
+        for (&start, &end) in self.initialized.iter() {
+            while let Some(&(&other_start, &other_end)) = other_intervals.peek() {
+                if other_start >= end {
+                    break;
+                }
+                let intersection_start = start.max(other_start);
+                let intersection_end = end.min(other_end);
+                if intersection_start < intersection_end {
+                    merged
+                        .initialized
+                        .insert(intersection_start, intersection_end);
+                }
+                if other_end > end {
+                    break;
+                }
+                other_intervals.next();
+            }
+        }
+        merged
+    }
+
+    /// The region is *Top* if every byte may be uninitialized.
+    fn is_top(&self) -> bool {
+        !self.completely_initialized && self.initialized.is_empty()
+    }
+}
+
+impl HasTop for InitializationDomain {
+    /// Return a region where every byte may be uninitialized.
+    fn top(&self) -> Self {
+        InitializationDomain::new_uninitialized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initialization_tracking() {
+        let mut region = InitializationDomain::new_uninitialized();
+        assert!(region.is_top());
+        region.mark_initialized(-16, -8);
+        region.mark_initialized(-4, 0);
+        assert!(region.is_initialized(-16, -8));
+        assert!(!region.is_initialized(-16, -4));
+        assert!(region.is_uninitialized(-8, -4));
+        assert!(!region.is_uninitialized(-10, -6));
+        // Adjacent and overlapping intervals are coalesced.
+        region.mark_initialized(-8, -2);
+        assert!(region.is_initialized(-16, 0));
+        assert_eq!(region.initialized.len(), 1);
+        region.mark_completely_initialized();
+        assert!(!region.is_uninitialized(-100, -90));
+    }
+
+    #[test]
+    fn initialization_merge() {
+        let mut region = InitializationDomain::new_uninitialized();
+        region.mark_initialized(-16, -8);
+        region.mark_initialized(-4, 0);
+        let mut other = InitializationDomain::new_uninitialized();
+        other.mark_initialized(-12, -2);
+        let merged = region.merge(&other);
+        assert!(merged.is_initialized(-12, -8));
+        assert!(merged.is_initialized(-4, -2));
+        assert!(merged.is_uninitialized(-16, -12));
+        assert!(merged.is_uninitialized(-8, -4));
+        assert!(merged.is_uninitialized(-2, 0));
+        assert_eq!(merged, other.merge(&region));
+
+        let mut completely_initialized = InitializationDomain::new_uninitialized();
+        completely_initialized.mark_completely_initialized();
+        assert_eq!(region.merge(&completely_initialized), region);
+        assert!(region
+            .merge(&InitializationDomain::new_uninitialized())
+            .is_top());
+    }
+}
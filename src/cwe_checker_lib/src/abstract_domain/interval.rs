@@ -4,7 +4,7 @@ use crate::intermediate_representation::*;
 use crate::prelude::*;
 
 use super::{AbstractDomain, HasTop, RegisterDomain, SizedDomain, SpecializeByConditional};
-use super::{TryToBitvec, TryToInterval};
+use super::{TryToBitvec, TryToInterval, TryToValueSet};
 
 mod simple_interval;
 pub use simple_interval::*;
@@ -404,7 +404,7 @@ impl RegisterDomain for IntervalDomain {
     fn bin_op(&self, op: BinOpType, rhs: &Self) -> Self {
         use BinOpType::*;
         match op {
-            Piece | IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
+            IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
             | IntCarry | IntSCarry | IntSBorrow | IntAnd | IntOr | IntXOr | IntRight
             | IntSRight | IntDiv | IntSDiv | IntRem | IntSRem | BoolAnd | BoolOr | BoolXOr
             | FloatEqual | FloatNotEqual | FloatLess | FloatLessEqual | FloatAdd | FloatSub
@@ -427,6 +427,12 @@ impl RegisterDomain for IntervalDomain {
                     widening_delay: std::cmp::max(self.widening_delay, rhs.widening_delay),
                 }
             }
+            Piece => IntervalDomain {
+                interval: self.interval.piece(&rhs.interval),
+                widening_lower_bound: None,
+                widening_upper_bound: None,
+                widening_delay: std::cmp::max(self.widening_delay, rhs.widening_delay),
+            },
             IntAdd => self.add(rhs),
             IntSub => self.sub(rhs),
             IntMult => self.signed_mul(rhs),
@@ -603,6 +609,27 @@ impl TryToInterval for IntervalDomain {
     }
 }
 
+impl TryToValueSet for IntervalDomain {
+    /// If the interval contains at most `max_cardinality` values, return all of them.
+    fn try_to_value_set(&self, max_cardinality: u64) -> Result<Vec<Bitvector>, Error> {
+        let length = if self.is_top() {
+            0
+        } else {
+            self.interval.length().try_to_u64().unwrap_or(0)
+        };
+        if length == 0 || length > max_cardinality {
+            return Err(anyhow!("Value set is too large."));
+        }
+        let one = Bitvector::one(self.interval.start.width());
+        let mut values = vec![self.interval.start.clone()];
+        while (values.len() as u64) < length {
+            let next_value = values.last().unwrap().clone() + &one;
+            values.push(next_value);
+        }
+        Ok(values)
+    }
+}
+
 impl Display for IntervalDomain {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.is_top() {
@@ -99,11 +99,17 @@ impl Interval {
 
     /// Take a subpiece of the bitvectors.
     ///
-    /// The function only tries to be exact if the interval contains exactly one value
-    /// or if the `low_byte` is zero.
+    /// The function only tries to be exact if the interval contains exactly one value,
+    /// if the `low_byte` is zero or if the subpiece contains the most significant byte.
     pub fn subpiece(self, low_byte: ByteSize, size: ByteSize) -> Self {
         if self.start == self.end {
             self.start.subpiece(low_byte, size).into()
+        } else if low_byte + size == self.bytesize() {
+            // Taking the most significant bytes is a (monotone) signed division by a power of two.
+            Interval {
+                start: self.start.subpiece(low_byte, size),
+                end: self.end.subpiece(low_byte, size),
+            }
         } else if low_byte == ByteSize::new(0) {
             let new_min = Bitvector::signed_min_value(size.into())
                 .into_sign_extend(self.bytesize())
@@ -125,6 +131,30 @@ impl Interval {
         }
     }
 
+    /// Compute the interval of values resulting from piecing together a value of `self` as the most significant part
+    /// and a value of `lower` as the least significant part.
+    ///
+    /// The result is only exact if `self` contains exactly one value.
+    pub fn piece(&self, lower: &Interval) -> Interval {
+        if self.start != self.end {
+            return Interval::new_top(self.bytesize() + lower.bytesize());
+        }
+        let (lower_start, lower_end) =
+            if lower.start.sign_bit().to_bool() == lower.end.sign_bit().to_bool() {
+                (lower.start.clone(), lower.end.clone())
+            } else {
+                // The interval either contains both -1 and 0 or wraps around when interpreted as unsigned.
+                (
+                    Bitvector::zero(lower.start.width()),
+                    Bitvector::unsigned_max_value(lower.start.width()),
+                )
+            };
+        Interval {
+            start: self.start.bin_op(BinOpType::Piece, &lower_start).unwrap(),
+            end: self.start.bin_op(BinOpType::Piece, &lower_end).unwrap(),
+        }
+    }
+
     /// Take the 2's complement of values in the interval.
     pub fn int_2_comp(self) -> Self {
         if self
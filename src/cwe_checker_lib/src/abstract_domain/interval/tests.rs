@@ -215,6 +215,37 @@ fn subpiece() {
     );
 }
 
+#[test]
+fn piece_and_most_significant_subpiece() {
+    let i32_interval = |start: i32, end: i32| {
+        IntervalDomain::new(Bitvector::from_i32(start), Bitvector::from_i32(end))
+    };
+    let lower = i32_interval(0x10, 0x20);
+    let piece = i32_interval(1, 1).bin_op(BinOpType::Piece, &lower);
+    assert_eq!(piece, IntervalDomain::mock(0x1_0000_0010, 0x1_0000_0020));
+    // The result is only exact if the upper part is exact.
+    assert!(lower.bin_op(BinOpType::Piece, &lower).is_top());
+    // Lower parts containing -1 and 0 may contain all unsigned values.
+    assert_eq!(
+        i32_interval(0, 0).bin_op(BinOpType::Piece, &i32_interval(-1, 1)),
+        IntervalDomain::mock(0, 0xffff_ffff)
+    );
+    // The upper half of a 64-bit addition of register pairs.
+    let sum = piece.bin_op(
+        BinOpType::IntAdd,
+        &i32_interval(2, 2).bin_op(BinOpType::Piece, &i32_interval(-0x10, -0x10)),
+    );
+    assert_eq!(
+        sum.subpiece(ByteSize::new(4), ByteSize::new(4)),
+        i32_interval(4, 4)
+    );
+    assert_eq!(
+        IntervalDomain::mock(-0x1_0000_0000, 0x2_0000_0000)
+            .subpiece(ByteSize::new(4), ByteSize::new(4)),
+        i32_interval(-1, 2)
+    );
+}
+
 #[test]
 fn un_op() {
     // Int2Comp
@@ -544,3 +575,32 @@ fn float_nan_bytesize() {
     assert!(result.is_top());
     assert_eq!(result.bytesize(), ByteSize::new(1));
 }
+
+#[test]
+fn value_set_enumeration() {
+    use crate::abstract_domain::DataDomain;
+
+    let interval = IntervalDomain::mock(-1, 2);
+    assert_eq!(
+        interval.try_to_value_set(4).unwrap(),
+        vec![
+            Bitvector::from_i64(-1),
+            Bitvector::from_i64(0),
+            Bitvector::from_i64(1),
+            Bitvector::from_i64(2)
+        ]
+    );
+    assert!(interval.try_to_value_set(3).is_err());
+    assert!(IntervalDomain::new_top(ByteSize::new(8))
+        .try_to_value_set(u64::MAX)
+        .is_err());
+
+    let data: DataDomain<IntervalDomain> = IntervalDomain::mock(5, 5).into();
+    assert_eq!(
+        data.try_to_value_set(1).unwrap(),
+        vec![Bitvector::from_i64(5)]
+    );
+    assert!(DataDomain::<IntervalDomain>::new_top(ByteSize::new(8))
+        .try_to_value_set(1)
+        .is_err());
+}
@@ -22,6 +22,12 @@ pub use mem_region::*;
 mod interval;
 pub use interval::*;
 
+mod initialization;
+pub use initialization::*;
+
+mod custom;
+pub use custom::*;
+
 /// The main trait describing an abstract domain.
 ///
 /// Each abstract domain is partially ordered.
@@ -124,6 +130,15 @@ pub trait TryToInterval {
     }
 }
 
+/// A conversion trait for abstract domains that can represent finite sets of register values.
+pub trait TryToValueSet {
+    /// If `self` represents a set of at most `max_cardinality` absolute values, return all of them in ascending order.
+    /// Else return an error.
+    ///
+    /// Note that the returned set may be an overapproximation of the values that can actually occur at runtime.
+    fn try_to_value_set(&self, max_cardinality: u64) -> Result<Vec<Bitvector>, Error>;
+}
+
 /// A trait for domains whose values can be restricted by knowing the result of a comparison of it with a known bitvector.
 /// The comparison may also be used to add widening hints to the domain.
 ///
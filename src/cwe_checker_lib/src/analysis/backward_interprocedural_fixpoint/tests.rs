@@ -87,6 +87,7 @@ fn mock_program() -> Term<Program> {
         term: Sub {
             name: "sub1".to_string(),
             blocks: vec![sub1_blk1, sub1_blk2],
+            qualified_name: None,
         },
     };
     let cond_jump = Jmp::CBranch {
@@ -122,6 +123,7 @@ fn mock_program() -> Term<Program> {
         term: Sub {
             name: "sub2".to_string(),
             blocks: vec![sub2_blk1, sub2_blk2],
+            qualified_name: None,
         },
     };
     let program = Term {
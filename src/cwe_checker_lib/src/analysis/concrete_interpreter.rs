@@ -0,0 +1,528 @@
+//! A lightweight concrete interpreter for the intermediate representation.
+//!
+//! The interpreter executes code with concrete values for all registers and memory cells.
+//! It is intended for code that does not depend on any input to the program,
+//! e.g. initialization routines that decrypt embedded strings or set up tables at program start.
+//! The global memory written by such code can then be added to the [`RuntimeMemoryImage`]
+//! before the static analyses run.
+//!
+//! The execution is strictly bounded by the [`InterpreterConfig`].
+//! It is aborted as soon as a value is not concretely known,
+//! e.g. on reads of uninitialized registers or stack memory, calls to extern functions or indirect jumps.
+//! Aborted executions have no effect on the state of the interpreter.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use std::collections::{BTreeMap, HashMap};
+
+/// The bounds for a concrete execution.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct InterpreterConfig {
+    /// The maximum number of `Def` and `Jmp` terms executed in a single execution.
+    pub max_steps: u64,
+    /// The maximum number of bytes of global memory that the executions may write to in total.
+    pub max_written_bytes: u64,
+}
+
+/// The concrete state of a program: registers and memory written so far.
+#[derive(Debug, Clone, Default)]
+struct ConcreteState {
+    /// The values of the registers.
+    registers: HashMap<Variable, Bitvector>,
+    /// The bytes written to the stack, mapped to their addresses.
+    stack: BTreeMap<u64, u8>,
+    /// The bytes written to global memory, mapped to their addresses.
+    global_memory: BTreeMap<u64, u8>,
+    /// The addresses and sizes of all stores to global memory.
+    global_stores: BTreeMap<u64, ByteSize>,
+}
+
+/// A concrete interpreter for the intermediate representation.
+///
+/// Writes to global memory are kept across executions,
+/// so that later executions see the global memory written by earlier ones.
+pub struct ConcreteInterpreter<'a> {
+    project: &'a Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    config: InterpreterConfig,
+    /// Maps the TIDs of blocks to the blocks.
+    blocks: HashMap<&'a Tid, &'a Term<Blk>>,
+    /// Maps the TIDs of functions to their first block.
+    sub_entries: HashMap<&'a Tid, &'a Term<Blk>>,
+    /// The address range reserved for the stack, given by its start and end address.
+    stack_range: (u64, u64),
+    /// The global memory state after the last successful execution.
+    global_state: ConcreteState,
+}
+
+impl<'a> ConcreteInterpreter<'a> {
+    /// The size of the stack memory available to an execution.
+    const STACK_SIZE: u64 = 0x10000;
+
+    /// Create a new interpreter for the given project.
+    pub fn new(
+        project: &'a Project,
+        runtime_memory_image: &'a RuntimeMemoryImage,
+        config: InterpreterConfig,
+    ) -> ConcreteInterpreter<'a> {
+        let mut blocks = HashMap::new();
+        let mut sub_entries = HashMap::new();
+        for sub in project.program.term.subs.iter() {
+            if let Some(first_block) = sub.term.blocks.first() {
+                sub_entries.insert(&sub.tid, first_block);
+            }
+            for block in sub.term.blocks.iter() {
+                blocks.insert(&block.tid, block);
+            }
+        }
+        let pointer_bits = u64::from(project.get_pointer_bytesize()) * 8;
+        let stack_end = (1u64 << (pointer_bits - 1)) - Self::STACK_SIZE;
+        ConcreteInterpreter {
+            project,
+            runtime_memory_image,
+            config,
+            blocks,
+            sub_entries,
+            stack_range: (stack_end - Self::STACK_SIZE, stack_end),
+            global_state: ConcreteState::default(),
+        }
+    }
+
+    /// Concretely execute the function with the given TID until it returns.
+    ///
+    /// On success the global memory written by the function is kept for later executions.
+    /// Returns an error if the execution was aborted.
+    pub fn execute_sub(&mut self, sub_tid: &Tid) -> Result<(), Error> {
+        let mut state = self.global_state.clone();
+        state.registers.clear();
+        state.stack.clear();
+        let stack_pointer = &self.project.stack_pointer_register;
+        state.registers.insert(
+            stack_pointer.clone(),
+            Bitvector::from_u64(self.stack_range.1).into_zero_resize(stack_pointer.size),
+        );
+        let mut block = *self
+            .sub_entries
+            .get(sub_tid)
+            .ok_or_else(|| anyhow!("Function {} has no blocks.", sub_tid))?;
+        let mut return_targets: Vec<&Tid> = Vec::new();
+        let mut steps: u64 = 0;
+        loop {
+            for def in block.term.defs.iter() {
+                steps += 1;
+                self.check_step_bound(steps)?;
+                self.execute_def(&mut state, def)?;
+            }
+            let next_block = match self.get_next_jump_target(&state, block)? {
+                Some(target) => self.resolve_jump_target(target, &mut return_targets)?,
+                None => None,
+            };
+            steps += 1;
+            self.check_step_bound(steps)?;
+            match next_block {
+                Some(next_block) => block = next_block,
+                None => break,
+            }
+        }
+        self.global_state = state;
+        Ok(())
+    }
+
+    /// Get the values of all global memory cells written by successful executions.
+    ///
+    /// The values are returned with the address and size of the last store to them.
+    pub fn get_written_global_values(&self) -> BTreeMap<u64, Bitvector> {
+        self.global_state
+            .global_stores
+            .iter()
+            .filter_map(|(address, size)| {
+                let value = self.read(&self.global_state, *address, *size).ok()?;
+                Some((*address, value))
+            })
+            .collect()
+    }
+
+    /// Return an error if the number of executed steps exceeds the configured bound.
+    fn check_step_bound(&self, steps: u64) -> Result<(), Error> {
+        if steps > self.config.max_steps {
+            Err(anyhow!(
+                "Execution exceeded the maximum of {} steps.",
+                self.config.max_steps
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Execute a single `Def`.
+    fn execute_def(&self, state: &mut ConcreteState, def: &Term<Def>) -> Result<(), Error> {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let value = self.eval(state, value)?;
+                state.registers.insert(var.clone(), value);
+            }
+            Def::Load { var, address } => {
+                let address = self.eval(state, address)?.try_to_u64()?;
+                let value = self.read(state, address, var.size)?;
+                state.registers.insert(var.clone(), value);
+            }
+            Def::Store { address, value } => {
+                let address = self.eval(state, address)?.try_to_u64()?;
+                let value = self.eval(state, value)?;
+                self.write(state, address, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the jump of the block that is taken in the given state.
+    /// Returns `None` if the block has no jump.
+    fn get_next_jump_target<'b>(
+        &self,
+        state: &ConcreteState,
+        block: &'b Term<Blk>,
+    ) -> Result<Option<&'b Jmp>, Error> {
+        for jmp in block.term.jmps.iter() {
+            match &jmp.term {
+                Jmp::CBranch { condition, .. } => {
+                    if !self.eval(state, condition)?.is_zero() {
+                        return Ok(Some(&jmp.term));
+                    }
+                }
+                jump => return Ok(Some(jump)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get the block that the execution continues at after the given jump.
+    /// Returns `None` if the execution returns from the executed function.
+    fn resolve_jump_target(
+        &self,
+        jump: &'a Jmp,
+        return_targets: &mut Vec<&'a Tid>,
+    ) -> Result<Option<&'a Term<Blk>>, Error> {
+        let target = match jump {
+            Jmp::Branch(target) | Jmp::CBranch { target, .. } => target,
+            Jmp::Call {
+                target,
+                return_: Some(return_target),
+            } => {
+                let entry = self
+                    .sub_entries
+                    .get(target)
+                    .ok_or_else(|| anyhow!("Call to {} cannot be executed concretely.", target))?;
+                return_targets.push(return_target);
+                return Ok(Some(entry));
+            }
+            Jmp::Return(_) => match return_targets.pop() {
+                Some(target) => target,
+                None => return Ok(None),
+            },
+            _ => return Err(anyhow!("Jump cannot be executed concretely.")),
+        };
+        self.blocks
+            .get(target)
+            .copied()
+            .map(Some)
+            .ok_or_else(|| anyhow!("Unknown jump target {}.", target))
+    }
+
+    /// Concretely evaluate an expression.
+    fn eval(&self, state: &ConcreteState, expression: &Expression) -> Result<Bitvector, Error> {
+        use Expression::*;
+        match expression {
+            Var(var) => state
+                .registers
+                .get(var)
+                .cloned()
+                .ok_or_else(|| anyhow!("Register {} is not initialized.", var.name)),
+            Const(value) => Ok(value.clone()),
+            BinOp { op, lhs, rhs } => self.eval(state, lhs)?.bin_op(*op, &self.eval(state, rhs)?),
+            UnOp { op, arg } => self.eval(state, arg)?.un_op(*op),
+            Cast { op, size, arg } => self.eval(state, arg)?.cast(*op, *size),
+            Subpiece {
+                low_byte,
+                size,
+                arg,
+            } => Ok(self.eval(state, arg)?.subpiece(*low_byte, *size)),
+            Unknown { description, .. } => Err(anyhow!(
+                "Unknown expression {} cannot be evaluated.",
+                description
+            )),
+        }
+    }
+
+    /// Return `true` if the address is contained in the memory range reserved for the stack.
+    fn is_stack_address(&self, address: u64) -> bool {
+        address >= self.stack_range.0 && address < self.stack_range.1
+    }
+
+    /// Return the end address (exclusive) of a memory access of the given size at the given address.
+    ///
+    /// Returns an error if the access would wrap around the end of the address space.
+    fn get_end_address(address: u64, size: ByteSize) -> Result<u64, Error> {
+        address
+            .checked_add(u64::from(size))
+            .ok_or_else(|| anyhow!("Memory access at {:#x} exceeds the address space.", address))
+    }
+
+    /// Read a value from memory.
+    ///
+    /// Bytes not yet written by the execution are read from the memory image of the binary.
+    fn read(
+        &self,
+        state: &ConcreteState,
+        address: u64,
+        size: ByteSize,
+    ) -> Result<Bitvector, Error> {
+        let mut bytes = Vec::new();
+        for byte_address in address..Self::get_end_address(address, size)? {
+            if self.is_stack_address(byte_address) {
+                let byte = state
+                    .stack
+                    .get(&byte_address)
+                    .ok_or_else(|| anyhow!("Read of uninitialized stack memory."))?;
+                bytes.push(*byte);
+                continue;
+            }
+            match state.global_memory.get(&byte_address) {
+                Some(byte) => bytes.push(*byte),
+                None => bytes.push(
+                    self.runtime_memory_image
+                        .read_loaded_bytes(byte_address, 1)?[0],
+                ),
+            }
+        }
+        if self.runtime_memory_image.is_little_endian_byte_order() {
+            bytes.reverse();
+        }
+        let mut bytes = bytes.into_iter();
+        let mut value = Bitvector::from_u8(bytes.next().unwrap());
+        for byte in bytes {
+            value = value.bin_op(BinOpType::Piece, &Bitvector::from_u8(byte))?;
+        }
+        Ok(value)
+    }
+
+    /// Write a value to memory.
+    ///
+    /// Only writes to the stack and to writeable global memory are allowed.
+    fn write(
+        &self,
+        state: &mut ConcreteState,
+        address: u64,
+        value: &Bitvector,
+    ) -> Result<(), Error> {
+        let size = ByteSize::from(value.width());
+        let end_address = Self::get_end_address(address, size)?;
+        let mut bytes: Vec<u8> = (0..u64::from(size))
+            .map(|index| {
+                value
+                    .subpiece(ByteSize::new(index), ByteSize::new(1))
+                    .try_to_u64()
+                    .unwrap() as u8
+            })
+            .collect();
+        if !self.runtime_memory_image.is_little_endian_byte_order() {
+            bytes.reverse();
+        }
+        let is_stack_write =
+            self.is_stack_address(address) && self.is_stack_address(end_address - 1);
+        if is_stack_write {
+            for (offset, byte) in bytes.into_iter().enumerate() {
+                state.stack.insert(address + offset as u64, byte);
+            }
+            return Ok(());
+        }
+        if !self
+            .runtime_memory_image
+            .is_interval_writeable(address, end_address)?
+        {
+            return Err(anyhow!("Write to read-only memory at {:#x}.", address));
+        }
+        for (offset, byte) in bytes.into_iter().enumerate() {
+            state.global_memory.insert(address + offset as u64, byte);
+        }
+        state.global_stores.insert(address, size);
+        if state.global_memory.len() as u64 > self.config.max_written_bytes {
+            return Err(anyhow!(
+                "Execution exceeded the maximum of {} written bytes.",
+                self.config.max_written_bytes
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_project(blocks: Vec<Term<Blk>>) -> Project {
+        let mut sub = Sub::mock("init");
+        sub.term.blocks = blocks;
+        let mut project = Project::mock_empty();
+        project.program.term.subs.push(sub);
+        project
+    }
+
+    fn config() -> InterpreterConfig {
+        InterpreterConfig {
+            max_steps: 100,
+            max_written_bytes: 8,
+        }
+    }
+
+    /// A function copying `num_bytes` bytes of the string at 0x3002 to 0x2000 in a loop.
+    fn copy_loop(num_bytes: i64) -> Vec<Term<Blk>> {
+        let counter = Variable::mock("RAX", 8);
+        let byte = Variable::mock("BL", 1);
+        let condition = Variable::mock("ZF", 1);
+        vec![
+            Blk::mock_with(
+                "start",
+                vec![Def::assign(
+                    "init_counter",
+                    counter.clone(),
+                    Expression::const_from_i64(0),
+                )],
+                Jmp::Branch(Tid::new("loop")),
+            ),
+            Blk::mock_with_jmps(
+                "loop",
+                vec![
+                    Def::load(
+                        "load",
+                        byte.clone(),
+                        Expression::Var(counter.clone()).plus_const(0x3002),
+                    ),
+                    Def::store(
+                        "store",
+                        Expression::Var(counter.clone()).plus_const(0x2000),
+                        Expression::Var(byte),
+                    ),
+                    Def::assign(
+                        "increment",
+                        counter.clone(),
+                        Expression::Var(counter.clone()).plus_const(1),
+                    ),
+                    Def::assign(
+                        "compare",
+                        condition.clone(),
+                        Expression::BinOp {
+                            op: BinOpType::IntLess,
+                            lhs: Box::new(Expression::Var(counter)),
+                            rhs: Box::new(Expression::const_from_i64(num_bytes)),
+                        },
+                    ),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("loop"),
+                        condition: Expression::Var(condition),
+                    },
+                    Jmp::Branch(Tid::new("end")),
+                ],
+            ),
+            Blk::mock_with(
+                "end",
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn execute_bounded_loop() {
+        let project = mock_project(copy_loop(4));
+        let mem_image = RuntimeMemoryImage::mock();
+        let mut interpreter = ConcreteInterpreter::new(&project, &mem_image, config());
+        interpreter.execute_sub(&Tid::new("init")).unwrap();
+        let values = interpreter.get_written_global_values();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[&0x2000], Bitvector::from_u8(b'H'));
+        assert_eq!(values[&0x2003], Bitvector::from_u8(b'l'));
+    }
+
+    #[test]
+    fn abort_on_exceeded_bounds() {
+        // Exceeds the number of written bytes
+        let project = mock_project(copy_loop(9));
+        let mem_image = RuntimeMemoryImage::mock();
+        let mut interpreter = ConcreteInterpreter::new(&project, &mem_image, config());
+        assert!(interpreter.execute_sub(&Tid::new("init")).is_err());
+        assert!(interpreter.get_written_global_values().is_empty());
+        // Exceeds the number of steps
+        let config = InterpreterConfig {
+            max_steps: 10,
+            max_written_bytes: 8,
+        };
+        let project = mock_project(copy_loop(4));
+        let mut interpreter = ConcreteInterpreter::new(&project, &mem_image, config);
+        assert!(interpreter.execute_sub(&Tid::new("init")).is_err());
+        assert!(interpreter.get_written_global_values().is_empty());
+    }
+
+    #[test]
+    fn abort_on_accesses_exceeding_address_space() {
+        let value = Variable::mock("RAX", 8);
+        let mem_image = RuntimeMemoryImage::mock();
+        let accesses = vec![
+            (
+                Def::store(
+                    "store",
+                    Expression::const_from_i64(-4),
+                    Expression::Var(value.clone()),
+                ),
+                "0xfffffffffffffffc",
+            ),
+            (
+                Def::load("load", value.clone(), Expression::const_from_i64(-1)),
+                "0xffffffffffffffff",
+            ),
+        ];
+        for (access, address) in accesses {
+            let project = mock_project(vec![Blk::mock_with(
+                "start",
+                vec![
+                    Def::assign("init_value", value.clone(), Expression::const_from_i64(1)),
+                    access,
+                ],
+                Jmp::Return(Expression::const_from_i64(0)),
+            )]);
+            let mut interpreter = ConcreteInterpreter::new(&project, &mem_image, config());
+            let error = interpreter.execute_sub(&Tid::new("init")).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Memory access at {} exceeds the address space.", address)
+            );
+        }
+    }
+
+    #[test]
+    fn config_parsing() {
+        use crate::utils::parse_config;
+        let config: InterpreterConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "max_steps": 100,
+            "max_written_bytes": 64
+        }))
+        .unwrap();
+        assert_eq!(config.max_steps, 100);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<InterpreterConfig>(&serde_json::json!({
+            "max_steps": -1,
+            "max_written_bytes": 64
+        }))
+        .is_err());
+        assert!(parse_config::<InterpreterConfig>(&serde_json::json!({"max_steps": 100})).is_err());
+        assert!(parse_config::<InterpreterConfig>(&serde_json::json!({
+            "max_step": 100,
+            "max_written_bytes": 64
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,443 @@
+//! Intraprocedural def-use and use-def chains of register values.
+//!
+//! The chains of a function are computed by a reaching definitions analysis
+//! over the intraprocedural control flow graph of the function.
+//! Definitions are `Def::Assign` and `Def::Load` terms
+//! and calls, which define all registers that are not callee-saved according to the standard calling convention.
+//! Uses are the input registers of `Def` terms and jumps,
+//! including the register parameters of extern functions at their call sites.
+//! At calls to functions of the program, the registers of the function that are parameter registers
+//! of the standard calling convention are used.
+//!
+//! Values stored in memory are not tracked.
+//! Registers without reaching definitions inside the function (e.g. parameters of the function) have empty use-def chains.
+
+use crate::intermediate_representation::*;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// A term defining register values.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Definition<'a> {
+    /// A `Def::Assign` or `Def::Load` term.
+    Def(&'a Term<Def>),
+    /// A call, defining all registers that are not callee-saved.
+    Call(&'a Term<Jmp>),
+}
+
+/// The reaching definitions of each register at some point of a function.
+type ReachingDefinitions<'a> = HashMap<&'a Variable, BTreeSet<&'a Tid>>;
+
+/// The def-use and use-def chains of the registers of a function.
+#[derive(Debug, Clone)]
+pub struct DefUseChains<'a> {
+    /// The definitions of the function, indexed by their term identifiers.
+    definitions: HashMap<&'a Tid, Definition<'a>>,
+    /// Maps the term identifiers of uses to the definitions reaching each used register.
+    reaching_definitions: HashMap<&'a Tid, HashMap<&'a Variable, BTreeSet<&'a Tid>>>,
+    /// Maps the term identifiers of definitions to the terms and registers using the defined values.
+    uses: HashMap<&'a Tid, BTreeSet<(&'a Tid, &'a Variable)>>,
+    /// The reaching definitions of all registers right before each call of the function.
+    call_reaching_definitions: HashMap<&'a Tid, ReachingDefinitions<'a>>,
+}
+
+impl<'a> DefUseChains<'a> {
+    /// Compute the def-use and use-def chains of the given function.
+    pub fn new(project: &'a Project, sub: &'a Term<Sub>) -> DefUseChains<'a> {
+        let builder = ChainBuilder::new(project, sub);
+        let block_start_states = builder.compute_block_start_states(sub);
+
+        let mut chains = DefUseChains {
+            definitions: HashMap::new(),
+            reaching_definitions: HashMap::new(),
+            uses: HashMap::new(),
+            call_reaching_definitions: HashMap::new(),
+        };
+        for block in sub.term.blocks.iter() {
+            let mut state = match block_start_states.get(&block.tid) {
+                Some(state) => state.clone(),
+                None => continue, // The block is not reachable from the function entry.
+            };
+            for def in block.term.defs.iter() {
+                chains.add_uses(&def.tid, get_def_inputs(def), &state);
+                if let Def::Assign { var, .. } | Def::Load { var, .. } = &def.term {
+                    chains.definitions.insert(&def.tid, Definition::Def(def));
+                    state.insert(var, [&def.tid].iter().cloned().collect());
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                chains.add_uses(&jmp.tid, builder.get_jmp_inputs(jmp), &state);
+                if is_call(jmp) {
+                    chains.definitions.insert(&jmp.tid, Definition::Call(jmp));
+                    chains
+                        .call_reaching_definitions
+                        .insert(&jmp.tid, state.clone());
+                }
+            }
+        }
+        chains
+    }
+
+    /// Add the uses of the given registers by the term with the given TID to the chains.
+    fn add_uses(
+        &mut self,
+        use_tid: &'a Tid,
+        used_registers: Vec<&'a Variable>,
+        state: &ReachingDefinitions<'a>,
+    ) {
+        for var in used_registers {
+            if let Some(def_tids) = state.get(var) {
+                for def_tid in def_tids {
+                    self.uses.entry(def_tid).or_default().insert((use_tid, var));
+                }
+                self.reaching_definitions
+                    .entry(use_tid)
+                    .or_default()
+                    .insert(var, def_tids.clone());
+            }
+        }
+    }
+
+    /// Get the uses of the values defined by the term with the given TID.
+    /// Each use is given by the TID of the using term and the used register.
+    pub fn uses_of(&self, def_tid: &Tid) -> impl Iterator<Item = (&'a Tid, &'a Variable)> + '_ {
+        self.uses.get(def_tid).into_iter().flatten().cloned()
+    }
+
+    /// Get the TIDs of the definitions of the register `var` that reach its use in the term with the given TID.
+    pub fn defs_reaching(
+        &self,
+        use_tid: &Tid,
+        var: &Variable,
+    ) -> impl Iterator<Item = &'a Tid> + '_ {
+        self.reaching_definitions
+            .get(use_tid)
+            .and_then(|reaching_definitions| reaching_definitions.get(var))
+            .into_iter()
+            .flatten()
+            .cloned()
+    }
+
+    /// Get the TIDs of the definitions of the register `var` that reach the call with the given TID,
+    /// regardless of whether the called function uses the register (e.g. for variadic extern functions).
+    pub fn defs_reaching_call(
+        &self,
+        call_tid: &Tid,
+        var: &Variable,
+    ) -> impl Iterator<Item = &'a Tid> + '_ {
+        self.call_reaching_definitions
+            .get(call_tid)
+            .and_then(|reaching_definitions| reaching_definitions.get(var))
+            .into_iter()
+            .flatten()
+            .cloned()
+    }
+
+    /// Get the definition with the given TID.
+    pub fn get_definition(&self, def_tid: &Tid) -> Option<Definition<'a>> {
+        self.definitions.get(def_tid).cloned()
+    }
+}
+
+/// Helper struct for the computation of the reaching definitions of a function.
+struct ChainBuilder<'a> {
+    /// The extern symbols of the project, indexed by their TIDs.
+    extern_symbols: HashMap<&'a Tid, &'a ExternSymbol>,
+    /// The registers occuring in the function that are overwritten by calls.
+    clobbered_registers: Vec<&'a Variable>,
+    /// The registers occuring in the function that are parameter registers of the standard calling convention.
+    internal_call_parameters: Vec<&'a Variable>,
+}
+
+impl<'a> ChainBuilder<'a> {
+    /// Create a new builder for the chains of the given function.
+    fn new(project: &'a Project, sub: &'a Term<Sub>) -> ChainBuilder<'a> {
+        let extern_symbols = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| (&symbol.tid, symbol))
+            .collect();
+        let mut builder = ChainBuilder {
+            extern_symbols,
+            clobbered_registers: Vec::new(),
+            internal_call_parameters: Vec::new(),
+        };
+        builder.clobbered_registers = builder.get_clobbered_registers(project, sub);
+        if let Some(cconv) = project.get_standard_calling_convention() {
+            builder.internal_call_parameters = builder
+                .clobbered_registers
+                .iter()
+                .filter(|var| cconv.parameter_register.contains(&var.name))
+                .cloned()
+                .collect();
+        }
+        builder
+    }
+
+    /// Get all registers occuring in the function that are overwritten by calls,
+    /// i.e. all registers except the stack pointer and the callee-saved registers of the standard calling convention.
+    fn get_clobbered_registers(
+        &self,
+        project: &'a Project,
+        sub: &'a Term<Sub>,
+    ) -> Vec<&'a Variable> {
+        let callee_saved_registers: HashSet<&str> = project
+            .get_standard_calling_convention()
+            .map(|cconv| {
+                cconv
+                    .callee_saved_register
+                    .iter()
+                    .map(String::as_str)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut registers: BTreeSet<&Variable> = BTreeSet::new();
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                registers.extend(get_def_inputs(def));
+                if let Def::Assign { var, .. } | Def::Load { var, .. } = &def.term {
+                    registers.insert(var);
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                registers.extend(self.get_jmp_inputs(jmp));
+            }
+        }
+        registers
+            .into_iter()
+            .filter(|var| {
+                **var != project.stack_pointer_register
+                    && !callee_saved_registers.contains(var.name.as_str())
+            })
+            .collect()
+    }
+
+    /// Compute the reaching definitions at the start of each block reachable from the function entry.
+    fn compute_block_start_states(
+        &self,
+        sub: &'a Term<Sub>,
+    ) -> HashMap<&'a Tid, ReachingDefinitions<'a>> {
+        let mut block_start_states: HashMap<&Tid, ReachingDefinitions> = HashMap::new();
+        let entry_block = match sub.term.blocks.first() {
+            Some(block) => block,
+            None => return block_start_states,
+        };
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        block_start_states.insert(&entry_block.tid, HashMap::new());
+        let mut worklist = vec![entry_block];
+        let mut queued: HashSet<&Tid> = [&entry_block.tid].iter().cloned().collect();
+        while let Some(block) = worklist.pop() {
+            queued.remove(&block.tid);
+            let mut state = block_start_states[&block.tid].clone();
+            for def in block.term.defs.iter() {
+                if let Def::Assign { var, .. } | Def::Load { var, .. } = &def.term {
+                    state.insert(var, [&def.tid].iter().cloned().collect());
+                }
+            }
+            let state_after_call = block.term.jmps.iter().find(|jmp| is_call(jmp)).map(|call| {
+                let mut state_after_call = state.clone();
+                for var in self.clobbered_registers.iter() {
+                    state_after_call.insert(var, [&call.tid].iter().cloned().collect());
+                }
+                state_after_call
+            });
+            let outgoing_state = state_after_call.as_ref().unwrap_or(&state);
+            for target in get_intraprocedural_jump_targets(block, sub) {
+                let target_block = match blocks.get(target) {
+                    Some(target_block) => *target_block,
+                    None => continue,
+                };
+                let mut changed = !block_start_states.contains_key(target);
+                let target_state = block_start_states.entry(target).or_default();
+                for (var, def_tids) in outgoing_state.iter() {
+                    let target_def_tids = target_state.entry(var).or_default();
+                    for def_tid in def_tids {
+                        changed |= target_def_tids.insert(def_tid);
+                    }
+                }
+                if changed && queued.insert(target) {
+                    worklist.push(target_block);
+                }
+            }
+        }
+        block_start_states
+    }
+
+    /// Get the registers used by the jump.
+    /// For calls to extern functions these include the register parameters of the function.
+    /// For calls to functions of the program these include the parameter registers occuring in the calling function.
+    fn get_jmp_inputs(&self, jmp: &'a Term<Jmp>) -> Vec<&'a Variable> {
+        match &jmp.term {
+            Jmp::BranchInd(target) | Jmp::CallInd { target, .. } | Jmp::Return(target) => {
+                target.input_vars()
+            }
+            Jmp::CBranch { condition, .. } => condition.input_vars(),
+            Jmp::Call { target, .. } => match self.extern_symbols.get(target) {
+                Some(symbol) => symbol
+                    .parameters
+                    .iter()
+                    .filter_map(|parameter| match parameter {
+                        Arg::Register(var) => Some(var),
+                        Arg::Stack { .. } => None,
+                    })
+                    .collect(),
+                None => self.internal_call_parameters.clone(),
+            },
+            Jmp::Branch(_) | Jmp::CallOther { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Get the registers used by the `Def` term.
+fn get_def_inputs(def: &Term<Def>) -> Vec<&Variable> {
+    match &def.term {
+        Def::Assign { value, .. } => value.input_vars(),
+        Def::Load { address, .. } => address.input_vars(),
+        Def::Store { address, value } => {
+            let mut inputs = address.input_vars();
+            inputs.extend(value.input_vars());
+            inputs
+        }
+    }
+}
+
+/// Returns `true` if the jump is a call.
+fn is_call(jmp: &Term<Jmp>) -> bool {
+    matches!(
+        jmp.term,
+        Jmp::Call { .. } | Jmp::CallInd { .. } | Jmp::CallOther { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_across_blocks() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with(
+                "entry",
+                vec![
+                    Def::assign("def_rax", rax.clone(), Expression::const_from_i64(1)),
+                    Def::assign("def_rbp", rbp.clone(), Expression::const_from_i64(2)),
+                ],
+                Jmp::CBranch {
+                    target: Tid::new("loop"),
+                    condition: Expression::Var(rdi.clone()),
+                },
+            ),
+            Blk::mock_with(
+                "loop",
+                vec![Def::assign(
+                    "increment",
+                    rax.clone(),
+                    Expression::Var(rax.clone()).plus_const(1),
+                )],
+                Jmp::CBranch {
+                    target: Tid::new("loop"),
+                    condition: Expression::Var(rax.clone()),
+                },
+            ),
+            Blk::mock_with(
+                "call",
+                vec![Def::assign(
+                    "def_rdi",
+                    rdi.clone(),
+                    Expression::Var(rax.clone()),
+                )],
+                Jmp::mock_call("mock_symbol", "return"),
+            ),
+            Blk::mock_with(
+                "return",
+                vec![Def::assign(
+                    "use_after_call",
+                    rdi.clone(),
+                    Expression::Var(rax.clone()).plus(Expression::Var(rbp.clone())),
+                )],
+                Jmp::Return(Expression::Var(rdi.clone())),
+            ),
+        ];
+        // The loop is left through a second jump to the call block.
+        sub.term.blocks[1].term.jmps.push(Term {
+            tid: Tid::new("loop_exit"),
+            term: Jmp::Branch(Tid::new("call")),
+        });
+        let chains = DefUseChains::new(&project, &sub);
+
+        let defs = |use_tid: &str, var: &Variable| -> Vec<String> {
+            chains
+                .defs_reaching(&Tid::new(use_tid), var)
+                .map(|tid| tid.to_string())
+                .collect()
+        };
+        assert_eq!(defs("increment", &rax), vec!["def_rax", "increment"]);
+        assert_eq!(defs("def_rdi", &rax), vec!["increment"]);
+        assert_eq!(defs("call_jmp", &rdi), vec!["def_rdi"]);
+        // RAX is not a parameter of the extern symbol, but its definitions reaching the call are known.
+        assert!(defs("call_jmp", &rax).is_empty());
+        let call_defs: Vec<String> = chains
+            .defs_reaching_call(&Tid::new("call_jmp"), &rax)
+            .map(|tid| tid.to_string())
+            .collect();
+        assert_eq!(call_defs, vec!["increment"]);
+        // The call overwrites RAX, but not the callee-saved RBP.
+        assert_eq!(defs("use_after_call", &rax), vec!["call_jmp"]);
+        assert_eq!(defs("use_after_call", &rbp), vec!["def_rbp"]);
+        assert_eq!(defs("return_jmp", &rdi), vec!["use_after_call"]);
+        // The parameter RDI of the function has no reaching definitions at the first branch.
+        assert!(defs("entry_jmp", &rdi).is_empty());
+
+        let uses: Vec<(String, String)> = chains
+            .uses_of(&Tid::new("increment"))
+            .map(|(tid, var)| (tid.to_string(), var.name.clone()))
+            .collect();
+        assert_eq!(uses.len(), 3);
+        assert!(uses.contains(&("def_rdi".to_string(), "RAX".to_string())));
+        assert!(uses.contains(&("loop_jmp".to_string(), "RAX".to_string())));
+        assert!(uses.contains(&("increment".to_string(), "RAX".to_string())));
+        assert!(matches!(
+            chains.get_definition(&Tid::new("call_jmp")),
+            Some(Definition::Call(_))
+        ));
+    }
+
+    #[test]
+    fn internal_call_parameters() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        let rdi = Variable::mock("RDI", 8);
+        let rax = Variable::mock("RAX", 8);
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![Blk::mock_with(
+            "entry",
+            vec![
+                Def::assign("def_rdi", rdi.clone(), Expression::const_from_i64(1)),
+                Def::assign("def_rax", rax.clone(), Expression::const_from_i64(2)),
+            ],
+            Jmp::Call {
+                target: Tid::new("func"),
+                return_: None,
+            },
+        )];
+        let chains = DefUseChains::new(&project, &sub);
+        let defs: Vec<&Tid> = chains.defs_reaching(&Tid::new("entry_jmp"), &rdi).collect();
+        assert_eq!(defs, vec![&Tid::new("def_rdi")]);
+        // RAX is not a parameter register.
+        assert!(chains.uses_of(&Tid::new("def_rax")).next().is_none());
+    }
+}
@@ -49,7 +49,7 @@ pub trait Context {
     type NodeLabel;
     /// The type of the value that gets assigned to each node.
     /// The values should form a partially ordered set.
-    type NodeValue: PartialEq + Eq;
+    type NodeValue: PartialEq + Eq + Clone;
 
     /// Get the graph on which the fixpoint computation operates.
     fn get_graph(&self) -> &DiGraph<Self::NodeLabel, Self::EdgeLabel>;
@@ -95,6 +95,10 @@ pub struct Computation<T: Context> {
     default_value: Option<T::NodeValue>,
     /// The internal map containing all known node values.
     node_values: FnvHashMap<NodeIndex, T::NodeValue>,
+    /// The nodes for which all assigned values get recorded in the trace.
+    traced_nodes: BTreeSet<NodeIndex>,
+    /// The values assigned to the traced nodes in the order of their assignment.
+    trace: Vec<(NodeIndex, T::NodeValue)>,
 }
 
 impl<T: Context> Computation<T> {
@@ -127,6 +131,8 @@ impl<T: Context> Computation<T> {
             worklist,
             default_value,
             node_values: FnvHashMap::default(),
+            traced_nodes: BTreeSet::new(),
+            trace: Vec::new(),
         }
     }
 
@@ -141,10 +147,24 @@ impl<T: Context> Computation<T> {
 
     /// Set the value of a node and mark the node as not yet stabilized.
     pub fn set_node_value(&mut self, node: NodeIndex, value: T::NodeValue) {
+        if self.traced_nodes.contains(&node) {
+            self.trace.push((node, value.clone()));
+        }
         self.node_values.insert(node, value);
         self.worklist.insert(self.node_priority_list[node.index()]);
     }
 
+    /// Record all values that get assigned to the given nodes from now on.
+    /// Intended for debugging, since it allows to inspect how the values evolve over the fixpoint iterations.
+    pub fn trace_nodes(&mut self, nodes: impl IntoIterator<Item = NodeIndex>) {
+        self.traced_nodes.extend(nodes);
+    }
+
+    /// Get the values assigned to the traced nodes in the order of their assignment.
+    pub fn get_trace(&self) -> &[(NodeIndex, T::NodeValue)] {
+        &self.trace
+    }
+
     /// Merge the value at a node with some new value.
     fn merge_node_value(&mut self, node: NodeIndex, value: T::NodeValue) {
         if let Some(old_value) = self.node_values.get(&node) {
@@ -289,5 +309,34 @@ mod tests {
 
         assert_eq!(30, *solution.get_node_value(NodeIndex::new(9)).unwrap());
         assert_eq!(0, *solution.get_node_value(NodeIndex::new(5)).unwrap());
+        assert!(solution.get_trace().is_empty());
+    }
+
+    #[test]
+    fn tracing() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(1), 5);
+        graph.add_edge(NodeIndex::new(1), NodeIndex::new(2), 1);
+        graph.add_edge(NodeIndex::new(2), NodeIndex::new(1), 0);
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(2), 2);
+
+        let mut solution = Computation::new(FPContext { graph }, None);
+        solution.trace_nodes(vec![NodeIndex::new(1)]);
+        solution.set_node_value(NodeIndex::new(0), 0);
+        solution.compute();
+
+        let traced_values: Vec<u64> = solution
+            .get_trace()
+            .iter()
+            .map(|(node, value)| {
+                assert_eq!(*node, NodeIndex::new(1));
+                *value
+            })
+            .collect();
+        assert_eq!(traced_values.last(), Some(&2));
+        assert!(traced_values.len() >= 2);
     }
 }
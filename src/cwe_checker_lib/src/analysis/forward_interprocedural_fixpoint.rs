@@ -8,13 +8,25 @@
 //! first construct a context object implementing the `Context`trait.
 //! Use it to construct a `Computation` object.
 //! The `Computation` object provides the necessary methods for the actual fixpoint computation.
+//!
+//! # Analysis failures
+//!
+//! A panic in a transition function does not abort the whole computation.
+//! Instead, the function containing the start node of the edge is marked as failed,
+//! and no values are propagated through edges starting in failed functions anymore.
+//! Callers of failed functions compute the value after the call with [`Context::update_call_to_failed_function`].
+//! Optionally, functions whose transition functions take longer than a time limit in total are marked as failed, too.
 
 use super::fixpoint::Context as GeneralFPContext;
 use super::graph::*;
 use super::interprocedural_fixpoint_generic::*;
 use crate::intermediate_representation::*;
 use petgraph::graph::EdgeIndex;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
 
 /// The context for an interprocedural fixpoint computation.
 ///
@@ -82,12 +94,32 @@ pub trait Context<'a> {
         block_before_condition: &Term<Blk>,
         is_true: bool,
     ) -> Option<Self::Value>;
+
+    /// Transition function for calls to functions whose analysis failed.
+    /// The value after the call has to be computed from the value before the call alone,
+    /// since the values inside the called function are not trustworthy.
+    ///
+    /// The default implementation returns `None`, i.e. it assumes that the called function does not return.
+    fn update_call_to_failed_function(
+        &self,
+        _value_before_call: &Self::Value,
+        _call_term: &Term<Jmp>,
+    ) -> Option<Self::Value> {
+        None
+    }
 }
 
 /// This struct is a wrapper to create a general fixpoint context out of an interprocedural fixpoint context.
 pub struct GeneralizedContext<'a, T: Context<'a>> {
     context: T,
     _phantom_graph_reference: PhantomData<Graph<'a>>,
+    /// Maps the TIDs of functions whose analysis failed to a description of the failure.
+    failed_functions: RefCell<BTreeMap<Tid, String>>,
+    /// The maximum time that the transition functions of a function may take in total.
+    function_time_limit: Option<Duration>,
+    /// The time that the transition functions of each function took so far.
+    /// Only tracked if a time limit is set.
+    time_spent: RefCell<HashMap<Tid, Duration>>,
 }
 
 impl<'a, T: Context<'a>> GeneralizedContext<'a, T> {
@@ -96,6 +128,38 @@ impl<'a, T: Context<'a>> GeneralizedContext<'a, T> {
         GeneralizedContext {
             context,
             _phantom_graph_reference: PhantomData,
+            failed_functions: RefCell::new(BTreeMap::new()),
+            function_time_limit: None,
+            time_spent: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Mark functions as failed if their transition functions take longer than the given time in total.
+    pub fn set_function_time_limit(&mut self, time_limit: Option<Duration>) {
+        self.function_time_limit = time_limit;
+    }
+
+    /// Get the TIDs of the functions whose analysis failed together with a description of the failure.
+    pub fn get_failed_functions(&self) -> BTreeMap<Tid, String> {
+        self.failed_functions.borrow().clone()
+    }
+
+    /// Check whether the analysis of the function with the given TID failed.
+    fn is_failed_function(&self, sub_tid: &Tid) -> bool {
+        self.failed_functions.borrow().contains_key(sub_tid)
+    }
+
+    /// Add the time that a transition function took to the time spent in the given function.
+    /// Returns `true` if the time limit for the function is exceeded.
+    fn exceeds_time_limit(&self, sub_tid: &Tid, elapsed: Duration) -> bool {
+        match self.function_time_limit {
+            Some(time_limit) => {
+                let mut time_spent = self.time_spent.borrow_mut();
+                let total_time = time_spent.entry(sub_tid.clone()).or_default();
+                *total_time += elapsed;
+                *total_time > time_limit
+            }
+            None => false,
         }
     }
 
@@ -142,11 +206,64 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
     /// Forward edge transition function.
     /// Applies the transition functions from the interprocedural context object
     /// corresponding to the type of the provided edge.
+    ///
+    /// If the transition function panics or exceeds the time limit,
+    /// the function containing the start node of the edge is marked as failed.
+    /// Edges starting in failed functions do not propagate values.
     fn update_edge(
         &self,
         node_value: &Self::NodeValue,
         edge: EdgeIndex,
     ) -> Option<Self::NodeValue> {
+        let graph = self.context.get_graph();
+        let (start_node, _end_node) = graph.edge_endpoints(edge).unwrap();
+        let sub = graph[start_node].get_sub();
+        if self.is_failed_function(&sub.tid) {
+            return None;
+        }
+        let start_time = Instant::now();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| {
+            self.update_edge_unguarded(node_value, edge)
+        })) {
+            Ok(value) => {
+                if self.exceeds_time_limit(&sub.tid, start_time.elapsed()) {
+                    self.failed_functions.borrow_mut().insert(
+                        sub.tid.clone(),
+                        format!(
+                            "Time limit of {} seconds exceeded",
+                            self.function_time_limit.unwrap().as_secs_f64()
+                        ),
+                    );
+                    None
+                } else {
+                    value
+                }
+            }
+            Err(panic_payload) => {
+                let panic_message = if let Some(message) = panic_payload.downcast_ref::<&str>() {
+                    message.to_string()
+                } else if let Some(message) = panic_payload.downcast_ref::<String>() {
+                    message.clone()
+                } else {
+                    "Unknown panic".to_string()
+                };
+                self.failed_functions
+                    .borrow_mut()
+                    .insert(sub.tid.clone(), format!("Panic: {}", panic_message));
+                None
+            }
+        }
+    }
+}
+
+impl<'a, T: Context<'a>> GeneralizedContext<'a, T> {
+    /// Apply the transition function corresponding to the type of the provided edge
+    /// without guarding against panics.
+    fn update_edge_unguarded(
+        &self,
+        node_value: &NodeValue<T::Value>,
+        edge: EdgeIndex,
+    ) -> Option<NodeValue<T::Value>> {
         let graph = self.context.get_graph();
         let (start_node, end_node) = graph.edge_endpoints(edge).unwrap();
 
@@ -160,7 +277,7 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                 });
                 end_val.map(NodeValue::Value)
             }
-            Edge::CallCombine(_) => Some(Self::NodeValue::Value(node_value.unwrap_value().clone())),
+            Edge::CallCombine(_) => Some(NodeValue::Value(node_value.unwrap_value().clone())),
             Edge::Call(call) => self
                 .context
                 .update_call(node_value.unwrap_value(), call, &graph[end_node])
@@ -179,13 +296,22 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                     call_stub,
                     interprocedural_flow,
                 } => {
-                    let return_from_block = match graph.node_weight(start_node) {
+                    let (return_from_block, return_from_sub) = match graph.node_weight(start_node) {
                         Some(Node::CallReturn {
                             call: _,
-                            return_: (return_from_block, _),
-                        }) => return_from_block,
+                            return_: (return_from_block, return_from_sub),
+                        }) => (return_from_block, return_from_sub),
                         _ => panic!("Malformed Control flow graph"),
                     };
+                    if self.is_failed_function(&return_from_sub.tid) {
+                        return call_stub
+                            .as_ref()
+                            .and_then(|value_before_call| {
+                                self.context
+                                    .update_call_to_failed_function(value_before_call, call_term)
+                            })
+                            .map(NodeValue::Value);
+                    }
                     let return_from_jmp = &return_from_block.term.jmps[0];
                     match self.context.update_return(
                         interprocedural_flow.as_ref(),
@@ -251,6 +377,50 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
     }
 }
 
+/// Compute the fixpoint, visiting each node at most `max_steps` times
+/// (see [`Computation::compute_with_max_steps`](super::fixpoint::Computation::compute_with_max_steps)).
+///
+/// If the analysis of functions fails during the computation,
+/// the returns from these functions are revisited
+/// so that the values after calls to them get computed by [`Context::update_call_to_failed_function`].
+/// Returns the functions whose analysis failed during this computation together with a description of the failure.
+pub fn compute_with_failure_isolation<'a, T: Context<'a>>(
+    computation: &mut super::fixpoint::Computation<GeneralizedContext<'a, T>>,
+    max_steps: u64,
+) -> BTreeMap<Tid, String> {
+    let previously_failed_functions = computation.get_context().get_failed_functions();
+    loop {
+        let already_failed_functions = computation.get_context().get_failed_functions();
+        computation.compute_with_max_steps(max_steps);
+        let failed_functions = computation.get_context().get_failed_functions();
+        if failed_functions.len() == already_failed_functions.len() {
+            return failed_functions
+                .into_iter()
+                .filter(|(sub_tid, _)| !previously_failed_functions.contains_key(sub_tid))
+                .collect();
+        }
+        let graph = computation.get_graph();
+        let call_return_nodes: Vec<_> = graph
+            .node_indices()
+            .filter(|node| match graph[*node] {
+                Node::CallReturn {
+                    return_: (_, return_from_sub),
+                    ..
+                } => {
+                    failed_functions.contains_key(&return_from_sub.tid)
+                        && !already_failed_functions.contains_key(&return_from_sub.tid)
+                }
+                _ => false,
+            })
+            .collect();
+        for node in call_return_nodes {
+            if let Some(value) = computation.get_node_value(node).cloned() {
+                computation.set_node_value(node, value);
+            }
+        }
+    }
+}
+
 /// Generate a new computation from the corresponding context and an optional default value for nodes.
 pub fn create_computation<'a, T: Context<'a>>(
     problem: T,
@@ -259,3 +429,140 @@ pub fn create_computation<'a, T: Context<'a>>(
     let generalized_problem = GeneralizedContext::new(problem);
     super::fixpoint::Computation::new(generalized_problem, default_value.map(NodeValue::Value))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use std::collections::HashSet;
+
+    /// A context counting the number of `Def` terms, which panics on the `Def` with TID `panic`.
+    struct PanickingContext<'a> {
+        graph: Graph<'a>,
+    }
+
+    impl<'a> Context<'a> for PanickingContext<'a> {
+        type Value = u64;
+
+        fn get_graph(&self) -> &Graph<'a> {
+            &self.graph
+        }
+
+        fn merge(&self, value1: &u64, value2: &u64) -> u64 {
+            std::cmp::max(*value1, *value2)
+        }
+
+        fn update_def(&self, value: &u64, def: &Term<Def>) -> Option<u64> {
+            if def.tid == Tid::new("panic") {
+                panic!("Transition function failed");
+            }
+            Some(value + 1)
+        }
+
+        fn update_jump(
+            &self,
+            value: &u64,
+            _jump: &Term<Jmp>,
+            _untaken_conditional: Option<&Term<Jmp>>,
+            _target: &Term<Blk>,
+        ) -> Option<u64> {
+            Some(*value)
+        }
+
+        fn update_call(&self, value: &u64, _call: &Term<Jmp>, _target: &Node) -> Option<u64> {
+            Some(*value)
+        }
+
+        fn update_return(
+            &self,
+            value: Option<&u64>,
+            _value_before_call: Option<&u64>,
+            _call_term: &Term<Jmp>,
+            _return_term: &Term<Jmp>,
+        ) -> Option<u64> {
+            value.copied()
+        }
+
+        fn update_call_stub(&self, value: &u64, _call: &Term<Jmp>) -> Option<u64> {
+            Some(*value)
+        }
+
+        fn specialize_conditional(
+            &self,
+            value: &u64,
+            _condition: &Expression,
+            _block_before_condition: &Term<Blk>,
+            _is_true: bool,
+        ) -> Option<u64> {
+            Some(*value)
+        }
+
+        fn update_call_to_failed_function(
+            &self,
+            value_before_call: &u64,
+            _call_term: &Term<Jmp>,
+        ) -> Option<u64> {
+            Some(value_before_call + 100)
+        }
+    }
+
+    #[test]
+    fn failed_function_isolation() {
+        let def = |name: &str| {
+            Def::assign(
+                name,
+                Variable::mock("RAX", 8),
+                Expression::Const(Bitvector::from_i64(0)),
+            )
+        };
+        let return_jmp = Jmp::Return(Expression::Var(Variable::mock("RAX", 8)));
+        let mut caller = Sub::mock("caller");
+        caller.term.blocks = vec![
+            Blk::mock_with(
+                "caller_blk",
+                vec![def("caller_def")],
+                Jmp::mock_call("callee", "return_blk"),
+            ),
+            Blk::mock_with("return_blk", Vec::new(), return_jmp.clone()),
+        ];
+        let mut callee = Sub::mock("callee");
+        callee.term.blocks = vec![Blk::mock_with(
+            "callee_blk",
+            vec![def("callee_def"), def("panic")],
+            return_jmp,
+        )];
+        let mut program = Program::mock_empty();
+        program.subs = vec![caller, callee];
+        let program = Term {
+            tid: Tid::new("program"),
+            term: program,
+        };
+        let graph = get_program_cfg(&program, HashSet::new());
+        let node_index = |tid: &str| {
+            graph
+                .node_indices()
+                .find(|node| {
+                    matches!(graph[*node], Node::BlkStart(block, _) if block.tid == Tid::new(tid))
+                })
+                .unwrap()
+        };
+        let (entry_node, return_node) = (node_index("caller_blk"), node_index("return_blk"));
+
+        let mut computation = create_computation(PanickingContext { graph }, None);
+        computation.set_node_value(entry_node, NodeValue::Value(0));
+        let failed_functions = compute_with_failure_isolation(&mut computation, 100);
+        assert_eq!(failed_functions.len(), 1);
+        assert_eq!(
+            failed_functions[&Tid::new("callee")],
+            "Panic: Transition function failed"
+        );
+        // The value before the call contains one `Def`, the rest comes from the failed function handler.
+        assert_eq!(
+            computation
+                .get_node_value(return_node)
+                .unwrap()
+                .unwrap_value(),
+            &101
+        );
+    }
+}
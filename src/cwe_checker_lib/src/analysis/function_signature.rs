@@ -0,0 +1,573 @@
+//! Inference of the register parameters of the internal functions of a program.
+//!
+//! A parameter register of the standard calling convention is a parameter of a function
+//! if its value may be read inside the function before it is written.
+//! Calls to other functions of the program read the parameters of the called function,
+//! so the signatures of all functions are computed together by a fixpoint iteration over the whole program.
+//! Calls to extern functions read their register parameters.
+//! Indirect calls and calls to unknown targets read all parameter registers.
+//! After a call all parameter registers are assumed to be overwritten.
+//!
+//! The pointer inference analysis uses the signatures (see the `function_signatures` option of its configuration)
+//! to pass only the parameter registers of the called function to it at calls.
+//! Changes of the values of other registers in the caller then do not trigger a recomputation of the callee.
+//!
+//! # Importing signatures
+//!
+//! The signatures inferred in one run can be exported (see [`export_signatures`])
+//! and imported in the next run, e.g. for the analysis of a related version of the same binary.
+//! The imported signatures are matched to the functions by name and used as initial assumptions,
+//! so that the fixpoint iteration only needs to propagate the changes between the two versions.
+//! The assumptions are verified after the fixpoint is reached:
+//! If an imported signature contains parameters that the function does not read,
+//! the assumption is discarded and the fixpoint is recomputed without it.
+//! Assumptions can only be refuted through the code of the function and the signatures of its callees,
+//! so consistent assumptions about (mutually) recursive functions are trusted.
+//! All functions whose inferred signature differs from the imported one are logged.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use crate::utils::log::LogMessage;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The inferred signature of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct FunctionSignature {
+    /// The names of the parameter registers of the function.
+    pub parameters: BTreeSet<String>,
+}
+
+/// Infer the signatures of all internal functions of the project.
+///
+/// The `imported_signatures` map function names to the signatures exported by an earlier run.
+/// They are used as verified initial assumptions of the fixpoint iteration.
+/// Returns the inferred signatures together with log messages
+/// for all functions whose inferred signature does not match the imported signature.
+pub fn infer_function_signatures(
+    project: &Project,
+    imported_signatures: &BTreeMap<String, FunctionSignature>,
+) -> (BTreeMap<Tid, FunctionSignature>, Vec<LogMessage>) {
+    let inference = SignatureInference::new(project);
+    let mut assumptions: HashMap<&Tid, &FunctionSignature> = inference
+        .subs
+        .iter()
+        .filter_map(|sub| {
+            imported_signatures
+                .get(&sub.term.name)
+                .map(|signature| (&sub.tid, signature))
+        })
+        .collect();
+    let mut logs = Vec::new();
+    let signatures = loop {
+        let (signatures, iterations) = inference.compute_fixpoint(&assumptions);
+        let rejected_assumptions: Vec<&Tid> = inference
+            .subs
+            .iter()
+            .filter(|sub| match assumptions.get(&sub.tid) {
+                Some(assumption) => !assumption
+                    .parameters
+                    .is_subset(&inference.get_parameters(sub, &signatures)),
+                None => false,
+            })
+            .map(|sub| &sub.tid)
+            .collect();
+        if rejected_assumptions.is_empty() {
+            logs.push(
+                LogMessage::new_debug(format!(
+                    "Fixpoint reached after {} iterations with {} imported signatures",
+                    iterations,
+                    assumptions.len()
+                ))
+                .source("Function Signatures"),
+            );
+            break signatures;
+        }
+        for sub_tid in rejected_assumptions {
+            assumptions.remove(sub_tid);
+        }
+    };
+    for sub in inference.subs.iter() {
+        if let Some(imported_signature) = imported_signatures.get(&sub.term.name) {
+            let parameters = &signatures[&sub.tid];
+            if *parameters != imported_signature.parameters {
+                let missing: Vec<&String> = parameters
+                    .difference(&imported_signature.parameters)
+                    .collect();
+                let unused: Vec<&String> = imported_signature
+                    .parameters
+                    .difference(parameters)
+                    .collect();
+                logs.push(
+                    LogMessage::new_info(format!(
+                        "Signature of {} does not match the imported signature: missing parameters {:?}, unused parameters {:?}",
+                        sub.term.name, missing, unused
+                    ))
+                    .location(sub.tid.clone())
+                    .source("Function Signatures"),
+                );
+            }
+        }
+    }
+    let signatures = signatures
+        .into_iter()
+        .map(|(sub_tid, parameters)| (sub_tid.clone(), FunctionSignature { parameters }))
+        .collect();
+    (signatures, logs)
+}
+
+/// Map the names of the functions of the project to their signatures,
+/// so that the signatures can be imported in another run by [`infer_function_signatures`].
+pub fn export_signatures(
+    project: &Project,
+    signatures: &BTreeMap<Tid, FunctionSignature>,
+) -> BTreeMap<String, FunctionSignature> {
+    project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter_map(|sub| {
+            signatures
+                .get(&sub.tid)
+                .map(|signature| (sub.term.name.clone(), signature.clone()))
+        })
+        .collect()
+}
+
+/// Get the registers read by the `Def` term.
+fn get_def_inputs(def: &Term<Def>) -> Vec<&Variable> {
+    match &def.term {
+        Def::Assign { value, .. } => value.input_vars(),
+        Def::Load { address, .. } => address.input_vars(),
+        Def::Store { address, value } => {
+            let mut inputs = address.input_vars();
+            inputs.extend(value.input_vars());
+            inputs
+        }
+    }
+}
+
+/// Helper struct for the fixpoint computation of the function signatures.
+struct SignatureInference<'a> {
+    /// The internal functions of the project, i.e. all functions that are not extern symbols.
+    subs: Vec<&'a Term<Sub>>,
+    /// The extern symbols of the project, indexed by their TIDs.
+    extern_symbols: HashMap<&'a Tid, &'a ExternSymbol>,
+    /// The parameter registers of the standard calling convention.
+    parameter_registers: BTreeSet<String>,
+}
+
+impl<'a> SignatureInference<'a> {
+    /// Create a new helper object for the given project.
+    fn new(project: &'a Project) -> SignatureInference<'a> {
+        let extern_symbols: HashMap<&Tid, &ExternSymbol> = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| (&symbol.tid, symbol))
+            .collect();
+        SignatureInference {
+            subs: project
+                .program
+                .term
+                .subs
+                .iter()
+                .filter(|sub| !extern_symbols.contains_key(&sub.tid))
+                .collect(),
+            extern_symbols,
+            parameter_registers: project
+                .get_standard_calling_convention()
+                .map(|cconv| cconv.parameter_register.iter().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Compute the parameters of all internal functions,
+    /// starting with the assumed signatures for the functions that have one and with no parameters otherwise.
+    /// Returns the fixpoint together with the number of iterations needed to reach it.
+    ///
+    /// Parameters are only ever added during the iteration,
+    /// so assumptions with unused parameters are not corrected.
+    fn compute_fixpoint(
+        &self,
+        assumptions: &HashMap<&Tid, &FunctionSignature>,
+    ) -> (BTreeMap<&'a Tid, BTreeSet<String>>, usize) {
+        let mut signatures: BTreeMap<&Tid, BTreeSet<String>> = self
+            .subs
+            .iter()
+            .map(|sub| {
+                let parameters = assumptions
+                    .get(&sub.tid)
+                    .map(|assumption| assumption.parameters.clone())
+                    .unwrap_or_default();
+                (&sub.tid, parameters)
+            })
+            .collect();
+        let mut iterations = 0;
+        loop {
+            iterations += 1;
+            let mut changed = false;
+            for sub in self.subs.iter() {
+                let parameters = self.get_parameters(sub, &signatures);
+                let signature = signatures.get_mut(&sub.tid).unwrap();
+                for parameter in parameters {
+                    changed |= signature.insert(parameter);
+                }
+            }
+            if !changed {
+                return (signatures, iterations);
+            }
+        }
+    }
+
+    /// Compute the parameters of the function,
+    /// given the current signatures of the functions that it may call.
+    fn get_parameters(
+        &self,
+        sub: &'a Term<Sub>,
+        signatures: &BTreeMap<&Tid, BTreeSet<String>>,
+    ) -> BTreeSet<String> {
+        let mut parameters = BTreeSet::new();
+        for (block, mut written) in self.compute_block_start_states(sub) {
+            for def in block.term.defs.iter() {
+                self.add_reads(&mut parameters, get_def_inputs(def), &written);
+                if let Def::Assign { var, .. } | Def::Load { var, .. } = &def.term {
+                    written.insert(&var.name);
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                match &jmp.term {
+                    Jmp::BranchInd(target) | Jmp::Return(target) => {
+                        self.add_reads(&mut parameters, target.input_vars(), &written)
+                    }
+                    Jmp::CBranch { condition, .. } => {
+                        self.add_reads(&mut parameters, condition.input_vars(), &written)
+                    }
+                    Jmp::CallInd { target, .. } => {
+                        self.add_reads(&mut parameters, target.input_vars(), &written);
+                        self.add_register_reads(
+                            &mut parameters,
+                            self.parameter_registers.iter(),
+                            &written,
+                        );
+                    }
+                    Jmp::Call { target, .. } => {
+                        if let Some(symbol) = self.extern_symbols.get(target) {
+                            let register_parameters =
+                                symbol
+                                    .parameters
+                                    .iter()
+                                    .filter_map(|parameter| match parameter {
+                                        Arg::Register(var) => Some(&var.name),
+                                        Arg::Stack { .. } => None,
+                                    });
+                            self.add_register_reads(&mut parameters, register_parameters, &written);
+                        } else if let Some(callee_parameters) = signatures.get(target) {
+                            self.add_register_reads(
+                                &mut parameters,
+                                callee_parameters.iter(),
+                                &written,
+                            );
+                        } else {
+                            self.add_register_reads(
+                                &mut parameters,
+                                self.parameter_registers.iter(),
+                                &written,
+                            );
+                        }
+                    }
+                    Jmp::Branch(_) | Jmp::CallOther { .. } => (),
+                }
+            }
+        }
+        parameters
+    }
+
+    /// Add the read registers that are parameter registers and have not been written yet to the parameters.
+    fn add_reads(
+        &self,
+        parameters: &mut BTreeSet<String>,
+        read_registers: Vec<&Variable>,
+        written: &HashSet<&str>,
+    ) {
+        self.add_register_reads(
+            parameters,
+            read_registers.into_iter().map(|var| &var.name),
+            written,
+        );
+    }
+
+    /// Add the read registers, given by their names, that are parameter registers and have not been written yet
+    /// to the parameters.
+    fn add_register_reads<'b>(
+        &self,
+        parameters: &mut BTreeSet<String>,
+        read_registers: impl Iterator<Item = &'b String>,
+        written: &HashSet<&str>,
+    ) {
+        for register in read_registers {
+            if self.parameter_registers.contains(register) && !written.contains(register.as_str()) {
+                parameters.insert(register.clone());
+            }
+        }
+    }
+
+    /// Compute the parameter registers that are written on all paths
+    /// from the function entry to the start of each reachable block of the function.
+    fn compute_block_start_states<'b>(
+        &'b self,
+        sub: &'a Term<Sub>,
+    ) -> Vec<(&'a Term<Blk>, HashSet<&'b str>)> {
+        let entry_block = match sub.term.blocks.first() {
+            Some(block) => block,
+            None => return Vec::new(),
+        };
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_start_states: HashMap<&Tid, HashSet<&str>> = HashMap::new();
+        block_start_states.insert(&entry_block.tid, HashSet::new());
+        let mut worklist = vec![entry_block];
+        while let Some(block) = worklist.pop() {
+            let mut written = block_start_states[&block.tid].clone();
+            for def in block.term.defs.iter() {
+                if let Def::Assign { var, .. } | Def::Load { var, .. } = &def.term {
+                    written.insert(&var.name);
+                }
+            }
+            if block.term.jmps.iter().any(|jmp| {
+                matches!(
+                    jmp.term,
+                    Jmp::Call { .. } | Jmp::CallInd { .. } | Jmp::CallOther { .. }
+                )
+            }) {
+                written.extend(self.parameter_registers.iter().map(String::as_str));
+            }
+            for target in get_intraprocedural_jump_targets(block, sub) {
+                let target_block = match blocks.get(target) {
+                    Some(target_block) => *target_block,
+                    None => continue,
+                };
+                let changed = match block_start_states.get_mut(target) {
+                    Some(target_state) => {
+                        let old_len = target_state.len();
+                        target_state.retain(|register| written.contains(register));
+                        target_state.len() != old_len
+                    }
+                    None => {
+                        block_start_states.insert(target, written.clone());
+                        true
+                    }
+                };
+                if changed {
+                    worklist.push(target_block);
+                }
+            }
+        }
+        sub.term
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                block_start_states
+                    .remove(&block.tid)
+                    .map(|state| (block, state))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project with the parameter registers RDI and RSI and the following functions:
+    /// - `leaf` reads RSI.
+    /// - `wrapper` calls `leaf`.
+    /// - `setter` writes RSI and then calls `leaf`.
+    /// - `branch` writes RSI only if RDI is non-zero and then reads RSI.
+    /// - `extern_caller` calls the extern symbol with the parameter RDI.
+    /// - `recursive` calls itself and reads no register otherwise.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock();
+        cconv.parameter_register = vec!["RDI".to_string(), "RSI".to_string()];
+        project.calling_conventions = vec![cconv];
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let rsi = Variable::mock("RSI", 8);
+        let sub = |name: &str, blocks: Vec<Term<Blk>>| {
+            let mut sub = Sub::mock(name);
+            sub.term.blocks = blocks;
+            sub
+        };
+        let call = |name: &str, defs: Vec<Term<Def>>, target: &str| {
+            Blk::mock_with(
+                name,
+                defs,
+                Jmp::Call {
+                    target: Tid::new(target),
+                    return_: None,
+                },
+            )
+        };
+        project.program.term.subs = vec![
+            sub("wrapper", vec![call("wrapper_entry", vec![], "leaf")]),
+            sub(
+                "setter",
+                vec![call(
+                    "setter_entry",
+                    vec![Def::assign(
+                        "setter_rsi",
+                        rsi.clone(),
+                        Expression::const_from_i64(1),
+                    )],
+                    "leaf",
+                )],
+            ),
+            sub(
+                "leaf",
+                vec![Blk::mock_with(
+                    "leaf_entry",
+                    vec![Def::assign(
+                        "leaf_rax",
+                        rax.clone(),
+                        Expression::Var(rsi.clone()),
+                    )],
+                    Jmp::Return(Expression::Var(rax.clone())),
+                )],
+            ),
+            sub(
+                "branch",
+                vec![
+                    Blk::mock_with(
+                        "branch_entry",
+                        vec![],
+                        Jmp::CBranch {
+                            target: Tid::new("branch_exit"),
+                            condition: Expression::Var(rdi),
+                        },
+                    ),
+                    Blk::mock_with(
+                        "branch_write",
+                        vec![Def::assign(
+                            "branch_rsi",
+                            rsi.clone(),
+                            Expression::const_from_i64(1),
+                        )],
+                        Jmp::Branch(Tid::new("branch_exit")),
+                    ),
+                    Blk::mock_with("branch_exit", vec![], Jmp::Return(Expression::Var(rsi))),
+                ],
+            ),
+            sub(
+                "extern_caller",
+                vec![call("extern_caller_entry", vec![], "mock_symbol")],
+            ),
+            sub(
+                "recursive",
+                vec![call("recursive_entry", vec![], "recursive")],
+            ),
+        ];
+        // The conditional jump falls through to the write of RSI.
+        project.program.term.subs[3].term.blocks[0]
+            .term
+            .jmps
+            .push(Term {
+                tid: Tid::new("branch_fallthrough"),
+                term: Jmp::Branch(Tid::new("branch_write")),
+            });
+        project
+    }
+
+    fn parameters(signatures: &BTreeMap<String, FunctionSignature>, name: &str) -> Vec<String> {
+        signatures[name].parameters.iter().cloned().collect()
+    }
+
+    #[test]
+    fn signatures_across_calls() {
+        let project = mock_project();
+        let (signatures, logs) = infer_function_signatures(&project, &BTreeMap::new());
+        let signatures = export_signatures(&project, &signatures);
+        assert_eq!(signatures.len(), 6);
+        assert_eq!(parameters(&signatures, "leaf"), vec!["RSI"]);
+        assert_eq!(parameters(&signatures, "wrapper"), vec!["RSI"]);
+        assert!(parameters(&signatures, "setter").is_empty());
+        assert_eq!(parameters(&signatures, "branch"), vec!["RDI", "RSI"]);
+        assert_eq!(parameters(&signatures, "extern_caller"), vec!["RDI"]);
+        assert!(parameters(&signatures, "recursive").is_empty());
+        // The signature of `leaf` is only known to `wrapper` in the second iteration.
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].text,
+            "Fixpoint reached after 3 iterations with 0 imported signatures"
+        );
+
+        // Importing the exported signatures reaches the fixpoint after one iteration without mismatches.
+        let json = serde_json::to_string(&signatures).unwrap();
+        let imported: BTreeMap<String, FunctionSignature> = serde_json::from_str(&json).unwrap();
+        let (reinferred, logs) = infer_function_signatures(&project, &imported);
+        assert_eq!(export_signatures(&project, &reinferred), signatures);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].text,
+            "Fixpoint reached after 1 iterations with 6 imported signatures"
+        );
+    }
+
+    #[test]
+    fn mismatching_imported_signatures() {
+        let project = mock_project();
+        let (expected, _) = infer_function_signatures(&project, &BTreeMap::new());
+        let signature = |parameters: &[&str]| FunctionSignature {
+            parameters: parameters.iter().map(|name| name.to_string()).collect(),
+        };
+        let imported: BTreeMap<String, FunctionSignature> = vec![
+            // RSI is missing and RDI is not used.
+            ("leaf".to_string(), signature(&["RDI"])),
+            // The assumption is consistent with the recursive call, so it is trusted.
+            ("recursive".to_string(), signature(&["RDI"])),
+            ("branch".to_string(), signature(&["RDI", "RSI"])),
+            ("removed_function".to_string(), signature(&["RDI"])),
+        ]
+        .into_iter()
+        .collect();
+        let (signatures, logs) = infer_function_signatures(&project, &imported);
+        let signatures = export_signatures(&project, &signatures);
+        let mut expected = export_signatures(&project, &expected);
+        expected.insert("recursive".to_string(), signature(&["RDI"]));
+        assert_eq!(signatures, expected);
+        let messages: Vec<(&str, Option<&Tid>)> = logs
+            .iter()
+            .map(|log| (log.text.as_str(), log.location.as_ref()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "Fixpoint reached after 3 iterations with 2 imported signatures",
+                    None
+                ),
+                (
+                    "Signature of leaf does not match the imported signature: missing parameters [\"RSI\"], unused parameters [\"RDI\"]",
+                    Some(&Tid::new("leaf"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_calling_convention() {
+        let mut project = mock_project();
+        project.calling_conventions = Vec::new();
+        let (signatures, _) = infer_function_signatures(&project, &BTreeMap::new());
+        assert!(signatures
+            .values()
+            .all(|signature| signature.parameters.is_empty()));
+    }
+}
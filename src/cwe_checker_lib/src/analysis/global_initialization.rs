@@ -0,0 +1,296 @@
+//! A pre-pass analysing the global initialization phase of a program,
+//! i.e. the constructors in the `.init_array` section that the loader executes before `main`.
+//!
+//! Constructors often fill global function pointer tables or configuration variables.
+//! Since the values of writeable global memory are not tracked by the other analyses,
+//! such values would be unknown to them.
+//! The pre-pass collects all constant values that the constructors write to global memory
+//! and that are not overwritten anywhere else in the program.
+//! These values are added to the [`RuntimeMemoryImage`],
+//! so that the main analyses see them as known values of global memory.
+//!
+//! Furthermore, the constructors (and destructors from the `.fini_array` section) are added to the entry points of the program.
+//! The entry points are ordered in the sequence in which they are executed at runtime,
+//! i.e. constructors first, then the original entry points and the destructors last.
+//!
+//! Note that the pre-pass analyses each basic block of a constructor independently.
+//! Values computed across basic block boundaries are not found.
+//! If enabled, the constructors are additionally executed by the [concrete interpreter](crate::analysis::concrete_interpreter),
+//! which also finds values computed in loops or across function calls, e.g. decrypted strings or generated tables.
+//! Values found by the concrete execution take precedence over the values found by the per-block analysis.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::concrete_interpreter::{ConcreteInterpreter, InterpreterConfig};
+use crate::analysis::pointer_inference::State;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::{get_elf_init_and_fini_arrays, RuntimeMemoryImage};
+use crate::utils::log::LogMessage;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Run the global initialization pre-pass.
+///
+/// Orders the entry points of the project according to the global initialization order
+/// and adds the global values set by the constructors to the runtime memory image.
+/// If an `interpreter_config` is given, the constructors are also executed concretely within its bounds.
+/// Returns log messages summarizing the results.
+pub fn run(
+    project: &mut Project,
+    binary: &[u8],
+    runtime_memory_image: &mut RuntimeMemoryImage,
+    interpreter_config: Option<InterpreterConfig>,
+) -> Vec<LogMessage> {
+    let (init_array, fini_array) = match get_elf_init_and_fini_arrays(binary) {
+        Ok(arrays) => arrays,
+        Err(err) => {
+            return vec![LogMessage::new_debug(format!("{}", err)).source("Global Initialization")]
+        }
+    };
+    let address_base_offset = project.program.term.address_base_offset;
+    let sub_address_map: HashMap<u64, Tid> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter_map(|sub| {
+            let address = u64::from_str_radix(&sub.tid.address, 16).ok()?;
+            Some((address, sub.tid.clone()))
+        })
+        .collect();
+    let to_sub_tids = |addresses: &[u64]| -> Vec<Tid> {
+        addresses
+            .iter()
+            .filter_map(|address| sub_address_map.get(&(address + address_base_offset)))
+            .cloned()
+            .collect()
+    };
+    let constructors = to_sub_tids(&init_array);
+    let destructors = to_sub_tids(&fini_array);
+
+    order_entry_points(project, &constructors, &destructors);
+    let mut logs = Vec::new();
+    let emulated_values = match interpreter_config {
+        Some(config) => {
+            let mut interpreter = ConcreteInterpreter::new(project, runtime_memory_image, config);
+            for constructor in constructors.iter() {
+                if let Err(err) = interpreter.execute_sub(constructor) {
+                    logs.push(
+                        LogMessage::new_debug(format!(
+                            "Concrete execution of constructor {} aborted: {}",
+                            constructor, err
+                        ))
+                        .source("Global Initialization"),
+                    );
+                }
+            }
+            interpreter.get_written_global_values()
+        }
+        None => BTreeMap::new(),
+    };
+    let initialized_values = collect_initialized_global_values(
+        project,
+        &constructors,
+        runtime_memory_image,
+        emulated_values,
+    );
+    let num_initialized_values = initialized_values.len();
+    for (address, value) in initialized_values {
+        runtime_memory_image.add_initialized_global_value(address, value);
+    }
+    logs.push(
+        LogMessage::new_info(format!(
+            "Global initialization: Analyzed {} constructors, found {} initialized global values.",
+            constructors.len(),
+            num_initialized_values
+        ))
+        .source("Global Initialization"),
+    );
+    logs
+}
+
+/// Order the entry points of the project in the sequence in which they are executed:
+/// constructors first, then the original entry points, and destructors last.
+fn order_entry_points(project: &mut Project, constructors: &[Tid], destructors: &[Tid]) {
+    let mut seen = HashSet::new();
+    let original_entry_points = std::mem::take(&mut project.program.term.entry_points);
+    project.program.term.entry_points = constructors
+        .iter()
+        .chain(original_entry_points.iter())
+        .chain(destructors.iter())
+        .filter(|tid| seen.insert((*tid).clone()))
+        .cloned()
+        .collect();
+}
+
+/// Compute the effect of the `Def`s of the block on a state assuming nothing is known at the start of the block.
+/// For each store instruction, the evaluated target address and the stored value are passed to `handle_store`.
+fn for_each_store_in_block(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    block: &Term<Blk>,
+    mut handle_store: impl FnMut(Result<Bitvector, Error>, Result<Bitvector, Error>),
+) {
+    let mut state = State::new(&project.stack_pointer_register, block.tid.clone());
+    for def in block.term.defs.iter() {
+        match &def.term {
+            Def::Store { address, value } => {
+                handle_store(
+                    state.eval(address).try_to_bitvec(),
+                    state.eval(value).try_to_bitvec(),
+                );
+                let _ = state.handle_store(address, value, runtime_memory_image);
+            }
+            Def::Assign { var, value } => {
+                state.handle_register_assign(var, value);
+            }
+            Def::Load { var, address } => {
+                let _ = state.handle_load(var, address, runtime_memory_image);
+            }
+        }
+    }
+}
+
+/// Collect the constant values written to writeable global memory by the given constructors.
+///
+/// A value is only collected if all writes of the constructors to its address store the same constant value
+/// and if no code outside of the constructors writes to the same address.
+/// The `emulated_values` found by a concrete execution of the constructors replace the values found by the per-block analysis.
+fn collect_initialized_global_values(
+    project: &Project,
+    constructors: &[Tid],
+    runtime_memory_image: &RuntimeMemoryImage,
+    emulated_values: BTreeMap<u64, Bitvector>,
+) -> BTreeMap<u64, Bitvector> {
+    // A `None` value marks addresses whose value is not known after the initialization phase.
+    let mut global_values: BTreeMap<u64, Option<Bitvector>> = BTreeMap::new();
+    let constructor_set: HashSet<&Tid> = constructors.iter().collect();
+    let subs: HashMap<&Tid, &Term<Sub>> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub))
+        .collect();
+    for constructor in constructors {
+        for block in subs[constructor].term.blocks.iter() {
+            for_each_store_in_block(project, runtime_memory_image, block, |address, value| {
+                if let Ok(address) = address {
+                    if !matches!(
+                        runtime_memory_image.is_address_writeable(&address),
+                        Ok(true)
+                    ) {
+                        return;
+                    }
+                    let address = address.try_to_u64().unwrap();
+                    let value = value.ok();
+                    match global_values.get(&address) {
+                        Some(old_value) if *old_value != value => {
+                            global_values.insert(address, None);
+                        }
+                        _ => {
+                            global_values.insert(address, value);
+                        }
+                    }
+                }
+            });
+        }
+    }
+    for (address, value) in emulated_values {
+        global_values.insert(address, Some(value));
+    }
+    if global_values.is_empty() {
+        return BTreeMap::new();
+    }
+    // Remove values that may be overwritten after the initialization phase.
+    for sub in project.program.term.subs.iter() {
+        if constructor_set.contains(&sub.tid) {
+            continue;
+        }
+        for block in sub.term.blocks.iter() {
+            for_each_store_in_block(project, runtime_memory_image, block, |address, _value| {
+                if let Some(address) = address.ok().and_then(|address| address.try_to_u64().ok()) {
+                    if global_values.contains_key(&address) {
+                        global_values.insert(address, None);
+                    }
+                }
+            });
+        }
+    }
+    global_values
+        .into_iter()
+        .filter_map(|(address, value)| Some((address, value?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_sub(name: &str, defs: Vec<Term<Def>>) -> Term<Sub> {
+        let mut block = Blk::mock();
+        block.tid = Tid::new(format!("blk_{}", name));
+        block.term.defs = defs;
+        let mut sub = Sub::mock(name);
+        sub.term.blocks.push(block);
+        sub
+    }
+
+    fn store_const(tid: &str, address: u64, value: u32) -> Term<Def> {
+        Def::store(
+            tid,
+            Expression::Const(Bitvector::from_u64(address)),
+            Expression::Const(Bitvector::from_u32(value)),
+        )
+    }
+
+    #[test]
+    fn initialized_global_values() {
+        let mut project = Project::mock_empty();
+        project.program.term.subs = vec![
+            mock_sub(
+                "constructor",
+                vec![
+                    store_const("init_1", 0x2000, 42),
+                    store_const("init_2", 0x2004, 1),
+                    store_const("read_only", 0x1000, 1),
+                ],
+            ),
+            mock_sub("main", vec![store_const("overwrite", 0x2004, 2)]),
+        ];
+        project.program.term.entry_points = vec![Tid::new("main")];
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let constructors = vec![Tid::new("constructor")];
+
+        let values = collect_initialized_global_values(
+            &project,
+            &constructors,
+            &runtime_memory_image,
+            BTreeMap::new(),
+        );
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[&0x2000], Bitvector::from_u32(42));
+
+        // Values found by concrete execution replace the statically found values,
+        // but are still removed if overwritten outside of the constructors.
+        let emulated_values = vec![
+            (0x2000, Bitvector::from_u32(43)),
+            (0x2004, Bitvector::from_u32(1)),
+        ]
+        .into_iter()
+        .collect();
+        let values = collect_initialized_global_values(
+            &project,
+            &constructors,
+            &runtime_memory_image,
+            emulated_values,
+        );
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[&0x2000], Bitvector::from_u32(43));
+
+        order_entry_points(&mut project, &constructors, &[Tid::new("main")]);
+        assert_eq!(
+            project.program.term.entry_points,
+            vec![Tid::new("constructor"), Tid::new("main")]
+        );
+    }
+}
@@ -41,10 +41,19 @@
 //!
 //! The artificial *CallReturn* nodes enable enriching the information flowing through a return edge
 //! with information recovered from the corresponding callsite during a fixpoint computation.
+//!
+//! # Separate calling contexts
+//!
+//! In the graph above the states of all call sites of a function are merged at the start of the function.
+//! The function [`add_separate_context_copies`](fn.add_separate_context_copies.html) copies the nodes of selected functions
+//! once for each call site in another function, so that each of these call sites calls its own copy
+//! and the returns of each copy only lead back to the call site of the copy.
 
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
 use std::collections::{HashMap, HashSet};
 
 /// The graph type of an interprocedural control flow graph
@@ -100,6 +109,19 @@ impl<'a> Node<'a> {
             }
         }
     }
+
+    /// Get the function that the node belongs to.
+    /// For the artificial `CallReturn` and `CallSource` nodes this is the calling function.
+    pub fn get_sub(&self) -> &'a Term<Sub> {
+        use Node::*;
+        match self {
+            BlkStart(_blk, sub) | BlkEnd(_blk, sub) => sub,
+            CallReturn { call: (_, sub), .. }
+            | CallSource {
+                source: (_, sub), ..
+            } => sub,
+        }
+    }
 }
 
 impl<'a> std::fmt::Display for Node<'a> {
@@ -483,6 +505,197 @@ pub fn get_program_cfg(program: &Term<Program>, extern_subs: HashSet<Tid>) -> Gr
     builder.build()
 }
 
+/// Copy the nodes of the given functions once for each call site in another function,
+/// so that the states at different call sites are not merged at the start of the functions.
+///
+/// Each call site calls its own copy and the returns of each copy only lead back to the call site of the copy.
+/// Since callers are copied before their callees, calls from copies of a function get their own copies of the called functions, too.
+/// The original function is still called from inside recursion cycles.
+///
+/// The copied nodes are equal to the original nodes.
+/// They are appended to the graph, so that the node indices of the original nodes do not change.
+pub fn add_separate_context_copies<'a>(
+    graph: &mut Graph<'a>,
+    program: &'a Term<Program>,
+    separate_context_subs: &HashSet<Tid>,
+) {
+    for sub in get_callers_first_order(program, separate_context_subs) {
+        let entry_node = match graph.node_indices().find(|node| match graph[*node] {
+            Node::BlkStart(block, node_sub) => {
+                node_sub.tid == sub.tid
+                    && sub.term.blocks.first().map(|first| &first.tid) == Some(&block.tid)
+            }
+            _ => false,
+        }) {
+            Some(entry_node) => entry_node,
+            None => continue,
+        };
+        let sub_nodes: Vec<NodeIndex> = graph
+            .node_indices()
+            .filter(|node| get_node_sub(&graph[*node]).tid == sub.tid)
+            .collect();
+        let call_edges: Vec<(NodeIndex, &'a Term<Jmp>)> = graph
+            .edges_directed(entry_node, Direction::Incoming)
+            .filter_map(|edge| match edge.weight() {
+                Edge::Call(call) if get_node_sub(&graph[edge.source()]).tid != sub.tid => {
+                    Some((edge.source(), *call))
+                }
+                _ => None,
+            })
+            .collect();
+        for (call_source_node, call) in call_edges {
+            add_context_copy(graph, &sub_nodes, entry_node, call_source_node, call);
+        }
+    }
+}
+
+/// Get the function that the node belongs to.
+/// For the artificial `CallReturn` and `CallSource` nodes this is the calling function.
+fn get_node_sub<'a>(node: &Node<'a>) -> &'a Term<Sub> {
+    match node {
+        Node::BlkStart(_blk, sub) | Node::BlkEnd(_blk, sub) => sub,
+        Node::CallReturn { call: (_, sub), .. }
+        | Node::CallSource {
+            source: (_, sub), ..
+        } => sub,
+    }
+}
+
+/// Get the functions with the given TIDs ordered such that callers come before their callees.
+/// Inside recursion cycles the order is arbitrary.
+fn get_callers_first_order<'a>(
+    program: &'a Term<Program>,
+    sub_tids: &HashSet<Tid>,
+) -> Vec<&'a Term<Sub>> {
+    let subs: HashMap<&Tid, &'a Term<Sub>> = program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub))
+        .collect();
+    let callees = |sub: &'a Term<Sub>| -> Vec<&'a Term<Sub>> {
+        sub.term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+            .filter_map(|jmp| match &jmp.term {
+                Jmp::Call { target, .. } => subs.get(target).copied(),
+                _ => None,
+            })
+            .collect()
+    };
+    // Iterative depth-first search collecting the functions in post order.
+    let mut post_order = Vec::new();
+    let mut visited: HashSet<&Tid> = HashSet::new();
+    for root in program.term.subs.iter() {
+        if !visited.insert(&root.tid) {
+            continue;
+        }
+        let mut stack = vec![(root, callees(root).into_iter())];
+        while let Some((sub, remaining_callees)) = stack.last_mut() {
+            let sub = *sub;
+            match remaining_callees.next() {
+                Some(callee) => {
+                    if visited.insert(&callee.tid) {
+                        stack.push((callee, callees(callee).into_iter()));
+                    }
+                }
+                None => {
+                    post_order.push(sub);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    post_order
+        .into_iter()
+        .rev()
+        .filter(|sub| sub_tids.contains(&sub.tid))
+        .collect()
+}
+
+/// Add a copy of the given nodes of a function and of the edges between them
+/// and move the call from the given `CallSource` node (and the corresponding returns) to the copy.
+fn add_context_copy<'a>(
+    graph: &mut Graph<'a>,
+    sub_nodes: &[NodeIndex],
+    entry_node: NodeIndex,
+    call_source_node: NodeIndex,
+    call: &'a Term<Jmp>,
+) {
+    let call_return_nodes: HashSet<NodeIndex> = graph
+        .edges(call_source_node)
+        .filter(|edge| matches!(edge.weight(), Edge::CrCallStub))
+        .map(|edge| edge.target())
+        .collect();
+    let copies: HashMap<NodeIndex, NodeIndex> = sub_nodes
+        .iter()
+        .map(|node| (*node, graph.add_node(graph[*node])))
+        .collect();
+    let mut new_edges = Vec::new();
+    for edge in graph.edge_references() {
+        match (copies.get(&edge.source()), copies.get(&edge.target())) {
+            (Some(source), Some(target)) => new_edges.push((*source, *target, *edge.weight())),
+            // Returns are only copied for the call site of the copy.
+            (Some(source), None) => {
+                if !matches!(edge.weight(), Edge::CrReturnStub)
+                    || call_return_nodes.contains(&edge.target())
+                {
+                    new_edges.push((*source, edge.target(), *edge.weight()))
+                }
+            }
+            // Calls from other call sites are not copied.
+            (None, Some(target)) => {
+                if !matches!(edge.weight(), Edge::Call(_)) {
+                    new_edges.push((edge.source(), *target, *edge.weight()))
+                }
+            }
+            (None, None) => (),
+        }
+    }
+    for (source, target, edge) in new_edges {
+        graph.add_edge(source, target, edge);
+    }
+    graph.add_edge(call_source_node, copies[&entry_node], Edge::Call(call));
+    graph.retain_edges(|graph, edge| {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        let is_moved_call = source == call_source_node && target == entry_node;
+        let is_moved_return = copies.contains_key(&source)
+            && call_return_nodes.contains(&target)
+            && matches!(graph[edge], Edge::CrReturnStub);
+        !is_moved_call && !is_moved_return
+    });
+}
+
+/// Render the control flow graph in the DOT format of Graphviz.
+///
+/// Nodes are labeled as given by their `Display` implementation and edges by their edge type.
+pub fn to_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph cfg {\n");
+    for node in graph.node_indices() {
+        dot += &format!("    {} [label=\"{}\"];\n", node.index(), graph[node]);
+    }
+    for edge in graph.edge_references() {
+        let label = match edge.weight() {
+            Edge::Block => "Block",
+            Edge::Jump(..) => "Jump",
+            Edge::Call(_) => "Call",
+            Edge::ExternCallStub(_) => "ExternCallStub",
+            Edge::CrCallStub => "CrCallStub",
+            Edge::CrReturnStub => "CrReturnStub",
+            Edge::CallCombine(_) => "CallCombine",
+            Edge::ReturnCombine(_) => "ReturnCombine",
+        };
+        dot += &format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            edge.source().index(),
+            edge.target().index(),
+            label
+        );
+    }
+    dot + "}\n"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,6 +738,7 @@ mod tests {
             term: Sub {
                 name: "sub1".to_string(),
                 blocks: vec![sub1_blk1, sub1_blk2],
+                qualified_name: None,
             },
         };
         let cond_jump = Jmp::CBranch {
@@ -560,6 +774,7 @@ mod tests {
             term: Sub {
                 name: "sub2".to_string(),
                 blocks: vec![sub2_blk1, sub2_blk2],
+                qualified_name: None,
             },
         };
         let program = Term {
@@ -581,6 +796,10 @@ mod tests {
         println!("{}", serde_json::to_string_pretty(&graph).unwrap());
         assert_eq!(graph.node_count(), 16);
         assert_eq!(graph.edge_count(), 20);
+
+        let dot = to_dot(&graph);
+        assert!(dot.starts_with("digraph cfg {"));
+        assert_eq!(dot.matches(" -> ").count(), 20);
     }
 
     #[test]
@@ -604,6 +823,7 @@ mod tests {
             term: Sub {
                 name: "sub".to_string(),
                 blocks: vec![blk_term],
+                qualified_name: None,
             },
         };
         let mut program = Program::mock_empty();
@@ -616,4 +836,108 @@ mod tests {
         assert_eq!(graph.node_count(), 2);
         assert_eq!(graph.edge_count(), 2);
     }
+
+    #[test]
+    fn separate_context_copies() {
+        let block = |name: &str, jmp: Jmp| Term {
+            tid: Tid::new(name),
+            term: Blk {
+                defs: Vec::new(),
+                jmps: vec![Term {
+                    tid: Tid::new(format!("{}_jmp", name)),
+                    term: jmp,
+                }],
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        let call = |name: &str, target: &str, return_: &str| {
+            block(
+                name,
+                Jmp::Call {
+                    target: Tid::new(target),
+                    return_: Some(Tid::new(return_)),
+                },
+            )
+        };
+        let ret = |name: &str| block(name, Jmp::Return(Expression::const_from_i64(0)));
+        let sub = |name: &str, blocks: Vec<Term<Blk>>| Term {
+            tid: Tid::new(name),
+            term: Sub {
+                name: name.to_string(),
+                blocks,
+                qualified_name: None,
+            },
+        };
+        let mut program = Program::mock_empty();
+        program.subs = vec![
+            sub("leaf", vec![ret("leaf_ret")]),
+            sub(
+                "callee",
+                vec![call("callee_call", "leaf", "callee_ret"), ret("callee_ret")],
+            ),
+            sub(
+                "main",
+                vec![
+                    call("main_call_1", "callee", "main_call_2"),
+                    call("main_call_2", "callee", "main_ret"),
+                    ret("main_ret"),
+                ],
+            ),
+        ];
+        let program = Term {
+            tid: Tid::new("program"),
+            term: program,
+        };
+        let original_graph = get_program_cfg(&program, HashSet::new());
+        assert_eq!(original_graph.node_count(), 18);
+
+        let mut graph = original_graph.clone();
+        add_separate_context_copies(
+            &mut graph,
+            &program,
+            &[Tid::new("callee")].iter().cloned().collect(),
+        );
+        // Each call site in `main` gets a copy of the blocks, the call site and the call return node of `callee`.
+        assert_eq!(graph.node_count(), 30);
+        for node in original_graph.node_indices() {
+            assert_eq!(graph[node], original_graph[node]);
+        }
+        let callee_entries: Vec<NodeIndex> = graph
+            .node_indices()
+            .filter(|node| matches!(graph[*node], Node::BlkStart(block, _) if block.tid.to_string() == "callee_call"))
+            .collect();
+        assert_eq!(callee_entries.len(), 3);
+        // The original function is not called anymore and the copies have one caller each.
+        let num_calls = |node: NodeIndex| {
+            graph
+                .edges_directed(node, Direction::Incoming)
+                .filter(|edge| matches!(edge.weight(), Edge::Call(_)))
+                .count()
+        };
+        assert_eq!(num_calls(callee_entries[0]), 0);
+        assert_eq!(num_calls(callee_entries[1]), 1);
+        assert_eq!(num_calls(callee_entries[2]), 1);
+        // Each call return node in `main` is reached by the returns of exactly one copy.
+        for node in graph.node_indices() {
+            if let Node::CallReturn { call, .. } = graph[node] {
+                let num_returns = graph
+                    .edges_directed(node, Direction::Incoming)
+                    .filter(|edge| matches!(edge.weight(), Edge::CrReturnStub))
+                    .count();
+                assert_eq!(num_returns, 1, "{}", call.0.tid);
+            }
+        }
+
+        // Calls from the copies of `callee` get their own copies of `leaf`.
+        let mut graph = original_graph.clone();
+        add_separate_context_copies(
+            &mut graph,
+            &program,
+            &[Tid::new("callee"), Tid::new("leaf")]
+                .iter()
+                .cloned()
+                .collect(),
+        );
+        assert_eq!(graph.node_count(), 30 + 3 * 2);
+    }
 }
@@ -2,8 +2,14 @@
 //! as well as analyses depending on these modules.
 
 pub mod backward_interprocedural_fixpoint;
+pub mod concrete_interpreter;
+pub mod def_use;
 pub mod fixpoint;
 pub mod forward_interprocedural_fixpoint;
+pub mod function_signature;
+pub mod global_initialization;
 pub mod graph;
 pub mod interprocedural_fixpoint_generic;
 pub mod pointer_inference;
+pub mod resource_lifetime;
+pub mod string_abstraction;
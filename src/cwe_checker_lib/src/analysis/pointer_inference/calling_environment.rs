@@ -0,0 +1,437 @@
+//! Concrete presets for the program arguments and environment variables of the analyzed program.
+//!
+//! If calling environments are configured, the analysis starts the `main` function
+//! with the argument count and the argument vector given by the environments
+//! instead of with unknown parameters.
+//! The argument vector and the argument strings are materialized as memory objects of type
+//! [`ObjectType::ProgramEnvironment`](super::object::ObjectType::ProgramEnvironment)
+//! with known sizes, contents and string lengths.
+//! Lookups of environment variables by the configured `environment_lookup_symbols` (e.g. `getenv`)
+//! return `NULL` for variables that are unset in all environments
+//! and pointers to new memory objects containing the value for variables that are set in all environments.
+//!
+//! If more than one environment is configured, the analysis assumes that the program runs in one of them,
+//! i.e. the start state of `main` is the merge of the start states of the environments.
+
+use super::object::ObjectType;
+use super::{Data, State, ValueDomain};
+use crate::abstract_domain::*;
+use crate::analysis::string_abstraction::StringLengthBounds;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use std::collections::BTreeMap;
+
+/// The name of the function that is started with the parameters given by the calling environments.
+pub const ENTRY_FUNCTION_NAME: &str = "main";
+
+/// The program arguments and environment variables that the program is assumed to be started with.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct CallingEnvironment {
+    /// The program arguments, starting with the program name `argv[0]`.
+    /// `null` denotes an argument with unknown content.
+    pub arguments: Vec<Option<String>>,
+    /// Environment variables mapped to their values.
+    /// `null` denotes a variable that is not set.
+    /// Variables that are not contained in the map may or may not be set.
+    pub environment_variables: BTreeMap<String, Option<String>>,
+}
+
+/// The value of an environment variable according to all configured calling environments.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EnvironmentVariable<'a> {
+    /// The variable is set in all environments, with one of the given values.
+    Set(Vec<&'a str>),
+    /// The variable is not set in any environment.
+    Unset,
+    /// The variable is not specified in some environment or only set in some of the environments.
+    Unknown,
+}
+
+/// Look up the value of the environment variable in the calling environments.
+pub fn get_environment_variable<'a>(
+    environments: &'a [CallingEnvironment],
+    name: &str,
+) -> EnvironmentVariable<'a> {
+    let mut values = Vec::new();
+    let mut is_unset = false;
+    for environment in environments {
+        match environment.environment_variables.get(name) {
+            Some(Some(value)) => values.push(value.as_str()),
+            Some(None) => is_unset = true,
+            None => return EnvironmentVariable::Unknown,
+        }
+    }
+    match (values.is_empty(), is_unset) {
+        (false, false) => {
+            values.sort_unstable();
+            values.dedup();
+            EnvironmentVariable::Set(values)
+        }
+        (true, true) => EnvironmentVariable::Unset,
+        _ => EnvironmentVariable::Unknown,
+    }
+}
+
+/// Compute the start state of the function with the given TID in the given calling environments.
+/// The start states of the individual environments are merged.
+///
+/// Returns an error if the locations of the `main` parameters cannot be determined
+/// from the standard calling convention of the project.
+pub fn get_entry_state(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    sub_tid: &Tid,
+    environments: &[CallingEnvironment],
+) -> Result<State, Error> {
+    let (argc_location, argv_location) = get_main_parameters(project)?;
+    let mut merged_state: Option<State> = None;
+    for environment in environments {
+        let mut state = State::new(&project.stack_pointer_register, sub_tid.clone());
+        let argv = add_argument_vector(&mut state, project, sub_tid, &environment.arguments);
+        let argc = Bitvector::from_u64(environment.arguments.len() as u64)
+            .into_truncate(project.get_pointer_bytesize())
+            .map_err(|_| anyhow!("Too many program arguments"))?;
+        set_parameter(
+            &mut state,
+            project,
+            runtime_memory_image,
+            &argc_location,
+            ValueDomain::from(argc).into(),
+        )?;
+        set_parameter(
+            &mut state,
+            project,
+            runtime_memory_image,
+            &argv_location,
+            argv,
+        )?;
+        merged_state = Some(match merged_state {
+            Some(merged_state) => merged_state.merge(&state),
+            None => state,
+        });
+    }
+    merged_state.ok_or_else(|| anyhow!("No calling environment given"))
+}
+
+/// Get the locations of the `argc` and `argv` parameters of `main` according to the standard calling convention.
+/// If the calling convention passes no parameters in registers (e.g. `cdecl` on x86),
+/// the parameters are passed on the stack after the return address.
+fn get_main_parameters(project: &Project) -> Result<(Arg, Arg), Error> {
+    let cconv = project
+        .get_standard_calling_convention()
+        .ok_or_else(|| anyhow!("No standard calling convention"))?;
+    let pointer_size = project.get_pointer_bytesize();
+    match &cconv.parameter_register[..] {
+        [] => Ok((
+            Arg::Stack {
+                offset: u64::from(pointer_size) as i64,
+                size: pointer_size,
+            },
+            Arg::Stack {
+                offset: 2 * u64::from(pointer_size) as i64,
+                size: pointer_size,
+            },
+        )),
+        [argc, argv, ..] => {
+            let register = |name: &String| {
+                Arg::Register(Variable {
+                    name: name.clone(),
+                    size: pointer_size,
+                    is_temp: false,
+                })
+            };
+            Ok((register(argc), register(argv)))
+        }
+        [_] => Err(anyhow!("Only one parameter register")),
+    }
+}
+
+/// Write the value of a parameter to its location in the start state of a function.
+fn set_parameter(
+    state: &mut State,
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    location: &Arg,
+    value: Data,
+) -> Result<(), Error> {
+    match location {
+        Arg::Register(register) => {
+            state.set_register(register, value);
+            Ok(())
+        }
+        Arg::Stack { offset, .. } => state.write_to_address(
+            &Expression::Var(project.stack_pointer_register.clone()).plus_const(*offset),
+            &value,
+            runtime_memory_image,
+        ),
+    }
+}
+
+/// Add the argument vector and the argument strings to the state and return the pointer to the argument vector.
+/// The argument vector is terminated by a null pointer.
+fn add_argument_vector(
+    state: &mut State,
+    project: &Project,
+    sub_tid: &Tid,
+    arguments: &[Option<String>],
+) -> Data {
+    let pointer_size = project.get_pointer_bytesize();
+    let slot_offset = |index: usize| {
+        pointer_bytes(
+            pointer_size,
+            (index as u64 * u64::from(pointer_size)) as i64,
+        )
+    };
+    let argv_id = AbstractIdentifier::new(
+        sub_tid.clone().with_id_suffix("_argv"),
+        AbstractLocation::Register("argv".to_string(), pointer_size),
+    );
+    let argv = add_object(state, argv_id.clone(), pointer_size);
+    state
+        .memory
+        .set_object_size(&argv_id, slot_offset(arguments.len() + 1));
+    for (index, argument) in arguments.iter().enumerate() {
+        let argument_id = AbstractIdentifier::new(
+            sub_tid.clone().with_id_suffix(&format!("_argv_{}", index)),
+            AbstractLocation::Register(format!("argv[{}]", index), pointer_size),
+        );
+        let values: Vec<&str> = argument.iter().map(String::as_str).collect();
+        let argument_pointer = add_string_object(state, argument_id, &values, pointer_size);
+        store(
+            state,
+            argv.add_to_offset(&slot_offset(index)),
+            argument_pointer,
+        );
+    }
+    store(
+        state,
+        argv.add_to_offset(&slot_offset(arguments.len())),
+        pointer_bytes(pointer_size, 0).into(),
+    );
+    argv.into()
+}
+
+/// Add a new object for a string of the program environment to the state and return a pointer to it.
+/// If the string has a unique known value, the value is written to the object.
+/// The length of the string is recorded if its possible values are known.
+pub fn add_string_object(
+    state: &mut State,
+    object_id: AbstractIdentifier,
+    values: &[&str],
+    pointer_size: ByteSize,
+) -> Data {
+    let pointer = add_object(state, object_id.clone(), pointer_size);
+    if let [value] = values {
+        for (index, byte) in value.bytes().chain(std::iter::once(0)).enumerate() {
+            store(
+                state,
+                pointer.add_to_offset(&pointer_bytes(pointer_size, index as i64)),
+                ValueDomain::from(Bitvector::from_u8(byte)).into(),
+            );
+        }
+        state.memory.set_object_size(
+            &object_id,
+            pointer_bytes(pointer_size, value.len() as i64 + 1),
+        );
+    }
+    let pointer: Data = pointer.into();
+    let min_length = values.iter().map(|value| value.len()).min();
+    let max_length = values.iter().map(|value| value.len()).max();
+    if let (Some(min_length), Some(max_length)) = (min_length, max_length) {
+        state.memory.set_string_length(
+            &pointer,
+            StringLengthBounds {
+                lower_bound: min_length as u64,
+                upper_bound: Some(max_length as u64),
+            },
+        );
+    }
+    pointer
+}
+
+/// Add a new object of type [`ObjectType::ProgramEnvironment`] to the state and return a pointer to its start.
+fn add_object(
+    state: &mut State,
+    object_id: AbstractIdentifier,
+    pointer_size: ByteSize,
+) -> PointerDomain<ValueDomain> {
+    state.memory.add_abstract_object(
+        object_id.clone(),
+        pointer_bytes(pointer_size, 0),
+        ObjectType::ProgramEnvironment,
+        pointer_size,
+    );
+    PointerDomain::new(object_id, pointer_bytes(pointer_size, 0))
+}
+
+/// Store the value at the address in a memory object of the state.
+fn store(state: &mut State, address: PointerDomain<ValueDomain>, value: Data) {
+    // Writes to new objects at exact offsets cannot fail.
+    let _ = state.memory.set_value(address, value);
+}
+
+/// Create an absolute value of pointer size.
+fn pointer_bytes(pointer_size: ByteSize, value: i64) -> ValueDomain {
+    Bitvector::from_i64(value)
+        .into_truncate(pointer_size)
+        .unwrap()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment(arguments: &[&str], variables: &[(&str, Option<&str>)]) -> CallingEnvironment {
+        CallingEnvironment {
+            arguments: arguments
+                .iter()
+                .map(|argument| Some(argument.to_string()))
+                .collect(),
+            environment_variables: variables
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+                .collect(),
+        }
+    }
+
+    fn mock_project(parameter_registers: &[&str]) -> Project {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention {
+            parameter_register: parameter_registers
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            ..CallingConvention::mock()
+        }];
+        project
+    }
+
+    #[test]
+    fn environment_variables() {
+        let environments = vec![
+            environment(
+                &[],
+                &[("HOME", Some("/root")), ("DEBUG", None), ("USER", None)],
+            ),
+            environment(&[], &[("HOME", Some("/home/user")), ("DEBUG", None)]),
+        ];
+        assert_eq!(
+            get_environment_variable(&environments, "HOME"),
+            EnvironmentVariable::Set(vec!["/home/user", "/root"])
+        );
+        assert_eq!(
+            get_environment_variable(&environments, "DEBUG"),
+            EnvironmentVariable::Unset
+        );
+        assert_eq!(
+            get_environment_variable(&environments, "USER"),
+            EnvironmentVariable::Unknown
+        );
+        assert_eq!(
+            get_environment_variable(&environments[..1], "USER"),
+            EnvironmentVariable::Unset
+        );
+    }
+
+    #[test]
+    fn entry_state() {
+        let project = mock_project(&["RDI", "RSI"]);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let rdi = Variable::mock("RDI", 8);
+        let rsi = Variable::mock("RSI", 8);
+        let environments = vec![environment(&["prog", "-v"], &[])];
+        let state = get_entry_state(
+            &project,
+            &runtime_memory_image,
+            &Tid::new("main"),
+            &environments,
+        )
+        .unwrap();
+        assert_eq!(
+            state.get_register(&rdi),
+            Data::Value(Bitvector::from_u64(2).into())
+        );
+        let argv = match state.get_register(&rsi) {
+            Data::Pointer(pointer) => pointer,
+            _ => panic!(),
+        };
+        let argv_id = argv.ids().next().unwrap().clone();
+        assert!(matches!(
+            state.memory.get_object_type(&argv_id),
+            Ok(Some(ObjectType::ProgramEnvironment))
+        ));
+        assert_eq!(
+            state.memory.get_object_size(&argv_id),
+            Some(Bitvector::from_u64(24).into())
+        );
+        let argument = state
+            .memory
+            .get_value(
+                &argv.add_to_offset(&Bitvector::from_u64(8).into()).into(),
+                ByteSize::new(8),
+            )
+            .unwrap();
+        assert_eq!(
+            state.memory.get_string_length(&argument),
+            Some(StringLengthBounds::exact(2))
+        );
+        assert_eq!(
+            state.memory.get_value(&argument, ByteSize::new(1)).unwrap(),
+            Data::Value(Bitvector::from_u8(b'-').into())
+        );
+        assert_eq!(
+            state
+                .memory
+                .get_value(
+                    &argv.add_to_offset(&Bitvector::from_u64(16).into()).into(),
+                    ByteSize::new(8)
+                )
+                .unwrap(),
+            Data::Value(Bitvector::from_u64(0).into())
+        );
+
+        // In two environments the argument count is only known to lie in an interval.
+        let environments = vec![
+            environment(&["prog", "-v"], &[]),
+            environment(&["prog", "-v", "file"], &[]),
+        ];
+        let state = get_entry_state(
+            &project,
+            &runtime_memory_image,
+            &Tid::new("main"),
+            &environments,
+        )
+        .unwrap();
+        assert_eq!(
+            state.get_register(&rdi),
+            Data::Value(ValueDomain::from(IntervalDomain::mock(2, 3)))
+        );
+
+        // Without parameter registers the parameters are passed on the stack.
+        let project = mock_project(&[]);
+        let state = get_entry_state(
+            &project,
+            &runtime_memory_image,
+            &Tid::new("main"),
+            &environments[..1],
+        )
+        .unwrap();
+        let argc_address = Expression::Var(project.stack_pointer_register.clone()).plus_const(8);
+        assert_eq!(
+            state
+                .load_value(&argc_address, ByteSize::new(8), &runtime_memory_image)
+                .unwrap(),
+            Data::Value(Bitvector::from_u64(2).into())
+        );
+        assert!(get_entry_state(
+            &mock_project(&["RDI"]),
+            &runtime_memory_image,
+            &Tid::new("main"),
+            &environments
+        )
+        .is_err());
+    }
+}
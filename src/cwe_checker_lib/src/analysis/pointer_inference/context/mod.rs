@@ -1,14 +1,21 @@
-use super::object::ObjectType;
-use crate::analysis::graph::Graph;
+use super::object::{ObjectState, ObjectType};
+use crate::analysis::function_signature::FunctionSignature;
+use crate::analysis::graph::{add_separate_context_copies, Graph};
+use crate::analysis::string_abstraction::{
+    get_constant_string, get_formatted_string_length_bounds,
+};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::*;
+use crate::utils::symbol_utils::get_variadic_parameter;
 use crate::{abstract_domain::*, utils::binary::RuntimeMemoryImage};
-use std::collections::{BTreeMap, BTreeSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
+use super::calling_environment::{self, CallingEnvironment, EnvironmentVariable};
 use super::state::State;
 use super::ValueDomain;
-use super::{Config, Data, VERSION};
+use super::{Config, Data, MemoryPoolConfig, StringFormattingParameters, VERSION};
 
 // contains trait implementations for the `Context` struct,
 // especially the implementation of the `interprocedural_fixpoint::Context` trait.
@@ -18,8 +25,10 @@ mod trait_impls;
 ///
 /// The struct also implements the `interprocedural_fixpoint::Context` trait to enable the fixpoint computation.
 pub struct Context<'a> {
-    /// The program control flow graph on which the fixpoint will be computed
-    pub graph: &'a Graph<'a>,
+    /// The program control flow graph on which the fixpoint will be computed.
+    /// Contains copies of the functions with separate calling contexts,
+    /// see [`add_separate_context_copies`](crate::analysis::graph::add_separate_context_copies).
+    pub graph: Cow<'a, Graph<'a>>,
     /// A reference to the `Project` object representing the binary
     pub project: &'a Project,
     /// The runtime memory image for reading global read-only variables.
@@ -37,6 +46,80 @@ pub struct Context<'a> {
     pub allocation_symbols: Vec<String>,
     /// Names of `free`-like extern functions.
     pub deallocation_symbols: Vec<String>,
+    /// Names of `realloc`-like extern functions.
+    pub reallocation_symbols: Vec<String>,
+    /// Names of allocation functions returning uninitialized memory.
+    pub uninitialized_allocation_symbols: Vec<String>,
+    /// Names of allocation and reallocation functions mapped to the indices of their size parameters.
+    pub allocation_size_parameters: BTreeMap<String, Vec<usize>>,
+    /// Memory pools with known start addresses, whose allocation functions are modeled by the analysis.
+    pub memory_pools: Vec<MemoryPool>,
+    /// Maps the TIDs of calls to pool allocation functions to the index of the corresponding pool in `memory_pools`.
+    /// Since the time component of the abstract identifier of a memory object is the allocation call,
+    /// this allows to find the pool that a memory object belongs to.
+    pub pool_allocation_sites: HashMap<Tid, usize>,
+    /// Maps the TIDs of internal functions to their parameter registers given by the configuration.
+    /// Functions whose name is not unique in the program are not contained in the map.
+    pub function_signatures: HashMap<Tid, BTreeSet<String>>,
+    /// Names of extern functions mapped to the interval containing their return values.
+    pub return_value_intervals: BTreeMap<String, (i64, i64)>,
+    /// Names of `sprintf`-like extern functions mapped to the indices of their parameters.
+    pub string_formatting_symbols: BTreeMap<String, StringFormattingParameters>,
+    /// The program arguments and environment variables that the program is assumed to be started with.
+    pub calling_environments: Vec<CallingEnvironment>,
+    /// Names of `getenv`-like extern functions.
+    pub environment_lookup_symbols: Vec<String>,
+    /// The TIDs of the functions whose calling contexts are always merged.
+    pub merged_context_subs: HashSet<Tid>,
+    /// The constructor of the custom numeric domain extending the values assigned to or loaded into registers.
+    pub custom_domain: Option<CustomDomainConstructor>,
+}
+
+/// Return the identifier of the memory object that the value points to if the target is unique.
+/// It is used as the root cause of warnings about accesses through the value.
+fn get_root_cause(value: &Data) -> Option<String> {
+    match value {
+        Data::Pointer(pointer) if pointer.targets().len() == 1 => {
+            pointer.ids().next().map(|id| id.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// A memory pool with a known start address.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MemoryPool {
+    /// The start address of the pool.
+    pub base_address: u64,
+    /// The size of the pool in bytes.
+    pub size: u64,
+    /// Names of extern functions that allocate memory from the pool.
+    pub allocation_symbols: Vec<String>,
+    /// Names of extern functions that return memory to the pool.
+    pub deallocation_symbols: Vec<String>,
+    /// Whether the allocation functions return offsets relative to the start of the pool instead of pointers.
+    pub returns_offset: bool,
+}
+
+impl MemoryPool {
+    /// Create a memory pool from its configuration.
+    /// Returns an error if the pool address is not known or cannot be parsed.
+    pub fn from_config(config: &MemoryPoolConfig) -> Result<MemoryPool, Error> {
+        let address = config.pool_address.as_ref().ok_or_else(|| {
+            anyhow!(
+                "Address of memory pool {} unknown. The pool is ignored.",
+                config.pool_symbol
+            )
+        })?;
+        let base_address = u64::from_str_radix(address.trim_start_matches("0x"), 16)?;
+        Ok(MemoryPool {
+            base_address,
+            size: config.pool_size,
+            allocation_symbols: config.allocation_symbols.clone(),
+            deallocation_symbols: config.deallocation_symbols.clone(),
+            returns_offset: config.returns_offset,
+        })
+    }
 }
 
 impl<'a> Context<'a> {
@@ -53,14 +136,321 @@ impl<'a> Context<'a> {
         for symbol in project.program.term.extern_symbols.iter() {
             extern_symbol_map.insert(symbol.tid.clone(), symbol);
         }
+        let mut memory_pools = Vec::new();
+        for pool_config in config.memory_pools.iter() {
+            match MemoryPool::from_config(pool_config) {
+                Ok(pool) => memory_pools.push(pool),
+                Err(err) => {
+                    let log_message =
+                        LogMessage::new_error(format!("{}", err)).source("Pointer Inference");
+                    let _ = log_collector.send(LogThreadMsg::Log(log_message));
+                }
+            }
+        }
+        let pool_allocation_sites =
+            compute_pool_allocation_sites(project, &extern_symbol_map, &memory_pools);
+        let function_signatures =
+            compute_function_signature_map(project, config.function_signatures);
+        let get_sub_tids = |names: &[String]| -> HashSet<Tid> {
+            project
+                .program
+                .term
+                .subs
+                .iter()
+                .filter(|sub| names.contains(&sub.term.name))
+                .map(|sub| sub.tid.clone())
+                .collect()
+        };
+        let separate_context_subs = get_sub_tids(&config.separate_context_functions);
+        let graph = if separate_context_subs.is_empty() {
+            Cow::Borrowed(control_flow_graph)
+        } else {
+            let mut graph = control_flow_graph.clone();
+            add_separate_context_copies(&mut graph, &project.program, &separate_context_subs);
+            Cow::Owned(graph)
+        };
         Context {
-            graph: control_flow_graph,
+            graph,
             project,
             runtime_memory_image,
             extern_symbol_map,
             log_collector,
             allocation_symbols: config.allocation_symbols,
             deallocation_symbols: config.deallocation_symbols,
+            reallocation_symbols: config.reallocation_symbols,
+            uninitialized_allocation_symbols: config.uninitialized_allocation_symbols,
+            allocation_size_parameters: config.allocation_size_parameters,
+            memory_pools,
+            pool_allocation_sites,
+            function_signatures,
+            return_value_intervals: config.return_value_intervals,
+            string_formatting_symbols: config.string_formatting_symbols,
+            calling_environments: config.calling_environments,
+            environment_lookup_symbols: config.environment_lookup_symbols,
+            merged_context_subs: get_sub_tids(&config.merged_context_functions),
+            custom_domain: config.custom_domain,
+        }
+    }
+
+    /// Extend the value of the given register (resp. the offsets if it is a pointer)
+    /// by the custom numeric domain of the context.
+    /// Does nothing if no custom domain is configured.
+    fn extend_register_by_custom_domain(&self, state: &mut State, var: &Variable) {
+        let constructor = match self.custom_domain {
+            Some(constructor) => constructor,
+            None => return,
+        };
+        let extended_value = match state.get_register(var) {
+            Data::Value(value) => Data::Value(value.extend_by_custom_domain(constructor)),
+            Data::Pointer(pointer) => Data::Pointer(PointerDomain::with_targets(
+                pointer
+                    .targets()
+                    .iter()
+                    .map(|(id, offset)| (id.clone(), offset.extend_by_custom_domain(constructor)))
+                    .collect(),
+            )),
+            Data::Top(_) => return,
+        };
+        state.set_register(var, extended_value);
+    }
+
+    /// Compute the start state of the given function if it is an entry point of the analysis.
+    /// The `main` function is started in the configured calling environments (if any).
+    /// If the calling environments cannot be applied, an error is logged and the default start state is returned.
+    pub fn get_entry_state(&self, sub: &Term<Sub>) -> State {
+        if sub.term.name == calling_environment::ENTRY_FUNCTION_NAME
+            && !self.calling_environments.is_empty()
+        {
+            match calling_environment::get_entry_state(
+                self.project,
+                self.runtime_memory_image,
+                &sub.tid,
+                &self.calling_environments,
+            ) {
+                Ok(state) => return state,
+                Err(err) => {
+                    let log_message =
+                        LogMessage::new_error(format!("Calling environments not applied: {}", err))
+                            .source("Pointer Inference");
+                    let _ = self.log_collector.send(LogThreadMsg::Log(log_message));
+                }
+            }
+        }
+        State::new(&self.project.stack_pointer_register, sub.tid.clone())
+    }
+
+    /// Return the memory pool that the given extern function allocates memory from.
+    fn get_pool_of_allocation_symbol(&self, symbol_name: &str) -> Option<&MemoryPool> {
+        self.memory_pools.iter().find(|pool| {
+            pool.allocation_symbols
+                .iter()
+                .any(|name| name == symbol_name)
+        })
+    }
+
+    /// Return `true` if the given extern function returns memory to one of the memory pools.
+    fn is_pool_deallocation_symbol(&self, symbol_name: &str) -> bool {
+        self.memory_pools.iter().any(|pool| {
+            pool.deallocation_symbols
+                .iter()
+                .any(|name| name == symbol_name)
+        })
+    }
+
+    /// Check whether the given `Def` accesses memory outside of a memory object allocated from a memory pool.
+    ///
+    /// If the size of the allocated object is known (because the pool allocation function
+    /// is also configured in `allocation_size_parameters`), the access is checked against the object size.
+    /// Otherwise the object is only known to lie inside the pool, so that it cannot be larger than the pool itself.
+    /// Then only accesses at a negative offset or at an offset larger than the pool size
+    /// relative to the start of the object are detected, while overflows into neighbouring objects of the same pool are not.
+    /// A CWE warning is generated if the access is out of bounds for all possible offsets of the access.
+    fn check_for_out_of_pool_access(&self, state: &State, def: &Term<Def>) {
+        if self.pool_allocation_sites.is_empty() {
+            return;
+        }
+        let (address, access_size, cwe_name, description) = match &def.term {
+            Def::Load { var, address } => (address, var.size, "CWE125", "Out-of-bounds Read"),
+            Def::Store { address, value } => {
+                (address, value.bytesize(), "CWE787", "Out-of-bounds Write")
+            }
+            Def::Assign { .. } => return,
+        };
+        if let Data::Pointer(pointer) = state.eval(address) {
+            for (id, offset) in pointer.targets() {
+                if let Some(pool_index) = self.pool_allocation_sites.get(id.get_tid()) {
+                    let pool = &self.memory_pools[*pool_index];
+                    let object_size = state
+                        .memory
+                        .get_object_size(id)
+                        .and_then(|size| size.try_to_offset_interval().ok())
+                        .map(|(_, max_size)| max_size)
+                        .filter(|max_size| *max_size >= 0 && (*max_size as u64) < pool.size);
+                    let bound = object_size.unwrap_or(pool.size as i64);
+                    if let Ok((lower_bound, upper_bound)) = offset.try_to_offset_interval() {
+                        if upper_bound < 0 || lower_bound + u64::from(access_size) as i64 > bound {
+                            let mut other =
+                                vec![vec!["pool_size".to_string(), pool.size.to_string()]];
+                            if let Some(object_size) = object_size {
+                                other.push(vec![
+                                    "allocation_size".to_string(),
+                                    object_size.to_string(),
+                                ]);
+                            }
+                            let warning = CweWarning::new(
+                                cwe_name,
+                                VERSION,
+                                format!(
+                                    "({}) Access outside of memory allocated from pool at 0x{:x} at {}",
+                                    description, pool.base_address, def.tid.address
+                                ),
+                            )
+                            .tids(vec![format!("{}", def.tid)])
+                            .addresses(vec![def.tid.address.clone()])
+                            .other(other)
+                            .root_cause(id);
+                            let _ = self
+                                .log_collector
+                                .send(LogThreadMsg::Cwe(Box::new(warning)));
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the freed heap object whose contents the given offset or length value was derived from,
+    /// if the value is used for an access to a different heap object that is alive.
+    ///
+    /// Values loaded from an object before it was freed (e.g. its length field) do not fit objects of other sizes,
+    /// so using them as indices into or lengths of newly allocated objects is a common precursor of heap corruptions.
+    /// Targets whose size was computed from the stale value or equals the size of the freed object are ignored,
+    /// as are values known to lie inside the target object.
+    fn get_stale_value_origin<'b>(
+        &self,
+        state: &State,
+        target: &AbstractIdentifier,
+        value: &'b ValueDomain,
+    ) -> Option<&'b AbstractIdentifier> {
+        if !state
+            .memory
+            .is_heap_object_in_state(target, ObjectState::Alive)
+        {
+            return None;
+        }
+        let target_size = state.memory.get_object_size(target);
+        let target_size_bitvec = target_size
+            .as_ref()
+            .and_then(|size| size.try_to_bitvec().ok());
+        if let (Some(size), Ok((lower_bound, upper_bound))) = (
+            target_size_bitvec
+                .as_ref()
+                .and_then(|size| size.try_to_i64().ok()),
+            value.try_to_offset_interval(),
+        ) {
+            if lower_bound >= 0 && upper_bound < size {
+                return None;
+            }
+        }
+        value.get_origins().iter().find(|origin| {
+            *origin != target
+                && state
+                    .memory
+                    .is_heap_object_in_state(origin, ObjectState::Dangling)
+                && !matches!(&target_size, Some(size) if size.get_origins().contains(origin))
+                && (target_size_bitvec.is_none()
+                    || target_size_bitvec
+                        != state
+                            .memory
+                            .get_object_size(origin)
+                            .and_then(|size| size.try_to_bitvec().ok()))
+        })
+    }
+
+    /// Generate a CWE-416 warning for an access to a heap object
+    /// with an offset or a length derived from the contents of the freed object `origin`.
+    fn report_stale_value_use(&self, tid: &Tid, origin: &AbstractIdentifier, description: String) {
+        let warning = CweWarning::new("CWE416", VERSION, description)
+            .tids(vec![format!("{}", tid)])
+            .addresses(vec![tid.address.clone()])
+            .other(vec![vec![
+                "stale_value_origin".to_string(),
+                origin.to_string(),
+            ]])
+            .root_cause(origin);
+        let _ = self
+            .log_collector
+            .send(LogThreadMsg::Cwe(Box::new(warning)));
+    }
+
+    /// Check whether a store instruction writes into a heap object
+    /// at an offset derived from the contents of a freed object.
+    fn check_for_stale_offset_write(&self, state: &State, def: &Term<Def>) {
+        if let Def::Store { address, .. } = &def.term {
+            if let Data::Pointer(pointer) = state.eval(address) {
+                for (target, offset) in pointer.targets() {
+                    if let Some(origin) = self.get_stale_value_origin(state, target, offset) {
+                        self.report_stale_value_use(
+                            &def.tid,
+                            origin,
+                            format!(
+                                "(Use After Free) Write into a heap object at an offset derived from the contents of the freed object {} at {}",
+                                origin, def.tid.address
+                            ),
+                        );
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check whether a call to an extern function gets a pointer to a heap object
+    /// together with an offset or length derived from the contents of a freed object.
+    fn check_parameters_for_stale_lengths(
+        &self,
+        state: &State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) {
+        let parameter_values: Vec<Data> = extern_symbol
+            .parameters
+            .iter()
+            .filter_map(|parameter| {
+                state
+                    .eval_parameter_arg(
+                        parameter,
+                        &self.project.stack_pointer_register,
+                        self.runtime_memory_image,
+                    )
+                    .ok()
+            })
+            .collect();
+        for pointer_value in parameter_values.iter() {
+            let pointer = match pointer_value {
+                Data::Pointer(pointer) => pointer,
+                _ => continue,
+            };
+            for (target, offset) in pointer.targets() {
+                let lengths = parameter_values.iter().filter_map(|value| match value {
+                    Data::Value(length) => Some(length),
+                    _ => None,
+                });
+                for length in std::iter::once(offset).chain(lengths) {
+                    if let Some(origin) = self.get_stale_value_origin(state, target, length) {
+                        self.report_stale_value_use(
+                            &call.tid,
+                            origin,
+                            format!(
+                                "(Use After Free) Call to {} accesses a heap object with an offset or length derived from the contents of the freed object {} at {}",
+                                extern_symbol.name, origin, call.tid.address
+                            ),
+                        );
+                        return;
+                    }
+                }
+            }
         }
     }
 
@@ -81,14 +471,7 @@ impl<'a> Context<'a> {
         &self,
         state_before_return: &State,
     ) -> Result<(), Error> {
-        let expected_stack_pointer_offset = match self.project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                Bitvector::from_u64(u64::from(self.project.get_pointer_bytesize()))
-                    .into_truncate(apint::BitWidth::from(self.project.get_pointer_bytesize()))
-                    .unwrap()
-            }
-            _ => Bitvector::zero(apint::BitWidth::from(self.project.get_pointer_bytesize())),
-        };
+        let expected_stack_pointer_offset = self.project.get_stack_pointer_offset_on_return();
         match state_before_return.get_register(&self.project.stack_pointer_register) {
             Data::Pointer(pointer) => {
                 if pointer.targets().len() == 1 {
@@ -116,11 +499,17 @@ impl<'a> Context<'a> {
     /// Add a new abstract object and a pointer to it in the return register of an extern call.
     /// This models the behaviour of `malloc`-like functions,
     /// except that we cannot represent possible `NULL` pointers as return values yet.
+    ///
+    /// The returned pointer points to the given offset relative to the start of the new object.
+    ///
+    /// If the size of the new object is known, it is stored in the object.
     fn add_new_object_in_call_return_register(
         &self,
         mut state: State,
         call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
+        pointer_offset: Bitvector,
+        allocation_size: Option<ValueDomain>,
     ) -> State {
         match extern_symbol.get_unique_return_register() {
             Ok(return_register) => {
@@ -135,10 +524,16 @@ impl<'a> Context<'a> {
                     super::object::ObjectType::Heap,
                     address_bytesize,
                 );
-                let pointer = PointerDomain::new(
-                    object_id,
-                    Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
-                );
+                if let Some(allocation_size) = allocation_size {
+                    state.memory.set_object_size(&object_id, allocation_size);
+                }
+                if self
+                    .uninitialized_allocation_symbols
+                    .contains(&extern_symbol.name)
+                {
+                    state.memory.mark_object_as_uninitialized(&object_id);
+                }
+                let pointer = PointerDomain::new(object_id, pointer_offset.into());
                 state.set_register(return_register, pointer.into());
                 state
             }
@@ -150,6 +545,132 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Compute the size of the memory object allocated by a call to the given allocation function
+    /// as the product of its size parameters, as configured in `allocation_size_parameters`.
+    ///
+    /// Returns `None` if the function has no configured size parameters
+    /// or if one of them is not an absolute value of pointer size.
+    fn get_allocation_size(
+        &self,
+        state: &State,
+        extern_symbol: &ExternSymbol,
+    ) -> Option<ValueDomain> {
+        let parameter_indices = self.allocation_size_parameters.get(&extern_symbol.name)?;
+        let address_bytesize = self.project.get_pointer_bytesize();
+        let mut allocation_size: Option<ValueDomain> = None;
+        for index in parameter_indices {
+            let parameter = state
+                .eval_parameter_arg(
+                    extern_symbol.parameters.get(*index)?,
+                    &self.project.stack_pointer_register,
+                    self.runtime_memory_image,
+                )
+                .ok()?;
+            let size = match parameter {
+                Data::Value(size) if size.bytesize() == address_bytesize => size,
+                _ => return None,
+            };
+            allocation_size = Some(match allocation_size {
+                Some(product) => product.bin_op(BinOpType::IntMult, &size),
+                None => size,
+            });
+        }
+        allocation_size
+    }
+
+    /// Mark the objects that the pointer may point to as freed.
+    /// If one of the objects may have been already freed, generate a CWE warning for the given call.
+    fn mark_pointer_targets_as_freed(
+        &self,
+        new_state: &mut State,
+        pointer: &PointerDomain<ValueDomain>,
+        call: &Term<Jmp>,
+    ) {
+        if let Err(possible_double_frees) = new_state.mark_mem_object_as_freed(pointer) {
+            let warning = CweWarning {
+                name: "CWE415".to_string(),
+                version: VERSION.to_string(),
+                addresses: vec![call.tid.address.clone()],
+                tids: vec![format!("{}", call.tid)],
+                symbols: Vec::new(),
+                other: vec![possible_double_frees
+                    .into_iter()
+                    .map(|(id, err)| format!("{}: {}", id, err))
+                    .collect()],
+                description: format!(
+                    "(Double Free) Object may have been freed before at {}",
+                    call.tid.address
+                ),
+                root_cause: get_root_cause(&Data::Pointer(pointer.clone())),
+                related: Vec::new(),
+                score: None,
+                finding_id: None,
+                first_seen: None,
+                last_seen: None,
+                triage_state: None,
+            };
+            let _ = self
+                .log_collector
+                .send(LogThreadMsg::Cwe(Box::new(warning)));
+        }
+    }
+
+    /// Handle a call to a `realloc`-like function.
+    ///
+    /// A new heap object is created for the return value and the content of the old object is copied into it.
+    /// Since the old object may have been moved, it is marked as freed,
+    /// so that later accesses through pointers to the old object are reported as use-after-frees.
+    /// If the first parameter is not a pointer (e.g. a `NULL` pointer), the call behaves like `malloc`.
+    fn handle_realloc(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let address_bytesize = self.project.get_pointer_bytesize();
+        let old_pointer = extern_symbol.parameters.first().map(|parameter| {
+            state.eval_parameter_arg(
+                parameter,
+                &self.project.stack_pointer_register,
+                self.runtime_memory_image,
+            )
+        });
+        let mut new_state = self.add_new_object_in_call_return_register(
+            new_state,
+            call,
+            extern_symbol,
+            Bitvector::zero(apint::BitWidth::from(address_bytesize)),
+            self.get_allocation_size(state, extern_symbol),
+        );
+        let new_object_id = match extern_symbol.get_unique_return_register() {
+            Ok(return_register) => AbstractIdentifier::new(
+                call.tid.clone(),
+                AbstractLocation::from_var(return_register).unwrap(),
+            ),
+            Err(_) => return new_state,
+        };
+        match old_pointer {
+            Some(Ok(Data::Pointer(old_pointer))) => {
+                // If the old pointer may point to the new object (e.g. for calls inside loops),
+                // we cannot distinguish the old and the new object.
+                if old_pointer.targets().contains_key(&new_object_id) {
+                    return new_state;
+                }
+                new_state
+                    .memory
+                    .copy_object_content(&old_pointer, &new_object_id);
+                self.mark_pointer_targets_as_freed(&mut new_state, &old_pointer, call);
+                new_state
+            }
+            Some(Err(err)) => {
+                self.log_debug(Err(err), Some(&call.tid));
+                new_state
+            }
+            _ => new_state,
+        }
+    }
+
     /// Mark the object that the parameter of a call is pointing to as freed.
     /// If the object may have been already freed, generate a CWE warning.
     /// This models the behaviour of `free` and similar functions.
@@ -170,26 +691,7 @@ impl<'a> Context<'a> {
                 match parameter_value {
                     Ok(memory_object_pointer) => {
                         if let Data::Pointer(pointer) = memory_object_pointer {
-                            if let Err(possible_double_frees) =
-                                new_state.mark_mem_object_as_freed(&pointer)
-                            {
-                                let warning = CweWarning {
-                                    name: "CWE415".to_string(),
-                                    version: VERSION.to_string(),
-                                    addresses: vec![call.tid.address.clone()],
-                                    tids: vec![format!("{}", call.tid)],
-                                    symbols: Vec::new(),
-                                    other: vec![possible_double_frees
-                                        .into_iter()
-                                        .map(|(id, err)| format!("{}: {}", id, err))
-                                        .collect()],
-                                    description: format!(
-                                        "(Double Free) Object may have been freed before at {}",
-                                        call.tid.address
-                                    ),
-                                };
-                                let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
-                            }
+                            self.mark_pointer_targets_as_freed(&mut new_state, &pointer, call);
                         } else {
                             self.log_debug(
                                 Err(anyhow!("Free on a non-pointer value called.")),
@@ -239,8 +741,17 @@ impl<'a> Context<'a> {
                                 "(Use After Free) Call to {} may access freed memory at {}",
                                 extern_symbol.name, call.tid.address
                             ),
+                            root_cause: get_root_cause(&value),
+                            related: Vec::new(),
+                            score: None,
+                            finding_id: None,
+                            first_seen: None,
+                            last_seen: None,
+                            triage_state: None,
                         };
-                        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+                        let _ = self
+                            .log_collector
+                            .send(LogThreadMsg::Cwe(Box::new(warning)));
                     }
                 }
                 Err(err) => self.log_debug(
@@ -290,6 +801,135 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Set the return register of the extern symbol to the given (signed) interval.
+    /// If the interval does not fit into the return register, the return value stays unknown.
+    fn set_return_value_interval(
+        &self,
+        mut state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        (min, max): (i64, i64),
+    ) -> State {
+        let return_register = match extern_symbol.get_unique_return_register() {
+            Ok(return_register) => return_register,
+            Err(err) => {
+                self.log_debug(Err(err), Some(&call.tid));
+                return state;
+            }
+        };
+        let width = apint::BitWidth::from(return_register.size);
+        let resize = |value: i64| -> Option<Bitvector> {
+            let bitvec = Bitvector::from_i64(value).into_sign_resize(width);
+            (bitvec.try_to_i64().ok()? == value).then_some(bitvec)
+        };
+        if let (Some(min), Some(max)) = (resize(min), resize(max)) {
+            state.set_register(return_register, ValueDomain::new(min, max).into());
+        }
+        state
+    }
+
+    /// Set the return value of a `getenv`-like function according to the configured calling environments.
+    ///
+    /// If the variable is unset in all environments, the return value is a NULL pointer.
+    /// If it is set in all environments, the return value points to a new memory object containing its value.
+    /// Otherwise, or if the name of the variable is not a constant string, the return value stays unknown.
+    fn set_environment_variable_lookup_result(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let name = extern_symbol.parameters.first().and_then(|parameter| {
+            let name = state
+                .eval_parameter_arg(
+                    parameter,
+                    &self.project.stack_pointer_register,
+                    self.runtime_memory_image,
+                )
+                .ok()?;
+            get_constant_string(&name, self.runtime_memory_image)
+        });
+        let (name, return_register) = match (name, extern_symbol.get_unique_return_register()) {
+            (Some(name), Ok(return_register)) => (name, return_register),
+            _ => return new_state,
+        };
+        match calling_environment::get_environment_variable(&self.calling_environments, name) {
+            EnvironmentVariable::Unset => new_state.set_register(
+                return_register,
+                Bitvector::zero(apint::BitWidth::from(return_register.size)).into(),
+            ),
+            EnvironmentVariable::Set(values) => {
+                let object_id = AbstractIdentifier::new(
+                    call.tid.clone(),
+                    AbstractLocation::from_var(return_register).unwrap(),
+                );
+                let pointer = calling_environment::add_string_object(
+                    &mut new_state,
+                    object_id,
+                    &values,
+                    self.project.get_pointer_bytesize(),
+                );
+                new_state.set_register(return_register, pointer);
+            }
+            EnvironmentVariable::Unknown => (),
+        }
+        new_state
+    }
+
+    /// Record bounds for the length of the string that a `sprintf`-like function writes into its destination buffer.
+    ///
+    /// The bounds are computed from the format string, which has to be a constant string in the binary,
+    /// and from the arguments in the state before the call.
+    /// For functions with a size parameter (e.g. `snprintf`) the bounds are truncated to the buffer size.
+    fn set_formatted_string_length(
+        &self,
+        state: &State,
+        mut new_state: State,
+        extern_symbol: &ExternSymbol,
+        parameters: &StringFormattingParameters,
+    ) -> State {
+        let eval_parameter = |index: usize| -> Option<Data> {
+            state
+                .eval_parameter_arg(
+                    &get_variadic_parameter(self.project, extern_symbol, index),
+                    &self.project.stack_pointer_register,
+                    self.runtime_memory_image,
+                )
+                .ok()
+        };
+        let (destination, format_string) = match (
+            eval_parameter(parameters.destination_parameter),
+            eval_parameter(parameters.format_string_parameter).and_then(|format_string| {
+                get_constant_string(&format_string, self.runtime_memory_image)
+            }),
+        ) {
+            (Some(destination), Some(format_string)) => (destination, format_string),
+            _ => return new_state,
+        };
+        let mut bounds = get_formatted_string_length_bounds(
+            state,
+            format_string,
+            |index| eval_parameter(parameters.format_string_parameter + 1 + index),
+            self.runtime_memory_image,
+        );
+        if let Some((min_size, max_size)) = parameters
+            .size_parameter
+            .and_then(eval_parameter)
+            .and_then(|size| size.try_to_offset_interval().ok())
+            .filter(|(min_size, _)| *min_size >= 0)
+        {
+            let max_length = std::cmp::max(max_size, 1) as u64 - 1;
+            bounds.lower_bound =
+                std::cmp::min(bounds.lower_bound, std::cmp::max(min_size, 1) as u64 - 1);
+            bounds.upper_bound = Some(bounds.upper_bound.map_or(max_length, |upper_bound| {
+                std::cmp::min(upper_bound, max_length)
+            }));
+        }
+        new_state.memory.set_string_length(&destination, bounds);
+        new_state
+    }
+
     /// Handle an extern symbol call, whose concrete effect on the state is unknown.
     /// Basically, we assume that the call may write to all memory objects and register that is has access to.
     fn handle_generic_extern_call(
@@ -375,6 +1015,35 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Get the abstract identifier that represents the stack frame of the caller inside the callee of the given call.
+    ///
+    /// Usually the identifier is determined by the call instruction.
+    /// For callees with merged calling contexts all callers share one identifier
+    /// unless the caller already knows this identifier (e.g. for recursive calls).
+    fn get_caller_stack_id(
+        &self,
+        state_before_call: &State,
+        call: &Term<Jmp>,
+    ) -> AbstractIdentifier {
+        let stack_location =
+            AbstractLocation::from_var(&self.project.stack_pointer_register).unwrap();
+        if let Jmp::Call { target, .. } = &call.term {
+            if self.merged_context_subs.contains(target) {
+                let merged_caller_stack_id = AbstractIdentifier::new(
+                    target.clone().with_id_suffix("_callers"),
+                    stack_location.clone(),
+                );
+                if !state_before_call
+                    .memory
+                    .contains_object(&merged_caller_stack_id)
+                {
+                    return merged_caller_stack_id;
+                }
+            }
+        }
+        AbstractIdentifier::new(call.tid.clone(), stack_location)
+    }
+
     /// Get the offset of the current stack pointer to the base of the current stack frame.
     fn get_current_stack_offset(&self, state: &State) -> ValueDomain {
         if let Data::Pointer(ref stack_pointer) =
@@ -392,5 +1061,59 @@ impl<'a> Context<'a> {
     }
 }
 
+/// Collect the TIDs of all calls to pool allocation functions in the program
+/// and map them to the index of the corresponding memory pool.
+fn compute_pool_allocation_sites(
+    project: &Project,
+    extern_symbol_map: &BTreeMap<Tid, &ExternSymbol>,
+    memory_pools: &[MemoryPool],
+) -> HashMap<Tid, usize> {
+    let mut pool_allocation_sites = HashMap::new();
+    if memory_pools.is_empty() {
+        return pool_allocation_sites;
+    }
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if let Some(symbol) = extern_symbol_map.get(target) {
+                        if let Some(pool_index) = memory_pools
+                            .iter()
+                            .position(|pool| pool.allocation_symbols.contains(&symbol.name))
+                        {
+                            pool_allocation_sites.insert(jmp.tid.clone(), pool_index);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    pool_allocation_sites
+}
+
+/// Map the TIDs of the internal functions to the parameters of their signatures.
+/// Signatures are matched to functions by name, so signatures of functions with ambiguous names are ignored.
+fn compute_function_signature_map(
+    project: &Project,
+    mut signatures: BTreeMap<String, FunctionSignature>,
+) -> HashMap<Tid, BTreeSet<String>> {
+    let mut name_counts: HashMap<&str, usize> = HashMap::new();
+    for sub in project.program.term.subs.iter() {
+        *name_counts.entry(&sub.term.name).or_insert(0) += 1;
+    }
+    project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| name_counts[sub.term.name.as_str()] == 1)
+        .filter_map(|sub| {
+            signatures
+                .remove(&sub.term.name)
+                .map(|signature| (sub.tid.clone(), signature.parameters))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests;
@@ -70,7 +70,9 @@ fn mock_project() -> (Project, Config) {
         extern_symbols: vec![
             mock_extern_symbol("malloc"),
             mock_extern_symbol("free"),
+            mock_extern_symbol("realloc"),
             mock_extern_symbol("other"),
+            mock_extern_symbol("atoi"),
         ],
         entry_points: Vec::new(),
         address_base_offset: 0,
@@ -84,6 +86,7 @@ fn mock_project() -> (Project, Config) {
         parameter_register: vec!["RDX".to_string()],
         return_register: vec!["RDX".to_string()],
         callee_saved_register: vec!["callee_saved_reg".to_string()],
+        stack_alignment: None,
     };
     (
         Project {
@@ -95,6 +98,28 @@ fn mock_project() -> (Project, Config) {
         Config {
             allocation_symbols: vec!["malloc".into()],
             deallocation_symbols: vec!["free".into()],
+            reallocation_symbols: vec!["realloc".into()],
+            uninitialized_allocation_symbols: vec!["malloc".into()],
+            allocation_size_parameters: vec![("malloc".to_string(), vec![0])].into_iter().collect(),
+            memory_pools: Vec::new(),
+            function_signatures: BTreeMap::new(),
+            return_value_intervals: vec![("atoi".to_string(), (-5, 10))].into_iter().collect(),
+            string_formatting_symbols: vec![(
+                "sprintf".to_string(),
+                StringFormattingParameters {
+                    destination_parameter: 0,
+                    size_parameter: None,
+                    format_string_parameter: 1,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            calling_environments: Vec::new(),
+            environment_lookup_symbols: vec!["getenv".into()],
+            function_time_limit: None,
+            custom_domain: None,
+            merged_context_functions: Vec::new(),
+            separate_context_functions: Vec::new(),
         },
     )
 }
@@ -147,6 +172,7 @@ fn context_problem_implementation() {
         term: Sub {
             name: "caller_sub".into(),
             blocks: vec![target_block.clone()],
+            qualified_name: None,
         },
     };
     let target_node = crate::analysis::graph::Node::BlkStart(&target_block, &sub);
@@ -163,7 +189,7 @@ fn context_problem_implementation() {
         .memory
         .set_value(
             PointerDomain::new(new_id("func", "RSP"), bv(-30)),
-            Data::Value(bv(33).into()),
+            Data::Value(bv(33)),
         )
         .unwrap();
     // Emulate  removing the return pointer from the stack for x64
@@ -213,6 +239,16 @@ fn context_problem_implementation() {
         ))
     );
     assert_eq!(state_after_malloc.memory.get_num_objects(), 2);
+    // The size of the allocated object is propagated from the size parameter.
+    let mut state_with_size = state.clone();
+    state_with_size.set_register(&register("RDX"), Data::Value(bv(16)));
+    let state_with_size = context.update_call_stub(&state_with_size, &malloc).unwrap();
+    assert_eq!(
+        state_with_size
+            .memory
+            .get_object_size(&new_id("call_extern_malloc", "RDX")),
+        Some(bv(16))
+    );
     assert_eq!(
         state_after_malloc.get_register(&register("RSP")),
         state
@@ -287,7 +323,7 @@ fn update_return() {
     let callsite_id = new_id("call_callee", "RSP");
     state_before_return.memory.add_abstract_object(
         callsite_id.clone(),
-        bv(0).into(),
+        bv(0),
         ObjectType::Stack,
         ByteSize::new(8),
     );
@@ -301,7 +337,7 @@ fn update_return() {
     let other_callsite_id = new_id("call_callee_other", "RSP");
     state_before_return.memory.add_abstract_object(
         other_callsite_id.clone(),
-        bv(0).into(),
+        bv(0),
         ObjectType::Stack,
         ByteSize::new(8),
     );
@@ -329,7 +365,7 @@ fn update_return() {
     let caller_caller_id = new_id("caller_caller", "RSP");
     state_before_call.memory.add_abstract_object(
         caller_caller_id.clone(),
-        bv(0).into(),
+        bv(0),
         ObjectType::Stack,
         ByteSize::new(8),
     );
@@ -373,6 +409,185 @@ fn update_return() {
     assert_eq!(state.get_register(&register("RSP")), expected_rsp);
 }
 
+#[test]
+fn call_with_stack_parameters_after_alloca() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::pointer_inference::Data;
+    use Expression::*;
+
+    let (project, config) = mock_project();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let mut state = context
+        .update_def(&state, &reg_add_term("RSP", -16, "prologue"))
+        .unwrap();
+    let local_pointer = Data::Pointer(PointerDomain::new(new_id("main", "RSP"), bv(-8)));
+    state.set_register(&register("RDI"), local_pointer);
+    state
+        .memory
+        .set_value(
+            PointerDomain::new(new_id("main", "RSP"), bv(-8)),
+            Data::Value(bv(42)),
+        )
+        .unwrap();
+    state.set_register(
+        &register("RAX"),
+        Data::Value(ValueDomain::new(
+            Bitvector::from_i64(16),
+            Bitvector::from_i64(64),
+        )),
+    );
+    let alloca = Def::assign(
+        "alloca",
+        register("RSP"),
+        Var(register("RSP")).minus(Var(register("RAX"))),
+    );
+    let state = context.update_def(&state, &alloca).unwrap();
+    let allocation_start = ValueDomain::new(Bitvector::from_i64(-80), Bitvector::from_i64(-32));
+    assert_eq!(
+        state.get_register(&register("RSP")),
+        Data::Pointer(PointerDomain::new(
+            new_id("main", "RSP"),
+            allocation_start.clone()
+        ))
+    );
+    // Writing a stack parameter does not overwrite the rest of the stack frame.
+    let store_parameter = Def::store(
+        "store_parameter",
+        Var(register("RSP")),
+        Const(Bitvector::from_i64(1)),
+    );
+    let state = context.update_def(&state, &store_parameter).unwrap();
+    assert_eq!(
+        state
+            .memory
+            .get_value(
+                &PointerDomain::new(new_id("main", "RSP"), bv(-8)).into(),
+                ByteSize::new(8)
+            )
+            .unwrap(),
+        Data::Value(bv(42))
+    );
+
+    // The callee sees the caller stack frame relative to the stack pointer at the call.
+    let target_block = Blk::mock_with("func_start", Vec::new(), Jmp::Return(Var(register("RAX"))));
+    let sub = Sub::mock("func");
+    let target_node = crate::analysis::graph::Node::BlkStart(&target_block, &sub);
+    let call = call_term("func");
+    let callee_state = context.update_call(&state, &call, &target_node).unwrap();
+    assert!(callee_state.stack_allocations.is_empty());
+    assert_eq!(
+        callee_state.get_register(&register("RDI")),
+        Data::Pointer(PointerDomain::new(
+            new_id("call_func", "RSP"),
+            ValueDomain::new(Bitvector::from_i64(24), Bitvector::from_i64(72))
+        ))
+    );
+    let callee_state = context
+        .update_def(&callee_state, &reg_add_term("RSP", 8, "return_address"))
+        .unwrap();
+
+    // The allocation is still known after the return.
+    let return_state = context
+        .update_return(
+            Some(&callee_state),
+            Some(&state),
+            &call,
+            &return_term("return_target"),
+        )
+        .unwrap();
+    assert_eq!(return_state.stack_allocations, state.stack_allocations);
+    assert_eq!(
+        return_state.stack_allocations[&Tid::new("alloca")].start,
+        allocation_start
+    );
+    assert_eq!(
+        return_state.get_register(&register("RSP")),
+        Data::Pointer(PointerDomain::new(
+            new_id("main", "RSP"),
+            ValueDomain::new(Bitvector::from_i64(-72), Bitvector::from_i64(-24))
+        ))
+    );
+}
+
+#[test]
+fn merged_calling_contexts() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, mut config) = mock_project();
+    project.program.term.subs.push(Term {
+        tid: Tid::new("func"),
+        term: Sub {
+            name: "func".into(),
+            blocks: Vec::new(),
+            qualified_name: None,
+        },
+    });
+    config.merged_context_functions = vec!["func".into()];
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    assert!(context.merged_context_subs.contains(&Tid::new("func")));
+
+    let target_block = Term {
+        tid: Tid::new("func_start"),
+        term: Blk {
+            defs: Vec::new(),
+            jmps: Vec::new(),
+            indirect_jmp_targets: Vec::new(),
+        },
+    };
+    let target_node =
+        crate::analysis::graph::Node::BlkStart(&target_block, &project.program.term.subs[0]);
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let mut other_call = call_term("func");
+    other_call.tid = Tid::new("call_func_other");
+
+    // Both call sites share the same caller stack ID.
+    let merged_caller_id = new_id("func_callers", "RSP");
+    for call in [call_term("func"), other_call].iter() {
+        let callee_state = context.update_call(&state, call, &target_node).unwrap();
+        assert_eq!(callee_state.caller_stack_ids.len(), 1);
+        assert!(callee_state.caller_stack_ids.contains(&merged_caller_id));
+    }
+
+    // Recursive calls fall back to the caller stack ID of the call site.
+    let callee_state = context
+        .update_call(&state, &call_term("func"), &target_node)
+        .unwrap();
+    let recursive_call = Term {
+        tid: Tid::new("recursive_call_func"),
+        term: call_term("func").term,
+    };
+    let recursive_state = context
+        .update_call(&callee_state, &recursive_call, &target_node)
+        .unwrap();
+    assert_eq!(recursive_state.caller_stack_ids.len(), 1);
+    assert!(recursive_state
+        .caller_stack_ids
+        .contains(&new_id("recursive_call_func", "RSP")));
+
+    // The return is matched to the merged caller stack ID.
+    let state_before_return = context
+        .update_def(
+            &callee_state,
+            &reg_add_term("RSP", 8, "stack_offset_on_return_adjustment"),
+        )
+        .unwrap();
+    let return_state = context
+        .update_return(
+            Some(&state_before_return),
+            Some(&state),
+            &call_term("func"),
+            &return_term("return_target"),
+        )
+        .unwrap();
+    assert_eq!(return_state.stack_id, new_id("main", "RSP"));
+}
+
 #[test]
 fn specialize_conditional() {
     use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
@@ -482,3 +697,393 @@ fn specialize_conditional() {
     let result = context.specialize_conditional(&state, &condition, &block, false);
     assert!(result.is_none());
 }
+
+#[test]
+fn memory_pool_allocation() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, mut config) = mock_project();
+    project
+        .program
+        .term
+        .extern_symbols
+        .push(mock_extern_symbol("pool_alloc"));
+    let mut sub = Sub::mock("main");
+    let mut block = Blk::mock();
+    block.term.jmps = vec![call_term("extern_pool_alloc"), call_term("extern_malloc")];
+    sub.term.blocks.push(block);
+    project.program.term.subs.push(sub);
+    config.memory_pools.push(MemoryPoolConfig {
+        pool_symbol: "pool".to_string(),
+        pool_address: Some("0x2000".to_string()),
+        pool_size: 16,
+        allocation_symbols: vec!["pool_alloc".to_string()],
+        deallocation_symbols: vec!["pool_free".to_string()],
+        returns_offset: true,
+    });
+    config
+        .allocation_size_parameters
+        .insert("pool_alloc".to_string(), vec![0]);
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    assert_eq!(context.memory_pools[0].base_address, 0x2000);
+    assert!(context.is_pool_deallocation_symbol("pool_free"));
+    // Only the calls to the pool allocation function are pool allocation sites.
+    assert_eq!(
+        context.pool_allocation_sites,
+        HashMap::from([(Tid::new("call_extern_pool_alloc"), 0)])
+    );
+
+    // The allocation function returns an offset relative to the pool start.
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let pool_alloc = call_term("extern_pool_alloc");
+    let mut state = context.update_call_stub(&state, &pool_alloc).unwrap();
+    assert_eq!(
+        state.get_register(&register("RDX")),
+        PointerDomain::new(new_id("call_extern_pool_alloc", "RDX"), bv(-0x2000)).into()
+    );
+    // Adding the pool start yields a pointer to the start of the allocated object.
+    let add_pool_start = reg_add_term("RDX", 0x2000, "add_pool_start");
+    state = context.update_def(&state, &add_pool_start).unwrap();
+    assert_eq!(
+        state.get_register(&register("RDX")),
+        PointerDomain::new(new_id("call_extern_pool_alloc", "RDX"), bv(0)).into()
+    );
+    let store_at = |offset: i64| {
+        Def::store(
+            "store",
+            Expression::Var(register("RDX")).plus_const(offset),
+            Expression::Const(Bitvector::from_u32(42)),
+        )
+    };
+    // If the size of the allocated object is unknown, only accesses beyond the pool size are detected.
+    context.update_def(&state, &store_at(8)).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+    context.update_def(&state, &store_at(16)).unwrap();
+    match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => {
+            assert_eq!(warning.name, "CWE787");
+            assert_eq!(
+                warning.other,
+                vec![vec!["pool_size".to_string(), "16".to_string()]]
+            );
+        }
+        _ => panic!("Expected a CWE warning."),
+    }
+
+    // Accesses beyond a known allocation size are detected, even if they stay inside the pool.
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), Data::Value(bv(8)));
+    let mut state = context.update_call_stub(&state, &pool_alloc).unwrap();
+    state = context.update_def(&state, &add_pool_start).unwrap();
+    context.update_def(&state, &store_at(4)).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+    context.update_def(&state, &store_at(8)).unwrap();
+    match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => assert_eq!(
+            warning.other[1],
+            vec!["allocation_size".to_string(), "8".to_string()]
+        ),
+        _ => panic!("Expected a CWE warning."),
+    }
+}
+
+#[test]
+fn update_call_with_function_signature() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::function_signature::FunctionSignature;
+    use crate::analysis::pointer_inference::Data;
+
+    let (mut project, mut config) = mock_project();
+    project.calling_conventions[0].name = "__stdcall".to_string();
+    project.program.term.subs = vec![Sub::mock("func"), Sub::mock("no_parameters")];
+    config
+        .function_signatures
+        .insert("no_parameters".to_string(), FunctionSignature::default());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    assert_eq!(context.function_signatures.len(), 1);
+
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), Data::Value(bv(1)));
+    let target_block = Blk::mock();
+    let sub = Sub::mock("caller_sub");
+    let target_node = crate::analysis::graph::Node::BlkStart(&target_block, &sub);
+    // Functions without a signature get all parameter registers.
+    let callee_state = context
+        .update_call(&state, &call_term("func"), &target_node)
+        .unwrap();
+    assert_eq!(
+        callee_state.get_register(&register("RDX")),
+        Data::Value(bv(1))
+    );
+    // Parameter registers not contained in the signature are removed.
+    let callee_state = context
+        .update_call(&state, &call_term("no_parameters"), &target_node)
+        .unwrap();
+    assert_eq!(
+        callee_state.get_register(&register("RDX")),
+        Data::new_top(ByteSize::new(8))
+    );
+}
+
+#[test]
+fn realloc_handling() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state = State::new(&register("RSP"), Tid::new("main"));
+
+    let mut state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    let old_pointer = Data::Pointer(PointerDomain::new(
+        new_id("call_extern_malloc", "RDX"),
+        bv(0),
+    ));
+    state
+        .store_value(&old_pointer, &Data::Value(bv(42)), &runtime_memory_image)
+        .unwrap();
+    state.set_register(&register("callee_saved_reg"), old_pointer.clone());
+
+    let state = context
+        .update_call_stub(&state, &call_term("extern_realloc"))
+        .unwrap();
+    let new_pointer = Data::Pointer(PointerDomain::new(
+        new_id("call_extern_realloc", "RDX"),
+        bv(0),
+    ));
+    assert_eq!(state.get_register(&register("RDX")), new_pointer);
+    // The content of the old object is copied to the new object.
+    // The loaded value is tagged with the object it was loaded from.
+    let mut expected_value = bv(42);
+    expected_value.add_origins(
+        &vec![new_id("call_extern_realloc", "RDX")]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(
+        state
+            .load_value(
+                &Expression::Var(register("RDX")),
+                ByteSize::new(8),
+                &runtime_memory_image
+            )
+            .unwrap(),
+        Data::Value(expected_value)
+    );
+    // Pointers to the old object are dangling after the call.
+    assert!(state.memory.is_dangling_pointer(&old_pointer, false));
+    assert!(!state.memory.is_dangling_pointer(&new_pointer, false));
+}
+
+#[test]
+fn return_value_intervals() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state = State::new(&register("RSP"), Tid::new("main"));
+
+    let state = context
+        .update_call_stub(&state, &call_term("extern_atoi"))
+        .unwrap();
+    assert_eq!(
+        state.get_register(&register("RDX")),
+        Data::Value(ValueDomain::new(
+            Bitvector::from_i64(-5),
+            Bitvector::from_i64(10)
+        ))
+    );
+}
+
+#[test]
+fn stale_length_writes() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    let old_object = new_id("call_extern_malloc", "RDX");
+
+    // Allocate an object of size 16 and load its (unknown) length field.
+    state.set_register(&register("RDX"), Data::Value(bv(16)));
+    let mut state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    let load_length = Def::load(
+        "load_length",
+        register("callee_saved_reg"),
+        Expression::Var(register("RDX")),
+    );
+    state = context.update_def(&state, &load_length).unwrap();
+    match state.get_register(&register("callee_saved_reg")) {
+        Data::Value(length) => {
+            assert!(length.get_origins().contains(&old_object));
+            assert!(length.try_to_interval().is_err());
+        }
+        _ => panic!("Expected a tagged value."),
+    }
+    let indexed_store = Def::store(
+        "indexed_store",
+        Expression::Var(register("RDX")).plus(Expression::Var(register("callee_saved_reg"))),
+        Expression::Const(Bitvector::from_u8(0)),
+    );
+    // Writes into the object itself are not reported.
+    context.update_def(&state, &indexed_store).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+
+    // Free the object (keeping the dangling pointer on the stack) and allocate a smaller object.
+    let save_pointer = Def::store(
+        "save_pointer",
+        Expression::Var(register("RSP")).plus_const(-8),
+        Expression::Var(register("RDX")),
+    );
+    state = context.update_def(&state, &save_pointer).unwrap();
+    state = context
+        .update_call_stub(&state, &call_term("extern_free"))
+        .unwrap();
+    state.set_register(
+        &register("RDX"),
+        PointerDomain::new(new_id("new_object", "RDX"), bv(0)).into(),
+    );
+    state.memory.add_abstract_object(
+        new_id("new_object", "RDX"),
+        bv(0),
+        ObjectType::Heap,
+        ByteSize::new(8),
+    );
+    state
+        .memory
+        .set_object_size(&new_id("new_object", "RDX"), bv(8));
+    context.update_def(&state, &indexed_store).unwrap();
+    match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => {
+            assert_eq!(warning.name, "CWE416");
+            assert_eq!(warning.root_cause, Some(old_object.to_string()));
+        }
+        _ => panic!("Expected a CWE warning."),
+    }
+
+    // Objects whose size was computed from the stale value are not reported.
+    let mut stale_size = bv(16);
+    stale_size.add_origins(&vec![old_object].into_iter().collect());
+    state
+        .memory
+        .set_object_size(&new_id("new_object", "RDX"), stale_size);
+    context.update_def(&state, &indexed_store).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+}
+
+#[test]
+fn string_formatting() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::string_abstraction::StringLengthBounds;
+
+    let (mut project, config) = mock_project();
+    let mut sprintf = mock_extern_symbol("sprintf");
+    sprintf.parameters = vec![
+        Arg::Register(register("RDX")),
+        Arg::Register(register("RSI")),
+    ];
+    project.program.term.extern_symbols.push(sprintf);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    let destination = Data::Pointer(PointerDomain::new(state.stack_id.clone(), bv(-32)));
+    state.set_register(&register("RDX"), destination.clone());
+
+    // The format string "Hello World" is a constant string in the binary.
+    state.set_register(&register("RSI"), Data::Value(bv(0x3002)));
+    let new_state = context
+        .update_call_stub(&state, &call_term("extern_sprintf"))
+        .unwrap();
+    assert_eq!(
+        new_state.memory.get_string_length(&destination),
+        Some(StringLengthBounds::exact(11))
+    );
+    // Without a constant format string nothing is recorded.
+    state.set_register(
+        &register("RSI"),
+        Data::Value(ValueDomain::new_top(ByteSize::new(8))),
+    );
+    let new_state = context
+        .update_call_stub(&state, &call_term("extern_sprintf"))
+        .unwrap();
+    assert_eq!(new_state.memory.get_string_length(&destination), None);
+}
+
+#[test]
+fn environment_variable_lookup() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::string_abstraction::StringLengthBounds;
+
+    let (mut project, mut config) = mock_project();
+    project
+        .program
+        .term
+        .extern_symbols
+        .push(mock_extern_symbol("getenv"));
+    config.calling_environments = vec![CallingEnvironment {
+        arguments: Vec::new(),
+        environment_variables: vec![
+            ("AB".to_string(), Some("value".to_string())),
+            ("Hello World".to_string(), None),
+        ]
+        .into_iter()
+        .collect(),
+    }];
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+
+    // The variable "AB" is set.
+    state.set_register(&register("RDX"), Data::Value(bv(0x300e)));
+    let new_state = context
+        .update_call_stub(&state, &call_term("extern_getenv"))
+        .unwrap();
+    let value = new_state.get_register(&register("RDX"));
+    assert_eq!(
+        value,
+        Data::Pointer(PointerDomain::new(
+            new_id("call_extern_getenv", "RDX"),
+            bv(0)
+        ))
+    );
+    assert_eq!(
+        new_state.memory.get_string_length(&value),
+        Some(StringLengthBounds::exact(5))
+    );
+    // The variable "Hello World" is unset.
+    state.set_register(&register("RDX"), Data::Value(bv(0x3002)));
+    let new_state = context
+        .update_call_stub(&state, &call_term("extern_getenv"))
+        .unwrap();
+    assert_eq!(new_state.get_register(&register("RDX")), Data::Value(bv(0)));
+    // Unknown variable names result in unknown return values.
+    state.set_register(
+        &register("RDX"),
+        Data::Value(ValueDomain::new_top(ByteSize::new(8))),
+    );
+    let new_state = context
+        .update_call_stub(&state, &call_term("extern_getenv"))
+        .unwrap();
+    assert!(new_state.get_register(&register("RDX")).is_top());
+}
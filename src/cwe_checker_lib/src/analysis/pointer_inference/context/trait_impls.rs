@@ -18,6 +18,12 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
     fn update_def(&self, state: &Self::Value, def: &Term<Def>) -> Option<Self::Value> {
         // first check for use-after-frees
         if state.contains_access_of_dangling_memory(&def.term) {
+            let root_cause = match &def.term {
+                Def::Load { address, .. } | Def::Store { address, .. } => {
+                    get_root_cause(&state.eval(address))
+                }
+                Def::Assign { .. } => None,
+            };
             let warning = CweWarning {
                 name: "CWE416".to_string(),
                 version: VERSION.to_string(),
@@ -29,9 +35,20 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                     "(Use After Free) Access through a dangling pointer at {}",
                     def.tid.address
                 ),
+                root_cause,
+                related: Vec::new(),
+                score: None,
+                finding_id: None,
+                first_seen: None,
+                last_seen: None,
+                triage_state: None,
             };
-            let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+            let _ = self
+                .log_collector
+                .send(LogThreadMsg::Cwe(Box::new(warning)));
         }
+        self.check_for_out_of_pool_access(state, def);
+        self.check_for_stale_offset_write(state, def);
 
         match &def.term {
             Def::Store { address, value } => {
@@ -44,7 +61,12 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             }
             Def::Assign { var, value } => {
                 let mut new_state = state.clone();
-                new_state.handle_register_assign(var, value);
+                if *var == self.project.stack_pointer_register {
+                    new_state.handle_stack_pointer_assign(&def.tid, var, value);
+                } else {
+                    new_state.handle_register_assign(var, value);
+                    self.extend_register_by_custom_domain(&mut new_state, var);
+                }
                 Some(new_state)
             }
             Def::Load { var, address } => {
@@ -53,6 +75,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                     new_state.handle_load(var, address, &self.runtime_memory_image),
                     Some(&def.tid),
                 );
+                self.extend_register_by_custom_domain(&mut new_state, var);
                 Some(new_state)
             }
         }
@@ -89,10 +112,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 callee_tid.clone(),
                 AbstractLocation::from_var(&self.project.stack_pointer_register).unwrap(),
             );
-            let new_caller_stack_id = AbstractIdentifier::new(
-                call_term.tid.clone(),
-                AbstractLocation::from_var(&self.project.stack_pointer_register).unwrap(),
-            );
+            let new_caller_stack_id = self.get_caller_stack_id(state, call_term);
             let stack_offset_adjustment = self.get_current_stack_offset(state);
             let address_bytesize = self.project.stack_pointer_register.size;
 
@@ -105,6 +125,11 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             if let Some(cconv) = self.project.get_standard_calling_convention() {
                 // Note that this may lead to analysis errors if the function uses another calling convention.
                 callee_state.remove_callee_saved_register(cconv);
+                // Remove parameter registers that the callee does not read according to its signature,
+                // so that changes of their values in the caller do not trigger a recomputation of the callee.
+                if let Some(parameters) = self.function_signatures.get(callee_tid) {
+                    callee_state.remove_unused_parameter_register(cconv, parameters);
+                }
             }
             // Replace the caller stack ID with one determined by the call instruction.
             // This has to be done *before* adding the new callee stack id to avoid confusing caller and callee stack ids in case of recursive calls.
@@ -132,6 +157,8 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 )
                 .into(),
             );
+            // Dynamic stack allocations belong to the caller stack frame.
+            callee_state.stack_allocations = BTreeMap::new();
             // set the list of caller stack ids to only this caller id
             callee_state.caller_stack_ids = BTreeSet::new();
             callee_state.caller_stack_ids.insert(new_caller_stack_id);
@@ -183,10 +210,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             };
 
         let original_caller_stack_id = &state_before_call.stack_id;
-        let caller_stack_id = AbstractIdentifier::new(
-            call_term.tid.clone(),
-            AbstractLocation::from_var(&self.project.stack_pointer_register).unwrap(),
-        );
+        let caller_stack_id = self.get_caller_stack_id(state_before_call, call_term);
         let callee_stack_id = &state_before_return.stack_id;
         let stack_offset_on_call = self.get_current_stack_offset(state_before_call);
 
@@ -226,6 +250,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         state_after_return.stack_id = original_caller_stack_id.clone();
         state_after_return.caller_stack_ids = state_before_call.caller_stack_ids.clone();
         state_after_return.ids_known_to_caller = state_before_call.ids_known_to_caller.clone();
+        state_after_return.stack_allocations = state_before_call.stack_allocations.clone();
 
         state_after_return.readd_caller_objects(state_before_call);
 
@@ -272,18 +297,89 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             self.adjust_stack_register_on_extern_call(state, &mut new_state);
             // Check parameter for possible use-after-frees
             self.check_parameter_register_for_dangling_pointer(state, call, extern_symbol);
+            self.check_parameters_for_stale_lengths(state, call, extern_symbol);
 
+            let address_bytesize = self.project.get_pointer_bytesize();
             match extern_symbol.name.as_str() {
+                pool_alloc_fn if self.get_pool_of_allocation_symbol(pool_alloc_fn).is_some() => {
+                    let pool = self.get_pool_of_allocation_symbol(pool_alloc_fn).unwrap();
+                    // If the allocation function returns an offset into the pool,
+                    // adding the start address of the pool to the returned value yields a pointer to the start of the new object.
+                    let pointer_offset = if pool.returns_offset {
+                        -Bitvector::from_u64(pool.base_address)
+                            .into_truncate(apint::BitWidth::from(address_bytesize))
+                            .unwrap()
+                    } else {
+                        Bitvector::zero(apint::BitWidth::from(address_bytesize))
+                    };
+                    let allocation_size = self.get_allocation_size(state, extern_symbol);
+                    Some(self.add_new_object_in_call_return_register(
+                        new_state,
+                        call,
+                        extern_symbol,
+                        pointer_offset,
+                        allocation_size,
+                    ))
+                }
+                realloc_like_fn
+                    if self
+                        .reallocation_symbols
+                        .iter()
+                        .any(|x| x == realloc_like_fn) =>
+                {
+                    Some(self.handle_realloc(state, new_state, call, extern_symbol))
+                }
                 malloc_like_fn if self.allocation_symbols.iter().any(|x| x == malloc_like_fn) => {
                     Some(self.add_new_object_in_call_return_register(
                         new_state,
                         call,
                         extern_symbol,
+                        Bitvector::zero(apint::BitWidth::from(address_bytesize)),
+                        self.get_allocation_size(state, extern_symbol),
                     ))
                 }
-                free_like_fn if self.deallocation_symbols.iter().any(|x| x == free_like_fn) => {
+                free_like_fn
+                    if self.deallocation_symbols.iter().any(|x| x == free_like_fn)
+                        || self.is_pool_deallocation_symbol(free_like_fn) =>
+                {
                     Some(self.mark_parameter_object_as_freed(state, new_state, call, extern_symbol))
                 }
+                formatting_fn if self.string_formatting_symbols.contains_key(formatting_fn) => {
+                    let new_state =
+                        self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+                    Some(self.set_formatted_string_length(
+                        state,
+                        new_state,
+                        extern_symbol,
+                        &self.string_formatting_symbols[formatting_fn],
+                    ))
+                }
+                lookup_fn
+                    if !self.calling_environments.is_empty()
+                        && self
+                            .environment_lookup_symbols
+                            .iter()
+                            .any(|x| x == lookup_fn) =>
+                {
+                    let new_state =
+                        self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+                    Some(self.set_environment_variable_lookup_result(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                    ))
+                }
+                bounded_fn if self.return_value_intervals.contains_key(bounded_fn) => {
+                    let new_state =
+                        self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+                    Some(self.set_return_value_interval(
+                        new_state,
+                        call,
+                        extern_symbol,
+                        self.return_value_intervals[bounded_fn],
+                    ))
+                }
                 _ => Some(self.handle_generic_extern_call(state, new_state, call, extern_symbol)),
             }
         } else {
@@ -291,6 +387,15 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         }
     }
 
+    /// Handle a call to a function whose analysis failed like a call to an unknown function.
+    fn update_call_to_failed_function(
+        &self,
+        state_before_call: &State,
+        _call_term: &Term<Jmp>,
+    ) -> Option<State> {
+        self.handle_call_to_generic_unknown_function(state_before_call)
+    }
+
     /// Update the state with the knowledge that some conditional evaluated to true or false.
     fn specialize_conditional(
         &self,
@@ -4,6 +4,10 @@
 //! that the program knows about at specific program points during execution.
 //! Possible memory management errors, like access to memory that may already have been freed,
 //! are reported to the user.
+//! Values loaded from heap objects are tagged with the objects they were loaded from.
+//! Thus the analysis also reports accesses to heap objects with offsets or lengths
+//! derived from the contents of objects that were freed in the meantime,
+//! e.g. a length loaded from an object before it was freed and used as an index into a smaller object.
 //!
 //! Keep in mind that the analysis operates on a best-effort basis.
 //! In cases where we cannot know
@@ -13,29 +17,43 @@
 //!
 //! See the `Config` struct for configurable analysis parameters.
 
-use super::fixpoint::Computation;
-use super::forward_interprocedural_fixpoint::GeneralizedContext;
+use super::fixpoint::{Computation, Context as _};
+use super::forward_interprocedural_fixpoint::{
+    compute_with_failure_isolation, Context as _, GeneralizedContext,
+};
+use super::function_signature::FunctionSignature;
 use super::interprocedural_fixpoint_generic::NodeValue;
-use crate::analysis::graph::{Graph, Node};
+use crate::analysis::graph::{Edge, Graph, Node};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::*;
+use crate::utils::report::escape_html;
 use crate::{
-    abstract_domain::{DataDomain, IntervalDomain},
+    abstract_domain::{
+        AbstractIdentifier, CustomDomainConstructor, DataDomain, ExtendedIntervalDomain,
+        TryToValueSet,
+    },
     utils::binary::RuntimeMemoryImage,
 };
-use petgraph::graph::NodeIndex;
-use petgraph::visit::IntoNodeReferences;
+use object_types::HeapObjectType;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::{EdgeRef, IntoNodeReferences};
 use petgraph::Direction;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
+mod calling_environment;
 mod context;
 pub mod object;
 mod object_list;
+pub mod object_types;
 mod state;
 
+pub use calling_environment::CallingEnvironment;
 use context::Context;
-pub use state::State;
+pub use state::{StackAllocation, State};
 
 /// The version number of the analysis.
 const VERSION: &str = "0.1";
@@ -45,16 +63,20 @@ pub static CWE_MODULE: crate::CweModule = crate::CweModule {
     name: "Memory",
     version: VERSION,
     run: extract_pi_analysis_results,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The abstract domain to use for absolute values.
-pub type ValueDomain = IntervalDomain;
+/// It extends the interval domain by the custom numeric domain given in the configuration (if any).
+pub type ValueDomain = ExtendedIntervalDomain;
 
 /// The abstract domain type for representing register values.
 pub type Data = DataDomain<ValueDomain>;
 
 /// Configurable parameters for the analysis.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// Names of extern functions that are `malloc`-like,
     /// i.e. the unique return value is a pointer to a newly allocated chunk of memory or a NULL pointer.
@@ -64,12 +86,155 @@ pub struct Config {
     /// Note that the analysis currently does not detect mismatching allocation-deallocation pairs,
     /// i.e. it cannot distinguish between memory allocated by `malloc` and memory allocated by `new`.
     pub deallocation_symbols: Vec<String>,
+    /// Names of extern functions that are `realloc`-like,
+    /// i.e. the memory chunk that the first parameter points to gets moved to a newly allocated chunk of memory,
+    /// whose address is the unique return value.
+    /// Since the chunk may be moved, pointers to the old chunk are treated as dangling after the call.
+    #[serde(default)]
+    pub reallocation_symbols: Vec<String>,
+    /// Names of allocation functions whose returned memory is not initialized, e.g. `malloc` but not `calloc`.
+    /// The analysis tracks which bytes of the memory objects allocated by them are initialized.
+    #[serde(default)]
+    pub uninitialized_allocation_symbols: Vec<String>,
+    /// Names of allocation and reallocation functions mapped to the indices of their size parameters.
+    /// The size of the allocated memory object is the product of the size parameters,
+    /// e.g. `calloc` has the two size parameters `nmemb` and `size`.
+    #[serde(default)]
+    pub allocation_size_parameters: BTreeMap<String, Vec<usize>>,
+    /// Fixed-size static memory pools managed by custom allocation functions.
+    /// Memory objects allocated by these functions are sub-regions of the corresponding pool.
+    #[serde(default)]
+    pub memory_pools: Vec<MemoryPoolConfig>,
+    /// Maps names of internal functions to their register parameters,
+    /// usually the signatures computed by [`infer_function_signatures`](crate::analysis::function_signature::infer_function_signatures).
+    /// At calls to these functions only the parameter registers of the calling convention
+    /// that are contained in the signature are passed to the callee.
+    /// The signatures must contain all parameter registers that the function may read.
+    #[serde(default)]
+    pub function_signatures: BTreeMap<String, FunctionSignature>,
+    /// Names of extern functions whose return value is known to lie in the given (signed) interval,
+    /// e.g. integer parsing functions like `atoi`.
+    /// Checks on the return value (e.g. range checks) refine the interval as usual.
+    #[serde(default)]
+    pub return_value_intervals: BTreeMap<String, (i64, i64)>,
+    /// Names of `sprintf`-like extern functions mapped to the indices of their parameters.
+    /// The length of the string written to the destination buffer is approximated
+    /// from the format string and the arguments and recorded in the memory object of the destination.
+    #[serde(default)]
+    pub string_formatting_symbols: BTreeMap<String, StringFormattingParameters>,
+    /// The program arguments and environment variables that the program is assumed to be started with.
+    /// If not empty, the `main` function is analyzed with the argument count and argument vector
+    /// given by the environments instead of with unknown parameters.
+    #[serde(default)]
+    pub calling_environments: Vec<CallingEnvironment>,
+    /// Names of `getenv`-like extern functions, i.e. the unique parameter is the name of an environment variable
+    /// and the return value is a pointer to its value or a NULL pointer if it is not set.
+    /// The return values are only modeled if calling environments are configured.
+    #[serde(default)]
+    pub environment_lookup_symbols: Vec<String>,
+    /// If set, the analysis of a function is aborted if it takes longer than the given number of seconds in total.
+    /// Calls to functions whose analysis was aborted are handled like calls to unknown functions.
+    #[serde(default)]
+    pub function_time_limit: Option<u64>,
+    /// The constructor of a custom numeric domain (see [`CustomDomain`](crate::abstract_domain::CustomDomain))
+    /// that extends the interval domain for values assigned to or loaded into registers.
+    /// If not set, only the interval domain is used.
+    /// It can only be set through the library API.
+    #[serde(skip)]
+    pub custom_domain: Option<CustomDomainConstructor>,
+    /// Names of functions (e.g. logging functions or small getters) whose calling contexts are always merged.
+    /// The stack frames of all their callers are represented by one memory object inside the function,
+    /// which reduces the analysis time at the cost of precision for the callers.
+    #[serde(default)]
+    pub merged_context_functions: Vec<String>,
+    /// Names of functions that are analyzed separately for each call site in another function.
+    /// The analysis internally uses a copy of the control flow graph
+    /// where these functions are copied for each call site (see [`add_separate_context_copies`](crate::analysis::graph::add_separate_context_copies)).
+    /// The results at the copies are merged after the fixpoint computation.
+    #[serde(default)]
+    pub separate_context_functions: Vec<String>,
+}
+
+impl Config {
+    /// Look up the addresses of all memory pools without a configured address
+    /// in the symbol table of the given binary.
+    /// The `address_base_offset` is added to the found addresses
+    /// to align them with the addresses used in the program representation.
+    /// Pools whose address cannot be determined are ignored by the analysis.
+    pub fn resolve_memory_pool_addresses(&mut self, binary: &[u8], address_base_offset: u64) {
+        for pool in self.memory_pools.iter_mut() {
+            if pool.pool_address.is_none() {
+                if let Ok(address) =
+                    crate::utils::binary::get_elf_symbol_address(binary, &pool.pool_symbol)
+                {
+                    pool.pool_address = Some(format!("{:x}", address + address_base_offset));
+                }
+            }
+        }
+    }
+}
+
+/// A fixed-size memory pool (usually a global array) from which custom allocation functions hand out memory,
+/// as is common in embedded and RTOS code.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryPoolConfig {
+    /// The name of the global symbol marking the start of the pool.
+    pub pool_symbol: String,
+    /// The start address of the pool as a hexadecimal string.
+    /// If not provided, the address is looked up in the symbol table of the binary.
+    #[serde(default)]
+    pub pool_address: Option<String>,
+    /// The size of the pool in bytes.
+    pub pool_size: u64,
+    /// Names of extern functions that allocate memory from the pool.
+    pub allocation_symbols: Vec<String>,
+    /// Names of extern functions that return memory to the pool.
+    /// Their unique parameter is the pointer (or offset) returned by the allocation function.
+    #[serde(default)]
+    pub deallocation_symbols: Vec<String>,
+    /// If `true`, the allocation functions return offsets relative to the start of the pool
+    /// instead of pointers into the pool.
+    #[serde(default)]
+    pub returns_offset: bool,
+}
+
+/// The parameters of a `sprintf`-like function writing a formatted string into a destination buffer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StringFormattingParameters {
+    /// The index of the parameter pointing to the destination buffer.
+    pub destination_parameter: usize,
+    /// The index of the parameter containing the size of the destination buffer (e.g. for `snprintf`).
+    #[serde(default)]
+    pub size_parameter: Option<usize>,
+    /// The index of the format string parameter.
+    pub format_string_parameter: usize,
 }
 
 /// A wrapper struct for the pointer inference computation object.
 pub struct PointerInference<'a> {
     computation: Computation<GeneralizedContext<'a, Context<'a>>>,
     log_collector: crossbeam_channel::Sender<LogThreadMsg>,
+    /// The control flow graph without the copies of the functions with separate calling contexts.
+    /// Its node indices are the same as the indices of the original nodes in the graph of the computation.
+    control_flow_graph: &'a Graph<'a>,
+    /// Maps the TID of a jump to the `BlkEnd` node of the block containing the jump.
+    /// For blocks contained in more than one function the node in the function containing the block in the program is used.
+    jmp_to_blk_end_node_map: HashMap<Tid, NodeIndex>,
+    /// Maps the TID of a block to its `BlkStart` node.
+    /// For blocks contained in more than one function the node in the function containing the block in the program is used.
+    blk_to_blk_start_node_map: HashMap<Tid, NodeIndex>,
+    /// Maps the TID of a `Def` to the `BlkStart` node of the block containing the `Def`.
+    /// For blocks contained in more than one function the node in the function containing the block in the program is used.
+    def_to_blk_start_node_map: HashMap<Tid, NodeIndex>,
+    /// The inferred types of heap objects if already computed.
+    heap_object_types: OnceCell<BTreeMap<AbstractIdentifier, HeapObjectType>>,
+    /// Maps the nodes of functions with separate calling contexts to the corresponding nodes of the copies of the functions.
+    context_copies: HashMap<NodeIndex, Vec<NodeIndex>>,
+    /// The values of the nodes with copies merged with the values of their copies.
+    /// Computed after the fixpoint computation.
+    merged_copy_values: HashMap<NodeIndex, NodeValue<State>>,
     /// The log messages and CWE warnings that have been generated during the pointer inference analysis.
     pub collected_logs: (Vec<LogMessage>, Vec<CweWarning>),
 }
@@ -83,6 +248,7 @@ impl<'a> PointerInference<'a> {
         config: Config,
         log_sender: crossbeam_channel::Sender<LogThreadMsg>,
     ) -> PointerInference<'a> {
+        let function_time_limit = config.function_time_limit.map(Duration::from_secs);
         let context = Context::new(
             project,
             runtime_memory_image,
@@ -107,11 +273,48 @@ impl<'a> PointerInference<'a> {
             }
         }
         let mut tid_to_graph_indices_map = HashMap::new();
-        for node in context.graph.node_indices() {
-            if let super::graph::Node::BlkStart(block, sub) = context.graph[node] {
-                tid_to_graph_indices_map.insert((block.tid.clone(), sub.tid.clone()), node);
+        let mut jmp_to_blk_end_node_map = HashMap::new();
+        let mut blk_to_blk_start_node_map = HashMap::new();
+        let mut def_to_blk_start_node_map = HashMap::new();
+        for node in control_flow_graph.node_indices() {
+            match control_flow_graph[node] {
+                Node::BlkStart(block, sub) => {
+                    tid_to_graph_indices_map.insert((block.tid.clone(), sub.tid.clone()), node);
+                    blk_to_blk_start_node_map
+                        .entry(block.tid.clone())
+                        .or_insert(node);
+                    for def in block.term.defs.iter() {
+                        def_to_blk_start_node_map
+                            .entry(def.tid.clone())
+                            .or_insert(node);
+                    }
+                }
+                Node::BlkEnd(block, _sub) => {
+                    for jmp in block.term.jmps.iter() {
+                        jmp_to_blk_end_node_map
+                            .entry(jmp.tid.clone())
+                            .or_insert(node);
+                    }
+                }
+                _ => (),
             }
         }
+        // The copies of functions with separate calling contexts are appended to the graph of the computation.
+        let original_nodes: HashMap<Node, NodeIndex> = control_flow_graph
+            .node_references()
+            .map(|(node_id, node)| (*node, node_id))
+            .collect();
+        let mut context_copies: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        for node in context
+            .graph
+            .node_indices()
+            .skip(control_flow_graph.node_count())
+        {
+            context_copies
+                .entry(original_nodes[&context.graph[node]])
+                .or_default()
+                .push(node);
+        }
         let entry_sub_to_entry_node_map: HashMap<Tid, NodeIndex> = entry_sub_to_entry_blocks_map
             .into_iter()
             .filter_map(|(sub_tid, block_tid)| {
@@ -124,39 +327,87 @@ impl<'a> PointerInference<'a> {
                 }
             })
             .collect();
-        let mut fixpoint_computation =
-            super::forward_interprocedural_fixpoint::create_computation(context, None);
+        let mut generalized_context = GeneralizedContext::new(context);
+        generalized_context.set_function_time_limit(function_time_limit);
+        let mut fixpoint_computation = Computation::new(generalized_context, None);
         let _ = log_sender.send(LogThreadMsg::Log(LogMessage::new_debug(format!(
             "Pointer Inference: Adding {} entry points",
             entry_sub_to_entry_node_map.len()
         ))));
         for (sub_tid, start_node_index) in entry_sub_to_entry_node_map.into_iter() {
+            let entry_state = fixpoint_computation
+                .get_context()
+                .get_context()
+                .get_entry_state(subs[&sub_tid]);
             fixpoint_computation.set_node_value(
                 start_node_index,
-                super::interprocedural_fixpoint_generic::NodeValue::Value(State::new(
-                    &project.stack_pointer_register,
-                    sub_tid,
-                )),
+                super::interprocedural_fixpoint_generic::NodeValue::Value(entry_state),
             );
         }
         PointerInference {
             computation: fixpoint_computation,
             log_collector: log_sender,
+            control_flow_graph,
+            jmp_to_blk_end_node_map,
+            blk_to_blk_start_node_map,
+            def_to_blk_start_node_map,
+            heap_object_types: OnceCell::new(),
+            context_copies,
+            merged_copy_values: HashMap::new(),
             collected_logs: (Vec::new(), Vec::new()),
         }
     }
 
     /// Compute the fixpoint of the pointer inference analysis.
     /// Has a `max_steps` bound for the fixpoint algorithm to prevent infinite loops.
+    ///
+    /// If the analysis of a function fails, an error message is logged
+    /// and the computation is continued with calls to the function handled like calls to unknown functions.
     pub fn compute(&mut self) {
-        self.computation.compute_with_max_steps(100); // TODO: make max_steps configurable!
+        let failed_functions = compute_with_failure_isolation(&mut self.computation, 100); // TODO: make max_steps configurable!
+        for (sub_tid, failure) in failed_functions {
+            let _ = self.log_collector.send(LogThreadMsg::Log(
+                LogMessage::new_error(format!(
+                    "Pointer Inference: Analysis of function failed: {}",
+                    failure
+                ))
+                .location(sub_tid),
+            ));
+        }
+        self.merge_context_copies();
+    }
+
+    /// Merge the values of all nodes with copies in functions with separate calling contexts
+    /// with the values of their copies, so that the values at the original nodes cover all calling contexts.
+    fn merge_context_copies(&mut self) {
+        let mut merged_copy_values = HashMap::new();
+        for (node_id, copies) in self.context_copies.iter() {
+            let merged_value = std::iter::once(node_id)
+                .chain(copies.iter())
+                .filter_map(|node| self.computation.get_node_value(*node))
+                .fold(
+                    None,
+                    |merged: Option<NodeValue<State>>, value| match merged {
+                        Some(merged) => Some(self.computation.get_context().merge(&merged, value)),
+                        None => Some(value.clone()),
+                    },
+                );
+            if let Some(merged_value) = merged_value {
+                merged_copy_values.insert(*node_id, merged_value);
+            }
+        }
+        self.merged_copy_values = merged_copy_values;
     }
 
     /// Print results serialized as YAML to stdout
     pub fn print_yaml(&self) {
-        let graph = self.computation.get_graph();
-        for (node_index, value) in self.computation.node_values().iter() {
-            let node = graph.node_weight(*node_index).unwrap();
+        let graph = self.get_graph();
+        for node_index in graph.node_indices() {
+            let value = match self.get_node_value(node_index) {
+                Some(value) => value,
+                None => continue,
+            };
+            let node = graph.node_weight(node_index).unwrap();
             if let Ok(string) = serde_yaml::to_string(&(node, value)) {
                 println!("{}", string);
             } else {
@@ -173,17 +424,98 @@ impl<'a> PointerInference<'a> {
     /// Note that this output cannot be used for serialization/deserialization,
     /// but is only intended for user output and debugging.
     pub fn generate_compact_json(&self) -> serde_json::Value {
-        let graph = self.computation.get_graph();
+        let graph = self.get_graph();
         let mut json_nodes = serde_json::Map::new();
-        for (node_index, node_value) in self.computation.node_values().iter() {
-            let node = graph.node_weight(*node_index).unwrap();
-            if let NodeValue::Value(value) = node_value {
+        for node_index in graph.node_indices() {
+            let node = graph.node_weight(node_index).unwrap();
+            if let Some(NodeValue::Value(value)) = self.get_node_value(node_index) {
                 json_nodes.insert(format!("{}", node), value.to_json_compact());
             }
         }
         serde_json::Value::Object(json_nodes)
     }
 
+    /// Record the states computed at the start and at the end of each block of the function with the given name
+    /// during the fixpoint computation.
+    /// Has to be called before the computation is started.
+    /// Returns an error if no function with the given name exists.
+    pub fn trace_function(&mut self, function_name: &str) -> Result<(), Error> {
+        let nodes: Vec<NodeIndex> = self
+            .computation
+            .get_graph()
+            .node_references()
+            .filter_map(|(node_index, node)| match node {
+                Node::BlkStart(_, sub) | Node::BlkEnd(_, sub) if sub.term.name == function_name => {
+                    Some(node_index)
+                }
+                _ => None,
+            })
+            .collect();
+        if nodes.is_empty() {
+            return Err(anyhow!("Function {} not found", function_name));
+        }
+        self.computation.trace_nodes(nodes);
+        Ok(())
+    }
+
+    /// Render the states recorded for the traced function (see [`PointerInference::trace_function`])
+    /// as a standalone HTML page.
+    ///
+    /// The page contains one table per block.
+    /// The n-th row of a table shows the n-th state computed for the start of the block
+    /// next to the n-th state computed for the end of the block.
+    pub fn render_trace_as_html(&self) -> String {
+        let graph = self.computation.get_graph();
+        let mut block_states: BTreeMap<Tid, (Vec<String>, Vec<String>)> = BTreeMap::new();
+        for (node_index, node_value) in self.computation.get_trace() {
+            let state = match node_value {
+                NodeValue::Value(state) => format!("{:#}", state.to_json_compact()),
+                NodeValue::CallFlowCombinator { .. } => continue,
+            };
+            match graph[*node_index] {
+                Node::BlkStart(block, _) => block_states
+                    .entry(block.tid.clone())
+                    .or_default()
+                    .0
+                    .push(state),
+                Node::BlkEnd(block, _) => block_states
+                    .entry(block.tid.clone())
+                    .or_default()
+                    .1
+                    .push(state),
+                _ => (),
+            }
+        }
+        let mut tables = String::new();
+        for (block_tid, (incoming_states, outgoing_states)) in block_states {
+            tables += &format!(
+                "<h2>{}</h2>\n<table border=\"1\">\n\
+                 <tr><th>Iteration</th><th>Incoming state</th><th>Outgoing state</th></tr>\n",
+                escape_html(&block_tid.to_string())
+            );
+            for iteration in 0..std::cmp::max(incoming_states.len(), outgoing_states.len()) {
+                let cell = |states: &Vec<String>| {
+                    states
+                        .get(iteration)
+                        .map(|state| format!("<pre>{}</pre>", escape_html(state)))
+                        .unwrap_or_default()
+                };
+                tables += &format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    iteration + 1,
+                    cell(&incoming_states),
+                    cell(&outgoing_states)
+                );
+            }
+            tables += "</table>\n";
+        }
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Pointer inference trace</title></head>\n\
+             <body>\n<h1>Pointer inference trace</h1>\n{}</body>\n</html>\n",
+            tables
+        )
+    }
+
     /// Print a compacted json representation of the results to stdout.
     /// Note that this output cannot be used for serialization/deserialization,
     /// but is only intended for user output and debugging.
@@ -191,21 +523,239 @@ impl<'a> PointerInference<'a> {
         println!("{:#}", self.generate_compact_json());
     }
 
-    /// Get the underlying graph of the computation.
+    /// Get the control flow graph of the computation.
+    ///
+    /// The copies of functions with separate calling contexts are internal to the computation
+    /// and not contained in the returned graph.
     pub fn get_graph(&self) -> &Graph {
-        self.computation.get_graph()
+        self.control_flow_graph
     }
 
     /// Get the context object of the computation.
-    pub fn get_context(&self) -> &Context {
+    pub fn get_context(&self) -> &Context<'a> {
         self.computation.get_context().get_context()
     }
 
     /// Get the value associated to a node in the computed fixpoint
     /// (or intermediate state of the algorithm if the fixpoint has not been reached yet).
     /// Returns `None` if no value is associated to the Node.
+    ///
+    /// For nodes of functions with separate calling contexts the value is merged from all calling contexts.
     pub fn get_node_value(&self, node_id: NodeIndex) -> Option<&NodeValue<State>> {
-        self.computation.get_node_value(node_id)
+        self.merged_copy_values
+            .get(&node_id)
+            .or_else(|| self.computation.get_node_value(node_id))
+    }
+
+    /// Check whether a value is associated to the node or to one of its copies in functions with separate calling contexts.
+    fn has_value_in_any_context(&self, node_id: NodeIndex) -> bool {
+        self.computation.get_node_value(node_id).is_some()
+            || self
+                .context_copies
+                .get(&node_id)
+                .into_iter()
+                .flatten()
+                .any(|copy| self.computation.get_node_value(*copy).is_some())
+    }
+
+    /// Get the state right before the execution of the jump with the given TID,
+    /// i.e. the state at the end of the block containing the jump.
+    /// Returns `None` if no state is known at that program point.
+    pub fn get_state_at_jmp_tid(&self, jmp_tid: &Tid) -> Option<&State> {
+        let node_id = self.jmp_to_blk_end_node_map.get(jmp_tid)?;
+        match self.get_node_value(*node_id) {
+            Some(NodeValue::Value(state)) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Get the state at the start of the block with the given TID.
+    /// Returns `None` if no state is known at that program point.
+    pub fn get_state_at_blk_start(&self, blk_tid: &Tid) -> Option<&State> {
+        let node_id = self.blk_to_blk_start_node_map.get(blk_tid)?;
+        match self.get_node_value(*node_id) {
+            Some(NodeValue::Value(state)) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Get the state right before the execution of the `Def` or `Jmp` with the given TID.
+    ///
+    /// For `Def` terms the state is computed by applying the effects of all preceding `Def` terms of the block
+    /// to the state at the start of the block.
+    /// Returns `None` if no state is known at that program point.
+    pub fn get_state_at_tid(&self, tid: &Tid) -> Option<Cow<'_, State>> {
+        if let Some(state) = self.get_state_at_jmp_tid(tid) {
+            return Some(Cow::Borrowed(state));
+        }
+        let node_id = self.def_to_blk_start_node_map.get(tid)?;
+        let block = match self.get_graph()[*node_id] {
+            Node::BlkStart(block, _sub) => block,
+            _ => return None,
+        };
+        let mut state = match self.get_node_value(*node_id) {
+            Some(NodeValue::Value(state)) => state.clone(),
+            _ => return None,
+        };
+        for def in block.term.defs.iter() {
+            if def.tid == *tid {
+                break;
+            }
+            self.apply_def(&mut state, def);
+        }
+        Some(Cow::Owned(state))
+    }
+
+    /// Get the states right before the execution of the jump with the given TID,
+    /// one for each intraprocedural jump edge leading to the block containing the jump,
+    /// together with the index of the corresponding edge in the control flow graph.
+    ///
+    /// In contrast to [`get_state_at_jmp_tid`](Self::get_state_at_jmp_tid)
+    /// the states of different paths are not merged at the start of the block,
+    /// e.g. to distinguish early-return paths reaching a shared function epilogue.
+    /// If the block is also reached through other edges (e.g. returns from calls)
+    /// or no state is known for the jump edges leading to it,
+    /// the merged state at the jump is returned in addition (without an edge index).
+    pub fn get_states_at_jmp_tid_per_path(
+        &self,
+        jmp_tid: &Tid,
+    ) -> Vec<(Option<EdgeIndex>, Cow<'_, State>)> {
+        let graph = self.get_graph();
+        let block = match self.jmp_to_blk_end_node_map.get(jmp_tid) {
+            Some(node_id) => match graph[*node_id] {
+                Node::BlkEnd(block, _sub) => block,
+                _ => return Vec::new(),
+            },
+            None => return Vec::new(),
+        };
+        let mut states = Vec::new();
+        let mut add_merged_state = true;
+        if let Some(start_node_id) = self.blk_to_blk_start_node_map.get(&block.tid) {
+            add_merged_state = false;
+            for edge in graph.edges_directed(*start_node_id, Direction::Incoming) {
+                match (edge.weight(), self.get_node_value(edge.source())) {
+                    (Edge::Jump(jump, untaken_conditional), Some(NodeValue::Value(state))) => {
+                        if let Some(mut state) =
+                            self.get_context()
+                                .update_jump(state, jump, *untaken_conditional, block)
+                        {
+                            for def in block.term.defs.iter() {
+                                self.apply_def(&mut state, def);
+                            }
+                            states.push((Some(edge.id()), Cow::Owned(state)));
+                        }
+                    }
+                    _ => add_merged_state = true,
+                }
+            }
+        }
+        if add_merged_state || states.is_empty() {
+            if let Some(state) = self.get_state_at_jmp_tid(jmp_tid) {
+                states.push((None, Cow::Borrowed(state)));
+            }
+        }
+        states
+    }
+
+    /// Apply the effects of the `Def` term to the state.
+    fn apply_def(&self, state: &mut State, def: &Term<Def>) {
+        let runtime_memory_image = self.get_context().runtime_memory_image;
+        match &def.term {
+            Def::Store { address, value } => {
+                let _ = state.handle_store(address, value, runtime_memory_image);
+            }
+            Def::Assign { var, value } => {
+                if *var == self.get_context().project.stack_pointer_register {
+                    state.handle_stack_pointer_assign(&def.tid, var, value);
+                } else {
+                    state.handle_register_assign(var, value);
+                }
+            }
+            Def::Load { var, address } => {
+                let _ = state.handle_load(var, address, runtime_memory_image);
+            }
+        }
+    }
+
+    /// Get the abstract memory objects that the value of the expression may point to
+    /// right before the execution of the `Def` or `Jmp` with the given TID,
+    /// together with the possible offsets into each object.
+    ///
+    /// Returns an empty map if the value is known to be an absolute value
+    /// and `None` if nothing is known about the value or the state at the given TID.
+    pub fn get_points_to_set_at_tid(
+        &self,
+        tid: &Tid,
+        expression: &Expression,
+    ) -> Option<BTreeMap<AbstractIdentifier, ValueDomain>> {
+        self.get_state_at_tid(tid)?.get_points_to_set(expression)
+    }
+
+    /// Get the memory cell that the value of the expression points to
+    /// right before the execution of the `Def` or `Jmp` with the given TID,
+    /// given by the abstract memory object and the offset into it.
+    ///
+    /// Returns `None` if no state is known at the given TID,
+    /// if the value may point to more than one object
+    /// or if the offset into the object is not exactly known.
+    pub fn get_unique_memory_cell_at_tid(
+        &self,
+        tid: &Tid,
+        expression: &Expression,
+    ) -> Option<(AbstractIdentifier, i64)> {
+        self.get_state_at_tid(tid)?
+            .get_unique_memory_cell(expression)
+    }
+
+    /// Check whether the values of the two expressions may point to the same memory location
+    /// right before the execution of the `Def` or `Jmp` with the given TID.
+    ///
+    /// If no state is known at the given TID, the expressions are assumed to alias.
+    pub fn may_alias_at_tid(
+        &self,
+        tid: &Tid,
+        expression_1: &Expression,
+        expression_2: &Expression,
+    ) -> bool {
+        match self.get_state_at_tid(tid) {
+            Some(state) => state.may_alias(expression_1, expression_2),
+            None => true,
+        }
+    }
+
+    /// Get the finite set of absolute values that the expression may have
+    /// right before the execution of the `Def` or `Jmp` with the given TID.
+    ///
+    /// Returns an error if no state is known at the given TID,
+    /// if the value is not an absolute value
+    /// or if the value set contains more than `max_cardinality` values.
+    pub fn get_value_set_at_tid(
+        &self,
+        tid: &Tid,
+        expression: &Expression,
+        max_cardinality: u64,
+    ) -> Result<Vec<Bitvector>, Error> {
+        self.get_state_at_tid(tid)
+            .ok_or_else(|| anyhow!("No state known at {}", tid))?
+            .eval(expression)
+            .try_to_value_set(max_cardinality)
+    }
+
+    /// Get the finite set of possible target addresses of an indirect jump or call.
+    ///
+    /// Returns an error if the jump is not indirect or if the targets cannot be enumerated,
+    /// see [`PointerInference::get_value_set_at_tid`].
+    pub fn get_indirect_jump_targets(
+        &self,
+        jmp: &Term<Jmp>,
+        max_cardinality: u64,
+    ) -> Result<Vec<Bitvector>, Error> {
+        match &jmp.term {
+            Jmp::BranchInd(target) | Jmp::CallInd { target, .. } => {
+                self.get_value_set_at_tid(&jmp.tid, target, max_cardinality)
+            }
+            _ => Err(anyhow!("{} is not an indirect jump", jmp.tid)),
+        }
     }
 
     /// Add speculative entry points to the fixpoint algorithm state.
@@ -234,12 +784,12 @@ impl<'a> PointerInference<'a> {
                 start_block_to_sub_map.insert(&start_block.tid, sub);
             }
         }
-        let graph = self.computation.get_graph();
+        let graph = self.get_graph();
         let mut new_entry_points = Vec::new();
         for (node_id, node) in graph.node_references() {
             if let Node::BlkStart(block, sub) = node {
                 if start_block_to_sub_map.get(&block.tid) == Some(sub)
-                    && self.computation.get_node_value(node_id).is_none()
+                    && !self.has_value_in_any_context(node_id)
                     && (!only_cfg_roots
                         || graph
                             .neighbors_directed(node_id, Direction::Incoming)
@@ -255,16 +805,11 @@ impl<'a> PointerInference<'a> {
             new_entry_points.len()
         ));
         for entry in new_entry_points {
-            let sub_tid = start_block_to_sub_map
-                [&self.computation.get_graph()[entry].get_block().tid]
-                .tid
-                .clone();
+            let sub = start_block_to_sub_map[&self.computation.get_graph()[entry].get_block().tid];
+            let entry_state = self.get_context().get_entry_state(sub);
             self.computation.set_node_value(
                 entry,
-                super::interprocedural_fixpoint_generic::NodeValue::Value(State::new(
-                    &project.stack_pointer_register,
-                    sub_tid,
-                )),
+                super::interprocedural_fixpoint_generic::NodeValue::Value(entry_state),
             );
         }
     }
@@ -272,13 +817,13 @@ impl<'a> PointerInference<'a> {
     /// Print the number of blocks that have a state associated to them.
     /// Intended for debug purposes.
     fn count_blocks_with_state(&self) {
-        let graph = self.computation.get_graph();
+        let graph = self.get_graph();
         let mut stateful_blocks: i64 = 0;
         let mut all_blocks: i64 = 0;
         for (node_id, node) in graph.node_references() {
             if let Node::BlkStart(_block, _sub) = node {
                 all_blocks += 1;
-                if self.computation.get_node_value(node_id).is_some() {
+                if self.has_value_in_any_context(node_id) {
                     stateful_blocks += 1;
                 }
             }
@@ -402,7 +947,7 @@ pub fn extract_pi_analysis_results(
     analysis_results: &AnalysisResults,
     _analysis_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
-    let pi_anaylsis = analysis_results.pointer_inference.unwrap();
+    let pi_anaylsis = analysis_results.pointer_inference().unwrap();
     pi_anaylsis.collected_logs.clone()
 }
 
@@ -438,6 +983,34 @@ pub fn run<'a>(
     computation
 }
 
+/// Compute the pointer inference analysis while recording the states of all blocks of the function with the given name.
+/// Returns the recorded states rendered as a standalone HTML page
+/// or an error if no function with the given name exists.
+///
+/// Note that the format of the output is intended for debugging and subject to change.
+pub fn run_with_trace<'a>(
+    project: &'a Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    control_flow_graph: &'a Graph<'a>,
+    config: Config,
+    function_name: &str,
+) -> Result<String, Error> {
+    let logging_thread = LogThread::spawn(collect_all_logs);
+
+    let mut computation = PointerInference::new(
+        project,
+        runtime_memory_image,
+        control_flow_graph,
+        config,
+        logging_thread.get_msg_sender(),
+    );
+    computation.trace_function(function_name)?;
+    computation.compute_with_speculative_entry_points(project);
+    let _ = logging_thread.collect();
+
+    Ok(computation.render_trace_as_html())
+}
+
 /// This function is responsible for collecting logs and CWE warnings.
 /// For warnings with the same origin address only the last one is kept.
 /// This prevents duplicates but may suppress some log messages
@@ -461,7 +1034,7 @@ fn collect_all_logs(
             LogThreadMsg::Cwe(cwe_warning) => match &cwe_warning.addresses[..] {
                 [] => panic!("Unexpected CWE warning without origin address"),
                 [address, ..] => {
-                    collected_cwes.insert(address.clone(), cwe_warning);
+                    collected_cwes.insert(address.clone(), *cwe_warning);
                 }
             },
             LogThreadMsg::Terminate => break,
@@ -489,9 +1062,103 @@ mod tests {
             let config = Config {
                 allocation_symbols: vec!["malloc".to_string()],
                 deallocation_symbols: vec!["free".to_string()],
+                reallocation_symbols: vec!["realloc".to_string()],
+                uninitialized_allocation_symbols: vec!["malloc".to_string()],
+                allocation_size_parameters: BTreeMap::new(),
+                memory_pools: Vec::new(),
+                function_signatures: BTreeMap::new(),
+                return_value_intervals: BTreeMap::new(),
+                string_formatting_symbols: BTreeMap::new(),
+                calling_environments: Vec::new(),
+                environment_lookup_symbols: Vec::new(),
+                function_time_limit: None,
+                custom_domain: None,
+                merged_context_functions: Vec::new(),
+                separate_context_functions: Vec::new(),
             };
             let (log_sender, _) = crossbeam_channel::unbounded();
             PointerInference::new(project, mem_image, graph, config, log_sender)
         }
     }
+
+    #[test]
+    fn separate_calling_contexts() {
+        let block = |name: &str, jmp: Jmp| Term {
+            tid: Tid::new(name),
+            term: Blk {
+                defs: Vec::new(),
+                jmps: vec![Term {
+                    tid: Tid::new(format!("{}_jmp", name)),
+                    term: jmp,
+                }],
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        let call = |name: &str, return_: &str| {
+            block(
+                name,
+                Jmp::Call {
+                    target: Tid::new("callee"),
+                    return_: Some(Tid::new(return_)),
+                },
+            )
+        };
+        let mut callee = Sub::mock("callee");
+        callee.term.blocks = vec![block(
+            "callee_start",
+            Jmp::Return(Expression::Var(Variable::mock("RAX", 8u64))),
+        )];
+        let caller = |name: &str| {
+            let mut caller = Sub::mock(name);
+            caller.term.blocks = vec![
+                call(&format!("{}_call", name), &format!("{}_end", name)),
+                block(
+                    &format!("{}_end", name),
+                    Jmp::Return(Expression::Var(Variable::mock("RAX", 8u64))),
+                ),
+            ];
+            caller
+        };
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.subs = vec![callee, caller("main"), caller("other")];
+        project.program.term.entry_points = vec![Tid::new("main"), Tid::new("other")];
+        let mem_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(
+            &project.program,
+            std::collections::HashSet::new(),
+        );
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "allocation_symbols": [],
+            "deallocation_symbols": [],
+            "separate_context_functions": ["callee"]
+        }))
+        .unwrap();
+        let (log_sender, _) = crossbeam_channel::unbounded();
+        let mut pointer_inference =
+            PointerInference::new(&project, &mem_image, &graph, config, log_sender);
+        pointer_inference.compute();
+
+        // The copies of `callee` are only contained in the graph of the computation.
+        assert_eq!(
+            pointer_inference.get_graph().node_count(),
+            graph.node_count()
+        );
+        assert!(pointer_inference.computation.get_graph().node_count() > graph.node_count());
+        // Each copy of `callee` is analyzed for only one calling context.
+        let callee_start = pointer_inference.blk_to_blk_start_node_map[&Tid::new("callee_start")];
+        assert_eq!(pointer_inference.context_copies[&callee_start].len(), 2);
+        for copy in pointer_inference.context_copies[&callee_start].iter() {
+            let state = match pointer_inference.computation.get_node_value(*copy) {
+                Some(NodeValue::Value(state)) => state,
+                _ => panic!(),
+            };
+            assert_eq!(state.caller_stack_ids.len(), 1);
+        }
+        // The state at the start of `callee` is merged from both calling contexts.
+        let state = pointer_inference
+            .get_state_at_blk_start(&Tid::new("callee_start"))
+            .unwrap();
+        assert_eq!(state.caller_stack_ids.len(), 2);
+    }
 }
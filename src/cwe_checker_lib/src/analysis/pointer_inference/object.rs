@@ -2,10 +2,11 @@
 
 use super::{Data, ValueDomain};
 use crate::abstract_domain::*;
+use crate::analysis::string_abstraction::StringLengthBounds;
 use crate::prelude::*;
 use derive_more::Deref;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::DerefMut;
 use std::sync::Arc;
 
@@ -49,6 +50,17 @@ pub struct AbstractObjectInfo {
     type_: Option<ObjectType>,
     /// The actual content of the memory object
     memory: MemRegion<Data>,
+    /// The size of the memory object if known.
+    size: Option<ValueDomain>,
+    /// The bytes of the memory object that are initialized on all execution paths.
+    /// Only objects allocated by functions returning uninitialized memory (like `malloc`) start as uninitialized.
+    /// All other objects are treated as completely initialized.
+    initialization: InitializationDomain,
+    /// Bounds for the lengths of the C-strings starting at the given offsets,
+    /// as far as they are known from calls to string formatting functions.
+    /// Writes to the object remove the strings that they may overlap.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    string_lengths: BTreeMap<i64, StringLengthBounds>,
 }
 
 impl AbstractObjectInfo {
@@ -60,6 +72,9 @@ impl AbstractObjectInfo {
             state: Some(ObjectState::Alive),
             type_: Some(type_),
             memory: MemRegion::new(address_bytesize),
+            size: None,
+            initialization: InitializationDomain::new_completely_initialized(),
+            string_lengths: BTreeMap::new(),
         }
     }
 
@@ -76,6 +91,8 @@ impl AbstractObjectInfo {
         if let Data::Pointer(ref pointer) = value {
             self.pointer_targets.extend(pointer.ids().cloned());
         };
+        self.mark_as_initialized(offset, value.bytesize());
+        self.remove_overlapped_strings(offset, value.bytesize());
         if let Ok(concrete_offset) = offset.try_to_bitvec() {
             if self.is_unique {
                 self.memory.add(value, concrete_offset);
@@ -100,6 +117,8 @@ impl AbstractObjectInfo {
         if let Data::Pointer(ref pointer) = value {
             self.pointer_targets.extend(pointer.ids().cloned());
         };
+        self.mark_as_initialized(offset, value.bytesize());
+        self.remove_overlapped_strings(offset, value.bytesize());
         if let Ok(concrete_offset) = offset.try_to_bitvec() {
             let merged_value = self
                 .memory
@@ -114,6 +133,68 @@ impl AbstractObjectInfo {
         }
     }
 
+    /// Mark the bytes written by a write of the given size to the given offset as initialized.
+    /// If the offset is not exactly known, the whole object is marked as initialized.
+    ///
+    /// Writes to non-unique objects also mark the bytes as initialized,
+    /// since the written object cannot be distinguished from the other objects represented by it.
+    fn mark_as_initialized(&mut self, offset: &ValueDomain, bytesize: ByteSize) {
+        match offset.try_to_offset() {
+            Ok(start) => self
+                .initialization
+                .mark_initialized(start, start + u64::from(bytesize) as i64),
+            Err(_) => self.initialization.mark_completely_initialized(),
+        }
+    }
+
+    /// Remove the strings that a write of the given size to the given offset may overlap
+    /// (including their null terminators).
+    fn remove_overlapped_strings(&mut self, offset: &ValueDomain, bytesize: ByteSize) {
+        if self.string_lengths.is_empty() {
+            return;
+        }
+        match offset.try_to_offset_interval() {
+            Ok((start, end)) => {
+                let write_end = end.saturating_add(u64::from(bytesize) as i64);
+                self.string_lengths.retain(|string_start, bounds| {
+                    write_end <= *string_start
+                        || matches!(bounds.upper_bound, Some(length) if string_start.saturating_add(length as i64) < start)
+                });
+            }
+            Err(_) => self.string_lengths.clear(),
+        }
+    }
+
+    /// Record bounds for the length of the C-string starting at the given offset.
+    ///
+    /// If the object is not unique, the bounds are merged with the known bounds of a string at the same offset.
+    /// Without known bounds nothing is recorded, since the other represented objects may not contain a string there.
+    pub fn set_string_length(&mut self, offset: i64, bounds: StringLengthBounds) {
+        if self.is_unique {
+            self.string_lengths.insert(offset, bounds);
+        } else if let Some(old_bounds) = self.string_lengths.get_mut(&offset) {
+            *old_bounds = old_bounds.merge(&bounds);
+        }
+    }
+
+    /// Get the recorded bounds for the length of the C-string starting at the given offset.
+    pub fn get_string_length(&self, offset: i64) -> Option<StringLengthBounds> {
+        self.string_lengths.get(&offset).copied()
+    }
+
+    /// Mark all bytes of the object as uninitialized.
+    /// Used for objects allocated by functions returning uninitialized memory (like `malloc`).
+    pub fn mark_as_uninitialized(&mut self) {
+        self.initialization = InitializationDomain::new_uninitialized();
+    }
+
+    /// Return `true` if none of the bytes read by a read of the given size at the given offset
+    /// is initialized on all execution paths.
+    pub fn is_uninitialized(&self, offset: i64, bytesize: ByteSize) -> bool {
+        self.initialization
+            .is_uninitialized(offset, offset + u64::from(bytesize) as i64)
+    }
+
     /// Get all abstract IDs that the object may contain pointers to.
     /// This yields an overapproximation of possible pointer targets.
     pub fn get_referenced_ids_overapproximation(&self) -> &BTreeSet<AbstractIdentifier> {
@@ -182,15 +263,39 @@ impl AbstractObjectInfo {
         self.type_
     }
 
+    /// Get the size of the memory object if known.
+    pub fn get_size(&self) -> Option<&ValueDomain> {
+        self.size.as_ref()
+    }
+
+    /// If `self.is_unique==true`, set the size of the object. Else merge the new size with the old.
+    pub fn set_size(&mut self, size: ValueDomain) {
+        self.size = match &self.size {
+            Some(old_size) if !self.is_unique => Some(old_size.merge(&size)),
+            _ => Some(size),
+        };
+    }
+
     /// Invalidates all memory and adds the `additional_targets` to the pointer targets.
     /// Represents the effect of unknown write instructions to the object
     /// which may include writing pointers to targets from the `additional_targets` set to the object.
     pub fn assume_arbitrary_writes(&mut self, additional_targets: &BTreeSet<AbstractIdentifier>) {
         self.memory = MemRegion::new(self.memory.get_address_bytesize());
+        self.initialization.mark_completely_initialized();
+        self.string_lengths.clear();
         self.pointer_targets
             .extend(additional_targets.iter().cloned());
     }
 
+    /// Replace the content of the memory object with the content of the other memory object.
+    /// This models copying the whole object, e.g. when it is moved by `realloc`.
+    pub fn copy_content_from(&mut self, other: &AbstractObjectInfo) {
+        self.memory = other.memory.clone();
+        self.pointer_targets = other.pointer_targets.clone();
+        self.initialization = other.initialization.clone();
+        self.string_lengths = other.string_lengths.clone();
+    }
+
     /// Mark the memory object as freed.
     /// Returns an error if a possible double free is detected
     /// or the memory object may not be a heap object.
@@ -245,6 +350,19 @@ impl AbstractDomain for AbstractObjectInfo {
             state: same_or_none(&self.state, &other.state),
             type_: same_or_none(&self.type_, &other.type_),
             memory: self.memory.merge(&other.memory),
+            size: match (&self.size, &other.size) {
+                (Some(size), Some(other_size)) => Some(size.merge(other_size)),
+                _ => None,
+            },
+            initialization: self.initialization.merge(&other.initialization),
+            string_lengths: self
+                .string_lengths
+                .iter()
+                .filter_map(|(offset, bounds)| {
+                    let other_bounds = other.string_lengths.get(offset)?;
+                    Some((*offset, bounds.merge(other_bounds)))
+                })
+                .collect(),
         }
     }
 
@@ -293,13 +411,16 @@ fn same_or_none<T: Eq + Clone>(left: &Option<T>, right: &Option<T>) -> Option<T>
     }
 }
 
-/// An object is either a stack or a heap object.
+/// An object is either a stack frame, a heap object
+/// or memory provided by the operating system at program start.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum ObjectType {
     /// A stack object, i.e. the stack frame of a function.
     Stack,
     /// A memory object located on the heap.
     Heap,
+    /// Memory provided by the operating system at program start, e.g. the program arguments and environment strings.
+    ProgramEnvironment,
 }
 
 /// An object is either alive or dangling (because the memory was freed or a function return invalidated the stack frame).
@@ -322,6 +443,9 @@ mod tests {
             state: Some(ObjectState::Alive),
             type_: Some(ObjectType::Heap),
             memory: MemRegion::new(ByteSize::new(8)),
+            size: None,
+            initialization: InitializationDomain::new_completely_initialized(),
+            string_lengths: BTreeMap::new(),
         };
         AbstractObject(Arc::new(obj_info))
     }
@@ -380,6 +504,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn string_length_tracking() {
+        let mut object = new_abstract_object();
+        let bounds = StringLengthBounds {
+            lower_bound: 2,
+            upper_bound: Some(10),
+        };
+        object.set_string_length(0, bounds);
+        object.set_string_length(32, StringLengthBounds::exact(4));
+        // Writes behind the null terminator or before the string do not change it.
+        object.set_value(new_data(1), &bv(11)).unwrap();
+        object.set_value(new_data(1), &bv(24)).unwrap();
+        assert_eq!(object.get_string_length(0), Some(bounds));
+        assert_eq!(
+            object.get_string_length(32),
+            Some(StringLengthBounds::exact(4))
+        );
+        // Writes to the string or its null terminator remove it.
+        object.set_value(new_data(1), &bv(10)).unwrap();
+        assert_eq!(object.get_string_length(0), None);
+        assert_eq!(
+            object.get_string_length(32),
+            Some(StringLengthBounds::exact(4))
+        );
+        object.set_value(new_data(1), &bv(28)).unwrap();
+        assert_eq!(object.get_string_length(32), None);
+
+        object.set_string_length(0, StringLengthBounds::exact(4));
+        let mut other_object = new_abstract_object();
+        other_object.set_string_length(0, bounds);
+        other_object.set_string_length(16, bounds);
+        let merged_object = object.merge(&other_object);
+        assert_eq!(merged_object.get_string_length(0), Some(bounds));
+        assert_eq!(merged_object.get_string_length(16), None);
+    }
+
+    #[test]
+    fn initialization_tracking() {
+        let mut object = new_abstract_object();
+        assert!(!object.is_uninitialized(0, ByteSize::new(8)));
+        object.mark_as_uninitialized();
+        object.set_value(new_data(3), &bv(8)).unwrap();
+        assert!(object.is_uninitialized(0, ByteSize::new(8)));
+        assert!(!object.is_uninitialized(4, ByteSize::new(8)));
+        // Merging keeps only the bytes initialized in both objects.
+        let mut other_object = object.clone();
+        other_object.set_value(new_data(4), &bv(0)).unwrap();
+        assert!(!other_object.is_uninitialized(0, ByteSize::new(8)));
+        assert!(object
+            .merge(&other_object)
+            .is_uninitialized(0, ByteSize::new(8)));
+        // Writes to unknown offsets may initialize every byte.
+        object
+            .set_value(new_data(5), &ValueDomain::new_top(ByteSize::new(8)))
+            .unwrap();
+        assert!(!object.is_uninitialized(0, ByteSize::new(8)));
+    }
+
     #[test]
     fn replace_id() {
         use std::collections::BTreeMap;
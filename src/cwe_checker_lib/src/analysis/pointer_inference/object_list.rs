@@ -1,6 +1,7 @@
 use super::object::*;
 use super::{Data, ValueDomain};
 use crate::abstract_domain::*;
+use crate::analysis::string_abstraction::StringLengthBounds;
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
@@ -148,6 +149,11 @@ impl AbstractObjectList {
         }
     }
 
+    /// Check whether the object list contains an object with the given ID.
+    pub fn contains_object(&self, object_id: &AbstractIdentifier) -> bool {
+        self.objects.contains_key(object_id)
+    }
+
     /// Remove the memory object that `object_id` points to from the object list.
     pub fn remove_object(&mut self, object_id: &AbstractIdentifier) {
         self.objects.remove(object_id);
@@ -219,6 +225,30 @@ impl AbstractObjectList {
         self.objects.keys().cloned().collect()
     }
 
+    /// Copy the content of the memory object that `source_pointer` points to
+    /// into the memory object with the identifier `target_id`.
+    ///
+    /// The content is only copied if the pointer has a unique target and points exactly to offset zero,
+    /// since otherwise the start of the copied object is not known.
+    pub fn copy_object_content(
+        &mut self,
+        source_pointer: &PointerDomain<ValueDomain>,
+        target_id: &AbstractIdentifier,
+    ) {
+        if let [(source_id, offset)] = source_pointer.targets().iter().collect::<Vec<_>>()[..] {
+            if !matches!(offset.try_to_offset(), Ok(0)) {
+                return;
+            }
+            let source = self.objects.get(source_id).cloned();
+            if let (Some((source_object, source_offset)), Some((target_object, target_offset))) =
+                (source, self.objects.get_mut(target_id))
+            {
+                target_object.copy_content_from(&source_object);
+                *target_offset = source_offset;
+            }
+        }
+    }
+
     /// Mark a memory object as already freed (i.e. pointers to it are dangling).
     ///
     /// If the object cannot be identified uniquely, all possible targets are marked as having an unknown status.
@@ -296,6 +326,85 @@ impl AbstractObjectList {
         }
     }
 
+    /// Set the size of a memory object.
+    /// Does nothing if no object with the given ID is contained in the object list.
+    pub fn set_object_size(&mut self, object_id: &AbstractIdentifier, size: ValueDomain) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.set_size(size);
+        }
+    }
+
+    /// Return the size of a memory object if it is known.
+    pub fn get_object_size(&self, object_id: &AbstractIdentifier) -> Option<ValueDomain> {
+        self.objects
+            .get(object_id)
+            .and_then(|(object, _)| object.get_size().cloned())
+    }
+
+    /// Mark all bytes of a memory object as uninitialized.
+    /// Does nothing if no object with the given ID is contained in the object list.
+    pub fn mark_object_as_uninitialized(&mut self, object_id: &AbstractIdentifier) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.mark_as_uninitialized();
+        }
+    }
+
+    /// Return `true` if a read of the given size from the given address only reads bytes
+    /// that are uninitialized on all execution paths.
+    ///
+    /// Returns `false` if the address may point to more than one object
+    /// or if the offset into the object is not exactly known.
+    pub fn is_uninitialized_read(&self, address: &Data, size: ByteSize) -> bool {
+        let pointer = match address {
+            Data::Pointer(pointer) if pointer.targets().len() == 1 => pointer,
+            _ => return false,
+        };
+        let (id, pointer_offset) = pointer.targets().iter().next().unwrap();
+        match self.objects.get(id) {
+            Some((object, id_offset)) => {
+                match (pointer_offset.clone() + id_offset.clone()).try_to_offset() {
+                    Ok(offset) => object.is_uninitialized(offset, size),
+                    Err(_) => false,
+                }
+            }
+            None => false,
+        }
+    }
+
+    /// Record bounds for the length of the C-string that the given pointer points to.
+    ///
+    /// Nothing is recorded if the pointer may point to more than one object
+    /// or if the offset into the object is not exactly known.
+    pub fn set_string_length(&mut self, pointer: &Data, bounds: StringLengthBounds) {
+        let pointer = match pointer {
+            Data::Pointer(pointer) if pointer.targets().len() == 1 => pointer,
+            _ => return,
+        };
+        let (id, pointer_offset) = pointer.targets().iter().next().unwrap();
+        if let Some((object, id_offset)) = self.objects.get_mut(id) {
+            if let Ok(offset) = (pointer_offset.clone() + id_offset.clone()).try_to_offset() {
+                object.set_string_length(offset, bounds);
+            }
+        }
+    }
+
+    /// Get the recorded bounds for the length of the C-string that the given pointer points to.
+    ///
+    /// Returns `None` if the pointer may point to more than one object,
+    /// if the offset into the object is not exactly known or if no string length is recorded for the offset.
+    pub fn get_string_length(&self, pointer: &Data) -> Option<StringLengthBounds> {
+        let pointer = match pointer {
+            Data::Pointer(pointer) if pointer.targets().len() == 1 => pointer,
+            _ => return None,
+        };
+        let (id, pointer_offset) = pointer.targets().iter().next().unwrap();
+        let (object, id_offset) = self.objects.get(id)?;
+        let offset = (pointer_offset.clone() + id_offset.clone())
+            .try_to_offset()
+            .ok()?;
+        object.get_string_length(offset)
+    }
+
     // Return the object type of a memory object.
     // Returns an error if no object with the given ID is contained in the object list.
     pub fn get_object_type(
@@ -307,6 +416,32 @@ impl AbstractObjectList {
             None => Err(()),
         }
     }
+
+    /// Return the IDs of all heap objects that are alive on all execution paths,
+    /// i.e. that may not have been freed yet.
+    pub fn get_alive_heap_object_ids(&self) -> BTreeSet<AbstractIdentifier> {
+        self.objects
+            .keys()
+            .filter(|id| self.is_heap_object_in_state(id, ObjectState::Alive))
+            .cloned()
+            .collect()
+    }
+
+    /// Return `true` if the object with the given ID is a heap object
+    /// that is in the given state on all execution paths.
+    pub fn is_heap_object_in_state(
+        &self,
+        object_id: &AbstractIdentifier,
+        state: ObjectState,
+    ) -> bool {
+        match self.objects.get(object_id) {
+            Some((object, _)) => {
+                object.get_object_type() == Some(ObjectType::Heap)
+                    && object.get_state() == Some(state)
+            }
+            None => false,
+        }
+    }
 }
 
 impl AbstractDomain for AbstractObjectList {
@@ -526,7 +661,7 @@ mod tests {
             AbstractObjectList::from_stack_id(new_id("stack"), ByteSize::new(8));
         other_obj_list.add_abstract_object(
             new_id("heap_obj"),
-            bv(0).into(),
+            bv(0),
             ObjectType::Heap,
             ByteSize::new(8),
         );
@@ -0,0 +1,246 @@
+//! Inference of coarse types of heap objects from the accesses to them.
+//!
+//! For each heap object the sizes of all loads and stores through pointers to it are collected,
+//! together with the kinds of the stored values (pointers or absolute values).
+//! From this summary a coarse type of the object is inferred,
+//! e.g. byte buffers (usually strings) are only accessed one byte at a time
+//! while arrays of pointers are only accessed with pointer-sized accesses storing pointers.
+//! Checks can use the type to tailor their thresholds to the object,
+//! see [`HeapObjectType::overflow_tolerance`].
+//!
+//! Only accesses through pointers with the allocation site as their target are summarized.
+//! Accesses inside functions that receive a pointer to the object as a parameter are not attributed to the object.
+
+use super::{Data, PointerInference, ValueDomain};
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The coarse type of a heap object.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HeapObjectType {
+    /// A buffer that is only accessed one byte at a time, e.g. a string buffer.
+    ByteBuffer,
+    /// A struct containing pointers and other fields.
+    StructWithPointerFields,
+    /// An array of pointers, i.e. all accesses are pointer-sized and all stored values are pointers.
+    PointerArray,
+    /// The accesses to the object do not match any of the other types or the object is never accessed.
+    Unknown,
+}
+
+impl HeapObjectType {
+    /// The number of bytes by which accesses may exceed the bounds of an object of this type
+    /// before the access is reported as out-of-bounds.
+    ///
+    /// Off-by-one accesses to byte buffers are often caused by the terminating null byte of strings
+    /// and by imprecisions of the analysis in loops over the buffer,
+    /// so they are tolerated to reduce false positives.
+    /// For all other objects every out-of-bounds access should be reported.
+    pub fn overflow_tolerance(&self) -> u64 {
+        match self {
+            HeapObjectType::ByteBuffer => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for HeapObjectType {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HeapObjectType::ByteBuffer => write!(formatter, "byte_buffer"),
+            HeapObjectType::StructWithPointerFields => {
+                write!(formatter, "struct_with_pointer_fields")
+            }
+            HeapObjectType::PointerArray => write!(formatter, "pointer_array"),
+            HeapObjectType::Unknown => write!(formatter, "unknown"),
+        }
+    }
+}
+
+/// A summary of the accesses to a memory object.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct AccessSummary {
+    /// The sizes of all loads and stores accessing the object.
+    pub access_sizes: BTreeSet<ByteSize>,
+    /// `true` if a pointer-sized access at an offset not aligned to the pointer size was found.
+    pub has_unaligned_access: bool,
+    /// The number of stores of pointers into the object.
+    pub pointer_stores: u64,
+    /// The number of stores of absolute values into the object.
+    pub value_stores: u64,
+}
+
+impl AccessSummary {
+    /// Add an access of the given size at the given offset to the summary.
+    /// For stores the stored value has to be provided.
+    pub fn add_access(
+        &mut self,
+        offset: &ValueDomain,
+        size: ByteSize,
+        stored_value: Option<&Data>,
+        pointer_size: ByteSize,
+    ) {
+        self.access_sizes.insert(size);
+        if let Ok(offset) = offset.try_to_offset() {
+            if size == pointer_size && offset % u64::from(pointer_size) as i64 != 0 {
+                self.has_unaligned_access = true;
+            }
+        }
+        match stored_value {
+            Some(Data::Pointer(_)) => self.pointer_stores += 1,
+            Some(Data::Value(_)) => self.value_stores += 1,
+            Some(Data::Top(_)) | None => (),
+        }
+    }
+
+    /// Infer the coarse type of the object from the summary of its accesses.
+    pub fn infer_type(&self, pointer_size: ByteSize) -> HeapObjectType {
+        if self.access_sizes.is_empty() {
+            return HeapObjectType::Unknown;
+        }
+        let only_pointer_sized_accesses =
+            self.access_sizes.iter().all(|size| *size == pointer_size);
+        if self.pointer_stores > 0 {
+            if only_pointer_sized_accesses && self.value_stores == 0 && !self.has_unaligned_access {
+                HeapObjectType::PointerArray
+            } else {
+                HeapObjectType::StructWithPointerFields
+            }
+        } else if self.access_sizes.iter().all(|size| u64::from(*size) == 1) {
+            HeapObjectType::ByteBuffer
+        } else {
+            HeapObjectType::Unknown
+        }
+    }
+}
+
+impl<'a> PointerInference<'a> {
+    /// Summarize the accesses to heap objects in all blocks of the program.
+    pub fn summarize_heap_object_accesses(&self) -> BTreeMap<AbstractIdentifier, AccessSummary> {
+        let context = self.get_context();
+        let pointer_size = context.project.get_pointer_bytesize();
+        let mut summaries: BTreeMap<AbstractIdentifier, AccessSummary> = BTreeMap::new();
+        for node in self.blk_to_blk_start_node_map.values() {
+            let block = match self.get_graph()[*node] {
+                Node::BlkStart(block, _sub) => block,
+                _ => continue,
+            };
+            let mut state = match self.get_node_value(*node) {
+                Some(NodeValue::Value(state)) => state.clone(),
+                _ => continue,
+            };
+            for def in block.term.defs.iter() {
+                let (address, size, stored_value) = match &def.term {
+                    Def::Load { var, address } => (address, var.size, None),
+                    Def::Store { address, value } => {
+                        (address, value.bytesize(), Some(state.eval(value)))
+                    }
+                    Def::Assign { .. } => {
+                        self.apply_def(&mut state, def);
+                        continue;
+                    }
+                };
+                if let Data::Pointer(pointer) = state.eval(address) {
+                    for (id, offset) in pointer.targets() {
+                        if let Ok(Some(super::object::ObjectType::Heap)) =
+                            state.memory.get_object_type(id)
+                        {
+                            summaries.entry(id.clone()).or_default().add_access(
+                                offset,
+                                size,
+                                stored_value.as_ref(),
+                                pointer_size,
+                            );
+                        }
+                    }
+                }
+                self.apply_def(&mut state, def);
+            }
+        }
+        summaries
+    }
+
+    /// Get the inferred coarse type of the heap object with the given ID.
+    ///
+    /// The types of all heap objects are computed on the first call to this function.
+    /// Returns [`HeapObjectType::Unknown`] for objects without known accesses or other memory objects.
+    pub fn get_heap_object_type(&self, object_id: &AbstractIdentifier) -> HeapObjectType {
+        let pointer_size = self.get_context().project.get_pointer_bytesize();
+        self.heap_object_types
+            .get_or_init(|| {
+                self.summarize_heap_object_accesses()
+                    .into_iter()
+                    .map(|(id, summary)| (id, summary.infer_type(pointer_size)))
+                    .collect()
+            })
+            .get(object_id)
+            .cloned()
+            .unwrap_or(HeapObjectType::Unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractLocation, PointerDomain};
+
+    #[test]
+    fn type_inference() {
+        let pointer_size = ByteSize::new(8);
+        let offset = |value: i64| ValueDomain::from(Bitvector::from_i64(value));
+        let pointer: Data = PointerDomain::new(
+            AbstractIdentifier::new(
+                Tid::new("malloc_call"),
+                AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+            ),
+            offset(0),
+        )
+        .into();
+        let value = Data::Value(offset(42));
+
+        let mut pointer_array = AccessSummary::default();
+        pointer_array.add_access(&offset(0), pointer_size, Some(&pointer), pointer_size);
+        pointer_array.add_access(&offset(8), pointer_size, None, pointer_size);
+        assert_eq!(
+            pointer_array.infer_type(pointer_size),
+            HeapObjectType::PointerArray
+        );
+
+        let mut struct_with_pointers = pointer_array.clone();
+        struct_with_pointers.add_access(&offset(16), ByteSize::new(4), Some(&value), pointer_size);
+        assert_eq!(
+            struct_with_pointers.infer_type(pointer_size),
+            HeapObjectType::StructWithPointerFields
+        );
+        let mut unaligned = pointer_array.clone();
+        unaligned.add_access(&offset(4), pointer_size, None, pointer_size);
+        assert_eq!(
+            unaligned.infer_type(pointer_size),
+            HeapObjectType::StructWithPointerFields
+        );
+
+        let mut byte_buffer = AccessSummary::default();
+        byte_buffer.add_access(&offset(0), ByteSize::new(1), Some(&value), pointer_size);
+        byte_buffer.add_access(&offset(5), ByteSize::new(1), None, pointer_size);
+        assert_eq!(
+            byte_buffer.infer_type(pointer_size),
+            HeapObjectType::ByteBuffer
+        );
+        assert_eq!(byte_buffer.infer_type(pointer_size).overflow_tolerance(), 1);
+        byte_buffer.add_access(&offset(8), ByteSize::new(4), Some(&value), pointer_size);
+        assert_eq!(
+            byte_buffer.infer_type(pointer_size),
+            HeapObjectType::Unknown
+        );
+
+        assert_eq!(
+            AccessSummary::default().infer_type(pointer_size),
+            HeapObjectType::Unknown
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::utils::binary::RuntimeMemoryImage;
 
+use super::super::object::ObjectState;
 use super::*;
 
 impl State {
@@ -39,6 +40,86 @@ impl State {
         self.set_register(target, self.eval(expression))
     }
 
+    /// Evaluate the expression on the given state and write the result to the stack pointer register.
+    ///
+    /// If the assignment moves the stack pointer from a known offset in the current stack frame
+    /// by an unknown amount (as `alloca` calls and variable-length arrays do),
+    /// the allocated region is recorded in `self.stack_allocations` under the TID of the assignment.
+    /// The stack pointer itself stays a pointer into the stack frame,
+    /// so that stack parameters of later calls are still found relative to the stack frame.
+    /// Allocations are removed once the stack pointer is reset above their end.
+    pub fn handle_stack_pointer_assign(
+        &mut self,
+        def_tid: &Tid,
+        stack_register: &Variable,
+        expression: &Expression,
+    ) {
+        let new_value = self.eval(expression);
+        let old_offset = self.get_stack_offset(&self.get_register(stack_register));
+        let new_offset = self.get_stack_offset(&new_value);
+        if let (Some(old_offset), Some(new_offset)) = (old_offset, new_offset) {
+            match (old_offset.try_to_offset(), new_offset.try_to_offset()) {
+                (Ok(end), Err(_)) => {
+                    self.stack_allocations.insert(
+                        def_tid.clone(),
+                        StackAllocation {
+                            start: new_offset,
+                            end,
+                        },
+                    );
+                }
+                (_, Ok(new_offset)) => self
+                    .stack_allocations
+                    .retain(|_, allocation| allocation.end > new_offset),
+                _ => (),
+            }
+        }
+        self.set_register(stack_register, new_value);
+    }
+
+    /// If the value is a pointer with the current stack frame as its only target, return its offset.
+    fn get_stack_offset(&self, value: &Data) -> Option<ValueDomain> {
+        match value {
+            Data::Pointer(pointer) if pointer.targets().len() == 1 => {
+                pointer.targets().get(&self.stack_id).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the dynamic stack allocation containing the smallest possible value of the given stack frame offset
+    /// together with the TID of its allocation site.
+    /// If several allocations may contain the offset, the one ending first is returned.
+    pub fn get_stack_allocation(&self, offset: &ValueDomain) -> Option<(&Tid, &StackAllocation)> {
+        let (offset_start, _) = offset.try_to_offset_interval().ok()?;
+        self.stack_allocations
+            .iter()
+            .filter(|(_, allocation)| {
+                offset_start < allocation.end
+                    && match allocation.start.try_to_offset_interval() {
+                        Ok((allocation_start, _)) => offset_start >= allocation_start,
+                        Err(_) => true,
+                    }
+            })
+            .min_by_key(|(_, allocation)| allocation.end)
+    }
+
+    /// If `pointer` points into a dynamic stack allocation of the current stack frame,
+    /// return the TID of the allocation site
+    /// and the number of bytes between the pointer target and the end of the allocation.
+    ///
+    /// If the offset is not exactly known, the smallest possible distance is returned.
+    pub fn get_stack_space_until_allocation_end(&self, pointer: &Data) -> Option<(&Tid, u64)> {
+        let offset = self.get_stack_offset(pointer)?;
+        let (_, offset_upper_bound) = offset.try_to_offset_interval().ok()?;
+        let (tid, allocation) = self.get_stack_allocation(&offset)?;
+        if offset_upper_bound < allocation.end {
+            Some((tid, (allocation.end - offset_upper_bound) as u64))
+        } else {
+            None
+        }
+    }
+
     /// Store `value` at the given `address`.
     pub fn store_value(
         &mut self,
@@ -143,10 +224,38 @@ impl State {
                 }
             }
             Data::Top(_) => Ok(Data::new_top(size)),
-            Data::Pointer(_) => Ok(self.memory.get_value(&address, size)?),
+            Data::Pointer(ref pointer) => {
+                let value = self.memory.get_value(&address, size)?;
+                Ok(self.add_load_origins(value, pointer.ids()))
+            }
         }
     }
 
+    /// Tag a value loaded from memory with the alive heap objects among the given targets of the load address.
+    ///
+    /// Since pointers are not tagged, unknown values loaded from heap objects are assumed to be non-pointer values.
+    /// This allows checks to recognize values derived from the contents of objects that were freed later on.
+    fn add_load_origins<'a>(
+        &self,
+        value: Data,
+        targets: impl Iterator<Item = &'a AbstractIdentifier>,
+    ) -> Data {
+        let origins: BTreeSet<AbstractIdentifier> = targets
+            .filter(|id| self.memory.is_heap_object_in_state(id, ObjectState::Alive))
+            .cloned()
+            .collect();
+        if origins.is_empty() {
+            return value;
+        }
+        let mut tagged_value = match value {
+            Data::Top(size) => ValueDomain::new_top(size),
+            Data::Value(value) => value,
+            Data::Pointer(_) => return value,
+        };
+        tagged_value.add_origins(&origins);
+        Data::Value(tagged_value)
+    }
+
     /// Handle a load instruction by assigning the value loaded from the address given by the `address` expression to `var`.
     pub fn handle_load(
         &mut self,
@@ -247,6 +356,82 @@ impl State {
         }
     }
 
+    /// If `pointer` points into the current stack frame at a negative offset,
+    /// return the number of bytes between the pointer target and the base of the stack frame.
+    ///
+    /// Writing more bytes than this through the pointer overwrites memory outside of the current stack frame,
+    /// e.g. the return address on x86.
+    /// If the offset is not exactly known, the smallest possible distance is returned.
+    pub fn get_stack_space_until_frame_base(&self, pointer: &Data) -> Option<u64> {
+        if let Data::Pointer(pointer) = pointer {
+            if let [(id, offset)] = pointer.targets().iter().collect::<Vec<_>>()[..] {
+                if *id == self.stack_id {
+                    if let Ok((_, offset_upper_bound)) = offset.try_to_offset_interval() {
+                        if offset_upper_bound < 0 {
+                            return Some((-offset_upper_bound) as u64);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Get the abstract memory objects that the value of the expression may point to,
+    /// together with the possible offsets into each object.
+    ///
+    /// Returns an empty map if the value is known to be an absolute value (e.g. a global address)
+    /// and `None` if nothing is known about the value.
+    pub fn get_points_to_set(
+        &self,
+        expression: &Expression,
+    ) -> Option<BTreeMap<AbstractIdentifier, ValueDomain>> {
+        match self.eval(expression) {
+            Data::Pointer(pointer) => Some(pointer.targets().clone()),
+            Data::Value(_) => Some(BTreeMap::new()),
+            Data::Top(_) => None,
+        }
+    }
+
+    /// Get the memory cell that the value of the expression points to,
+    /// given by the abstract memory object and the offset into it.
+    ///
+    /// Returns `None` if the value may point to more than one object
+    /// or if the offset into the object is not exactly known.
+    pub fn get_unique_memory_cell(
+        &self,
+        expression: &Expression,
+    ) -> Option<(AbstractIdentifier, i64)> {
+        match self.eval(expression) {
+            Data::Pointer(pointer) => match pointer.targets().iter().collect::<Vec<_>>()[..] {
+                [(id, offset)] => Some((id.clone(), offset.try_to_offset().ok()?)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Check whether the values of the two expressions may be equal,
+    /// i.e. whether they may point to the same memory location.
+    ///
+    /// Pointers to memory objects never alias absolute values,
+    /// since the pointer inference analysis represents global memory by absolute addresses.
+    /// If nothing is known about one of the values, they may alias.
+    pub fn may_alias(&self, expression_1: &Expression, expression_2: &Expression) -> bool {
+        match (self.eval(expression_1), self.eval(expression_2)) {
+            (Data::Pointer(pointer_1), Data::Pointer(pointer_2)) => {
+                pointer_1.targets().iter().any(|(id, offset_1)| {
+                    matches!(pointer_2.targets().get(id), Some(offset_2) if intervals_may_intersect(offset_1, offset_2))
+                })
+            }
+            (Data::Value(value_1), Data::Value(value_2)) => {
+                intervals_may_intersect(&value_1, &value_2)
+            }
+            (Data::Pointer(_), Data::Value(_)) | (Data::Value(_), Data::Pointer(_)) => false,
+            (Data::Top(_), _) | (_, Data::Top(_)) => true,
+        }
+    }
+
     /// Check if an expression contains a use-after-free
     pub fn contains_access_of_dangling_memory(&self, def: &Def) -> bool {
         match def {
@@ -280,3 +465,15 @@ impl State {
         None
     }
 }
+
+/// Check whether the two values may be equal.
+/// Returns `true` if one of the values is not a bounded interval.
+fn intervals_may_intersect(value_1: &ValueDomain, value_2: &ValueDomain) -> bool {
+    match (
+        value_1.try_to_offset_interval(),
+        value_2.try_to_offset_interval(),
+    ) {
+        (Ok((start_1, end_1)), Ok((start_2, end_2))) => start_1 <= end_2 && start_2 <= end_1,
+        _ => true,
+    }
+}
@@ -35,6 +35,23 @@ pub struct State {
     /// Note that IDs that the callee should not have access to are not included here.
     /// For these IDs the caller can assume that the contents of the corresponding memory object were not accessed or modified by the call.
     pub ids_known_to_caller: BTreeSet<AbstractIdentifier>,
+    /// The dynamic allocations in the current stack frame, e.g. by `alloca` or for variable-length arrays,
+    /// indexed by the TID of the stack pointer assignment allocating them.
+    pub stack_allocations: BTreeMap<Tid, StackAllocation>,
+}
+
+/// A dynamically sized region of the current stack frame, e.g. allocated by `alloca` or for a variable-length array.
+///
+/// The stack pointer stays a pointer into the stack frame after the allocation,
+/// so that the bounds of the allocation are given as offsets relative to the stack frame base.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StackAllocation {
+    /// The possible offsets of the start of the allocation,
+    /// i.e. the values of the stack pointer after the allocation.
+    pub start: ValueDomain,
+    /// The offset of the end of the allocation,
+    /// i.e. the value of the stack pointer before the allocation.
+    pub end: i64,
 }
 
 impl State {
@@ -60,6 +77,7 @@ impl State {
             stack_id,
             caller_stack_ids: BTreeSet::new(),
             ids_known_to_caller: BTreeSet::new(),
+            stack_allocations: BTreeMap::new(),
         }
     }
 
@@ -326,6 +344,19 @@ impl State {
         }
     }
 
+    /// Remove all knowledge about the contents of the parameter registers of the calling convention
+    /// that are not contained in the given parameters of a function signature.
+    pub fn remove_unused_parameter_register(
+        &mut self,
+        cconv: &CallingConvention,
+        parameters: &BTreeSet<String>,
+    ) {
+        self.register.retain(|register, _| {
+            !cconv.parameter_register.contains(&register.name)
+                || parameters.contains(&register.name)
+        });
+    }
+
     /// Try to restrict the input variables of `expression` on `self`
     /// so that `expression` only evaluates to values represented by the given `result`.
     ///
@@ -660,6 +691,21 @@ impl AbstractDomain for State {
             }
         }
         let merged_memory_objects = self.memory.merge(&other.memory);
+        // Allocations whose end differs between the states have no common bounds.
+        let merged_stack_allocations = self
+            .stack_allocations
+            .iter()
+            .filter_map(|(tid, allocation)| match other.stack_allocations.get(tid) {
+                Some(other_allocation) if allocation.end == other_allocation.end => Some((
+                    tid.clone(),
+                    StackAllocation {
+                        start: allocation.start.merge(&other_allocation.start),
+                        end: allocation.end,
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
         State {
             register: merged_register,
             memory: merged_memory_objects,
@@ -674,6 +720,7 @@ impl AbstractDomain for State {
                 .union(&other.ids_known_to_caller)
                 .cloned()
                 .collect(),
+            stack_allocations: merged_stack_allocations,
         }
     }
 
@@ -719,6 +766,25 @@ impl State {
                     .collect(),
             ),
         );
+        if !self.stack_allocations.is_empty() {
+            state_map.insert(
+                "stack_allocations".into(),
+                Value::Object(
+                    self.stack_allocations
+                        .iter()
+                        .map(|(tid, allocation)| {
+                            (
+                                format!("{}", tid),
+                                Value::String(format!(
+                                    "[{}, {}]",
+                                    allocation.start, allocation.end
+                                )),
+                            )
+                        })
+                        .collect(),
+                ),
+            );
+        }
 
         Value::Object(state_map)
     }
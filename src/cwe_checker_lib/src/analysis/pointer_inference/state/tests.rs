@@ -473,6 +473,73 @@ fn global_mem_access() {
         .is_err());
 }
 
+#[test]
+fn points_to_set_and_may_alias() {
+    let mut state = State::new(&register("RSP"), Tid::new("func_tid"));
+    let stack_id = new_id("func_tid", "RSP");
+    let heap_id = new_id("heap_obj", "RAX");
+    state.set_register(
+        &register("RAX"),
+        Data::Pointer(PointerDomain::new(heap_id.clone(), bv(0))),
+    );
+    state.set_register(&register("RBX"), Data::Top(ByteSize::new(8)));
+
+    let points_to = state.get_points_to_set(&reg_sub("RSP", 8)).unwrap();
+    assert_eq!(points_to.len(), 1);
+    assert_eq!(points_to[&stack_id], bv(-8));
+    assert_eq!(
+        state.get_points_to_set(&Expression::const_from_i64(0x2000)),
+        Some(BTreeMap::new())
+    );
+    assert_eq!(
+        state.get_points_to_set(&Expression::Var(register("RBX"))),
+        None
+    );
+
+    assert_eq!(
+        state.get_unique_memory_cell(&reg_sub("RSP", 8)),
+        Some((stack_id.clone(), -8))
+    );
+    assert_eq!(
+        state.get_unique_memory_cell(&Expression::const_from_i64(0x2000)),
+        None
+    );
+    state.set_register(
+        &register("RCX"),
+        Data::Pointer(
+            PointerDomain::new(heap_id.clone(), bv(0))
+                .merge(&PointerDomain::new(stack_id.clone(), bv(-16))),
+        ),
+    );
+    // Pointers to more than one object do not point to a unique memory cell.
+    assert_eq!(
+        state.get_unique_memory_cell(&Expression::Var(register("RCX"))),
+        None
+    );
+    state.set_register(
+        &register("RCX"),
+        Data::Pointer(PointerDomain::new(
+            heap_id.clone(),
+            IntervalDomain::new(Bitvector::from_i64(0), Bitvector::from_i64(8)).into(),
+        )),
+    );
+    // Neither do pointers with inexact offsets.
+    assert_eq!(
+        state.get_unique_memory_cell(&Expression::Var(register("RCX"))),
+        None
+    );
+
+    assert!(state.may_alias(&reg_sub("RSP", 8), &reg_add("RSP", -8)));
+    assert!(!state.may_alias(&reg_sub("RSP", 8), &reg_sub("RSP", 16)));
+    assert!(!state.may_alias(&reg_sub("RSP", 8), &reg_add("RAX", -8)));
+    assert!(!state.may_alias(&reg_add("RAX", 0), &Expression::const_from_i64(0x2000)));
+    assert!(state.may_alias(
+        &Expression::const_from_i64(0x2000),
+        &Expression::const_from_i64(0x2000)
+    ));
+    assert!(state.may_alias(&Expression::Var(register("RBX")), &reg_add("RAX", 0)));
+}
+
 /// Test expression specialization except for binary operations.
 #[test]
 fn specialize_by_expression_results() {
@@ -988,3 +1055,57 @@ fn specialize_by_unsigned_comparison_op() {
         IntervalDomain::mock_with_bounds(Some(-19), -5, -1, None).into()
     );
 }
+
+#[test]
+fn dynamic_stack_allocation() {
+    let mut state = State::new(&register("RSP"), Tid::new("time0"));
+    state.handle_stack_pointer_assign(&Tid::new("sub_rsp"), &register("RSP"), &reg_sub("RSP", 16));
+    let frame_pointer = state.get_register(&register("RSP"));
+    state.set_register(&register("RBP"), frame_pointer.clone());
+    let size = ValueDomain::new(Bitvector::from_i64(16), Bitvector::from_i64(64));
+    state.set_register(&register("RAX"), Data::Value(size));
+    let allocation = Expression::BinOp {
+        op: BinOpType::IntSub,
+        lhs: Box::new(Expression::Var(register("RSP"))),
+        rhs: Box::new(Expression::Var(register("RAX"))),
+    };
+    state.handle_register_assign(&register("RCX"), &allocation);
+    state.handle_stack_pointer_assign(
+        &Tid::new("alloca"),
+        &register("RSP"),
+        &Expression::Var(register("RCX")),
+    );
+
+    // The stack pointer stays a pointer into the stack frame.
+    let allocation_start = ValueDomain::new(Bitvector::from_i64(-80), Bitvector::from_i64(-32));
+    let allocation_pointer = Data::Pointer(PointerDomain::new(
+        new_id("time0", "RSP"),
+        allocation_start.clone(),
+    ));
+    assert_eq!(state.get_register(&register("RSP")), allocation_pointer);
+    assert_eq!(state.get_register(&register("RCX")), allocation_pointer);
+    assert_eq!(state.memory.get_num_objects(), 1);
+    assert_eq!(
+        state.stack_allocations.get(&Tid::new("alloca")),
+        Some(&StackAllocation {
+            start: allocation_start,
+            end: -16,
+        })
+    );
+    assert_eq!(
+        state.get_stack_space_until_allocation_end(&allocation_pointer),
+        Some((&Tid::new("alloca"), 16))
+    );
+    assert_eq!(
+        state.get_stack_space_until_allocation_end(&frame_pointer),
+        None
+    );
+
+    // Resetting the stack pointer frees the allocation.
+    state.handle_stack_pointer_assign(
+        &Tid::new("leave"),
+        &register("RSP"),
+        &Expression::Var(register("RBP")),
+    );
+    assert!(state.stack_allocations.is_empty());
+}
@@ -0,0 +1,533 @@
+//! A generic engine for finding resources that are still acquired
+//! when a function returns without keeping a reference to them.
+//!
+//! The engine is shared by checks for different kinds of resources,
+//! e.g. heap memory ([CWE-401](crate::checkers::cwe_401)),
+//! file descriptors and handles ([CWE-775](crate::checkers::cwe_775))
+//! or locks ([CWE-667](crate::checkers::cwe_667)).
+//! Each check provides a [`ResourceTracker`] that computes which resources leak in a given state.
+//! The engine provides the states at all function exits, separately for each path into the block of the exit,
+//! and removes resources whose acquisition failed on the path.
+//! To this end the path is followed backwards across blocks with a unique predecessor
+//! and the conditional jumps on it are resolved to checks of values against constants (e.g. `fd < 0` or `ptr == NULL`).
+//!
+//! Each resource is only reported once per function and acquisition site.
+
+use crate::intermediate_representation::*;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use std::collections::{BTreeSet, HashMap};
+
+/// The maximal number of jumps that are followed backwards from a function exit to find failure checks.
+const MAX_FAILURE_CHECK_PATH_LENGTH: usize = 8;
+
+/// An intraprocedural jump from the end of the source block to the start of another block.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PathEdge<'a> {
+    /// The block containing the jump.
+    pub source: &'a Term<Blk>,
+    /// The jump.
+    pub jump: &'a Term<Jmp>,
+    /// The conditional jump of the source block that was not taken, if the jump is the fallthrough of it.
+    pub untaken_conditional: Option<&'a Term<Jmp>>,
+}
+
+/// A comparison of a value with a constant that has a known result on a path.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValueCheck<'a> {
+    /// The compared value.
+    pub value: Expression,
+    /// The term right before which the value has to be evaluated.
+    pub tid: &'a Tid,
+    /// The comparison operation, with the value as its left operand.
+    /// One of `IntEqual`, `IntLess`, `IntLessEqual`, `IntSLess` and `IntSLessEqual`.
+    pub op: BinOpType,
+    /// The constant that the value is compared with.
+    pub constant: Bitvector,
+    /// Whether the comparison holds on the path.
+    pub holds: bool,
+}
+
+impl<'a> ValueCheck<'a> {
+    /// Return whether the check shows that the value is zero.
+    pub fn is_zero_check(&self) -> bool {
+        self.op == BinOpType::IntEqual && self.holds && self.constant.is_zero()
+    }
+
+    /// Return whether the check shows that the value is not zero,
+    /// e.g. because it is negative or because it is checked to be unequal to zero.
+    pub fn is_nonzero_check(&self) -> bool {
+        (self.op == BinOpType::IntEqual && !self.holds && self.constant.is_zero())
+            || self.is_negative_check()
+    }
+
+    /// Return whether the check shows that the value is negative when interpreted as a signed integer.
+    pub fn is_negative_check(&self) -> bool {
+        let constant = match self.constant.try_to_i64() {
+            Ok(constant) => constant,
+            Err(_) => return false,
+        };
+        match (self.op, self.holds) {
+            (BinOpType::IntEqual, true) => constant < 0,
+            (BinOpType::IntSLess, true) => constant <= 0,
+            (BinOpType::IntSLessEqual, true) => constant < 0,
+            _ => false,
+        }
+    }
+}
+
+/// The interface between the engine and the checks for specific kinds of resources.
+pub trait ResourceTracker<'a> {
+    /// The identifier of a tracked resource.
+    type Resource: Ord + Clone;
+    /// The state of the tracked resources at a point of a function.
+    type State;
+
+    /// Get the states right before the return instruction,
+    /// separately for each jump into the block of the return instruction if possible.
+    /// States for which the path into the block is unknown are returned without an edge.
+    fn get_states_at_return(
+        &self,
+        sub: &'a Term<Sub>,
+        return_jmp: &'a Term<Jmp>,
+    ) -> Vec<(Option<PathEdge<'a>>, Self::State)>;
+
+    /// Get the resources that are still acquired but no longer referenced when returning with the given state.
+    fn get_leaked_resources(&self, state: &Self::State) -> BTreeSet<Self::Resource>;
+
+    /// Get the resources whose acquisition failed if the check holds,
+    /// e.g. because the check shows that the returned handle is invalid.
+    fn get_failed_resources(&self, check: &ValueCheck) -> BTreeSet<Self::Resource>;
+
+    /// Get the TID of the callsite that acquired the resource.
+    fn get_acquisition_site(&self, resource: &Self::Resource) -> Tid;
+}
+
+/// A resource leaking at a return instruction of a function.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Leak<'a> {
+    /// The function.
+    pub sub: &'a Term<Sub>,
+    /// The return instruction at which the resource leaks.
+    pub return_jmp: &'a Term<Jmp>,
+    /// The callsite that acquired the resource.
+    pub acquisition_site: Tid,
+}
+
+/// Find the resources leaking at the return instructions of all functions
+/// except the given program exit functions (e.g. `main`),
+/// since the operating system releases the resources of the program after it terminates.
+pub fn find_leaks<'a, T: ResourceTracker<'a>>(
+    project: &'a Project,
+    tracker: &T,
+    program_exit_functions: &[String],
+) -> Vec<Leak<'a>> {
+    let mut leaks = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        if program_exit_functions.contains(&sub.term.name) {
+            continue;
+        }
+        let incoming_edges = get_incoming_edges(sub);
+        let mut reported_acquisition_sites = BTreeSet::new();
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if !matches!(jmp.term, Jmp::Return(_)) {
+                    continue;
+                }
+                for (edge, state) in tracker.get_states_at_return(sub, jmp) {
+                    let failed_resources = edge
+                        .map(|edge| get_failed_resources_on_path(tracker, &incoming_edges, edge))
+                        .unwrap_or_default();
+                    for resource in tracker.get_leaked_resources(&state) {
+                        if failed_resources.contains(&resource) {
+                            continue;
+                        }
+                        let acquisition_site = tracker.get_acquisition_site(&resource);
+                        if reported_acquisition_sites.insert(acquisition_site.clone()) {
+                            leaks.push(Leak {
+                                sub,
+                                return_jmp: jmp,
+                                acquisition_site,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    leaks
+}
+
+/// Map the blocks of the function to the jumps leading to their starts.
+/// Returns from calls are represented by `None`, since they do not correspond to jumps.
+pub fn get_incoming_edges<'a>(sub: &'a Term<Sub>) -> HashMap<&'a Tid, Vec<Option<PathEdge<'a>>>> {
+    let mut incoming_edges: HashMap<&Tid, Vec<Option<PathEdge>>> = HashMap::new();
+    for block in sub.term.blocks.iter() {
+        let conditional = block
+            .term
+            .jmps
+            .iter()
+            .find(|jmp| matches!(jmp.term, Jmp::CBranch { .. }));
+        for jmp in block.term.jmps.iter() {
+            let edge = match &jmp.term {
+                Jmp::Branch(target) => (
+                    target,
+                    Some(PathEdge {
+                        source: block,
+                        jump: jmp,
+                        untaken_conditional: conditional,
+                    }),
+                ),
+                Jmp::CBranch { target, .. } => (
+                    target,
+                    Some(PathEdge {
+                        source: block,
+                        jump: jmp,
+                        untaken_conditional: None,
+                    }),
+                ),
+                Jmp::Call {
+                    return_: Some(target),
+                    ..
+                }
+                | Jmp::CallInd {
+                    return_: Some(target),
+                    ..
+                }
+                | Jmp::CallOther {
+                    return_: Some(target),
+                    ..
+                } => (target, None),
+                _ => continue,
+            };
+            incoming_edges.entry(edge.0).or_default().push(edge.1);
+        }
+    }
+    // Keep only edges to blocks of the function.
+    let intraprocedural_targets: BTreeSet<&Tid> = sub
+        .term
+        .blocks
+        .iter()
+        .flat_map(|block| get_intraprocedural_jump_targets(block, sub))
+        .collect();
+    incoming_edges.retain(|target, _| intraprocedural_targets.contains(target));
+    incoming_edges
+}
+
+/// Collect the resources whose acquisition failed on the path ending with the given edge.
+///
+/// The path is followed backwards as long as its blocks are only reached through a unique jump.
+fn get_failed_resources_on_path<'a, T: ResourceTracker<'a>>(
+    tracker: &T,
+    incoming_edges: &HashMap<&Tid, Vec<Option<PathEdge<'a>>>>,
+    mut edge: PathEdge<'a>,
+) -> BTreeSet<T::Resource> {
+    let mut failed_resources = BTreeSet::new();
+    for _ in 0..MAX_FAILURE_CHECK_PATH_LENGTH {
+        if let Some(check) = get_value_check(edge.source, edge.jump, edge.untaken_conditional) {
+            failed_resources.append(&mut tracker.get_failed_resources(&check));
+        }
+        edge = match incoming_edges.get(&edge.source.tid).map(Vec::as_slice) {
+            Some([Some(incoming_edge)]) => *incoming_edge,
+            _ => break,
+        };
+    }
+    failed_resources
+}
+
+/// If the jump given by the jump and the untaken conditional jump is only taken
+/// if some value compares to a constant in a certain way, return the corresponding check.
+///
+/// The condition is followed backwards through the `Def` terms of the block across copies and negations to the comparison.
+/// The compared value is followed further backwards across copies and `TEST`-like self-conjunctions.
+/// Comparisons of differences `x - c` with zero are interpreted as comparisons of `x` with `c`.
+pub fn get_value_check<'a>(
+    block: &'a Term<Blk>,
+    jump: &Term<Jmp>,
+    untaken_conditional: Option<&Term<Jmp>>,
+) -> Option<ValueCheck<'a>> {
+    let (mut condition, mut condition_holds) = match (&jump.term, untaken_conditional) {
+        (Jmp::CBranch { condition, .. }, _) => (condition.clone(), true),
+        (
+            _,
+            Some(Term {
+                term: Jmp::CBranch { condition, .. },
+                ..
+            }),
+        ) => (condition.clone(), false),
+        _ => return None,
+    };
+    let mut defs = block.term.defs.iter().rev();
+    let mut check = loop {
+        let def = defs.next()?;
+        let value = match &def.term {
+            Def::Assign { var, value } if condition == Expression::Var(var.clone()) => value,
+            _ => continue,
+        };
+        match value {
+            Expression::UnOp {
+                op: UnOpType::BoolNegate,
+                arg,
+            } => {
+                condition = (**arg).clone();
+                condition_holds = !condition_holds;
+            }
+            Expression::Var(_) => condition = value.clone(),
+            Expression::BinOp { op, lhs, rhs } => {
+                let (op, holds) = match op {
+                    BinOpType::IntNotEqual => (BinOpType::IntEqual, !condition_holds),
+                    BinOpType::IntEqual
+                    | BinOpType::IntLess
+                    | BinOpType::IntLessEqual
+                    | BinOpType::IntSLess
+                    | BinOpType::IntSLessEqual => (*op, condition_holds),
+                    _ => return None,
+                };
+                let (value, constant) = match (lhs.as_ref(), rhs.as_ref()) {
+                    (value, Expression::Const(constant)) => (value, constant),
+                    (Expression::Const(constant), value) if op == BinOpType::IntEqual => {
+                        (value, constant)
+                    }
+                    _ => return None,
+                };
+                break ValueCheck {
+                    value: value.clone(),
+                    tid: &def.tid,
+                    op,
+                    constant: constant.clone(),
+                    holds,
+                };
+            }
+            _ => return None,
+        }
+    };
+    for def in defs {
+        let compared_var = match &check.value {
+            Expression::Var(var) => var,
+            _ => break,
+        };
+        if let Def::Assign { var, value } = &def.term {
+            if var == compared_var {
+                check.value = match value {
+                    Expression::Var(_) => value.clone(),
+                    Expression::BinOp {
+                        op: BinOpType::IntAnd,
+                        lhs,
+                        rhs,
+                    } if lhs == rhs => (**lhs).clone(),
+                    Expression::BinOp {
+                        op: BinOpType::IntSub,
+                        lhs,
+                        rhs,
+                    } if check.op == BinOpType::IntEqual && check.constant.is_zero() => {
+                        match rhs.as_ref() {
+                            Expression::Const(constant) => {
+                                check.constant = constant.clone();
+                                (**lhs).clone()
+                            }
+                            _ => break,
+                        }
+                    }
+                    _ => break,
+                };
+                check.tid = &def.tid;
+            }
+        }
+    }
+    Some(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(tid: &str, var: &Variable, op: BinOpType, lhs: Expression, rhs: i64) -> Term<Def> {
+        Def::assign(
+            tid,
+            var.clone(),
+            Expression::BinOp {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(Expression::Const(Bitvector::from_i64(rhs))),
+            },
+        )
+    }
+
+    fn conditional_jump(condition: &Variable) -> Term<Jmp> {
+        Term {
+            tid: Tid::new("jcc"),
+            term: Jmp::CBranch {
+                target: Tid::new("next"),
+                condition: Expression::Var(condition.clone()),
+            },
+        }
+    }
+
+    #[test]
+    fn null_checks() {
+        let rax = Variable::mock("RAX", 8);
+        let zf = Variable::mock("ZF", 1);
+        let negated = Variable::mock("$U1", 1);
+        let test_result = Variable::mock("$U2", 8);
+        let block = Blk::mock_with_jmps(
+            "block",
+            vec![
+                Def::assign(
+                    "test",
+                    test_result.clone(),
+                    Expression::BinOp {
+                        op: BinOpType::IntAnd,
+                        lhs: Box::new(Expression::Var(rax.clone())),
+                        rhs: Box::new(Expression::Var(rax.clone())),
+                    },
+                ),
+                compare(
+                    "compare",
+                    &zf,
+                    BinOpType::IntEqual,
+                    Expression::Var(test_result),
+                    0,
+                ),
+                Def::assign(
+                    "negate",
+                    negated.clone(),
+                    Expression::UnOp {
+                        op: UnOpType::BoolNegate,
+                        arg: Box::new(Expression::Var(zf)),
+                    },
+                ),
+            ],
+            Vec::new(),
+        );
+        // Jump if the pointer is not NULL.
+        let jump_if_not_null = conditional_jump(&negated);
+        let fallthrough = Term {
+            tid: Tid::new("fallthrough"),
+            term: Jmp::Branch(Tid::new("error")),
+        };
+        let check = get_value_check(&block, &fallthrough, Some(&jump_if_not_null)).unwrap();
+        assert_eq!(check.value, Expression::Var(rax));
+        assert_eq!(check.tid, &Tid::new("test"));
+        assert!(check.is_zero_check());
+        assert!(!check.is_nonzero_check());
+        let check = get_value_check(&block, &jump_if_not_null, None).unwrap();
+        assert!(!check.is_zero_check());
+        assert!(check.is_nonzero_check());
+    }
+
+    #[test]
+    fn negative_checks() {
+        let rax = Variable::mock("RAX", 8);
+        let sf = Variable::mock("SF", 1);
+        let zf = Variable::mock("ZF", 1);
+        let difference = Variable::mock("$U1", 8);
+        // Jump if the descriptor is negative.
+        let block = Blk::mock_with_jmps(
+            "block",
+            vec![compare(
+                "sign",
+                &sf,
+                BinOpType::IntSLess,
+                Expression::Var(rax.clone()),
+                0,
+            )],
+            Vec::new(),
+        );
+        let check = get_value_check(&block, &conditional_jump(&sf), None).unwrap();
+        assert!(check.is_negative_check());
+        assert!(!check.is_zero_check());
+        // Jump if the descriptor equals -1.
+        let block = Blk::mock_with_jmps(
+            "block",
+            vec![
+                Def::assign(
+                    "subtract",
+                    difference.clone(),
+                    Expression::Var(rax.clone()).plus_const(1),
+                ),
+                compare(
+                    "compare",
+                    &zf,
+                    BinOpType::IntEqual,
+                    Expression::Var(difference.clone()),
+                    0,
+                ),
+            ],
+            Vec::new(),
+        );
+        // `x + 1 == 0` is not recognized, only `x - c == 0`.
+        let check = get_value_check(&block, &conditional_jump(&zf), None).unwrap();
+        assert!(!check.is_negative_check());
+        let block = Blk::mock_with_jmps(
+            "block",
+            vec![
+                Def::assign(
+                    "subtract",
+                    difference.clone(),
+                    Expression::BinOp {
+                        op: BinOpType::IntSub,
+                        lhs: Box::new(Expression::Var(rax.clone())),
+                        rhs: Box::new(Expression::Const(Bitvector::from_i64(-1))),
+                    },
+                ),
+                compare(
+                    "compare",
+                    &zf,
+                    BinOpType::IntEqual,
+                    Expression::Var(difference),
+                    0,
+                ),
+            ],
+            Vec::new(),
+        );
+        let check = get_value_check(&block, &conditional_jump(&zf), None).unwrap();
+        assert_eq!(check.value, Expression::Var(rax));
+        assert!(check.is_negative_check());
+    }
+
+    #[test]
+    fn incoming_edges() {
+        let mut sub = Sub::mock("func");
+        let block = |name: &str, jmps: Vec<Jmp>| Term {
+            tid: Tid::new(name),
+            term: Blk {
+                defs: Vec::new(),
+                jmps: jmps
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, jmp)| Term {
+                        tid: Tid::new(format!("{}_jmp_{}", name, index)),
+                        term: jmp,
+                    })
+                    .collect(),
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        sub.term.blocks = vec![
+            block(
+                "entry",
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("exit"),
+                        condition: Expression::Var(Variable::mock("ZF", 1)),
+                    },
+                    Jmp::Branch(Tid::new("call")),
+                ],
+            ),
+            block("call", vec![Jmp::mock_call("callee", "exit")]),
+            block("exit", vec![Jmp::Return(Expression::const_from_i64(0))]),
+        ];
+        let edges = get_incoming_edges(&sub);
+        let call_edges = &edges[&Tid::new("call")];
+        assert_eq!(call_edges.len(), 1);
+        let call_edge = call_edges[0].unwrap();
+        assert_eq!(call_edge.jump.tid, Tid::new("entry_jmp_1"));
+        assert_eq!(
+            call_edge.untaken_conditional.unwrap().tid,
+            Tid::new("entry_jmp_0")
+        );
+        let exit_edges = &edges[&Tid::new("exit")];
+        assert_eq!(exit_edges.len(), 2);
+        assert!(exit_edges.contains(&None));
+        assert!(!edges.contains_key(&Tid::new("entry")));
+    }
+}
@@ -0,0 +1,412 @@
+//! A lightweight string abstraction that approximates the lengths of C-strings
+//! based on the results of the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//!
+//! The functions in this module serve as a bridge between the pointer inference results
+//! and checks that need to reason about the size of string copies,
+//! e.g. the check for CWE-120 (buffer copy without checking the size of the input).
+//!
+//! The Pointer Inference analysis uses [`get_formatted_string_length_bounds`]
+//! to approximate the lengths of the strings written by `sprintf`-like functions
+//! and records them in the memory objects of the destination buffers,
+//! so that later accesses to the formatted strings can be bounds-checked.
+//!
+//! String lengths are always given without the terminating null byte.
+
+use crate::abstract_domain::{TryToBitvec, TryToInterval};
+use crate::analysis::pointer_inference::{Data, State};
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::format_string::{parse_format_string, FormatSpecifier};
+
+/// An interval of possible lengths of a C-string, not counting the terminating null byte.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct StringLengthBounds {
+    /// The minimal length of the string.
+    pub lower_bound: u64,
+    /// The maximal length of the string.
+    /// `None` means that the length is not bounded from above.
+    pub upper_bound: Option<u64>,
+}
+
+impl StringLengthBounds {
+    /// Create bounds representing a string of exactly known length.
+    pub fn exact(length: u64) -> StringLengthBounds {
+        StringLengthBounds {
+            lower_bound: length,
+            upper_bound: Some(length),
+        }
+    }
+
+    /// Create bounds representing a string about whose length nothing is known.
+    pub fn unknown() -> StringLengthBounds {
+        StringLengthBounds {
+            lower_bound: 0,
+            upper_bound: None,
+        }
+    }
+
+    /// Create bounds for a string with a length between the given bounds.
+    fn new(lower_bound: u64, upper_bound: u64) -> StringLengthBounds {
+        StringLengthBounds {
+            lower_bound,
+            upper_bound: Some(upper_bound),
+        }
+    }
+
+    /// Return the bounds for the concatenation of strings with the given bounds.
+    pub fn append(&self, other: &StringLengthBounds) -> StringLengthBounds {
+        StringLengthBounds {
+            lower_bound: self.lower_bound.saturating_add(other.lower_bound),
+            upper_bound: match (self.upper_bound, other.upper_bound) {
+                (Some(upper_bound), Some(other_upper_bound)) => {
+                    upper_bound.checked_add(other_upper_bound)
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Return the smallest bounds containing both bounds.
+    pub fn merge(&self, other: &StringLengthBounds) -> StringLengthBounds {
+        StringLengthBounds {
+            lower_bound: std::cmp::min(self.lower_bound, other.lower_bound),
+            upper_bound: match (self.upper_bound, other.upper_bound) {
+                (Some(upper_bound), Some(other_upper_bound)) => {
+                    Some(std::cmp::max(upper_bound, other_upper_bound))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Return `true` if a string with a length inside the bounds
+    /// (plus its null terminator) may not fit into a buffer of the given size.
+    /// Returns `true` if no upper bound on the length is known.
+    pub fn may_exceed(&self, buffer_size: u64) -> bool {
+        match self.upper_bound {
+            Some(upper_bound) => upper_bound >= buffer_size,
+            None => true,
+        }
+    }
+}
+
+impl std::fmt::Display for StringLengthBounds {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.upper_bound {
+            Some(upper_bound) => write!(formatter, "[{}, {}]", self.lower_bound, upper_bound),
+            None => write!(formatter, "[{}, unbounded]", self.lower_bound),
+        }
+    }
+}
+
+/// Compute bounds for the length of the C-string that `pointer` points to in the given state.
+///
+/// - If the pointer is an absolute address of a string in the memory image of the binary,
+///   the exact length of the string is returned.
+/// - If the Pointer Inference analysis recorded bounds for the string (e.g. for the result of `sprintf`),
+///   the recorded bounds are returned.
+/// - If the pointer points into the current stack frame,
+///   the string has to fit between the pointer target and the base of the stack frame.
+/// - In all other cases nothing is known about the length of the string.
+pub fn get_string_length_bounds(
+    state: &State,
+    pointer: &Data,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> StringLengthBounds {
+    if let Ok(address) = pointer.try_to_bitvec() {
+        if let Ok(string) = runtime_memory_image.read_string_until_null_terminator(&address) {
+            return StringLengthBounds::exact(string.len() as u64);
+        }
+    }
+    let recorded_bounds = state.memory.get_string_length(pointer);
+    if let Some(bounds) = recorded_bounds.filter(|bounds| bounds.upper_bound.is_some()) {
+        return bounds;
+    }
+    let lower_bound = recorded_bounds.map_or(0, |bounds| bounds.lower_bound);
+    if let Some(stack_space) = state.get_stack_space_until_frame_base(pointer) {
+        return StringLengthBounds {
+            lower_bound,
+            upper_bound: Some(std::cmp::max(stack_space - 1, lower_bound)),
+        };
+    }
+    StringLengthBounds {
+        lower_bound,
+        upper_bound: None,
+    }
+}
+
+/// Get bounds for the length of the C-string that `pointer` points to
+/// if the string is a string in the memory image of the binary
+/// or if the Pointer Inference analysis recorded bounds for it.
+///
+/// In contrast to [`get_string_length_bounds`] the size of the buffer containing the string is not used as a bound.
+pub fn get_known_string_length_bounds(
+    state: &State,
+    pointer: &Data,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Option<StringLengthBounds> {
+    if let Ok(address) = pointer.try_to_bitvec() {
+        if let Ok(string) = runtime_memory_image.read_string_until_null_terminator(&address) {
+            return Some(StringLengthBounds::exact(string.len() as u64));
+        }
+    }
+    state.memory.get_string_length(pointer)
+}
+
+/// Compute bounds for the length of the string that a `sprintf`-like function writes for the given format string.
+///
+/// The function `get_argument` returns the value of the variadic argument with the given index,
+/// where index 0 denotes the first argument after the format string.
+/// Lengths of string arguments are computed with [`get_string_length_bounds`],
+/// lengths of integer arguments from their value intervals or the maximal number of digits of their type.
+/// Floating point numbers and widths or precisions given as arguments (e.g. `%*d`) are not bounded from above.
+pub fn get_formatted_string_length_bounds(
+    state: &State,
+    format_string: &str,
+    get_argument: impl Fn(usize) -> Option<Data>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> StringLengthBounds {
+    let mut bounds = StringLengthBounds::exact(0);
+    let mut literal_start = 0;
+    for specifier in parse_format_string(format_string) {
+        let literal_length =
+            match get_literal_length(&format_string[literal_start..specifier.offset]) {
+                Some(length) => length,
+                None => return StringLengthBounds::unknown(),
+            };
+        let specifier_end = specifier.offset + specifier.length;
+        let specifier_bounds = if format_string[specifier.offset..specifier_end].contains('*') {
+            StringLengthBounds::unknown()
+        } else {
+            let argument = get_argument(specifier.argument_index);
+            get_specifier_length_bounds(state, &specifier, argument, runtime_memory_image)
+        };
+        bounds = bounds
+            .append(&StringLengthBounds::exact(literal_length))
+            .append(&specifier_bounds);
+        literal_start = specifier_end;
+    }
+    match get_literal_length(&format_string[literal_start..]) {
+        Some(length) => bounds.append(&StringLengthBounds::exact(length)),
+        None => StringLengthBounds::unknown(),
+    }
+}
+
+/// Get the number of characters that the text between conversion specifiers of a format string prints.
+/// Returns `None` if the text contains a malformed conversion specifier.
+fn get_literal_length(text: &str) -> Option<u64> {
+    let escaped_percent_signs = text.matches("%%").count();
+    if text.matches('%').count() != 2 * escaped_percent_signs {
+        return None;
+    }
+    Some((text.len() - escaped_percent_signs) as u64)
+}
+
+/// Compute bounds for the number of characters printed for a conversion specifier with the given argument.
+fn get_specifier_length_bounds(
+    state: &State,
+    specifier: &FormatSpecifier,
+    argument: Option<Data>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> StringLengthBounds {
+    let is_long = matches!(
+        specifier.length_modifier.as_str(),
+        "l" | "ll" | "q" | "j" | "z" | "t"
+    );
+    // Only values that have the same representation as signed and unsigned integers of all sizes are used.
+    let value_interval = argument
+        .as_ref()
+        .and_then(|argument| argument.try_to_offset_interval().ok())
+        .filter(|(start, end)| *start >= 0 && *end <= i64::from(i32::MAX));
+    let digit_bounds = |max_digits_int: u64, max_digits_long: u64, digits: fn(i64) -> usize| {
+        match value_interval {
+            Some((start, end)) => StringLengthBounds::new(digits(start) as u64, digits(end) as u64),
+            None if is_long => StringLengthBounds::new(1, max_digits_long),
+            None => StringLengthBounds::new(1, max_digits_int),
+        }
+    };
+    let mut bounds = match specifier.conversion {
+        'd' | 'i' => digit_bounds(11, 20, |value| value.to_string().len()),
+        'u' => digit_bounds(10, 20, |value| value.to_string().len()),
+        'x' | 'X' => digit_bounds(8, 16, |value| format!("{:x}", value).len()),
+        'o' => digit_bounds(11, 22, |value| format!("{:o}", value).len()),
+        'c' => StringLengthBounds::exact(1),
+        'p' => StringLengthBounds::new(3, 18),
+        'n' => StringLengthBounds::exact(0),
+        's' => {
+            let string_bounds = match &argument {
+                Some(argument) => get_string_length_bounds(state, argument, runtime_memory_image),
+                None => StringLengthBounds::unknown(),
+            };
+            match specifier.precision {
+                Some(precision) => StringLengthBounds {
+                    lower_bound: std::cmp::min(string_bounds.lower_bound, precision as u64),
+                    upper_bound: Some(
+                        string_bounds
+                            .upper_bound
+                            .map_or(precision as u64, |upper_bound| {
+                                std::cmp::min(upper_bound, precision as u64)
+                            }),
+                    ),
+                },
+                None => string_bounds,
+            }
+        }
+        'f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A' => StringLengthBounds {
+            lower_bound: 1,
+            upper_bound: None,
+        },
+        _ => StringLengthBounds::unknown(),
+    };
+    if let (Some(precision), 'd' | 'i' | 'u' | 'x' | 'X' | 'o') =
+        (specifier.precision, specifier.conversion)
+    {
+        // The precision is the minimal number of digits. A sign may precede them.
+        bounds.lower_bound = std::cmp::max(bounds.lower_bound, precision as u64);
+        bounds.upper_bound = bounds
+            .upper_bound
+            .map(|upper_bound| std::cmp::max(upper_bound, precision as u64 + 1));
+    }
+    if let Some(width) = specifier.width {
+        bounds.lower_bound = std::cmp::max(bounds.lower_bound, width as u64);
+        bounds.upper_bound = bounds
+            .upper_bound
+            .map(|upper_bound| std::cmp::max(upper_bound, width as u64));
+    }
+    bounds
+}
+
+/// Get the content of the C-string that `pointer` points to
+/// if the pointer is the absolute address of a constant string in read-only memory of the binary.
+///
+/// Strings in writable memory are not constant, since they may be modified at runtime.
+pub fn get_constant_string<'a>(
+    pointer: &Data,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+) -> Option<&'a str> {
+    let address = pointer.try_to_bitvec().ok()?;
+    if runtime_memory_image.is_address_writeable(&address).ok()? {
+        return None;
+    }
+    runtime_memory_image
+        .read_string_until_null_terminator(&address)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::ValueDomain;
+    use crate::intermediate_representation::*;
+
+    #[test]
+    fn string_length_bounds() {
+        let stack_register = Variable::mock("RSP", 8);
+        let state = State::new(&stack_register, Tid::new("func"));
+        let mem_image = RuntimeMemoryImage::mock();
+
+        let global_string: Data = Bitvector::from_u64(0x3002).into();
+        let bounds = get_string_length_bounds(&state, &global_string, &mem_image);
+        assert_eq!(bounds, StringLengthBounds::exact(11));
+        assert!(bounds.may_exceed(11));
+        assert!(!bounds.may_exceed(12));
+
+        let stack_string = state.eval(&Expression::Var(stack_register.clone()).plus_const(-16));
+        let bounds = get_string_length_bounds(&state, &stack_string, &mem_image);
+        assert_eq!(bounds.upper_bound, Some(15));
+        assert_eq!(format!("{}", bounds), "[0, 15]");
+
+        let unknown_string = Data::Top(ByteSize::new(8));
+        let bounds = get_string_length_bounds(&state, &unknown_string, &mem_image);
+        assert_eq!(bounds, StringLengthBounds::unknown());
+        assert!(bounds.may_exceed(1));
+    }
+
+    #[test]
+    fn recorded_string_lengths() {
+        let stack_register = Variable::mock("RSP", 8);
+        let mut state = State::new(&stack_register, Tid::new("func"));
+        let mem_image = RuntimeMemoryImage::mock();
+        let stack_string = state.eval(&Expression::Var(stack_register.clone()).plus_const(-16));
+        assert_eq!(
+            get_known_string_length_bounds(&state, &stack_string, &mem_image),
+            None
+        );
+
+        state
+            .memory
+            .set_string_length(&stack_string, StringLengthBounds::new(2, 4));
+        assert_eq!(
+            get_string_length_bounds(&state, &stack_string, &mem_image),
+            StringLengthBounds::new(2, 4)
+        );
+        assert_eq!(
+            get_known_string_length_bounds(&state, &stack_string, &mem_image),
+            Some(StringLengthBounds::new(2, 4))
+        );
+        // Without a recorded upper bound the size of the stack frame is used.
+        let lower_bound_only = StringLengthBounds {
+            lower_bound: 3,
+            upper_bound: None,
+        };
+        state
+            .memory
+            .set_string_length(&stack_string, lower_bound_only);
+        assert_eq!(
+            get_string_length_bounds(&state, &stack_string, &mem_image),
+            StringLengthBounds::new(3, 15)
+        );
+    }
+
+    #[test]
+    fn formatted_string_lengths() {
+        let stack_register = Variable::mock("RSP", 8);
+        let state = State::new(&stack_register, Tid::new("func"));
+        let mem_image = RuntimeMemoryImage::mock();
+        let arguments: Vec<Data> = vec![
+            Bitvector::from_u64(0x3002).into(),
+            ValueDomain::new(Bitvector::from_i64(5), Bitvector::from_i64(120)).into(),
+            Data::Top(ByteSize::new(8)),
+        ];
+        let bounds_of = |format_string: &str| {
+            get_formatted_string_length_bounds(
+                &state,
+                format_string,
+                |index| arguments.get(index).cloned(),
+                &mem_image,
+            )
+        };
+
+        assert_eq!(bounds_of("100%% done"), StringLengthBounds::exact(9));
+        assert_eq!(bounds_of("<%s>"), StringLengthBounds::exact(13));
+        assert_eq!(bounds_of("%.5s"), StringLengthBounds::exact(5));
+        assert_eq!(bounds_of("%s: %d"), StringLengthBounds::new(14, 16));
+        assert_eq!(bounds_of("%s %x %d"), StringLengthBounds::new(15, 26));
+        assert_eq!(bounds_of("%s %lx %ld"), StringLengthBounds::new(15, 35));
+        assert_eq!(bounds_of("%08x %c"), StringLengthBounds::new(10, 10));
+        assert_eq!(bounds_of("%s %.4d"), StringLengthBounds::new(16, 17));
+        // Strings of unknown length, floats and widths given as arguments are not bounded from above.
+        assert_eq!(bounds_of("%s %s %s").upper_bound, None);
+        assert_eq!(bounds_of("%s %s %s").lower_bound, 13);
+        assert_eq!(bounds_of("%f").upper_bound, None);
+        assert_eq!(bounds_of("%*d").upper_bound, None);
+        assert_eq!(bounds_of("bad %"), StringLengthBounds::unknown());
+    }
+
+    #[test]
+    fn constant_strings() {
+        let mem_image = RuntimeMemoryImage::mock();
+        let global_string: Data = Bitvector::from_u64(0x3002).into();
+        assert_eq!(
+            get_constant_string(&global_string, &mem_image),
+            Some("Hello World")
+        );
+        // Strings in writable memory are not constant.
+        let writable_string: Data = Bitvector::from_u64(0x2000).into();
+        assert_eq!(get_constant_string(&writable_string, &mem_image), None);
+        assert_eq!(
+            get_constant_string(&Data::Top(ByteSize::new(8)), &mem_image),
+            None
+        );
+    }
+}
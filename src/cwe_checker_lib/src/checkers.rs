@@ -1,19 +1,71 @@
 //! The implemented CWE checks.
+//! The [`stack_integrity`] check does not correspond to a specific CWE
+//! but detects inconsistencies that affect the reliability of the other checks.
+//! The [`hardening`] and [`secret_hygiene`] checks do not correspond to specific CWEs either
+//! but report missing exploit mitigations resp. missing protections of secrets in memory.
 //! See their module descriptions for detailed information about each check.
 //!
 //! Currently the `Memory` check is not contained in this module
 //! but directly incorporated into the [`pointer_inference`](crate::analysis::pointer_inference) module.
 //! See there for detailed information about this check.
 
+pub mod cwe_120;
+pub mod cwe_121;
+pub mod cwe_122;
+pub mod cwe_129;
+pub mod cwe_131;
+pub mod cwe_1335;
+pub mod cwe_134;
+pub mod cwe_1341;
+pub mod cwe_14;
 pub mod cwe_190;
+pub mod cwe_197;
+pub mod cwe_208;
 pub mod cwe_215;
 pub mod cwe_243;
+pub mod cwe_252;
+pub mod cwe_273;
+pub mod cwe_295;
+pub mod cwe_321;
+pub mod cwe_326;
+pub mod cwe_327;
 pub mod cwe_332;
+pub mod cwe_337;
+pub mod cwe_362;
 pub mod cwe_367;
+pub mod cwe_369;
+pub mod cwe_401;
 pub mod cwe_426;
+pub mod cwe_457;
 pub mod cwe_467;
+pub mod cwe_468;
+pub mod cwe_469;
 pub mod cwe_476;
+pub mod cwe_477;
+pub mod cwe_479;
+pub mod cwe_532;
 pub mod cwe_560;
+pub mod cwe_606;
+pub mod cwe_617;
+pub mod cwe_667;
+pub mod cwe_672;
+pub mod cwe_674;
 pub mod cwe_676;
+pub mod cwe_685;
+pub mod cwe_690;
+pub mod cwe_732;
+pub mod cwe_761;
+pub mod cwe_770;
+pub mod cwe_775;
 pub mod cwe_78;
 pub mod cwe_782;
+pub mod cwe_789;
+pub mod cwe_798;
+pub mod cwe_835;
+pub mod cwe_843;
+pub mod cwe_88;
+pub mod cwe_908;
+pub mod cwe_910;
+pub mod hardening;
+pub mod secret_hygiene;
+pub mod stack_integrity;
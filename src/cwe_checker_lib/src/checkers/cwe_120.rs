@@ -0,0 +1,634 @@
+//! This module implements a check for CWE-120: Buffer Copy without Checking Size of Input ('Classic Buffer Overflow').
+//!
+//! Functions like `strcpy` copy a C-string into a destination buffer without checking
+//! whether the destination buffer is large enough to hold the string.
+//! If the source string is longer than the destination buffer,
+//! memory behind the buffer gets overwritten.
+//! The same happens for functions like `memcpy` if the length parameter is larger than the destination buffer.
+//!
+//! See <https://cwe.mitre.org/data/definitions/120.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a function listed in the symbols or the copy functions for CWE120 (configurable in config.json)
+//! we use the results of the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! to compute the size of the destination buffer.
+//! If the destination points into a memory object of known size (e.g. a heap object or a dynamic stack allocation),
+//! the number of bytes between the destination and the end of the object is the size of the buffer.
+//! If the destination points into the stack frame of the calling function,
+//! the number of bytes between the destination and the base of the stack frame
+//! is an upper bound for the size of the destination buffer.
+//!
+//! The number of copied bytes is approximated by the [string abstraction](crate::analysis::string_abstraction)
+//! for string copy functions and by the interval domain of the Pointer Inference analysis for the length parameter of copy functions.
+//! For functions appending to the string in the destination buffer (e.g. `strcat`)
+//! the length of the destination string is added.
+//! It is known if the destination string is a constant string in the binary
+//! or if the Pointer Inference analysis recorded its length, e.g. because it was written by `sprintf`.
+//! Otherwise the upper bound of the number of copied bytes is unknown.
+//! Each call is then classified into one of the following outcomes:
+//! - *proven overflow*: All possible numbers of copied bytes are larger than the destination buffer.
+//! - *possible overflow*: The number of copied bytes is bounded, but some possible values are larger than the destination buffer.
+//! - *unknown length*: Nothing is known about the number of copied bytes.
+//!
+//! Proven and possible overflows are always reported.
+//! Whether copies of unknown length are reported is controlled by the `unknown_length_policy` in config.json.
+//! By default they are reported with low confidence.
+//! The buffer size, the outcome and the confidence of the finding are annotated in the warning.
+//!
+//! ## False Positives
+//!
+//! - The string length approximation may overapproximate the actual length of the source string.
+//! - The interval domain may overapproximate the possible values of the length parameter.
+//! - Copies of unknown length are often bounded by checks that the analysis cannot see.
+//!
+//! ## False Negatives
+//!
+//! - Only destination buffers in objects of known size or on the stack of the calling function are checked.
+//! - The size of a buffer in the stack frame is overapproximated by the distance to the stack frame base,
+//!   so overflows into other local variables are not detected.
+//! - Copies of unknown length are not reported if the `unknown_length_policy` is `suppress`.
+//! - Appends to strings whose length is not known are treated as copies of unknown length.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec, TryToInterval};
+use crate::analysis::pointer_inference::{Data, PointerInference, State, ValueDomain};
+use crate::analysis::string_abstraction::{
+    get_known_string_length_bounds, get_string_length_bounds, StringLengthBounds,
+};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE120",
+    version: "0.3",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// String copy functions.
+    /// The first parameter of these functions is the destination buffer
+    /// and the second parameter is the source string.
+    symbols: Vec<String>,
+    /// Copy functions with a length parameter, mapped to the index of the length parameter.
+    /// The first parameter of these functions is the destination buffer.
+    #[serde(default)]
+    copy_functions: BTreeMap<String, usize>,
+    /// String copy functions appending the source string to the string in the destination buffer.
+    /// These functions also have to be contained in the `symbols`.
+    #[serde(default)]
+    append_symbols: Vec<String>,
+    /// How to handle copies where nothing is known about the number of copied bytes.
+    #[serde(default)]
+    unknown_length_policy: UnknownLengthPolicy,
+}
+
+/// The policy for copies where nothing is known about the number of copied bytes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownLengthPolicy {
+    /// Report the copy with the same confidence as possible overflows.
+    Report,
+    /// Report the copy with low confidence.
+    #[default]
+    ReportLowConfidence,
+    /// Do not report the copy.
+    Suppress,
+}
+
+/// The outcome of comparing the number of copied bytes with the size of the destination buffer.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CopyOutcome {
+    /// All possible numbers of copied bytes are larger than the buffer.
+    ProvenOverflow,
+    /// The number of copied bytes is bounded, but may be larger than the buffer.
+    PossibleOverflow,
+    /// Nothing is known about the number of copied bytes.
+    UnknownLength,
+}
+
+impl CopyOutcome {
+    /// Classify a copy of at least `lower_bound` and at most `upper_bound` bytes into a buffer of the given size.
+    /// An `upper_bound` of `None` means that the number of copied bytes is unknown.
+    /// Returns `None` if the copied bytes always fit into the buffer.
+    pub fn classify(
+        lower_bound: u64,
+        upper_bound: Option<u64>,
+        buffer_size: u64,
+    ) -> Option<CopyOutcome> {
+        match upper_bound {
+            None => Some(CopyOutcome::UnknownLength),
+            Some(_) if lower_bound > buffer_size => Some(CopyOutcome::ProvenOverflow),
+            Some(upper_bound) if upper_bound > buffer_size => Some(CopyOutcome::PossibleOverflow),
+            Some(_) => None,
+        }
+    }
+
+    /// Get the confidence of a finding with this outcome under the given policy for copies of unknown length.
+    /// Returns `None` if the finding should not be reported.
+    fn get_confidence(&self, policy: UnknownLengthPolicy) -> Option<&'static str> {
+        match (self, policy) {
+            (CopyOutcome::ProvenOverflow, _) => Some("high"),
+            (CopyOutcome::PossibleOverflow, _)
+            | (CopyOutcome::UnknownLength, UnknownLengthPolicy::Report) => Some("medium"),
+            (CopyOutcome::UnknownLength, UnknownLengthPolicy::ReportLowConfidence) => Some("low"),
+            (CopyOutcome::UnknownLength, UnknownLengthPolicy::Suppress) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CopyOutcome {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CopyOutcome::ProvenOverflow => write!(formatter, "proven_overflow"),
+            CopyOutcome::PossibleOverflow => write!(formatter, "possible_overflow"),
+            CopyOutcome::UnknownLength => write!(formatter, "unknown_length"),
+        }
+    }
+}
+
+/// Compute the size of the buffer that the destination parameter of the call points to,
+/// together with the memory object containing the buffer.
+/// Returns `None` if the size of the buffer is unknown.
+fn get_destination_buffer_size(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    state: &State,
+    symbol: &ExternSymbol,
+) -> Option<(AbstractIdentifier, u64)> {
+    let dest = state
+        .eval_parameter_arg(
+            symbol.parameters.first()?,
+            &project.stack_pointer_register,
+            pointer_inference.get_context().runtime_memory_image,
+        )
+        .ok()?;
+    get_buffer_size(state, &dest)
+}
+
+/// Compute the size of the buffer that the pointer points to,
+/// together with the memory object containing the buffer.
+///
+/// If the pointer points to a known offset into a single object of known size,
+/// the buffer ends at the end of the object.
+/// Otherwise, if the pointer points into a dynamic stack allocation of the current stack frame,
+/// the buffer ends at the end of the allocation.
+/// Pointers to other parts of the current stack frame point to buffers ending at the base of the stack frame.
+/// Returns `None` if the size of the buffer is unknown.
+fn get_buffer_size(state: &State, pointer: &Data) -> Option<(AbstractIdentifier, u64)> {
+    if let Some((id, offset)) = get_unique_target(pointer) {
+        let object_size = state
+            .memory
+            .get_object_size(id)
+            .and_then(|size| size.try_to_offset_interval().ok())
+            .map(|(_, max_size)| max_size);
+        if let (Some(object_size), Ok(offset)) = (object_size, offset.try_to_offset()) {
+            if offset >= 0 && offset < object_size {
+                return Some((id.clone(), (object_size - offset) as u64));
+            }
+        }
+    }
+    if let Some((_, buffer_size)) = state.get_stack_space_until_allocation_end(pointer) {
+        return Some((state.stack_id.clone(), buffer_size));
+    }
+    state
+        .get_stack_space_until_frame_base(pointer)
+        .map(|buffer_size| (state.stack_id.clone(), buffer_size))
+}
+
+/// Get the target of a pointer with exactly one target.
+fn get_unique_target(pointer: &Data) -> Option<(&AbstractIdentifier, &ValueDomain)> {
+    match pointer {
+        Data::Pointer(pointer) if pointer.targets().len() == 1 => pointer.targets().iter().next(),
+        _ => None,
+    }
+}
+
+/// Compute bounds for the number of bytes copied by a call to a string copy function,
+/// i.e. the length of the source string plus its null terminator.
+fn get_string_copy_bounds(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    state: &State,
+    symbol: &ExternSymbol,
+) -> Option<(u64, Option<u64>)> {
+    let runtime_memory_image = pointer_inference.get_context().runtime_memory_image;
+    let source = state
+        .eval_parameter_arg(
+            symbol.parameters.get(1)?,
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?;
+    let source_length = get_string_length_bounds(state, &source, runtime_memory_image);
+    Some((
+        source_length.lower_bound + 1,
+        source_length.upper_bound.map(|upper_bound| upper_bound + 1),
+    ))
+}
+
+/// Compute bounds for the number of bytes written by a call to a string append function
+/// counted from the start of the destination buffer,
+/// i.e. the length of the destination string plus the length of the source string plus the null terminator.
+fn get_string_append_bounds(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    state: &State,
+    symbol: &ExternSymbol,
+) -> Option<(u64, Option<u64>)> {
+    let (copy_lower_bound, copy_upper_bound) =
+        get_string_copy_bounds(project, pointer_inference, state, symbol)?;
+    let runtime_memory_image = pointer_inference.get_context().runtime_memory_image;
+    let dest = state
+        .eval_parameter_arg(
+            symbol.parameters.first()?,
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?;
+    let dest_length = get_known_string_length_bounds(state, &dest, runtime_memory_image)
+        .unwrap_or_else(StringLengthBounds::unknown);
+    let bounds = dest_length.append(&StringLengthBounds {
+        lower_bound: copy_lower_bound,
+        upper_bound: copy_upper_bound,
+    });
+    Some((bounds.lower_bound, bounds.upper_bound))
+}
+
+/// Compute bounds for the number of bytes copied by a call to a copy function
+/// from the value of its length parameter.
+/// Lengths that may be negative when interpreted as signed integers are treated as unknown.
+fn get_length_parameter_bounds(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    state: &State,
+    symbol: &ExternSymbol,
+    length_index: usize,
+) -> Option<(u64, Option<u64>)> {
+    let length = state
+        .eval_parameter_arg(
+            symbol.parameters.get(length_index)?,
+            &project.stack_pointer_register,
+            pointer_inference.get_context().runtime_memory_image,
+        )
+        .ok()?;
+    match length.try_to_offset_interval() {
+        Ok((start, end)) if start >= 0 => Some((start as u64, Some(end as u64))),
+        _ => Some((0, None)),
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    jmp: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    buffer_size: u64,
+    copy_bounds: (u64, Option<u64>),
+    outcome: CopyOutcome,
+    confidence: &str,
+    object_id: &AbstractIdentifier,
+) -> CweWarning {
+    let copy_size = match copy_bounds {
+        (lower_bound, Some(upper_bound)) => format!("[{}, {}]", lower_bound, upper_bound),
+        (lower_bound, None) => format!("[{}, unbounded]", lower_bound),
+    };
+    let description = match outcome {
+        CopyOutcome::ProvenOverflow => "copies more bytes than fit into",
+        CopyOutcome::PossibleOverflow => "may copy more bytes than fit into",
+        CopyOutcome::UnknownLength => "copies an unknown number of bytes into",
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Buffer Copy without Checking Size of Input) Call to {} at {} {} buffer of size {}",
+            symbol.name, jmp.tid.address, description, buffer_size
+        ),
+    )
+    .tids(vec![format!("{}", jmp.tid)])
+    .addresses(vec![jmp.tid.address.clone()])
+    .symbols(vec![symbol.name.clone()])
+    .other(vec![
+        vec!["buffer_size".to_string(), buffer_size.to_string()],
+        vec!["copy_size".to_string(), copy_size],
+        vec!["outcome".to_string(), outcome.to_string()],
+        vec!["confidence".to_string(), confidence.to_string()],
+    ])
+    // The destination buffer is identified by the memory object and its distance to the end of the object.
+    .root_cause(format!("{} - {}", object_id, buffer_size))
+}
+
+/// Execute the CWE check.
+///
+/// For each call to an extern symbol from the symbol list or the copy functions configured in the configuration file
+/// we check whether the copied bytes may not fit into the stack buffer they are copied into.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    let mut symbols = config.symbols.clone();
+    symbols.extend(config.copy_functions.keys().cloned());
+    let symbol_map = get_symbol_map(project, &symbols);
+    for sub in project.program.term.subs.iter() {
+        for (_block, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            let state = match pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            let (object_id, buffer_size) =
+                match get_destination_buffer_size(project, pointer_inference, state, symbol) {
+                    Some(buffer) => buffer,
+                    None => continue,
+                };
+            let copy_bounds = match config.copy_functions.get(&symbol.name) {
+                Some(length_index) => get_length_parameter_bounds(
+                    project,
+                    pointer_inference,
+                    state,
+                    symbol,
+                    *length_index,
+                ),
+                None if config.append_symbols.contains(&symbol.name) => {
+                    get_string_append_bounds(project, pointer_inference, state, symbol)
+                }
+                None => get_string_copy_bounds(project, pointer_inference, state, symbol),
+            };
+            if let Some((lower_bound, upper_bound)) = copy_bounds {
+                if let Some(outcome) = CopyOutcome::classify(lower_bound, upper_bound, buffer_size)
+                {
+                    if let Some(confidence) = outcome.get_confidence(config.unknown_length_policy) {
+                        cwe_warnings.push(generate_cwe_warning(
+                            jmp,
+                            symbol,
+                            buffer_size,
+                            (lower_bound, upper_bound),
+                            outcome,
+                            confidence,
+                            &object_id,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{
+        AbstractDomain, AbstractLocation, DataDomain, PointerDomain, SizedDomain,
+    };
+    use crate::analysis::graph::get_program_cfg;
+    use crate::analysis::pointer_inference::object::ObjectType;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    #[test]
+    fn classify_copy_outcome() {
+        assert_eq!(
+            CopyOutcome::classify(20, Some(30), 16),
+            Some(CopyOutcome::ProvenOverflow)
+        );
+        assert_eq!(
+            CopyOutcome::classify(8, Some(30), 16),
+            Some(CopyOutcome::PossibleOverflow)
+        );
+        assert_eq!(CopyOutcome::classify(8, Some(16), 16), None);
+        assert_eq!(
+            CopyOutcome::classify(0, None, 16),
+            Some(CopyOutcome::UnknownLength)
+        );
+        // Even the empty string does not fit into a buffer of size zero.
+        assert_eq!(
+            CopyOutcome::classify(1, Some(1), 0),
+            Some(CopyOutcome::ProvenOverflow)
+        );
+
+        let unknown = CopyOutcome::UnknownLength;
+        assert_eq!(
+            unknown.get_confidence(UnknownLengthPolicy::Report),
+            Some("medium")
+        );
+        assert_eq!(
+            unknown.get_confidence(UnknownLengthPolicy::ReportLowConfidence),
+            Some("low")
+        );
+        assert_eq!(unknown.get_confidence(UnknownLengthPolicy::Suppress), None);
+        assert_eq!(
+            CopyOutcome::ProvenOverflow.get_confidence(UnknownLengthPolicy::Suppress),
+            Some("high")
+        );
+        assert_eq!(
+            CopyOutcome::PossibleOverflow.get_confidence(UnknownLengthPolicy::Suppress),
+            Some("medium")
+        );
+    }
+
+    #[test]
+    fn unknown_length_policy() {
+        // Copies of unknown length are reported by default.
+        let config: Config = parse_config(&serde_json::json!({"symbols": ["strcpy"]})).unwrap();
+        assert_eq!(
+            config.unknown_length_policy,
+            UnknownLengthPolicy::ReportLowConfidence
+        );
+        let config: Config = parse_config(&serde_json::json!({
+            "symbols": ["strcpy"],
+            "unknown_length_policy": "suppress"
+        }))
+        .unwrap();
+        assert_eq!(config.unknown_length_policy, UnknownLengthPolicy::Suppress);
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "symbols": ["strcpy"],
+            "unknown_length_policy": "ignore"
+        }))
+        .is_err());
+        // The string copy functions are required.
+        assert!(parse_config::<Config>(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn buffer_sizes() {
+        let stack_register = Variable::mock("RSP", 8);
+        let mut state = State::new(&stack_register, Tid::new("func"));
+        let stack_pointer = Expression::Var(stack_register);
+
+        // Buffers in the stack frame end at the frame base.
+        let stack_buffer = state.eval(&stack_pointer.clone().plus_const(-16));
+        assert_eq!(
+            get_buffer_size(&state, &stack_buffer),
+            Some((state.stack_id.clone(), 16))
+        );
+        // Pointers to the parameters above the stack frame are not checked.
+        let stack_parameter = state.eval(&stack_pointer.plus_const(8));
+        assert_eq!(get_buffer_size(&state, &stack_parameter), None);
+
+        // Buffers in objects of known size end at the end of the object.
+        let heap_id = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        state.memory.add_abstract_object(
+            heap_id.clone(),
+            Bitvector::from_i64(0).into(),
+            ObjectType::Heap,
+            ByteSize::new(8),
+        );
+        let heap_buffer = |offset: i64| -> Data {
+            PointerDomain::new(heap_id.clone(), Bitvector::from_i64(offset).into()).into()
+        };
+        // The size of the object is unknown.
+        assert_eq!(get_buffer_size(&state, &heap_buffer(4)), None);
+        state
+            .memory
+            .set_object_size(&heap_id, Bitvector::from_i64(0x20).into());
+        assert_eq!(
+            get_buffer_size(&state, &heap_buffer(4)),
+            Some((heap_id.clone(), 0x1c))
+        );
+        // The destination is already outside of the object.
+        assert_eq!(get_buffer_size(&state, &heap_buffer(0x20)), None);
+        assert_eq!(get_buffer_size(&state, &heap_buffer(-4)), None);
+        // The destination may point into several objects.
+        let merged_pointer = heap_buffer(4).merge(&stack_buffer);
+        assert_eq!(get_buffer_size(&state, &merged_pointer), None);
+
+        assert_eq!(
+            get_buffer_size(&state, &DataDomain::new_top(ByteSize::new(8))),
+            None
+        );
+    }
+
+    /// A function calling the given extern symbol with the destination `RSP - buffer_size`
+    /// and the given Defs setting the other parameters.
+    fn copy_sub(name: &str, target: &str, buffer_size: i64, mut defs: Vec<Term<Def>>) -> Term<Sub> {
+        defs.push(Def::assign(
+            &format!("{}_destination", name),
+            Variable::mock("RDI", 8),
+            Expression::Var(Variable::mock("RSP", 8)).plus_const(-buffer_size),
+        ));
+        let mut sub = Sub::mock(name);
+        sub.term.blocks = vec![
+            Blk::mock_with(
+                &format!("{}_0", name),
+                defs,
+                Jmp::mock_call(target, &format!("{}_1", name)),
+            ),
+            Blk::mock_with(
+                &format!("{}_1", name),
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ];
+        sub
+    }
+
+    /// Run the check on the project and return the TIDs and outcomes of the warnings.
+    fn reported_copies(project: &Project, config: serde_json::Value) -> Vec<(String, String)> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| (warning.tids[0].clone(), warning.other[2][1].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn copies_into_stack_buffers() {
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols = ["strcpy", "strcat", "memcpy"]
+            .iter()
+            .map(|name| {
+                let mut symbol = ExternSymbol::mock();
+                symbol.tid = Tid::new(*name);
+                symbol.name = name.to_string();
+                symbol.parameters = vec![
+                    Arg::mock_register("RDI"),
+                    Arg::mock_register("RSI"),
+                    Arg::mock_register("RDX"),
+                ];
+                symbol
+            })
+            .collect();
+        // Points to the read-only string "Hello World" in the mock runtime memory image.
+        let source = |name: &str| {
+            Def::assign(
+                &format!("{}_source", name),
+                Variable::mock("RSI", 8),
+                Expression::const_from_i64(0x3002),
+            )
+        };
+        let length = |name: &str, length: i64| {
+            Def::assign(
+                &format!("{}_length", name),
+                Variable::mock("RDX", 8),
+                Expression::const_from_i64(length),
+            )
+        };
+        project.program.term.subs = vec![
+            copy_sub("too_small", "strcpy", 8, vec![source("too_small")]),
+            copy_sub("large_enough", "strcpy", 12, vec![source("large_enough")]),
+            // The source is a parameter of the function and thus of unknown length.
+            copy_sub("unknown_source", "strcpy", 16, Vec::new()),
+            copy_sub("append", "strcat", 16, vec![source("append")]),
+            copy_sub("long_copy", "memcpy", 8, vec![length("long_copy", 16)]),
+            copy_sub("short_copy", "memcpy", 8, vec![length("short_copy", 8)]),
+        ];
+        let warning = |name: &str, outcome: &str| (format!("{}_0_jmp", name), outcome.to_string());
+
+        let config = json!({
+            "symbols": ["strcpy", "strcat"],
+            "copy_functions": {"memcpy": 2},
+            "append_symbols": ["strcat"]
+        });
+        assert_eq!(
+            reported_copies(&project, config),
+            vec![
+                warning("too_small", "proven_overflow"),
+                warning("unknown_source", "unknown_length"),
+                warning("append", "unknown_length"),
+                warning("long_copy", "proven_overflow"),
+            ]
+        );
+        // Without the append symbols, `strcat` is checked like `strcpy`.
+        // Without the copy functions, `memcpy` is not checked at all.
+        let config = json!({
+            "symbols": ["strcpy", "strcat"],
+            "unknown_length_policy": "suppress"
+        });
+        assert_eq!(
+            reported_copies(&project, config),
+            vec![warning("too_small", "proven_overflow")]
+        );
+    }
+}
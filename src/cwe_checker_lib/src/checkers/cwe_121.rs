@@ -0,0 +1,426 @@
+//! This module implements a check for CWE-121: Stack-based Buffer Overflow.
+//!
+//! Reading a string with the `%s` or `%[` conversion specifiers of `scanf`-like functions
+//! without a maximum field width writes an input-dependent number of bytes into the target buffer.
+//! If the target buffer is located on the stack, an attacker can overwrite other stack contents
+//! including the return address of the function.
+//!
+//! See <https://cwe.mitre.org/data/definitions/121.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a function configured in config.json we use the
+//! [Pointer Inference analysis](crate::analysis::pointer_inference) to determine the format string parameter.
+//! If it is a constant address of a string in the memory image of the binary,
+//! we parse the format string and look for `%s` and `%[` specifiers without a field width.
+//! If the argument corresponding to such a specifier points into a stack frame
+//! or into a dynamic stack allocation (e.g. by `alloca`), a warning is generated.
+//! If the size of the target buffer is known, it is annotated in the warning.
+//! For buffers in the stack frame of the calling function the size is overapproximated by the distance to the stack frame base.
+//!
+//! Additionally, we check all `Store` instructions for writes through stack pointers
+//! that may exceed the bounds of the object they point to.
+//! A store overflows the stack frame of its function if the offset interval of the target address
+//! starts inside the frame but the written bytes may reach the frame base, e.g. for copy loops without bounds checks.
+//! For stores into dynamic stack allocations (e.g. by `alloca`) the written bytes must not reach the end of the allocation instead.
+//! One warning is generated per overflowed stack object and function.
+//! It contains the TID of the first offending store and the name of the function whose stack frame is overflowed.
+//!
+//! ## False Positives
+//!
+//! - The input may be bounded by other means, e.g. if the source string of `sscanf` is known to be short.
+//! - The offset intervals of pointers in loops may be overapproximated if the loop bound cannot be represented in the interval domain.
+//!
+//! ## False Negatives
+//!
+//! - Format strings that are not constant addresses are not checked.
+//! - Specifiers whose field width is larger than the target buffer are not detected.
+//! - Functions taking a `va_list` instead of variadic arguments are not supported.
+//! - Stores that overflow one local variable into another local variable of the same stack frame are not detected.
+//! - Stores through pointers with completely unknown offsets are not checked.
+//! - Writes into stack frames of callers (i.e. through pointers passed as parameters) are not checked.
+
+use crate::abstract_domain::{AbstractDomain, TryToBitvec, TryToInterval};
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::format_string::parse_scanf_format_string;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE121",
+    version: "0.2",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions taking a `scanf`-style format string, mapped to the index of the format string parameter.
+    format_string_index: BTreeMap<String, usize>,
+}
+
+/// An unbounded string specifier whose argument points to a stack buffer.
+struct Finding {
+    /// The conversion character of the specifier, i.e. `s` or `[`.
+    conversion: char,
+    /// The index of the parameter corresponding to the specifier.
+    parameter_index: usize,
+    /// The size of the stack buffer if known.
+    buffer_size: Option<u64>,
+}
+
+/// Check the call to a `scanf`-like function for unbounded string specifiers writing into stack buffers.
+fn check_call(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    symbol: &ExternSymbol,
+    format_string_index: usize,
+) -> Vec<Finding> {
+    let stack_register = &project.stack_pointer_register;
+    let eval_parameter = |index: usize| {
+        state.eval_parameter_arg(
+            &get_variadic_parameter(project, symbol, index),
+            stack_register,
+            runtime_memory_image,
+        )
+    };
+    let format_string = match eval_parameter(format_string_index)
+        .ok()
+        .and_then(|format_string| format_string.try_to_bitvec().ok())
+        .and_then(|address| {
+            runtime_memory_image
+                .read_string_until_null_terminator(&address)
+                .ok()
+        }) {
+        Some(format_string) => format_string,
+        None => return Vec::new(),
+    };
+    parse_scanf_format_string(format_string)
+        .into_iter()
+        .filter(|specifier| {
+            matches!(specifier.conversion, 's' | '[')
+                && specifier.width.is_none()
+                // With the `m` modifier the buffer is allocated by the called function.
+                && !specifier.length_modifier.contains('m')
+        })
+        .filter_map(|specifier| {
+            let parameter_index = format_string_index + 1 + specifier.argument_index;
+            let pointer = eval_parameter(parameter_index).ok()?;
+            let buffer_size = get_stack_buffer_size(state, &pointer)?;
+            Some(Finding {
+                conversion: specifier.conversion,
+                parameter_index,
+                buffer_size,
+            })
+        })
+        .collect()
+}
+
+/// Compute the size of the stack buffer that the pointer points to.
+/// Returns `None` if the pointer does not point into a stack frame
+/// and `Some(None)` if the size of the stack buffer is unknown.
+///
+/// For dynamic stack allocations in the current stack frame the buffer size is the distance to the end of the allocation.
+/// For other buffers in the stack frame of the current function it is the distance to the stack frame base.
+fn get_stack_buffer_size(state: &State, pointer: &Data) -> Option<Option<u64>> {
+    let pointer_domain = match pointer {
+        Data::Pointer(pointer_domain) => pointer_domain,
+        _ => return None,
+    };
+    if pointer_domain.targets().len() != 1 {
+        return None;
+    }
+    let (id, _) = pointer_domain.targets().iter().next().unwrap();
+    if *id == state.stack_id {
+        match state.get_stack_space_until_allocation_end(pointer) {
+            Some((_, buffer_size)) => Some(Some(buffer_size)),
+            None => Some(state.get_stack_space_until_frame_base(pointer)),
+        }
+    } else if state.caller_stack_ids.contains(id) {
+        Some(None)
+    } else {
+        None
+    }
+}
+
+/// A stack object that may be overflowed by a store.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+enum OverflowedStackObject {
+    /// The stack frame of the function containing the store.
+    StackFrame,
+    /// A dynamic stack allocation, e.g. by `alloca`, identified by the TID of the allocation site.
+    StackAllocation(Tid),
+}
+
+/// Check whether a store of `size` bytes to the given address may write outside of the bounds of a stack object.
+///
+/// For the stack frame of the current function this is the case
+/// if the offset interval starts inside the frame and the written bytes may reach the frame base.
+/// For dynamic stack allocations the end of the written bytes is compared to the end of the allocation in the stack frame.
+/// Stores through pointers with unknown offsets are ignored.
+fn get_overflowed_stack_object(
+    state: &State,
+    address: &Expression,
+    size: ByteSize,
+) -> Option<OverflowedStackObject> {
+    let pointer = match state.eval(address) {
+        Data::Pointer(pointer) => pointer,
+        _ => return None,
+    };
+    let size = u64::from(size) as i64;
+    let offset = match pointer.targets().get(&state.stack_id) {
+        Some(offset) if !offset.is_top() => offset,
+        _ => return None,
+    };
+    let (offset_start, offset_end) = offset.try_to_offset_interval().ok()?;
+    let write_end = offset_end.saturating_add(size);
+    if let Some((allocation_site, allocation)) = state.get_stack_allocation(offset) {
+        if write_end > allocation.end {
+            return Some(OverflowedStackObject::StackAllocation(
+                allocation_site.clone(),
+            ));
+        }
+    } else if offset_start < 0 && write_end > 0 {
+        return Some(OverflowedStackObject::StackFrame);
+    }
+    None
+}
+
+/// Check all stores in the function for writes outside of the bounds of stack objects.
+/// Returns the first offending store for each overflowed stack object.
+fn check_stores<'a>(
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+) -> Vec<(&'a Term<Def>, OverflowedStackObject)> {
+    let mut overflowed_objects = BTreeSet::new();
+    let mut findings = Vec::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            if let Def::Store { address, value } = &def.term {
+                let overflowed_object =
+                    pointer_inference
+                        .get_state_at_tid(&def.tid)
+                        .and_then(|state| {
+                            get_overflowed_stack_object(&state, address, value.bytesize())
+                        });
+                if let Some(overflowed_object) = overflowed_object {
+                    if overflowed_objects.insert(overflowed_object.clone()) {
+                        findings.push((def, overflowed_object));
+                    }
+                }
+            }
+        }
+    }
+    findings
+}
+
+/// Generate the CWE warning for a store that may overflow a stack object.
+fn generate_store_cwe_warning(
+    sub: &Term<Sub>,
+    def: &Term<Def>,
+    overflowed_object: &OverflowedStackObject,
+) -> CweWarning {
+    let (object_description, mut other) = match overflowed_object {
+        OverflowedStackObject::StackFrame => (
+            format!("the stack frame of {}", sub.term.display_name()),
+            Vec::new(),
+        ),
+        OverflowedStackObject::StackAllocation(allocation_site) => (
+            format!(
+                "the stack allocation at {} in {}",
+                allocation_site.address,
+                sub.term.display_name()
+            ),
+            vec![vec![
+                "allocation_site".to_string(),
+                allocation_site.address.clone(),
+            ]],
+        ),
+    };
+    other.insert(
+        0,
+        vec!["function".to_string(), sub.term.display_name().to_string()],
+    );
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Stack-based Buffer Overflow) Write at {} may exceed the bounds of {}",
+            def.tid.address, object_description
+        ),
+    )
+    .tids(vec![format!("{}", def.tid)])
+    .addresses(vec![def.tid.address.clone()])
+    .other(other)
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(jmp: &Term<Jmp>, symbol: &ExternSymbol, finding: &Finding) -> CweWarning {
+    let buffer_description = match finding.buffer_size {
+        Some(size) => format!("stack buffer of size {}", size),
+        None => "stack buffer".to_string(),
+    };
+    let mut other = vec![vec![
+        "parameter_index".to_string(),
+        finding.parameter_index.to_string(),
+    ]];
+    if let Some(size) = finding.buffer_size {
+        other.push(vec!["buffer_size".to_string(), size.to_string()]);
+    }
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Stack-based Buffer Overflow) Call to {} at {} reads unbounded %{} input into {}",
+            symbol.name, jmp.tid.address, finding.conversion, buffer_description
+        ),
+    )
+    .tids(vec![format!("{}", jmp.tid)])
+    .addresses(vec![jmp.tid.address.clone()])
+    .symbols(vec![symbol.name.clone()])
+    .other(other)
+}
+
+/// Run the CWE check.
+/// For each call to a `scanf`-like function we check for `%s` and `%[` specifiers without field width
+/// whose arguments point to stack buffers.
+/// Furthermore, we check all stores for writes through stack pointers that exceed the bounds of the stack object.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbols: Vec<String> = config.format_string_index.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbols);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            if let Some(state) = pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                for finding in check_call(
+                    project,
+                    analysis_results.runtime_memory_image,
+                    state,
+                    symbol,
+                    config.format_string_index[&symbol.name],
+                ) {
+                    cwe_warnings.push(generate_cwe_warning(jmp, symbol, &finding));
+                }
+            }
+        }
+        for (def, overflowed_object) in check_stores(pointer_inference, sub) {
+            cwe_warnings.push(generate_store_cwe_warning(sub, def, &overflowed_object));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+    use crate::analysis::pointer_inference::{StackAllocation, ValueDomain};
+
+    #[test]
+    fn stack_buffers() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let stack_pointer: Data =
+            PointerDomain::new(state.stack_id.clone(), Bitvector::from_i64(-0x20).into()).into();
+        assert_eq!(
+            get_stack_buffer_size(&state, &stack_pointer),
+            Some(Some(0x20))
+        );
+        state.stack_allocations.insert(
+            Tid::new("alloca_call"),
+            StackAllocation {
+                start: Bitvector::from_i64(-0x30).into(),
+                end: -0x18,
+            },
+        );
+        assert_eq!(
+            get_stack_buffer_size(&state, &stack_pointer),
+            Some(Some(0x8))
+        );
+        let frame_pointer: Data =
+            PointerDomain::new(state.stack_id.clone(), Bitvector::from_i64(-0x10).into()).into();
+        assert_eq!(
+            get_stack_buffer_size(&state, &frame_pointer),
+            Some(Some(0x10))
+        );
+        let heap_id = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        let heap_pointer: Data = PointerDomain::new(heap_id, Bitvector::from_i64(0).into()).into();
+        assert_eq!(get_stack_buffer_size(&state, &heap_pointer), None);
+        assert_eq!(
+            get_stack_buffer_size(&state, &Data::new_top(ByteSize::new(8))),
+            None
+        );
+    }
+
+    #[test]
+    fn stack_stores() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let rdi = Variable::mock("RDI", 8);
+        let address = Expression::Var(rdi.clone());
+        let interval = |start: i64, end: i64| -> ValueDomain {
+            ValueDomain::new(Bitvector::from_i64(start), Bitvector::from_i64(end))
+        };
+        let stack_id = state.stack_id.clone();
+        let set_pointer = |state: &mut State, id: &AbstractIdentifier, offset: ValueDomain| {
+            state.set_register(&rdi, PointerDomain::new(id.clone(), offset).into());
+        };
+
+        set_pointer(&mut state, &stack_id, interval(-0x20, -0x10));
+        assert_eq!(
+            get_overflowed_stack_object(&state, &address, ByteSize::new(8)),
+            None
+        );
+        set_pointer(&mut state, &stack_id, interval(-0x20, i64::MAX));
+        assert_eq!(
+            get_overflowed_stack_object(&state, &address, ByteSize::new(1)),
+            Some(OverflowedStackObject::StackFrame)
+        );
+        // Stores to stack parameters are not overflows.
+        set_pointer(&mut state, &stack_id, interval(8, 8));
+        assert_eq!(
+            get_overflowed_stack_object(&state, &address, ByteSize::new(8)),
+            None
+        );
+
+        state.stack_allocations.insert(
+            Tid::new("alloca_call"),
+            StackAllocation {
+                start: interval(-0x40, -0x20),
+                end: -0x10,
+            },
+        );
+        set_pointer(&mut state, &stack_id, interval(-0x40, -0x14));
+        assert_eq!(
+            get_overflowed_stack_object(&state, &address, ByteSize::new(4)),
+            None
+        );
+        set_pointer(&mut state, &stack_id, interval(-0x40, -0x10));
+        assert_eq!(
+            get_overflowed_stack_object(&state, &address, ByteSize::new(4)),
+            Some(OverflowedStackObject::StackAllocation(Tid::new(
+                "alloca_call"
+            )))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! This module implements a check for CWE-122: Heap-based Buffer Overflow.
+//!
+//! Writing behind the end of a heap object overwrites other heap objects or the metadata of the heap allocator.
+//! Depending on the allocator this can lead to arbitrary code execution.
+//!
+//! See <https://cwe.mitre.org/data/definitions/122.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The [Pointer Inference analysis](crate::analysis::pointer_inference) propagates the sizes of heap objects
+//! from the size parameters of the allocation functions (configured in the `allocation_size_parameters`
+//! of the `Memory` section in config.json) into the abstract memory objects created at the call sites.
+//! For each `Store` instruction we check whether the target address points into a heap object of known size
+//! and whether the end of the written bytes may exceed the largest possible size of the object.
+//! Accesses at most one byte behind the end of objects inferred to be byte buffers are tolerated
+//! (see [`HeapObjectType::overflow_tolerance`]).
+//! One warning is generated per overflowed heap object and function.
+//! It contains the TID of the first offending store and the allocation site of the object.
+//!
+//! ## False Positives
+//!
+//! - The offset intervals of pointers in loops may be overapproximated if the loop bound cannot be represented in the interval domain.
+//! - Objects allocated by the same call in a loop are represented by the same abstract object,
+//!   so the sizes of different allocations get merged.
+//!
+//! ## False Negatives
+//!
+//! - Heap objects whose size is not an absolute value known to the interval domain are not checked.
+//! - Writes by extern functions like `memcpy` are not checked.
+//! - Writes to heap objects in functions that receive a pointer to the object as a parameter are not checked.
+
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier, TryToInterval};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::object_types::HeapObjectType;
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+use std::collections::BTreeSet;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE122",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// A store that may write behind the end of a heap object.
+struct HeapOverflow<'a> {
+    /// The offending store.
+    def: &'a Term<Def>,
+    /// The identifier of the overflowed heap object.
+    object_id: AbstractIdentifier,
+    /// The largest possible size of the heap object.
+    object_size: i64,
+    /// The largest possible end offset of the written bytes.
+    write_end: i64,
+}
+
+/// Check whether a store of `size` bytes to the given address may write behind the end of a heap object of known size.
+/// Returns the overflowed object together with its largest possible size and the largest possible end of the write.
+///
+/// The `get_overflow_tolerance` function returns the number of bytes by which writes may exceed the given object.
+fn get_overflowed_heap_object(
+    state: &State,
+    address: &Expression,
+    size: ByteSize,
+    get_overflow_tolerance: impl Fn(&AbstractIdentifier) -> u64,
+) -> Option<(AbstractIdentifier, i64, i64)> {
+    let pointer = match state.eval(address) {
+        Data::Pointer(pointer) => pointer,
+        _ => return None,
+    };
+    for (id, offset) in pointer.targets() {
+        if offset.is_top()
+            || !matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap)))
+        {
+            continue;
+        }
+        let object_size = match state.memory.get_object_size(id) {
+            Some(object_size) if !object_size.is_top() => object_size,
+            _ => continue,
+        };
+        if let (Ok((_, max_object_size)), Ok((offset_start, offset_end))) = (
+            object_size.try_to_offset_interval(),
+            offset.try_to_offset_interval(),
+        ) {
+            let write_end = offset_end.saturating_add(u64::from(size) as i64);
+            let tolerance = get_overflow_tolerance(id) as i64;
+            if offset_start >= 0
+                && offset_start < max_object_size
+                && write_end > max_object_size.saturating_add(tolerance)
+            {
+                return Some((id.clone(), max_object_size, write_end));
+            }
+        }
+    }
+    None
+}
+
+/// Check all stores in the function for writes behind the end of heap objects.
+/// Returns the first offending store for each overflowed heap object.
+fn check_stores<'a>(
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+) -> Vec<HeapOverflow<'a>> {
+    let get_overflow_tolerance = |id: &AbstractIdentifier| {
+        pointer_inference
+            .get_heap_object_type(id)
+            .overflow_tolerance()
+    };
+    let mut overflowed_objects = BTreeSet::new();
+    let mut overflows = Vec::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            if let Def::Store { address, value } = &def.term {
+                let overflow = pointer_inference
+                    .get_state_at_tid(&def.tid)
+                    .and_then(|state| {
+                        get_overflowed_heap_object(
+                            &state,
+                            address,
+                            value.bytesize(),
+                            get_overflow_tolerance,
+                        )
+                    });
+                if let Some((object_id, object_size, write_end)) = overflow {
+                    if overflowed_objects.insert(object_id.clone()) {
+                        overflows.push(HeapOverflow {
+                            def,
+                            object_id,
+                            object_size,
+                            write_end,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    overflows
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    overflow: &HeapOverflow,
+    object_type: HeapObjectType,
+) -> CweWarning {
+    let allocation_site = overflow.object_id.get_tid();
+    let mut other = vec![
+        vec!["object_size".to_string(), overflow.object_size.to_string()],
+        vec!["write_end".to_string(), overflow.write_end.to_string()],
+    ];
+    if object_type != HeapObjectType::Unknown {
+        other.push(vec!["object_type".to_string(), object_type.to_string()]);
+    }
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Heap-based Buffer Overflow) Write at {} in {} may exceed the heap object of size {} allocated at {}",
+            overflow.def.tid.address, sub.term.display_name(), overflow.object_size, allocation_site.address
+        ),
+    )
+    .tids(vec![
+        format!("{}", overflow.def.tid),
+        format!("{}", allocation_site),
+    ])
+    .addresses(vec![
+        overflow.def.tid.address.clone(),
+        allocation_site.address.clone(),
+    ])
+    .other(other)
+    .root_cause(&overflow.object_id)
+}
+
+/// Run the CWE check.
+/// For each store we check whether it may write behind the end of a heap object of known size.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for overflow in check_stores(pointer_inference, sub) {
+            let object_type = pointer_inference.get_heap_object_type(&overflow.object_id);
+            cwe_warnings.push(generate_cwe_warning(sub, &overflow, object_type));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+    use crate::analysis::pointer_inference::ValueDomain;
+
+    #[test]
+    fn heap_overflows() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let rdi = Variable::mock("RDI", 8);
+        let address = Expression::Var(rdi.clone());
+        let heap_id = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        state.memory.add_abstract_object(
+            heap_id.clone(),
+            Bitvector::from_i64(0).into(),
+            ObjectType::Heap,
+            ByteSize::new(8),
+        );
+        state
+            .memory
+            .set_object_size(&heap_id, Bitvector::from_i64(0x10).into());
+        let set_pointer = |state: &mut State, start: i64, end: i64| {
+            let offset = ValueDomain::new(Bitvector::from_i64(start), Bitvector::from_i64(end));
+            state.set_register(&rdi, PointerDomain::new(heap_id.clone(), offset).into());
+        };
+        let no_tolerance = |_: &AbstractIdentifier| 0;
+
+        set_pointer(&mut state, 0, 8);
+        assert!(
+            get_overflowed_heap_object(&state, &address, ByteSize::new(8), no_tolerance).is_none()
+        );
+        set_pointer(&mut state, 0, 0x10);
+        assert_eq!(
+            get_overflowed_heap_object(&state, &address, ByteSize::new(8), no_tolerance),
+            Some((heap_id.clone(), 0x10, 0x18))
+        );
+        // Off-by-one writes are tolerated for byte buffers.
+        set_pointer(&mut state, 0, 0x10);
+        let byte_buffer_tolerance =
+            |_: &AbstractIdentifier| HeapObjectType::ByteBuffer.overflow_tolerance();
+        assert!(get_overflowed_heap_object(
+            &state,
+            &address,
+            ByteSize::new(1),
+            byte_buffer_tolerance
+        )
+        .is_none());
+
+        // Objects of unknown size are not checked.
+        state
+            .memory
+            .set_object_size(&heap_id, ValueDomain::new_top(ByteSize::new(8)));
+        set_pointer(&mut state, 0, 0x100);
+        assert!(
+            get_overflowed_heap_object(&state, &address, ByteSize::new(8), no_tolerance).is_none()
+        );
+    }
+}
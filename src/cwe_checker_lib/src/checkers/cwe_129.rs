@@ -0,0 +1,893 @@
+//! This module implements a check for CWE-129: Improper Validation of Array Index.
+//!
+//! If an index derived from untrusted input is used to access an array without checking it against the array bounds,
+//! an attacker can read or write memory outside of the array.
+//!
+//! See <https://cwe.mitre.org/data/definitions/129.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each function we run a simple intraprocedural taint analysis.
+//! Taint sources are the return values of the functions configured as `return_value_sources` in config.json
+//! (e.g. `atoi`) and the memory pointed to by the parameters configured as `parameter_sources` (e.g. `scanf`).
+//! The return values of the integer parsing functions configured as `parsing_functions` (e.g. `strtoll`)
+//! are tainted if the string that they parse is tainted.
+//! Global variables and memory ranges configured as `memory_sources` (e.g. DMA buffers or NVRAM settings in firmware)
+//! are tainted at the start of each function.
+//! In a multi-binary session (see [`ipc_contracts`](crate::utils::ipc_contracts)) the data received by consumer functions
+//! on channels produced by other binaries of the session is tainted as well.
+//! The warnings of functions containing such calls list the producer calls as their `ipc_origins`.
+//! Taint is propagated through registers and through stack and heap memory cells
+//! whose addresses are known through the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! When a tainted index is multiplied by a constant or shifted by a constant, the stride of the array access is recognized.
+//!
+//! For each load or store through an address computed from a tainted index and a known base pointer
+//! we use the interval domain of the Pointer Inference analysis to determine the possible offsets of the access.
+//! A warning is generated if the offset into the target object is unbounded,
+//! i.e. the index was not range-checked after parsing it (or it can take at least 2^31 values),
+//! or if the access may exceed the stack frame of the function
+//! or the end of a dynamic stack allocation (`alloca` or variable-length array) in the stack frame.
+//! Warnings for accesses to heap objects contain the [inferred type](crate::analysis::pointer_inference::object_types)
+//! of the object as `object_type` (e.g. `pointer_array` or `byte_buffer`) to help with triage.
+//!
+//! ## False Positives
+//!
+//! - Bounds checks that the interval domain cannot represent (e.g. checks on values in untracked memory) are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - The taint analysis is intraprocedural.
+//!   Indices passed as parameters or returned from internal functions are not tracked.
+//! - Indices bounded by a check against a bound larger than the array are only detected for stack arrays
+//!   if the access exceeds the stack frame.
+//! - Accesses through base pointers unknown to the Pointer Inference analysis are not checked.
+
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier, TryToBitvec, TryToInterval};
+use crate::analysis::pointer_inference::object_types::HeapObjectType;
+use crate::analysis::pointer_inference::{Data, PointerInference, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::ipc_contracts::{get_channel_name, IpcSource};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE129",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions whose return values are untrusted.
+    return_value_sources: Vec<String>,
+    /// Functions that write untrusted values to the memory pointed to by the parameters with the given indices.
+    parameter_sources: BTreeMap<String, Vec<usize>>,
+    /// Functions parsing an integer from the string pointed to by the parameter with the given index.
+    /// Their return values are untrusted if the string is untrusted.
+    #[serde(default)]
+    parsing_functions: BTreeMap<String, usize>,
+    /// Global memory regions whose content is untrusted.
+    #[serde(default)]
+    memory_sources: Vec<MemorySource>,
+    /// Consumer functions receiving untrusted data from other binaries of a multi-binary session.
+    /// Filled in from the session file by the caller, not meant to be configured manually.
+    #[serde(default)]
+    ipc_sources: Vec<IpcSource>,
+}
+
+/// A global variable or memory range whose content is untrusted.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MemorySource {
+    /// The name of a global variable in the symbol table of the binary.
+    #[serde(default)]
+    symbol: Option<String>,
+    /// The start address of the memory range as a hexadecimal string.
+    /// Takes precedence over the symbol.
+    #[serde(default)]
+    address: Option<String>,
+    /// The size of the memory range in bytes.
+    /// If not provided for a symbol, the size from the symbol table is used.
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+impl MemorySource {
+    /// Compute the address range `[start, end)` of the memory source.
+    /// The `address_base_offset` is added to addresses found in the symbol table
+    /// to align them with the addresses used in the program representation.
+    fn get_address_range(
+        &self,
+        object_symbols: &[(String, u64, u64)],
+        address_base_offset: u64,
+    ) -> Result<(u64, u64), Error> {
+        let (start, symbol_size) = match (&self.address, &self.symbol) {
+            (Some(address), _) => (
+                u64::from_str_radix(address.trim_start_matches("0x"), 16)?,
+                None,
+            ),
+            (None, Some(symbol)) => {
+                let (_, address, size) = object_symbols
+                    .iter()
+                    .find(|(name, _, _)| name == symbol)
+                    .ok_or_else(|| anyhow!("Symbol {} not found.", symbol))?;
+                (address + address_base_offset, Some(*size))
+            }
+            (None, None) => return Err(anyhow!("Memory source without symbol or address.")),
+        };
+        let size = self
+            .size
+            .or(symbol_size)
+            .ok_or_else(|| anyhow!("Size of memory source at {:x} unknown.", start))?;
+        Ok((start, start + std::cmp::max(size, 1)))
+    }
+}
+
+/// The taint state of a function at a program point.
+///
+/// Tainted values are mapped to the stride recognized for them,
+/// i.e. the factor by which the original untrusted value was multiplied.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub(crate) struct TaintState {
+    /// Tainted registers.
+    registers: HashMap<Variable, u64>,
+    /// Tainted memory cells, given by the abstract object and the offset into it.
+    memory: HashMap<(AbstractIdentifier, i64), u64>,
+    /// Tainted global memory ranges `[start, end)` with stride 1.
+    global_memory: Vec<(u64, u64)>,
+}
+
+impl TaintState {
+    /// Compute the stride of an expression if it is tainted.
+    pub(crate) fn eval(&self, expression: &Expression) -> Option<u64> {
+        use Expression::*;
+        match expression {
+            Var(var) => self.registers.get(var).copied(),
+            Const(_) | Unknown { .. } => None,
+            BinOp { op, lhs, rhs } => match (self.eval(lhs), self.eval(rhs)) {
+                (Some(stride), None) | (None, Some(stride)) => {
+                    let constant = match (op, lhs.as_ref(), rhs.as_ref()) {
+                        (_, Const(constant), _) | (_, _, Const(constant)) => {
+                            constant.try_to_u64().ok()
+                        }
+                        _ => None,
+                    };
+                    match (op, constant) {
+                        (BinOpType::IntMult, Some(factor)) => Some(stride.saturating_mul(factor)),
+                        (BinOpType::IntLeft, Some(shift)) if shift < 64 => {
+                            Some(stride.saturating_mul(1 << shift))
+                        }
+                        _ => Some(stride),
+                    }
+                }
+                (Some(stride), Some(_)) => Some(stride),
+                (None, None) => None,
+            },
+            UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => self.eval(arg),
+        }
+    }
+
+    /// Merge the other state into `self`.
+    /// Returns `true` if `self` changed.
+    fn merge_with(&mut self, other: &TaintState) -> bool {
+        let mut changed = false;
+        for (var, stride) in other.registers.iter() {
+            if !self.registers.contains_key(var) {
+                self.registers.insert(var.clone(), *stride);
+                changed = true;
+            }
+        }
+        for (location, stride) in other.memory.iter() {
+            if !self.memory.contains_key(location) {
+                self.memory.insert(location.clone(), *stride);
+                changed = true;
+            }
+        }
+        for range in other.global_memory.iter() {
+            if !self.global_memory.contains(range) {
+                self.global_memory.push(*range);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns `true` if the global address is contained in a tainted global memory range.
+    fn is_tainted_global(&self, address: &Data) -> bool {
+        if let Data::Value(value) = address {
+            if let Some(address) = value
+                .try_to_bitvec()
+                .ok()
+                .and_then(|address| address.try_to_u64().ok())
+            {
+                return self
+                    .global_memory
+                    .iter()
+                    .any(|(start, end)| *start <= address && address < *end);
+            }
+        }
+        false
+    }
+
+    /// Load the taint of the memory cell that the address points to.
+    pub(crate) fn load(&self, address: &Data) -> Option<u64> {
+        if self.is_tainted_global(address) {
+            return Some(1);
+        }
+        if let Data::Pointer(pointer) = address {
+            for (id, offset) in pointer.targets() {
+                if let Ok(offset) = offset.try_to_bitvec() {
+                    let location = (id.clone(), offset.try_to_i64().ok()?);
+                    if let Some(stride) = self.memory.get(&location) {
+                        return Some(*stride);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Set the taint of the memory cells that the address may point to.
+    /// Taints are only removed if the address has exactly one target.
+    /// Taints of global memory are never removed.
+    fn store(&mut self, address: &Data, taint: Option<u64>, size: ByteSize) {
+        if let (Some(_), Data::Value(value)) = (taint, address) {
+            if let Some(start) = value
+                .try_to_bitvec()
+                .ok()
+                .and_then(|address| address.try_to_u64().ok())
+            {
+                if !self.is_tainted_global(address) {
+                    self.global_memory.push((start, start + u64::from(size)));
+                }
+            }
+        }
+        if let Data::Pointer(pointer) = address {
+            let is_unique_target = pointer.targets().len() == 1;
+            for (id, offset) in pointer.targets() {
+                if let Some(offset) = offset
+                    .try_to_bitvec()
+                    .ok()
+                    .and_then(|offset| offset.try_to_i64().ok())
+                {
+                    let location = (id.clone(), offset);
+                    match taint {
+                        Some(stride) => {
+                            self.memory.insert(location, stride);
+                        }
+                        None if is_unique_target => {
+                            self.memory.remove(&location);
+                        }
+                        None => (),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Indices that can take at least this many values are treated as not range-checked.
+/// This corresponds to the range of an `int` parsed from an untrusted string.
+const UNCHECKED_INDEX_RANGE_SIZE: i128 = 1 << 31;
+
+/// Returns `true` if the offset interval of an access through an index with the given stride
+/// is too large for the index to be range-checked.
+fn is_unchecked_offset_interval(start: i64, end: i64, stride: u64) -> bool {
+    (i128::from(end) - i128::from(start)) / i128::from(std::cmp::max(stride, 1)) + 1
+        >= UNCHECKED_INDEX_RANGE_SIZE
+}
+
+/// A detected access through an unchecked tainted index.
+struct TaintedAccess<'a> {
+    /// The `Def` accessing memory.
+    def: &'a Term<Def>,
+    /// The recognized stride of the index.
+    stride: u64,
+    /// The possible offsets of the access into the target object.
+    /// `None` if the offsets are unbounded, i.e. the index was not range-checked.
+    offset_bounds: Option<(i64, i64)>,
+    /// The target object of the access.
+    object_id: AbstractIdentifier,
+    /// Whether the access targets a dynamic stack allocation instead of the rest of the stack frame.
+    is_stack_allocation: bool,
+}
+
+/// The context of the intraprocedural taint analysis.
+///
+/// Other checks can reuse the taint sources and the taint propagation of this check
+/// through [`Context::get_taint_states_at_calls`].
+pub(crate) struct Context<'a> {
+    project: &'a Project,
+    pointer_inference: &'a PointerInference<'a>,
+    config: Config,
+    extern_symbols: HashMap<&'a Tid, &'a ExternSymbol>,
+    /// The taint state at the start of each function, containing the configured memory sources.
+    entry_state: TaintState,
+}
+
+impl<'a> Context<'a> {
+    /// Check a memory access through the given address for an unchecked tainted index.
+    fn check_access(
+        &self,
+        pi_state: &PointerInferenceState,
+        taint_state: &TaintState,
+        def: &'a Term<Def>,
+        address: &Expression,
+        access_size: ByteSize,
+    ) -> Option<TaintedAccess<'a>> {
+        let stride = taint_state.eval(address)?;
+        let pointer = match pi_state.eval(address) {
+            Data::Pointer(pointer) => pointer,
+            _ => return None,
+        };
+        for (id, offset) in pointer.targets() {
+            let offset_interval = offset.try_to_offset_interval().ok();
+            let is_unchecked = match offset_interval {
+                Some((start, end)) => is_unchecked_offset_interval(start, end, stride),
+                None => offset.is_top(),
+            };
+            if is_unchecked {
+                return Some(TaintedAccess {
+                    def,
+                    stride,
+                    offset_bounds: None,
+                    object_id: id.clone(),
+                    is_stack_allocation: false,
+                });
+            }
+            if let Some((start, end)) = offset_interval {
+                let access_end = end + u64::from(access_size) as i64;
+                let (exceeds_object, is_stack_allocation) = if *id != pi_state.stack_id {
+                    (false, false)
+                } else if let Some((_, allocation)) = pi_state.get_stack_allocation(offset) {
+                    (access_end > allocation.end, true)
+                } else {
+                    (access_end > 0, false)
+                };
+                if exceeds_object {
+                    return Some(TaintedAccess {
+                        def,
+                        stride,
+                        offset_bounds: Some((start, end)),
+                        object_id: id.clone(),
+                        is_stack_allocation,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Apply the effects of the `Def`s of the block to the taint state
+    /// and collect the accesses through unchecked tainted indices.
+    fn handle_defs(
+        &self,
+        block: &'a Term<Blk>,
+        pi_state: &mut PointerInferenceState,
+        taint_state: &mut TaintState,
+        accesses: &mut BTreeMap<Tid, TaintedAccess<'a>>,
+    ) {
+        let runtime_memory_image = self.pointer_inference.get_context().runtime_memory_image;
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => {
+                    match taint_state.eval(value) {
+                        Some(stride) => taint_state.registers.insert(var.clone(), stride),
+                        None => taint_state.registers.remove(var),
+                    };
+                    if *var == self.project.stack_pointer_register {
+                        pi_state.handle_stack_pointer_assign(&def.tid, var, value);
+                    } else {
+                        pi_state.handle_register_assign(var, value);
+                    }
+                }
+                Def::Load { var, address } => {
+                    if let Some(access) =
+                        self.check_access(pi_state, taint_state, def, address, var.size)
+                    {
+                        accesses.insert(def.tid.clone(), access);
+                    }
+                    match taint_state.load(&pi_state.eval(address)) {
+                        Some(stride) => taint_state.registers.insert(var.clone(), stride),
+                        None => taint_state.registers.remove(var),
+                    };
+                    let _ = pi_state.handle_load(var, address, runtime_memory_image);
+                }
+                Def::Store { address, value } => {
+                    if let Some(access) =
+                        self.check_access(pi_state, taint_state, def, address, value.bytesize())
+                    {
+                        accesses.insert(def.tid.clone(), access);
+                    }
+                    taint_state.store(
+                        &pi_state.eval(address),
+                        taint_state.eval(value),
+                        value.bytesize(),
+                    );
+                    let _ = pi_state.handle_store(address, value, runtime_memory_image);
+                }
+            }
+        }
+    }
+
+    /// Compute the taint state after returning from a call.
+    ///
+    /// Taints of registers that are not callee-saved are removed.
+    /// If the called function is a taint source, its return registers
+    /// or the memory pointed to by the configured parameters get tainted.
+    fn handle_call(
+        &self,
+        pi_state: &PointerInferenceState,
+        taint_state: &TaintState,
+        target: Option<&Tid>,
+    ) -> TaintState {
+        let mut new_state = taint_state.clone();
+        let symbol = target.and_then(|target| self.extern_symbols.get(target));
+        let calling_convention = match symbol {
+            Some(symbol) => Some(symbol.get_calling_convention(self.project)),
+            None => self.project.get_standard_calling_convention(),
+        };
+        if let Some(calling_convention) = calling_convention {
+            new_state
+                .registers
+                .retain(|var, _| calling_convention.callee_saved_register.contains(&var.name));
+            if let Some(symbol) = symbol {
+                self.apply_taint_source(pi_state, &mut new_state, symbol, calling_convention);
+            }
+        }
+        new_state
+    }
+
+    /// Taint the values returned by the extern symbol if it is configured as a taint source.
+    fn apply_taint_source(
+        &self,
+        pi_state: &PointerInferenceState,
+        taint_state: &mut TaintState,
+        symbol: &ExternSymbol,
+        calling_convention: &CallingConvention,
+    ) {
+        if self.config.return_value_sources.contains(&symbol.name) {
+            for return_value in symbol.return_values.iter() {
+                if let Arg::Register(var) = return_value {
+                    taint_state.registers.insert(var.clone(), 1);
+                }
+            }
+        }
+        if let Some(index) = self.config.parsing_functions.get(&symbol.name) {
+            let is_tainted_string = self
+                .eval_parameter(pi_state, symbol, calling_convention, *index)
+                .map(|pointer| taint_state.load(&pointer).is_some())
+                .unwrap_or(false);
+            if is_tainted_string {
+                for return_value in symbol.return_values.iter() {
+                    if let Arg::Register(var) = return_value {
+                        taint_state.registers.insert(var.clone(), 1);
+                    }
+                }
+            }
+        }
+        if let Some(parameter_indices) = self.config.parameter_sources.get(&symbol.name) {
+            for index in parameter_indices {
+                if let Some(pointer) =
+                    self.eval_parameter(pi_state, symbol, calling_convention, *index)
+                {
+                    taint_state.store(&pointer, Some(1), ByteSize::new(1));
+                }
+            }
+        }
+        if let Some(source) = self.get_ipc_source(pi_state, symbol) {
+            if let Some(pointer) = self.eval_parameter(
+                pi_state,
+                symbol,
+                calling_convention,
+                source.consumer.data_parameter,
+            ) {
+                taint_state.store(&pointer, Some(1), ByteSize::new(1));
+            }
+        }
+    }
+
+    /// Get the IPC source corresponding to a call to the extern symbol
+    /// if the symbol is a consumer function called on a channel produced by another binary.
+    fn get_ipc_source(
+        &self,
+        pi_state: &PointerInferenceState,
+        symbol: &ExternSymbol,
+    ) -> Option<&IpcSource> {
+        let mut sources = self
+            .config
+            .ipc_sources
+            .iter()
+            .filter(|source| source.consumer.symbol == symbol.name)
+            .peekable();
+        let consumer = &sources.peek()?.consumer;
+        let channel = get_channel_name(
+            self.project,
+            self.pointer_inference.get_context().runtime_memory_image,
+            pi_state,
+            symbol,
+            consumer,
+        )?;
+        sources.find(|source| source.channel == channel)
+    }
+
+    /// Get the producer calls of other binaries sending the data received by consumer calls in the function.
+    fn get_ipc_origins(&self, sub: &Term<Sub>) -> Vec<String> {
+        let mut origins = Vec::new();
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                let symbol = match &jmp.term {
+                    Jmp::Call { target, .. } => match self.extern_symbols.get(target) {
+                        Some(symbol) => symbol,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                if let Some(source) = self
+                    .pointer_inference
+                    .get_state_at_jmp_tid(&jmp.tid)
+                    .and_then(|pi_state| self.get_ipc_source(pi_state, symbol))
+                {
+                    for origin in source.origins.iter() {
+                        if !origins.contains(origin) {
+                            origins.push(origin.clone());
+                        }
+                    }
+                }
+            }
+        }
+        origins
+    }
+
+    /// Evaluate the parameter with the given index of a call to the extern symbol.
+    ///
+    /// Parameters of variadic functions are not contained in the symbol,
+    /// so we fall back to the parameter registers of the calling convention.
+    fn eval_parameter(
+        &self,
+        pi_state: &PointerInferenceState,
+        symbol: &ExternSymbol,
+        calling_convention: &CallingConvention,
+        index: usize,
+    ) -> Option<Data> {
+        match symbol.parameters.get(index) {
+            Some(parameter) => pi_state
+                .eval_parameter_arg(
+                    parameter,
+                    &self.project.stack_pointer_register,
+                    self.pointer_inference.get_context().runtime_memory_image,
+                )
+                .ok(),
+            None => calling_convention
+                .parameter_register
+                .get(index)
+                .map(|register| {
+                    pi_state.eval(&Expression::Var(Variable {
+                        name: register.clone(),
+                        size: self.project.get_pointer_bytesize(),
+                        is_temp: false,
+                    }))
+                }),
+        }
+    }
+
+    /// Create the context of the taint analysis with the given configuration.
+    /// Memory sources whose address range cannot be determined are ignored with a log message.
+    pub(crate) fn new(
+        analysis_results: &'a AnalysisResults<'a>,
+        config: Config,
+    ) -> (Context<'a>, Vec<LogMessage>) {
+        let project = analysis_results.project;
+        let mut logs = Vec::new();
+        let mut entry_state = TaintState::default();
+        if !config.memory_sources.is_empty() {
+            let object_symbols =
+                crate::utils::binary::get_elf_object_symbols(analysis_results.binary)
+                    .unwrap_or_default();
+            for source in config.memory_sources.iter() {
+                match source
+                    .get_address_range(&object_symbols, project.program.term.address_base_offset)
+                {
+                    Ok(range) => entry_state.global_memory.push(range),
+                    Err(err) => logs.push(
+                        LogMessage::new_info(format!("Ignoring memory source: {}", err))
+                            .source(CWE_MODULE.name),
+                    ),
+                }
+            }
+        }
+        let context = Context {
+            project,
+            pointer_inference: analysis_results.pointer_inference().unwrap(),
+            config,
+            extern_symbols: project
+                .program
+                .term
+                .extern_symbols
+                .iter()
+                .map(|symbol| (&symbol.tid, symbol))
+                .collect(),
+            entry_state,
+        };
+        (context, logs)
+    }
+
+    /// Compute the taint states at the start of the blocks of the function
+    /// and collect the accesses through unchecked tainted indices.
+    fn compute_block_start_states(
+        &self,
+        sub: &'a Term<Sub>,
+        accesses: &mut BTreeMap<Tid, TaintedAccess<'a>>,
+    ) -> HashMap<&'a Tid, TaintState> {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, TaintState> = HashMap::new();
+        let mut worklist: Vec<&Tid> = Vec::new();
+        if let Some(first_block) = sub.term.blocks.first() {
+            block_states.insert(&first_block.tid, self.entry_state.clone());
+            worklist.push(&first_block.tid);
+        }
+        let mut in_worklist: HashSet<&Tid> = worklist.iter().copied().collect();
+        while let Some(block_tid) = worklist.pop() {
+            in_worklist.remove(block_tid);
+            let block = blocks[block_tid];
+            let mut pi_state = match self.pointer_inference.get_state_at_blk_start(block_tid) {
+                Some(pi_state) => pi_state.clone(),
+                None => continue,
+            };
+            let mut taint_state = block_states[block_tid].clone();
+            self.handle_defs(block, &mut pi_state, &mut taint_state, accesses);
+            for jmp in block.term.jmps.iter() {
+                let (successor, successor_state) = match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        (target, taint_state.clone())
+                    }
+                    Jmp::Call {
+                        target,
+                        return_: Some(return_target),
+                    } => (
+                        return_target,
+                        self.handle_call(&pi_state, &taint_state, Some(target)),
+                    ),
+                    Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => (
+                        return_target,
+                        self.handle_call(&pi_state, &taint_state, None),
+                    ),
+                    Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } => (return_target, taint_state.clone()),
+                    _ => continue,
+                };
+                if let Some(successor) = blocks.get(successor) {
+                    let changed = match block_states.get_mut(&successor.tid) {
+                        Some(state) => state.merge_with(&successor_state),
+                        None => {
+                            block_states.insert(&successor.tid, successor_state);
+                            true
+                        }
+                    };
+                    if changed && in_worklist.insert(&successor.tid) {
+                        worklist.push(&successor.tid);
+                    }
+                }
+            }
+        }
+        block_states
+    }
+
+    /// Run the taint analysis on the function and return the accesses through unchecked tainted indices.
+    fn analyze_sub(&self, sub: &'a Term<Sub>) -> Vec<TaintedAccess<'a>> {
+        let mut accesses = BTreeMap::new();
+        self.compute_block_start_states(sub, &mut accesses);
+        accesses.into_values().collect()
+    }
+
+    /// Run the taint analysis on the function
+    /// and return the taint states right before the execution of the calls in the function.
+    pub(crate) fn get_taint_states_at_calls(
+        &self,
+        sub: &'a Term<Sub>,
+    ) -> HashMap<&'a Tid, TaintState> {
+        let mut accesses = BTreeMap::new();
+        let block_states = self.compute_block_start_states(sub, &mut accesses);
+        let mut call_states = HashMap::new();
+        for block in sub.term.blocks.iter() {
+            let (mut pi_state, mut taint_state) = match (
+                self.pointer_inference.get_state_at_blk_start(&block.tid),
+                block_states.get(&block.tid),
+            ) {
+                (Some(pi_state), Some(taint_state)) => (pi_state.clone(), taint_state.clone()),
+                _ => continue,
+            };
+            self.handle_defs(block, &mut pi_state, &mut taint_state, &mut accesses);
+            for jmp in block.term.jmps.iter() {
+                if matches!(jmp.term, Jmp::Call { .. } | Jmp::CallInd { .. }) {
+                    call_states.insert(&jmp.tid, taint_state.clone());
+                }
+            }
+        }
+        call_states
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(sub: &Term<Sub>, access: &TaintedAccess) -> CweWarning {
+    let (description, offset_bounds) = match access.offset_bounds {
+        Some((start, end)) => (
+            if access.is_stack_allocation {
+                "may exceed the dynamic stack allocation"
+            } else {
+                "may exceed the stack frame"
+            },
+            format!("[{}, {}]", start, end),
+        ),
+        None => ("is not bounded", "unbounded".to_string()),
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Validation of Array Index) Memory access at {} in function {} uses an untrusted index that {}",
+            access.def.tid.address, sub.term.display_name(), description
+        ),
+    )
+    .tids(vec![format!("{}", access.def.tid)])
+    .addresses(vec![access.def.tid.address.clone()])
+    .other(vec![
+        vec!["stride".to_string(), access.stride.to_string()],
+        vec!["offset_bounds".to_string(), offset_bounds],
+        vec![
+            "range_checked".to_string(),
+            access.offset_bounds.is_some().to_string(),
+        ],
+    ])
+    .root_cause(&access.object_id)
+}
+
+/// Run the CWE check.
+/// For each function we check whether untrusted indices are used to access memory without a bounds check.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let (context, logs) = Context::new(analysis_results, config);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let accesses = context.analyze_sub(sub);
+        if accesses.is_empty() {
+            continue;
+        }
+        let ipc_origins = if context.config.ipc_sources.is_empty() {
+            Vec::new()
+        } else {
+            context.get_ipc_origins(sub)
+        };
+        for access in accesses {
+            let mut warning = generate_cwe_warning(sub, &access);
+            if !ipc_origins.is_empty() {
+                let mut origins = vec!["ipc_origins".to_string()];
+                origins.extend(ipc_origins.iter().cloned());
+                warning.other.push(origins);
+            }
+            let object_type = context
+                .pointer_inference
+                .get_heap_object_type(&access.object_id);
+            if object_type != HeapObjectType::Unknown {
+                warning
+                    .other
+                    .push(vec!["object_type".to_string(), object_type.to_string()]);
+            }
+            cwe_warnings.push(warning);
+        }
+    }
+    (logs, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stride_recognition() {
+        let index = Variable::mock("RAX", 8);
+        let base = Variable::mock("RBX", 8);
+        let mut state = TaintState::default();
+        state.registers.insert(index.clone(), 1);
+
+        let scaled_index = Expression::BinOp {
+            op: BinOpType::IntMult,
+            lhs: Box::new(Expression::Var(index.clone())),
+            rhs: Box::new(Expression::const_from_i64(4)),
+        };
+        let address = Expression::Var(base.clone()).plus(scaled_index);
+        assert_eq!(state.eval(&address), Some(4));
+
+        let shifted_index = Expression::BinOp {
+            op: BinOpType::IntLeft,
+            lhs: Box::new(Expression::Var(index.clone()).cast(CastOpType::IntSExt)),
+            rhs: Box::new(Expression::const_from_i64(3)),
+        };
+        assert_eq!(
+            state.eval(&Expression::Var(base.clone()).plus(shifted_index)),
+            Some(8)
+        );
+        assert_eq!(state.eval(&Expression::Var(base).plus_const(8)), None);
+    }
+
+    #[test]
+    fn unchecked_offset_intervals() {
+        // An `int` parsed by `atoi` and used as an index into an `int` array without a range check.
+        let int_range = (i64::from(i32::MIN), i64::from(i32::MAX));
+        assert!(is_unchecked_offset_interval(
+            int_range.0 * 4 - 0x40,
+            int_range.1 * 4 - 0x40,
+            4
+        ));
+        // The same index after checking `0 <= index < 100`.
+        assert!(!is_unchecked_offset_interval(-0x40, 99 * 4 - 0x40, 4));
+        assert!(is_unchecked_offset_interval(i64::MIN, i64::MAX, 0));
+    }
+
+    #[test]
+    fn memory_sources() {
+        let symbols = vec![("nvram_settings".to_string(), 0x1000, 0x100)];
+        let by_symbol = MemorySource {
+            symbol: Some("nvram_settings".to_string()),
+            address: None,
+            size: None,
+        };
+        assert_eq!(
+            by_symbol.get_address_range(&symbols, 0x10000).unwrap(),
+            (0x11000, 0x11100)
+        );
+        let by_address = MemorySource {
+            symbol: None,
+            address: Some("0x2000".to_string()),
+            size: Some(4),
+        };
+        assert_eq!(
+            by_address.get_address_range(&symbols, 0x10000).unwrap(),
+            (0x2000, 0x2004)
+        );
+        let unknown_size = MemorySource {
+            size: None,
+            ..by_address
+        };
+        assert!(unknown_size.get_address_range(&symbols, 0).is_err());
+
+        let mut state = TaintState::default();
+        state.global_memory.push((0x2000, 0x2004));
+        assert_eq!(
+            state.load(&Data::from(Bitvector::from_u64(0x2002))),
+            Some(1)
+        );
+        assert_eq!(state.load(&Data::from(Bitvector::from_u64(0x2004))), None);
+        state.store(
+            &Data::from(Bitvector::from_u64(0x3000)),
+            Some(4),
+            ByteSize::new(8),
+        );
+        assert_eq!(
+            state.load(&Data::from(Bitvector::from_u64(0x3004))),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,696 @@
+//! This module implements a check for CWE-131: Incorrect Calculation of Buffer Size.
+//!
+//! Buffer sizes are often computed from the size of the data that is later copied into the buffer.
+//! Typical mistakes are forgetting the terminating null byte of strings, as in `malloc(strlen(s))` followed by `strcpy`,
+//! or allocating `n * sizeof(T)` bytes for a buffer that is later written with `n * sizeof(T) + k` bytes.
+//! In both cases the copy writes behind the end of the allocated buffer.
+//!
+//! See <https://cwe.mitre.org/data/definitions/131.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the `allocation_functions` configured in config.json
+//! we compute a symbolic expression for the allocation size.
+//! It is obtained by following the [def-use chains](crate::analysis::def_use) of the size parameters backwards
+//! through register copies and additions, subtractions, multiplications and left shifts.
+//! Values loaded from memory cells (as identified by the [Pointer Inference analysis](crate::analysis::pointer_inference))
+//! are replaced by the value stored to the same cell if there is exactly one such store in the function.
+//! The leaves of the expression are the return values of calls, the values of registers at the start of the function
+//! and the values of memory cells that are not written in the function.
+//! Each expression is normalized to the sum of a symbolic term and a constant.
+//!
+//! Then we search the same function for calls to the configured `copy_functions` (e.g. `memcpy`)
+//! and `string_copy_functions` (e.g. `strcpy`) whose destination points into the allocated object.
+//! The size of a copy is the symbolic expression of its size parameter.
+//! The size of a string copy is `strlen(source) + 1` for each call to one of the `length_functions`
+//! in the function whose parameter has the same value as the source parameter of the string copy.
+//! If the symbolic terms of the allocation size and the copy size are equal
+//! and the constant of the copy size plus the offset of the destination into the object
+//! is larger than the constant of the allocation size, a warning is generated.
+//!
+//! ## False Positives
+//!
+//! - The value of a memory cell with exactly one store in the function may be changed by other functions
+//!   or through other pointers before it is loaded.
+//! - Calls to length functions on paths that do not reach the allocation are also matched with the string copies.
+//!
+//! ## False Negatives
+//!
+//! - Only allocations and copies in the same function are compared.
+//! - Sizes computed by other operations (e.g. divisions) or along several paths are not resolved.
+//! - Sizes stored in memory cells that are written more than once in the function are not resolved.
+//! - Overflows that depend on runtime values (e.g. copying `m` bytes into a buffer of size `n`) are not detected.
+
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier, AbstractLocation, TryToBitvec};
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE131",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The maximal number of definitions followed when computing a symbolic size.
+const MAX_RESOLUTION_DEPTH: usize = 16;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Maps allocation functions to the indices of their size parameters.
+    /// The allocation size is the product of the size parameters.
+    allocation_functions: BTreeMap<String, Vec<usize>>,
+    /// Functions copying a number of bytes given by a parameter into a destination buffer.
+    copy_functions: BTreeMap<String, CopyParameters>,
+    /// Functions copying a null-terminated string into a destination buffer.
+    #[serde(default)]
+    string_copy_functions: BTreeMap<String, StringCopyParameters>,
+    /// Functions returning the length of the string given as their first parameter, e.g. `strlen`.
+    #[serde(default)]
+    length_functions: Vec<String>,
+}
+
+/// The parameter indices of a copy function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CopyParameters {
+    /// The index of the destination parameter.
+    destination: usize,
+    /// The index of the parameter containing the number of copied bytes.
+    size: usize,
+}
+
+/// The parameter indices of a string copy function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StringCopyParameters {
+    /// The index of the destination parameter.
+    destination: usize,
+    /// The index of the source string parameter.
+    source: usize,
+}
+
+/// A symbolic term over the values of a function.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub(crate) enum SymbolicTerm {
+    /// The value of a register defined by the call with the given TID
+    /// or at the start of the function if the TID is `None`.
+    Register(Option<Tid>, Variable),
+    /// The value of a memory cell that is not written in the function.
+    MemoryCell(AbstractIdentifier, i64),
+    /// The sum of two terms.
+    Sum(Box<SymbolicTerm>, Box<SymbolicTerm>),
+    /// The product of two terms.
+    Product(Box<SymbolicTerm>, Box<SymbolicTerm>),
+    /// A term multiplied by a constant factor.
+    Scaled(Box<SymbolicTerm>, i64),
+}
+
+impl SymbolicTerm {
+    /// The sum of two terms. The summands are ordered to make the representation unique.
+    fn sum(lhs: SymbolicTerm, rhs: SymbolicTerm) -> SymbolicTerm {
+        if lhs <= rhs {
+            SymbolicTerm::Sum(Box::new(lhs), Box::new(rhs))
+        } else {
+            SymbolicTerm::Sum(Box::new(rhs), Box::new(lhs))
+        }
+    }
+
+    /// The product of two terms. The factors are ordered to make the representation unique.
+    fn product(lhs: SymbolicTerm, rhs: SymbolicTerm) -> SymbolicTerm {
+        if lhs <= rhs {
+            SymbolicTerm::Product(Box::new(lhs), Box::new(rhs))
+        } else {
+            SymbolicTerm::Product(Box::new(rhs), Box::new(lhs))
+        }
+    }
+
+    /// Multiply the term by a nonzero constant factor.
+    fn scale(self, factor: i64) -> Option<SymbolicTerm> {
+        match self {
+            _ if factor == 1 => Some(self),
+            SymbolicTerm::Scaled(term, old_factor) => {
+                Some(SymbolicTerm::Scaled(term, old_factor.checked_mul(factor)?))
+            }
+            term => Some(SymbolicTerm::Scaled(Box::new(term), factor)),
+        }
+    }
+}
+
+/// A symbolic size, i.e. the sum of an optional symbolic term and a constant.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct SymbolicSize {
+    /// The symbolic part of the size. `None` if the size is a constant.
+    pub(crate) term: Option<SymbolicTerm>,
+    /// The constant part of the size.
+    pub(crate) constant: i64,
+}
+
+impl SymbolicSize {
+    /// A constant size.
+    fn from_constant(constant: i64) -> SymbolicSize {
+        SymbolicSize {
+            term: None,
+            constant,
+        }
+    }
+
+    /// A size consisting only of the given term.
+    fn from_term(term: SymbolicTerm) -> SymbolicSize {
+        SymbolicSize {
+            term: Some(term),
+            constant: 0,
+        }
+    }
+
+    /// Add two sizes. Returns `None` if the constants overflow.
+    fn add(self, other: SymbolicSize) -> Option<SymbolicSize> {
+        let term = match (self.term, other.term) {
+            (Some(lhs), Some(rhs)) => Some(SymbolicTerm::sum(lhs, rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+        Some(SymbolicSize {
+            term,
+            constant: self.constant.checked_add(other.constant)?,
+        })
+    }
+
+    /// Multiply two sizes.
+    /// Returns `None` if the constants overflow
+    /// or if both sizes have symbolic terms and one of them has a nonzero constant.
+    fn multiply(self, other: SymbolicSize) -> Option<SymbolicSize> {
+        match (self.term, other.term) {
+            (None, None) => Some(SymbolicSize::from_constant(
+                self.constant.checked_mul(other.constant)?,
+            )),
+            (Some(term), None) => Self::scale(term, self.constant, other.constant),
+            (None, Some(term)) => Self::scale(term, other.constant, self.constant),
+            (Some(lhs), Some(rhs)) if self.constant == 0 && other.constant == 0 => {
+                Some(SymbolicSize::from_term(SymbolicTerm::product(lhs, rhs)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Multiply the size `term + constant` by the given factor.
+    fn scale(term: SymbolicTerm, constant: i64, factor: i64) -> Option<SymbolicSize> {
+        let constant = constant.checked_mul(factor)?;
+        if factor == 0 {
+            Some(SymbolicSize::from_constant(0))
+        } else {
+            Some(SymbolicSize {
+                term: Some(term.scale(factor)?),
+                constant,
+            })
+        }
+    }
+}
+
+/// Computes symbolic sizes of the values of a function.
+pub(crate) struct SizeResolver<'a> {
+    /// The def-use chains of the function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+    /// The memory cells written by the stores of the function, together with the stored values.
+    stores: Vec<((AbstractIdentifier, i64), &'a Tid, &'a Expression)>,
+}
+
+impl<'a> SizeResolver<'a> {
+    /// Create a new resolver for the given function.
+    pub(crate) fn new(
+        sub: &'a Term<Sub>,
+        def_use_chains: &'a DefUseChains<'a>,
+        pointer_inference: &'a PointerInference<'a>,
+    ) -> SizeResolver<'a> {
+        let mut resolver = SizeResolver {
+            def_use_chains,
+            pointer_inference,
+            stores: Vec::new(),
+        };
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                if let Def::Store { address, value } = &def.term {
+                    if let Some(cell) = resolver
+                        .pointer_inference
+                        .get_unique_memory_cell_at_tid(&def.tid, address)
+                    {
+                        resolver.stores.push((cell, &def.tid, value));
+                    }
+                }
+            }
+        }
+        resolver
+    }
+
+    /// Compute the symbolic size of the register `var` at its use in the term with the given TID.
+    pub(crate) fn resolve_register(
+        &self,
+        use_tid: &Tid,
+        var: &Variable,
+        depth: usize,
+    ) -> Option<SymbolicSize> {
+        if depth > MAX_RESOLUTION_DEPTH {
+            return None;
+        }
+        let def_tids: Vec<&Tid> = self.def_use_chains.defs_reaching(use_tid, var).collect();
+        match def_tids[..] {
+            [] => Some(SymbolicSize::from_term(SymbolicTerm::Register(
+                None,
+                var.clone(),
+            ))),
+            [def_tid] => match self.def_use_chains.get_definition(def_tid)? {
+                Definition::Def(Term {
+                    tid,
+                    term: Def::Assign { value, .. },
+                }) => self.resolve_expression(tid, value, depth + 1),
+                Definition::Def(Term {
+                    tid,
+                    term: Def::Load { address, .. },
+                }) => self.resolve_load(tid, address, depth + 1),
+                Definition::Def(_) => None,
+                Definition::Call(call) => Some(SymbolicSize::from_term(SymbolicTerm::Register(
+                    Some(call.tid.clone()),
+                    var.clone(),
+                ))),
+            },
+            _ => None,
+        }
+    }
+
+    /// Compute the symbolic size of the value loaded from the address by the load with the given TID.
+    fn resolve_load(&self, tid: &Tid, address: &Expression, depth: usize) -> Option<SymbolicSize> {
+        let cell = self
+            .pointer_inference
+            .get_unique_memory_cell_at_tid(tid, address)?;
+        let stores: Vec<_> = self
+            .stores
+            .iter()
+            .filter(|(store_cell, _, _)| *store_cell == cell)
+            .collect();
+        match stores[..] {
+            [] => Some(SymbolicSize::from_term(SymbolicTerm::MemoryCell(
+                cell.0, cell.1,
+            ))),
+            [(_, store_tid, value)] => self.resolve_expression(store_tid, value, depth),
+            _ => None,
+        }
+    }
+
+    /// Compute the symbolic size of the expression evaluated by the term with the given TID.
+    pub(crate) fn resolve_expression(
+        &self,
+        tid: &Tid,
+        expression: &Expression,
+        depth: usize,
+    ) -> Option<SymbolicSize> {
+        match expression {
+            Expression::Const(constant) => {
+                Some(SymbolicSize::from_constant(constant.try_to_i64().ok()?))
+            }
+            Expression::Var(var) => self.resolve_register(tid, var, depth),
+            Expression::BinOp { op, lhs, rhs } => {
+                let lhs = self.resolve_expression(tid, lhs, depth)?;
+                let rhs = self.resolve_expression(tid, rhs, depth)?;
+                match op {
+                    BinOpType::IntAdd => lhs.add(rhs),
+                    BinOpType::IntSub => lhs.add(rhs.multiply(SymbolicSize::from_constant(-1))?),
+                    BinOpType::IntMult => lhs.multiply(rhs),
+                    BinOpType::IntLeft => match rhs {
+                        SymbolicSize {
+                            term: None,
+                            constant: shift @ 0..=62,
+                        } => lhs.multiply(SymbolicSize::from_constant(1 << shift)),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Expression::Cast {
+                op: CastOpType::IntZExt,
+                arg,
+                ..
+            }
+            | Expression::Cast {
+                op: CastOpType::IntSExt,
+                arg,
+                ..
+            } => self.resolve_expression(tid, arg, depth),
+            Expression::Subpiece { low_byte, arg, .. } if *low_byte == ByteSize::new(0) => {
+                self.resolve_expression(tid, arg, depth)
+            }
+            _ => None,
+        }
+    }
+
+    /// Compute the symbolic size of the parameter with the given index of the call to the extern symbol.
+    fn resolve_parameter(
+        &self,
+        call: &Term<Jmp>,
+        symbol: &ExternSymbol,
+        index: usize,
+    ) -> Option<SymbolicSize> {
+        match symbol.parameters.get(index)? {
+            Arg::Register(var) => self.resolve_register(&call.tid, var, 0),
+            Arg::Stack { .. } => None,
+        }
+    }
+}
+
+/// Get the value of the parameter with the given index of a call to the extern symbol
+/// right before the call.
+fn get_parameter_value(
+    project: &Project,
+    analysis_results: &AnalysisResults,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    index: usize,
+) -> Option<Data> {
+    let state = analysis_results
+        .pointer_inference()?
+        .get_state_at_tid(&call.tid)?;
+    state
+        .eval_parameter_arg(
+            symbol.parameters.get(index)?,
+            &project.stack_pointer_register,
+            analysis_results.runtime_memory_image,
+        )
+        .ok()
+}
+
+/// Get the offset of the pointer into the object with the given identifier, if it is exactly known.
+fn get_offset_into_object(pointer: &Data, object_id: &AbstractIdentifier) -> Option<i64> {
+    match pointer {
+        Data::Pointer(pointer) => pointer
+            .targets()
+            .get(object_id)?
+            .try_to_bitvec()
+            .ok()?
+            .try_to_i64()
+            .ok(),
+        _ => None,
+    }
+}
+
+/// Return `true` if a copy of `copy_size` bytes to the given offset into an object of `allocation_size` bytes
+/// writes behind the end of the object.
+fn is_overflowing_copy(
+    allocation_size: &SymbolicSize,
+    copy_size: &SymbolicSize,
+    offset: i64,
+) -> bool {
+    allocation_size.term.is_some()
+        && allocation_size.term == copy_size.term
+        && offset.saturating_add(copy_size.constant) > allocation_size.constant
+}
+
+/// Generate the warning for a copy that writes behind the end of an allocated object.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    allocation_call: &Term<Jmp>,
+    allocation_symbol: &ExternSymbol,
+    copy_call: &Term<Jmp>,
+    copy_name: &str,
+    overflow: i64,
+) -> CweWarning {
+    let description = format!(
+        "(Incorrect Calculation of Buffer Size) The buffer allocated by {} at {} in {} is {} byte(s) too small for the data copied into it by {} at {}.",
+        allocation_symbol.name,
+        allocation_call.tid.address,
+        sub.term.display_name(),
+        overflow,
+        copy_name,
+        copy_call.tid.address
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![
+            allocation_call.tid.address.clone(),
+            copy_call.tid.address.clone(),
+        ])
+        .tids(vec![
+            allocation_call.tid.to_string(),
+            copy_call.tid.to_string(),
+        ])
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            allocation_symbol.name.clone(),
+            copy_name.to_string(),
+        ])
+        .other(vec![vec!["overflow".to_string(), overflow.to_string()]])
+}
+
+/// Run the CWE check.
+/// Compare the symbolic sizes of allocated objects with the symbolic sizes of copies into the objects
+/// and flag copies that write behind the end of the object.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let allocation_symbols: Vec<String> = config.allocation_functions.keys().cloned().collect();
+    let allocation_map = get_symbol_map(project, &allocation_symbols);
+    if allocation_map.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let copy_symbols: Vec<String> = config.copy_functions.keys().cloned().collect();
+    let copy_map = get_symbol_map(project, &copy_symbols);
+    let string_copy_symbols: Vec<String> = config.string_copy_functions.keys().cloned().collect();
+    let string_copy_map = get_symbol_map(project, &string_copy_symbols);
+    let length_map = get_symbol_map(project, &config.length_functions);
+
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let allocation_calls = get_callsites(sub, &allocation_map);
+        if allocation_calls.is_empty() {
+            continue;
+        }
+        let def_use_chains = analysis_results.def_use_chains(&sub.tid).unwrap();
+        let resolver = SizeResolver::new(sub, def_use_chains, pointer_inference);
+
+        // The sizes written by each copy, together with the copy destination.
+        let mut copies: Vec<(&Term<Jmp>, &str, Data, SymbolicSize)> = Vec::new();
+        for (_, call, symbol) in get_callsites(sub, &copy_map) {
+            let parameters = &config.copy_functions[&symbol.name];
+            if let (Some(destination), Some(size)) = (
+                get_parameter_value(
+                    project,
+                    analysis_results,
+                    call,
+                    symbol,
+                    parameters.destination,
+                ),
+                resolver.resolve_parameter(call, symbol, parameters.size),
+            ) {
+                copies.push((call, &symbol.name, destination, size));
+            }
+        }
+        let length_calls = get_callsites(sub, &length_map);
+        for (_, call, symbol) in get_callsites(sub, &string_copy_map) {
+            let parameters = &config.string_copy_functions[&symbol.name];
+            let (destination, source) = match (
+                get_parameter_value(
+                    project,
+                    analysis_results,
+                    call,
+                    symbol,
+                    parameters.destination,
+                ),
+                get_parameter_value(project, analysis_results, call, symbol, parameters.source),
+            ) {
+                (Some(destination), Some(source)) if !source.is_top() => (destination, source),
+                _ => continue,
+            };
+            for (_, length_call, length_symbol) in length_calls.iter() {
+                let return_register = match length_symbol.get_unique_return_register() {
+                    Ok(register) => register,
+                    Err(_) => continue,
+                };
+                if get_parameter_value(project, analysis_results, length_call, length_symbol, 0)
+                    == Some(source.clone())
+                {
+                    let size = SymbolicSize {
+                        term: Some(SymbolicTerm::Register(
+                            Some(length_call.tid.clone()),
+                            return_register.clone(),
+                        )),
+                        constant: 1,
+                    };
+                    copies.push((call, &symbol.name, destination.clone(), size));
+                }
+            }
+        }
+
+        for (_, allocation_call, allocation_symbol) in allocation_calls {
+            let object_id = match allocation_symbol.get_unique_return_register() {
+                Ok(register) => AbstractIdentifier::new(
+                    allocation_call.tid.clone(),
+                    AbstractLocation::from_var(register).unwrap(),
+                ),
+                Err(_) => continue,
+            };
+            let allocation_size = config.allocation_functions[&allocation_symbol.name]
+                .iter()
+                .try_fold(SymbolicSize::from_constant(1), |size, index| {
+                    size.multiply(resolver.resolve_parameter(
+                        allocation_call,
+                        allocation_symbol,
+                        *index,
+                    )?)
+                });
+            let allocation_size = match allocation_size {
+                Some(size) => size,
+                None => continue,
+            };
+            for (copy_call, copy_name, destination, copy_size) in copies.iter() {
+                let offset = match get_offset_into_object(destination, &object_id) {
+                    Some(offset) => offset,
+                    None => continue,
+                };
+                if is_overflowing_copy(&allocation_size, copy_size, offset) {
+                    cwe_warnings.push(generate_cwe_warning(
+                        sub,
+                        allocation_call,
+                        allocation_symbol,
+                        copy_call,
+                        copy_name,
+                        offset + copy_size.constant - allocation_size.constant,
+                    ));
+                    break;
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::HashSet;
+
+    fn register(name: &str) -> SymbolicSize {
+        SymbolicSize::from_term(SymbolicTerm::Register(None, Variable::mock(name, 8)))
+    }
+
+    fn constant(value: i64) -> SymbolicSize {
+        SymbolicSize::from_constant(value)
+    }
+
+    #[test]
+    fn symbolic_size_arithmetic() {
+        // (n + 1) * 8 == n * 8 + 8
+        let lhs = register("RDI")
+            .add(constant(1))
+            .unwrap()
+            .multiply(constant(8))
+            .unwrap();
+        let rhs = register("RDI")
+            .multiply(constant(4))
+            .unwrap()
+            .multiply(constant(2))
+            .unwrap()
+            .add(constant(8))
+            .unwrap();
+        assert_eq!(lhs, rhs);
+        assert_eq!(lhs.constant, 8);
+        // Sums and products do not depend on the order of their operands.
+        assert_eq!(
+            register("RDI").add(register("RSI")),
+            register("RSI").add(register("RDI"))
+        );
+        assert_eq!(
+            register("RDI").multiply(register("RSI")),
+            register("RSI").multiply(register("RDI"))
+        );
+        assert_eq!(
+            register("RDI")
+                .add(constant(1))
+                .unwrap()
+                .multiply(register("RSI")),
+            None
+        );
+        assert_eq!(register("RDI").multiply(constant(0)), Some(constant(0)));
+    }
+
+    #[test]
+    fn overflowing_copies() {
+        let allocation_size = register("RDI").multiply(constant(8)).unwrap();
+        let copy_size = allocation_size.clone().add(constant(8)).unwrap();
+        assert!(is_overflowing_copy(&allocation_size, &copy_size, 0));
+        assert!(!is_overflowing_copy(&allocation_size, &allocation_size, 0));
+        assert!(is_overflowing_copy(&allocation_size, &allocation_size, 1));
+        // Sizes with different symbolic terms are not comparable.
+        let copy_size = register("RSI")
+            .multiply(constant(8))
+            .unwrap()
+            .add(constant(8))
+            .unwrap();
+        assert!(!is_overflowing_copy(&allocation_size, &copy_size, 0));
+        // Constant sizes are handled by other checks.
+        assert!(!is_overflowing_copy(&constant(8), &constant(16), 0));
+    }
+
+    #[test]
+    fn resolve_symbolic_sizes() {
+        let project = Project::mock_empty();
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let mut sub = Sub::mock("func");
+        let mut block = Blk::mock();
+        block.term.defs = vec![
+            Def::assign(
+                "shift",
+                rax.clone(),
+                Expression::BinOp {
+                    op: BinOpType::IntLeft,
+                    lhs: Box::new(Expression::Var(rdi.clone())),
+                    rhs: Box::new(Expression::const_from_i64(3)),
+                },
+            ),
+            Def::assign(
+                "add",
+                rax.clone(),
+                Expression::Var(rax.clone()).plus_const(8),
+            ),
+        ];
+        block.term.jmps = vec![Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::Var(rax.clone())),
+        }];
+        sub.term.blocks = vec![block];
+        let def_use_chains = DefUseChains::new(&project, &sub);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let resolver = SizeResolver {
+            def_use_chains: &def_use_chains,
+            pointer_inference: &pointer_inference,
+            stores: Vec::new(),
+        };
+        assert_eq!(
+            resolver.resolve_register(&Tid::new("return"), &rax, 0),
+            register("RDI")
+                .multiply(constant(8))
+                .unwrap()
+                .add(constant(8))
+        );
+    }
+}
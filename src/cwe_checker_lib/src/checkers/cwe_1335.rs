@@ -0,0 +1,258 @@
+//! This module implements a check for CWE-1335: Incorrect Bitwise Shift of Integer.
+//!
+//! Shifting a value by an amount greater than or equal to its bit width (or by a negative amount)
+//! is undefined behavior in C.
+//! Depending on the architecture the shift amount is truncated or the result is zero,
+//! which frequently leads to wrong masks, e.g. in parsers computing `(1 << bits) - 1` for a field width read from the input.
+//!
+//! See <https://cwe.mitre.org/data/definitions/1335.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each left or right shift (`INT_LEFT`, `INT_RIGHT` and `INT_SRIGHT`) in the right hand side of a Def
+//! we use the [Pointer Inference analysis](crate::analysis::pointer_inference) to compute the value interval of the shift amount.
+//! If the interval may contain values greater than or equal to the bit width of the shifted operand, a warning is generated.
+//! Negative values are interpreted as unsigned values and thus also exceed the bit width.
+//!
+//! ## False Positives
+//!
+//! - The value intervals of shift amounts in loops may be overapproximated by the interval domain.
+//! - Checks that restrict the shift amount on all paths are only taken into account
+//!   if the Pointer Inference analysis can narrow the interval accordingly.
+//!
+//! ## False Negatives
+//!
+//! - Shift amounts about which nothing is known are not reported.
+//! - Many architectures (e.g. x86 or MIPS) mask the shift amount of their shift instructions.
+//!   The masked shift amount is always smaller than the bit width,
+//!   so that out-of-range shifts in the source code are not detected on these architectures.
+
+use crate::abstract_domain::{AbstractDomain, Interval, TryToInterval};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+use std::collections::HashSet;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE1335",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// Collect the shifted operands and the shift amounts of all shift operations contained in the expression.
+fn collect_shifts<'a>(
+    expression: &'a Expression,
+    shifts: &mut Vec<(&'a Expression, &'a Expression)>,
+) {
+    use Expression::*;
+    match expression {
+        BinOp { op, lhs, rhs } => {
+            if matches!(
+                op,
+                BinOpType::IntLeft | BinOpType::IntRight | BinOpType::IntSRight
+            ) {
+                shifts.push((lhs, rhs));
+            }
+            collect_shifts(lhs, shifts);
+            collect_shifts(rhs, shifts);
+        }
+        UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => collect_shifts(arg, shifts),
+        Var(_) | Const(_) | Unknown { .. } => (),
+    }
+}
+
+/// Check whether the interval of the shift amount may contain values
+/// greater than or equal to the given bit width when interpreted as unsigned values.
+fn may_exceed_width(amount: &Interval, bit_width: u64) -> bool {
+    // If both bounds are smaller than the bit width, they are non-negative
+    // and thus all values in between are smaller than the bit width, too.
+    [&amount.start, &amount.end].iter().any(|bound| {
+        bound
+            .try_to_u64()
+            .map(|bound| bound >= bit_width)
+            .unwrap_or(true)
+    })
+}
+
+/// Get the value interval of the shift amount according to the Pointer Inference analysis.
+/// Returns `None` if nothing is known about the shift amount.
+fn get_shift_amount(amount: &Data) -> Option<Interval> {
+    match amount {
+        Data::Value(value) if !value.is_top() => value.try_to_interval().ok(),
+        _ => None,
+    }
+}
+
+/// Format the interval of the shift amount for use in a warning.
+fn format_shift_amount(amount: &Interval) -> String {
+    let format_bound = |bound: &Bitvector| match bound.try_to_i64() {
+        Ok(bound) => bound.to_string(),
+        Err(_) => format!("{:#x}", bound),
+    };
+    if amount.start == amount.end {
+        format_bound(&amount.start)
+    } else {
+        format!(
+            "[{}, {}]",
+            format_bound(&amount.start),
+            format_bound(&amount.end)
+        )
+    }
+}
+
+/// Check all shifts in the function for shift amounts that may exceed the bit width of the shifted operand.
+/// Returns the Defs containing such shifts together with the bit width and the interval of the shift amount.
+fn check_sub<'a>(
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+) -> Vec<(&'a Term<Def>, u64, Interval)> {
+    let mut findings = Vec::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            let mut shifts = Vec::new();
+            match &def.term {
+                Def::Assign { value, .. } => collect_shifts(value, &mut shifts),
+                Def::Load { address, .. } => collect_shifts(address, &mut shifts),
+                Def::Store { address, value } => {
+                    collect_shifts(address, &mut shifts);
+                    collect_shifts(value, &mut shifts);
+                }
+            }
+            let state = match pointer_inference.get_state_at_tid(&def.tid) {
+                Some(state) if !shifts.is_empty() => state,
+                _ => continue,
+            };
+            for (operand, amount) in shifts {
+                let bit_width = operand.bytesize().as_bit_length() as u64;
+                match get_shift_amount(&state.eval(amount)) {
+                    Some(amount) if may_exceed_width(&amount, bit_width) => {
+                        findings.push((def, bit_width, amount));
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+    findings
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    def: &Term<Def>,
+    bit_width: u64,
+    amount: &Interval,
+) -> CweWarning {
+    let amount = format_shift_amount(amount);
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Incorrect Bitwise Shift of Integer) Shift at {} in {} shifts a {}-bit value by {}, which may be greater than or equal to its bit width",
+            def.tid.address, sub.term.display_name(), bit_width, amount
+        ),
+    )
+    .tids(vec![format!("{}", def.tid)])
+    .addresses(vec![def.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string()])
+    .other(vec![
+        vec!["bit_width".to_string(), bit_width.to_string()],
+        vec!["shift_amount".to_string(), amount],
+    ])
+}
+
+/// Run the CWE check.
+/// For each shift we check whether the shift amount may exceed the bit width of the shifted operand.
+/// At most one warning is generated per instruction,
+/// since the flags computed by a shift instruction often contain shifts by the same amount.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    let mut reported_addresses = HashSet::new();
+    for sub in project.program.term.subs.iter() {
+        for (def, bit_width, amount) in check_sub(pointer_inference, sub) {
+            if reported_addresses.insert(&def.tid.address) {
+                cwe_warnings.push(generate_cwe_warning(sub, def, bit_width, &amount));
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::SizedDomain;
+    use crate::analysis::pointer_inference::ValueDomain;
+
+    fn interval(start: i64, end: i64) -> Interval {
+        Interval::new(Bitvector::from_i64(start), Bitvector::from_i64(end))
+    }
+
+    #[test]
+    fn shift_collection() {
+        let rax = Expression::Var(Variable::mock("RAX", 8));
+        let rcx = Expression::Var(Variable::mock("RCX", 8));
+        let shift = Expression::BinOp {
+            op: BinOpType::IntLeft,
+            lhs: Box::new(Expression::const_from_i64(1)),
+            rhs: Box::new(rcx.clone()),
+        };
+        let expression = Expression::BinOp {
+            op: BinOpType::IntSRight,
+            lhs: Box::new(rax.clone()),
+            rhs: Box::new(shift.clone().plus_const(-1)),
+        };
+        let mut shifts = Vec::new();
+        collect_shifts(&expression, &mut shifts);
+        assert_eq!(shifts.len(), 2);
+        assert_eq!(shifts[0].0, &rax);
+        assert_eq!(shifts[1], (&Expression::const_from_i64(1), &rcx));
+    }
+
+    #[test]
+    fn shift_amounts() {
+        assert!(!may_exceed_width(&interval(0, 31), 32));
+        assert!(may_exceed_width(&interval(0, 32), 32));
+        assert!(!may_exceed_width(&interval(40, 40), 64));
+        // Negative shift amounts are huge when interpreted as unsigned values.
+        assert!(may_exceed_width(&interval(-1, 4), 64));
+
+        let amount = Data::Value(ValueDomain::new(
+            Bitvector::from_i64(8),
+            Bitvector::from_i64(40),
+        ));
+        assert_eq!(get_shift_amount(&amount), Some(interval(8, 40)));
+        assert_eq!(get_shift_amount(&Data::Top(ByteSize::new(8))), None);
+        assert_eq!(
+            get_shift_amount(&Data::Value(ValueDomain::new_top(ByteSize::new(8)))),
+            None
+        );
+
+        assert_eq!(format_shift_amount(&interval(40, 40)), "40");
+        assert_eq!(format_shift_amount(&interval(-1, 4)), "[-1, 4]");
+        let warning = generate_cwe_warning(
+            &Sub::mock("func"),
+            &Def::assign(
+                "shift",
+                Variable::mock("RAX", 8),
+                Expression::const_from_i64(0),
+            ),
+            32,
+            &interval(8, 40),
+        );
+        assert!(warning.description.ends_with(
+            "shifts a 32-bit value by [8, 40], which may be greater than or equal to its bit width"
+        ));
+    }
+}
@@ -0,0 +1,557 @@
+//! This module implements a check for CWE-134: Use of Externally-Controlled Format String.
+//!
+//! If an attacker controls the format string of a `printf`-like function,
+//! they can read from the stack with conversion specifiers like `%x` or `%s`
+//! and write to memory with the `%n` specifier.
+//! The `%n` conversion specifier makes `printf`-like functions write the number of characters printed so far
+//! to the memory that the corresponding argument points to.
+//! If an attacker can modify the format string or the pointer argument of a `%n` specifier,
+//! this yields a powerful write primitive.
+//!
+//! See <https://cwe.mitre.org/data/definitions/134.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a function configured in config.json we use the
+//! [Pointer Inference analysis](crate::analysis::pointer_inference) to determine the format string parameter.
+//! If it is a constant address of a string in the memory image of the binary,
+//! we parse the format string and classify its conversion specifiers.
+//! A warning with high severity is generated
+//! - if the format string contains `%n` and is located in writable memory,
+//!   i.e. it is not a read-only constant and may be modified at runtime,
+//! - or if the argument corresponding to a `%n` specifier of a read-only format string
+//!   is not a pointer to a memory object known to the Pointer Inference analysis or to writable global memory.
+//!   Such pointers may be controlled by an attacker.
+//!
+//! The argument corresponding to a specifier is located using the calling convention of the called function.
+//!
+//! Format strings that are not constant strings in read-only memory
+//! (see [`get_constant_string`](crate::analysis::string_abstraction::get_constant_string))
+//! are checked for being influenced by untrusted input:
+//! - A format string pointing to a memory object that a call to one of the `parameter_sources` (e.g. `fgets`)
+//!   in the same function writes input to is tainted.
+//! - A format string register whose value is the return value of one of the `return_value_sources` (e.g. `getenv`)
+//!   is tainted. The return value is followed through register copies along the
+//!   [def-use chains](crate::analysis::def_use) of the function.
+//!
+//! A warning with high severity is generated for tainted format strings.
+//!
+//! ## False Positives
+//!
+//! - Pointer arguments that the Pointer Inference analysis could not track are treated as untrusted.
+//! - The taint sources of a function are not ordered with respect to the call,
+//!   so input read into a buffer after it was used as a format string still taints the format string.
+//!
+//! ## False Negatives
+//!
+//! - Format strings that are not constant addresses (e.g. format strings built on the stack)
+//!   are only checked for `%n` specifiers if their content is known.
+//! - The taint analysis is intraprocedural.
+//!   Input read into a buffer in another function than the call is not tracked,
+//!   and neither is input copied to other buffers.
+//! - Functions taking a `va_list` instead of variadic arguments are not supported.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec, TryToInterval};
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference, State, ValueDomain};
+use crate::analysis::string_abstraction::get_constant_string;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::format_string::{parse_format_string, FormatSpecifier};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE134",
+    version: "0.2",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions taking a format string, mapped to the index of the format string parameter.
+    format_string_index: BTreeMap<String, usize>,
+    /// Functions that write untrusted input to the memory pointed to by the parameters with the given indices.
+    #[serde(default)]
+    parameter_sources: BTreeMap<String, Vec<usize>>,
+    /// Functions returning pointers to untrusted strings.
+    #[serde(default)]
+    return_value_sources: Vec<String>,
+}
+
+/// A `%n` specifier that may be abused by an attacker.
+enum Finding {
+    /// The format string containing the specifier is located in writable memory.
+    WritableFormatString {
+        /// The address of the format string.
+        address: u64,
+    },
+    /// The pointer argument of the specifier is not known to point to a valid target.
+    UntrustedPointer {
+        /// The index of the parameter corresponding to the specifier.
+        parameter_index: usize,
+    },
+    /// The format string is not constant and may contain untrusted input.
+    TaintedFormatString {
+        /// The call to the taint source.
+        source_call: Tid,
+        /// The name of the taint source.
+        source_symbol: String,
+    },
+}
+
+/// A memory location that a taint source writes untrusted input to.
+struct TaintedTarget<'a> {
+    /// The target object.
+    object_id: AbstractIdentifier,
+    /// The possible offsets of the target into the object.
+    offset: ValueDomain,
+    /// The call to the taint source.
+    source_call: &'a Tid,
+    /// The name of the taint source.
+    source_symbol: &'a str,
+}
+
+/// Return `true` if the two offsets into a memory object may be equal.
+fn offsets_may_overlap(offset: &ValueDomain, other_offset: &ValueDomain) -> bool {
+    match (
+        offset.try_to_offset_interval(),
+        other_offset.try_to_offset_interval(),
+    ) {
+        (Ok((start, end)), Ok((other_start, other_end))) => {
+            start <= other_end && other_start <= end
+        }
+        _ => true,
+    }
+}
+
+/// Collect the memory locations that calls to parameter taint sources in the function write untrusted input to.
+fn get_tainted_targets<'a>(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+    source_map: &HashMap<Tid, &'a ExternSymbol>,
+    config: &Config,
+) -> Vec<TaintedTarget<'a>> {
+    let runtime_memory_image = pointer_inference.get_context().runtime_memory_image;
+    let mut tainted_targets = Vec::new();
+    for (_block, jmp, symbol) in get_callsites(sub, source_map) {
+        let state = match pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+            Some(state) => state,
+            None => continue,
+        };
+        for parameter_index in config.parameter_sources[&symbol.name].iter() {
+            if let Ok(Data::Pointer(pointer)) = state.eval_parameter_arg(
+                &get_variadic_parameter(project, symbol, *parameter_index),
+                &project.stack_pointer_register,
+                runtime_memory_image,
+            ) {
+                for (object_id, offset) in pointer.targets() {
+                    tainted_targets.push(TaintedTarget {
+                        object_id: object_id.clone(),
+                        offset: offset.clone(),
+                        source_call: &jmp.tid,
+                        source_symbol: &symbol.name,
+                    });
+                }
+            }
+        }
+    }
+    tainted_targets
+}
+
+/// Follow the register `var` used by the term with the given TID backwards through register copies
+/// and return the call to a return value taint source defining its value if there is one.
+fn find_return_value_source<'a>(
+    def_use_chains: &DefUseChains<'a>,
+    extern_symbols: &HashMap<&Tid, &'a ExternSymbol>,
+    return_value_sources: &[String],
+    use_tid: &Tid,
+    var: &Variable,
+) -> Option<(&'a Tid, &'a ExternSymbol)> {
+    let mut worklist: Vec<(Tid, Variable)> = vec![(use_tid.clone(), var.clone())];
+    let mut visited_defs: HashSet<&Tid> = HashSet::new();
+    while let Some((use_tid, var)) = worklist.pop() {
+        for def_tid in def_use_chains.defs_reaching(&use_tid, &var) {
+            if !visited_defs.insert(def_tid) {
+                continue;
+            }
+            match def_use_chains.get_definition(def_tid) {
+                Some(Definition::Call(Term {
+                    tid,
+                    term: Jmp::Call { target, .. },
+                })) => {
+                    if let Some(symbol) = extern_symbols.get(target) {
+                        if return_value_sources.contains(&symbol.name) {
+                            return Some((tid, symbol));
+                        }
+                    }
+                }
+                Some(Definition::Def(Term {
+                    tid,
+                    term:
+                        Def::Assign {
+                            value: Expression::Var(input),
+                            ..
+                        },
+                })) => worklist.push((tid.clone(), input.clone())),
+                _ => (),
+            }
+        }
+    }
+    None
+}
+
+/// The taint sources of a function.
+struct TaintSources<'a> {
+    /// The memory locations that calls to parameter taint sources in the function write untrusted input to.
+    tainted_targets: Vec<TaintedTarget<'a>>,
+    /// The def-use chains of the function.
+    def_use_chains: Option<&'a DefUseChains<'a>>,
+    /// The extern symbols of the project, indexed by their TIDs.
+    extern_symbols: &'a HashMap<&'a Tid, &'a ExternSymbol>,
+    /// The names of the return value taint sources.
+    return_value_sources: &'a [String],
+}
+
+impl<'a> TaintSources<'a> {
+    /// Check whether the format string of the call is not constant and may contain untrusted input.
+    fn check_format_string(
+        &self,
+        project: &Project,
+        runtime_memory_image: &RuntimeMemoryImage,
+        state: &State,
+        jmp: &Term<Jmp>,
+        format_string_parameter: &Arg,
+    ) -> Option<Finding> {
+        let format_string = state
+            .eval_parameter_arg(
+                format_string_parameter,
+                &project.stack_pointer_register,
+                runtime_memory_image,
+            )
+            .ok()?;
+        if get_constant_string(&format_string, runtime_memory_image).is_some() {
+            return None;
+        }
+        if let Data::Pointer(pointer) = &format_string {
+            for (object_id, offset) in pointer.targets() {
+                if let Some(target) = self.tainted_targets.iter().find(|target| {
+                    target.object_id == *object_id && offsets_may_overlap(&target.offset, offset)
+                }) {
+                    return Some(Finding::TaintedFormatString {
+                        source_call: target.source_call.clone(),
+                        source_symbol: target.source_symbol.to_string(),
+                    });
+                }
+            }
+        }
+        if let (Arg::Register(var), Some(def_use_chains)) =
+            (format_string_parameter, self.def_use_chains)
+        {
+            if let Some((source_call, symbol)) = find_return_value_source(
+                def_use_chains,
+                self.extern_symbols,
+                self.return_value_sources,
+                &jmp.tid,
+                var,
+            ) {
+                return Some(Finding::TaintedFormatString {
+                    source_call: source_call.clone(),
+                    source_symbol: symbol.name.clone(),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Check the call to a format string function for abusable `%n` specifiers.
+fn check_call(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    symbol: &ExternSymbol,
+    format_string_index: usize,
+) -> Vec<Finding> {
+    let stack_register = &project.stack_pointer_register;
+    let eval_parameter = |index: usize| {
+        state.eval_parameter_arg(
+            &get_variadic_parameter(project, symbol, index),
+            stack_register,
+            runtime_memory_image,
+        )
+    };
+    let address = match eval_parameter(format_string_index)
+        .ok()
+        .and_then(|format_string| format_string.try_to_bitvec().ok())
+    {
+        Some(address) => address,
+        None => return Vec::new(),
+    };
+    let write_specifiers: Vec<FormatSpecifier> =
+        match runtime_memory_image.read_string_until_null_terminator(&address) {
+            Ok(format_string) => parse_format_string(format_string)
+                .into_iter()
+                .filter(|specifier| specifier.is_write())
+                .collect(),
+            Err(_) => return Vec::new(),
+        };
+    if write_specifiers.is_empty() {
+        return Vec::new();
+    }
+    if let (Ok(true), Ok(address)) = (
+        runtime_memory_image.is_address_writeable(&address),
+        address.try_to_u64(),
+    ) {
+        return vec![Finding::WritableFormatString { address }];
+    }
+    write_specifiers
+        .iter()
+        .filter_map(|specifier| {
+            let parameter_index = format_string_index + 1 + specifier.argument_index;
+            let pointer = eval_parameter(parameter_index).ok()?;
+            if is_trusted_pointer(runtime_memory_image, &pointer) {
+                None
+            } else {
+                Some(Finding::UntrustedPointer { parameter_index })
+            }
+        })
+        .collect()
+}
+
+/// A pointer is trusted if it points to a memory object known to the Pointer Inference analysis
+/// or if it is the address of writable global memory.
+fn is_trusted_pointer(runtime_memory_image: &RuntimeMemoryImage, pointer: &Data) -> bool {
+    match pointer {
+        Data::Pointer(_) => true,
+        Data::Value(_) => match pointer.try_to_bitvec() {
+            Ok(address) => matches!(
+                runtime_memory_image.is_address_writeable(&address),
+                Ok(true)
+            ),
+            Err(_) => false,
+        },
+        Data::Top(_) => false,
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(jmp: &Term<Jmp>, symbol: &ExternSymbol, finding: &Finding) -> CweWarning {
+    let (description, detail) = match finding {
+        Finding::WritableFormatString { address } => (
+            format!(
+                "Format string at {:#x} containing %n is located in writable memory",
+                address
+            ),
+            vec![
+                "format_string_address".to_string(),
+                format!("{:#x}", address),
+            ],
+        ),
+        Finding::UntrustedPointer { parameter_index } => (
+            format!(
+                "%n writes through the untrusted pointer in parameter {}",
+                parameter_index
+            ),
+            vec!["parameter_index".to_string(), parameter_index.to_string()],
+        ),
+        Finding::TaintedFormatString {
+            source_call,
+            source_symbol,
+        } => (
+            format!(
+                "Format string may contain untrusted input from the call to {} at {}",
+                source_symbol, source_call.address
+            ),
+            vec![
+                "taint_source".to_string(),
+                format!("{} at {}", source_symbol, source_call.address),
+            ],
+        ),
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Externally-Controlled Format String) {} in call to {} at {}",
+            description, symbol.name, jmp.tid.address
+        ),
+    )
+    .tids(vec![format!("{}", jmp.tid)])
+    .addresses(vec![jmp.tid.address.clone()])
+    .symbols(vec![symbol.name.clone()])
+    .other(vec![
+        vec!["severity".to_string(), "high".to_string()],
+        detail,
+    ])
+}
+
+/// Run the CWE check.
+/// For each call to a format string function we check for `%n` specifiers that may be abused by an attacker
+/// and for format strings that may contain untrusted input.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbols: Vec<String> = config.format_string_index.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbols);
+    let source_symbols: Vec<String> = config.parameter_sources.keys().cloned().collect();
+    let source_map = get_symbol_map(project, &source_symbols);
+    let extern_symbols: HashMap<&Tid, &ExternSymbol> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .map(|symbol| (&symbol.tid, symbol))
+        .collect();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let callsites = get_callsites(sub, &symbol_map);
+        if callsites.is_empty() {
+            continue;
+        }
+        let taint_sources = TaintSources {
+            tainted_targets: get_tainted_targets(
+                project,
+                pointer_inference,
+                sub,
+                &source_map,
+                &config,
+            ),
+            def_use_chains: analysis_results.def_use_chains(&sub.tid),
+            extern_symbols: &extern_symbols,
+            return_value_sources: &config.return_value_sources,
+        };
+        for (_block, jmp, symbol) in callsites {
+            if let Some(state) = pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                let format_string_index = config.format_string_index[&symbol.name];
+                let mut findings = check_call(
+                    project,
+                    analysis_results.runtime_memory_image,
+                    state,
+                    symbol,
+                    format_string_index,
+                );
+                findings.extend(taint_sources.check_format_string(
+                    project,
+                    analysis_results.runtime_memory_image,
+                    state,
+                    jmp,
+                    &get_variadic_parameter(project, symbol, format_string_index),
+                ));
+                for finding in findings {
+                    cwe_warnings.push(generate_cwe_warning(jmp, symbol, &finding));
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+
+    #[test]
+    fn untrusted_pointers() {
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let stack_id = AbstractIdentifier::new(
+            Tid::new("func"),
+            AbstractLocation::from_var(&Variable::mock("RSP", 8)).unwrap(),
+        );
+        let stack_pointer: Data =
+            PointerDomain::new(stack_id, Bitvector::from_i64(-8).into()).into();
+        assert!(is_trusted_pointer(&runtime_memory_image, &stack_pointer));
+        assert!(!is_trusted_pointer(
+            &runtime_memory_image,
+            &Data::new_top(ByteSize::new(8))
+        ));
+        assert!(!is_trusted_pointer(
+            &runtime_memory_image,
+            &Bitvector::from_u64(0x42).into()
+        ));
+    }
+
+    #[test]
+    fn return_value_sources() {
+        let mut project = Project::mock_empty();
+        let mut getenv = ExternSymbol::mock();
+        getenv.tid = Tid::new("getenv");
+        getenv.name = "getenv".to_string();
+        let mut printf = ExternSymbol::mock();
+        printf.tid = Tid::new("printf");
+        printf.name = "printf".to_string();
+        project.program.term.extern_symbols = vec![getenv, printf];
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let mut call_block = Blk::mock();
+        call_block.term.jmps = vec![Term {
+            tid: Tid::new("getenv_call"),
+            term: Jmp::Call {
+                target: Tid::new("getenv"),
+                return_: Some(Tid::new("return_block")),
+            },
+        }];
+        let mut return_block = Blk::mock();
+        return_block.tid = Tid::new("return_block");
+        return_block.term.defs = vec![Def::assign("copy", rdi.clone(), Expression::Var(rax))];
+        return_block.term.jmps = vec![Term {
+            tid: Tid::new("printf_call"),
+            term: Jmp::Call {
+                target: Tid::new("printf"),
+                return_: None,
+            },
+        }];
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![call_block, return_block];
+        let chains = DefUseChains::new(&project, &sub);
+        let extern_symbols: HashMap<&Tid, &ExternSymbol> = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| (&symbol.tid, symbol))
+            .collect();
+        let printf_call = Tid::new("printf_call");
+        let (source_call, symbol) = find_return_value_source(
+            &chains,
+            &extern_symbols,
+            &["getenv".to_string()],
+            &printf_call,
+            &rdi,
+        )
+        .unwrap();
+        assert_eq!(*source_call, Tid::new("getenv_call"));
+        assert_eq!(symbol.name, "getenv");
+        assert!(
+            find_return_value_source(&chains, &extern_symbols, &[], &printf_call, &rdi).is_none()
+        );
+    }
+
+    #[test]
+    fn offset_overlap() {
+        let offset = |start: i64, end: i64| -> ValueDomain {
+            ValueDomain::new(Bitvector::from_i64(start), Bitvector::from_i64(end))
+        };
+        assert!(offsets_may_overlap(&offset(-64, -64), &offset(-64, -32)));
+        assert!(!offsets_may_overlap(&offset(-64, -64), &offset(-32, -16)));
+        assert!(offsets_may_overlap(
+            &offset(0, 0),
+            &ValueDomain::new_top(ByteSize::new(8))
+        ));
+    }
+}
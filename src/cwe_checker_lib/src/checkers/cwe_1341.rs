@@ -0,0 +1,217 @@
+//! This module implements a check for CWE-1341: Multiple Releases of Same Resource or Handle.
+//!
+//! Closing a file descriptor or handle twice is undefined behaviour for handles like `FILE` streams.
+//! For plain file descriptors the second `close` may close an unrelated descriptor
+//! that another part of the program (or another thread) opened in the meantime and got the same number,
+//! so that subsequent reads and writes end up in the wrong file or socket.
+//!
+//! See <https://cwe.mitre.org/data/definitions/1341.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We reuse the intraprocedural descriptor analysis of the [CWE-775 check](super::cwe_775),
+//! which tracks the descriptors returned by the configured `acquisition_symbols` (e.g. `open` or `fopen`)
+//! through registers and through the stack frame of the function.
+//! The memory cells of the stack frame are determined with the [Pointer Inference analysis](crate::analysis::pointer_inference),
+//! analogous to how the Pointer Inference analysis tracks the states of heap objects to detect double frees.
+//! For each descriptor the analysis records the calls to the configured `release_symbols` (e.g. `close` or `fclose`)
+//! that release it on some path.
+//! Acquiring the descriptor again (e.g. in the next iteration of a loop) resets this record.
+//!
+//! A warning is generated for each call to a release symbol and descriptor
+//! that may already have been released on a path to the call.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - Descriptors are identified by the callsites of their acquisition.
+//!   If a descriptor variable is reassigned to the result of the same callsite on only some paths
+//!   (e.g. after a failed acquisition), the descriptors of different iterations may be confused.
+//!
+//! ## False Negatives
+//!
+//! - Descriptors are only tracked within the function acquiring them.
+//!   Descriptors passed to other functions or released in callees are not checked.
+//! - Descriptors stored outside of the stack frame of the function (e.g. in a struct on the heap) are not tracked.
+//! - Different handles to the same underlying descriptor (e.g. a `FILE` stream and the descriptor returned by `fileno`)
+//!   are not related to each other.
+
+use super::cwe_775::{DescriptorTracker, DoubleRelease};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE1341",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions returning a new descriptor or handle.
+    acquisition_symbols: Vec<String>,
+    /// Extern functions releasing the descriptor passed as the parameter with the given index.
+    release_symbols: BTreeMap<String, usize>,
+    /// Extern functions that may keep the descriptors passed to them as parameters.
+    #[serde(default)]
+    escaping_symbols: Vec<String>,
+}
+
+/// Generate the CWE warning for a descriptor that may be released twice.
+fn generate_cwe_warning(sub: &Term<Sub>, double_release: &DoubleRelease) -> CweWarning {
+    let previous_addresses: Vec<String> = double_release
+        .previous_releases
+        .iter()
+        .map(|tid| tid.address.clone())
+        .collect();
+    let description = format!(
+        "(Multiple Releases of Same Resource or Handle) The descriptor acquired at {} may be released again by {} at {} in {} after it was already released at {}",
+        double_release.descriptor.address,
+        double_release.symbol.name,
+        double_release.call.tid.address,
+        sub.term.display_name(),
+        previous_addresses.join(", ")
+    );
+    let mut addresses = vec![
+        double_release.call.tid.address.clone(),
+        double_release.descriptor.address.clone(),
+    ];
+    addresses.extend(previous_addresses);
+    let mut tids = vec![
+        double_release.call.tid.to_string(),
+        double_release.descriptor.to_string(),
+    ];
+    tids.extend(
+        double_release
+            .previous_releases
+            .iter()
+            .map(|tid| tid.to_string()),
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(addresses)
+        .tids(tids)
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            double_release.symbol.name.clone(),
+        ])
+}
+
+/// Run the CWE check.
+/// For each call to a release symbol we check whether the released descriptors may have been released before.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let tracker = DescriptorTracker::new(
+        project,
+        pointer_inference,
+        &config.acquisition_symbols,
+        &config.release_symbols,
+        &config.escaping_symbols,
+    );
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for double_release in tracker.find_double_releases(sub) {
+            cwe_warnings.push(generate_cwe_warning(sub, &double_release));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::{BTreeSet, HashSet};
+
+    fn mock_symbol(name: &str) -> ExternSymbol {
+        ExternSymbol {
+            tid: Tid::new(name),
+            name: name.to_string(),
+            ..ExternSymbol::mock()
+        }
+    }
+
+    #[test]
+    fn double_close() {
+        // The descriptor acquired in `entry` is closed in `first_close` and again in `second_close`.
+        // The descriptor acquired in `reopen` is closed once in `close_new`.
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with("entry", Vec::new(), Jmp::mock_call("open", "first_close")),
+            Blk::mock_with(
+                "first_close",
+                vec![
+                    Def::assign("save", rbp.clone(), Expression::Var(rax.clone())),
+                    Def::assign("arg_1", rdi.clone(), Expression::Var(rax.clone())),
+                ],
+                Jmp::mock_call("close", "second_close"),
+            ),
+            Blk::mock_with(
+                "second_close",
+                vec![Def::assign("arg_2", rdi.clone(), Expression::Var(rbp))],
+                Jmp::mock_call("close", "reopen"),
+            ),
+            Blk::mock_with("reopen", Vec::new(), Jmp::mock_call("open", "close_new")),
+            Blk::mock_with(
+                "close_new",
+                vec![Def::assign("arg_3", rdi, Expression::Var(rax))],
+                Jmp::mock_call("close", "exit"),
+            ),
+            Blk::mock_with(
+                "exit",
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ];
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![mock_symbol("open"), mock_symbol("close")];
+        project.program.term.subs = vec![sub];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let tracker = DescriptorTracker::new(
+            &project,
+            &pointer_inference,
+            &["open".to_string()],
+            &vec![("close".to_string(), 0)].into_iter().collect(),
+            &[],
+        );
+
+        let double_releases = tracker.find_double_releases(&project.program.term.subs[0]);
+        assert_eq!(double_releases.len(), 1);
+        assert_eq!(double_releases[0].call.tid, Tid::new("second_close_jmp"));
+        assert_eq!(double_releases[0].descriptor, Tid::new("entry_jmp"));
+        let previous_releases: BTreeSet<Tid> =
+            vec![Tid::new("first_close_jmp")].into_iter().collect();
+        assert_eq!(double_releases[0].previous_releases, previous_releases);
+
+        let warning = generate_cwe_warning(&project.program.term.subs[0], &double_releases[0]);
+        assert_eq!(
+            warning.tids,
+            vec![
+                "second_close_jmp".to_string(),
+                "entry_jmp".to_string(),
+                "first_close_jmp".to_string()
+            ]
+        );
+    }
+}
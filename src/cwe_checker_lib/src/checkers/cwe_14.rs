@@ -0,0 +1,664 @@
+//! This module implements a check for CWE-14: Compiler Removal of Code to Clear Buffers
+//! and CWE-244: Improper Clearing of Heap Memory Before Release ('Heap Inspection').
+//!
+//! Buffers holding secrets like passwords or derived keys should be wiped before they are freed
+//! or go out of scope, since the memory may be reused and read by other parts of the program.
+//! Wiping the buffer with `memset` is not enough:
+//! A `memset` whose result is never read again (e.g. right before `free`) is a dead store
+//! that compilers are allowed to remove.
+//! Functions like `explicit_bzero` or `memset_s` are guaranteed to not be optimized away.
+//!
+//! See <https://cwe.mitre.org/data/definitions/14.html> and <https://cwe.mitre.org/data/definitions/244.html>
+//! for detailed descriptions.
+//!
+//! ## How the check works
+//!
+//! The buffers receiving secrets are the memory objects owned by the function
+//! (i.e. its stack frame and heap objects allocated in it)
+//! that the configured `secret_parameters` of a call point to according to the [Pointer Inference analysis](crate::analysis::pointer_inference),
+//! e.g. the key buffer of `EVP_BytesToKey` or the password buffer of `readpassphrase`.
+//! Buffers also receive secrets from the configured `copy_symbols` (e.g. `strcpy`),
+//! if the source is a secret buffer or a pointer returned by one of the `secret_sources` (e.g. `getpass`).
+//! The latter is determined by the taint analysis of the [CWE-208 check](super::cwe_208).
+//!
+//! For each secret buffer we check the calls of the function to the configured `wipe_symbols` and `elidable_wipe_symbols`.
+//! - If the buffer is wiped by one of the `wipe_symbols` (e.g. `explicit_bzero`), it is assumed to be cleared.
+//! - If the buffer is only wiped by one of the `elidable_wipe_symbols` (e.g. `memset`) and goes out of scope afterwards,
+//!   i.e. it is freed in the function or lies on the stack, a CWE-14 warning is generated.
+//! - If the buffer is not wiped at all and goes out of scope, a CWE-244 warning is generated.
+//!
+//! Furthermore, a warning is generated if the buffer returned by a secret source is wiped by neither kind of function.
+//!
+//! ## False Positives
+//!
+//! - Secrets wiped by a callee of the function are not recognized as wiped.
+//! - A `memset` followed by further reads of the buffer is not removed by the compiler, but still flagged.
+//!
+//! ## False Negatives
+//!
+//! - The order of the calls is not checked, e.g. a buffer wiped before the secret is written to it counts as wiped.
+//! - Secrets copied with `Store` instructions instead of calls to the copy functions are not tracked.
+//! - Heap buffers that are not freed in the function allocating them are not checked.
+//! - Wipes of only a part of the buffer are not recognized.
+
+use super::cwe_208::{Context, TaintState};
+use super::secret_hygiene::is_term_in_sub;
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_variadic_parameter;
+use crate::CweModule;
+use std::collections::{btree_map::Entry, BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE14",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions returning pointers to secrets.
+    secret_sources: Vec<String>,
+    /// Functions writing secrets to the memory pointed to by the parameter with the given index.
+    #[serde(default)]
+    secret_parameters: BTreeMap<String, usize>,
+    /// Functions copying the memory pointed to by one parameter to the memory pointed to by another parameter.
+    #[serde(default)]
+    copy_symbols: BTreeMap<String, CopyParameters>,
+    /// Functions wiping the buffer given as their first parameter that are not removed by the compiler.
+    wipe_symbols: Vec<String>,
+    /// Functions wiping the buffer given as their first parameter that the compiler may remove.
+    #[serde(default)]
+    elidable_wipe_symbols: Vec<String>,
+}
+
+/// The parameters of a copy function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CopyParameters {
+    /// The index of the parameter pointing to the destination.
+    destination: usize,
+    /// The index of the parameter pointing to the source.
+    source: usize,
+}
+
+/// A memory cell given by the memory object and the offset into it.
+type Cell = (AbstractIdentifier, i64);
+
+/// The calls of a function that are relevant for the wiping of secret buffers.
+#[derive(Default)]
+struct WipeCalls<'a> {
+    /// The cells wiped by calls to functions that are not removed by the compiler.
+    wiped: HashSet<Cell>,
+    /// The cells wiped by calls to functions that the compiler may remove, mapped to the wiping call.
+    elidable_wiped: HashMap<Cell, &'a Term<Jmp>>,
+    /// The heap objects freed in the function, mapped to the freeing call.
+    freed: HashMap<AbstractIdentifier, &'a Term<Jmp>>,
+}
+
+/// A secret buffer that is not securely wiped before it goes out of scope.
+#[derive(Debug, PartialEq, Eq)]
+struct UnwipedSecret<'a> {
+    /// The call writing the secret to the buffer.
+    secret_call: &'a Term<Jmp>,
+    /// The memory cell of the buffer.
+    cell: Cell,
+    /// The call to a wiping function that the compiler may remove.
+    elidable_wipe: Option<&'a Term<Jmp>>,
+    /// The call freeing the buffer, if it is a heap object.
+    free: Option<&'a Term<Jmp>>,
+}
+
+/// Return the memory cells that the given parameter of the call points to
+/// and that are owned by the function of the call,
+/// i.e. cells of its stack frame and of the heap objects allocated in the function.
+fn get_owned_cells(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &PointerInferenceState,
+    sub: &Term<Sub>,
+    symbol: &ExternSymbol,
+    parameter_index: usize,
+) -> Vec<Cell> {
+    let pointer = match state.eval_parameter_arg(
+        &get_variadic_parameter(project, symbol, parameter_index),
+        &project.stack_pointer_register,
+        runtime_memory_image,
+    ) {
+        Ok(Data::Pointer(pointer)) => pointer,
+        _ => return Vec::new(),
+    };
+    pointer
+        .targets()
+        .iter()
+        .filter(|(id, _)| {
+            **id == state.stack_id
+                || (matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap)))
+                    && is_term_in_sub(sub, id.get_tid()))
+        })
+        .filter_map(|(id, offset)| {
+            let offset = offset.try_to_bitvec().ok()?.try_to_i64().ok()?;
+            Some((id.clone(), offset))
+        })
+        .collect()
+}
+
+/// Get the secret buffers of the function, mapped to the call writing the secret to them.
+fn get_secret_cells<'a>(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    context: &Context,
+    sub: &Term<Sub>,
+    config: &Config,
+    states: &[(
+        &'a Term<Jmp>,
+        &ExternSymbol,
+        PointerInferenceState,
+        TaintState,
+    )],
+) -> BTreeMap<Cell, &'a Term<Jmp>> {
+    let mut secrets = BTreeMap::new();
+    for (call, symbol, pi_state, _) in states.iter() {
+        if let Some(index) = config.secret_parameters.get(&symbol.name) {
+            for cell in
+                get_owned_cells(project, runtime_memory_image, pi_state, sub, symbol, *index)
+            {
+                secrets.entry(cell).or_insert(*call);
+            }
+        }
+    }
+    // Secrets may be copied several times, so we iterate until no new secret buffers are found.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (call, symbol, pi_state, taint_state) in states.iter() {
+            let parameters = match config.copy_symbols.get(&symbol.name) {
+                Some(parameters) => parameters,
+                None => continue,
+            };
+            let source = get_variadic_parameter(project, symbol, parameters.source);
+            let is_secret_source = context.is_secret_parameter(pi_state, taint_state, &source)
+                || get_owned_cells(
+                    project,
+                    runtime_memory_image,
+                    pi_state,
+                    sub,
+                    symbol,
+                    parameters.source,
+                )
+                .iter()
+                .any(|cell| secrets.contains_key(cell));
+            if !is_secret_source {
+                continue;
+            }
+            for cell in get_owned_cells(
+                project,
+                runtime_memory_image,
+                pi_state,
+                sub,
+                symbol,
+                parameters.destination,
+            ) {
+                if let Entry::Vacant(entry) = secrets.entry(cell) {
+                    entry.insert(*call);
+                    changed = true;
+                }
+            }
+        }
+    }
+    secrets
+}
+
+/// Get the secret buffers that are not securely wiped before they go out of scope.
+///
+/// Stack buffers go out of scope when the function returns.
+/// Heap buffers go out of scope when they are freed in the function.
+fn get_unwiped_secrets<'a>(
+    sub: &Term<Sub>,
+    secrets: &BTreeMap<Cell, &'a Term<Jmp>>,
+    wipe_calls: &WipeCalls<'a>,
+) -> Vec<UnwipedSecret<'a>> {
+    let mut unwiped_secrets = Vec::new();
+    for (cell, secret_call) in secrets.iter() {
+        if wipe_calls.wiped.contains(cell) {
+            continue;
+        }
+        let free = if *cell.0.get_tid() == sub.tid {
+            None
+        } else {
+            match wipe_calls.freed.get(&cell.0) {
+                Some(free) => Some(*free),
+                None => continue,
+            }
+        };
+        unwiped_secrets.push(UnwipedSecret {
+            secret_call,
+            cell: cell.clone(),
+            elidable_wipe: wipe_calls.elidable_wiped.get(cell).cloned(),
+            free,
+        });
+    }
+    unwiped_secrets
+}
+
+/// Generate the CWE warning for a secret buffer that is not securely wiped.
+fn generate_cwe_warning(sub: &Term<Sub>, secret: &UnwipedSecret) -> CweWarning {
+    let location = match secret.free {
+        Some(free) => format!(
+            "the heap buffer allocated at {} is freed at {}",
+            secret.cell.0.get_tid().address,
+            free.tid.address
+        ),
+        None => "the stack buffer goes out of scope".to_string(),
+    };
+    let description = match secret.elidable_wipe {
+        Some(wipe) => format!(
+            "(Compiler Removal of Code to Clear Buffers) The secret written at {} in {} is only cleared by the call at {}, which the compiler may remove since {} afterwards",
+            secret.secret_call.tid.address, sub.term.display_name(), wipe.tid.address, location
+        ),
+        None => format!(
+            "(Improper Clearing of Memory Before Release) The secret written at {} in {} is not cleared before {}",
+            secret.secret_call.tid.address, sub.term.display_name(), location
+        ),
+    };
+    let mut jmps = vec![secret.secret_call];
+    jmps.extend(secret.elidable_wipe);
+    jmps.extend(secret.free);
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .tids(jmps.iter().map(|jmp| jmp.tid.to_string()).collect())
+        .addresses(jmps.iter().map(|jmp| jmp.tid.address.clone()).collect())
+        .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Generate the CWE warning for the buffers returned by secret sources that are not wiped in the function.
+fn generate_secret_source_warning(
+    sub: &Term<Sub>,
+    source_calls: &[(&Term<Jmp>, &ExternSymbol)],
+) -> CweWarning {
+    let descriptions: Vec<String> = source_calls
+        .iter()
+        .map(|(call, symbol)| format!("{} at {}", symbol.name, call.tid.address))
+        .collect();
+    let mut symbols = vec![sub.term.display_name().to_string()];
+    symbols.extend(source_calls.iter().map(|(_, symbol)| symbol.name.clone()));
+    symbols.dedup();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Clearing of Memory Before Release) The secret returned by {} in {} is not cleared",
+            descriptions.join(", "),
+            sub.term.display_name()
+        ),
+    )
+    .tids(
+        source_calls
+            .iter()
+            .map(|(call, _)| call.tid.to_string())
+            .collect(),
+    )
+    .addresses(
+        source_calls
+            .iter()
+            .map(|(call, _)| call.tid.address.clone())
+            .collect(),
+    )
+    .symbols(symbols)
+}
+
+/// Run the CWE check.
+/// For each function we check whether the secret buffers owned by it are securely wiped before they go out of scope.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let deallocation_symbols = &pointer_inference.get_context().deallocation_symbols;
+    let context = Context::new(
+        project,
+        pointer_inference,
+        Vec::new(),
+        config.secret_sources.clone(),
+        BTreeMap::new(),
+        Vec::new(),
+    );
+
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let states = context.get_states_at_calls(sub);
+        let source_calls: Vec<(&Term<Jmp>, &ExternSymbol)> = states
+            .iter()
+            .filter(|(_, symbol, _, _)| config.secret_sources.contains(&symbol.name))
+            .map(|(call, symbol, _, _)| (*call, *symbol))
+            .collect();
+        let secrets = get_secret_cells(
+            project,
+            runtime_memory_image,
+            &context,
+            sub,
+            &config,
+            &states,
+        );
+        if secrets.is_empty() && source_calls.is_empty() {
+            continue;
+        }
+        let mut wipe_calls = WipeCalls::default();
+        let mut is_source_wiped = false;
+        for (call, symbol, pi_state, taint_state) in states.iter() {
+            let is_wipe = config.wipe_symbols.contains(&symbol.name);
+            let is_elidable_wipe = config.elidable_wipe_symbols.contains(&symbol.name);
+            if is_wipe || is_elidable_wipe {
+                is_source_wiped |= context.is_secret_parameter(
+                    pi_state,
+                    taint_state,
+                    &get_variadic_parameter(project, symbol, 0),
+                );
+                for cell in get_owned_cells(project, runtime_memory_image, pi_state, sub, symbol, 0)
+                {
+                    if is_wipe {
+                        wipe_calls.wiped.insert(cell);
+                    } else {
+                        wipe_calls.elidable_wiped.entry(cell).or_insert(*call);
+                    }
+                }
+            } else if deallocation_symbols.contains(&symbol.name) {
+                for (id, _) in
+                    get_owned_cells(project, runtime_memory_image, pi_state, sub, symbol, 0)
+                {
+                    wipe_calls.freed.entry(id).or_insert(*call);
+                }
+            }
+        }
+        for secret in get_unwiped_secrets(sub, &secrets, &wipe_calls) {
+            cwe_warnings.push(generate_cwe_warning(sub, &secret));
+        }
+        if !source_calls.is_empty() && !is_source_wiped {
+            cwe_warnings.push(generate_secret_source_warning(sub, &source_calls));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::AbstractLocation;
+    use crate::analysis::graph::get_program_cfg;
+    use serde_json::json;
+
+    #[test]
+    fn unwiped_secrets() {
+        let sub = Sub::mock("func");
+        let location = AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap();
+        let stack_id = AbstractIdentifier::new(sub.tid.clone(), location.clone());
+        let heap_id = AbstractIdentifier::new(Tid::new("malloc"), location.clone());
+        let other_heap_id = AbstractIdentifier::new(Tid::new("other_malloc"), location);
+        let derive = Jmp::call("derive", "derive", None);
+        let memset = Jmp::call("memset", "memset", None);
+        let free = Jmp::call("free", "free", None);
+        let secrets: BTreeMap<Cell, &Term<Jmp>> = vec![
+            ((stack_id.clone(), -32), &derive),
+            ((stack_id.clone(), -64), &derive),
+            ((heap_id.clone(), 0), &derive),
+            ((other_heap_id, 0), &derive),
+        ]
+        .into_iter()
+        .collect();
+        let mut wipe_calls = WipeCalls::default();
+        wipe_calls.wiped.insert((stack_id.clone(), -64));
+        wipe_calls
+            .elidable_wiped
+            .insert((heap_id.clone(), 0), &memset);
+        wipe_calls.freed.insert(heap_id.clone(), &free);
+
+        let unwiped_secrets = get_unwiped_secrets(&sub, &secrets, &wipe_calls);
+        // The heap object that is not freed in the function is not checked.
+        assert_eq!(
+            unwiped_secrets,
+            vec![
+                UnwipedSecret {
+                    secret_call: &derive,
+                    cell: (stack_id, -32),
+                    elidable_wipe: None,
+                    free: None,
+                },
+                UnwipedSecret {
+                    secret_call: &derive,
+                    cell: (heap_id, 0),
+                    elidable_wipe: Some(&memset),
+                    free: Some(&free),
+                }
+            ]
+        );
+        let warning = generate_cwe_warning(&sub, &unwiped_secrets[1]);
+        assert!(warning
+            .description
+            .starts_with("(Compiler Removal of Code to Clear Buffers)"));
+        assert_eq!(
+            warning.tids,
+            vec![
+                "derive".to_string(),
+                "memset".to_string(),
+                "free".to_string()
+            ]
+        );
+    }
+
+    fn var(name: &str) -> Expression {
+        Expression::Var(Variable::mock(name, 8))
+    }
+
+    /// A function calling the given extern functions in order.
+    /// Before each call the given Defs are executed.
+    /// The block of the i-th call is named `{name}_{i}`.
+    fn call_sequence(name: &str, calls: Vec<(Vec<Term<Def>>, &str)>) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        let call_count = calls.len();
+        for (index, (defs, target)) in calls.into_iter().enumerate() {
+            sub.term.blocks.push(Blk::mock_with(
+                &format!("{}_{}", name, index),
+                defs,
+                Jmp::mock_call(target, &format!("{}_{}", name, index + 1)),
+            ));
+        }
+        sub.term.blocks.push(Blk::mock_with(
+            &format!("{}_{}", name, call_count),
+            Vec::new(),
+            Jmp::Return(Expression::const_from_i64(0)),
+        ));
+        sub
+    }
+
+    /// Run the check on the project.
+    /// Returns the TIDs of the warnings and whether they are CWE-14 warnings about elidable wipes.
+    fn check_project(project: &Project, config: serde_json::Value) -> Vec<(Vec<String>, bool)> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": ["malloc"],
+                "deallocation_symbols": ["free"]
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| {
+                let is_elidable_wipe = warning
+                    .description
+                    .starts_with("(Compiler Removal of Code to Clear Buffers)");
+                (warning.tids, is_elidable_wipe)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn secret_buffers() {
+        let mut project = Project::mock_empty();
+        let mut calling_convention = CallingConvention::mock();
+        calling_convention.parameter_register = vec!["RDI".to_string(), "RSI".to_string()];
+        project.calling_conventions = vec![calling_convention];
+        project.program.term.extern_symbols = [
+            "derive_key",
+            "strcpy",
+            "getpass",
+            "explicit_bzero",
+            "memset",
+            "malloc",
+            "free",
+        ]
+        .iter()
+        .map(|name| ExternSymbol::mock_with(name, &["RDI", "RSI"]))
+        .collect();
+        // The frame pointer is callee-saved, so that it is not changed by calls.
+        let frame = |name: &str| {
+            Def::assign(
+                &format!("{}_frame", name),
+                Variable::mock("RBP", 8),
+                var("RSP").plus_const(-0x40),
+            )
+        };
+        let buffer = |tid: &str| Def::assign(tid, Variable::mock("RDI", 8), var("RBP"));
+        let stack_secret = |name: &str, wipe: Option<&'static str>| {
+            let mut calls = vec![(
+                vec![frame(name), buffer(&format!("{}_secret", name))],
+                "derive_key",
+            )];
+            if let Some(wipe) = wipe {
+                calls.push((vec![buffer(&format!("{}_wipe", name))], wipe));
+            }
+            call_sequence(name, calls)
+        };
+        project.program.term.subs = vec![
+            stack_secret("wiped", Some("explicit_bzero")),
+            stack_secret("elidable", Some("memset")),
+            stack_secret("unwiped", None),
+            // The wiped secret buffer is copied to another stack buffer, which is not wiped.
+            call_sequence(
+                "copied",
+                vec![
+                    (vec![frame("copied"), buffer("copied_secret")], "derive_key"),
+                    (
+                        vec![
+                            Def::assign(
+                                "copied_destination",
+                                Variable::mock("RDI", 8),
+                                var("RBP").plus_const(0x20),
+                            ),
+                            Def::assign("copied_source", Variable::mock("RSI", 8), var("RBP")),
+                        ],
+                        "strcpy",
+                    ),
+                    (vec![buffer("copied_wipe")], "explicit_bzero"),
+                ],
+            ),
+            // A heap buffer only wiped by `memset` before it is freed.
+            call_sequence(
+                "heap",
+                vec![
+                    (Vec::new(), "malloc"),
+                    (
+                        vec![
+                            Def::assign("heap_save", Variable::mock("RBP", 8), var("RAX")),
+                            buffer("heap_secret"),
+                        ],
+                        "derive_key",
+                    ),
+                    (vec![buffer("heap_wipe")], "memset"),
+                    (vec![buffer("heap_free")], "free"),
+                ],
+            ),
+            // The secret returned by `getpass` is not wiped.
+            call_sequence("source", vec![(Vec::new(), "getpass")]),
+            // The secret returned by `getpass` is wiped.
+            call_sequence(
+                "source_wiped",
+                vec![
+                    (Vec::new(), "getpass"),
+                    (
+                        vec![Def::assign(
+                            "source_wiped_wipe",
+                            Variable::mock("RDI", 8),
+                            var("RAX"),
+                        )],
+                        "explicit_bzero",
+                    ),
+                ],
+            ),
+        ];
+        let call = |block: &str| format!("{}_jmp", block);
+
+        let config = json!({
+            "secret_sources": ["getpass"],
+            "secret_parameters": {"derive_key": 0},
+            "copy_symbols": {"strcpy": {"destination": 0, "source": 1}},
+            "wipe_symbols": ["explicit_bzero"],
+            "elidable_wipe_symbols": ["memset"]
+        });
+        assert_eq!(
+            check_project(&project, config),
+            vec![
+                (vec![call("elidable_0"), call("elidable_1")], true),
+                (vec![call("unwiped_0")], false),
+                (vec![call("copied_1")], false),
+                (vec![call("heap_1"), call("heap_2"), call("heap_3")], true),
+                (vec![call("source_0")], false),
+            ]
+        );
+
+        // Without elidable wipe functions, calls to `memset` do not count as wipes at all.
+        // Without copy functions, the copied secret is not tracked.
+        let config = json!({
+            "secret_sources": [],
+            "secret_parameters": {"derive_key": 0},
+            "wipe_symbols": ["explicit_bzero"]
+        });
+        assert_eq!(
+            check_project(&project, config),
+            vec![
+                (vec![call("elidable_0")], false),
+                (vec![call("unwiped_0")], false),
+                (vec![call("heap_1"), call("heap_3")], false),
+            ]
+        );
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: Config = parse_config(&json!({
+            "secret_sources": ["getpass"],
+            "wipe_symbols": ["explicit_bzero"]
+        }))
+        .unwrap();
+        assert!(config.secret_parameters.is_empty());
+        assert!(config.copy_symbols.is_empty());
+        assert!(config.elidable_wipe_symbols.is_empty());
+        assert!(parse_config::<Config>(&json!({"secret_sources": ["getpass"]})).is_err());
+        assert!(parse_config::<Config>(&json!({
+            "secret_sources": [],
+            "wipe_symbols": [],
+            "copy_symbols": {"strcpy": {"destination": 0}}
+        }))
+        .is_err());
+    }
+}
@@ -31,6 +31,7 @@
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
 use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
 use crate::CweModule;
 
@@ -39,11 +40,14 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE190",
     version: "0.1",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The configuration struct.
 /// The `symbols` are extern function names.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     symbols: Vec<String>,
 }
@@ -105,7 +109,7 @@ pub fn check_cwe(
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let mut cwe_warnings = Vec::new();
     let symbol_map = get_symbol_map(project, &config.symbols);
     for sub in project.program.term.subs.iter() {
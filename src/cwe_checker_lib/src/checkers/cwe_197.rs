@@ -0,0 +1,361 @@
+//! This module implements a check for CWE-197: Numeric Truncation Error.
+//!
+//! If a size value is computed with a larger bit width than the parameter of the function that receives it,
+//! the value gets truncated.
+//! If the upper bits of the value are not zero, the function receives a much smaller size than intended.
+//! For memory allocation and copy functions this often leads to buffer overflows.
+//!
+//! See <https://cwe.mitre.org/data/definitions/197.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a function configured in the CWE197 section of config.json
+//! we follow the backward slice of the configured size parameters through the function containing the call
+//! along the [def-use chains](crate::analysis::def_use) of the function.
+//! If the slice contains a truncation (i.e. a `SUBPIECE` operation keeping only the lower bytes of its input)
+//! of a length value, we use the interval domain of the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! to check whether the upper bits of the truncated value may be nonzero.
+//! If so, a warning is generated that contains the address of the truncation as additional information.
+//!
+//! Length values are values computed by an arithmetic operation
+//! and return values of the `length_functions` configured in config.json (e.g. `strlen`).
+//! For example, the 64-bit return value of `strlen` truncated to a 32-bit `int` is flagged
+//! if it is used as the size argument of `malloc`.
+//!
+//! ## False Positives
+//!
+//! - The interval domain may overapproximate the possible values of the truncated size.
+//! - The truncated bits may be zero due to checks not representable in the interval domain.
+//!
+//! ## False Negatives
+//!
+//! - Truncations and size computations outside of the function containing the call are not detected.
+//! - The slice is not followed through values stored in memory.
+//! - Size parameters passed on the stack are not checked.
+//! - The P-Code `TRUNC` operation converts floating point numbers to integers,
+//!   so conversions from floating point values are not checked.
+
+use crate::abstract_domain::TryToInterval;
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE197",
+    version: "0.3",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Maps the names of extern functions to the indices of their size parameters.
+    size_parameters: BTreeMap<String, Vec<usize>>,
+    /// Extern functions returning lengths, e.g. `strlen`.
+    #[serde(default)]
+    length_functions: Vec<String>,
+}
+
+/// A truncation found in the backward slice of a size parameter.
+struct Truncation<'a> {
+    /// The TID of the `Def` containing the truncation.
+    def_tid: &'a Tid,
+    /// The expression whose value gets truncated.
+    truncated_value: &'a Expression,
+    /// The size of the value after the truncation.
+    target_size: ByteSize,
+}
+
+/// Find a truncating `SUBPIECE` subexpression in the given expression,
+/// i.e. a `SUBPIECE` that only keeps the lower bytes of its input.
+fn find_truncating_subpiece(expr: &Expression) -> Option<(&Expression, ByteSize)> {
+    use Expression::*;
+    match expr {
+        Subpiece {
+            low_byte,
+            size,
+            arg,
+        } if *low_byte == ByteSize::new(0) && *size < arg.bytesize() => Some((arg, *size)),
+        Var(_) | Const(_) | Unknown { .. } => None,
+        BinOp { lhs, rhs, .. } => {
+            find_truncating_subpiece(lhs).or_else(|| find_truncating_subpiece(rhs))
+        }
+        UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => find_truncating_subpiece(arg),
+    }
+}
+
+/// Return `true` if the expression is an arithmetic operation that can produce values not fitting into the target size.
+fn is_size_arithmetic(expr: &Expression) -> bool {
+    matches!(
+        expr,
+        Expression::BinOp {
+            op: BinOpType::IntMult | BinOpType::IntAdd | BinOpType::IntLeft | BinOpType::IntSub,
+            ..
+        }
+    )
+}
+
+/// Return `true` if the truncated value is a length value,
+/// i.e. if it is computed by an arithmetic operation or returned by one of the length functions,
+/// either directly or through a register assigned by one of the definitions reaching the truncation.
+fn is_length_value(
+    def_use_chains: &DefUseChains,
+    length_functions: &HashMap<Tid, &ExternSymbol>,
+    truncation_tid: &Tid,
+    value: &Expression,
+) -> bool {
+    if is_size_arithmetic(value) {
+        return true;
+    }
+    if let Expression::Var(var) = value {
+        return def_use_chains.defs_reaching(truncation_tid, var).any(
+            |def_tid| match def_use_chains.get_definition(def_tid) {
+                Some(Definition::Def(Term {
+                    term: Def::Assign { value, .. },
+                    ..
+                })) => is_size_arithmetic(value),
+                Some(Definition::Call(Term {
+                    term: Jmp::Call { target, .. },
+                    ..
+                })) => match length_functions.get(target) {
+                    Some(symbol) => symbol
+                        .return_values
+                        .iter()
+                        .any(|return_value| *return_value == Arg::Register(var.clone())),
+                    None => false,
+                },
+                _ => false,
+            },
+        );
+    }
+    false
+}
+
+/// Follow the backward slice of `parameter` at the callsite through the function
+/// and return the first truncation of a length value found in it.
+fn find_truncation_in_backward_slice<'a>(
+    def_use_chains: &DefUseChains<'a>,
+    length_functions: &HashMap<Tid, &ExternSymbol>,
+    callsite: &Tid,
+    parameter: &Variable,
+) -> Option<Truncation<'a>> {
+    let mut worklist: Vec<(Tid, Variable)> = vec![(callsite.clone(), parameter.clone())];
+    let mut visited_defs: HashSet<&Tid> = HashSet::new();
+    while let Some((use_tid, var)) = worklist.pop() {
+        for def_tid in def_use_chains.defs_reaching(&use_tid, &var) {
+            if !visited_defs.insert(def_tid) {
+                continue;
+            }
+            // The slice is not followed into memory (`Load`) or into called functions.
+            if let Some(Definition::Def(Term {
+                tid,
+                term: Def::Assign { value, .. },
+            })) = def_use_chains.get_definition(def_tid)
+            {
+                if let Some((truncated_value, target_size)) = find_truncating_subpiece(value) {
+                    if is_length_value(def_use_chains, length_functions, tid, truncated_value) {
+                        return Some(Truncation {
+                            def_tid: tid,
+                            truncated_value,
+                            target_size,
+                        });
+                    }
+                }
+                worklist.extend(
+                    value
+                        .input_vars()
+                        .into_iter()
+                        .map(|input| (tid.clone(), input.clone())),
+                );
+            }
+        }
+    }
+    None
+}
+
+/// Check whether the truncated value may have nonzero bits above the target size.
+/// Unbounded values are assumed to have nonzero upper bits.
+fn upper_bits_may_be_nonzero(state: &State, truncation: &Truncation) -> bool {
+    let value = state.eval(truncation.truncated_value);
+    if let Data::Pointer(_) = value {
+        return false;
+    }
+    let max_target_value = match u64::from(truncation.target_size) {
+        size if size >= 8 => return false,
+        size => (1u64 << (8 * size)) - 1,
+    };
+    match value.try_to_offset_interval() {
+        Ok((start, end)) => start < 0 || end as u64 > max_target_value,
+        Err(_) => true,
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    callsite: &Tid,
+    called_symbol: &ExternSymbol,
+    truncation_site: &Tid,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Numeric Truncation Error) Size parameter of call to {} at {} may be truncated at {}",
+            called_symbol.name, callsite.address, truncation_site.address
+        ),
+    )
+    .tids(vec![
+        format!("{}", callsite),
+        format!("{}", truncation_site),
+    ])
+    .addresses(vec![
+        callsite.address.clone(),
+        truncation_site.address.clone(),
+    ])
+    .symbols(vec![called_symbol.name.clone()])
+}
+
+/// Run the CWE check.
+/// For each size parameter of a call to one of the symbols configured in config.json
+/// we check whether it is the result of a truncation of a value whose upper bits may be nonzero.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbols: Vec<String> = config.size_parameters.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbols);
+    let length_functions = get_symbol_map(project, &config.length_functions);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let def_use_chains = analysis_results.def_use_chains(&sub.tid).unwrap();
+        for (_block, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            for parameter_index in config.size_parameters[&symbol.name].iter() {
+                let truncation = match symbol.parameters.get(*parameter_index) {
+                    Some(Arg::Register(parameter)) => find_truncation_in_backward_slice(
+                        def_use_chains,
+                        &length_functions,
+                        &jmp.tid,
+                        parameter,
+                    ),
+                    _ => None,
+                };
+                if let Some(truncation) = truncation {
+                    if let Some(state) = pointer_inference.get_state_at_tid(truncation.def_tid) {
+                        if upper_bits_may_be_nonzero(&state, &truncation) {
+                            cwe_warnings.push(generate_cwe_warning(
+                                &jmp.tid,
+                                symbol,
+                                truncation.def_tid,
+                            ));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation_in_backward_slice() {
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let edi = Variable::mock("EDI", 4);
+        let mut size_block = Blk::mock();
+        size_block.term.defs = vec![Def::assign(
+            "mult",
+            rax.clone(),
+            Expression::Var(rax.clone()).plus(Expression::Var(rax.clone())),
+        )];
+        size_block.term.jmps = vec![Term {
+            tid: Tid::new("branch"),
+            term: Jmp::Branch(Tid::new("call_block")),
+        }];
+        let mut call_block = Blk::mock();
+        call_block.tid = Tid::new("call_block");
+        call_block.term.defs = vec![
+            Def::assign(
+                "truncate",
+                edi.clone(),
+                Expression::Var(rax.clone()).subpiece(ByteSize::new(0), ByteSize::new(4)),
+            ),
+            Def::assign(
+                "extend",
+                rdi.clone(),
+                Expression::Var(edi).cast(CastOpType::IntZExt),
+            ),
+        ];
+        call_block.term.jmps = vec![Term {
+            tid: Tid::new("call"),
+            term: Jmp::Call {
+                target: Tid::new("mock_symbol"),
+                return_: None,
+            },
+        }];
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![size_block, call_block];
+        let chains = DefUseChains::new(&project, &sub);
+        let length_functions = HashMap::new();
+        let truncation =
+            find_truncation_in_backward_slice(&chains, &length_functions, &Tid::new("call"), &rdi)
+                .unwrap();
+        assert_eq!(*truncation.def_tid, Tid::new("truncate"));
+        assert_eq!(truncation.target_size, ByteSize::new(4));
+        assert_eq!(*truncation.truncated_value, Expression::Var(rax.clone()));
+
+        // Truncations of values not computed by arithmetic operations are ignored.
+        sub.term.blocks[0].term.defs.clear();
+        let chains = DefUseChains::new(&project, &sub);
+        assert!(find_truncation_in_backward_slice(
+            &chains,
+            &length_functions,
+            &Tid::new("call"),
+            &rdi
+        )
+        .is_none());
+
+        // Truncations of return values of length functions are found.
+        sub.term.blocks[0].term.jmps[0].term = Jmp::Call {
+            target: Tid::new("strlen"),
+            return_: Some(Tid::new("call_block")),
+        };
+        let mut strlen = ExternSymbol::mock();
+        strlen.tid = Tid::new("strlen");
+        strlen.name = "strlen".to_string();
+        project.program.term.extern_symbols.push(strlen.clone());
+        let chains = DefUseChains::new(&project, &sub);
+        assert!(find_truncation_in_backward_slice(
+            &chains,
+            &length_functions,
+            &Tid::new("call"),
+            &rdi
+        )
+        .is_none());
+        let length_functions: HashMap<Tid, &ExternSymbol> =
+            vec![(strlen.tid.clone(), &strlen)].into_iter().collect();
+        let truncation =
+            find_truncation_in_backward_slice(&chains, &length_functions, &Tid::new("call"), &rdi)
+                .unwrap();
+        assert_eq!(*truncation.def_tid, Tid::new("truncate"));
+    }
+}
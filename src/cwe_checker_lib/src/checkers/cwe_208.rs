@@ -0,0 +1,703 @@
+//! This module implements a check for CWE-208: Observable Timing Discrepancy.
+//!
+//! Comparison functions like `memcmp` or `strcmp` return as soon as the first differing byte is found.
+//! If such a comparison is used to check a secret (e.g. a password or a key) against user input,
+//! an attacker can measure the time of the comparison to guess the secret byte by byte.
+//! Secrets should be compared with a constant-time comparison function instead.
+//!
+//! See <https://cwe.mitre.org/data/definitions/208.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each function we run a simple intraprocedural taint analysis tracking pointers to secrets.
+//! Secrets are
+//! - global variables whose ELF symbol names contain one of the `secret_global_keywords` configured in config.json,
+//! - the memory pointed to by the return values of the functions configured as `secret_sources` (e.g. `getpass`).
+//!
+//! Taint is propagated through registers and through stack and heap memory cells
+//! whose addresses are known through the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! A warning is generated
+//! - for each call to one of the configured `comparison_functions` where one of the first two parameters points to a secret,
+//! - for each load from a secret inside a loop where the loaded value decides a conditional jump in the same basic block,
+//!   i.e. for hand-written byte-wise comparison loops that exit on the first mismatch.
+//!
+//! ## False Positives
+//!
+//! - Global variables are only recognized as secrets by their names, which may be misleading.
+//! - Loops exiting on other conditions of the secret (e.g. on the terminating zero byte of a string)
+//!   are also reported as comparison loops.
+//!
+//! ## False Negatives
+//!
+//! - The taint analysis is intraprocedural.
+//!   Secrets passed as parameters or returned from internal functions are not tracked.
+//! - Secrets in stripped binaries or in non-ELF files are only found through the configured source functions.
+//! - Comparison loops whose loaded value and conditional jump are located in different basic blocks are not detected.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, PointerInference, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::get_elf_object_symbols;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_variadic_parameter;
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE208",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Comparison functions that return on the first mismatch.
+    /// The first two parameters are assumed to be the compared values.
+    comparison_functions: Vec<String>,
+    /// Functions returning pointers to secrets.
+    secret_sources: Vec<String>,
+    /// Global variables whose names contain one of these keywords (ignoring case) are assumed to contain secrets.
+    #[serde(default)]
+    secret_global_keywords: Vec<String>,
+}
+
+/// The taint state of a function at a program point.
+/// Tainted values are pointers to secrets or values loaded from secrets.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub(crate) struct TaintState {
+    /// Tainted registers.
+    registers: HashSet<Variable>,
+    /// Tainted memory cells, given by the abstract object and the offset into it.
+    memory: HashSet<(AbstractIdentifier, i64)>,
+}
+
+impl TaintState {
+    /// Check whether the expression is tainted.
+    /// Constants are tainted if they are addresses of secret global variables.
+    fn eval(&self, expression: &Expression, secret_globals: &[(u64, u64)]) -> bool {
+        use Expression::*;
+        match expression {
+            Var(var) => self.registers.contains(var),
+            Const(constant) => match constant.try_to_u64() {
+                Ok(address) => is_secret_global_address(address, secret_globals),
+                Err(_) => false,
+            },
+            Unknown { .. } => false,
+            BinOp { lhs, rhs, .. } => {
+                self.eval(lhs, secret_globals) || self.eval(rhs, secret_globals)
+            }
+            UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => {
+                self.eval(arg, secret_globals)
+            }
+        }
+    }
+
+    /// Merge the other state into `self`.
+    /// Returns `true` if `self` changed.
+    fn merge_with(&mut self, other: &TaintState) -> bool {
+        let old_size = self.registers.len() + self.memory.len();
+        self.registers.extend(other.registers.iter().cloned());
+        self.memory.extend(other.memory.iter().cloned());
+        self.registers.len() + self.memory.len() != old_size
+    }
+
+    /// Check whether the memory cell that the address points to is tainted.
+    pub(crate) fn load(&self, address: &Data) -> bool {
+        if let Data::Pointer(pointer) = address {
+            for (id, offset) in pointer.targets() {
+                if let Some(offset) = offset
+                    .try_to_bitvec()
+                    .ok()
+                    .and_then(|offset| offset.try_to_i64().ok())
+                {
+                    if self.memory.contains(&(id.clone(), offset)) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Set the taint of the memory cells that the address may point to.
+    /// Taints are only removed if the address has exactly one target.
+    fn store(&mut self, address: &Data, taint: bool) {
+        if let Data::Pointer(pointer) = address {
+            let is_unique_target = pointer.targets().len() == 1;
+            for (id, offset) in pointer.targets() {
+                if let Some(offset) = offset
+                    .try_to_bitvec()
+                    .ok()
+                    .and_then(|offset| offset.try_to_i64().ok())
+                {
+                    let location = (id.clone(), offset);
+                    if taint {
+                        self.memory.insert(location);
+                    } else if is_unique_target {
+                        self.memory.remove(&location);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check whether the address lies inside one of the given address ranges of secret global variables.
+fn is_secret_global_address(address: u64, secret_globals: &[(u64, u64)]) -> bool {
+    secret_globals
+        .iter()
+        .any(|(start, end)| *start <= address && address < *end)
+}
+
+/// A detected comparison of a secret that may exit early.
+enum SecretComparison<'a> {
+    /// A call to a comparison function with a secret parameter.
+    Call {
+        /// The call instruction.
+        jmp: &'a Term<Jmp>,
+        /// The called comparison function.
+        symbol: &'a ExternSymbol,
+    },
+    /// A load from a secret inside a loop that exits depending on the loaded value.
+    Loop {
+        /// The load instruction.
+        load: &'a Term<Def>,
+    },
+}
+
+/// The context of the intraprocedural taint analysis.
+pub(crate) struct Context<'a> {
+    project: &'a Project,
+    pointer_inference: &'a PointerInference<'a>,
+    /// Functions returning pointers to secrets.
+    secret_sources: Vec<String>,
+    /// Functions writing secrets to the memory pointed to by the parameter with the given index.
+    secret_parameters: BTreeMap<String, usize>,
+    /// Comparison functions that return on the first mismatch.
+    comparison_functions: Vec<String>,
+    extern_symbols: HashMap<&'a Tid, &'a ExternSymbol>,
+    /// The address ranges of global variables assumed to contain secrets.
+    secret_globals: Vec<(u64, u64)>,
+}
+
+impl<'a> Context<'a> {
+    /// Create the context of the taint analysis.
+    pub(crate) fn new(
+        project: &'a Project,
+        pointer_inference: &'a PointerInference<'a>,
+        secret_globals: Vec<(u64, u64)>,
+        secret_sources: Vec<String>,
+        secret_parameters: BTreeMap<String, usize>,
+        comparison_functions: Vec<String>,
+    ) -> Context<'a> {
+        Context {
+            project,
+            pointer_inference,
+            secret_sources,
+            secret_parameters,
+            comparison_functions,
+            extern_symbols: project
+                .program
+                .term
+                .extern_symbols
+                .iter()
+                .map(|symbol| (&symbol.tid, symbol))
+                .collect(),
+            secret_globals,
+        }
+    }
+
+    /// Check whether the given value is the address of a secret global variable.
+    fn is_secret_global(&self, value: &Data) -> bool {
+        match value.try_to_bitvec() {
+            Ok(address) => match address.try_to_u64() {
+                Ok(address) => is_secret_global_address(address, &self.secret_globals),
+                Err(_) => false,
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Apply the effects of the `Def`s of the block to the taint state.
+    /// Return the loads from secrets together with their indices in the block.
+    fn handle_defs(
+        &self,
+        block: &'a Term<Blk>,
+        pi_state: &mut PointerInferenceState,
+        taint_state: &mut TaintState,
+    ) -> Vec<(usize, &'a Term<Def>)> {
+        let runtime_memory_image = self.pointer_inference.get_context().runtime_memory_image;
+        let mut secret_loads = Vec::new();
+        for (index, def) in block.term.defs.iter().enumerate() {
+            match &def.term {
+                Def::Assign { var, value } => {
+                    if taint_state.eval(value, &self.secret_globals) {
+                        taint_state.registers.insert(var.clone());
+                    } else {
+                        taint_state.registers.remove(var);
+                    }
+                    pi_state.handle_register_assign(var, value);
+                }
+                Def::Load { var, address } => {
+                    let address_value = pi_state.eval(address);
+                    if taint_state.eval(address, &self.secret_globals)
+                        || self.is_secret_global(&address_value)
+                    {
+                        secret_loads.push((index, def));
+                        taint_state.registers.insert(var.clone());
+                    } else if taint_state.load(&address_value) {
+                        taint_state.registers.insert(var.clone());
+                    } else {
+                        taint_state.registers.remove(var);
+                    }
+                    let _ = pi_state.handle_load(var, address, runtime_memory_image);
+                }
+                Def::Store { address, value } => {
+                    let taint = taint_state.eval(value, &self.secret_globals)
+                        || self.is_secret_global(&pi_state.eval(value));
+                    taint_state.store(&pi_state.eval(address), taint);
+                    let _ = pi_state.handle_store(address, value, runtime_memory_image);
+                }
+            }
+        }
+        secret_loads
+    }
+
+    /// Check whether the parameter of a call points to a secret.
+    pub(crate) fn is_secret_parameter(
+        &self,
+        pi_state: &PointerInferenceState,
+        taint_state: &TaintState,
+        parameter: &Arg,
+    ) -> bool {
+        let is_tainted = match parameter {
+            Arg::Register(var) => taint_state.registers.contains(var),
+            Arg::Stack { offset, .. } => taint_state.load(&pi_state.eval(
+                &Expression::Var(self.project.stack_pointer_register.clone()).plus_const(*offset),
+            )),
+        };
+        is_tainted
+            || matches!(
+                pi_state.eval_parameter_arg(
+                    parameter,
+                    &self.project.stack_pointer_register,
+                    self.pointer_inference.get_context().runtime_memory_image,
+                ),
+                Ok(value) if self.is_secret_global(&value)
+            )
+    }
+
+    /// Compute the taint state after returning from a call.
+    ///
+    /// Taints of registers that are not callee-saved are removed.
+    /// If the called function is a secret source, its return registers get tainted.
+    /// If it writes secrets to the memory pointed to by a parameter, the targeted memory cells get tainted.
+    fn handle_call(
+        &self,
+        pi_state: &PointerInferenceState,
+        taint_state: &TaintState,
+        call: &Term<Jmp>,
+    ) -> TaintState {
+        let mut new_state = taint_state.clone();
+        let symbol = match &call.term {
+            Jmp::Call { target, .. } => self.extern_symbols.get(target),
+            _ => None,
+        };
+        let calling_convention = match symbol {
+            Some(symbol) => Some(symbol.get_calling_convention(self.project)),
+            None => self.project.get_standard_calling_convention(),
+        };
+        if let Some(calling_convention) = calling_convention {
+            new_state
+                .registers
+                .retain(|var| calling_convention.callee_saved_register.contains(&var.name));
+        }
+        if let Some(symbol) = symbol {
+            if self.secret_sources.contains(&symbol.name) {
+                for return_value in symbol.return_values.iter() {
+                    if let Arg::Register(var) = return_value {
+                        new_state.registers.insert(var.clone());
+                    }
+                }
+            }
+            if let Some(index) = self.secret_parameters.get(&symbol.name) {
+                if let Ok(address) = pi_state.eval_parameter_arg(
+                    &get_variadic_parameter(self.project, symbol, *index),
+                    &self.project.stack_pointer_register,
+                    self.pointer_inference.get_context().runtime_memory_image,
+                ) {
+                    new_state.store(&address, true);
+                }
+            }
+        }
+        new_state
+    }
+
+    /// Compute the taint states at the start of the blocks of the function.
+    fn compute_block_states(&self, sub: &'a Term<Sub>) -> HashMap<&'a Tid, TaintState> {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, TaintState> = HashMap::new();
+        let mut worklist: Vec<&Tid> = Vec::new();
+        if let Some(first_block) = sub.term.blocks.first() {
+            block_states.insert(&first_block.tid, TaintState::default());
+            worklist.push(&first_block.tid);
+        }
+        let mut in_worklist: HashSet<&Tid> = worklist.iter().copied().collect();
+        while let Some(block_tid) = worklist.pop() {
+            in_worklist.remove(block_tid);
+            let block = blocks[block_tid];
+            let mut pi_state = match self.pointer_inference.get_state_at_blk_start(block_tid) {
+                Some(pi_state) => pi_state.clone(),
+                None => continue,
+            };
+            let mut taint_state = block_states[block_tid].clone();
+            self.handle_defs(block, &mut pi_state, &mut taint_state);
+            for jmp in block.term.jmps.iter() {
+                let (successor, successor_state) = match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        (target, taint_state.clone())
+                    }
+                    Jmp::Call {
+                        return_: Some(return_target),
+                        ..
+                    }
+                    | Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => (
+                        return_target,
+                        self.handle_call(&pi_state, &taint_state, jmp),
+                    ),
+                    Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } => (return_target, taint_state.clone()),
+                    _ => continue,
+                };
+                if let Some(successor) = blocks.get(successor) {
+                    let changed = match block_states.get_mut(&successor.tid) {
+                        Some(state) => state.merge_with(&successor_state),
+                        None => {
+                            block_states.insert(&successor.tid, successor_state);
+                            true
+                        }
+                    };
+                    if changed && in_worklist.insert(&successor.tid) {
+                        worklist.push(&successor.tid);
+                    }
+                }
+            }
+        }
+        block_states
+    }
+
+    /// Get the calls of the function to extern symbols
+    /// together with the Pointer Inference states and the taint states right before them.
+    pub(crate) fn get_states_at_calls(
+        &self,
+        sub: &'a Term<Sub>,
+    ) -> Vec<(
+        &'a Term<Jmp>,
+        &'a ExternSymbol,
+        PointerInferenceState,
+        TaintState,
+    )> {
+        let block_states = self.compute_block_states(sub);
+        let mut states = Vec::new();
+        for block in sub.term.blocks.iter() {
+            let (mut pi_state, mut taint_state) = match (
+                self.pointer_inference.get_state_at_blk_start(&block.tid),
+                block_states.get(&block.tid),
+            ) {
+                (Some(pi_state), Some(taint_state)) => (pi_state.clone(), taint_state.clone()),
+                _ => continue,
+            };
+            self.handle_defs(block, &mut pi_state, &mut taint_state);
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if let Some(symbol) = self.extern_symbols.get(target) {
+                        states.push((jmp, *symbol, pi_state.clone(), taint_state.clone()));
+                    }
+                }
+            }
+        }
+        states
+    }
+
+    /// Run the taint analysis on the function and return the detected comparisons of secrets.
+    fn analyze_sub(&self, sub: &'a Term<Sub>) -> Vec<SecretComparison<'a>> {
+        let block_states = self.compute_block_states(sub);
+        let mut comparisons: BTreeMap<Tid, SecretComparison<'a>> = BTreeMap::new();
+        for block in sub.term.blocks.iter() {
+            let (mut pi_state, mut taint_state) = match (
+                self.pointer_inference.get_state_at_blk_start(&block.tid),
+                block_states.get(&block.tid),
+            ) {
+                (Some(pi_state), Some(taint_state)) => (pi_state.clone(), taint_state.clone()),
+                _ => continue,
+            };
+            for (index, load) in self.handle_defs(block, &mut pi_state, &mut taint_state) {
+                if let Def::Load { var, .. } = &load.term {
+                    if decides_conditional_jump(block, index, var) && is_in_loop(sub, &block.tid) {
+                        comparisons.insert(load.tid.clone(), SecretComparison::Loop { load });
+                    }
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if let Some(symbol) = self.extern_symbols.get(target) {
+                        if self.comparison_functions.contains(&symbol.name)
+                            && symbol.parameters.iter().take(2).any(|parameter| {
+                                self.is_secret_parameter(&pi_state, &taint_state, parameter)
+                            })
+                        {
+                            comparisons
+                                .insert(jmp.tid.clone(), SecretComparison::Call { jmp, symbol });
+                        }
+                    }
+                }
+            }
+        }
+        comparisons.into_values().collect()
+    }
+}
+
+/// Check whether the value loaded into `var` by the `Def` at `load_index`
+/// decides a conditional jump at the end of the block.
+fn decides_conditional_jump(block: &Term<Blk>, load_index: usize, var: &Variable) -> bool {
+    let mut dependent_vars: HashSet<&Variable> = HashSet::new();
+    dependent_vars.insert(var);
+    for def in block.term.defs[(load_index + 1)..].iter() {
+        match &def.term {
+            Def::Assign {
+                var: assigned_var,
+                value,
+            } => {
+                if value
+                    .input_vars()
+                    .into_iter()
+                    .any(|input| dependent_vars.contains(input))
+                {
+                    dependent_vars.insert(assigned_var);
+                } else {
+                    dependent_vars.remove(assigned_var);
+                }
+            }
+            Def::Load {
+                var: assigned_var, ..
+            } => {
+                dependent_vars.remove(assigned_var);
+            }
+            Def::Store { .. } => (),
+        }
+    }
+    block.term.jmps.iter().any(|jmp| match &jmp.term {
+        Jmp::CBranch { condition, .. } => condition
+            .input_vars()
+            .into_iter()
+            .any(|input| dependent_vars.contains(input)),
+        _ => false,
+    })
+}
+
+/// Check whether the block is contained in a loop inside the function.
+fn is_in_loop(sub: &Term<Sub>, block_tid: &Tid) -> bool {
+    let successors: HashMap<&Tid, Vec<&Tid>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| {
+            let targets = block
+                .term
+                .jmps
+                .iter()
+                .filter_map(|jmp| match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => Some(target),
+                    Jmp::Call { return_, .. }
+                    | Jmp::CallInd { return_, .. }
+                    | Jmp::CallOther { return_, .. } => return_.as_ref(),
+                    Jmp::BranchInd(_) | Jmp::Return(_) => None,
+                })
+                .collect();
+            (&block.tid, targets)
+        })
+        .collect();
+    let mut visited: HashSet<&Tid> = HashSet::new();
+    let mut stack: Vec<&Tid> = successors.get(block_tid).cloned().unwrap_or_default();
+    while let Some(tid) = stack.pop() {
+        if tid == block_tid {
+            return true;
+        }
+        if visited.insert(tid) {
+            if let Some(targets) = successors.get(tid) {
+                stack.extend(targets.iter().copied());
+            }
+        }
+    }
+    false
+}
+
+/// Compute the address ranges of the global variables whose names contain one of the given keywords.
+pub(crate) fn get_secret_globals(
+    binary: &[u8],
+    keywords: &[String],
+    address_base_offset: u64,
+) -> Result<Vec<(u64, u64)>, Error> {
+    let keywords: Vec<String> = keywords.iter().map(|word| word.to_lowercase()).collect();
+    Ok(get_elf_object_symbols(binary)?
+        .into_iter()
+        .filter(|(name, _, _)| {
+            let name = name.to_lowercase();
+            keywords
+                .iter()
+                .any(|keyword| name.contains(keyword.as_str()))
+        })
+        .map(|(_, address, size)| {
+            let start = address + address_base_offset;
+            (start, start + std::cmp::max(size, 1))
+        })
+        .collect())
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(sub: &Term<Sub>, comparison: &SecretComparison) -> CweWarning {
+    let (description, tid, symbols) = match comparison {
+        SecretComparison::Call { jmp, symbol } => (
+            format!("with {}", symbol.name),
+            &jmp.tid,
+            vec![sub.term.display_name().to_string(), symbol.name.clone()],
+        ),
+        SecretComparison::Loop { load } => (
+            "byte-wise with early exit".to_string(),
+            &load.tid,
+            vec![sub.term.display_name().to_string()],
+        ),
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Observable Timing Discrepancy) Secret compared {} at {} in function {}",
+            description,
+            tid.address,
+            sub.term.display_name()
+        ),
+    )
+    .tids(vec![format!("{}", tid)])
+    .addresses(vec![tid.address.clone()])
+    .symbols(symbols)
+}
+
+/// Run the CWE check.
+/// For each function we check whether secrets are compared by functions or loops that exit on the first mismatch.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let mut logs = Vec::new();
+    let secret_globals = match get_secret_globals(
+        analysis_results.binary,
+        &config.secret_global_keywords,
+        project.program.term.address_base_offset,
+    ) {
+        Ok(secret_globals) => secret_globals,
+        Err(err) => {
+            logs.push(
+                LogMessage::new_debug(format!("Secret global variables not available: {}", err))
+                    .source(CWE_MODULE.name),
+            );
+            Vec::new()
+        }
+    };
+    let context = Context::new(
+        project,
+        analysis_results.pointer_inference().unwrap(),
+        secret_globals,
+        config.secret_sources,
+        BTreeMap::new(),
+        config.comparison_functions,
+    );
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for comparison in context.analyze_sub(sub) {
+            cwe_warnings.push(generate_cwe_warning(sub, &comparison));
+        }
+    }
+    (logs, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn early_exit_loop_recognition() {
+        let byte = Variable::mock("AL", 1);
+        let other_byte = Variable::mock("CL", 1);
+        let flag = Variable::mock("ZF", 1);
+        let mut block = Blk::mock();
+        block.term.defs = vec![
+            Def::load(
+                "load",
+                byte.clone(),
+                Expression::Var(Variable::mock("RDI", 8)),
+            ),
+            Def::assign(
+                "compare",
+                flag.clone(),
+                Expression::BinOp {
+                    op: BinOpType::IntEqual,
+                    lhs: Box::new(Expression::Var(byte.clone())),
+                    rhs: Box::new(Expression::Var(other_byte.clone())),
+                },
+            ),
+        ];
+        block.term.jmps = vec![
+            Term {
+                tid: Tid::new("cbranch"),
+                term: Jmp::CBranch {
+                    target: Tid::new("exit"),
+                    condition: Expression::Var(flag.clone()),
+                },
+            },
+            Jmp::branch("loop", "block"),
+        ];
+        assert!(decides_conditional_jump(&block, 0, &byte));
+        // Values not used in the condition do not decide the jump.
+        assert!(!decides_conditional_jump(
+            &block,
+            0,
+            &Variable::mock("DL", 1)
+        ));
+
+        let mut sub = Sub::mock("sub");
+        sub.term.blocks.push(block.clone());
+        assert!(is_in_loop(&sub, &Tid::new("block")));
+        sub.term.blocks[0].term.jmps.pop();
+        assert!(!is_in_loop(&sub, &Tid::new("block")));
+    }
+
+    #[test]
+    fn secret_global_constants() {
+        let state = TaintState::default();
+        let secret_globals = vec![(0x3000, 0x3010)];
+        let address = Expression::Const(Bitvector::from_u64(0x3000))
+            .plus(Expression::Var(Variable::mock("RAX", 8)));
+        assert!(state.eval(&address, &secret_globals));
+        assert!(!state.eval(&Expression::const_from_i64(0x3010), &secret_globals));
+    }
+}
@@ -27,6 +27,8 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE215",
     version: "0.2",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
 };
 
 /// Run the check.
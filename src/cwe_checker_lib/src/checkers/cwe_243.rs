@@ -30,7 +30,8 @@ use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::graph_utils::is_sink_call_reachable_from_source_call;
 use crate::utils::log::{CweWarning, LogMessage};
-use crate::utils::symbol_utils::find_symbol;
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::find_symbols;
 use crate::CweModule;
 
 /// The module name and version
@@ -38,11 +39,14 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE243",
     version: "0.2",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The configuration struct contains the list of functions
 /// that are assumed to be used to correctly drop priviledges after a `chroot` call.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     priviledge_dropping_functions: Vec<String>,
 }
@@ -61,17 +65,20 @@ fn blk_calls_tid(blk: &Term<Blk>, tid: &Tid) -> Option<Tid> {
     None
 }
 
-/// Check whether the given `sub` calls both the `chdir_tid`
+/// Check whether the given `sub` calls both one of the `chdir_tids`
 /// and at least one of the `priviledge_dropping_tids`.
 /// If yes, return true.
 fn sub_calls_chdir_and_priviledge_dropping_func(
     sub: &Term<Sub>,
-    chdir_tid: &Tid,
+    chdir_tids: &[Tid],
     priviledge_dropping_tids: &[Tid],
 ) -> bool {
     let mut is_chdir_called = false;
     for blk in sub.term.blocks.iter() {
-        if blk_calls_tid(blk, chdir_tid).is_some() {
+        if chdir_tids
+            .iter()
+            .any(|tid| blk_calls_tid(blk, tid).is_some())
+        {
             is_chdir_called = true;
             break;
         }
@@ -97,11 +104,11 @@ fn generate_cwe_warning(sub: &Term<Sub>, callsite: &Tid) -> CweWarning {
         CWE_MODULE.version,
         format!(
             "(The program utilizes chroot without dropping privileges and/or changing the directory) at {} ({})",
-            callsite.address, sub.term.name
+            callsite.address, sub.term.display_name()
         ))
         .tids(vec![format!("{}", callsite)])
         .addresses(vec![callsite.address.clone()])
-        .symbols(vec![sub.term.name.clone()])
+        .symbols(vec![sub.term.display_name().to_string()])
 }
 
 /// Run the check.
@@ -119,49 +126,53 @@ pub fn check_cwe(
     let project = analysis_results.project;
     let graph = analysis_results.control_flow_graph;
 
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let find_symbol_tids = |name: &str| -> Vec<Tid> {
+        find_symbols(&project.program, name)
+            .into_iter()
+            .map(|(tid, _)| tid.clone())
+            .collect()
+    };
     let priviledge_dropping_tids: Vec<Tid> = config
         .priviledge_dropping_functions
-        .into_iter()
-        .filter_map(|func_name| {
-            if let Some((tid, _)) = find_symbol(&project.program, &func_name) {
-                Some(tid.clone())
-            } else {
-                None
-            }
-        })
+        .iter()
+        .flat_map(|func_name| find_symbol_tids(func_name))
         .collect();
 
-    let chroot_tid = match find_symbol(&project.program, "chroot") {
-        Some((tid, _)) => tid.clone(),
-        None => return (Vec::new(), Vec::new()), // chroot is never called by the program
-    };
+    let chroot_tids = find_symbol_tids("chroot");
+    if chroot_tids.is_empty() {
+        return (Vec::new(), Vec::new()); // chroot is never called by the program
+    }
+    let chdir_tids = find_symbol_tids("chdir");
 
     let mut cwe_warnings = Vec::new();
     for node in graph.node_indices() {
         if let Node::BlkEnd(blk, sub) = graph[node] {
-            if let Some(callsite_tid) = blk_calls_tid(blk, &chroot_tid) {
-                if let Some(chdir_tid) =
-                    find_symbol(&project.program, "chdir").map(|(tid, _)| tid.clone())
-                {
+            let chroot_call = chroot_tids
+                .iter()
+                .find_map(|tid| Some((tid, blk_calls_tid(blk, tid)?)));
+            if let Some((chroot_tid, callsite_tid)) = chroot_call {
+                if !chdir_tids.is_empty() {
                     if graph.neighbors(node).count() > 1 {
                         panic!("Malformed Control flow graph: More than one edge for extern function call")
                     }
                     let chroot_return_to_node = graph.neighbors(node).next().unwrap();
                     // If chdir is called after chroot, we assume a secure chroot jail.
-                    if is_sink_call_reachable_from_source_call(
-                        graph,
-                        chroot_return_to_node,
-                        &chroot_tid,
-                        &chdir_tid,
-                    )
-                    .is_none()
-                    {
+                    let is_chdir_called_after_chroot = chdir_tids.iter().any(|chdir_tid| {
+                        is_sink_call_reachable_from_source_call(
+                            graph,
+                            chroot_return_to_node,
+                            chroot_tid,
+                            chdir_tid,
+                        )
+                        .is_some()
+                    });
+                    if !is_chdir_called_after_chroot {
                         // If chdir is not called after chroot, it has to be called before it.
                         // Additionally priviledges must be dropped to secure the chroot jail in this case.
                         if !sub_calls_chdir_and_priviledge_dropping_func(
                             sub,
-                            &chdir_tid,
+                            &chdir_tids[..],
                             &priviledge_dropping_tids[..],
                         ) {
                             cwe_warnings.push(generate_cwe_warning(sub, &callsite_tid));
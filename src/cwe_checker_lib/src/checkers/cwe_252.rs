@@ -0,0 +1,693 @@
+//! This module implements a check for CWE-252: Unchecked Return Value
+//! in combination with CWE-250: Execution with Unnecessary Privileges.
+//!
+//! Privilege dropping functions like `setuid` may fail, in which case the program keeps its privileges.
+//! If the return value of such a call is not checked and the program executes another program afterwards,
+//! the executed program runs with the privileges that should have been dropped.
+//!
+//! See <https://cwe.mitre.org/data/definitions/252.html>
+//! and <https://cwe.mitre.org/data/definitions/250.html> for detailed descriptions.
+//!
+//! ## How the check works
+//!
+//! For each call to a privilege dropping function configured in config.json
+//! we track the return value through registers and through the memory cells known to the
+//! [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! along all paths of the calling function starting at the return site of the call.
+//! On a path the return value counts as checked once a conditional jump depends on it.
+//! If a call to one of the configured `exec`-like functions is reachable on a path where the return value is unchecked,
+//! a warning is generated.
+//! Calls to functions from which a call to an `exec`-like function is reachable in the call graph are treated the same way.
+//!
+//! ## False Positives
+//!
+//! - Checks of the return value in other functions (e.g. in a wrapper function) are not recognized.
+//! - The called function may not actually execute another program on all paths.
+//!
+//! ## False Negatives
+//!
+//! - Any conditional jump depending on the return value counts as a check, even if it does not handle the error.
+//! - Calls to `exec`-like functions after returning from the function calling the privilege dropping function are not detected.
+//! - Indirect calls are ignored.
+
+use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::pointer_inference::PointerInference;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::{HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE252",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions dropping privileges, whose return values have to be checked.
+    privilege_dropping_functions: Vec<String>,
+    /// Functions executing other programs.
+    #[serde(default)]
+    exec_functions: Vec<String>,
+}
+
+/// The locations containing the return value of a privilege dropping call.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct TaintState {
+    /// Registers containing the return value.
+    registers: HashSet<Variable>,
+    /// Memory cells containing the return value, given by the abstract object and the offset into it.
+    memory: HashSet<(AbstractIdentifier, i64)>,
+}
+
+impl TaintState {
+    /// Check whether the value of the expression depends on the return value.
+    fn is_tainted(&self, expression: &Expression) -> bool {
+        expression
+            .input_vars()
+            .into_iter()
+            .any(|var| self.registers.contains(var))
+    }
+
+    /// Merge the other state into `self`. Returns `true` if `self` changed.
+    fn merge_with(&mut self, other: &TaintState) -> bool {
+        let old_size = self.registers.len() + self.memory.len();
+        self.registers.extend(other.registers.iter().cloned());
+        self.memory.extend(other.memory.iter().cloned());
+        self.registers.len() + self.memory.len() != old_size
+    }
+}
+
+/// The context of the check.
+struct Context<'a> {
+    project: &'a Project,
+    pointer_inference: Option<&'a PointerInference<'a>>,
+    /// The TIDs of the `exec`-like functions and of the functions from which a call to them is reachable,
+    /// mapped to their names.
+    exec_reaching_targets: HashMap<Tid, String>,
+}
+
+impl<'a> Context<'a> {
+    /// Update the taint state for the `Def` terms of the block.
+    fn handle_defs(&self, block: &Term<Blk>, state: &mut TaintState) {
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => {
+                    if state.is_tainted(value) {
+                        state.registers.insert(var.clone());
+                    } else {
+                        state.registers.remove(var);
+                    }
+                }
+                Def::Load { var, address } => {
+                    if self
+                        .pointer_inference
+                        .and_then(|pointer_inference| {
+                            pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+                        })
+                        .map(|cell| state.memory.contains(&cell))
+                        .unwrap_or(false)
+                    {
+                        state.registers.insert(var.clone());
+                    } else {
+                        state.registers.remove(var);
+                    }
+                }
+                Def::Store { address, value } => {
+                    if let Some(cell) = self.pointer_inference.and_then(|pointer_inference| {
+                        pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+                    }) {
+                        if state.is_tainted(value) {
+                            state.memory.insert(cell);
+                        } else {
+                            state.memory.remove(&cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove the registers not preserved by calls from the taint state.
+    fn handle_call(&self, state: &TaintState) -> TaintState {
+        let mut state = state.clone();
+        match self.project.get_standard_calling_convention() {
+            Some(calling_convention) => state
+                .registers
+                .retain(|var| calling_convention.callee_saved_register.contains(&var.name)),
+            None => state.registers.clear(),
+        }
+        state
+    }
+
+    /// Search for a call to an `exec`-like function (or a function reaching one)
+    /// on a path starting at the given block on which the return value was not checked.
+    /// The taint state contains the locations of the return value at the start of the block.
+    ///
+    /// Returns the found call together with the name of the called function.
+    fn find_unchecked_exec_call(
+        &self,
+        sub: &'a Term<Sub>,
+        start_block: &Tid,
+        start_state: TaintState,
+    ) -> Option<(&'a Term<Jmp>, &str)> {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, TaintState> = HashMap::new();
+        let mut worklist: Vec<&Tid> = Vec::new();
+        if let Some((block_tid, _)) = blocks.get_key_value(start_block) {
+            block_states.insert(block_tid, start_state);
+            worklist.push(block_tid);
+        }
+        while let Some(block_tid) = worklist.pop() {
+            let block = blocks[block_tid];
+            let mut state = block_states[block_tid].clone();
+            self.handle_defs(block, &mut state);
+            let mut successors = Vec::new();
+            for jmp in block.term.jmps.iter() {
+                match &jmp.term {
+                    Jmp::CBranch { condition, .. } if state.is_tainted(condition) => {
+                        // The return value is checked on all paths leaving the block.
+                        successors.clear();
+                        break;
+                    }
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        successors.push((target, state.clone()))
+                    }
+                    Jmp::Call { target, return_ } => {
+                        if let Some(name) = self.exec_reaching_targets.get(target) {
+                            return Some((jmp, name));
+                        }
+                        if let Some(return_target) = return_ {
+                            successors.push((return_target, self.handle_call(&state)));
+                        }
+                    }
+                    Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => successors.push((return_target, self.handle_call(&state))),
+                    Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } => successors.push((return_target, state.clone())),
+                    _ => (),
+                }
+            }
+            for (successor, successor_state) in successors {
+                if let Some((successor, _)) = blocks.get_key_value(successor) {
+                    let changed = match block_states.get_mut(successor) {
+                        Some(state) => state.merge_with(&successor_state),
+                        None => {
+                            block_states.insert(successor, successor_state);
+                            true
+                        }
+                    };
+                    if changed && !worklist.contains(successor) {
+                        worklist.push(successor);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Compute the TIDs of the `exec`-like functions and of all functions of the program
+/// from which a call to an `exec`-like function is reachable in the call graph.
+fn get_exec_reaching_targets(
+    analysis_results: &AnalysisResults,
+    exec_symbols: &HashMap<Tid, &ExternSymbol>,
+) -> HashMap<Tid, String> {
+    let program = &analysis_results.project.program;
+    let mut targets: HashMap<Tid, String> = exec_symbols
+        .iter()
+        .map(|(tid, symbol)| (tid.clone(), symbol.name.clone()))
+        .collect();
+    let mut worklist: Vec<&Tid> = program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| !get_callsites(sub, exec_symbols).is_empty())
+        .map(|sub| &sub.tid)
+        .collect();
+    let sub_names: HashMap<&Tid, &str> = program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub.term.display_name()))
+        .collect();
+    let call_graph = analysis_results.call_graph();
+    while let Some(sub_tid) = worklist.pop() {
+        if targets.contains_key(sub_tid) {
+            continue;
+        }
+        targets.insert(sub_tid.clone(), sub_names[sub_tid].to_string());
+        worklist.extend(call_graph.get_callers(sub_tid));
+    }
+    targets
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    privilege_dropping_call: &Term<Jmp>,
+    privilege_dropping_symbol: &ExternSymbol,
+    exec_call: &Term<Jmp>,
+    exec_target: &str,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Unchecked Return Value) The return value of {} at {} in function {} is not checked before {} is called at {}",
+            privilege_dropping_symbol.name,
+            privilege_dropping_call.tid.address,
+            sub.term.display_name(),
+            exec_target,
+            exec_call.tid.address
+        ),
+    )
+    .tids(vec![
+        format!("{}", privilege_dropping_call.tid),
+        format!("{}", exec_call.tid),
+    ])
+    .addresses(vec![
+        privilege_dropping_call.tid.address.clone(),
+        exec_call.tid.address.clone(),
+    ])
+    .symbols(vec![
+        privilege_dropping_symbol.name.clone(),
+        exec_target.to_string(),
+    ])
+}
+
+/// A call to a privilege dropping function whose return value is not checked
+/// before a call to one of the target functions (or to a function reaching one of them).
+pub(crate) struct UncheckedCall<'a> {
+    /// The function containing the call.
+    pub sub: &'a Term<Sub>,
+    /// The call to the privilege dropping function.
+    pub privilege_dropping_call: &'a Term<Jmp>,
+    /// The privilege dropping function.
+    pub privilege_dropping_symbol: &'a ExternSymbol,
+    /// The call to the target function reached without checking the return value.
+    pub target_call: &'a Term<Jmp>,
+    /// The name of the called target function.
+    pub target_name: String,
+}
+
+/// For each call to one of the privilege dropping functions search for a call to one of the target functions
+/// (or to a function from which a call to one of them is reachable)
+/// that may be executed without checking the return value of the privilege dropping function first.
+pub(crate) fn find_unchecked_calls<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    privilege_dropping_symbols: &HashMap<Tid, &'a ExternSymbol>,
+    target_symbols: &HashMap<Tid, &ExternSymbol>,
+) -> Vec<UncheckedCall<'a>> {
+    let project = analysis_results.project;
+    let context = Context {
+        project,
+        pointer_inference: analysis_results.pointer_inference(),
+        exec_reaching_targets: get_exec_reaching_targets(analysis_results, target_symbols),
+    };
+    let mut unchecked_calls = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, jmp, symbol) in get_callsites(sub, privilege_dropping_symbols) {
+            let return_target = match &jmp.term {
+                Jmp::Call {
+                    return_: Some(return_target),
+                    ..
+                } => return_target,
+                _ => continue,
+            };
+            let mut state = TaintState::default();
+            for return_value in symbol.return_values.iter() {
+                if let Arg::Register(var) = return_value {
+                    state.registers.insert(var.clone());
+                }
+            }
+            if let Some((target_call, target_name)) =
+                context.find_unchecked_exec_call(sub, return_target, state)
+            {
+                unchecked_calls.push(UncheckedCall {
+                    sub,
+                    privilege_dropping_call: jmp,
+                    privilege_dropping_symbol: symbol,
+                    target_call,
+                    target_name: target_name.to_string(),
+                });
+            }
+        }
+    }
+    unchecked_calls
+}
+
+/// Run the CWE check.
+/// For each call to a privilege dropping function we check whether an `exec`-like function may get called afterwards
+/// without checking the return value of the privilege dropping function first.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let privilege_dropping_symbols = get_symbol_map(project, &config.privilege_dropping_functions);
+    if privilege_dropping_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let exec_symbols = get_symbol_map(project, &config.exec_functions);
+    let cwe_warnings =
+        find_unchecked_calls(analysis_results, &privilege_dropping_symbols, &exec_symbols)
+            .into_iter()
+            .map(|unchecked_call| {
+                generate_cwe_warning(
+                    unchecked_call.sub,
+                    unchecked_call.privilege_dropping_call,
+                    unchecked_call.privilege_dropping_symbol,
+                    unchecked_call.target_call,
+                    &unchecked_call.target_name,
+                )
+            })
+            .collect();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    #[test]
+    fn unchecked_exec_calls() {
+        let project = Project::mock_empty();
+        let return_register = Variable::mock("RAX", 8);
+        let flag = Variable::mock("ZF", 1);
+        let call_system = Jmp::Call {
+            target: Tid::new("system"),
+            return_: None,
+        };
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "return_site",
+                vec![Def::assign(
+                    "check",
+                    flag.clone(),
+                    Expression::Var(return_register.clone()).un_op(UnOpType::BoolNegate),
+                )],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("error"),
+                        condition: Expression::Var(flag.clone()),
+                    },
+                    Jmp::Branch(Tid::new("exec")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "overwrite",
+                vec![Def::assign(
+                    "overwrite_def",
+                    flag.clone(),
+                    Expression::Const(Bitvector::from_u8(0)),
+                )],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("error"),
+                        condition: Expression::Var(flag),
+                    },
+                    Jmp::Branch(Tid::new("exec")),
+                ],
+            ),
+            Blk::mock_with_jmps("exec", Vec::new(), vec![call_system]),
+            Blk::mock_with_jmps("error", Vec::new(), Vec::new()),
+        ];
+        let context = Context {
+            project: &project,
+            pointer_inference: None,
+            exec_reaching_targets: vec![(Tid::new("system"), "system".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        let mut state = TaintState::default();
+        state.registers.insert(return_register);
+        // The return value is checked before the call to `system`.
+        assert!(context
+            .find_unchecked_exec_call(&sub, &Tid::new("return_site"), state.clone())
+            .is_none());
+        // The conditional jump does not depend on the return value.
+        let (exec_call, exec_target) = context
+            .find_unchecked_exec_call(&sub, &Tid::new("overwrite"), state)
+            .unwrap();
+        assert_eq!(exec_call.tid, Tid::new("exec_jmp_0"));
+        assert_eq!(exec_target, "system");
+    }
+
+    fn extern_symbol(name: &str) -> ExternSymbol {
+        let mut symbol = ExternSymbol::mock();
+        symbol.tid = Tid::new(name);
+        symbol.name = name.to_string();
+        symbol
+    }
+
+    /// A comparison of the given register with zero, followed by a conditional jump
+    /// to the block `{name}_error` and a jump to the block `{name}_exec` otherwise.
+    fn check_block(name: &str, block_name: &str, var: &str) -> Term<Blk> {
+        let flag = Variable::mock("ZF", 1);
+        Blk::mock_with_jmps(
+            block_name,
+            vec![Def::assign(
+                &format!("{}_compare", block_name),
+                flag.clone(),
+                Expression::BinOp {
+                    op: BinOpType::IntEqual,
+                    lhs: Box::new(Expression::Var(Variable::mock(var, 8))),
+                    rhs: Box::new(Expression::const_from_i64(0)),
+                },
+            )],
+            vec![
+                Jmp::CBranch {
+                    target: Tid::new(format!("{}_error", name)),
+                    condition: Expression::Var(flag),
+                },
+                Jmp::Branch(Tid::new(format!("{}_exec", name))),
+            ],
+        )
+    }
+
+    /// The final blocks of the test functions, calling `exec_target` in the block `{name}_exec`.
+    fn exec_blocks(name: &str, exec_target: &str) -> Vec<Term<Blk>> {
+        let ret = || Jmp::Return(Expression::const_from_i64(0));
+        vec![
+            Blk::mock_with_jmps(
+                &format!("{}_exec", name),
+                Vec::new(),
+                vec![Jmp::mock_call(exec_target, &format!("{}_error", name))],
+            ),
+            Blk::mock_with_jmps(&format!("{}_error", name), Vec::new(), vec![ret()]),
+        ]
+    }
+
+    fn sub(name: &str, blocks: Vec<Term<Blk>>) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        sub.term.blocks = blocks;
+        sub
+    }
+
+    /// Run the check on the project and return the TIDs of the reported privilege dropping calls
+    /// together with the names of the called `exec`-like functions.
+    fn reported_calls(project: &Project, config: serde_json::Value) -> Vec<(String, String)> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| (warning.tids[0].clone(), warning.symbols[1].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn privilege_dropping_calls() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![
+            extern_symbol("setuid"),
+            extern_symbol("system"),
+            extern_symbol("puts"),
+        ];
+        let setuid_block = |name: &str, defs: Vec<Term<Def>>, return_: &str| {
+            Blk::mock_with_jmps(
+                &format!("{}_0", name),
+                defs,
+                vec![Jmp::mock_call("setuid", &format!("{}_{}", name, return_))],
+            )
+        };
+        let rbp = Expression::Var(Variable::mock("RBP", 8));
+        // The frame pointer is callee-saved, so that it is not changed by calls.
+        let frame_pointer = |tid: &str| {
+            Def::assign(
+                tid,
+                Variable::mock("RBP", 8),
+                Expression::Var(Variable::mock("RSP", 8)).plus_const(-0x10),
+            )
+        };
+        project.program.term.subs = vec![
+            // setuid(0); system(...)
+            sub(
+                "unchecked",
+                [
+                    vec![Blk::mock_with_jmps(
+                        "unchecked_0",
+                        Vec::new(),
+                        vec![Jmp::mock_call("setuid", "unchecked_exec")],
+                    )],
+                    exec_blocks("unchecked", "system"),
+                ]
+                .concat(),
+            ),
+            // if (setuid(0) == 0) system(...)
+            sub(
+                "checked",
+                [
+                    vec![
+                        setuid_block("checked", Vec::new(), "1"),
+                        check_block("checked", "checked_1", "RAX"),
+                    ],
+                    exec_blocks("checked", "system"),
+                ]
+                .concat(),
+            ),
+            // setuid(0); run(); with `run` calling `system`
+            sub(
+                "wrapper",
+                [
+                    vec![setuid_block("wrapper", Vec::new(), "exec")],
+                    exec_blocks("wrapper", "run"),
+                ]
+                .concat(),
+            ),
+            sub(
+                "run",
+                vec![
+                    Blk::mock_with_jmps(
+                        "run_0",
+                        Vec::new(),
+                        vec![Jmp::mock_call("system", "run_1")],
+                    ),
+                    Blk::mock_with_jmps(
+                        "run_1",
+                        Vec::new(),
+                        vec![Jmp::Return(Expression::const_from_i64(0))],
+                    ),
+                ],
+            ),
+            // The return value is saved on the stack during a call to `puts` and checked afterwards.
+            sub(
+                "saved",
+                [
+                    vec![
+                        setuid_block("saved", vec![frame_pointer("saved_frame")], "1"),
+                        Blk::mock_with_jmps(
+                            "saved_1",
+                            vec![Def::store(
+                                "saved_store",
+                                rbp.clone(),
+                                Expression::Var(Variable::mock("RAX", 8)),
+                            )],
+                            vec![Jmp::mock_call("puts", "saved_2")],
+                        ),
+                        Blk::mock_with_jmps(
+                            "saved_2",
+                            vec![Def::load(
+                                "saved_load",
+                                Variable::mock("RCX", 8),
+                                rbp.clone(),
+                            )],
+                            vec![Jmp::Branch(Tid::new("saved_3"))],
+                        ),
+                        check_block("saved", "saved_3", "RCX"),
+                    ],
+                    exec_blocks("saved", "system"),
+                ]
+                .concat(),
+            ),
+            // The return register is checked after it was overwritten by a call to `puts`.
+            sub(
+                "clobbered",
+                [
+                    vec![
+                        setuid_block("clobbered", Vec::new(), "1"),
+                        Blk::mock_with_jmps(
+                            "clobbered_1",
+                            Vec::new(),
+                            vec![Jmp::mock_call("puts", "clobbered_2")],
+                        ),
+                        check_block("clobbered", "clobbered_2", "RAX"),
+                    ],
+                    exec_blocks("clobbered", "system"),
+                ]
+                .concat(),
+            ),
+        ];
+
+        let config = json!({
+            "privilege_dropping_functions": ["setuid"],
+            "exec_functions": ["system"]
+        });
+        let string_pair = |call: &str, target: &str| (call.to_string(), target.to_string());
+        assert_eq!(
+            reported_calls(&project, config),
+            vec![
+                string_pair("unchecked_0_jmp_0", "system"),
+                string_pair("wrapper_0_jmp_0", "run"),
+                string_pair("clobbered_0_jmp_0", "system"),
+            ]
+        );
+        // Without exec functions nothing is reported.
+        let config = json!({"privilege_dropping_functions": ["setuid"]});
+        assert!(reported_calls(&project, config).is_empty());
+        // The return value of `puts` is checked in `clobbered`, but not in `saved`.
+        let config = json!({
+            "privilege_dropping_functions": ["puts"],
+            "exec_functions": ["system"]
+        });
+        assert_eq!(
+            reported_calls(&project, config),
+            vec![string_pair("saved_1_jmp_0", "system")]
+        );
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: Config =
+            parse_config(&json!({"privilege_dropping_functions": ["setuid"]})).unwrap();
+        assert!(config.exec_functions.is_empty());
+        assert!(parse_config::<Config>(&json!({"exec_functions": ["system"]})).is_err());
+    }
+}
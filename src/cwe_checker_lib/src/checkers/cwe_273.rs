@@ -0,0 +1,270 @@
+//! This module implements a check for CWE-273: Improper Check for Dropped Privileges.
+//!
+//! Privilege dropping functions like `setuid` may fail, in which case the program keeps its privileges.
+//! Programs that do not check whether dropping privileges succeeded
+//! may perform operations with privileges that they should not have anymore.
+//! Furthermore, privileges have to be dropped in the right order:
+//! After the user ID was changed from root (e.g. by `setuid`),
+//! the program is usually no longer allowed to change its group ID or supplementary groups.
+//! So group privileges have to be dropped before user privileges.
+//!
+//! See <https://cwe.mitre.org/data/definitions/273.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the `user_dropping_functions` or `group_dropping_functions` configured in config.json
+//! we track the return value along all paths of the calling function, as in the [CWE-252 check](super::cwe_252).
+//! If one of the configured `privileged_functions` (e.g. `open` or `bind`) may be called
+//! before the return value is checked, a warning is generated.
+//! Calls to functions from which a call to a privileged function is reachable in the call graph are treated the same way.
+//! Unchecked return values before calls to `exec`-like functions are reported by the CWE-252 check.
+//!
+//! Furthermore, a warning is generated for each call to a user dropping function
+//! from which a call to a group dropping function is reachable in the same function.
+//!
+//! ## False Positives
+//!
+//! - Checks of the return value in other functions (e.g. in a wrapper function) are not recognized.
+//! - Calls to group dropping functions that do not drop privileges (e.g. `setgid(getgid())`) are flagged
+//!   if they happen after dropping user privileges.
+//!
+//! ## False Negatives
+//!
+//! - Any conditional jump depending on the return value counts as a check, even if it does not handle the error.
+//! - Calls to privileged functions after returning from the function calling the privilege dropping function are not detected.
+//! - Group dropping calls are only detected in the function calling the user dropping function.
+
+use super::cwe_252::find_unchecked_calls;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::{HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE273",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions dropping user privileges, e.g. `setuid`.
+    user_dropping_functions: Vec<String>,
+    /// Functions dropping group privileges, e.g. `setgid` or `setgroups`.
+    #[serde(default)]
+    group_dropping_functions: Vec<String>,
+    /// Functions whose effect depends on the privileges of the program, e.g. `open`.
+    privileged_functions: Vec<String>,
+}
+
+/// Search for a call to one of the target functions reachable from the start of the given block.
+fn find_reachable_call<'a>(
+    sub: &'a Term<Sub>,
+    start_block: &Tid,
+    targets: &HashMap<Tid, &ExternSymbol>,
+) -> Option<&'a Term<Jmp>> {
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut visited: HashSet<Tid> = HashSet::new();
+    let mut worklist: Vec<Tid> = vec![start_block.clone()];
+    while let Some(block_tid) = worklist.pop() {
+        let block = match blocks.get(&block_tid) {
+            Some(block) => block,
+            None => continue,
+        };
+        if !visited.insert(block_tid) {
+            continue;
+        }
+        for jmp in block.term.jmps.iter() {
+            match &jmp.term {
+                Jmp::Call { target, return_ } => {
+                    if targets.contains_key(target) {
+                        return Some(jmp);
+                    }
+                    worklist.extend(return_.iter().cloned());
+                }
+                Jmp::Branch(target) | Jmp::CBranch { target, .. } => worklist.push(target.clone()),
+                Jmp::CallInd { return_, .. } | Jmp::CallOther { return_, .. } => {
+                    worklist.extend(return_.iter().cloned())
+                }
+                Jmp::BranchInd(_) => worklist.extend(
+                    block
+                        .term
+                        .indirect_jmp_targets
+                        .iter()
+                        .map(|address| Tid::blk_id_at_address(address)),
+                ),
+                Jmp::Return(_) => (),
+            }
+        }
+    }
+    None
+}
+
+/// Generate the warning for a privilege dropping call whose return value is not checked before a privileged operation.
+fn generate_unchecked_warning(
+    sub: &Term<Sub>,
+    dropping_call: &Term<Jmp>,
+    dropping_symbol: &ExternSymbol,
+    privileged_call: &Term<Jmp>,
+    privileged_target: &str,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Check for Dropped Privileges) The return value of {} at {} in function {} is not checked before {} is called at {}",
+            dropping_symbol.name,
+            dropping_call.tid.address,
+            sub.term.display_name(),
+            privileged_target,
+            privileged_call.tid.address
+        ),
+    )
+    .tids(vec![
+        dropping_call.tid.to_string(),
+        privileged_call.tid.to_string(),
+    ])
+    .addresses(vec![
+        dropping_call.tid.address.clone(),
+        privileged_call.tid.address.clone(),
+    ])
+    .symbols(vec![
+        dropping_symbol.name.clone(),
+        privileged_target.to_string(),
+    ])
+}
+
+/// Generate the warning for a call to a group dropping function after user privileges were dropped.
+fn generate_order_warning(
+    sub: &Term<Sub>,
+    user_call: &Term<Jmp>,
+    user_symbol: &ExternSymbol,
+    group_call: &Term<Jmp>,
+    group_symbol: &ExternSymbol,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Check for Dropped Privileges) {} at {} in function {} is called after user privileges were dropped by {} at {}",
+            group_symbol.name,
+            group_call.tid.address,
+            sub.term.display_name(),
+            user_symbol.name,
+            user_call.tid.address
+        ),
+    )
+    .tids(vec![user_call.tid.to_string(), group_call.tid.to_string()])
+    .addresses(vec![
+        user_call.tid.address.clone(),
+        group_call.tid.address.clone(),
+    ])
+    .symbols(vec![user_symbol.name.clone(), group_symbol.name.clone()])
+}
+
+/// Run the CWE check.
+/// Flag privilege dropping calls whose return values are not checked before privileged operations
+/// and calls dropping group privileges after user privileges were dropped.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let user_dropping_symbols = get_symbol_map(project, &config.user_dropping_functions);
+    let group_dropping_symbols = get_symbol_map(project, &config.group_dropping_functions);
+    if user_dropping_symbols.is_empty() && group_dropping_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let mut cwe_warnings = Vec::new();
+
+    let privilege_dropping_symbols: HashMap<Tid, &ExternSymbol> = user_dropping_symbols
+        .iter()
+        .chain(group_dropping_symbols.iter())
+        .map(|(tid, symbol)| (tid.clone(), *symbol))
+        .collect();
+    let privileged_symbols = get_symbol_map(project, &config.privileged_functions);
+    for unchecked_call in find_unchecked_calls(
+        analysis_results,
+        &privilege_dropping_symbols,
+        &privileged_symbols,
+    ) {
+        cwe_warnings.push(generate_unchecked_warning(
+            unchecked_call.sub,
+            unchecked_call.privilege_dropping_call,
+            unchecked_call.privilege_dropping_symbol,
+            unchecked_call.target_call,
+            &unchecked_call.target_name,
+        ));
+    }
+
+    for sub in project.program.term.subs.iter() {
+        for (_block, user_call, user_symbol) in get_callsites(sub, &user_dropping_symbols) {
+            if let Jmp::Call {
+                return_: Some(return_target),
+                ..
+            } = &user_call.term
+            {
+                if let Some(group_call) =
+                    find_reachable_call(sub, return_target, &group_dropping_symbols)
+                {
+                    let group_symbol = match &group_call.term {
+                        Jmp::Call { target, .. } => group_dropping_symbols[target],
+                        _ => continue,
+                    };
+                    cwe_warnings.push(generate_order_warning(
+                        sub,
+                        user_call,
+                        user_symbol,
+                        group_call,
+                        group_symbol,
+                    ));
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reachable_calls() {
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with(
+                "drop_groups",
+                Vec::new(),
+                Jmp::mock_call("setgid", "drop_user"),
+            ),
+            Blk::mock_with("drop_user", Vec::new(), Jmp::mock_call("setuid", "loop")),
+            Blk::mock_with("loop", Vec::new(), Jmp::Branch(Tid::new("loop"))),
+        ];
+        let mut setgid = ExternSymbol::mock();
+        setgid.tid = Tid::new("setgid");
+        let targets: HashMap<Tid, &ExternSymbol> =
+            vec![(setgid.tid.clone(), &setgid)].into_iter().collect();
+        assert_eq!(
+            find_reachable_call(&sub, &Tid::new("drop_groups"), &targets)
+                .unwrap()
+                .tid,
+            Tid::new("drop_groups_jmp")
+        );
+        assert!(find_reachable_call(&sub, &Tid::new("drop_user"), &targets).is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! This module implements a check for CWE-295: Improper Certificate Validation.
+//!
+//! TLS connections without certificate validation are open to man-in-the-middle attacks,
+//! since an attacker can present an arbitrary certificate to the client.
+//! Certificate validation is often disabled during development and then forgotten,
+//! e.g. by `curl_easy_setopt(handle, CURLOPT_SSL_VERIFYPEER, 0)`
+//! or by `SSL_CTX_set_verify(ctx, SSL_VERIFY_NONE, NULL)`.
+//!
+//! See <https://cwe.mitre.org/data/definitions/295.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The `disabling_calls` in config.json describe calls to library functions that disable certificate validation.
+//! Each entry consists of the name of the function and conditions on the values of some of its parameters,
+//! e.g. that the option parameter of `curl_easy_setopt` is `CURLOPT_SSL_VERIFYPEER` and the value parameter is zero.
+//! For each call to one of these functions the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! computes the values of the parameters.
+//! If all parameters of one of the entries are constants satisfying the conditions, a warning is generated.
+//!
+//! ## False Positives
+//!
+//! - Certificate validation may be disabled on purpose, e.g. in test code or for connections to localhost.
+//! - Certificate validation may be performed manually after the TLS handshake.
+//!
+//! ## False Negatives
+//!
+//! - Parameter values that are not known to be constant (e.g. read from a configuration file) are not flagged.
+//! - Verification callbacks that accept all certificates are not detected.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::State;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE295",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Calls to library functions that disable certificate validation.
+    disabling_calls: Vec<DisablingCall>,
+}
+
+/// A call to a library function that disables certificate validation
+/// if the parameters have the configured constant values.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DisablingCall {
+    /// The name of the called function.
+    symbol: String,
+    /// The conditions that the parameters of the call have to satisfy.
+    parameters: Vec<ParameterCondition>,
+    /// A short description of the effect of the call, used in the warning.
+    description: String,
+}
+
+/// A condition on the value of a parameter.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ParameterCondition {
+    /// The index of the parameter.
+    index: usize,
+    /// The parameter has to be a constant equal to one of these values.
+    values: Vec<u64>,
+}
+
+/// Check whether the parameters of the call to the extern symbol satisfy the conditions of the disabling call.
+fn is_disabling_call(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    symbol: &ExternSymbol,
+    disabling_call: &DisablingCall,
+) -> bool {
+    disabling_call.parameters.iter().all(|condition| {
+        let value = state
+            .eval_parameter_arg(
+                &get_variadic_parameter(project, symbol, condition.index),
+                &project.stack_pointer_register,
+                runtime_memory_image,
+            )
+            .ok()
+            .and_then(|value| value.try_to_bitvec().ok())
+            .and_then(|value| value.try_to_u64().ok());
+        match value {
+            Some(value) => condition.values.contains(&value),
+            None => false,
+        }
+    })
+}
+
+/// Generate the warning for a call that disables certificate validation.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    disabling_call: &DisablingCall,
+) -> CweWarning {
+    let description = format!(
+        "(Improper Certificate Validation) {} disables certificate validation at {}: {}.",
+        sub.term.display_name(),
+        call.tid.address,
+        disabling_call.description
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![call.tid.address.clone()])
+        .tids(vec![call.tid.to_string()])
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            disabling_call.symbol.clone(),
+        ])
+}
+
+/// Flag calls to library functions that disable the validation of TLS certificates.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let symbol_names: Vec<String> = config
+        .disabling_calls
+        .iter()
+        .map(|disabling_call| disabling_call.symbol.clone())
+        .collect();
+    let symbol_map = get_symbol_map(project, &symbol_names);
+    if symbol_map.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_, call, symbol) in get_callsites(sub, &symbol_map) {
+            let state = match pointer_inference.get_state_at_tid(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            if let Some(disabling_call) = config.disabling_calls.iter().find(|disabling_call| {
+                disabling_call.symbol == symbol.name
+                    && is_disabling_call(
+                        project,
+                        analysis_results.runtime_memory_image,
+                        &state,
+                        symbol,
+                        disabling_call,
+                    )
+            }) {
+                warnings.push(generate_cwe_warning(sub, call, disabling_call));
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabling_call_detection() {
+        let mut project = Project::mock_empty();
+        let mut calling_convention = CallingConvention::mock();
+        calling_convention.parameter_register =
+            vec!["RDI".to_string(), "RSI".to_string(), "RDX".to_string()];
+        project.calling_conventions = vec![calling_convention];
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "curl_easy_setopt".to_string();
+        let disabling_call: DisablingCall = serde_json::from_value(serde_json::json!({
+            "symbol": "curl_easy_setopt",
+            "parameters": [{"index": 1, "values": [64]}, {"index": 2, "values": [0]}],
+            "description": "CURLOPT_SSL_VERIFYPEER set to 0"
+        }))
+        .unwrap();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        state.set_register(&Variable::mock("RSI", 8), Bitvector::from_u64(64).into());
+        // The value parameter is unknown.
+        assert!(!is_disabling_call(
+            &project,
+            &runtime_memory_image,
+            &state,
+            &symbol,
+            &disabling_call
+        ));
+        state.set_register(&Variable::mock("RDX", 8), Bitvector::from_u64(0).into());
+        assert!(is_disabling_call(
+            &project,
+            &runtime_memory_image,
+            &state,
+            &symbol,
+            &disabling_call
+        ));
+        // CURLOPT_SSL_VERIFYHOST
+        state.set_register(&Variable::mock("RSI", 8), Bitvector::from_u64(81).into());
+        assert!(!is_disabling_call(
+            &project,
+            &runtime_memory_image,
+            &state,
+            &symbol,
+            &disabling_call
+        ));
+    }
+}
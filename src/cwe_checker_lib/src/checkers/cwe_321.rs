@@ -0,0 +1,237 @@
+//! This module implements a check for CWE-321: Use of Hard-coded Cryptographic Key.
+//!
+//! Keys embedded in the binary can be extracted by everyone with access to the binary.
+//! Data encrypted or authenticated with such a key is not protected against attackers,
+//! and the key cannot be changed without patching the binary.
+//!
+//! See <https://cwe.mitre.org/data/definitions/321.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The `key_parameters` map in config.json maps names of extern functions of cryptographic libraries
+//! to the index of their key parameter and optionally to the index of the parameter containing the key size.
+//! For each call to these functions the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! computes the value of the key parameter.
+//! If it is an address in the global memory of the binary, a warning is generated,
+//! containing the address of the key and its size if known.
+//! The key size is taken from the size parameter if it is constant
+//! or from the size of the data object starting at the key address in the ELF symbol tables.
+//!
+//! Keys in writeable memory are only flagged if the key (or its first `MIN_KEY_SIZE` bytes if the size is unknown)
+//! is not zero-initialized in the binary, since zero-initialized buffers are usually filled with the key at runtime.
+//!
+//! ## False Positives
+//!
+//! - Initialized keys in writeable memory that are overwritten at runtime before their use are flagged.
+//!
+//! ## False Negatives
+//!
+//! - Keys that are copied to the stack or the heap before their use are not detected.
+//! - Keys that are derived from hard-coded data at runtime (e.g. by decoding or XOR-ing) are not detected.
+//! - Functions taking the key inside of a structure are not supported.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::{get_elf_object_symbols, RuntimeMemoryImage};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE321",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The number of bytes checked for zero-initialization of keys of unknown size in writeable memory.
+const MIN_KEY_SIZE: u64 = 16;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions taking a cryptographic key as parameter.
+    key_parameters: BTreeMap<String, KeyParameter>,
+}
+
+/// The key parameter of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct KeyParameter {
+    /// The index of the parameter pointing to the key.
+    parameter: usize,
+    /// The index of the parameter containing the size of the key, if there is one.
+    #[serde(default)]
+    size_parameter: Option<usize>,
+    /// If set, the size parameter contains the key size in bits instead of bytes.
+    #[serde(default)]
+    size_in_bits: bool,
+}
+
+/// Get the value of the parameter with the given index of a call to the extern symbol as a constant.
+fn get_constant_parameter(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    symbol: &ExternSymbol,
+    index: usize,
+) -> Option<u64> {
+    match state.eval_parameter_arg(
+        &get_variadic_parameter(project, symbol, index),
+        &project.stack_pointer_register,
+        runtime_memory_image,
+    ) {
+        Ok(value @ Data::Value(_)) => value.try_to_bitvec().ok()?.try_to_u64().ok(),
+        _ => None,
+    }
+}
+
+/// Check whether the key at the given address is hard-coded in the binary.
+///
+/// Keys in read-only memory are always hard-coded.
+/// Keys in writeable memory are hard-coded if they are not zero-initialized.
+fn is_hardcoded_key(
+    runtime_memory_image: &RuntimeMemoryImage,
+    address: u64,
+    size: Option<u64>,
+) -> bool {
+    match runtime_memory_image.is_address_writeable(&Bitvector::from_u64(address)) {
+        Ok(false) => true,
+        Ok(true) => {
+            let size = size.unwrap_or(MIN_KEY_SIZE);
+            match runtime_memory_image.read_loaded_bytes(address, size) {
+                Ok(bytes) => bytes.iter().any(|byte| *byte != 0),
+                Err(_) => false,
+            }
+        }
+        Err(_) => false,
+    }
+}
+
+/// Generate the warning for a call to a function taking a hard-coded key.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    key_address: u64,
+    key_size: Option<u64>,
+) -> CweWarning {
+    let size_description = match key_size {
+        Some(size) => format!(" ({} bytes)", size),
+        None => String::new(),
+    };
+    let description = format!(
+        "(Use of Hard-coded Cryptographic Key) The key passed to {} in {} at {} is the constant data at {:#x}{}.",
+        symbol.name, sub.term.display_name(), call.tid.address, key_address, size_description
+    );
+    let mut other = vec![vec![
+        "key_address".to_string(),
+        format!("{:#x}", key_address),
+    ]];
+    if let Some(size) = key_size {
+        other.push(vec!["key_size".to_string(), size.to_string()]);
+    }
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![call.tid.address.clone()])
+        .tids(vec![call.tid.to_string()])
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            symbol.name.clone(),
+        ])
+        .other(other)
+}
+
+/// Flag calls to cryptographic functions with keys located in the global memory of the binary.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let symbol_names: Vec<String> = config.key_parameters.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbol_names);
+    if symbol_map.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    // The sizes of the data objects in the ELF symbol tables, indexed by their addresses.
+    let object_sizes: HashMap<u64, u64> = get_elf_object_symbols(analysis_results.binary)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, _, size)| *size > 0)
+        .map(|(_, address, size)| (address + project.program.term.address_base_offset, size))
+        .collect();
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_, call, symbol) in get_callsites(sub, &symbol_map) {
+            let state = match pointer_inference.get_state_at_tid(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            let key_parameter = &config.key_parameters[&symbol.name];
+            let key_address = match get_constant_parameter(
+                project,
+                runtime_memory_image,
+                &state,
+                symbol,
+                key_parameter.parameter,
+            ) {
+                Some(address) => address,
+                None => continue,
+            };
+            let key_size = key_parameter
+                .size_parameter
+                .and_then(|index| {
+                    get_constant_parameter(project, runtime_memory_image, &state, symbol, index)
+                })
+                .map(|size| {
+                    if key_parameter.size_in_bits {
+                        size / 8
+                    } else {
+                        size
+                    }
+                })
+                .or_else(|| object_sizes.get(&key_address).cloned());
+            if is_hardcoded_key(runtime_memory_image, key_address, key_size) {
+                warnings.push(generate_cwe_warning(
+                    sub,
+                    call,
+                    symbol,
+                    key_address,
+                    key_size,
+                ));
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hardcoded_key_detection() {
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        // Read-only memory
+        assert!(is_hardcoded_key(&runtime_memory_image, 0x1000, Some(4)));
+        assert!(is_hardcoded_key(&runtime_memory_image, 0x3002, None));
+        // Zero-initialized writeable memory
+        assert!(!is_hardcoded_key(&runtime_memory_image, 0x2000, Some(8)));
+        assert!(!is_hardcoded_key(&runtime_memory_image, 0x2000, None));
+        // Not global memory
+        assert!(!is_hardcoded_key(&runtime_memory_image, 0x4000, Some(8)));
+    }
+}
@@ -0,0 +1,273 @@
+//! This module implements a check for CWE-326: Inadequate Encryption Strength.
+//!
+//! Keys that are too short can be broken by brute force or by known attacks on the underlying problem.
+//! RSA keys, DSA and Diffie-Hellman parameters with less than 2048 bits
+//! and elliptic curves over fields with less than 224 bits are considered inadequate today.
+//!
+//! See <https://cwe.mitre.org/data/definitions/326.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the configured `key_size_parameters` (e.g. `RSA_generate_key_ex`)
+//! we evaluate the key size parameter with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! If it is a constant below the configured minimum for the function, a warning is generated
+//! that contains the key size found.
+//!
+//! For each call to one of the configured `curve_parameters` (e.g. `EC_KEY_new_by_curve_name`)
+//! the numeric identifier (NID) of the curve is evaluated the same way.
+//! If it is the identifier of one of the configured `weak_curves`, a warning is generated
+//! that contains the name and the identifier of the curve.
+//!
+//! ## False Positives
+//!
+//! - Short keys may be generated on purpose, e.g. in tests or for compatibility with legacy devices.
+//!
+//! ## False Negatives
+//!
+//! - Only key sizes known exactly are checked.
+//!   Key sizes read from configuration files or computed at runtime are not reported.
+//! - Curves selected by name (e.g. through `OBJ_txt2nid`) are not checked.
+//! - The NIDs of the `weak_curves` are the ones of OpenSSL.
+//!   Other libraries numbering their curves differently need their own configuration.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE326",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions generating keys or key parameters,
+    /// mapped to the parameter containing the key size in bits.
+    key_size_parameters: BTreeMap<String, KeySizeParameter>,
+    /// Extern functions selecting an elliptic curve,
+    /// mapped to the index of the parameter containing the numeric identifier (NID) of the curve.
+    #[serde(default)]
+    curve_parameters: BTreeMap<String, usize>,
+    /// Names of weak elliptic curves mapped to their numeric identifiers.
+    #[serde(default)]
+    weak_curves: BTreeMap<String, u64>,
+}
+
+/// The key size parameter of an extern function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct KeySizeParameter {
+    /// The index of the parameter.
+    index: usize,
+    /// The minimal key size in bits that is considered adequate.
+    minimum_bits: u64,
+}
+
+/// Get the name of the weak curve with the given numeric identifier.
+fn get_weak_curve(config: &Config, nid: u64) -> Option<&str> {
+    config
+        .weak_curves
+        .iter()
+        .find(|(_, weak_nid)| **weak_nid == nid)
+        .map(|(name, _)| name.as_str())
+}
+
+/// Evaluate the parameter with the given index at the call to a constant.
+///
+/// Returns `None` if the value of the parameter is not known exactly.
+fn eval_constant_parameter(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+    call: &Term<Jmp>,
+    (symbol, index): (&ExternSymbol, usize),
+) -> Option<u64> {
+    let state = pointer_inference.get_state_at_jmp_tid(&call.tid)?;
+    let parameter = get_variadic_parameter(project, symbol, index);
+    match state
+        .eval_parameter_arg(
+            &parameter,
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?
+    {
+        Data::Value(value) => value.try_to_bitvec().ok()?.try_to_u64().ok(),
+        _ => None,
+    }
+}
+
+/// Generate the CWE warning for a call generating a key shorter than the minimal key size.
+fn generate_key_size_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    bits: u64,
+    minimum_bits: u64,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Inadequate Encryption Strength) {} calls {} at {} with a key size of {} bits, but at least {} bits are required",
+            sub.term.display_name(), symbol.name, call.tid.address, bits, minimum_bits
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![
+        vec!["key_size".to_string(), bits.to_string()],
+        vec!["minimum_key_size".to_string(), minimum_bits.to_string()],
+    ])
+}
+
+/// Generate the CWE warning for a call selecting a weak elliptic curve.
+fn generate_curve_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    curve: &str,
+    nid: u64,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Inadequate Encryption Strength) {} calls {} at {} with the weak elliptic curve {} (NID {})",
+            sub.term.display_name(), symbol.name, call.tid.address, curve, nid
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![
+        vec!["curve".to_string(), curve.to_string()],
+        vec!["nid".to_string(), nid.to_string()],
+    ])
+}
+
+/// Run the CWE check.
+/// For each call to a configured function we check whether the key size passed to it is too small
+/// or whether the elliptic curve passed to it is weak.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let key_size_symbol_names: Vec<String> = config.key_size_parameters.keys().cloned().collect();
+    let key_size_symbols = get_symbol_map(project, &key_size_symbol_names);
+    let curve_symbol_names: Vec<String> = config.curve_parameters.keys().cloned().collect();
+    let curve_symbols = get_symbol_map(project, &curve_symbol_names);
+    let eval = |call, symbol, index| {
+        eval_constant_parameter(
+            project,
+            pointer_inference,
+            runtime_memory_image,
+            call,
+            (symbol, index),
+        )
+    };
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, call, symbol) in get_callsites(sub, &key_size_symbols) {
+            let parameter = &config.key_size_parameters[&symbol.name];
+            if let Some(bits) = eval(call, symbol, parameter.index) {
+                if bits < parameter.minimum_bits {
+                    cwe_warnings.push(generate_key_size_warning(
+                        sub,
+                        call,
+                        symbol,
+                        bits,
+                        parameter.minimum_bits,
+                    ));
+                }
+            }
+        }
+        for (_block, call, symbol) in get_callsites(sub, &curve_symbols) {
+            let nid = match eval(call, symbol, config.curve_parameters[&symbol.name]) {
+                Some(nid) => nid,
+                None => continue,
+            };
+            if let Some(curve) = get_weak_curve(&config, nid) {
+                cwe_warnings.push(generate_curve_warning(sub, call, symbol, curve, nid));
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weak_curves() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "key_size_parameters": {},
+            "curve_parameters": { "EC_KEY_new_by_curve_name": 0 },
+            "weak_curves": { "secp160r1": 709, "prime192v1": 409 }
+        }))
+        .unwrap();
+        assert_eq!(get_weak_curve(&config, 409), Some("prime192v1"));
+        assert_eq!(get_weak_curve(&config, 709), Some("secp160r1"));
+        // NID_X9_62_prime256v1
+        assert_eq!(get_weak_curve(&config, 415), None);
+
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "EC_KEY_new_by_curve_name".to_string();
+        let warning = generate_curve_warning(
+            &Sub::mock("func"),
+            &Jmp::call(
+                "call_EC_KEY_new_by_curve_name",
+                "EC_KEY_new_by_curve_name",
+                None,
+            ),
+            &symbol,
+            "prime192v1",
+            409,
+        );
+        assert!(warning
+            .description
+            .ends_with("with the weak elliptic curve prime192v1 (NID 409)"));
+    }
+
+    #[test]
+    fn key_size_warning() {
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "RSA_generate_key_ex".to_string();
+        let warning = generate_key_size_warning(
+            &Sub::mock("func"),
+            &Jmp::call("call_RSA_generate_key_ex", "RSA_generate_key_ex", None),
+            &symbol,
+            1024,
+            2048,
+        );
+        assert!(warning
+            .description
+            .ends_with("with a key size of 1024 bits, but at least 2048 bits are required"));
+        assert_eq!(
+            warning.other,
+            vec![
+                vec!["key_size".to_string(), "1024".to_string()],
+                vec!["minimum_key_size".to_string(), "2048".to_string()]
+            ]
+        );
+    }
+}
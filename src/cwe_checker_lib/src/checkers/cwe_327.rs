@@ -0,0 +1,320 @@
+//! This module implements a check for CWE-327: Use of a Broken or Risky Cryptographic Algorithm.
+//!
+//! Cryptographic algorithms like MD5, DES or RC4 are broken and do not provide the security that they were designed for.
+//! Other algorithms like SHA-1 are only broken for some purposes, e.g. collision attacks make them unsuitable for signatures.
+//!
+//! See <https://cwe.mitre.org/data/definitions/327.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The `algorithms` map in config.json maps names of extern functions of cryptographic libraries
+//! (e.g. OpenSSL, mbedTLS and wolfSSL) to the algorithm that they implement and to a suggested replacement.
+//! A name ending in `*` matches all functions starting with the name, e.g. `DES_*` matches `DES_ecb_encrypt`.
+//! For each function calling extern functions of a broken algorithm one warning per algorithm is generated,
+//! containing the addresses of all calls to functions of the algorithm.
+//!
+//! Algorithms marked with `only_in_signature_context` are only flagged in signature contexts,
+//! i.e. if the calling function or one of its direct callers also calls one of the configured `signature_symbols`.
+//!
+//! ## False Positives
+//!
+//! - Broken algorithms may be used for purposes where their weaknesses do not matter,
+//!   e.g. MD5 as a non-cryptographic checksum or for compatibility with legacy protocols.
+//!
+//! ## False Negatives
+//!
+//! - Algorithms selected at runtime (e.g. by name through `EVP_get_cipherbyname`) are not detected.
+//! - Statically linked or inlined implementations of broken algorithms are not detected.
+//! - Signature contexts spanning more than two functions of the call graph are not detected.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE327",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Names of extern functions mapped to the broken algorithm that they implement.
+    /// Names ending in `*` match all functions starting with the name.
+    algorithms: BTreeMap<String, Algorithm>,
+    /// Names of extern functions creating or verifying signatures.
+    #[serde(default)]
+    signature_symbols: Vec<String>,
+}
+
+/// A broken or risky cryptographic algorithm.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Algorithm {
+    /// The name of the algorithm.
+    name: String,
+    /// The suggested replacement for the algorithm.
+    replacement: String,
+    /// If set, the algorithm is only flagged in signature contexts.
+    #[serde(default)]
+    only_in_signature_context: bool,
+}
+
+/// The calls to extern functions of an algorithm together with the called functions.
+type AlgorithmCalls<'a> = Vec<(&'a Term<Jmp>, &'a ExternSymbol)>;
+
+/// Get the algorithm that the extern function with the given name implements.
+/// Exact matches take precedence over prefix patterns, longer prefixes take precedence over shorter ones.
+fn get_algorithm<'a>(config: &'a Config, symbol_name: &str) -> Option<&'a Algorithm> {
+    if let Some(algorithm) = config.algorithms.get(symbol_name) {
+        return Some(algorithm);
+    }
+    config
+        .algorithms
+        .iter()
+        .filter_map(|(pattern, algorithm)| {
+            let prefix = pattern.strip_suffix('*')?;
+            symbol_name
+                .starts_with(prefix)
+                .then_some((prefix.len(), algorithm))
+        })
+        .max_by_key(|(prefix_length, _)| *prefix_length)
+        .map(|(_, algorithm)| algorithm)
+}
+
+/// Return the targets of all direct calls in the function together with the call terms.
+fn get_direct_calls(sub: &Term<Sub>) -> impl Iterator<Item = (&Term<Jmp>, &Tid)> {
+    sub.term
+        .blocks
+        .iter()
+        .flat_map(|block| block.term.jmps.iter())
+        .filter_map(|jmp| match &jmp.term {
+            Jmp::Call { target, .. } => Some((jmp, target)),
+            _ => None,
+        })
+}
+
+/// Generate the warning for the calls to functions of a broken algorithm in the given function.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    algorithm: &Algorithm,
+    calls: &[(&Term<Jmp>, &ExternSymbol)],
+) -> CweWarning {
+    let mut symbol_names: Vec<&str> = calls
+        .iter()
+        .map(|(_, symbol)| symbol.name.as_str())
+        .collect();
+    symbol_names.sort_unstable();
+    symbol_names.dedup();
+    let description = format!(
+        "(Use of a Broken or Risky Cryptographic Algorithm) {} uses {} ({}) at {}. Use {} instead.",
+        sub.term.display_name(),
+        algorithm.name,
+        symbol_names.join(", "),
+        calls
+            .iter()
+            .map(|(call, _)| call.tid.address.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        algorithm.replacement
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(
+            calls
+                .iter()
+                .map(|(call, _)| call.tid.address.clone())
+                .collect(),
+        )
+        .tids(calls.iter().map(|(call, _)| call.tid.to_string()).collect())
+        .symbols(
+            std::iter::once(sub.term.display_name().to_string())
+                .chain(symbol_names.iter().map(|name| name.to_string()))
+                .collect(),
+        )
+        .other(vec![
+            vec!["algorithm".to_string(), algorithm.name.clone()],
+            vec!["replacement".to_string(), algorithm.replacement.clone()],
+        ])
+}
+
+/// Flag calls to extern functions implementing broken or risky cryptographic algorithms.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let mut algorithm_symbols: HashMap<&Tid, (&ExternSymbol, &Algorithm)> = HashMap::new();
+    let mut signature_symbols: HashSet<&Tid> = HashSet::new();
+    for symbol in project.program.term.extern_symbols.iter() {
+        if let Some(algorithm) = get_algorithm(&config, &symbol.name) {
+            algorithm_symbols.insert(&symbol.tid, (symbol, algorithm));
+        }
+        if config.signature_symbols.contains(&symbol.name) {
+            signature_symbols.insert(&symbol.tid);
+        }
+    }
+    if algorithm_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let signing_subs: HashSet<&Tid> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| get_direct_calls(sub).any(|(_, target)| signature_symbols.contains(target)))
+        .map(|sub| &sub.tid)
+        .collect();
+    let call_graph = analysis_results.call_graph();
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let is_signature_context = signing_subs.contains(&sub.tid)
+            || call_graph
+                .get_callers(&sub.tid)
+                .any(|caller| signing_subs.contains(caller));
+        let mut calls_by_algorithm: BTreeMap<&str, (&Algorithm, AlgorithmCalls)> = BTreeMap::new();
+        for (call, target) in get_direct_calls(sub) {
+            if let Some((symbol, algorithm)) = algorithm_symbols.get(target) {
+                if !algorithm.only_in_signature_context || is_signature_context {
+                    calls_by_algorithm
+                        .entry(algorithm.name.as_str())
+                        .or_insert_with(|| (algorithm, Vec::new()))
+                        .1
+                        .push((call, symbol));
+                }
+            }
+        }
+        for (algorithm, calls) in calls_by_algorithm.values() {
+            warnings.push(generate_cwe_warning(sub, algorithm, calls));
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    fn mock_config() -> serde_json::Value {
+        json!({
+            "algorithms": {
+                "MD5_Init": {"name": "MD5", "replacement": "SHA-256"},
+                "DES_*": {"name": "DES", "replacement": "AES"},
+                "DES_ede3_*": {"name": "3DES", "replacement": "AES"},
+                "SHA1_Init": {"name": "SHA-1", "replacement": "SHA-256", "only_in_signature_context": true}
+            },
+            "signature_symbols": ["RSA_sign"]
+        })
+    }
+
+    #[test]
+    fn algorithm_lookup() {
+        let config: Config = serde_json::from_value(mock_config()).unwrap();
+        assert_eq!(get_algorithm(&config, "MD5_Init").unwrap().name, "MD5");
+        assert!(get_algorithm(&config, "MD5_Update").is_none());
+        assert_eq!(
+            get_algorithm(&config, "DES_ecb_encrypt").unwrap().name,
+            "DES"
+        );
+        assert_eq!(
+            get_algorithm(&config, "DES_ede3_cbc_encrypt").unwrap().name,
+            "3DES"
+        );
+    }
+
+    #[test]
+    fn weak_algorithm_calls() {
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols = [
+            "MD5_Init",
+            "DES_set_key",
+            "DES_ecb_encrypt",
+            "SHA1_Init",
+            "RSA_sign",
+        ]
+        .iter()
+        .map(|name| ExternSymbol::mock_with(name, &["RDI"]))
+        .collect();
+        project.program.term.subs = vec![
+            Sub::mock_with(
+                "encrypt",
+                vec![
+                    Blk::mock_with(
+                        "encrypt_0",
+                        vec![],
+                        Jmp::mock_call("DES_set_key", "encrypt_1"),
+                    ),
+                    Blk::mock_with(
+                        "encrypt_1",
+                        vec![],
+                        Jmp::mock_call("DES_ecb_encrypt", "encrypt_2"),
+                    ),
+                    Blk::mock_with("encrypt_2", vec![], Jmp::mock_call("MD5_Init", "encrypt_3")),
+                    Blk::mock_with("encrypt_3", vec![], Jmp::mock_return()),
+                ],
+            ),
+            Sub::mock_with(
+                "checksum",
+                vec![
+                    Blk::mock_with(
+                        "checksum_0",
+                        vec![],
+                        Jmp::mock_call("SHA1_Init", "checksum_1"),
+                    ),
+                    Blk::mock_with("checksum_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            Sub::mock_with(
+                "digest",
+                vec![
+                    Blk::mock_with("digest_0", vec![], Jmp::mock_call("SHA1_Init", "digest_1")),
+                    Blk::mock_with("digest_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            Sub::mock_with(
+                "sign",
+                vec![
+                    Blk::mock_with("sign_0", vec![], Jmp::mock_call("digest", "sign_1")),
+                    Blk::mock_with("sign_1", vec![], Jmp::mock_call("RSA_sign", "sign_2")),
+                    Blk::mock_with("sign_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+
+        let (_, warnings) = check_cwe(&analysis_results, &mock_config());
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["encrypt_0_jmp".to_string(), "encrypt_1_jmp".to_string()]
+        );
+        assert_eq!(
+            warnings[0].symbols,
+            vec![
+                "encrypt".to_string(),
+                "DES_ecb_encrypt".to_string(),
+                "DES_set_key".to_string()
+            ]
+        );
+        assert_eq!(
+            warnings[0].other[1],
+            vec!["replacement".to_string(), "AES".to_string()]
+        );
+        assert_eq!(warnings[1].tids, vec!["encrypt_2_jmp".to_string()]);
+        // SHA-1 is only flagged in the function called by the signing function.
+        assert_eq!(warnings[2].tids, vec!["digest_0_jmp".to_string()]);
+    }
+}
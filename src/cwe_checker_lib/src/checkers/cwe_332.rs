@@ -22,7 +22,8 @@
 
 use crate::prelude::*;
 use crate::utils::log::{CweWarning, LogMessage};
-use crate::utils::symbol_utils::find_symbol;
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::find_symbols;
 use crate::CweModule;
 
 /// The module name and version
@@ -30,6 +31,8 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE332",
     version: "0.1",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The configuration struct contains pairs of symbol names,
@@ -37,7 +40,8 @@ pub static CWE_MODULE: CweModule = CweModule {
 /// and the second name is the name of a corresponding random number generator access function.
 /// It is assumed that a program has to call the seeding function first
 /// to ensure that the RNG does not generate predictable random numbers.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pairs: Vec<(String, String)>,
 }
@@ -60,12 +64,12 @@ pub fn check_cwe(
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let mut cwe_warnings = Vec::new();
 
     for (secure_initializer_func, rand_func) in config.pairs.iter() {
-        if find_symbol(&project.program, rand_func).is_some()
-            && find_symbol(&project.program, secure_initializer_func).is_none()
+        if !find_symbols(&project.program, rand_func).is_empty()
+            && find_symbols(&project.program, secure_initializer_func).is_empty()
         {
             cwe_warnings.push(generate_cwe_warning(secure_initializer_func, rand_func));
         }
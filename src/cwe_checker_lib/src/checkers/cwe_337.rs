@@ -0,0 +1,671 @@
+//! This module implements a check for CWE-337: Predictable Seed in Pseudo-Random Number Generator (PRNG).
+//!
+//! A PRNG seeded with a predictable value (e.g. the current time or the process ID)
+//! generates a predictable sequence of numbers, since an attacker can guess the seed by trying all likely values.
+//! A typical example is `srand(time(NULL))`.
+//!
+//! See <https://cwe.mitre.org/data/definitions/337.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the `seeding_functions` configured in config.json
+//! we trace the seed parameter backwards to the definitions that it is computed from.
+//! If the seed depends on the return value of one of the configured `low_entropy_sources` (e.g. `time` or `getpid`),
+//! a warning is generated.
+//!
+//! The trace follows
+//! - registers through the def-use chains of the function,
+//! - values stored to and loaded from the same memory cell of the function,
+//!   using the [Pointer Inference analysis](crate::analysis::pointer_inference) to identify the memory cells,
+//! - return values of internal functions into the return instructions of the called function,
+//! - register parameters of functions to the values of the parameter registers at the call sites of the function.
+//!
+//! All inputs of an arithmetic expression are followed,
+//! so that e.g. `time(NULL) ^ getpid()` is also recognized as predictable.
+//!
+//! ## False Positives
+//!
+//! - Mixing a low-entropy value with a value of high entropy (e.g. read from `/dev/urandom`) is still flagged.
+//!
+//! ## False Negatives
+//!
+//! - Low-entropy values returned through pointer parameters (e.g. by `gettimeofday`) are not tracked.
+//! - Return values and parameters of internal functions are only followed
+//!   if they are defined in the basic block containing the return instruction or the call.
+//! - Parameters passed on the stack to internal functions are not followed.
+//! - The length of the traced dependency chains is bounded.
+
+use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::def_use::Definition;
+use crate::analysis::pointer_inference::PointerInference;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE337",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The maximal number of definitions that are followed backwards from a seed parameter.
+const MAX_TRACE_DEPTH: usize = 16;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Names of PRNG seeding functions mapped to the index of their seed parameter.
+    seeding_functions: BTreeMap<String, usize>,
+    /// Names of functions returning predictable values, e.g. the current time or the process ID.
+    low_entropy_sources: Vec<String>,
+}
+
+/// The context for tracing seed values backwards to their sources.
+struct Tracer<'a> {
+    /// The analysis results containing the def-use chains of the functions.
+    analysis_results: &'a AnalysisResults<'a>,
+    /// The results of the Pointer Inference analysis, if available.
+    pointer_inference: Option<&'a PointerInference<'a>>,
+    /// The low-entropy source functions, indexed by their TIDs.
+    low_entropy_sources: HashMap<&'a Tid, &'a str>,
+    /// The internal functions, indexed by their TIDs.
+    subs: HashMap<&'a Tid, &'a Term<Sub>>,
+    /// The call sites of the internal functions.
+    callsites: CallSites<'a>,
+}
+
+/// Maps the TIDs of functions to their call sites, given by the calling function and the block containing the call.
+type CallSites<'a> = HashMap<&'a Tid, Vec<(&'a Term<Sub>, &'a Term<Blk>)>>;
+
+/// The low-entropy sources found for a seed, given by the TIDs of the source calls mapped to the source function names.
+type Sources<'a> = BTreeMap<&'a Tid, &'a str>;
+
+impl<'a> Tracer<'a> {
+    /// Create a new tracer for the program of the analysis results.
+    fn new(analysis_results: &'a AnalysisResults<'a>, config: &Config) -> Tracer<'a> {
+        let program = &analysis_results.project.program.term;
+        let low_entropy_sources = program
+            .extern_symbols
+            .iter()
+            .filter(|symbol| config.low_entropy_sources.contains(&symbol.name))
+            .map(|symbol| (&symbol.tid, symbol.name.as_str()))
+            .collect();
+        let subs: HashMap<&Tid, &Term<Sub>> =
+            program.subs.iter().map(|sub| (&sub.tid, sub)).collect();
+        let mut callsites: CallSites = HashMap::new();
+        for sub in program.subs.iter() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call { target, .. } = &jmp.term {
+                        if subs.contains_key(target) {
+                            callsites.entry(target).or_default().push((sub, block));
+                        }
+                    }
+                }
+            }
+        }
+        Tracer {
+            analysis_results,
+            pointer_inference: analysis_results.pointer_inference(),
+            low_entropy_sources,
+            subs,
+            callsites,
+        }
+    }
+
+    /// Find the low-entropy sources of the seed parameter of the given call to a seeding function.
+    fn trace_seed(
+        &self,
+        sub: &'a Term<Sub>,
+        call: &'a Term<Jmp>,
+        seed_parameter: &'a Arg,
+    ) -> Sources<'a> {
+        let mut sources = BTreeMap::new();
+        let mut visited = HashSet::new();
+        match seed_parameter {
+            Arg::Register(var) => self.trace_use(
+                sub,
+                &call.tid,
+                var,
+                MAX_TRACE_DEPTH,
+                &mut visited,
+                &mut sources,
+            ),
+            Arg::Stack { offset, .. } => {
+                let address =
+                    Expression::Var(self.analysis_results.project.stack_pointer_register.clone())
+                        .plus_const(*offset);
+                if let Some(cell) = self.pointer_inference.and_then(|pointer_inference| {
+                    pointer_inference.get_unique_memory_cell_at_tid(&call.tid, &address)
+                }) {
+                    self.trace_memory_cell(sub, &cell, MAX_TRACE_DEPTH, &mut visited, &mut sources);
+                }
+            }
+        }
+        sources
+    }
+
+    /// Trace the value of the register `var` at its use by the term with the given TID.
+    fn trace_use(
+        &self,
+        sub: &'a Term<Sub>,
+        use_tid: &'a Tid,
+        var: &'a Variable,
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        if depth == 0 || !visited.insert((use_tid, var)) {
+            return;
+        }
+        let def_use_chains = match self.analysis_results.def_use_chains(&sub.tid) {
+            Some(def_use_chains) => def_use_chains,
+            None => return,
+        };
+        let reaching_defs: Vec<&Tid> = def_use_chains.defs_reaching(use_tid, var).collect();
+        if reaching_defs.is_empty() {
+            // The register is a parameter of the function.
+            self.trace_parameter(sub, var, depth - 1, visited, sources);
+        }
+        for def_tid in reaching_defs {
+            if let Some(definition) = def_use_chains.get_definition(def_tid) {
+                self.trace_definition(sub, definition, var, depth - 1, visited, sources);
+            }
+        }
+    }
+
+    /// Trace the value of the register `var` at the end of the `Def` terms of the given block,
+    /// i.e. right before the jumps of the block.
+    /// Only definitions inside the block and parameters of the function are followed.
+    fn trace_at_block_end(
+        &self,
+        sub: &'a Term<Sub>,
+        block: &'a Term<Blk>,
+        var: &'a Variable,
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        if depth == 0 || !visited.insert((&block.tid, var)) {
+            return;
+        }
+        let local_definition = block.term.defs.iter().rev().find(|def| {
+            matches!(&def.term, Def::Assign { var: defined_var, .. } | Def::Load { var: defined_var, .. } if defined_var == var)
+        });
+        match local_definition {
+            Some(def) => {
+                self.trace_definition(sub, Definition::Def(def), var, depth - 1, visited, sources)
+            }
+            None if sub.term.blocks.first().map(|first| &first.tid) == Some(&block.tid) => {
+                self.trace_parameter(sub, var, depth - 1, visited, sources)
+            }
+            None => (),
+        }
+    }
+
+    /// Trace the values defined by the given definition.
+    /// The register `var` is the register defined by calls.
+    fn trace_definition(
+        &self,
+        sub: &'a Term<Sub>,
+        definition: Definition<'a>,
+        var: &'a Variable,
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        match definition {
+            Definition::Def(def) => match &def.term {
+                Def::Assign { value, .. } => {
+                    for input in value.input_vars() {
+                        self.trace_use(sub, &def.tid, input, depth, visited, sources);
+                    }
+                }
+                Def::Load { address, .. } => {
+                    if let Some(cell) = self.pointer_inference.and_then(|pointer_inference| {
+                        pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+                    }) {
+                        self.trace_memory_cell(sub, &cell, depth, visited, sources);
+                    }
+                }
+                Def::Store { .. } => (),
+            },
+            Definition::Call(call) => {
+                let target = match &call.term {
+                    Jmp::Call { target, .. } => target,
+                    _ => return,
+                };
+                if let Some(source_name) = self.low_entropy_sources.get(target) {
+                    sources.insert(&call.tid, source_name);
+                } else if let Some(callee) = self.subs.get(target) {
+                    // Trace the return value of the called function.
+                    for block in callee.term.blocks.iter() {
+                        if block
+                            .term
+                            .jmps
+                            .iter()
+                            .any(|jmp| matches!(jmp.term, Jmp::Return(_)))
+                        {
+                            self.trace_at_block_end(callee, block, var, depth, visited, sources);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Trace the value of the parameter register `var` of the function to the call sites of the function.
+    fn trace_parameter(
+        &self,
+        sub: &'a Term<Sub>,
+        var: &'a Variable,
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        for (caller, block) in self.callsites.get(&sub.tid).into_iter().flatten() {
+            self.trace_at_block_end(caller, block, var, depth, visited, sources);
+        }
+    }
+
+    /// Trace the values stored to the given memory cell by `Store` terms of the function.
+    fn trace_memory_cell(
+        &self,
+        sub: &'a Term<Sub>,
+        cell: &(AbstractIdentifier, i64),
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        for def in sub
+            .term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.defs.iter())
+        {
+            if let Def::Store { address, value } = &def.term {
+                if self
+                    .pointer_inference
+                    .and_then(|pointer_inference| {
+                        pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+                    })
+                    .as_ref()
+                    == Some(cell)
+                {
+                    for input in value.input_vars() {
+                        self.trace_use(sub, &def.tid, input, depth, visited, sources);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Generate the warning for a call to a seeding function with a seed derived from the given sources.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    seeding_function: &str,
+    sources: &Sources,
+) -> CweWarning {
+    let mut source_names: Vec<&str> = sources.values().cloned().collect();
+    source_names.sort_unstable();
+    source_names.dedup();
+    let description = format!(
+        "(Predictable Seed in PRNG) The seed passed to {} in {} at {} is derived from {}.",
+        seeding_function,
+        sub.term.display_name(),
+        call.tid.address,
+        source_names.join(", ")
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![call.tid.address.clone()])
+        .tids(
+            std::iter::once(call.tid.to_string())
+                .chain(sources.keys().map(|tid| tid.to_string()))
+                .collect(),
+        )
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            seeding_function.to_string(),
+        ])
+        .other(vec![std::iter::once("sources".to_string())
+            .chain(source_names.iter().map(|name| name.to_string()))
+            .collect()])
+}
+
+/// Flag calls to PRNG seeding functions whose seed is derived from low-entropy sources.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let seeding_functions: HashMap<&Tid, (&ExternSymbol, &Arg)> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter_map(|symbol| {
+            let index = config.seeding_functions.get(&symbol.name)?;
+            Some((&symbol.tid, (symbol, symbol.parameters.get(*index)?)))
+        })
+        .collect();
+    if seeding_functions.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let tracer = Tracer::new(analysis_results, &config);
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for jmp in sub
+            .term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+        {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some((symbol, seed_parameter)) = seeding_functions.get(target) {
+                    let sources = tracer.trace_seed(sub, jmp, seed_parameter);
+                    if !sources.is_empty() {
+                        warnings.push(generate_cwe_warning(sub, jmp, &symbol.name, &sources));
+                    }
+                }
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    fn copy(tid: &str, var: &str, value: Expression) -> Term<Def> {
+        Def::assign(tid, Variable::mock(var, 8), value)
+    }
+
+    fn var(name: &str) -> Expression {
+        Expression::Var(Variable::mock(name, 8))
+    }
+
+    #[test]
+    fn predictable_seeds() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = ["srand", "time", "getpid", "rand"]
+            .iter()
+            .map(|name| ExternSymbol::mock_with(name, &["RDI"]))
+            .collect();
+        project.program.term.subs = vec![
+            // srand(time(NULL))
+            Sub::mock_with(
+                "direct",
+                vec![
+                    Blk::mock_with("direct_0", vec![], Jmp::mock_call("time", "direct_1")),
+                    Blk::mock_with(
+                        "direct_1",
+                        vec![copy("direct_copy", "RDI", var("RAX"))],
+                        Jmp::mock_call("srand", "direct_2"),
+                    ),
+                    Blk::mock_with("direct_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // srand(rand())
+            Sub::mock_with(
+                "unpredictable",
+                vec![
+                    Blk::mock_with(
+                        "unpredictable_0",
+                        vec![],
+                        Jmp::mock_call("rand", "unpredictable_1"),
+                    ),
+                    Blk::mock_with(
+                        "unpredictable_1",
+                        vec![copy("unpredictable_copy", "RDI", var("RAX"))],
+                        Jmp::mock_call("srand", "unpredictable_2"),
+                    ),
+                    Blk::mock_with("unpredictable_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // Returns getpid() ^ 42
+            Sub::mock_with(
+                "get_seed",
+                vec![
+                    Blk::mock_with("get_seed_0", vec![], Jmp::mock_call("getpid", "get_seed_1")),
+                    Blk::mock_with(
+                        "get_seed_1",
+                        vec![copy(
+                            "get_seed_xor",
+                            "RAX",
+                            Expression::BinOp {
+                                op: BinOpType::IntXOr,
+                                lhs: Box::new(var("RAX")),
+                                rhs: Box::new(Expression::const_from_i64(42)),
+                            },
+                        )],
+                        Jmp::mock_return(),
+                    ),
+                ],
+            ),
+            // Seeds the PRNG with its parameter.
+            Sub::mock_with(
+                "seed_with",
+                vec![
+                    Blk::mock_with(
+                        "seed_with_0",
+                        vec![],
+                        Jmp::mock_call("srand", "seed_with_1"),
+                    ),
+                    Blk::mock_with("seed_with_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // seed_with(get_seed())
+            Sub::mock_with(
+                "indirect",
+                vec![
+                    Blk::mock_with(
+                        "indirect_0",
+                        vec![],
+                        Jmp::mock_call("get_seed", "indirect_1"),
+                    ),
+                    Blk::mock_with(
+                        "indirect_1",
+                        vec![copy("indirect_copy", "RDI", var("RAX"))],
+                        Jmp::mock_call("seed_with", "indirect_2"),
+                    ),
+                    Blk::mock_with("indirect_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let config = json!({
+            "seeding_functions": {"srand": 0},
+            "low_entropy_sources": ["time", "getpid"]
+        });
+
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["direct_1_jmp".to_string(), "direct_0_jmp".to_string()]
+        );
+        assert_eq!(warnings[1].tids[0], "seed_with_0_jmp");
+        assert_eq!(
+            warnings[1].other,
+            vec![vec!["sources".to_string(), "getpid".to_string()]]
+        );
+    }
+
+    /// Run the check with the Pointer Inference analysis on the project
+    /// and return the TIDs of the seeding calls and the sources of the generated warnings.
+    fn check_project(project: &Project, config: serde_json::Value) -> Vec<(String, Vec<String>)> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| (warning.tids[0].clone(), warning.other[0][1..].to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn seeds_through_memory_and_mixed_sources() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = ["srand", "time", "getpid"]
+            .iter()
+            .map(|name| ExternSymbol::mock_with(name, &["RDI"]))
+            .collect();
+        // The frame pointer is callee-saved, so that it is not changed by calls.
+        let frame_pointer = |tid: &str| copy(tid, "RBP", var("RSP").plus_const(-0x10));
+        let stack_variable = |offset: i64| var("RBP").plus_const(offset);
+        project.program.term.subs = vec![
+            // The seed is stored to a stack variable and loaded from it again.
+            Sub::mock_with(
+                "stored",
+                vec![
+                    Blk::mock_with(
+                        "stored_0",
+                        vec![frame_pointer("stored_frame")],
+                        Jmp::mock_call("time", "stored_1"),
+                    ),
+                    Blk::mock_with(
+                        "stored_1",
+                        vec![
+                            Def::store("stored_store", stack_variable(0), var("RAX")),
+                            Def::load("stored_load", Variable::mock("RDI", 8), stack_variable(0)),
+                        ],
+                        Jmp::mock_call("srand", "stored_2"),
+                    ),
+                    Blk::mock_with("stored_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // The seed is loaded from another stack variable than the one the time was stored to.
+            Sub::mock_with(
+                "other_variable",
+                vec![
+                    Blk::mock_with(
+                        "other_variable_0",
+                        vec![frame_pointer("other_variable_frame")],
+                        Jmp::mock_call("time", "other_variable_1"),
+                    ),
+                    Blk::mock_with(
+                        "other_variable_1",
+                        vec![
+                            Def::store("other_variable_store", stack_variable(0), var("RAX")),
+                            Def::load(
+                                "other_variable_load",
+                                Variable::mock("RDI", 8),
+                                stack_variable(8),
+                            ),
+                        ],
+                        Jmp::mock_call("srand", "other_variable_2"),
+                    ),
+                    Blk::mock_with("other_variable_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // srand(time(NULL) ^ getpid()) with the time kept in a stack variable during the call to getpid
+            Sub::mock_with(
+                "mixed",
+                vec![
+                    Blk::mock_with(
+                        "mixed_0",
+                        vec![frame_pointer("mixed_frame")],
+                        Jmp::mock_call("time", "mixed_1"),
+                    ),
+                    Blk::mock_with(
+                        "mixed_1",
+                        vec![Def::store("mixed_store", stack_variable(0), var("RAX"))],
+                        Jmp::mock_call("getpid", "mixed_2"),
+                    ),
+                    Blk::mock_with(
+                        "mixed_2",
+                        vec![
+                            Def::load("mixed_load", Variable::mock("RBX", 8), stack_variable(0)),
+                            copy(
+                                "mixed_xor",
+                                "RDI",
+                                Expression::BinOp {
+                                    op: BinOpType::IntXOr,
+                                    lhs: Box::new(var("RBX")),
+                                    rhs: Box::new(var("RAX")),
+                                },
+                            ),
+                        ],
+                        Jmp::mock_call("srand", "mixed_3"),
+                    ),
+                    Blk::mock_with("mixed_3", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+        let config = json!({
+            "seeding_functions": {"srand": 0},
+            "low_entropy_sources": ["time", "getpid"]
+        });
+        assert_eq!(
+            check_project(&project, config),
+            vec![
+                ("stored_1_jmp".to_string(), vec!["time".to_string()]),
+                (
+                    "mixed_2_jmp".to_string(),
+                    vec!["getpid".to_string(), "time".to_string()]
+                ),
+            ]
+        );
+
+        // Only the configured sources are reported.
+        let config = json!({
+            "seeding_functions": {"srand": 0},
+            "low_entropy_sources": ["getpid"]
+        });
+        assert_eq!(
+            check_project(&project, config),
+            vec![("mixed_2_jmp".to_string(), vec!["getpid".to_string()])]
+        );
+        // Seed parameter indices without a corresponding parameter of the seeding function are ignored.
+        let config = json!({
+            "seeding_functions": {"srand": 4},
+            "low_entropy_sources": ["time", "getpid"]
+        });
+        assert!(check_project(&project, config).is_empty());
+    }
+
+    #[test]
+    fn config_parsing() {
+        assert!(parse_config::<Config>(&json!({
+            "seeding_functions": {"srand": 0},
+            "low_entropy_sources": []
+        }))
+        .is_ok());
+        assert!(parse_config::<Config>(&json!({"seeding_functions": {"srand": 0}})).is_err());
+        assert!(parse_config::<Config>(&json!({
+            "seeding_functions": {"srand": "seed"},
+            "low_entropy_sources": []
+        }))
+        .is_err());
+    }
+}
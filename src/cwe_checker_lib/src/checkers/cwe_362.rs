@@ -0,0 +1,586 @@
+//! This module implements a check for CWE-362: Concurrent Execution using Shared Resource with Improper Synchronization ('Race Condition').
+//!
+//! A read-modify-write access to a global variable (e.g. `counter++`) consists of a load, a computation and a store.
+//! If two threads modify the same variable concurrently without holding a common lock,
+//! one thread may overwrite the result of the other thread, so that updates get lost.
+//! If the variable is e.g. a reference counter or the size of a buffer, this may lead to memory corruption.
+//!
+//! See <https://cwe.mitre.org/data/definitions/362.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The start routines of threads are the functions whose addresses are passed to the configured `thread_creation_symbols`
+//! (e.g. `pthread_create`), see [`Project::get_task_entry_points`].
+//! All functions reachable from start routines in the call graph run in thread context.
+//! The main context consists of the parts of the configured `main_functions` that are reachable after a thread was created
+//! (by a call to a thread creation function or a function creating threads)
+//! and of all functions called from these parts.
+//!
+//! In all functions we collect read-modify-write accesses to global variables,
+//! i.e. stores to a global address whose value is computed in the same basic block from a value loaded from the same address.
+//! The addresses are computed with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//!
+//! The locks held during an access are computed by the lockset analysis of the [CWE-667 check](super::cwe_667)
+//! for the locks acquired in the function of the access.
+//! Global locks held at all calls to a function are held during all accesses in the function as well,
+//! unless the function itself releases them.
+//!
+//! A warning is generated for each global variable that is modified both in thread context and in main context
+//! by accesses that hold no common lock.
+//! Since locks in other memory than global memory cannot be compared between functions,
+//! two accesses both holding such a lock are assumed to be synchronized.
+//!
+//! ## False Positives
+//!
+//! - Atomic operations (e.g. instructions with the `lock` prefix on x86) are not distinguished from other accesses.
+//! - Accesses in main context after all threads were joined are still reported.
+//! - Locks acquired by callers are ignored if the function releases them on some path.
+//! - Other means of synchronization than the configured lock functions are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Start routines whose address cannot be computed in the basic block of the thread creation call are not found.
+//! - Races between several threads executing thread context functions are not reported.
+//! - Plain reads and writes of global variables are not checked, only read-modify-write accesses.
+//! - Accesses to global variables through pointers that the Pointer Inference analysis cannot resolve to a constant address are missed.
+
+use super::cwe_667::{Lock, LockAnalysis};
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::graph_utils::{get_intraprocedural_jump_targets, CallGraph};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::CweModule;
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE362",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions creating threads, mapped to the index of the parameter containing the start routine of the thread.
+    thread_creation_symbols: BTreeMap<String, usize>,
+    /// Functions executed by the main thread, e.g. `main`.
+    #[serde(default)]
+    main_functions: Vec<String>,
+    /// Extern functions blocking until they acquire the lock passed as the parameter with the given index.
+    lock_symbols: BTreeMap<String, usize>,
+    /// Extern functions releasing the lock passed as the parameter with the given index.
+    unlock_symbols: BTreeMap<String, usize>,
+}
+
+/// A read-modify-write access to a global variable.
+#[derive(Clone, Copy)]
+struct GlobalAccess<'a> {
+    /// The function containing the access.
+    sub: &'a Term<Sub>,
+    /// The block containing the access.
+    block: &'a Term<Blk>,
+    /// The store instruction writing the modified value.
+    store: &'a Term<Def>,
+    /// The address of the global variable.
+    address: u64,
+}
+
+/// The locks held during an access.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct Lockset {
+    /// The addresses of the held global locks.
+    global_locks: BTreeSet<u64>,
+    /// Whether a lock in other memory than global memory is held.
+    holds_other_lock: bool,
+}
+
+impl Lockset {
+    /// Returns `true` if the accesses holding the two locksets are not synchronized by a common lock.
+    fn is_disjoint(&self, other: &Lockset) -> bool {
+        self.global_locks.is_disjoint(&other.global_locks)
+            && !(self.holds_other_lock && other.holds_other_lock)
+    }
+}
+
+/// Get the address of the writable global memory accessed at the given address by the `Def` term,
+/// if the address is a constant known to the Pointer Inference analysis.
+fn get_global_address(
+    pointer_inference: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+    def: &Term<Def>,
+    address: &Expression,
+) -> Option<u64> {
+    let address = match address {
+        Expression::Const(address) => address.clone(),
+        _ => match pointer_inference.get_state_at_tid(&def.tid)?.eval(address) {
+            Data::Value(address) => address.try_to_bitvec().ok()?,
+            _ => return None,
+        },
+    };
+    if runtime_memory_image.is_address_writeable(&address).ok()? {
+        address.try_to_u64().ok()
+    } else {
+        None
+    }
+}
+
+/// Collect the read-modify-write accesses to global variables in the function.
+/// The function `get_address` computes the global address accessed by a load or store instruction.
+fn get_read_modify_writes<'a>(
+    sub: &'a Term<Sub>,
+    get_address: impl Fn(&Term<Def>, &Expression) -> Option<u64>,
+) -> Vec<GlobalAccess<'a>> {
+    let mut accesses = Vec::new();
+    for block in sub.term.blocks.iter() {
+        // Registers containing values computed from values loaded from global addresses.
+        let mut loaded_values: HashMap<&Variable, u64> = HashMap::new();
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Load { var, address } => match get_address(def, address) {
+                    Some(address) => {
+                        loaded_values.insert(var, address);
+                    }
+                    None => {
+                        loaded_values.remove(var);
+                    }
+                },
+                Def::Assign { var, value } => {
+                    match value
+                        .input_vars()
+                        .into_iter()
+                        .find_map(|input| loaded_values.get(input).copied())
+                    {
+                        Some(address) => {
+                            loaded_values.insert(var, address);
+                        }
+                        None => {
+                            loaded_values.remove(var);
+                        }
+                    }
+                }
+                Def::Store { address, value } => {
+                    if let Some(address) = get_address(def, address) {
+                        let is_modified_value = value
+                            .input_vars()
+                            .into_iter()
+                            .any(|input| loaded_values.get(input) == Some(&address));
+                        if is_modified_value {
+                            accesses.push(GlobalAccess {
+                                sub,
+                                block,
+                                store: def,
+                                address,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    accesses
+}
+
+/// Get the functions reachable from the given functions in the call graph, including the given functions.
+fn get_reachable_subs<'a>(
+    call_graph: &'a CallGraph,
+    roots: impl IntoIterator<Item = &'a Tid>,
+) -> HashSet<&'a Tid> {
+    let mut reachable = HashSet::new();
+    let mut worklist: Vec<&Tid> = roots.into_iter().collect();
+    while let Some(sub_tid) = worklist.pop() {
+        if reachable.insert(sub_tid) {
+            worklist.extend(call_graph.get_callees(sub_tid));
+        }
+    }
+    reachable
+}
+
+/// The parts of the program running in the main thread while other threads may run.
+struct MainContext<'a> {
+    /// The blocks of the main functions that are reachable after a thread was created.
+    blocks: HashSet<&'a Tid>,
+    /// The functions called from these blocks and their callees.
+    subs: HashSet<&'a Tid>,
+}
+
+impl<'a> MainContext<'a> {
+    /// Compute the main context of the program.
+    fn new(
+        project: &'a Project,
+        call_graph: &'a CallGraph,
+        thread_creation_symbols: &HashMap<Tid, &ExternSymbol>,
+        main_functions: &[String],
+    ) -> MainContext<'a> {
+        // The functions creating threads, either directly or in one of their callees.
+        let mut creating_subs: HashSet<&Tid> = HashSet::new();
+        let mut worklist: Vec<&Tid> = project
+            .program
+            .term
+            .subs
+            .iter()
+            .filter(|sub| {
+                sub.term.blocks.iter().any(|block| {
+                    block.term.jmps.iter().any(|jmp| {
+                        matches!(&jmp.term, Jmp::Call { target, .. } if thread_creation_symbols.contains_key(target))
+                    })
+                })
+            })
+            .map(|sub| &sub.tid)
+            .collect();
+        while let Some(sub_tid) = worklist.pop() {
+            if creating_subs.insert(sub_tid) {
+                worklist.extend(call_graph.get_callers(sub_tid));
+            }
+        }
+
+        let mut blocks = HashSet::new();
+        let mut called_subs = Vec::new();
+        for sub in project
+            .program
+            .term
+            .subs
+            .iter()
+            .filter(|sub| main_functions.contains(&sub.term.name))
+        {
+            let mut worklist: Vec<&Tid> = Vec::new();
+            for jmp in sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+            {
+                if let Jmp::Call {
+                    target,
+                    return_: Some(return_),
+                } = &jmp.term
+                {
+                    if thread_creation_symbols.contains_key(target)
+                        || creating_subs.contains(target)
+                    {
+                        worklist.push(return_);
+                    }
+                }
+            }
+            while let Some(block_tid) = worklist.pop() {
+                if !blocks.insert(block_tid) {
+                    continue;
+                }
+                if let Some(block) = sub.term.blocks.iter().find(|blk| blk.tid == *block_tid) {
+                    for jmp in block.term.jmps.iter() {
+                        if let Jmp::Call { target, .. } = &jmp.term {
+                            called_subs.push(target);
+                        }
+                    }
+                    worklist.extend(get_intraprocedural_jump_targets(block, sub));
+                }
+            }
+        }
+        MainContext {
+            blocks,
+            subs: get_reachable_subs(call_graph, called_subs),
+        }
+    }
+
+    /// Returns `true` if the access happens in main context.
+    fn contains(&self, access: &GlobalAccess) -> bool {
+        self.blocks.contains(&access.block.tid) || self.subs.contains(&access.sub.tid)
+    }
+}
+
+/// Compute the global locks held at all calls to each function.
+/// The given root functions (e.g. the start routines of threads) are not called with locks held.
+fn get_entry_locks<'a>(
+    project: &'a Project,
+    lock_analysis: &LockAnalysis,
+    roots: &HashSet<&Tid>,
+) -> HashMap<&'a Tid, BTreeSet<u64>> {
+    let sub_tids: HashSet<&Tid> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| &sub.tid)
+        .collect();
+    let mut call_sites = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if sub_tids.contains(target) && !roots.contains(target) {
+                        call_sites.push((&sub.tid, &block.tid, target));
+                    }
+                }
+            }
+        }
+    }
+    let mut entry_locks: HashMap<&Tid, BTreeSet<u64>> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| roots.contains(&sub.tid))
+        .map(|sub| (&sub.tid, BTreeSet::new()))
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut new_entry_locks: HashMap<&Tid, BTreeSet<u64>> = HashMap::new();
+        for (caller, block, callee) in call_sites.iter() {
+            let caller_locks = match entry_locks.get(caller) {
+                Some(caller_locks) => caller_locks,
+                None => continue,
+            };
+            let held_locks: BTreeSet<u64> = caller_locks
+                .iter()
+                .copied()
+                .filter(|address| !lock_analysis.may_release_global_lock(caller, *address))
+                .chain(
+                    lock_analysis
+                        .get_held_locks(block)
+                        .filter_map(|lock| match lock {
+                            Lock::Global(address) => Some(*address),
+                            Lock::Object(..) => None,
+                        }),
+                )
+                .collect();
+            match new_entry_locks.entry(*callee) {
+                Entry::Vacant(entry) => {
+                    entry.insert(held_locks);
+                }
+                Entry::Occupied(mut entry) => {
+                    let common_locks = entry.get().intersection(&held_locks).copied().collect();
+                    entry.insert(common_locks);
+                }
+            }
+        }
+        for (callee, locks) in new_entry_locks {
+            if entry_locks.get(callee) != Some(&locks) {
+                entry_locks.insert(callee, locks);
+                changed = true;
+            }
+        }
+    }
+    entry_locks
+}
+
+/// Get the locks held during the access.
+fn get_lockset(
+    lock_analysis: &LockAnalysis,
+    entry_locks: &HashMap<&Tid, BTreeSet<u64>>,
+    access: &GlobalAccess,
+) -> Lockset {
+    let mut lockset = Lockset::default();
+    if let Some(locks) = entry_locks.get(&access.sub.tid) {
+        lockset.global_locks.extend(
+            locks.iter().filter(|address| {
+                !lock_analysis.may_release_global_lock(&access.sub.tid, **address)
+            }),
+        );
+    }
+    for lock in lock_analysis.get_held_locks(&access.block.tid) {
+        match lock {
+            Lock::Global(address) => {
+                lockset.global_locks.insert(*address);
+            }
+            Lock::Object(..) => lockset.holds_other_lock = true,
+        }
+    }
+    lockset
+}
+
+/// Generate the CWE warning for a global variable modified in thread context and in main context without a common lock.
+fn generate_cwe_warning(thread_access: &GlobalAccess, main_access: &GlobalAccess) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Race Condition) The global variable at {:#x} is modified by {} at {} in thread context and by {} at {} in main context without a common lock",
+            thread_access.address,
+            thread_access.sub.term.display_name(),
+            thread_access.store.tid.address,
+            main_access.sub.term.display_name(),
+            main_access.store.tid.address
+        ),
+    )
+    .tids(vec![
+        format!("{}", thread_access.store.tid),
+        format!("{}", main_access.store.tid),
+    ])
+    .addresses(vec![
+        thread_access.store.tid.address.clone(),
+        main_access.store.tid.address.clone(),
+    ])
+    .symbols(vec![
+        thread_access.sub.term.display_name().to_string(),
+        main_access.sub.term.display_name().to_string(),
+    ])
+    .other(vec![vec![
+        "global_address".to_string(),
+        format!("{:#x}", thread_access.address),
+    ]])
+}
+
+/// Run the CWE check.
+/// We collect the read-modify-write accesses to global variables in thread context and in main context
+/// and report global variables modified in both contexts without a common lock.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let call_graph = analysis_results.call_graph();
+
+    let start_routines = project.get_task_entry_points(&config.thread_creation_symbols);
+    if start_routines.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let thread_subs = get_reachable_subs(call_graph, start_routines.iter());
+    let thread_creation_symbols: Vec<String> =
+        config.thread_creation_symbols.keys().cloned().collect();
+    let main_context = MainContext::new(
+        project,
+        call_graph,
+        &get_symbol_map(project, &thread_creation_symbols),
+        &config.main_functions,
+    );
+
+    let lock_config = super::cwe_667::Config {
+        lock_symbols: config.lock_symbols.clone(),
+        unlock_symbols: config.unlock_symbols.clone(),
+        ..Default::default()
+    };
+    let lock_analysis = LockAnalysis::new(analysis_results, pointer_inference, &lock_config);
+    let roots: HashSet<&Tid> = start_routines
+        .iter()
+        .chain(
+            project
+                .program
+                .term
+                .subs
+                .iter()
+                .filter(|sub| config.main_functions.contains(&sub.term.name))
+                .map(|sub| &sub.tid),
+        )
+        .collect();
+    let entry_locks = get_entry_locks(project, &lock_analysis, &roots);
+
+    let mut thread_accesses: BTreeMap<u64, Vec<(GlobalAccess, Lockset)>> = BTreeMap::new();
+    let mut main_accesses: BTreeMap<u64, Vec<(GlobalAccess, Lockset)>> = BTreeMap::new();
+    for sub in project.program.term.subs.iter() {
+        let in_thread_context = thread_subs.contains(&sub.tid);
+        let accesses = get_read_modify_writes(sub, |def, address| {
+            get_global_address(
+                pointer_inference,
+                analysis_results.runtime_memory_image,
+                def,
+                address,
+            )
+        });
+        for access in accesses {
+            let lockset = get_lockset(&lock_analysis, &entry_locks, &access);
+            if main_context.contains(&access) {
+                main_accesses
+                    .entry(access.address)
+                    .or_default()
+                    .push((access, lockset.clone()));
+            }
+            if in_thread_context {
+                thread_accesses
+                    .entry(access.address)
+                    .or_default()
+                    .push((access, lockset));
+            }
+        }
+    }
+
+    let mut cwe_warnings = Vec::new();
+    for (address, accesses) in thread_accesses.iter() {
+        let other_accesses = match main_accesses.get(address) {
+            Some(other_accesses) => other_accesses,
+            None => continue,
+        };
+        if let Some((thread_access, main_access)) = accesses.iter().find_map(|(access, lockset)| {
+            other_accesses
+                .iter()
+                .find(|(_, other_lockset)| lockset.is_disjoint(other_lockset))
+                .map(|(other_access, _)| (access, other_access))
+        }) {
+            cwe_warnings.push(generate_cwe_warning(thread_access, main_access));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_modify_writes() {
+        let rax = Variable::mock("RAX", 8);
+        let load = |tid: &str, address: u64| Term {
+            tid: Tid::new(tid),
+            term: Def::Load {
+                var: rax.clone(),
+                address: Expression::Const(Bitvector::from_u64(address)),
+            },
+        };
+        let store = |tid: &str, address: u64| Term {
+            tid: Tid::new(tid),
+            term: Def::Store {
+                address: Expression::Const(Bitvector::from_u64(address)),
+                value: Expression::Var(rax.clone()),
+            },
+        };
+        let mut block = Blk::mock();
+        block.term.defs = vec![
+            load("load_counter", 0x1000),
+            Def::assign(
+                "increment",
+                rax.clone(),
+                Expression::Var(rax.clone()).plus_const(1),
+            ),
+            store("store_counter", 0x1000),
+            // Copying a global variable to another one is no read-modify-write access.
+            load("load_other", 0x2000),
+            store("store_copy", 0x1000),
+            // Overwriting the loaded value ends the read-modify-write access.
+            load("load_overwritten", 0x1000),
+            Def::assign("overwrite", rax.clone(), Expression::const_from_i64(0)),
+            store("store_constant", 0x1000),
+        ];
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![block];
+        let accesses = get_read_modify_writes(&sub, |_def, address| match address {
+            Expression::Const(address) => address.try_to_u64().ok(),
+            _ => None,
+        });
+        assert_eq!(accesses.len(), 1);
+        assert_eq!(accesses[0].store.tid, Tid::new("store_counter"));
+        assert_eq!(accesses[0].address, 0x1000);
+    }
+
+    #[test]
+    fn lockset_disjointness() {
+        let lockset = |global_locks: Vec<u64>, holds_other_lock: bool| Lockset {
+            global_locks: global_locks.into_iter().collect(),
+            holds_other_lock,
+        };
+        assert!(lockset(vec![], false).is_disjoint(&lockset(vec![], false)));
+        assert!(lockset(vec![1], false).is_disjoint(&lockset(vec![2], true)));
+        assert!(!lockset(vec![1, 2], false).is_disjoint(&lockset(vec![2], false)));
+        // Locks outside of global memory cannot be compared and are assumed to be the same lock.
+        assert!(!lockset(vec![], true).is_disjoint(&lockset(vec![], true)));
+    }
+}
@@ -27,6 +27,7 @@ use crate::intermediate_representation::Jmp;
 use crate::prelude::*;
 use crate::utils::graph_utils::is_sink_call_reachable_from_source_call;
 use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
 use crate::CweModule;
 use petgraph::visit::EdgeRef;
 use std::collections::HashMap;
@@ -36,12 +37,15 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE367",
     version: "0.1",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The configuration struct contains pairs of the form `(source_symbol, sink_symbol)`.
 /// The `source_symbol` corresponds to a check-call and the `sink_symbol` corresponds to a use-call.
 /// An execution path from a source call to a sink call corresponds to a possible Time-of-check Time-of-use Race Condition.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 struct Config {
     pairs: Vec<(String, String)>,
 }
@@ -71,37 +75,40 @@ pub fn check_cwe(
     analysis_results: &AnalysisResults,
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let project = analysis_results.project;
     let graph = analysis_results.control_flow_graph;
     let mut cwe_warnings = Vec::new();
 
-    let symbol_map: HashMap<&str, Tid> = project
-        .program
-        .term
-        .extern_symbols
-        .iter()
-        .map(|symbol| (symbol.name.as_str(), symbol.tid.clone()))
-        .collect();
+    // Several extern symbols may share the same name.
+    let mut symbol_map: HashMap<&str, Vec<Tid>> = HashMap::new();
+    for symbol in project.program.term.extern_symbols.iter() {
+        symbol_map
+            .entry(symbol.name.as_str())
+            .or_default()
+            .push(symbol.tid.clone());
+    }
 
     for (source, sink) in config.pairs {
-        if let (Some(source_tid), Some(sink_tid)) = (
+        if let (Some(source_tids), Some(sink_tids)) = (
             symbol_map.get(source.as_str()),
             symbol_map.get(sink.as_str()),
         ) {
             for edge in graph.edge_references() {
                 if let Edge::ExternCallStub(jmp) = edge.weight() {
                     if let Jmp::Call { target, .. } = &jmp.term {
-                        if target == source_tid {
-                            if let Some(sink_callsite) = is_sink_call_reachable_from_source_call(
-                                graph,
-                                edge.target(),
-                                target,
-                                sink_tid,
-                            ) {
+                        if source_tids.contains(target) {
+                            if let Some(sink_callsite) = sink_tids.iter().find_map(|sink_tid| {
+                                is_sink_call_reachable_from_source_call(
+                                    graph,
+                                    edge.target(),
+                                    target,
+                                    sink_tid,
+                                )
+                            }) {
                                 let source_callsite = graph[edge.target()].get_block().tid.clone();
                                 let sub_name = match graph[edge.target()] {
-                                    Node::BlkStart(_blk, sub) => sub.term.name.as_str(),
+                                    Node::BlkStart(_blk, sub) => sub.term.display_name(),
                                     _ => panic!("Malformed control flow graph."),
                                 };
                                 cwe_warnings.push(generate_cwe_warning(
@@ -0,0 +1,469 @@
+//! This module implements a check for CWE-369: Divide By Zero.
+//!
+//! Integer divisions by zero crash the program on most architectures
+//! and are undefined behavior in C, so an attacker controlling the divisor can cause a denial of service.
+//!
+//! See <https://cwe.mitre.org/data/definitions/369.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each integer division or remainder operation (`INT_DIV`, `INT_SDIV`, `INT_REM` and `INT_SREM`)
+//! we use the [Pointer Inference analysis](crate::analysis::pointer_inference) to compute the value of the divisor.
+//! - If the value interval of the divisor is known and contains zero, a warning is generated.
+//! - If nothing is known about the value of the divisor, we follow the divisor backwards along the
+//!   [def-use chains](crate::analysis::def_use) of the function to its sources.
+//!   A warning is generated if the divisor is derived from a parameter register of the function
+//!   or from the return value of one of the `input_symbols` configured in config.json (e.g. `atoi`),
+//!   unless the function compares a value derived from the same source with zero.
+//!
+//! ## False Positives
+//!
+//! - The value intervals of divisors in loops may be overapproximated by the interval domain.
+//! - Checks that ensure that a divisor is not zero without comparing it with zero
+//!   (e.g. by comparing it with a positive constant) are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - A comparison with zero anywhere in the function is accepted as a check,
+//!   regardless of whether it is actually executed before the division.
+//! - Divisors loaded from memory are only checked if the Pointer Inference analysis knows their value.
+//! - Divisors derived from parameters passed on the stack are not tracked.
+
+use crate::abstract_domain::TryToInterval;
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE369",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+/// The `input_symbols` are extern functions whose return values are considered to be external input.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    input_symbols: Vec<String>,
+}
+
+/// A source from which the value of a divisor is derived.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+enum DivisorSource<'a> {
+    /// The parameter register of the function with the given name.
+    Parameter(&'a str),
+    /// The return value of the call to an input function with the given TID.
+    Input(&'a Tid),
+}
+
+impl<'a> std::fmt::Display for DivisorSource<'a> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DivisorSource::Parameter(register) => write!(formatter, "parameter {}", register),
+            DivisorSource::Input(tid) => write!(formatter, "input at {}", tid.address),
+        }
+    }
+}
+
+/// The reason why a divisor may be zero.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Finding<'a> {
+    /// The known value interval of the divisor contains zero.
+    ZeroInInterval,
+    /// The divisor is derived from the given sources and not compared with zero.
+    UncheckedSource(BTreeSet<DivisorSource<'a>>),
+}
+
+/// Collect the divisors of all integer division and remainder operations contained in the expression.
+fn collect_divisors<'a>(expression: &'a Expression, divisors: &mut Vec<&'a Expression>) {
+    use Expression::*;
+    match expression {
+        BinOp { op, lhs, rhs } => {
+            if matches!(
+                op,
+                BinOpType::IntDiv | BinOpType::IntSDiv | BinOpType::IntRem | BinOpType::IntSRem
+            ) {
+                divisors.push(rhs);
+            }
+            collect_divisors(lhs, divisors);
+            collect_divisors(rhs, divisors);
+        }
+        UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => {
+            collect_divisors(arg, divisors)
+        }
+        Var(_) | Const(_) | Unknown { .. } => (),
+    }
+}
+
+/// If the expression compares a value with zero, return the compared value.
+fn get_value_compared_with_zero(expression: &Expression) -> Option<&Expression> {
+    use BinOpType::*;
+    if let Expression::BinOp { op, lhs, rhs } = expression {
+        if matches!(
+            op,
+            IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
+        ) {
+            match (lhs.as_ref(), rhs.as_ref()) {
+                (Expression::Const(constant), value) | (value, Expression::Const(constant))
+                    if constant.is_zero() =>
+                {
+                    return Some(value)
+                }
+                _ => (),
+            }
+        }
+    }
+    None
+}
+
+/// Helper struct for following values backwards to their sources in a function.
+struct SourceFinder<'a> {
+    /// The def-use chains of the function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The input functions, indexed by the TIDs of their extern symbols.
+    input_symbols: HashMap<&'a Tid, &'a ExternSymbol>,
+    /// The parameter registers of the standard calling convention.
+    parameter_registers: &'a [String],
+}
+
+impl<'a> SourceFinder<'a> {
+    /// Follow the input registers of the expression used by the term with the given TID backwards
+    /// through assignments and return the parameters and input function calls that the expression is derived from.
+    fn get_sources(&self, use_tid: &Tid, expression: &Expression) -> BTreeSet<DivisorSource<'a>> {
+        let mut sources = BTreeSet::new();
+        let mut worklist: Vec<(Tid, Variable)> = expression
+            .input_vars()
+            .into_iter()
+            .map(|var| (use_tid.clone(), var.clone()))
+            .collect();
+        let mut visited_defs: HashSet<&Tid> = HashSet::new();
+        while let Some((use_tid, var)) = worklist.pop() {
+            let mut reaching_defs = self.def_use_chains.defs_reaching(&use_tid, &var).peekable();
+            if reaching_defs.peek().is_none() {
+                if let Some(register) = self
+                    .parameter_registers
+                    .iter()
+                    .find(|register| **register == var.name)
+                {
+                    sources.insert(DivisorSource::Parameter(register));
+                }
+                continue;
+            }
+            for def_tid in reaching_defs {
+                if !visited_defs.insert(def_tid) {
+                    continue;
+                }
+                match self.def_use_chains.get_definition(def_tid) {
+                    Some(Definition::Call(Term {
+                        tid,
+                        term: Jmp::Call { target, .. },
+                    })) if self.input_symbols.contains_key(target) => {
+                        sources.insert(DivisorSource::Input(tid));
+                    }
+                    Some(Definition::Def(Term {
+                        tid,
+                        term: Def::Assign { value, .. },
+                    })) => {
+                        for input in value.input_vars() {
+                            worklist.push((tid.clone(), input.clone()));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        sources
+    }
+
+    /// Return the sources of all values that the function compares with zero.
+    fn get_zero_checked_sources(&self, sub: &Term<Sub>) -> BTreeSet<DivisorSource<'a>> {
+        let mut checked_sources = BTreeSet::new();
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                if let Def::Assign { value, .. } = &def.term {
+                    if let Some(compared_value) = get_value_compared_with_zero(value) {
+                        checked_sources.extend(self.get_sources(&def.tid, compared_value));
+                    }
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::CBranch { condition, .. } = &jmp.term {
+                    if let Some(compared_value) = get_value_compared_with_zero(condition) {
+                        checked_sources.extend(self.get_sources(&jmp.tid, compared_value));
+                    }
+                }
+            }
+        }
+        checked_sources
+    }
+}
+
+/// Check whether the value of the divisor may be zero according to the Pointer Inference analysis.
+/// Returns `None` if nothing is known about the value of the divisor.
+fn may_be_zero(divisor: &Data) -> Option<bool> {
+    match divisor {
+        Data::Value(value) => {
+            let interval = value.try_to_interval().ok()?;
+            Some(interval.contains(&Bitvector::zero(interval.bytesize().into())))
+        }
+        Data::Pointer(_) => Some(false),
+        Data::Top(_) => None,
+    }
+}
+
+/// Check all integer divisions in the function for divisors that may be zero.
+fn check_sub<'a>(
+    pointer_inference: &PointerInference,
+    source_finder: &SourceFinder<'a>,
+    sub: &'a Term<Sub>,
+) -> Vec<(&'a Term<Def>, Finding<'a>)> {
+    let mut findings = Vec::new();
+    let mut checked_sources = None;
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            let mut divisors = Vec::new();
+            match &def.term {
+                Def::Assign { value, .. } => collect_divisors(value, &mut divisors),
+                Def::Load { address, .. } => collect_divisors(address, &mut divisors),
+                Def::Store { address, value } => {
+                    collect_divisors(address, &mut divisors);
+                    collect_divisors(value, &mut divisors);
+                }
+            }
+            let state = match pointer_inference.get_state_at_tid(&def.tid) {
+                Some(state) if !divisors.is_empty() => state,
+                _ => continue,
+            };
+            for divisor in divisors {
+                let finding = match may_be_zero(&state.eval(divisor)) {
+                    Some(true) => Finding::ZeroInInterval,
+                    Some(false) => continue,
+                    None => {
+                        let checked_sources = checked_sources
+                            .get_or_insert_with(|| source_finder.get_zero_checked_sources(sub));
+                        let unchecked_sources: BTreeSet<_> = source_finder
+                            .get_sources(&def.tid, divisor)
+                            .into_iter()
+                            .filter(|source| !checked_sources.contains(source))
+                            .collect();
+                        if unchecked_sources.is_empty() {
+                            continue;
+                        }
+                        Finding::UncheckedSource(unchecked_sources)
+                    }
+                };
+                findings.push((def, finding));
+                break;
+            }
+        }
+    }
+    findings
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(sub: &Term<Sub>, def: &Term<Def>, finding: &Finding) -> CweWarning {
+    let (reason, other) = match finding {
+        Finding::ZeroInInterval => (
+            "the value range of the divisor contains zero".to_string(),
+            vec![vec!["divisor_source".to_string(), "value".to_string()]],
+        ),
+        Finding::UncheckedSource(sources) => {
+            let sources: Vec<String> = sources.iter().map(|source| source.to_string()).collect();
+            let mut other = vec!["divisor_source".to_string()];
+            other.extend(sources.iter().cloned());
+            (
+                format!(
+                    "the divisor is derived from {} without a check for zero",
+                    sources.join(", ")
+                ),
+                vec![other],
+            )
+        }
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Divide By Zero) Division at {} in {} may divide by zero: {}",
+            def.tid.address,
+            sub.term.display_name(),
+            reason
+        ),
+    )
+    .tids(vec![format!("{}", def.tid)])
+    .addresses(vec![def.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string()])
+    .other(other)
+}
+
+/// Run the CWE check.
+/// For each integer division we check whether the divisor may be zero.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let input_symbols: HashMap<&Tid, &ExternSymbol> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter(|symbol| config.input_symbols.contains(&symbol.name))
+        .map(|symbol| (&symbol.tid, symbol))
+        .collect();
+    let parameter_registers = project
+        .get_standard_calling_convention()
+        .map(|cconv| &cconv.parameter_register[..])
+        .unwrap_or(&[]);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let def_use_chains = match analysis_results.def_use_chains(&sub.tid) {
+            Some(def_use_chains) => def_use_chains,
+            None => continue,
+        };
+        let source_finder = SourceFinder {
+            def_use_chains,
+            input_symbols: input_symbols.clone(),
+            parameter_registers,
+        };
+        for (def, finding) in check_sub(pointer_inference, &source_finder, sub) {
+            cwe_warnings.push(generate_cwe_warning(sub, def, &finding));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::ValueDomain;
+
+    #[test]
+    fn divisor_collection() {
+        let rax = Expression::Var(Variable::mock("RAX", 8));
+        let rdi = Expression::Var(Variable::mock("RDI", 8));
+        let expression = rax
+            .clone()
+            .un_op(UnOpType::Int2Comp)
+            .plus(Expression::BinOp {
+                op: BinOpType::IntSRem,
+                lhs: Box::new(rax.clone()),
+                rhs: Box::new(rdi.clone()),
+            });
+        let mut divisors = Vec::new();
+        collect_divisors(&expression, &mut divisors);
+        assert_eq!(divisors, vec![&rdi]);
+
+        let comparison = Expression::BinOp {
+            op: BinOpType::IntEqual,
+            lhs: Box::new(Expression::const_from_i64(0)),
+            rhs: Box::new(rdi.clone()),
+        };
+        assert_eq!(get_value_compared_with_zero(&comparison), Some(&rdi));
+        assert_eq!(get_value_compared_with_zero(&rax), None);
+    }
+
+    #[test]
+    fn divisor_values() {
+        let interval = |start: i64, end: i64| {
+            Data::Value(ValueDomain::new(
+                Bitvector::from_i64(start),
+                Bitvector::from_i64(end),
+            ))
+        };
+        assert_eq!(may_be_zero(&interval(-1, 4)), Some(true));
+        assert_eq!(may_be_zero(&interval(1, 4)), Some(false));
+        assert_eq!(may_be_zero(&Data::Top(ByteSize::new(8))), None);
+    }
+
+    #[test]
+    fn divisor_sources() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "atoi".to_string();
+        let mut block = Blk::mock();
+        block.term.defs = vec![
+            Def::assign(
+                "param_copy",
+                rax.clone(),
+                Expression::Var(rdi.clone()).plus_const(1),
+            ),
+            Def::assign(
+                "param_use",
+                Variable::mock("RCX", 8),
+                Expression::Var(rax.clone()),
+            ),
+            Def::assign(
+                "zero_check",
+                Variable::mock("ZF", 1),
+                Expression::BinOp {
+                    op: BinOpType::IntEqual,
+                    lhs: Box::new(Expression::Var(rdi.clone())),
+                    rhs: Box::new(Expression::const_from_i64(0)),
+                },
+            ),
+        ];
+        block.term.jmps = vec![Term {
+            tid: Tid::new("input_call"),
+            term: Jmp::Call {
+                target: symbol.tid.clone(),
+                return_: Some(Tid::new("return_block")),
+            },
+        }];
+        let mut return_block = Blk::mock();
+        return_block.tid = Tid::new("return_block");
+        return_block.term.defs = vec![Def::assign(
+            "division",
+            Variable::mock("RCX", 8),
+            Expression::BinOp {
+                op: BinOpType::IntDiv,
+                lhs: Box::new(Expression::const_from_i64(8)),
+                rhs: Box::new(Expression::Var(rax.clone())),
+            },
+        )];
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![block, return_block];
+        project.program.term.subs = vec![sub];
+        project.program.term.extern_symbols = vec![symbol.clone()];
+
+        let sub = &project.program.term.subs[0];
+        let def_use_chains = DefUseChains::new(&project, sub);
+        let source_finder = SourceFinder {
+            def_use_chains: &def_use_chains,
+            input_symbols: vec![(&symbol.tid, &symbol)].into_iter().collect(),
+            parameter_registers: &project.calling_conventions[0].parameter_register,
+        };
+        let parameter_source: BTreeSet<_> =
+            vec![DivisorSource::Parameter("RDI")].into_iter().collect();
+        // The copy of the parameter is followed back to the parameter register.
+        assert_eq!(
+            source_finder.get_sources(&Tid::new("param_use"), &Expression::Var(rax.clone())),
+            parameter_source
+        );
+        assert_eq!(
+            source_finder.get_zero_checked_sources(sub),
+            parameter_source
+        );
+        // After the call the divisor is the return value of the input function.
+        assert_eq!(
+            source_finder.get_sources(&Tid::new("division"), &Expression::Var(rax)),
+            vec![DivisorSource::Input(&Tid::new("input_call"))]
+                .into_iter()
+                .collect()
+        );
+    }
+}
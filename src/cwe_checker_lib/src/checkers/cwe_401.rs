@@ -0,0 +1,362 @@
+//! This module implements a check for CWE-401: Missing Release of Memory after Effective Lifetime.
+//!
+//! Heap memory that is not freed before the last pointer to it is lost cannot be reused by the program.
+//! If this happens repeatedly (e.g. on an error path of a request handler), attackers may exhaust the available memory.
+//!
+//! See <https://cwe.mitre.org/data/definitions/401.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The [Pointer Inference analysis](crate::analysis::pointer_inference) tracks heap objects
+//! from their allocation through all functions that know pointers to them.
+//! The heap objects are identified by the abstract identifiers of the Pointer Inference analysis,
+//! i.e. by the callsites of their allocations.
+//! At each return instruction of a function we check the states of all paths leading to the return separately,
+//! so that early-return paths sharing the epilogue of the function are distinguished from the regular exit path.
+//! A heap object that was not freed on the path leaks at the return of the function
+//! if it is not reachable through the return registers
+//! or through memory objects known to callers of the function (e.g. parameter objects).
+//!
+//! To reduce false positives, objects are not reported
+//! - if their pointers may be stored to global memory or to unknown locations anywhere in the program,
+//! - if their pointers are passed to one of the configured `escaping_symbols` (e.g. `pthread_create`)
+//!   or to an indirect call with unknown target,
+//! - if the path to the return contains a check that the pointer to the object is NULL,
+//!   i.e. the allocation failed on this path,
+//! - if the function is one of the configured `program_exit_functions`,
+//!   since the operating system releases the memory of the program after it terminates.
+//!
+//! The function exits and the paths leading to them are enumerated
+//! by the [resource lifetime engine](crate::analysis::resource_lifetime),
+//! which is shared with the check for leaked file descriptors and handles.
+//! One warning is generated per function and allocation site.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - Heap objects freed through pointers that the Pointer Inference analysis lost track of are reported as leaked.
+//! - Ownership transfers to extern functions other than the configured `escaping_symbols` are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Objects freed only on some paths reaching the same point (e.g. by conditional frees) are not reported,
+//!   since the Pointer Inference analysis merges their states.
+//! - Pointers lost before the end of the function (e.g. by overwriting the only pointer to an object)
+//!   are only detected at the next function exit at which the object is still tracked.
+//! - Objects allocated in loops may be reported only once although several instances leak.
+//! - Leaks on paths through blocks with several predecessors are only recognized
+//!   if the object leaks on the merged path as well.
+
+use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::graph::{Edge, Node};
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::analysis::resource_lifetime::{find_leaks, PathEdge, ResourceTracker, ValueCheck};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::CweModule;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE401",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions that may keep the pointers passed to them as parameters,
+    /// so that the memory objects they point to do not leak when the caller loses its pointers to them.
+    #[serde(default)]
+    escaping_symbols: Vec<String>,
+    /// Functions whose return terminates the program, e.g. `main`.
+    /// Memory objects still allocated at their exits are not reported.
+    #[serde(default)]
+    program_exit_functions: Vec<String>,
+}
+
+/// Return the IDs of the heap objects that are alive in the given state at a function exit,
+/// but neither reachable through the given return registers nor through memory objects known to callers of the function.
+fn get_leaked_object_ids(
+    state: &State,
+    return_registers: &[String],
+) -> BTreeSet<AbstractIdentifier> {
+    let mut roots: BTreeSet<AbstractIdentifier> = state
+        .caller_stack_ids
+        .union(&state.ids_known_to_caller)
+        .cloned()
+        .collect();
+    for register in return_registers {
+        if let Some(value) = state.get_register_by_name(register) {
+            roots.append(&mut value.referenced_ids());
+        }
+    }
+    let reachable_ids = state.add_recursively_referenced_ids_to_id_set(roots);
+    state
+        .memory
+        .get_alive_heap_object_ids()
+        .into_iter()
+        .filter(|id| !reachable_ids.contains(id))
+        .collect()
+}
+
+/// Collect the IDs of all memory objects whose pointers may escape the tracking of the Pointer Inference analysis,
+/// i.e. that may be stored to global memory or to unknown locations,
+/// or that are passed to one of the escaping symbols or to an indirect call.
+fn get_escaped_object_ids(
+    analysis_results: &AnalysisResults,
+    escaping_symbols: &HashMap<Tid, &ExternSymbol>,
+) -> BTreeSet<AbstractIdentifier> {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let parameter_registers = project
+        .get_standard_calling_convention()
+        .map(|cconv| cconv.parameter_register.clone())
+        .unwrap_or_default();
+    let mut escaped_ids = BTreeSet::new();
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                if let Def::Store { address, value } = &def.term {
+                    if let Some(state) = pointer_inference.get_state_at_tid(&def.tid) {
+                        if !matches!(state.eval(address), Data::Pointer(_)) {
+                            let value_ids = state.eval(value).referenced_ids();
+                            escaped_ids.append(
+                                &mut state.add_recursively_referenced_ids_to_id_set(value_ids),
+                            );
+                        }
+                    }
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                let state = match pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                    Some(state) => state,
+                    None => continue,
+                };
+                let parameter_values: Vec<Data> = match &jmp.term {
+                    Jmp::Call { target, .. } => match escaping_symbols.get(target) {
+                        Some(symbol) => symbol
+                            .parameters
+                            .iter()
+                            .filter_map(|parameter| {
+                                state
+                                    .eval_parameter_arg(
+                                        parameter,
+                                        &project.stack_pointer_register,
+                                        analysis_results.runtime_memory_image,
+                                    )
+                                    .ok()
+                            })
+                            .collect(),
+                        None => continue,
+                    },
+                    Jmp::CallInd { .. } => parameter_registers
+                        .iter()
+                        .filter_map(|register| state.get_register_by_name(register))
+                        .collect(),
+                    _ => continue,
+                };
+                for value in parameter_values {
+                    let value_ids = value.referenced_ids();
+                    escaped_ids
+                        .append(&mut state.add_recursively_referenced_ids_to_id_set(value_ids));
+                }
+            }
+        }
+    }
+    escaped_ids
+}
+
+/// The [`ResourceTracker`] for heap objects tracked by the Pointer Inference analysis.
+struct HeapObjectTracker<'a> {
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+    /// The IDs of the memory objects whose pointers may escape the tracking of the Pointer Inference analysis.
+    escaped_ids: BTreeSet<AbstractIdentifier>,
+    /// The names of the return registers of the standard calling convention.
+    return_registers: Vec<String>,
+}
+
+impl<'a> ResourceTracker<'a> for HeapObjectTracker<'a> {
+    type Resource = AbstractIdentifier;
+    type State = Cow<'a, State>;
+
+    /// Get the Pointer Inference states at the return instruction for each incoming jump edge.
+    fn get_states_at_return(
+        &self,
+        _sub: &'a Term<Sub>,
+        return_jmp: &'a Term<Jmp>,
+    ) -> Vec<(Option<PathEdge<'a>>, Cow<'a, State>)> {
+        let graph = self.pointer_inference.get_graph();
+        self.pointer_inference
+            .get_states_at_jmp_tid_per_path(&return_jmp.tid)
+            .into_iter()
+            .map(|(edge, state)| {
+                let path_edge =
+                    edge.and_then(|edge| match (graph[edge], graph.edge_endpoints(edge)) {
+                        (Edge::Jump(jump, untaken_conditional), Some((source, _))) => {
+                            match graph[source] {
+                                Node::BlkEnd(block, _sub) => Some(PathEdge {
+                                    source: block,
+                                    jump,
+                                    untaken_conditional,
+                                }),
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    });
+                (path_edge, state)
+            })
+            .collect()
+    }
+
+    fn get_leaked_resources(&self, state: &Cow<'a, State>) -> BTreeSet<AbstractIdentifier> {
+        get_leaked_object_ids(state, &self.return_registers)
+            .into_iter()
+            .filter(|id| !self.escaped_ids.contains(id))
+            .collect()
+    }
+
+    /// The allocation of a heap object failed if the pointer to it is checked to be NULL.
+    fn get_failed_resources(&self, check: &ValueCheck) -> BTreeSet<AbstractIdentifier> {
+        if !check.is_zero_check() {
+            return BTreeSet::new();
+        }
+        match self.pointer_inference.get_state_at_tid(check.tid) {
+            Some(state) => state.eval(&check.value).referenced_ids(),
+            None => BTreeSet::new(),
+        }
+    }
+
+    fn get_acquisition_site(&self, id: &AbstractIdentifier) -> Tid {
+        id.get_tid().clone()
+    }
+}
+
+/// Generate the CWE warning for a memory object allocated at the given callsite
+/// that leaks at the given return instruction.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    return_jmp: &Term<Jmp>,
+    allocation_site: &Tid,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Missing Release of Memory after Effective Lifetime) The memory allocated at {} may leak when {} returns at {}",
+            allocation_site.address, sub.term.display_name(), return_jmp.tid.address
+        ),
+    )
+    .tids(vec![format!("{}", return_jmp.tid), format!("{}", allocation_site)])
+    .addresses(vec![
+        return_jmp.tid.address.clone(),
+        allocation_site.address.clone(),
+    ])
+    .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Run the CWE check.
+/// For each return instruction we check whether heap objects become unreachable on a path to it without being freed.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let escaping_symbols = get_symbol_map(project, &config.escaping_symbols);
+    let escaped_ids = get_escaped_object_ids(analysis_results, &escaping_symbols);
+    let tracker = HeapObjectTracker {
+        pointer_inference,
+        escaped_ids,
+        return_registers: project
+            .get_standard_calling_convention()
+            .map(|cconv| cconv.return_register.clone())
+            .unwrap_or_default(),
+    };
+    let cwe_warnings = find_leaks(project, &tracker, &config.program_exit_functions)
+        .into_iter()
+        .map(|leak| generate_cwe_warning(leak.sub, leak.return_jmp, &leak.acquisition_site))
+        .collect();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+    use crate::analysis::pointer_inference::object::ObjectType;
+
+    fn new_heap_id(name: &str) -> AbstractIdentifier {
+        AbstractIdentifier::new(
+            Tid::new(name),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        )
+    }
+
+    #[test]
+    fn leaked_objects() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let caller_id = new_heap_id("caller_malloc");
+        let returned_id = new_heap_id("returned_malloc");
+        let nested_id = new_heap_id("nested_malloc");
+        let leaked_id = new_heap_id("leaked_malloc");
+        let freed_id = new_heap_id("freed_malloc");
+        for id in [&caller_id, &returned_id, &nested_id, &leaked_id, &freed_id] {
+            state.memory.add_abstract_object(
+                id.clone(),
+                Bitvector::from_i64(0).into(),
+                ObjectType::Heap,
+                ByteSize::new(8),
+            );
+        }
+        state.ids_known_to_caller.insert(caller_id.clone());
+        let pointer = |id: &AbstractIdentifier| -> Data {
+            PointerDomain::new(id.clone(), Bitvector::from_i64(0).into()).into()
+        };
+        state.set_register(&Variable::mock("RAX", 8), pointer(&returned_id));
+        // Pointers stored in returned objects and in the current stack frame.
+        state
+            .store_value(
+                &pointer(&returned_id),
+                &pointer(&nested_id),
+                &crate::utils::binary::RuntimeMemoryImage::mock(),
+            )
+            .unwrap();
+        state
+            .write_to_address(
+                &Expression::Var(project.stack_pointer_register.clone()).plus_const(-8),
+                &pointer(&leaked_id),
+                &crate::utils::binary::RuntimeMemoryImage::mock(),
+            )
+            .unwrap();
+        state
+            .mark_mem_object_as_freed(&PointerDomain::new(
+                freed_id.clone(),
+                Bitvector::from_i64(0).into(),
+            ))
+            .unwrap();
+
+        let leaked_ids = get_leaked_object_ids(&state, &["RAX".to_string()]);
+        assert_eq!(leaked_ids, vec![leaked_id.clone()].into_iter().collect());
+        // Without return registers the returned objects leak, too.
+        let leaked_ids = get_leaked_object_ids(&state, &[]);
+        assert_eq!(
+            leaked_ids,
+            vec![returned_id, nested_id, leaked_id]
+                .into_iter()
+                .collect()
+        );
+    }
+}
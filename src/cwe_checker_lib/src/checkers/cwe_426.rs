@@ -34,7 +34,8 @@
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::{CweWarning, LogMessage};
-use crate::utils::symbol_utils::{find_symbol, get_calls_to_symbols};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{find_symbols, get_calls_to_symbols};
 use crate::CweModule;
 use std::collections::HashMap;
 
@@ -43,11 +44,14 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE426",
     version: "0.1",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// Function symbols read from *config.json*.
 /// The symbols are functions that change or drop privileges.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     symbols: Vec<String>,
 }
@@ -59,12 +63,13 @@ fn generate_cwe_warning(sub: &Term<Sub>) -> CweWarning {
         CWE_MODULE.version,
         format!(
             "(Untrusted Search Path) sub {} at {} may be vulnerable to PATH manipulation.",
-            sub.term.name, sub.tid.address
+            sub.term.display_name(),
+            sub.tid.address
         ),
     )
     .tids(vec![format!("{}", sub.tid)])
     .addresses(vec![sub.tid.address.clone()])
-    .symbols(vec![sub.term.name.clone()])
+    .symbols(vec![sub.term.display_name().to_string()])
 }
 
 /// Run the CWE check.
@@ -75,18 +80,15 @@ pub fn check_cwe(
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let mut cwe_warnings = Vec::new();
     let mut privilege_changing_symbols = HashMap::new();
     for symbol in config.symbols.iter() {
-        if let Some((tid, name)) = find_symbol(&project.program, symbol) {
-            privilege_changing_symbols.insert(tid, name);
-        }
-    }
-    let mut system_symbol = HashMap::new();
-    if let Some((tid, name)) = find_symbol(&project.program, "system") {
-        system_symbol.insert(tid, name);
+        privilege_changing_symbols.extend(find_symbols(&project.program, symbol));
     }
+    let system_symbol: HashMap<&Tid, &str> = find_symbols(&project.program, "system")
+        .into_iter()
+        .collect();
     if !system_symbol.is_empty() && !privilege_changing_symbols.is_empty() {
         for sub in project.program.term.subs.iter() {
             if !get_calls_to_symbols(sub, &system_symbol).is_empty()
@@ -0,0 +1,417 @@
+//! This module implements a check for CWE-457: Use of Uninitialized Variable.
+//!
+//! Reading a stack variable before it is initialized yields the value
+//! that a previous function call left at the same stack location.
+//! Attackers may be able to control this value or use it to leak sensitive data.
+//!
+//! See <https://cwe.mitre.org/data/definitions/457.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each function we compute which bytes of its stack frame are initialized on all paths to each instruction
+//! using the [`InitializationDomain`] in an intraprocedural dataflow analysis.
+//! The [Pointer Inference analysis](crate::analysis::pointer_inference) is used to compute
+//! the stack offsets of the targets of `Load` and `Store` instructions.
+//! - Stores initialize the written bytes.
+//!   Stores to inexact offsets initialize all bytes between the smallest and the largest possible target offset.
+//!   Stores to unknown offsets in the stack frame initialize the whole stack frame.
+//! - Calls initialize the stack frame above the smallest offset that a parameter of the call points to,
+//!   since the callee may write to the corresponding stack variable.
+//! - Loads from exactly known offsets inside the stack frame of the function
+//!   where none of the read bytes is initialized on all paths generate a warning.
+//!
+//! One warning is generated per function and stack offset.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - Stack variables initialized by callees through pointers not passed as parameters
+//!   (e.g. pointers stored in global variables) are not recognized as initialized.
+//! - Parameters of calls to functions inside the binary are only checked for the parameter registers
+//!   of the standard calling convention.
+//!
+//! ## False Negatives
+//!
+//! - Partially initialized reads (e.g. reading a struct where only some fields are initialized) are not reported.
+//! - Reads from inexact or unknown stack offsets (e.g. array accesses in loops) are not checked.
+//! - Calls are assumed to initialize all stack variables above the pointers passed to them.
+
+use crate::abstract_domain::{AbstractDomain, InitializationDomain, TryToInterval};
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_calling_convention_parameter;
+use crate::CweModule;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE457",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// An access to the stack frame of the current function.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum StackAccess {
+    /// An access to an offset between the given smallest and largest possible offset.
+    Offsets(i64, i64),
+    /// An access to an unknown offset.
+    Unknown,
+}
+
+/// Return the access to the stack frame of the function if the given value points to it.
+fn get_stack_access(state: &State, value: &Data) -> Option<StackAccess> {
+    let pointer = match value {
+        Data::Pointer(pointer) => pointer,
+        _ => return None,
+    };
+    let offset = pointer.targets().get(&state.stack_id)?;
+    match offset.try_to_offset_interval() {
+        Ok((start, end)) => Some(StackAccess::Offsets(start, end)),
+        Err(_) => Some(StackAccess::Unknown),
+    }
+}
+
+/// The accesses to the stack frame of a function, as computed by the Pointer Inference analysis.
+#[derive(Default)]
+struct StackAccesses<'a> {
+    /// The stack accesses of `Load` and `Store` instructions, indexed by their term identifiers.
+    memory_accesses: HashMap<&'a Tid, StackAccess>,
+    /// The smallest stack offsets that the parameters of calls point to, indexed by the term identifiers of the calls.
+    call_parameters: HashMap<&'a Tid, StackAccess>,
+}
+
+impl<'a> StackAccesses<'a> {
+    /// Compute the stack accesses of the given function.
+    fn new(
+        project: &Project,
+        pointer_inference: &PointerInference,
+        sub: &'a Term<Sub>,
+    ) -> StackAccesses<'a> {
+        let runtime_memory_image = pointer_inference.get_context().runtime_memory_image;
+        let extern_symbols: HashMap<&Tid, &ExternSymbol> = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| (&symbol.tid, symbol))
+            .collect();
+        let mut accesses = StackAccesses::default();
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                let address = match &def.term {
+                    Def::Load { address, .. } | Def::Store { address, .. } => address,
+                    Def::Assign { .. } => continue,
+                };
+                if let Some(state) = pointer_inference.get_state_at_tid(&def.tid) {
+                    if let Some(access) = get_stack_access(&state, &state.eval(address)) {
+                        accesses.memory_accesses.insert(&def.tid, access);
+                    }
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                let parameters = match &jmp.term {
+                    Jmp::Call { target, .. } => match extern_symbols.get(target) {
+                        Some(symbol) => symbol.parameters.clone(),
+                        None => get_register_parameters(project),
+                    },
+                    Jmp::CallInd { .. } => get_register_parameters(project),
+                    _ => continue,
+                };
+                if let Some(state) = pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                    if let Some(access) = get_smallest_parameter_access(
+                        project,
+                        runtime_memory_image,
+                        state,
+                        &parameters,
+                    ) {
+                        accesses.call_parameters.insert(&jmp.tid, access);
+                    }
+                }
+            }
+        }
+        accesses
+    }
+}
+
+/// Get the parameter registers of the standard calling convention.
+fn get_register_parameters(project: &Project) -> Vec<Arg> {
+    let cconv = match project.get_standard_calling_convention() {
+        Some(cconv) => cconv,
+        None => return Vec::new(),
+    };
+    (0..cconv.parameter_register.len())
+        .map(|index| get_calling_convention_parameter(project, cconv, index))
+        .collect()
+}
+
+/// Get the access to the smallest stack offset that one of the given parameters points to.
+fn get_smallest_parameter_access(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    parameters: &[Arg],
+) -> Option<StackAccess> {
+    let mut smallest_access = None;
+    for parameter in parameters {
+        let value = match state.eval_parameter_arg(
+            parameter,
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        ) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        smallest_access = match (smallest_access, get_stack_access(state, &value)) {
+            (_, Some(StackAccess::Unknown)) | (Some(StackAccess::Unknown), _) => {
+                Some(StackAccess::Unknown)
+            }
+            (Some(StackAccess::Offsets(start, _)), Some(StackAccess::Offsets(other_start, _))) => {
+                let start = start.min(other_start);
+                Some(StackAccess::Offsets(start, start))
+            }
+            (None, access) | (access, None) => access,
+        };
+    }
+    smallest_access
+}
+
+/// A read of a stack variable that may be uninitialized.
+struct UninitializedRead<'a> {
+    /// The `Load` instruction.
+    def: &'a Term<Def>,
+    /// The stack offset of the read stack variable.
+    offset: i64,
+}
+
+/// Apply the effects of the `Def` term to the initialization state of the stack frame.
+/// If the `Def` is a read of an uninitialized stack variable, return its offset.
+fn update_on_def(
+    state: &mut InitializationDomain,
+    def: &Term<Def>,
+    accesses: &StackAccesses,
+) -> Option<i64> {
+    let access = accesses.memory_accesses.get(&def.tid)?;
+    match (&def.term, access) {
+        (Def::Store { value, .. }, StackAccess::Offsets(start, end)) => {
+            state.mark_initialized(*start, end + u64::from(value.bytesize()) as i64);
+        }
+        (Def::Store { .. }, StackAccess::Unknown) => state.mark_completely_initialized(),
+        (Def::Load { var, .. }, StackAccess::Offsets(start, end)) if start == end => {
+            let read_end = start + u64::from(var.size) as i64;
+            // Only reads from the stack frame of the function are checked,
+            // since the region above it contains the return address and the stack parameters.
+            if read_end <= 0 && state.is_uninitialized(*start, read_end) {
+                return Some(*start);
+            }
+        }
+        _ => (),
+    }
+    None
+}
+
+/// Apply the effects of the jump term to the initialization state of the stack frame.
+fn update_on_jmp(state: &mut InitializationDomain, jmp: &Term<Jmp>, accesses: &StackAccesses) {
+    match accesses.call_parameters.get(&jmp.tid) {
+        Some(StackAccess::Offsets(start, _)) => state.mark_initialized(*start, 0),
+        Some(StackAccess::Unknown) => state.mark_completely_initialized(),
+        None => (),
+    }
+}
+
+/// Compute the initialization state of the stack frame at the start of each block reachable from the function entry.
+fn compute_block_start_states<'a>(
+    sub: &'a Term<Sub>,
+    accesses: &StackAccesses,
+) -> HashMap<&'a Tid, InitializationDomain> {
+    let mut block_start_states: HashMap<&Tid, InitializationDomain> = HashMap::new();
+    let entry_block = match sub.term.blocks.first() {
+        Some(block) => block,
+        None => return block_start_states,
+    };
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    block_start_states.insert(&entry_block.tid, InitializationDomain::new_uninitialized());
+    let mut worklist = vec![entry_block];
+    let mut queued: HashSet<&Tid> = [&entry_block.tid].iter().cloned().collect();
+    while let Some(block) = worklist.pop() {
+        queued.remove(&block.tid);
+        let mut state = block_start_states[&block.tid].clone();
+        for def in block.term.defs.iter() {
+            update_on_def(&mut state, def, accesses);
+        }
+        for jmp in block.term.jmps.iter() {
+            update_on_jmp(&mut state, jmp, accesses);
+        }
+        for target in get_intraprocedural_jump_targets(block, sub) {
+            let target_block = match blocks.get(target) {
+                Some(target_block) => *target_block,
+                None => continue,
+            };
+            let new_state = match block_start_states.get(target) {
+                Some(target_state) => target_state.merge(&state),
+                None => state.clone(),
+            };
+            if block_start_states.get(target) != Some(&new_state) {
+                block_start_states.insert(target, new_state);
+                if queued.insert(target) {
+                    worklist.push(target_block);
+                }
+            }
+        }
+    }
+    block_start_states
+}
+
+/// Find the first read of each uninitialized stack variable in the function.
+fn check_sub<'a>(sub: &'a Term<Sub>, accesses: &StackAccesses) -> Vec<UninitializedRead<'a>> {
+    let block_start_states = compute_block_start_states(sub, accesses);
+    let mut reported_offsets = BTreeSet::new();
+    let mut reads = Vec::new();
+    for block in sub.term.blocks.iter() {
+        let mut state = match block_start_states.get(&block.tid) {
+            Some(state) => state.clone(),
+            None => continue,
+        };
+        for def in block.term.defs.iter() {
+            if let Some(offset) = update_on_def(&mut state, def, accesses) {
+                if reported_offsets.insert(offset) {
+                    reads.push(UninitializedRead { def, offset });
+                }
+            }
+        }
+    }
+    reads
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(sub: &Term<Sub>, read: &UninitializedRead) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Use of Uninitialized Variable) The stack variable at offset {} in {} may be read at {} before it is initialized",
+            read.offset, sub.term.display_name(), read.def.tid.address
+        ),
+    )
+    .tids(vec![format!("{}", read.def.tid)])
+    .addresses(vec![read.def.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string()])
+    .other(vec![vec![
+        "stack_offset".to_string(),
+        read.offset.to_string(),
+    ]])
+}
+
+/// Run the CWE check.
+/// For each function we check for reads of stack variables that are not initialized on all paths to the read.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let accesses = StackAccesses::new(project, pointer_inference, sub);
+        for read in check_sub(sub, &accesses) {
+            cwe_warnings.push(generate_cwe_warning(sub, &read));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a block with the given defs that jumps to the given targets.
+    fn mock_store(name: &str) -> Term<Def> {
+        Term {
+            tid: Tid::new(name),
+            term: Def::Store {
+                address: Expression::Var(Variable::mock("RBP", 8)),
+                value: Expression::const_from_i64(0),
+            },
+        }
+    }
+
+    fn mock_load(name: &str) -> Term<Def> {
+        Term {
+            tid: Tid::new(name),
+            term: Def::Load {
+                var: Variable::mock("RAX", 8),
+                address: Expression::Var(Variable::mock("RBP", 8)),
+            },
+        }
+    }
+
+    #[test]
+    fn uninitialized_reads() {
+        // The variable at offset -16 is only initialized on one of the two paths to the read.
+        // The variable at offset -8 is initialized on both paths.
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "entry",
+                vec![mock_store("init_1")],
+                vec![Jmp::Branch(Tid::new("then")), Jmp::Branch(Tid::new("else"))],
+            ),
+            Blk::mock_with(
+                "then",
+                vec![mock_store("init_2")],
+                Jmp::Branch(Tid::new("exit")),
+            ),
+            Blk::mock_with("else", vec![], Jmp::Branch(Tid::new("exit"))),
+            Blk::mock_with_jmps(
+                "exit",
+                vec![
+                    mock_load("read_1"),
+                    mock_load("read_2"),
+                    mock_load("read_3"),
+                    mock_load("read_param"),
+                ],
+                Vec::new(),
+            ),
+        ];
+        let mut accesses = StackAccesses::default();
+        let tids: Vec<Tid> = [
+            "init_1",
+            "init_2",
+            "read_1",
+            "read_2",
+            "read_3",
+            "read_param",
+        ]
+        .iter()
+        .map(|name| Tid::new(*name))
+        .collect();
+        let offsets = [-8, -16, -8, -16, -16, 8];
+        for (tid, offset) in tids.iter().zip(offsets.iter()) {
+            accesses
+                .memory_accesses
+                .insert(tid, StackAccess::Offsets(*offset, *offset));
+        }
+        let reads = check_sub(&sub, &accesses);
+        assert_eq!(reads.len(), 1);
+        assert_eq!(reads[0].def.tid, Tid::new("read_2"));
+        assert_eq!(reads[0].offset, -16);
+
+        // A call with a pointer to the variable initializes it.
+        accesses.call_parameters.insert(
+            &sub.term.blocks[2].term.jmps[0].tid,
+            StackAccess::Offsets(-16, -16),
+        );
+        assert!(check_sub(&sub, &accesses).is_empty());
+    }
+}
@@ -11,6 +11,20 @@
 //! We check whether a parameter in a call to a function listed in the symbols for CWE467 (configurable in in config.json)
 //! is an immediate value that equals the size of a pointer (e.g. 4 bytes on x86).
 //!
+//! Additionally, for the copy, zeroing and compare functions configured as `buffer_functions` in config.json
+//! we check whether the length parameter equals the size of a pointer.
+//! If the results of the [Pointer Inference analysis](crate::analysis::pointer_inference) are available,
+//! they are used to determine the value of the length parameter
+//! and the memory object that the buffer parameter points to.
+//! Each finding gets a confidence value:
+//! - *high* if the buffer is a heap object whose allocation size is known and larger than the size of a pointer,
+//! - *low* if the buffer is a stack variable of exactly the size of a pointer,
+//! - *medium* otherwise.
+//!
+//! If the buffer is a heap object whose allocation size equals the size of a pointer,
+//! the length is intentional and no warning is generated.
+//! Only findings with at least the configured `min_confidence` are reported.
+//!
 //! ## False Positives
 //!
 //! - The size value might be correct and not a bug.
@@ -18,29 +32,91 @@
 //! ## False Negatives
 //!
 //! - If the incorrect size value is generated before the basic block that contains
-//! the call, the check will not be able to find it.
+//!   the call and the Pointer Inference analysis was not able to track it,
+//!   the check will not be able to find it.
 
 use crate::abstract_domain::TryToBitvec;
-use crate::analysis::pointer_inference::State;
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::binary::RuntimeMemoryImage;
 use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
 use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
 use crate::CweModule;
+use std::collections::{BTreeMap, HashMap};
 
 /// The module name and version
 pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE467",
-    version: "0.2",
+    version: "0.3",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
-/// Function symbols read from *config.json*.
-/// All parameters of these functions will be checked on whether they are pointer sized.
+/// The configuration read from *config.json*.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    /// Function symbols whose parameters will all be checked on whether they are pointer sized.
     symbols: Vec<String>,
+    /// Functions taking a buffer and the length of the buffer as parameters,
+    /// mapped to the indices of these parameters.
+    #[serde(default)]
+    buffer_functions: BTreeMap<String, BufferParameters>,
+    /// The minimal confidence of findings that get reported.
+    #[serde(default = "Confidence::default_min_confidence")]
+    min_confidence: Confidence,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            symbols: Vec::new(),
+            buffer_functions: BTreeMap::new(),
+            min_confidence: Confidence::default_min_confidence(),
+        }
+    }
+}
+
+/// The parameter indices of the buffer and the length parameter of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct BufferParameters {
+    /// The index of the buffer parameter.
+    buffer: usize,
+    /// The index of the length parameter.
+    length: usize,
+}
+
+/// The confidence that a pointer-sized length is not intentional.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// The buffer may really have the size of a pointer.
+    Low,
+    /// Nothing is known about the size of the buffer.
+    Medium,
+    /// The buffer is known to be larger than a pointer.
+    High,
+}
+
+impl Confidence {
+    /// The default minimal confidence of reported findings.
+    fn default_min_confidence() -> Confidence {
+        Confidence::Medium
+    }
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Confidence::Low => write!(formatter, "low"),
+            Confidence::Medium => write!(formatter, "medium"),
+            Confidence::High => write!(formatter, "high"),
+        }
+    }
 }
 
 /// Compute the program state at the end of the given basic block
@@ -82,18 +158,149 @@ fn check_for_pointer_sized_arg(
         if let Ok(param) =
             state.eval_parameter_arg(parameter, &project.stack_pointer_register, global_memory)
         {
-            if let Ok(param_value) = param.try_to_bitvec() {
-                if Ok(u64::from(pointer_size)) == param_value.try_to_u64() {
-                    return true;
-                }
+            if is_pointer_sized_value(&param, pointer_size) {
+                return true;
             }
         }
     }
     false
 }
 
+/// Check whether the given value is the constant `sizeof(void*)`.
+fn is_pointer_sized_value(value: &Data, pointer_size: ByteSize) -> bool {
+    if let Ok(value) = value.try_to_bitvec() {
+        Ok(u64::from(pointer_size)) == value.try_to_u64()
+    } else {
+        false
+    }
+}
+
+/// Collect all calls to allocation functions known to the pointer inference analysis.
+/// Since the abstract identifiers of heap objects are derived from the allocation call,
+/// this allows to find the allocation call corresponding to a heap object.
+fn get_allocation_calls<'a>(
+    project: &'a Project,
+    pointer_inference: &PointerInference,
+) -> HashMap<Tid, &'a ExternSymbol> {
+    let allocation_symbol_map =
+        get_symbol_map(project, &pointer_inference.get_context().allocation_symbols);
+    let mut allocation_calls = HashMap::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, jmp, symbol) in get_callsites(sub, &allocation_symbol_map) {
+            allocation_calls.insert(jmp.tid.clone(), symbol);
+        }
+    }
+    allocation_calls
+}
+
+/// Compute the size of the heap object allocated by the given call
+/// if the allocation function has a unique parameter with a known constant value.
+fn get_allocation_size(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    global_memory: &RuntimeMemoryImage,
+    allocation_call: &Tid,
+    allocation_symbol: &ExternSymbol,
+) -> Option<u64> {
+    let state = pointer_inference.get_state_at_jmp_tid(allocation_call)?;
+    let size = state
+        .eval_parameter_arg(
+            allocation_symbol.get_unique_parameter().ok()?,
+            &project.stack_pointer_register,
+            global_memory,
+        )
+        .ok()?;
+    size.try_to_bitvec().ok()?.try_to_u64().ok()
+}
+
+/// Compute the confidence for a pointer-sized length parameter
+/// depending on the memory object that the buffer parameter points to.
+/// Returns `None` if the length is intentional,
+/// i.e. if the buffer is a heap object of exactly the size of a pointer.
+fn get_confidence_for_buffer(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    allocation_calls: &HashMap<Tid, &ExternSymbol>,
+    state: &State,
+    buffer: &Data,
+) -> Option<Confidence> {
+    let pointer_size = u64::from(project.stack_pointer_register.size);
+    let global_memory = pointer_inference.get_context().runtime_memory_image;
+    if let Data::Pointer(pointer) = buffer {
+        if let [(id, _offset)] = pointer.targets().iter().collect::<Vec<_>>()[..] {
+            if let Some(allocation_symbol) = allocation_calls.get(id.get_tid()) {
+                return match get_allocation_size(
+                    project,
+                    pointer_inference,
+                    global_memory,
+                    id.get_tid(),
+                    allocation_symbol,
+                ) {
+                    Some(size) if size == pointer_size => None,
+                    Some(size) if size > pointer_size => Some(Confidence::High),
+                    _ => Some(Confidence::Medium),
+                };
+            }
+        }
+    }
+    if state.get_stack_space_until_frame_base(buffer) == Some(pointer_size) {
+        Some(Confidence::Low)
+    } else {
+        Some(Confidence::Medium)
+    }
+}
+
+/// Check whether the length parameter of the call to the buffer function `symbol` has value `sizeof(void*)`.
+/// If yes, return the confidence of the finding.
+fn check_for_pointer_sized_length(
+    project: &Project,
+    analysis_results: &AnalysisResults,
+    allocation_calls: &HashMap<Tid, &ExternSymbol>,
+    block: &Term<Blk>,
+    jmp: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    parameters: &BufferParameters,
+) -> Option<Confidence> {
+    let global_memory = analysis_results.runtime_memory_image;
+    let stack_register = &project.stack_pointer_register;
+    let (buffer_param, length_param) = (
+        symbol.parameters.get(parameters.buffer)?,
+        symbol.parameters.get(parameters.length)?,
+    );
+    let block_end_state;
+    let state = match analysis_results
+        .pointer_inference()
+        .and_then(|pointer_inference| pointer_inference.get_state_at_jmp_tid(&jmp.tid))
+    {
+        Some(state) => state,
+        None => {
+            block_end_state = compute_block_end_state(project, global_memory, block);
+            &block_end_state
+        }
+    };
+    let length = state
+        .eval_parameter_arg(length_param, stack_register, global_memory)
+        .ok()?;
+    if !is_pointer_sized_value(&length, project.stack_pointer_register.size) {
+        return None;
+    }
+    match (
+        analysis_results.pointer_inference(),
+        state.eval_parameter_arg(buffer_param, stack_register, global_memory),
+    ) {
+        (Some(pointer_inference), Ok(buffer)) => {
+            get_confidence_for_buffer(project, pointer_inference, allocation_calls, state, &buffer)
+        }
+        _ => Some(Confidence::Medium),
+    }
+}
+
 /// Generate the CWE warning for a detected instance of the CWE.
-fn generate_cwe_warning(jmp: &Term<Jmp>, extern_symbol: &ExternSymbol) -> CweWarning {
+fn generate_cwe_warning(
+    jmp: &Term<Jmp>,
+    extern_symbol: &ExternSymbol,
+    confidence: Confidence,
+) -> CweWarning {
     CweWarning::new(
         CWE_MODULE.name,
         CWE_MODULE.version,
@@ -104,6 +311,8 @@ fn generate_cwe_warning(jmp: &Term<Jmp>, extern_symbol: &ExternSymbol) -> CweWar
     )
     .tids(vec![format!("{}", jmp.tid)])
     .addresses(vec![jmp.tid.address.clone()])
+    .symbols(vec![extern_symbol.name.clone()])
+    .other(vec![vec!["confidence".to_string(), confidence.to_string()]])
 }
 
 /// Execute the CWE check.
@@ -111,24 +320,48 @@ fn generate_cwe_warning(jmp: &Term<Jmp>, extern_symbol: &ExternSymbol) -> CweWar
 /// For each call to an extern symbol from the symbol list configured in the configuration file
 /// we check whether a parameter has value `sizeof(void*)`,
 /// which may indicate an instance of CWE 467.
+/// For calls to the configured buffer functions only the length parameter is checked.
 pub fn check_cwe(
     analysis_results: &AnalysisResults,
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let mut cwe_warnings = Vec::new();
+    let allocation_calls = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => get_allocation_calls(project, pointer_inference),
+        None => HashMap::new(),
+    };
 
-    let symbol_map = get_symbol_map(project, &config.symbols);
+    let mut symbols = config.symbols.clone();
+    symbols.extend(config.buffer_functions.keys().cloned());
+    let symbol_map = get_symbol_map(project, &symbols);
     for sub in project.program.term.subs.iter() {
         for (block, jmp, symbol) in get_callsites(sub, &symbol_map) {
-            if check_for_pointer_sized_arg(
+            let confidence = if let Some(parameters) = config.buffer_functions.get(&symbol.name) {
+                check_for_pointer_sized_length(
+                    project,
+                    analysis_results,
+                    &allocation_calls,
+                    block,
+                    jmp,
+                    symbol,
+                    parameters,
+                )
+            } else if check_for_pointer_sized_arg(
                 project,
                 analysis_results.runtime_memory_image,
                 block,
                 symbol,
             ) {
-                cwe_warnings.push(generate_cwe_warning(jmp, symbol))
+                Some(Confidence::Medium)
+            } else {
+                None
+            };
+            if let Some(confidence) = confidence {
+                if confidence >= config.min_confidence {
+                    cwe_warnings.push(generate_cwe_warning(jmp, symbol, confidence))
+                }
             }
         }
     }
@@ -0,0 +1,398 @@
+//! This module implements a check for CWE-468: Incorrect Pointer Scaling.
+//!
+//! In C, adding an integer to a typed pointer implicitly scales the integer by the size of the pointed-to type.
+//! Scaling the offset again by hand (e.g. `p + i * sizeof(*p)`) multiplies it by the element size twice,
+//! while casting the pointer to a byte pointer before adding an element index (e.g. `*(int *)((char *)p + i)`)
+//! does not scale it at all.
+//! Both lead to accesses at wrong offsets, often out of the bounds of the array.
+//!
+//! See <https://cwe.mitre.org/data/definitions/468.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each memory access the address is expanded with the definitions of the registers earlier in the same block
+//! and split into a constant displacement and non-constant terms, each with the constant factor it is scaled by.
+//! The byte size of the access is used as a hint for the element size of the accessed array.
+//! - If an index is scaled by the square of the element size, it is assumed to be scaled twice.
+//! - If two non-constant terms are added without any scaling while the same memory object
+//!   (according to the [Pointer Inference analysis](crate::analysis::pointer_inference))
+//!   is accessed with an index scaled by the element size elsewhere in the function,
+//!   the index is assumed to be unscaled.
+//!
+//! ## False Positives
+//!
+//! - Arrays of structs whose size is the square of the size of the accessed field look like doubly scaled arrays.
+//!   Such accesses are ignored if the same memory object is also accessed at other fields of the struct.
+//! - Byte buffers containing elements of different sizes (e.g. in parsers) may be accessed both with scaled and with unscaled offsets.
+//!
+//! ## False Negatives
+//!
+//! - The address is only expanded within a block.
+//!   Offsets that are scaled and stored in a local variable before they are added to the pointer are not recognized.
+//! - Incorrect scaling by factors other than the element size is not recognized.
+
+use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+use std::collections::{BTreeSet, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE468",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// An address split into a constant displacement
+/// and non-constant terms together with the factors they are scaled by.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct AddressTerms {
+    /// The constant part of the address.
+    displacement: i64,
+    /// The non-constant terms of the address with their scaling factors.
+    terms: Vec<(u64, Expression)>,
+}
+
+impl AddressTerms {
+    /// Split the expression into its displacement and its scaled terms.
+    fn new(expression: &Expression) -> AddressTerms {
+        let mut address_terms = AddressTerms::default();
+        address_terms.add(expression, 1);
+        address_terms
+    }
+
+    /// Add the expression scaled by the given factor.
+    fn add(&mut self, expression: &Expression, scale: u64) {
+        use Expression::*;
+        match expression {
+            Const(constant) => {
+                if let Ok(constant) = constant.try_to_i64() {
+                    self.displacement = self
+                        .displacement
+                        .wrapping_add(constant.wrapping_mul(scale as i64));
+                }
+            }
+            BinOp {
+                op: BinOpType::IntAdd,
+                lhs,
+                rhs,
+            } => {
+                self.add(lhs, scale);
+                self.add(rhs, scale);
+            }
+            BinOp {
+                op: BinOpType::IntSub,
+                lhs,
+                rhs,
+            } if matches!(rhs.as_ref(), Const(_)) => {
+                self.add(lhs, scale);
+                if let Const(constant) = rhs.as_ref() {
+                    if let Ok(constant) = constant.try_to_i64() {
+                        self.displacement = self
+                            .displacement
+                            .wrapping_sub(constant.wrapping_mul(scale as i64));
+                    }
+                }
+            }
+            BinOp {
+                op: op @ (BinOpType::IntMult | BinOpType::IntLeft),
+                lhs,
+                rhs,
+            } => match (get_factor(*op, rhs), lhs.as_ref()) {
+                (Some(factor), _) => self.add(lhs, scale.wrapping_mul(factor)),
+                (None, Const(_)) if *op == BinOpType::IntMult => match get_factor(*op, lhs) {
+                    Some(factor) => self.add(rhs, scale.wrapping_mul(factor)),
+                    None => self.terms.push((scale, expression.clone())),
+                },
+                _ => self.terms.push((scale, expression.clone())),
+            },
+            Cast {
+                op: CastOpType::IntSExt | CastOpType::IntZExt,
+                arg,
+                ..
+            } => self.add(arg, scale),
+            _ => self.terms.push((scale, expression.clone())),
+        }
+    }
+
+    /// Get the number of non-constant terms that are not scaled.
+    fn count_unscaled_terms(&self) -> usize {
+        self.terms.iter().filter(|(scale, _)| *scale == 1).count()
+    }
+
+    /// Check whether one of the terms is scaled by the given factor.
+    fn has_scale(&self, factor: u64) -> bool {
+        self.terms.iter().any(|(scale, _)| *scale == factor)
+    }
+}
+
+/// Get the factor that a multiplication or left shift by the given constant scales by.
+fn get_factor(op: BinOpType, expression: &Expression) -> Option<u64> {
+    let constant = match expression {
+        Expression::Const(constant) => constant.try_to_u64().ok()?,
+        _ => return None,
+    };
+    match op {
+        BinOpType::IntMult => Some(constant),
+        BinOpType::IntLeft if constant < 64 => Some(1 << constant),
+        _ => None,
+    }
+}
+
+/// Substitute the registers in the expression with their definitions.
+fn substitute(expression: &Expression, definitions: &HashMap<&Variable, Expression>) -> Expression {
+    use Expression::*;
+    match expression {
+        Var(var) => definitions
+            .get(var)
+            .cloned()
+            .unwrap_or_else(|| expression.clone()),
+        Const(_) | Unknown { .. } => expression.clone(),
+        BinOp { op, lhs, rhs } => BinOp {
+            op: *op,
+            lhs: Box::new(substitute(lhs, definitions)),
+            rhs: Box::new(substitute(rhs, definitions)),
+        },
+        UnOp { op, arg } => UnOp {
+            op: *op,
+            arg: Box::new(substitute(arg, definitions)),
+        },
+        Cast { op, size, arg } => Cast {
+            op: *op,
+            size: *size,
+            arg: Box::new(substitute(arg, definitions)),
+        },
+        Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => Subpiece {
+            low_byte: *low_byte,
+            size: *size,
+            arg: Box::new(substitute(arg, definitions)),
+        },
+    }
+}
+
+/// A memory access of a function.
+struct MemoryAccess<'a> {
+    /// The `Load` or `Store` term.
+    def: &'a Term<Def>,
+    /// The byte size of the accessed value.
+    size: u64,
+    /// The address split into its terms.
+    address: AddressTerms,
+    /// The memory objects that the address points to.
+    targets: BTreeSet<AbstractIdentifier>,
+}
+
+/// Collect the memory accesses of the function.
+/// The addresses are expanded with the definitions of registers earlier in the same block.
+fn get_memory_accesses<'a>(
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+) -> Vec<MemoryAccess<'a>> {
+    let mut accesses = Vec::new();
+    for block in sub.term.blocks.iter() {
+        let mut definitions: HashMap<&Variable, Expression> = HashMap::new();
+        for def in block.term.defs.iter() {
+            let (address, size) = match &def.term {
+                Def::Load { var, address } => {
+                    let access = (substitute(address, &definitions), var.size);
+                    definitions.remove(var);
+                    access
+                }
+                Def::Store { address, value } => {
+                    (substitute(address, &definitions), value.bytesize())
+                }
+                Def::Assign { var, value } => {
+                    let value = substitute(value, &definitions);
+                    definitions.insert(var, value);
+                    continue;
+                }
+            };
+            let targets = match pointer_inference
+                .get_state_at_tid(&def.tid)
+                .map(|state| state.eval(&address))
+            {
+                Some(Data::Pointer(pointer)) => pointer.ids().cloned().collect(),
+                _ => BTreeSet::new(),
+            };
+            accesses.push(MemoryAccess {
+                def,
+                size: u64::from(size),
+                address: AddressTerms::new(&address),
+                targets,
+            });
+        }
+    }
+    accesses
+}
+
+/// Check whether the access scales an index by the square of the element size,
+/// i.e. whether the index is scaled twice.
+///
+/// Accesses to memory objects that are also accessed with the same scale at other displacements
+/// are assumed to be accesses to arrays of structs.
+fn is_doubly_scaled(access: &MemoryAccess, accesses: &[MemoryAccess]) -> bool {
+    if access.size < 2 {
+        return false;
+    }
+    let scale = access.size * access.size;
+    if !access.address.has_scale(scale) {
+        return false;
+    }
+    !accesses.iter().any(|other| {
+        other.address.has_scale(scale)
+            && (other.address.displacement != access.address.displacement
+                || other.size != access.size)
+            && !other.targets.is_disjoint(&access.targets)
+    })
+}
+
+/// Check whether the access adds an unscaled index to a pointer
+/// although the same memory object is accessed with indices scaled by the element size elsewhere.
+fn is_unscaled(access: &MemoryAccess, accesses: &[MemoryAccess]) -> bool {
+    if access.size < 2 || access.address.count_unscaled_terms() < 2 || access.targets.is_empty() {
+        return false;
+    }
+    accesses.iter().any(|other| {
+        other.size == access.size
+            && other.address.has_scale(access.size)
+            && !other.targets.is_disjoint(&access.targets)
+    })
+}
+
+/// Generate the CWE warning for an incorrectly scaled memory access.
+fn generate_cwe_warning(sub: &Term<Sub>, access: &MemoryAccess, doubly_scaled: bool) -> CweWarning {
+    let description = if doubly_scaled {
+        format!(
+            "(Incorrect Pointer Scaling) The index of the memory access at {} in {} is scaled by {} although the accessed elements have a size of {} bytes",
+            access.def.tid.address,
+            sub.term.display_name(),
+            access.size * access.size,
+            access.size
+        )
+    } else {
+        format!(
+            "(Incorrect Pointer Scaling) The memory access at {} in {} adds an unscaled index to a pointer to elements with a size of {} bytes",
+            access.def.tid.address, sub.term.display_name(), access.size
+        )
+    };
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .tids(vec![access.def.tid.to_string()])
+        .addresses(vec![access.def.tid.address.clone()])
+        .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Run the CWE check.
+/// For each memory access we compare the scaling of the index with the size of the accessed value.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let accesses = get_memory_accesses(pointer_inference, sub);
+        for access in accesses.iter() {
+            if is_doubly_scaled(access, &accesses) {
+                cwe_warnings.push(generate_cwe_warning(sub, access, true));
+            } else if is_unscaled(access, &accesses) {
+                cwe_warnings.push(generate_cwe_warning(sub, access, false));
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::AbstractLocation;
+
+    fn times(expression: Expression, factor: i64) -> Expression {
+        Expression::BinOp {
+            op: BinOpType::IntMult,
+            lhs: Box::new(expression),
+            rhs: Box::new(Expression::const_from_i64(factor)),
+        }
+    }
+
+    #[test]
+    fn address_terms() {
+        let rax = Expression::Var(Variable::mock("RAX", 8));
+        let rdx = Expression::Var(Variable::mock("RDX", 8));
+        // RAX + (sext(EDX) << 2) * 4 - 8
+        let index = Expression::Cast {
+            op: CastOpType::IntSExt,
+            size: ByteSize::new(8),
+            arg: Box::new(rdx.clone().subpiece(ByteSize::new(0), ByteSize::new(4))),
+        };
+        let address = rax
+            .clone()
+            .plus(times(
+                Expression::BinOp {
+                    op: BinOpType::IntLeft,
+                    lhs: Box::new(index),
+                    rhs: Box::new(Expression::const_from_i64(2)),
+                },
+                4,
+            ))
+            .minus_const(8);
+        let terms = AddressTerms::new(&address);
+        assert_eq!(terms.displacement, -8);
+        assert_eq!(
+            terms.terms,
+            vec![
+                (1, rax.clone()),
+                (16, rdx.subpiece(ByteSize::new(0), ByteSize::new(4)))
+            ]
+        );
+        assert!(terms.has_scale(16));
+        assert_eq!(terms.count_unscaled_terms(), 1);
+    }
+
+    #[test]
+    fn scaling_classification() {
+        let rax = Expression::Var(Variable::mock("RAX", 8));
+        let rdx = Expression::Var(Variable::mock("RDX", 8));
+        let array = AbstractIdentifier::new(
+            Tid::new("func"),
+            AbstractLocation::from_var(&Variable::mock("RDI", 8)).unwrap(),
+        );
+        let def = Def::assign("access", Variable::mock("RCX", 8), rax.clone());
+        let access = |address: Expression, size: u64| MemoryAccess {
+            def: &def,
+            size,
+            address: AddressTerms::new(&address),
+            targets: vec![array.clone()].into_iter().collect(),
+        };
+        let scaled = access(rax.clone().plus(times(rdx.clone(), 4)), 4);
+        let doubly_scaled = access(rax.clone().plus(times(rdx.clone(), 16)), 4);
+        let unscaled = access(rax.clone().plus(rdx.clone()), 4);
+        let struct_field = access(rax.plus(times(rdx, 16)).plus_const(4), 4);
+
+        let accesses = vec![scaled, doubly_scaled, unscaled];
+        assert!(!is_doubly_scaled(&accesses[0], &accesses));
+        assert!(is_doubly_scaled(&accesses[1], &accesses));
+        assert!(!is_unscaled(&accesses[0], &accesses));
+        assert!(is_unscaled(&accesses[2], &accesses));
+        // Other fields accessed with the same scale indicate an array of structs.
+        let accesses = vec![accesses.into_iter().nth(1).unwrap(), struct_field];
+        assert!(!is_doubly_scaled(&accesses[0], &accesses));
+        // Without a scaled access to the same object, unscaled accesses are not flagged.
+        let unscaled = access(
+            Expression::Var(Variable::mock("RSI", 8))
+                .plus(Expression::Var(Variable::mock("RCX", 8))),
+            4,
+        );
+        assert!(!is_unscaled(&unscaled, &[]));
+    }
+}
@@ -0,0 +1,161 @@
+//! This module implements a check for CWE-469: Use of Pointer Subtraction to Determine Size.
+//!
+//! The difference of two pointers is only meaningful if both point into the same memory object.
+//! Subtracting pointers to different objects (e.g. to compute the length of a buffer from a pointer into another buffer)
+//! yields a value depending on the memory layout of the process.
+//! If the result is used as a size, e.g. as the length of a copy operation, it may lead to out-of-bounds accesses.
+//!
+//! See <https://cwe.mitre.org/data/definitions/469.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each subtraction whose result is assigned to a register (i.e. not only used to set flags for a comparison)
+//! we evaluate both operands with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! If both are pointers and they may point to different abstract memory objects,
+//! a warning is generated.
+//!
+//! ## False Positives
+//!
+//! - The Pointer Inference analysis merges the targets of pointers on different paths.
+//!   A pointer that may point to different objects on different paths may always point to the same object
+//!   as the other pointer on each single path.
+//!
+//! ## False Negatives
+//!
+//! - Subtractions of pointers that the Pointer Inference analysis lost track of are not checked.
+//! - Subtractions of pointers stored in temporary registers are ignored,
+//!   since they cannot be distinguished from comparisons.
+
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE469",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// Check whether the `Def` subtracts two pointers that may point to different memory objects.
+fn is_subtraction_of_different_objects(state: &State, def: &Term<Def>) -> bool {
+    let (lhs, rhs) = match &def.term {
+        Def::Assign {
+            var,
+            value:
+                Expression::BinOp {
+                    op: BinOpType::IntSub,
+                    lhs,
+                    rhs,
+                },
+        } if !var.is_temp => (lhs, rhs),
+        _ => return false,
+    };
+    match (state.eval(lhs), state.eval(rhs)) {
+        (Data::Pointer(lhs), Data::Pointer(rhs)) => lhs
+            .ids()
+            .chain(rhs.ids())
+            .any(|id| !lhs.targets().contains_key(id) || !rhs.targets().contains_key(id)),
+        _ => false,
+    }
+}
+
+/// Generate the CWE warning for a subtraction of pointers to different memory objects.
+fn generate_cwe_warning(sub: &Term<Sub>, def: &Term<Def>) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Use of Pointer Subtraction to Determine Size) The pointers subtracted at {} in {} may point to different memory objects",
+            def.tid.address, sub.term.display_name()
+        ),
+    )
+    .tids(vec![format!("{}", def.tid)])
+    .addresses(vec![def.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Run the CWE check.
+/// For each subtraction we check whether it subtracts pointers to different memory objects.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                let is_different_objects = pointer_inference
+                    .get_state_at_tid(&def.tid)
+                    .map(|state| is_subtraction_of_different_objects(&state, def))
+                    .unwrap_or(false);
+                if is_different_objects {
+                    cwe_warnings.push(generate_cwe_warning(sub, def));
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+    use crate::analysis::pointer_inference::object::ObjectType;
+
+    #[test]
+    fn pointer_subtractions() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let heap_id = |call: &str| {
+            AbstractIdentifier::new(
+                Tid::new(call),
+                AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+            )
+        };
+        for call in ["first_malloc", "second_malloc"].iter() {
+            state.memory.add_abstract_object(
+                heap_id(call),
+                Bitvector::from_i64(0).into(),
+                ObjectType::Heap,
+                ByteSize::new(8),
+            );
+        }
+        let rdi = Variable::mock("RDI", 8);
+        let rsi = Variable::mock("RSI", 8);
+        let set_pointer = |state: &mut State, var: &Variable, call: &str, offset: i64| {
+            state.set_register(
+                var,
+                PointerDomain::new(heap_id(call), Bitvector::from_i64(offset).into()).into(),
+            );
+        };
+        let subtraction = Def::assign(
+            "sub",
+            Variable::mock("RAX", 8),
+            Expression::Var(rdi.clone()).minus(Expression::Var(rsi.clone())),
+        );
+
+        set_pointer(&mut state, &rdi, "first_malloc", 16);
+        set_pointer(&mut state, &rsi, "first_malloc", 0);
+        assert!(!is_subtraction_of_different_objects(&state, &subtraction));
+        set_pointer(&mut state, &rsi, "second_malloc", 0);
+        assert!(is_subtraction_of_different_objects(&state, &subtraction));
+        // Subtractions only setting flags for comparisons are ignored.
+        let comparison = Def::assign(
+            "cmp",
+            Variable {
+                is_temp: true,
+                ..Variable::mock("$U_1", 8)
+            },
+            Expression::Var(rdi).minus(Expression::Var(rsi)),
+        );
+        assert!(!is_subtraction_of_different_objects(&state, &comparison));
+    }
+}
@@ -43,6 +43,7 @@ use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
 use crate::CweModule;
 use petgraph::visit::EdgeRef;
 use std::collections::HashMap;
@@ -61,10 +62,13 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE476",
     version: "0.3",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The configuration struct
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// The names of symbols for which the analysis should check
     /// whether the return values are checked for being a Null pointer by the analysed binary.
@@ -74,16 +78,16 @@ pub struct Config {
 /// Run the CWE check.
 /// We check whether the return values of symbols configurable in the config file are being checked for Null pointers
 /// before any memory access (and thus potential Null pointer dereferences) through these values happen.
-pub fn check_cwe(
-    analysis_results: &AnalysisResults,
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let pointer_inference_results = analysis_results.pointer_inference().unwrap();
 
     let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
 
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let symbol_map = crate::utils::symbol_utils::get_symbol_map(project, &config.symbols[..]);
     let general_context = Context::new(
         project,
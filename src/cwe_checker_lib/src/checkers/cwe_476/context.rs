@@ -168,7 +168,8 @@ impl<'a> Context<'a> {
             taint_source.tid.address, taint_source_name))
             .addresses(vec![taint_source.tid.address.clone(), taint_access_location.address.clone()])
             .tids(vec![format!("{}", taint_source.tid), format!("{}", taint_access_location)])
-            .symbols(vec![taint_source_name]);
+            .symbols(vec![taint_source_name])
+            .root_cause(&taint_source.tid);
         let _ = self.cwe_collector.send(cwe_warning);
     }
 
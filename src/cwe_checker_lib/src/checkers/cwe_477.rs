@@ -0,0 +1,168 @@
+//! This module implements a check for CWE-477: Use of Obsolete Function.
+//!
+//! Obsolete functions like `gethostbyname` or `usleep` are not immediately dangerous
+//! (in contrast to the functions flagged by the [CWE-676 check](super::cwe_676)),
+//! but they are deprecated or removed in current standards,
+//! are often not thread-safe or lack error reporting,
+//! and have modern replacements that should be used instead.
+//!
+//! See <https://cwe.mitre.org/data/definitions/477.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Calls to the obsolete functions configured in the `symbols` of the `CWE477` section of config.json are flagged.
+//! Each function is mapped to its modern replacement, which is included in the warning.
+//! All warnings get the configured `severity`, which defaults to `low`.
+//!
+//! ## False Positives
+//!
+//! - Obsolete functions may be the only available option on the target platform.
+//!
+//! ## False Negatives
+//!
+//! - Obsolete functions that are statically linked into the binary are not recognized.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE477",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Obsolete functions, mapped to their modern replacements.
+    symbols: BTreeMap<String, String>,
+    /// The severity of the generated warnings.
+    #[serde(default)]
+    severity: Severity,
+}
+
+/// The severity of the warnings of the check.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The default severity for obsolete functions.
+    #[default]
+    Low,
+    /// Medium severity.
+    Medium,
+    /// High severity.
+    High,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Low => write!(formatter, "low"),
+            Severity::Medium => write!(formatter, "medium"),
+            Severity::High => write!(formatter, "high"),
+        }
+    }
+}
+
+/// Generate the CWE warning for a call to an obsolete function.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    replacement: &str,
+    severity: Severity,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Use of Obsolete Function) {} ({}) -> {}. Use {} instead.",
+            sub.term.display_name(),
+            call.tid.address,
+            symbol.name,
+            replacement
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![
+        sub.term.display_name().to_string(),
+        symbol.name.clone(),
+    ])
+    .other(vec![
+        vec!["severity".to_string(), severity.to_string()],
+        vec!["replacement".to_string(), replacement.to_string()],
+    ])
+}
+
+/// Run the CWE check.
+/// Flag all calls to obsolete functions.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbol_names: Vec<String> = config.symbols.keys().cloned().collect();
+    let symbols = get_symbol_map(project, &symbol_names);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, call, symbol) in get_callsites(sub, &symbols) {
+            cwe_warnings.push(generate_cwe_warning(
+                sub,
+                call,
+                symbol,
+                &config.symbols[&symbol.name],
+                config.severity,
+            ));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obsolete_function_warning() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "symbols": {"usleep": "nanosleep"}
+        }))
+        .unwrap();
+        assert_eq!(config.severity, Severity::Low);
+        let sub = Sub::mock("func");
+        let call = Term {
+            tid: Tid::new("call_usleep"),
+            term: Jmp::Call {
+                target: Tid::new("usleep"),
+                return_: None,
+            },
+        };
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "usleep".to_string();
+        let warning = generate_cwe_warning(
+            &sub,
+            &call,
+            &symbol,
+            &config.symbols["usleep"],
+            config.severity,
+        );
+        assert!(warning.description.ends_with("Use nanosleep instead."));
+        assert_eq!(
+            warning.other[0],
+            vec!["severity".to_string(), "low".to_string()]
+        );
+    }
+}
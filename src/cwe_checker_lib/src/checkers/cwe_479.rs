@@ -0,0 +1,394 @@
+//! This module implements a check for CWE-479: Signal Handler Use of a Non-reentrant Function.
+//!
+//! A signal handler may interrupt the program at any instruction,
+//! e.g. while `malloc` is modifying the heap metadata or while `printf` is modifying a buffer of the standard output.
+//! If the handler calls such a non-reentrant function itself, it may corrupt the state of the interrupted call.
+//! Depending on the function this leads to deadlocks, crashes or exploitable memory corruption.
+//! Only the functions listed as async-signal-safe by POSIX may be called from signal handlers.
+//!
+//! See <https://cwe.mitre.org/data/definitions/479.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the configured `handler_parameters` (e.g. `signal`)
+//! we evaluate the parameter containing the handler with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! For each call to one of the configured `sigaction_parameters` (e.g. `sigaction`)
+//! the handler is loaded from the first member of the `struct sigaction` that the parameter points to.
+//! If the handler is the constant address of a function of the program, the function is a signal handler.
+//!
+//! Signal handlers are never called by the program itself,
+//! so they are the roots of their own parts of the call graph.
+//! All functions reachable from a signal handler in the call graph run in signal handler context.
+//! A warning is generated for each call to one of the configured `unsafe_functions`
+//! (e.g. `malloc` or `printf`) in these functions.
+//!
+//! ## False Positives
+//!
+//! - Calls to unsafe functions are reported even if the handler only calls them
+//!   when the interrupted code cannot be in an unsafe function, e.g. when the signal is blocked elsewhere.
+//! - Calls to `exit` after which no other handler or thread can run are harmless in practice, but are reported.
+//!
+//! ## False Negatives
+//!
+//! - Handlers computed at runtime (e.g. chosen from a table) are not found.
+//! - Pointers to a `struct sigaction` passed on the stack (e.g. on 32-bit x86) are not evaluated.
+//! - On MIPS the handler is not the first member of `struct sigaction`, so handlers registered with `sigaction` are not found.
+//! - Indirect calls in signal handlers are not followed.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::graph_utils::CallGraph;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{btree_map::Entry, BTreeMap, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE479",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions registering a signal handler,
+    /// mapped to the index of the parameter containing the address of the handler.
+    handler_parameters: BTreeMap<String, usize>,
+    /// Functions registering the signal handler contained in a `struct sigaction`,
+    /// mapped to the index of the parameter pointing to the struct.
+    #[serde(default)]
+    sigaction_parameters: BTreeMap<String, usize>,
+    /// Functions that are not async-signal-safe.
+    unsafe_functions: Vec<String>,
+}
+
+/// Get the address of the signal handler registered by a call with the given state.
+///
+/// If `in_sigaction_struct` is set, the parameter points to a `struct sigaction`
+/// and the handler is the first member of the struct.
+/// Returns `None` if the address is not a constant.
+fn get_handler_address(
+    project: &Project,
+    state: &State,
+    runtime_memory_image: &RuntimeMemoryImage,
+    parameter: &Arg,
+    in_sigaction_struct: bool,
+) -> Option<u64> {
+    let handler = if in_sigaction_struct {
+        match parameter {
+            Arg::Register(var) => state
+                .load_value(
+                    &Expression::Var(var.clone()),
+                    project.get_pointer_bytesize(),
+                    runtime_memory_image,
+                )
+                .ok()?,
+            Arg::Stack { .. } => return None,
+        }
+    } else {
+        state
+            .eval_parameter_arg(
+                parameter,
+                &project.stack_pointer_register,
+                runtime_memory_image,
+            )
+            .ok()?
+    };
+    match handler {
+        Data::Value(value) => value.try_to_bitvec().ok()?.try_to_u64().ok(),
+        _ => None,
+    }
+}
+
+/// Get the signal handlers registered by calls to the configured functions,
+/// mapped to the calls registering them.
+fn get_signal_handlers<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    config: &Config,
+) -> BTreeMap<&'a Tid, Vec<&'a Term<Jmp>>> {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let handler_symbol_names: Vec<String> = config.handler_parameters.keys().cloned().collect();
+    let handler_symbols = get_symbol_map(project, &handler_symbol_names);
+    let sigaction_symbol_names: Vec<String> = config.sigaction_parameters.keys().cloned().collect();
+    let sigaction_symbols = get_symbol_map(project, &sigaction_symbol_names);
+    let sub_address_map: HashMap<u64, &Tid> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter_map(|sub| {
+            let address = u64::from_str_radix(&sub.tid.address, 16).ok()?;
+            Some((address, &sub.tid))
+        })
+        .collect();
+    // On ARM the lowest bit of the address marks Thumb code.
+    let ignore_thumb_bit = project.cpu_architecture.starts_with("ARM");
+
+    let mut handlers: BTreeMap<&Tid, Vec<&Term<Jmp>>> = BTreeMap::new();
+    for sub in project.program.term.subs.iter() {
+        let registrations = get_callsites(sub, &handler_symbols)
+            .into_iter()
+            .map(|(_, call, symbol)| (call, symbol, false))
+            .chain(
+                get_callsites(sub, &sigaction_symbols)
+                    .into_iter()
+                    .map(|(_, call, symbol)| (call, symbol, true)),
+            );
+        for (call, symbol, in_sigaction_struct) in registrations {
+            let index = if in_sigaction_struct {
+                config.sigaction_parameters[&symbol.name]
+            } else {
+                config.handler_parameters[&symbol.name]
+            };
+            let state = match pointer_inference.get_state_at_jmp_tid(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            let parameter = get_variadic_parameter(project, symbol, index);
+            let address = match get_handler_address(
+                project,
+                state,
+                analysis_results.runtime_memory_image,
+                &parameter,
+                in_sigaction_struct,
+            ) {
+                Some(address) if ignore_thumb_bit => address & !1,
+                Some(address) => address,
+                None => continue,
+            };
+            if let Some(handler) = sub_address_map.get(&address) {
+                handlers.entry(*handler).or_default().push(call);
+            }
+        }
+    }
+    handlers
+}
+
+/// Get the functions reachable from the signal handlers in the call graph,
+/// mapped to the first signal handler from which they are reachable.
+fn get_functions_reachable_from_handlers<'a>(
+    call_graph: &'a CallGraph,
+    handlers: impl IntoIterator<Item = &'a Tid>,
+) -> BTreeMap<&'a Tid, &'a Tid> {
+    let mut reachable_functions = BTreeMap::new();
+    for handler in handlers {
+        let mut stack = vec![handler];
+        while let Some(sub_tid) = stack.pop() {
+            if let Entry::Vacant(entry) = reachable_functions.entry(sub_tid) {
+                entry.insert(handler);
+                stack.extend(call_graph.get_callees(sub_tid));
+            }
+        }
+    }
+    reachable_functions
+}
+
+/// Generate the CWE warning for a call to an unsafe function reachable from a signal handler.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    handler: &Term<Sub>,
+) -> CweWarning {
+    let context = if sub.tid == handler.tid {
+        String::new()
+    } else {
+        format!(
+            ", which is called by the signal handler {}",
+            handler.term.display_name()
+        )
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Signal Handler Use of a Non-reentrant Function) {} calls the async-signal-unsafe function {} at {}{}",
+            sub.term.display_name(), symbol.name, call.tid.address, context
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![vec![
+        "signal_handler".to_string(),
+        handler.term.display_name().to_string(),
+    ]])
+}
+
+/// Run the CWE check.
+/// We collect the signal handlers registered with constant addresses
+/// and flag calls to unsafe functions in all functions reachable from them.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let unsafe_symbols = get_symbol_map(project, &config.unsafe_functions);
+    if unsafe_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let handlers = get_signal_handlers(analysis_results, &config);
+    let logs = handlers
+        .iter()
+        .map(|(handler, calls)| {
+            LogMessage::new_debug(format!(
+                "Signal handler {} registered at {}",
+                handler,
+                calls
+                    .iter()
+                    .map(|call| call.tid.address.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .source(CWE_MODULE.name)
+        })
+        .collect();
+    let call_graph = CallGraph::new(&project.program);
+    let reachable_functions =
+        get_functions_reachable_from_handlers(&call_graph, handlers.into_keys());
+    let subs: HashMap<&Tid, &Term<Sub>> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub))
+        .collect();
+
+    let mut cwe_warnings = Vec::new();
+    for (sub_tid, handler) in reachable_functions {
+        let sub = subs[sub_tid];
+        for (_block, call, symbol) in get_callsites(sub, &unsafe_symbols) {
+            cwe_warnings.push(generate_cwe_warning(sub, call, symbol, subs[handler]));
+        }
+    }
+    (logs, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::SizedDomain;
+    use crate::analysis::pointer_inference::ValueDomain;
+
+    #[test]
+    fn handler_addresses() {
+        let project = Project::mock_empty();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let rsi = Variable::mock("RSI", 8);
+        let parameter = Arg::Register(rsi.clone());
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("main"));
+        let handler = Data::Value(ValueDomain::from(Bitvector::from_u64(0x1000)));
+
+        // signal(SIGINT, handler)
+        state.set_register(&rsi, handler.clone());
+        let get_address = |state: &State, in_sigaction_struct| {
+            get_handler_address(
+                &project,
+                state,
+                &runtime_memory_image,
+                &parameter,
+                in_sigaction_struct,
+            )
+        };
+        assert_eq!(get_address(&state, false), Some(0x1000));
+        // The value of the parameter is no pointer to a struct sigaction.
+        assert_eq!(get_address(&state, true), None);
+
+        // sigaction(SIGINT, &action, NULL) with action.sa_handler = handler
+        let stack_pointer = Expression::Var(project.stack_pointer_register.clone());
+        let action = state.eval(&stack_pointer.plus_const(-0x98));
+        state
+            .store_value(&action, &handler, &runtime_memory_image)
+            .unwrap();
+        state.set_register(&rsi, action);
+        assert_eq!(get_address(&state, true), Some(0x1000));
+        assert_eq!(get_address(&state, false), None);
+
+        // Unknown handlers are ignored.
+        state.set_register(&rsi, Data::new_top(ByteSize::new(8)));
+        assert_eq!(get_address(&state, false), None);
+        let stack_parameter = Arg::Stack {
+            offset: 8,
+            size: ByteSize::new(8),
+        };
+        assert_eq!(
+            get_handler_address(
+                &project,
+                &state,
+                &runtime_memory_image,
+                &stack_parameter,
+                true
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn reachable_functions() {
+        let mut program = Program::mock_empty();
+        let sub = |name: &str, target: &str| {
+            let mut sub = Sub::mock(name);
+            sub.term.blocks = vec![Blk::mock_with(
+                &format!("{}_entry", name),
+                Vec::new(),
+                Jmp::mock_call(target, &format!("{}_return", name)),
+            )];
+            sub
+        };
+        let (main, handler, log) = (
+            sub("main", "work"),
+            sub("handler", "log"),
+            sub("log", "malloc"),
+        );
+        program.subs = vec![main, handler, log, Sub::mock("work")];
+        let program = Term {
+            tid: Tid::new("program"),
+            term: program,
+        };
+
+        let call_graph = CallGraph::new(&program);
+        let handler_tid = Tid::new("handler");
+        let reachable_functions =
+            get_functions_reachable_from_handlers(&call_graph, [&handler_tid]);
+        let log_tid = Tid::new("log");
+        let expected: BTreeMap<&Tid, &Tid> =
+            vec![(&handler_tid, &handler_tid), (&log_tid, &handler_tid)]
+                .into_iter()
+                .collect();
+        assert_eq!(reachable_functions, expected);
+        // Functions only called from main do not run in signal handler context.
+        assert!(!reachable_functions.contains_key(&Tid::new("work")));
+        assert!(get_functions_reachable_from_handlers(&call_graph, []).is_empty());
+    }
+
+    #[test]
+    fn cwe_warning() {
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "malloc".to_string();
+        let handler = Sub::mock("handler");
+        let call = Jmp::call("call_malloc", "malloc", None);
+        let warning = generate_cwe_warning(&Sub::mock("log"), &call, &symbol, &handler);
+        assert!(warning.description.ends_with(
+            "log calls the async-signal-unsafe function malloc at UNKNOWN, which is called by the signal handler handler"
+        ));
+        assert_eq!(
+            warning.other,
+            vec![vec!["signal_handler".to_string(), "handler".to_string()]]
+        );
+        let warning = generate_cwe_warning(&handler, &call, &symbol, &handler);
+        assert!(warning
+            .description
+            .ends_with("handler calls the async-signal-unsafe function malloc at UNKNOWN"));
+    }
+}
@@ -0,0 +1,555 @@
+//! This module implements a check for CWE-532: Insertion of Sensitive Information into Log File.
+//!
+//! Log files are usually readable by more users than the process writing them,
+//! are shipped to central logging servers and are kept for a long time.
+//! Secrets like passwords or keys written to logs (e.g. in debug messages) are exposed to all of them.
+//!
+//! See <https://cwe.mitre.org/data/definitions/532.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We reuse the intraprocedural taint analysis for secrets of the [CWE-208 check](super::cwe_208).
+//! Secrets are
+//! - the memory pointed to by the return values of the configured `secret_sources` (e.g. `getpass`),
+//! - the memory pointed to by the parameters of the configured `secret_parameters`
+//!   after the call (e.g. the key buffer written by `EVP_BytesToKey`),
+//! - global variables whose ELF symbol names contain one of the configured `secret_global_keywords`.
+//!
+//! The configured `log_sinks` are the functions writing log messages.
+//! For each sink the logged parameters are configured as `data_parameters`
+//! and, for `printf`-like functions, by the index of the `format_parameter`.
+//! The variadic parameters consumed by the format string are logged as well,
+//! if the format string is a constant.
+//! Sinks writing to a `FILE` stream (e.g. `fprintf`) are only considered
+//! if the value passed as the `stream_parameter` is loaded from one of the configured `log_streams` (e.g. `stderr`),
+//! which is determined with the [def-use chains](crate::analysis::def_use) of the function.
+//!
+//! A warning is generated for each call to a log sink with a logged parameter that points to a secret
+//! or contains a value loaded from a secret.
+//!
+//! ## False Positives
+//!
+//! - Secrets may be masked or hashed by the format (e.g. only the length of a password is logged with `%zu`).
+//! - Global variables are only recognized as secrets by their names, which may be misleading.
+//!
+//! ## False Negatives
+//!
+//! - The taint analysis is intraprocedural.
+//!   Secrets passed as parameters or returned from internal functions are not tracked.
+//! - Struct fields containing secrets are not recognized, since debug information is not used.
+//! - Streams passed on the stack (e.g. on x86) or accessed through the global offset table are not recognized.
+
+use super::cwe_208::{get_secret_globals, Context, TaintState};
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{PointerInference, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::{get_elf_object_symbols, RuntimeMemoryImage};
+use crate::utils::format_string::parse_format_string;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_variadic_parameter;
+use crate::CweModule;
+use std::collections::{BTreeMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE532",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions returning pointers to secrets.
+    secret_sources: Vec<String>,
+    /// Functions writing secrets to the memory pointed to by the parameter with the given index.
+    #[serde(default)]
+    secret_parameters: BTreeMap<String, usize>,
+    /// Global variables whose names contain one of these keywords (ignoring case) are assumed to contain secrets.
+    #[serde(default)]
+    secret_global_keywords: Vec<String>,
+    /// Functions writing log messages.
+    log_sinks: BTreeMap<String, LogSink>,
+    /// Global `FILE` streams that are used for logging, e.g. `stderr`.
+    #[serde(default)]
+    log_streams: Vec<String>,
+}
+
+/// The logged parameters of a log sink.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct LogSink {
+    /// The index of the format string parameter of `printf`-like functions.
+    #[serde(default)]
+    format_parameter: Option<usize>,
+    /// The indices of other parameters containing logged data.
+    #[serde(default)]
+    data_parameters: Vec<usize>,
+    /// The index of the `FILE` stream parameter of functions writing to streams.
+    #[serde(default)]
+    stream_parameter: Option<usize>,
+}
+
+/// Get the indices of the logged parameters of a call to the log sink.
+///
+/// If the format string is known, the variadic parameters consumed by it are logged as well.
+fn get_logged_parameter_indices(sink: &LogSink, format_string: Option<&str>) -> Vec<usize> {
+    let mut indices = sink.data_parameters.clone();
+    if let Some(format_index) = sink.format_parameter {
+        indices.push(format_index);
+        if let Some(format_string) = format_string {
+            indices.extend(
+                parse_format_string(format_string)
+                    .iter()
+                    .map(|specifier| format_index + 1 + specifier.argument_index),
+            );
+        }
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// Read the constant format string passed to the call, if it is known.
+fn get_format_string<'a>(
+    project: &Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    pi_state: &PointerInferenceState,
+    symbol: &ExternSymbol,
+    format_index: usize,
+) -> Option<&'a str> {
+    let address = pi_state
+        .eval_parameter_arg(
+            &get_variadic_parameter(project, symbol, format_index),
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?
+        .try_to_bitvec()
+        .ok()?;
+    runtime_memory_image
+        .read_string_until_null_terminator(&address)
+        .ok()
+}
+
+/// Check whether the value of the parameter at the call is loaded from the address of one of the log streams.
+///
+/// Assignments to registers are followed backwards along the def-use chains of the function.
+fn is_log_stream(
+    def_use_chains: &DefUseChains,
+    pointer_inference: &PointerInference,
+    call: &Term<Jmp>,
+    parameter: &Arg,
+    stream_addresses: &HashSet<u64>,
+) -> bool {
+    let var = match parameter {
+        Arg::Register(var) => var,
+        Arg::Stack { .. } => return false,
+    };
+    let mut visited = HashSet::new();
+    let mut worklist: Vec<&Tid> = def_use_chains.defs_reaching(&call.tid, var).collect();
+    while let Some(def_tid) = worklist.pop() {
+        if !visited.insert(def_tid) {
+            continue;
+        }
+        let def = match def_use_chains.get_definition(def_tid) {
+            Some(Definition::Def(def)) => def,
+            _ => continue,
+        };
+        match &def.term {
+            Def::Load { address, .. } => {
+                let is_stream_address = pointer_inference
+                    .get_state_at_tid(&def.tid)
+                    .and_then(|state| state.eval(address).try_to_bitvec().ok())
+                    .and_then(|address| address.try_to_u64().ok())
+                    .map(|address| stream_addresses.contains(&address))
+                    .unwrap_or(false);
+                if is_stream_address {
+                    return true;
+                }
+            }
+            Def::Assign { value, .. } => {
+                for input in value.input_vars() {
+                    worklist.extend(def_use_chains.defs_reaching(&def.tid, input));
+                }
+            }
+            Def::Store { .. } => (),
+        }
+    }
+    false
+}
+
+/// Generate the CWE warning for a call to a log sink with secret parameters.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    secret_indices: &[usize],
+) -> CweWarning {
+    let indices: Vec<String> = secret_indices
+        .iter()
+        .map(|index| index.to_string())
+        .collect();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Insertion of Sensitive Information into Log File) A secret is logged by {} at {} in {} (parameters {})",
+            symbol.name,
+            call.tid.address,
+            sub.term.display_name(),
+            indices.join(", ")
+        ),
+    )
+    .tids(vec![call.tid.to_string()])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+}
+
+/// Get the indices of the logged parameters of the call that point to secrets or contain secrets.
+fn get_secret_parameter_indices(
+    project: &Project,
+    context: &Context,
+    runtime_memory_image: &RuntimeMemoryImage,
+    symbol: &ExternSymbol,
+    sink: &LogSink,
+    pi_state: &PointerInferenceState,
+    taint_state: &TaintState,
+) -> Vec<usize> {
+    let format_string = sink.format_parameter.and_then(|format_index| {
+        get_format_string(
+            project,
+            runtime_memory_image,
+            pi_state,
+            symbol,
+            format_index,
+        )
+    });
+    get_logged_parameter_indices(sink, format_string)
+        .into_iter()
+        .filter(|index| {
+            let parameter = get_variadic_parameter(project, symbol, *index);
+            context.is_secret_parameter(pi_state, taint_state, &parameter)
+                || pi_state
+                    .eval_parameter_arg(
+                        &parameter,
+                        &project.stack_pointer_register,
+                        runtime_memory_image,
+                    )
+                    .map(|address| taint_state.load(&address))
+                    .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Run the CWE check.
+/// For each call to a log sink we check whether one of the logged parameters is a secret.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = parse_config(cwe_params).unwrap();
+    if !project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .any(|symbol| config.log_sinks.contains_key(&symbol.name))
+    {
+        return (Vec::new(), Vec::new());
+    }
+    let mut logs = Vec::new();
+    let secret_globals = match get_secret_globals(
+        analysis_results.binary,
+        &config.secret_global_keywords,
+        project.program.term.address_base_offset,
+    ) {
+        Ok(secret_globals) => secret_globals,
+        Err(err) => {
+            logs.push(
+                LogMessage::new_debug(format!("Secret global variables not available: {}", err))
+                    .source(CWE_MODULE.name),
+            );
+            Vec::new()
+        }
+    };
+    let stream_addresses: HashSet<u64> = get_elf_object_symbols(analysis_results.binary)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(name, _, _)| {
+            let name = name.split('@').next().unwrap_or_default();
+            config.log_streams.iter().any(|stream| stream == name)
+        })
+        .map(|(_, address, _)| address + project.program.term.address_base_offset)
+        .collect();
+    let context = Context::new(
+        project,
+        pointer_inference,
+        secret_globals,
+        config.secret_sources.clone(),
+        config.secret_parameters.clone(),
+        Vec::new(),
+    );
+
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let mut def_use_chains = None;
+        for (call, symbol, pi_state, taint_state) in context.get_states_at_calls(sub) {
+            let sink = match config.log_sinks.get(&symbol.name) {
+                Some(sink) => sink,
+                None => continue,
+            };
+            if let Some(stream_index) = sink.stream_parameter {
+                let def_use_chains =
+                    def_use_chains.get_or_insert_with(|| DefUseChains::new(project, sub));
+                let stream_parameter = get_variadic_parameter(project, symbol, stream_index);
+                if !is_log_stream(
+                    def_use_chains,
+                    pointer_inference,
+                    call,
+                    &stream_parameter,
+                    &stream_addresses,
+                ) {
+                    continue;
+                }
+            }
+            let secret_indices = get_secret_parameter_indices(
+                project,
+                &context,
+                runtime_memory_image,
+                symbol,
+                sink,
+                &pi_state,
+                &taint_state,
+            );
+            if !secret_indices.is_empty() {
+                cwe_warnings.push(generate_cwe_warning(sub, call, symbol, &secret_indices));
+            }
+        }
+    }
+    (logs, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use serde_json::json;
+
+    #[test]
+    fn logged_parameters() {
+        let syslog = LogSink {
+            format_parameter: Some(1),
+            ..LogSink::default()
+        };
+        assert_eq!(
+            get_logged_parameter_indices(&syslog, Some("user %s logged in with password %s")),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            get_logged_parameter_indices(&syslog, Some("%2$s %1$d %%")),
+            vec![1, 2, 3]
+        );
+        // Without a known format string only the format string itself is checked.
+        assert_eq!(get_logged_parameter_indices(&syslog, None), vec![1]);
+
+        let fputs = LogSink {
+            data_parameters: vec![0],
+            stream_parameter: Some(1),
+            ..LogSink::default()
+        };
+        assert_eq!(get_logged_parameter_indices(&fputs, None), vec![0]);
+    }
+
+    fn var(name: &str) -> Expression {
+        Expression::Var(Variable::mock(name, 8))
+    }
+
+    /// A function calling the given extern functions in order.
+    /// Before each call the given Defs are executed.
+    /// The block of the i-th call is named `{name}_{i}`.
+    fn call_sequence(name: &str, calls: Vec<(Vec<Term<Def>>, &str)>) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        let call_count = calls.len();
+        for (index, (defs, target)) in calls.into_iter().enumerate() {
+            sub.term.blocks.push(Blk::mock_with(
+                &format!("{}_{}", name, index),
+                defs,
+                Jmp::mock_call(target, &format!("{}_{}", name, index + 1)),
+            ));
+        }
+        sub.term.blocks.push(Blk::mock_with(
+            &format!("{}_{}", name, call_count),
+            Vec::new(),
+            Jmp::Return(Expression::const_from_i64(0)),
+        ));
+        sub
+    }
+
+    /// Run the check on the project and return the TIDs of the calls logging secrets.
+    fn reported_calls(project: &Project, config: serde_json::Value) -> Vec<String> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| warning.tids[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn logged_secrets() {
+        let mut project = Project::mock_empty();
+        let mut calling_convention = CallingConvention::mock();
+        calling_convention.parameter_register =
+            vec!["RDI".to_string(), "RSI".to_string(), "RDX".to_string()];
+        project.calling_conventions = vec![calling_convention];
+        project.program.term.extern_symbols = ["getpass", "derive_key", "puts", "syslog", "fputs"]
+            .iter()
+            .map(|name| ExternSymbol::mock_with(name, &["RDI", "RSI", "RDX"]))
+            .collect();
+        // Points to the read-only string "Hello World" in the mock runtime memory image.
+        let constant_string = |tid: &str, var: &str| {
+            Def::assign(
+                tid,
+                Variable::mock(var, 8),
+                Expression::const_from_i64(0x3002),
+            )
+        };
+        let secret =
+            |tid: &str, register: &str| Def::assign(tid, Variable::mock(register, 8), var("RAX"));
+        project.program.term.subs = vec![
+            // puts(getpass(...))
+            call_sequence(
+                "source",
+                vec![
+                    (Vec::new(), "getpass"),
+                    (vec![secret("source_arg", "RDI")], "puts"),
+                ],
+            ),
+            // puts("Hello World")
+            call_sequence(
+                "constant",
+                vec![(vec![constant_string("constant_arg", "RDI")], "puts")],
+            ),
+            // derive_key(buffer); puts(buffer)
+            call_sequence(
+                "parameter",
+                vec![
+                    (
+                        vec![
+                            Def::assign(
+                                "parameter_frame",
+                                Variable::mock("RBP", 8),
+                                var("RSP").plus_const(-0x40),
+                            ),
+                            Def::assign("parameter_key", Variable::mock("RDI", 8), var("RBP")),
+                        ],
+                        "derive_key",
+                    ),
+                    (
+                        vec![Def::assign(
+                            "parameter_arg",
+                            Variable::mock("RDI", 8),
+                            var("RBP"),
+                        )],
+                        "puts",
+                    ),
+                ],
+            ),
+            // syslog(priority, "Hello World", getpass(...))
+            // The format string consumes no arguments, so that the secret is not logged.
+            call_sequence(
+                "unused_argument",
+                vec![
+                    (Vec::new(), "getpass"),
+                    (
+                        vec![
+                            constant_string("unused_argument_format", "RSI"),
+                            secret("unused_argument_arg", "RDX"),
+                        ],
+                        "syslog",
+                    ),
+                ],
+            ),
+            // syslog(priority, getpass(...))
+            call_sequence(
+                "format",
+                vec![
+                    (Vec::new(), "getpass"),
+                    (vec![secret("format_arg", "RSI")], "syslog"),
+                ],
+            ),
+            // fputs(getpass(...), stream) with a stream that is not a log stream
+            call_sequence(
+                "stream",
+                vec![
+                    (Vec::new(), "getpass"),
+                    (vec![secret("stream_arg", "RDI")], "fputs"),
+                ],
+            ),
+        ];
+
+        let config = json!({
+            "secret_sources": ["getpass"],
+            "secret_parameters": {"derive_key": 0},
+            "log_sinks": {
+                "puts": {"data_parameters": [0]},
+                "syslog": {"format_parameter": 1},
+                "fputs": {"data_parameters": [0], "stream_parameter": 1}
+            },
+            "log_streams": ["stderr"]
+        });
+        assert_eq!(
+            reported_calls(&project, config),
+            vec!["source_1_jmp", "parameter_1_jmp", "format_1_jmp"]
+        );
+        // Only the configured secrets and logged parameters are checked.
+        let config = json!({
+            "secret_sources": ["getpass"],
+            "log_sinks": {"syslog": {"data_parameters": [2]}}
+        });
+        assert_eq!(
+            reported_calls(&project, config),
+            vec!["unused_argument_1_jmp"]
+        );
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: Config = parse_config(&json!({
+            "secret_sources": ["getpass"],
+            "log_sinks": {"puts": {}}
+        }))
+        .unwrap();
+        assert_eq!(config.log_sinks["puts"], LogSink::default());
+        assert!(config.log_streams.is_empty());
+        assert!(parse_config::<Config>(&json!({"secret_sources": ["getpass"]})).is_err());
+        assert!(parse_config::<Config>(&json!({
+            "secret_sources": [],
+            "log_sinks": {"puts": {"parameter": 0}}
+        }))
+        .is_err());
+    }
+}
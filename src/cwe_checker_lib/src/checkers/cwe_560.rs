@@ -36,6 +36,8 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE560",
     version: "0.2",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
 };
 
 /// An upper bound for the value of a presumably correct umask argument.
@@ -89,7 +91,7 @@ fn is_chmod_style_arg(arg: u64) -> bool {
 /// Generate the CWE warning for a detected instance of the CWE.
 fn generate_cwe_warning(sub: &Term<Sub>, jmp: &Term<Jmp>, permission_const: u64) -> CweWarning {
     CweWarning::new(CWE_MODULE.name, CWE_MODULE.version,
-        format!("(Use of umask() with chmod-style Argument) Function {} calls umask with argument {:#o}", sub.term.name, permission_const))
+        format!("(Use of umask() with chmod-style Argument) Function {} calls umask with argument {:#o}", sub.term.display_name(), permission_const))
         .tids(vec![format!("{}", jmp.tid)])
         .addresses(vec![jmp.tid.address.clone()])
         .other(vec![vec![
@@ -0,0 +1,742 @@
+//! This module implements a check for CWE-606: Unchecked Input for Loop Condition.
+//!
+//! If the number of iterations of a loop is controlled by untrusted input without an upper bound,
+//! an attacker can make the program loop for a very long time (denial of service)
+//! or, if the loop writes into a buffer, write behind the end of the buffer.
+//!
+//! See <https://cwe.mitre.org/data/definitions/606.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The loops of each function are computed from the strongly connected components of its control flow graph
+//! (see [`get_loops`]).
+//! For each conditional jump leaving a loop we trace the jump condition backwards along the
+//! [def-use chains](crate::analysis::def_use) of the function to the comparisons it is computed from.
+//! The compared values are then traced backwards to untrusted sources:
+//! the return values of the `return_value_sources` configured in config.json (e.g. `atoi`)
+//! and the memory written by the `parameter_sources` (e.g. the buffer of `recv`) in the same function.
+//! Values loaded from memory are traced to the values stored to the same memory cell in the function,
+//! using the [Pointer Inference analysis](crate::analysis::pointer_inference) to identify the memory cells.
+//!
+//! A source is considered checked
+//! - if the interval domain of the Pointer Inference analysis knows an upper bound for a compared value derived from it
+//!   that is smaller than the largest `int` value,
+//! - or if a value derived from it is compared to a nonzero constant by a conditional jump outside of the loop.
+//!
+//! A warning is generated for each loop exit whose condition depends on an unchecked source.
+//!
+//! ## False Positives
+//!
+//! - Checks in other functions (e.g. in a parsing function) are not recognized.
+//! - Loops whose number of iterations is bounded by other means (e.g. a fixed-size buffer checked in the loop body)
+//!   are flagged if the bound is not part of the loop condition.
+//! - The memory written by a parameter source is treated as tainted in the whole function,
+//!   even before the call to the source.
+//!
+//! ## False Negatives
+//!
+//! - The analysis is intraprocedural.
+//!   Loop bounds passed as parameters or returned from internal functions are not traced.
+//! - Any comparison of an untrusted value with a nonzero constant outside of the loop counts as a check,
+//!   even if it is not an upper bound check or does not happen on all paths to the loop.
+//! - The length of the traced dependency chains is bounded.
+
+use crate::abstract_domain::{AbstractIdentifier, AbstractLocation, TryToBitvec, TryToInterval};
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_loops;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_variadic_parameter};
+use crate::utils::taint_sources::{ParameterSource, TaintSourceConfig};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE606",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<TaintSourceConfig>,
+    default_config: crate::utils::default_config::<TaintSourceConfig>,
+};
+
+/// The maximal number of definitions that are followed backwards from a loop condition.
+const MAX_TRACE_DEPTH: usize = 16;
+
+/// Values with an upper bound of at least this size are treated as not checked.
+/// This corresponds to the largest value of an `int` parsed from an untrusted string.
+const UNCHECKED_UPPER_BOUND: i64 = i32::MAX as i64;
+
+/// The untrusted sources of a value, given by the TIDs of the source calls mapped to the source function names.
+pub(crate) type Sources<'a> = BTreeMap<&'a Tid, &'a str>;
+
+/// The source name of function inputs, see [`InputTracer::new`].
+pub(crate) const FUNCTION_INPUT: &str = "function input";
+
+/// A memory range written by a call to a parameter source,
+/// given by the memory object, the start and end offsets, the TID of the call and the name of the source.
+type TaintedRange<'a> = (AbstractIdentifier, i64, i64, &'a Tid, &'a str);
+
+/// A comparison computed by an `Assign` term, given by the TID of the term and the compared values.
+type Comparison<'a> = (&'a Tid, &'a Expression, &'a Expression);
+
+/// Returns `true` if the binary operation compares its operands.
+fn is_comparison(op: &BinOpType) -> bool {
+    is_ordering_comparison(op)
+        || matches!(
+            op,
+            BinOpType::IntEqual
+                | BinOpType::IntNotEqual
+                | BinOpType::IntCarry
+                | BinOpType::IntSCarry
+                | BinOpType::IntSBorrow
+        )
+}
+
+/// Returns `true` if the binary operation checks whether one operand is smaller than the other.
+/// Signed borrow flags are included, since x86 signed comparisons are computed from them.
+fn is_ordering_comparison(op: &BinOpType) -> bool {
+    matches!(
+        op,
+        BinOpType::IntLess
+            | BinOpType::IntSLess
+            | BinOpType::IntLessEqual
+            | BinOpType::IntSLessEqual
+            | BinOpType::IntSBorrow
+    )
+}
+
+/// Returns `true` if the expression is a nonzero constant.
+fn is_nonzero_constant(expression: &Expression) -> bool {
+    match expression {
+        Expression::Const(value) => !value.is_zero(),
+        _ => false,
+    }
+}
+
+/// The context for tracing values of a function backwards to untrusted input.
+pub(crate) struct InputTracer<'a> {
+    /// The function.
+    sub: &'a Term<Sub>,
+    /// The identifier of the stack frame of the function.
+    stack_id: AbstractIdentifier,
+    /// The stack pointer register.
+    stack_pointer_register: &'a Variable,
+    /// The def-use chains of the function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The results of the Pointer Inference analysis, if available.
+    pointer_inference: Option<&'a PointerInference<'a>>,
+    /// The return value sources, indexed by their TIDs.
+    return_value_sources: HashMap<Tid, &'a ExternSymbol>,
+    /// The memory ranges written by calls to parameter sources in the function.
+    tainted_ranges: Vec<TaintedRange<'a>>,
+    /// Whether inputs of the function are recorded as sources.
+    trace_function_inputs: bool,
+}
+
+impl<'a> InputTracer<'a> {
+    /// Create a new tracer for the given function.
+    ///
+    /// If `trace_function_inputs` is set, registers without definitions in the function
+    /// and memory cells of objects not created in the function (e.g. objects passed as parameters)
+    /// are recorded as a source named [`FUNCTION_INPUT`] with the TID of the function as key.
+    pub(crate) fn new(
+        analysis_results: &'a AnalysisResults<'a>,
+        sub: &'a Term<Sub>,
+        return_value_sources: HashMap<Tid, &'a ExternSymbol>,
+        parameter_sources: &HashMap<Tid, &'a ExternSymbol>,
+        parameter_source_config: &BTreeMap<String, ParameterSource>,
+        trace_function_inputs: bool,
+    ) -> InputTracer<'a> {
+        InputTracer {
+            sub,
+            stack_id: AbstractIdentifier::new(
+                sub.tid.clone(),
+                AbstractLocation::from_var(&analysis_results.project.stack_pointer_register)
+                    .unwrap(),
+            ),
+            stack_pointer_register: &analysis_results.project.stack_pointer_register,
+            def_use_chains: analysis_results.def_use_chains(&sub.tid).unwrap(),
+            pointer_inference: analysis_results.pointer_inference(),
+            return_value_sources,
+            tainted_ranges: get_tainted_ranges(
+                analysis_results,
+                sub,
+                parameter_sources,
+                parameter_source_config,
+            ),
+            trace_function_inputs,
+        }
+    }
+
+    /// Collect the comparisons that the value of the register `var` at its use in the term with the given TID
+    /// is computed from.
+    /// Comparisons of flags are traced further to the comparisons computing the flags.
+    fn collect_comparisons(
+        &self,
+        use_tid: &'a Tid,
+        var: &'a Variable,
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        comparisons: &mut Vec<Comparison<'a>>,
+    ) {
+        if depth == 0 || !visited.insert((use_tid, var)) {
+            return;
+        }
+        for def_tid in self.def_use_chains.defs_reaching(use_tid, var) {
+            if let Some(Definition::Def(Term {
+                tid,
+                term: Def::Assign { value, .. },
+            })) = self.def_use_chains.get_definition(def_tid)
+            {
+                match value {
+                    Expression::BinOp { op, lhs, rhs }
+                        if is_comparison(op) && lhs.bytesize() > ByteSize::new(1) =>
+                    {
+                        comparisons.push((tid, lhs, rhs))
+                    }
+                    _ => {
+                        for input in value.input_vars() {
+                            self.collect_comparisons(tid, input, depth - 1, visited, comparisons);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Trace the value of the register `var` at its use in the term with the given TID to its untrusted sources.
+    fn trace_use(
+        &self,
+        use_tid: &'a Tid,
+        var: &'a Variable,
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        if depth == 0 || !visited.insert((use_tid, var)) {
+            return;
+        }
+        let reaching_defs: Vec<&Tid> = self.def_use_chains.defs_reaching(use_tid, var).collect();
+        if reaching_defs.is_empty()
+            && self.trace_function_inputs
+            && var != self.stack_pointer_register
+        {
+            sources.insert(&self.sub.tid, FUNCTION_INPUT);
+        }
+        for def_tid in reaching_defs {
+            match self.def_use_chains.get_definition(def_tid) {
+                Some(Definition::Def(def)) => match &def.term {
+                    Def::Assign { value, .. } => {
+                        for input in value.input_vars() {
+                            self.trace_use(&def.tid, input, depth - 1, visited, sources);
+                        }
+                    }
+                    Def::Load { address, .. } => {
+                        match self.pointer_inference.and_then(|pointer_inference| {
+                            pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+                        }) {
+                            Some(cell) => {
+                                self.trace_memory_cell(&cell, depth - 1, visited, sources)
+                            }
+                            // Values loaded through pointers derived from function inputs are function inputs.
+                            None if self.trace_function_inputs => {
+                                for input in address.input_vars() {
+                                    self.trace_use(&def.tid, input, depth - 1, visited, sources);
+                                }
+                            }
+                            None => (),
+                        }
+                    }
+                    Def::Store { .. } => (),
+                },
+                Some(Definition::Call(Term {
+                    tid,
+                    term: Jmp::Call { target, .. },
+                })) => {
+                    if let Some(symbol) = self.return_value_sources.get(target) {
+                        if symbol
+                            .return_values
+                            .iter()
+                            .any(|return_value| *return_value == Arg::Register(var.clone()))
+                        {
+                            sources.insert(tid, &symbol.name);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Trace the value of the memory cell to the ranges written by parameter sources
+    /// and to the values stored to the cell in the function.
+    fn trace_memory_cell(
+        &self,
+        cell: &(AbstractIdentifier, i64),
+        depth: usize,
+        visited: &mut HashSet<(&'a Tid, &'a Variable)>,
+        sources: &mut Sources<'a>,
+    ) {
+        for (id, start, end, call_tid, source_name) in self.tainted_ranges.iter() {
+            if *id == cell.0 && *start <= cell.1 && cell.1 < *end {
+                sources.insert(call_tid, source_name);
+            }
+        }
+        if self.trace_function_inputs && !self.is_local_object(&cell.0) {
+            sources.insert(&self.sub.tid, FUNCTION_INPUT);
+        }
+        for def in self
+            .sub
+            .term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.defs.iter())
+        {
+            if let Def::Store { address, value } = &def.term {
+                if self
+                    .pointer_inference
+                    .and_then(|pointer_inference| {
+                        pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+                    })
+                    .as_ref()
+                    == Some(cell)
+                {
+                    for input in value.input_vars() {
+                        self.trace_use(&def.tid, input, depth, visited, sources);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the memory object is the stack frame of the function
+    /// or was created by a call in the function.
+    fn is_local_object(&self, id: &AbstractIdentifier) -> bool {
+        *id == self.stack_id
+            || self
+                .sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+                .any(|jmp| &jmp.tid == id.get_tid())
+    }
+
+    /// Get the untrusted sources of the memory that the value points to,
+    /// i.e. the calls to parameter sources writing to it.
+    /// If function inputs are traced, pointers to objects not created in the function are also function inputs.
+    pub(crate) fn get_pointer_sources(&self, value: &Data) -> Sources<'a> {
+        let mut sources = BTreeMap::new();
+        if let Data::Pointer(pointer) = value {
+            for (id, offset) in pointer.targets().iter() {
+                if let Ok(offset) = offset.try_to_offset() {
+                    for (range_id, start, end, call_tid, source_name) in self.tainted_ranges.iter()
+                    {
+                        if range_id == id && *start <= offset && offset < *end {
+                            sources.insert(*call_tid, *source_name);
+                        }
+                    }
+                }
+                if self.trace_function_inputs && !self.is_local_object(id) {
+                    sources.insert(&self.sub.tid, FUNCTION_INPUT);
+                }
+            }
+        }
+        sources
+    }
+
+    /// Get the untrusted sources of the value of the expression evaluated by the term with the given TID.
+    pub(crate) fn get_sources(&self, tid: &'a Tid, expression: &'a Expression) -> Sources<'a> {
+        let mut sources = BTreeMap::new();
+        let mut visited = HashSet::new();
+        for var in expression.input_vars() {
+            self.trace_use(tid, var, MAX_TRACE_DEPTH, &mut visited, &mut sources);
+        }
+        sources
+    }
+
+    /// Returns `true` if the interval domain knows an upper bound for the value of the expression
+    /// right before the term with the given TID that is small enough for the value to be checked.
+    pub(crate) fn has_checked_upper_bound(&self, tid: &Tid, expression: &Expression) -> bool {
+        let state = match self
+            .pointer_inference
+            .and_then(|pointer_inference| pointer_inference.get_state_at_tid(tid))
+        {
+            Some(state) => state,
+            None => return false,
+        };
+        match state.eval(expression).try_to_offset_interval() {
+            Ok((_, end)) => end < UNCHECKED_UPPER_BOUND,
+            Err(_) => false,
+        }
+    }
+
+    /// Get the comparisons of the condition of the conditional jump.
+    fn get_condition_comparisons(&self, jmp: &'a Term<Jmp>) -> Vec<Comparison<'a>> {
+        let mut comparisons = Vec::new();
+        if let Jmp::CBranch { condition, .. } = &jmp.term {
+            let mut visited = HashSet::new();
+            for var in condition.input_vars() {
+                self.collect_comparisons(
+                    &jmp.tid,
+                    var,
+                    MAX_TRACE_DEPTH,
+                    &mut visited,
+                    &mut comparisons,
+                );
+            }
+        }
+        comparisons
+    }
+
+    /// Get the TIDs of the sources checked by comparisons with nonzero constants
+    /// in conditional jumps of blocks outside of the given loop.
+    pub(crate) fn get_checked_sources(&self, loop_blocks: &HashSet<Tid>) -> HashSet<&'a Tid> {
+        let mut checked_sources = HashSet::new();
+        for block in self.sub.term.blocks.iter() {
+            if loop_blocks.contains(&block.tid) {
+                continue;
+            }
+            for jmp in block.term.jmps.iter() {
+                for (tid, lhs, rhs) in self.get_condition_comparisons(jmp) {
+                    let op = match self.def_use_chains.get_definition(tid) {
+                        Some(Definition::Def(Term {
+                            term:
+                                Def::Assign {
+                                    value: Expression::BinOp { op, .. },
+                                    ..
+                                },
+                            ..
+                        })) => op,
+                        _ => continue,
+                    };
+                    if !is_ordering_comparison(op) {
+                        continue;
+                    }
+                    for (value, bound) in [(lhs, rhs), (rhs, lhs)].iter() {
+                        if is_nonzero_constant(bound) {
+                            checked_sources.extend(self.get_sources(tid, value).into_keys());
+                        }
+                    }
+                }
+            }
+        }
+        checked_sources
+    }
+
+    /// Get the unchecked untrusted sources that the condition of the conditional jump leaving the loop depends on.
+    fn get_unchecked_sources(
+        &self,
+        jmp: &'a Term<Jmp>,
+        checked_sources: &HashSet<&'a Tid>,
+    ) -> Sources<'a> {
+        let mut unchecked_sources = BTreeMap::new();
+        let mut bounded_sources = HashSet::new();
+        for (tid, lhs, rhs) in self.get_condition_comparisons(jmp) {
+            for value in [lhs, rhs].iter() {
+                let sources = self.get_sources(tid, value);
+                if self.has_checked_upper_bound(tid, value) {
+                    bounded_sources.extend(sources.into_keys());
+                } else {
+                    unchecked_sources.extend(sources);
+                }
+            }
+        }
+        unchecked_sources.retain(|source_tid, _| {
+            !bounded_sources.contains(source_tid) && !checked_sources.contains(source_tid)
+        });
+        unchecked_sources
+    }
+
+    /// Get the conditional jumps leaving the loop whose conditions depend on unchecked untrusted sources,
+    /// together with these sources.
+    pub(crate) fn get_unchecked_loop_exits(
+        &self,
+        loop_blocks: &HashSet<Tid>,
+    ) -> Vec<(&'a Term<Jmp>, Sources<'a>)> {
+        let mut loop_exits = Vec::new();
+        let mut checked_sources = None;
+        for block in self
+            .sub
+            .term
+            .blocks
+            .iter()
+            .filter(|block| loop_blocks.contains(&block.tid))
+        {
+            let leaves_loop = block.term.jmps.iter().any(|jmp| match &jmp.term {
+                Jmp::Branch(target) | Jmp::CBranch { target, .. } => !loop_blocks.contains(target),
+                _ => false,
+            });
+            if !leaves_loop {
+                continue;
+            }
+            for jmp in block.term.jmps.iter() {
+                if !matches!(jmp.term, Jmp::CBranch { .. }) {
+                    continue;
+                }
+                let checked_sources =
+                    checked_sources.get_or_insert_with(|| self.get_checked_sources(loop_blocks));
+                let sources = self.get_unchecked_sources(jmp, checked_sources);
+                if !sources.is_empty() {
+                    loop_exits.push((jmp, sources));
+                }
+            }
+        }
+        loop_exits
+    }
+}
+
+/// Compute the memory ranges written by the calls to parameter sources in the function.
+fn get_tainted_ranges<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    sub: &'a Term<Sub>,
+    parameter_sources: &HashMap<Tid, &'a ExternSymbol>,
+    parameter_source_config: &BTreeMap<String, ParameterSource>,
+) -> Vec<TaintedRange<'a>> {
+    let project = analysis_results.project;
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return Vec::new(),
+    };
+    let mut tainted_ranges = Vec::new();
+    for (_, call, symbol) in get_callsites(sub, parameter_sources) {
+        let state = match pointer_inference.get_state_at_tid(&call.tid) {
+            Some(state) => state,
+            None => continue,
+        };
+        let parameter_source = &parameter_source_config[&symbol.name];
+        let eval_parameter = |index| {
+            state.eval_parameter_arg(
+                &get_variadic_parameter(project, symbol, index),
+                &project.stack_pointer_register,
+                analysis_results.runtime_memory_image,
+            )
+        };
+        let size = match parameter_source.size_parameter {
+            Some(index) => eval_parameter(index)
+                .ok()
+                .and_then(|size| size.try_to_bitvec().ok())
+                .and_then(|size| size.try_to_i64().ok())
+                .unwrap_or(i64::MAX),
+            None => u64::from(project.get_pointer_bytesize()) as i64,
+        };
+        if let Ok(Data::Pointer(pointer)) = eval_parameter(parameter_source.parameter) {
+            for (id, offset) in pointer.targets().iter() {
+                if let Ok(offset) = offset.try_to_offset() {
+                    tainted_ranges.push((
+                        id.clone(),
+                        offset,
+                        offset.saturating_add(size),
+                        &call.tid,
+                        symbol.name.as_str(),
+                    ));
+                }
+            }
+        }
+    }
+    tainted_ranges
+}
+
+/// Generate the warning for a loop whose exit condition depends on unchecked untrusted input.
+fn generate_cwe_warning(sub: &Term<Sub>, jmp: &Term<Jmp>, sources: &Sources) -> CweWarning {
+    let mut source_names: Vec<&str> = sources.values().cloned().collect();
+    source_names.sort_unstable();
+    source_names.dedup();
+    let description = format!(
+        "(Unchecked Input for Loop Condition) The loop condition at {} in {} depends on input from {} without an upper bound check.",
+        jmp.tid.address,
+        sub.term.display_name(),
+        source_names.join(", ")
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![jmp.tid.address.clone()])
+        .tids(
+            std::iter::once(jmp.tid.to_string())
+                .chain(sources.keys().map(|tid| tid.to_string()))
+                .collect(),
+        )
+        .symbols(vec![sub.term.display_name().to_string()])
+        .other(vec![std::iter::once("sources".to_string())
+            .chain(source_names.iter().map(|name| name.to_string()))
+            .collect()])
+}
+
+/// Flag loops whose exit conditions depend on untrusted input without an upper bound check.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: TaintSourceConfig = parse_config(cwe_params).unwrap();
+    let return_value_sources = config.get_return_value_sources(project);
+    let parameter_sources = config.get_parameter_sources(project);
+    if return_value_sources.is_empty() && parameter_sources.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let loops = get_loops(sub);
+        if loops.is_empty() {
+            continue;
+        }
+        let tracer = InputTracer::new(
+            analysis_results,
+            sub,
+            return_value_sources.clone(),
+            &parameter_sources,
+            &config.parameter_sources,
+            false,
+        );
+        let mut reported_jumps = HashSet::new();
+        for loop_blocks in loops.iter() {
+            for (jmp, sources) in tracer.get_unchecked_loop_exits(loop_blocks) {
+                if reported_jumps.insert(&jmp.tid) {
+                    warnings.push(generate_cwe_warning(sub, jmp, &sources));
+                }
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn less(name: &str, lhs: Expression, rhs: Expression) -> Term<Def> {
+        Def::assign(
+            name,
+            Variable::mock("CF", 1),
+            Expression::BinOp {
+                op: BinOpType::IntSLess,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        )
+    }
+
+    #[test]
+    fn loop_condition_sources() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        let mut atoi = ExternSymbol::mock();
+        atoi.tid = Tid::new("atoi");
+        atoi.name = "atoi".to_string();
+        project.program.term.extern_symbols.push(atoi.clone());
+        let rax = Variable::mock("RAX", 8);
+        let rbx = Variable::mock("RBX", 8);
+        let cf = Variable::mock("CF", 1);
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "entry",
+                Vec::new(),
+                vec![Jmp::Call {
+                    target: Tid::new("atoi"),
+                    return_: Some(Tid::new("check")),
+                }],
+            ),
+            Blk::mock_with_jmps(
+                "check",
+                vec![
+                    Def::assign(
+                        "copy",
+                        rbx.clone(),
+                        Expression::Var(rax.clone())
+                            .plus(Expression::Var(Variable::mock("RBP", 8))),
+                    ),
+                    less(
+                        "check_less",
+                        Expression::Var(rbx.clone()),
+                        Expression::const_from_i64(0),
+                    ),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("exit"),
+                        condition: Expression::Var(cf.clone()),
+                    },
+                    Jmp::Branch(Tid::new("loop")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "loop",
+                vec![
+                    Def::assign(
+                        "count",
+                        rax.clone(),
+                        Expression::Var(rax.clone()).plus_const(1),
+                    ),
+                    less(
+                        "loop_less",
+                        Expression::Var(rax.clone()),
+                        Expression::Var(rbx.clone()),
+                    ),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("loop"),
+                        condition: Expression::Var(cf.clone()),
+                    },
+                    Jmp::Branch(Tid::new("exit")),
+                ],
+            ),
+            Blk::mock_with_jmps("exit", Vec::new(), vec![Jmp::Return(Expression::Var(rax))]),
+        ];
+        let def_use_chains = DefUseChains::new(&project, &sub);
+        let tracer = InputTracer {
+            sub: &sub,
+            stack_id: AbstractIdentifier::new(
+                sub.tid.clone(),
+                AbstractLocation::from_var(&project.stack_pointer_register).unwrap(),
+            ),
+            stack_pointer_register: &project.stack_pointer_register,
+            def_use_chains: &def_use_chains,
+            pointer_inference: None,
+            return_value_sources: vec![(atoi.tid.clone(), &atoi)].into_iter().collect(),
+            tainted_ranges: Vec::new(),
+            trace_function_inputs: false,
+        };
+        let loop_blocks: HashSet<Tid> = vec![Tid::new("loop")].into_iter().collect();
+        let loop_exit = &sub.term.blocks[2].term.jmps[0];
+        // The comparison with zero is not an upper bound check.
+        let checked_sources = tracer.get_checked_sources(&loop_blocks);
+        assert!(checked_sources.is_empty());
+        let sources = tracer.get_unchecked_sources(loop_exit, &checked_sources);
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[&Tid::new("entry_jmp_0")], "atoi");
+        // A checked source is not reported.
+        let checked_sources: HashSet<&Tid> = sources.keys().cloned().collect();
+        assert!(tracer
+            .get_unchecked_sources(loop_exit, &checked_sources)
+            .is_empty());
+        // Function inputs are only recorded if requested.
+        let tracer = InputTracer {
+            trace_function_inputs: true,
+            ..tracer
+        };
+        let copy = &sub.term.blocks[1].term.defs[0];
+        let value = match &copy.term {
+            Def::Assign { value, .. } => value,
+            _ => panic!(),
+        };
+        let sources = tracer.get_sources(&copy.tid, value);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[&Tid::new("func")], FUNCTION_INPUT);
+    }
+
+    #[test]
+    fn comparison_operations() {
+        assert!(is_comparison(&BinOpType::IntEqual));
+        assert!(!is_ordering_comparison(&BinOpType::IntEqual));
+        assert!(is_ordering_comparison(&BinOpType::IntSBorrow));
+        assert!(!is_comparison(&BinOpType::IntAdd));
+        assert!(is_nonzero_constant(&Expression::const_from_i64(100)));
+        assert!(!is_nonzero_constant(&Expression::const_from_i64(0)));
+    }
+}
@@ -0,0 +1,280 @@
+//! This module implements a check for CWE-617: Reachable Assertion.
+//!
+//! Assertions terminate the program if their condition is violated.
+//! If an attacker can violate the condition of an assertion with crafted input,
+//! they can crash the program at will, e.g. to take down a server (denial of service).
+//!
+//! See <https://cwe.mitre.org/data/definitions/617.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the `assertion_functions` configured in config.json (e.g. `__assert_fail` or `abort`)
+//! we collect the conditional jumps guarding the call,
+//! i.e. the conditional jumps of the blocks from which the block containing the call is reached
+//! (directly or through a short chain of unconditional jumps).
+//! The conditions of these jumps are traced backwards to untrusted input in the same function,
+//! as in the [CWE-606 check](super::cwe_606):
+//! the return values and the memory written by the `taint_sources` configured in config.json
+//! (e.g. `atoi` or the buffer of `recv`).
+//!
+//! If a condition depends on the parameters of the function
+//! (i.e. on registers or memory objects that the function gets from its caller),
+//! it is considered untrusted if the function is `main` (whose parameters are the command line arguments)
+//! or if the call to the assertion function is reachable in the interprocedural control flow graph
+//! from a call to one of the source functions.
+//!
+//! A warning is generated for each call to an assertion function guarded by an untrusted condition.
+//!
+//! ## False Positives
+//!
+//! - Reachability in the interprocedural control flow graph does not imply that the untrusted input
+//!   flows into the parameters of the function.
+//! - Assertions whose conditions are guaranteed by earlier checks are flagged.
+//!
+//! ## False Negatives
+//!
+//! - Only the conditional jumps near the call to the assertion function are considered.
+//! - Untrusted input is only traced within a function.
+//!   Conditions depending on values returned by internal functions are not flagged.
+//! - Assertions removed by the compiler (e.g. with `NDEBUG`) cannot be detected.
+
+use super::cwe_606::{InputTracer, Sources};
+use crate::analysis::graph::{Graph, Node};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::utils::taint_sources::TaintSourceConfig;
+use crate::CweModule;
+use petgraph::graph::NodeIndex;
+use std::collections::{HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE617",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The maximal number of unconditional jumps followed backwards from a call to an assertion function
+/// when searching for the conditional jumps guarding it.
+const MAX_GUARD_DISTANCE: usize = 2;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions terminating the program when an assertion fails.
+    assertion_functions: Vec<String>,
+    /// The untrusted input sources.
+    taint_sources: TaintSourceConfig,
+}
+
+/// Get the conditional jumps guarding the given block of the function.
+///
+/// These are the conditional jumps of the blocks jumping to the given block.
+/// Blocks without conditional jumps are followed backwards up to `MAX_GUARD_DISTANCE` times.
+fn get_guarding_branches<'a>(sub: &'a Term<Sub>, block_tid: &Tid) -> Vec<&'a Term<Jmp>> {
+    let mut guards = Vec::new();
+    let mut visited = HashSet::new();
+    let mut worklist = vec![(block_tid.clone(), 0)];
+    while let Some((target, distance)) = worklist.pop() {
+        if !visited.insert(target.clone()) {
+            continue;
+        }
+        for block in sub.term.blocks.iter() {
+            if !get_intraprocedural_jump_targets(block, sub).contains(&&target) {
+                continue;
+            }
+            match block
+                .term
+                .jmps
+                .iter()
+                .find(|jmp| matches!(jmp.term, Jmp::CBranch { .. }))
+            {
+                Some(guard) => guards.push(guard),
+                None if distance < MAX_GUARD_DISTANCE => {
+                    worklist.push((block.tid.clone(), distance + 1))
+                }
+                None => (),
+            }
+        }
+    }
+    guards
+}
+
+/// Get the nodes of the control flow graph that are reachable from a call to one of the source functions.
+fn get_nodes_reachable_from_sources(
+    graph: &Graph,
+    sources: &HashMap<Tid, &ExternSymbol>,
+) -> HashSet<NodeIndex> {
+    let mut worklist: Vec<NodeIndex> = graph
+        .node_indices()
+        .filter(|node| match graph[*node] {
+            Node::BlkEnd(block, _) => block.term.jmps.iter().any(
+                |jmp| matches!(&jmp.term, Jmp::Call { target, .. } if sources.contains_key(target)),
+            ),
+            _ => false,
+        })
+        .collect();
+    let mut reachable: HashSet<NodeIndex> = worklist.iter().cloned().collect();
+    while let Some(node) = worklist.pop() {
+        for neighbor in graph.neighbors(node) {
+            if reachable.insert(neighbor) {
+                worklist.push(neighbor);
+            }
+        }
+    }
+    reachable
+}
+
+/// Generate the warning for a call to an assertion function guarded by untrusted conditions.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    guards: &[&Term<Jmp>],
+    sources: &Sources,
+) -> CweWarning {
+    let mut source_names: Vec<&str> = sources.values().cloned().collect();
+    source_names.sort_unstable();
+    source_names.dedup();
+    let description = format!(
+        "(Reachable Assertion) The call to {} at {} in {} depends on a condition derived from {}.",
+        symbol.name,
+        call.tid.address,
+        sub.term.display_name(),
+        source_names.join(", ")
+    );
+    let mut addresses = vec![call.tid.address.clone()];
+    addresses.extend(guards.iter().map(|guard| guard.tid.address.clone()));
+    let mut tids = vec![call.tid.to_string()];
+    tids.extend(guards.iter().map(|guard| guard.tid.to_string()));
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(addresses)
+        .tids(tids)
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            symbol.name.clone(),
+        ])
+        .other(vec![std::iter::once("sources".to_string())
+            .chain(source_names.iter().map(|name| name.to_string()))
+            .collect()])
+}
+
+/// Flag calls to assertion functions guarded by conditions that depend on untrusted input.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let assertion_functions = get_symbol_map(project, &config.assertion_functions);
+    if assertion_functions.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let return_value_sources = config.taint_sources.get_return_value_sources(project);
+    let parameter_sources = config.taint_sources.get_parameter_sources(project);
+    let all_sources: HashMap<Tid, &ExternSymbol> = return_value_sources
+        .iter()
+        .chain(parameter_sources.iter())
+        .map(|(tid, symbol)| (tid.clone(), *symbol))
+        .collect();
+    let graph = analysis_results.control_flow_graph;
+    let reachable_nodes = get_nodes_reachable_from_sources(graph, &all_sources);
+    let block_start_nodes: HashMap<(&Tid, &Tid), NodeIndex> = graph
+        .node_indices()
+        .filter_map(|node| match graph[node] {
+            Node::BlkStart(block, sub) => Some(((&sub.tid, &block.tid), node)),
+            _ => None,
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let assertion_calls = get_callsites(sub, &assertion_functions);
+        if assertion_calls.is_empty() {
+            continue;
+        }
+        let tracer = InputTracer::new(
+            analysis_results,
+            sub,
+            return_value_sources.clone(),
+            &parameter_sources,
+            &config.taint_sources.parameter_sources,
+            true,
+        );
+        for (block, call, symbol) in assertion_calls {
+            let inputs_are_untrusted = sub.term.name == "main"
+                || block_start_nodes
+                    .get(&(&sub.tid, &block.tid))
+                    .map(|node| reachable_nodes.contains(node))
+                    .unwrap_or(false);
+            let mut untrusted_guards = Vec::new();
+            let mut untrusted_sources = Sources::new();
+            for guard in get_guarding_branches(sub, &block.tid) {
+                let mut sources = match &guard.term {
+                    Jmp::CBranch { condition, .. } => tracer.get_sources(&guard.tid, condition),
+                    _ => continue,
+                };
+                if !inputs_are_untrusted {
+                    sources.remove(&sub.tid);
+                }
+                if !sources.is_empty() {
+                    untrusted_guards.push(guard);
+                    untrusted_sources.extend(sources);
+                }
+            }
+            if !untrusted_guards.is_empty() {
+                warnings.push(generate_cwe_warning(
+                    sub,
+                    call,
+                    symbol,
+                    &untrusted_guards,
+                    &untrusted_sources,
+                ));
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guarding_branches() {
+        // check -> (ok | fail_1 -> fail_2 -> fail_3 -> assert)
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "check",
+                vec![],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("ok"),
+                        condition: Expression::Var(Variable::mock("ZF", 1)),
+                    },
+                    Jmp::Branch(Tid::new("fail_1")),
+                ],
+            ),
+            Blk::mock_with_jmps("fail_1", vec![], vec![Jmp::Branch(Tid::new("fail_2"))]),
+            Blk::mock_with_jmps("fail_2", vec![], vec![Jmp::Branch(Tid::new("fail_3"))]),
+            Blk::mock_with_jmps("fail_3", vec![], vec![Jmp::Branch(Tid::new("assert"))]),
+            Blk::mock_with_jmps("assert", vec![], Vec::new()),
+            Blk::mock_with_jmps("ok", vec![], Vec::new()),
+        ];
+        let guards = get_guarding_branches(&sub, &Tid::new("fail_1"));
+        assert_eq!(guards.len(), 1);
+        assert_eq!(guards[0].tid, Tid::new("check_jmp_0"));
+        assert_eq!(get_guarding_branches(&sub, &Tid::new("fail_3")).len(), 1);
+        // The guard is too far away.
+        assert!(get_guarding_branches(&sub, &Tid::new("assert")).is_empty());
+        assert!(get_guarding_branches(&sub, &Tid::new("check")).is_empty());
+    }
+}
@@ -0,0 +1,1115 @@
+//! This module implements a check for CWE-667: Improper Locking.
+//!
+//! Locks that are not released on all paths, locks that are acquired again by the thread already holding them
+//! and pairs of locks that are acquired in different orders by different code paths
+//! may cause the program to deadlock.
+//!
+//! See <https://cwe.mitre.org/data/definitions/667.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Locks are identified by the pointers passed to the configured lock and unlock functions
+//! (e.g. `pthread_mutex_lock`, `sem_wait` or `pthread_spin_lock`),
+//! which are computed with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! Locks in global memory are identified by their address,
+//! all other locks by the abstract memory object containing them and their offset into it.
+//!
+//! For each function that calls one of the lock functions we run a simple intraprocedural lockset analysis
+//! that computes the locks held on all paths to each point of the function.
+//! A call to an internal function releases the locks that the callee (or one of its callees) releases.
+//! The check reports
+//! - locks that may still be held when the function returns.
+//!   The function exits are checked by the [resource lifetime engine](crate::analysis::resource_lifetime),
+//!   so that locks whose acquisition is checked to have failed on the path
+//!   (e.g. by a nonzero return value of `pthread_mutex_trylock`) are not reported.
+//!   Only locks that the function releases on some other path are reported,
+//!   since functions acquiring a lock on behalf of their caller would be reported otherwise.
+//!   Functions configured as `program_exit_functions` are not checked.
+//! - calls to the configured `lock_symbols` acquiring a lock that is already held,
+//!   either directly or in a called function.
+//!   Acquisitions by the `reentrant_lock_symbols` (e.g. `sem_wait` for counting semaphores)
+//!   and the `trylock_symbols` cannot deadlock and are not reported.
+//! - pairs of global locks that are acquired in both orders,
+//!   i.e. one lock is acquired while the other one is held and vice versa, possibly in different functions.
+//!   Two threads executing both code paths concurrently may deadlock.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - Recursive mutexes (`PTHREAD_MUTEX_RECURSIVE`) are not distinguished from other mutexes.
+//! - Inverse lock orders are also reported if the code paths cannot run concurrently
+//!   (e.g. during initialization) or if a third lock held on both paths prevents the deadlock.
+//!
+//! ## False Negatives
+//!
+//! - Locks acquired on only some of the paths reaching a point are no longer tracked after that point.
+//! - Locks whose address the Pointer Inference analysis cannot determine exactly are not tracked.
+//! - The acquisition order is only checked for locks in global memory.
+//! - Functions acquiring a lock without releasing it on any path are not reported.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::analysis::resource_lifetime::{
+    find_leaks, get_incoming_edges, PathEdge, ResourceTracker, ValueCheck,
+};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::graph_utils::CallGraph;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE667",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+/// All lock functions are mapped to the index of the parameter pointing to the lock.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions blocking until they acquire the lock, e.g. `pthread_mutex_lock`.
+    pub(crate) lock_symbols: BTreeMap<String, usize>,
+    /// Extern functions blocking until they acquire the lock
+    /// that may acquire a lock repeatedly without deadlocking, e.g. `sem_wait`.
+    #[serde(default)]
+    pub(crate) reentrant_lock_symbols: BTreeMap<String, usize>,
+    /// Extern functions acquiring the lock if possible without blocking, e.g. `pthread_mutex_trylock`.
+    /// They return zero if they acquired the lock.
+    #[serde(default)]
+    pub(crate) trylock_symbols: BTreeMap<String, usize>,
+    /// Extern functions releasing the lock, e.g. `pthread_mutex_unlock`.
+    pub(crate) unlock_symbols: BTreeMap<String, usize>,
+    /// Functions whose return terminates the program, e.g. `main`.
+    /// Locks still held at their exits are not reported.
+    #[serde(default)]
+    pub(crate) program_exit_functions: Vec<String>,
+}
+
+/// The operation of a call to a lock function on its lock.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum LockOperation {
+    /// Acquire the lock and deadlock if it is already held.
+    Lock,
+    /// Acquire the lock, which may already be held.
+    ReentrantLock,
+    /// Acquire the lock if it is not held, otherwise fail.
+    TryLock,
+    /// Release the lock.
+    Unlock,
+}
+
+/// A lock, given by its location in memory.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub(crate) enum Lock {
+    /// A lock at the given address in global memory.
+    Global(u64),
+    /// A lock in the abstract memory object with the given identifier at the given offset.
+    /// The identifier is only valid inside the function whose state it was computed from.
+    Object(AbstractIdentifier, i64),
+}
+
+/// The locks known to the analysis at some point of a function.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub(crate) struct LockState {
+    /// Registers that may contain the return values of calls to lock functions,
+    /// mapped to the TIDs of these calls.
+    registers: HashMap<Variable, BTreeSet<Tid>>,
+    /// The locks that are held on all paths to this point,
+    /// mapped to the TID of the call acquiring them.
+    held: BTreeMap<Lock, Tid>,
+}
+
+impl LockState {
+    /// Get the calls to lock functions that the value of the expression may be the return value of.
+    fn eval(&self, expression: &Expression) -> BTreeSet<Tid> {
+        match expression {
+            Expression::Var(var) => self.registers.get(var).cloned().unwrap_or_default(),
+            Expression::Cast { arg, .. } | Expression::Subpiece { arg, .. } => self.eval(arg),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Set the calls to lock functions that the register may contain the return value of.
+    fn set_register(&mut self, var: &Variable, calls: BTreeSet<Tid>) {
+        if calls.is_empty() {
+            self.registers.remove(var);
+        } else {
+            self.registers.insert(var.clone(), calls);
+        }
+    }
+
+    /// Merge the state with the state of another path to the same point.
+    /// A lock acquired at different calls on both paths keeps the smaller TID as its acquisition site.
+    /// Returns whether the state changed.
+    fn merge_with(&mut self, other: &LockState) -> bool {
+        let old_state = self.clone();
+        for (var, calls) in other.registers.iter() {
+            self.registers
+                .entry(var.clone())
+                .or_default()
+                .extend(calls.iter().cloned());
+        }
+        self.held = self
+            .held
+            .iter()
+            .filter_map(|(lock, site)| {
+                let other_site = other.held.get(lock)?;
+                Some((lock.clone(), std::cmp::min(site, other_site).clone()))
+            })
+            .collect();
+        *self != old_state
+    }
+}
+
+/// The effects of a function and its callees on global locks.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct FunctionSummary {
+    /// The addresses of the global locks acquired by calls to the `lock_symbols` or the `reentrant_lock_symbols`,
+    /// mapped to one of the acquiring calls and whether that call is reentrant.
+    acquired: BTreeMap<u64, (Tid, bool)>,
+    /// The addresses of the global locks that are released.
+    released: BTreeSet<u64>,
+    /// Whether locks outside of global memory or unknown locks are released.
+    releases_other_locks: bool,
+}
+
+impl FunctionSummary {
+    /// Add the effects of a callee to the summary.
+    /// Returns whether the summary changed.
+    fn add_callee(&mut self, callee: &FunctionSummary) -> bool {
+        let old_summary = self.clone();
+        for (address, site) in callee.acquired.iter() {
+            self.acquired
+                .entry(*address)
+                .or_insert_with(|| site.clone());
+        }
+        self.released.extend(callee.released.iter().copied());
+        self.releases_other_locks |= callee.releases_other_locks;
+        *self != old_summary
+    }
+}
+
+/// A call acquiring a lock that is already held.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct DoubleLock<'a> {
+    /// The function containing the call.
+    sub: &'a Term<Sub>,
+    /// The call that acquired the held lock.
+    held_site: Tid,
+    /// The call acquiring the lock again.
+    call: Tid,
+    /// The call to the lock function in a callee, if the lock is acquired again in a called function.
+    nested_site: Option<Tid>,
+}
+
+/// An acquisition of a global lock while another global lock is held.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct LockOrder<'a> {
+    /// The address of the held lock.
+    held_lock: u64,
+    /// The address of the acquired lock.
+    acquired_lock: u64,
+    /// The function containing the acquisition.
+    sub: &'a Term<Sub>,
+    /// The call that acquired the held lock.
+    held_site: Tid,
+    /// The call acquiring the other lock, either directly or in a called function.
+    acquisition_site: Tid,
+}
+
+/// The lockset analysis, which is also the [`ResourceTracker`] for locks.
+pub(crate) struct LockAnalysis<'a> {
+    /// The project.
+    project: &'a Project,
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+    /// The runtime memory image of the binary.
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    /// The extern lock functions with their operations and the index of their lock parameter.
+    lock_functions: HashMap<&'a Tid, (&'a ExternSymbol, LockOperation, usize)>,
+    /// The standard calling convention of the project, if known.
+    calling_convention: Option<&'a CallingConvention>,
+    /// The summaries of the internal functions.
+    summaries: HashMap<&'a Tid, FunctionSummary>,
+    /// The lock states at the start of the analyzed blocks.
+    block_start_states: HashMap<&'a Tid, LockState>,
+    /// Maps the TIDs of `Def` terms to the blocks containing them.
+    def_blocks: HashMap<&'a Tid, &'a Term<Blk>>,
+    /// The calls acquiring a lock that the same function releases on some path.
+    released_acquisition_sites: HashSet<Tid>,
+}
+
+impl<'a> LockAnalysis<'a> {
+    /// Create the analysis, compute the function summaries
+    /// and run the lockset analysis on all functions calling a lock function.
+    pub(crate) fn new(
+        analysis_results: &'a AnalysisResults<'a>,
+        pointer_inference: &'a PointerInference<'a>,
+        config: &Config,
+    ) -> LockAnalysis<'a> {
+        let project = analysis_results.project;
+        let operations = [
+            (&config.lock_symbols, LockOperation::Lock),
+            (&config.reentrant_lock_symbols, LockOperation::ReentrantLock),
+            (&config.trylock_symbols, LockOperation::TryLock),
+            (&config.unlock_symbols, LockOperation::Unlock),
+        ];
+        let mut lock_functions = HashMap::new();
+        for symbol in project.program.term.extern_symbols.iter() {
+            for (symbols, operation) in operations.iter() {
+                if let Some(index) = symbols.get(&symbol.name) {
+                    lock_functions.insert(&symbol.tid, (symbol, *operation, *index));
+                }
+            }
+        }
+        let mut analysis = LockAnalysis {
+            project,
+            pointer_inference,
+            runtime_memory_image: analysis_results.runtime_memory_image,
+            lock_functions,
+            calling_convention: project.get_standard_calling_convention(),
+            summaries: HashMap::new(),
+            block_start_states: HashMap::new(),
+            def_blocks: HashMap::new(),
+            released_acquisition_sites: HashSet::new(),
+        };
+        analysis.compute_summaries(analysis_results.call_graph());
+        for sub in project.program.term.subs.iter() {
+            let acquires_locks = sub.term.blocks.iter().any(|block| {
+                block.term.jmps.iter().any(|jmp| {
+                    matches!(analysis.get_lock_operation(jmp), Some((operation, _)) if operation != LockOperation::Unlock)
+                })
+            });
+            if acquires_locks {
+                analysis.analyze_sub(sub);
+            }
+        }
+        analysis
+    }
+
+    /// Get the lock that the parameter of the call points to, if it is known exactly.
+    fn get_lock(&self, call: &Term<Jmp>, parameter: &Arg) -> Option<Lock> {
+        let state = self.pointer_inference.get_state_at_jmp_tid(&call.tid)?;
+        let lock_pointer = state
+            .eval_parameter_arg(
+                parameter,
+                &self.project.stack_pointer_register,
+                self.runtime_memory_image,
+            )
+            .ok()?;
+        match lock_pointer {
+            Data::Pointer(pointer) => match pointer.targets().iter().collect::<Vec<_>>()[..] {
+                [(id, offset)] => {
+                    let offset = offset.try_to_bitvec().ok()?.try_to_i64().ok()?;
+                    Some(Lock::Object(id.clone(), offset))
+                }
+                _ => None,
+            },
+            Data::Value(address) => Some(Lock::Global(
+                address.try_to_bitvec().ok()?.try_to_u64().ok()?,
+            )),
+            Data::Top(_) => None,
+        }
+    }
+
+    /// If the jump is a call to a lock function, return its operation and the lock, if it is known.
+    fn get_lock_operation(&self, call: &Term<Jmp>) -> Option<(LockOperation, Option<Lock>)> {
+        let target = match &call.term {
+            Jmp::Call { target, .. } => target,
+            _ => return None,
+        };
+        let (symbol, operation, index) = self.lock_functions.get(target)?;
+        let lock = symbol
+            .parameters
+            .get(*index)
+            .and_then(|parameter| self.get_lock(call, parameter));
+        Some((*operation, lock))
+    }
+
+    /// Compute the summaries of all internal functions.
+    /// The effects of callees are added to the summaries of their callers until a fixpoint is reached.
+    fn compute_summaries(&mut self, call_graph: &CallGraph) {
+        let mut summaries = HashMap::new();
+        for sub in self.project.program.term.subs.iter() {
+            let mut summary = FunctionSummary::default();
+            for jmp in sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+            {
+                match self.get_lock_operation(jmp) {
+                    Some((LockOperation::Lock, Some(Lock::Global(address)))) => {
+                        summary
+                            .acquired
+                            .entry(address)
+                            .or_insert_with(|| (jmp.tid.clone(), false));
+                    }
+                    Some((LockOperation::ReentrantLock, Some(Lock::Global(address)))) => {
+                        summary
+                            .acquired
+                            .entry(address)
+                            .or_insert_with(|| (jmp.tid.clone(), true));
+                    }
+                    Some((LockOperation::Unlock, Some(Lock::Global(address)))) => {
+                        summary.released.insert(address);
+                    }
+                    Some((LockOperation::Unlock, _)) => summary.releases_other_locks = true,
+                    _ => (),
+                }
+            }
+            summaries.insert(&sub.tid, summary);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for sub in self.project.program.term.subs.iter() {
+                for callee in call_graph.get_callees(&sub.tid) {
+                    if callee == &sub.tid {
+                        continue;
+                    }
+                    let callee_summary = match summaries.get(callee) {
+                        Some(callee_summary) => callee_summary.clone(),
+                        None => continue,
+                    };
+                    if let Some(summary) = summaries.get_mut(&sub.tid) {
+                        changed |= summary.add_callee(&callee_summary);
+                    }
+                }
+            }
+        }
+        self.summaries = summaries;
+    }
+
+    /// Compute the lock states at the start of the blocks of the function
+    /// and the acquisition sites of locks that the function releases on some path.
+    fn analyze_sub(&mut self, sub: &'a Term<Sub>) {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, LockState> = HashMap::new();
+        let mut worklist: Vec<&Tid> = Vec::new();
+        if let Some(first_block) = sub.term.blocks.first() {
+            block_states.insert(&first_block.tid, LockState::default());
+            worklist.push(&first_block.tid);
+        }
+        let mut in_worklist: HashSet<&Tid> = worklist.iter().copied().collect();
+        while let Some(block_tid) = worklist.pop() {
+            in_worklist.remove(block_tid);
+            let block = blocks[block_tid];
+            let mut state = block_states[block_tid].clone();
+            for def in block.term.defs.iter() {
+                self.handle_def(&mut state, def);
+            }
+            for jmp in block.term.jmps.iter() {
+                let (successor, successor_state) = match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => (target, state.clone()),
+                    Jmp::Call {
+                        return_: Some(return_target),
+                        ..
+                    }
+                    | Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => {
+                        let mut state_after_call = state.clone();
+                        self.handle_call(&mut state_after_call, jmp);
+                        (return_target, state_after_call)
+                    }
+                    Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } => (return_target, state.clone()),
+                    _ => continue,
+                };
+                if !blocks.contains_key(successor) {
+                    continue;
+                }
+                let changed = match block_states.get_mut(successor) {
+                    Some(known_state) => known_state.merge_with(&successor_state),
+                    None => {
+                        block_states.insert(successor, successor_state);
+                        true
+                    }
+                };
+                if changed && in_worklist.insert(successor) {
+                    worklist.push(successor);
+                }
+            }
+        }
+        let mut released_locks = BTreeSet::new();
+        let mut acquisitions = Vec::new();
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                self.def_blocks.insert(&def.tid, block);
+            }
+            for jmp in block.term.jmps.iter() {
+                match self.get_lock_operation(jmp) {
+                    Some((LockOperation::Unlock, Some(lock))) => {
+                        released_locks.insert(lock);
+                    }
+                    Some((_, Some(lock))) => acquisitions.push((&jmp.tid, lock)),
+                    Some((_, None)) => (),
+                    None => {
+                        if let Jmp::Call { target, .. } = &jmp.term {
+                            if let Some(summary) = self.summaries.get(target) {
+                                released_locks.extend(
+                                    summary
+                                        .released
+                                        .iter()
+                                        .map(|address| Lock::Global(*address)),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (tid, lock) in acquisitions {
+            if released_locks.contains(&lock) {
+                self.released_acquisition_sites.insert(tid.clone());
+            }
+        }
+        self.block_start_states.extend(block_states);
+    }
+
+    /// Update the state according to the effects of the `Def` term.
+    fn handle_def(&self, state: &mut LockState, def: &Term<Def>) {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let calls = state.eval(value);
+                state.set_register(var, calls);
+            }
+            Def::Load { var, .. } => state.set_register(var, BTreeSet::new()),
+            Def::Store { .. } => (),
+        }
+    }
+
+    /// Update the state according to the effects of the call.
+    fn handle_call(&self, state: &mut LockState, call: &Term<Jmp>) {
+        let callee_saved_registers = self
+            .calling_convention
+            .map(|cconv| cconv.callee_saved_register.as_slice())
+            .unwrap_or_default();
+        state
+            .registers
+            .retain(|var, _| callee_saved_registers.contains(&var.name));
+        match self.get_lock_operation(call) {
+            Some((LockOperation::Unlock, Some(lock))) => {
+                state.held.remove(&lock);
+            }
+            // The released lock is unknown, so it may be any of the held locks.
+            Some((LockOperation::Unlock, None)) => state.held.clear(),
+            Some((_, lock)) => {
+                if let Some(lock) = lock {
+                    state.held.entry(lock).or_insert_with(|| call.tid.clone());
+                }
+                if let Jmp::Call { target, .. } = &call.term {
+                    let symbol = self.lock_functions[target].0;
+                    if let Some(Arg::Register(return_register)) = symbol.return_values.first() {
+                        state.set_register(
+                            return_register,
+                            vec![call.tid.clone()].into_iter().collect(),
+                        );
+                    }
+                }
+            }
+            None => {
+                if let Jmp::Call { target, .. } = &call.term {
+                    if let Some(summary) = self.summaries.get(target) {
+                        state.held.retain(|lock, _| match lock {
+                            Lock::Global(address) => !summary.released.contains(address),
+                            Lock::Object(..) => !summary.releases_other_locks,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the locks held on all paths to the start of the block.
+    /// Since locks are only acquired and released by calls, they are also held by all `Def` terms of the block
+    /// and right before its jumps.
+    /// Only locks acquired in the function containing the block are known.
+    pub(crate) fn get_held_locks(&self, block_tid: &Tid) -> impl Iterator<Item = &Lock> {
+        self.block_start_states
+            .get(block_tid)
+            .into_iter()
+            .flat_map(|state| state.held.keys())
+    }
+
+    /// Returns `true` if the function or one of its callees may release the global lock at the given address.
+    pub(crate) fn may_release_global_lock(&self, sub_tid: &Tid, address: u64) -> bool {
+        self.summaries
+            .get(sub_tid)
+            .map(|summary| summary.released.contains(&address))
+            .unwrap_or(false)
+    }
+
+    /// Get the lock state at the end of the block (but before the effects of its jumps).
+    fn get_state_at_block_end(&self, block: &Term<Blk>) -> Option<LockState> {
+        let mut state = self.block_start_states.get(&block.tid)?.clone();
+        for def in block.term.defs.iter() {
+            self.handle_def(&mut state, def);
+        }
+        Some(state)
+    }
+
+    /// Find the calls of the function acquiring held locks
+    /// and the acquisitions of global locks while other global locks are held.
+    fn find_lock_misuse(&self, sub: &'a Term<Sub>) -> (Vec<DoubleLock<'a>>, Vec<LockOrder<'a>>) {
+        let mut double_locks = Vec::new();
+        let mut lock_orders = Vec::new();
+        for block in sub.term.blocks.iter() {
+            let state = match self.get_state_at_block_end(block) {
+                Some(state) => state,
+                None => continue,
+            };
+            let held_globals: Vec<(u64, &Tid)> = state
+                .held
+                .iter()
+                .filter_map(|(lock, site)| match lock {
+                    Lock::Global(address) => Some((*address, site)),
+                    Lock::Object(..) => None,
+                })
+                .collect();
+            for jmp in block.term.jmps.iter() {
+                // The acquired global locks with the acquisition site, the nested site in a callee
+                // and whether the acquisition may deadlock if the lock is already held.
+                let mut acquisitions = Vec::new();
+                match self.get_lock_operation(jmp) {
+                    Some((LockOperation::Lock, Some(lock))) => {
+                        if let Some(held_site) = state.held.get(&lock) {
+                            double_locks.push(DoubleLock {
+                                sub,
+                                held_site: held_site.clone(),
+                                call: jmp.tid.clone(),
+                                nested_site: None,
+                            });
+                        }
+                        if let Lock::Global(address) = lock {
+                            acquisitions.push((address, None, false));
+                        }
+                    }
+                    Some((LockOperation::ReentrantLock, Some(Lock::Global(address)))) => {
+                        acquisitions.push((address, None, true))
+                    }
+                    Some(_) => (),
+                    None => {
+                        if let Jmp::Call { target, .. } = &jmp.term {
+                            if let Some(summary) = self.summaries.get(target) {
+                                for (address, (site, reentrant)) in summary.acquired.iter() {
+                                    acquisitions.push((*address, Some(site), *reentrant));
+                                }
+                            }
+                        }
+                    }
+                }
+                for (address, nested_site, reentrant) in acquisitions {
+                    for (held_address, held_site) in held_globals.iter() {
+                        if *held_address != address {
+                            lock_orders.push(LockOrder {
+                                held_lock: *held_address,
+                                acquired_lock: address,
+                                sub,
+                                held_site: (*held_site).clone(),
+                                acquisition_site: jmp.tid.clone(),
+                            });
+                        } else if nested_site.is_some() && !reentrant {
+                            double_locks.push(DoubleLock {
+                                sub,
+                                held_site: (*held_site).clone(),
+                                call: jmp.tid.clone(),
+                                nested_site: nested_site.cloned(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        (double_locks, lock_orders)
+    }
+}
+
+impl<'a> ResourceTracker<'a> for LockAnalysis<'a> {
+    type Resource = Tid;
+    type State = LockState;
+
+    /// Get the lock states at the return instruction for each incoming jump.
+    fn get_states_at_return(
+        &self,
+        sub: &'a Term<Sub>,
+        return_jmp: &'a Term<Jmp>,
+    ) -> Vec<(Option<PathEdge<'a>>, LockState)> {
+        let block = match sub
+            .term
+            .blocks
+            .iter()
+            .find(|block| block.term.jmps.contains(return_jmp))
+        {
+            Some(block) => block,
+            None => return Vec::new(),
+        };
+        let incoming_edges = get_incoming_edges(sub);
+        let mut states = Vec::new();
+        let mut add_merged_state = true;
+        if let Some(edges) = incoming_edges.get(&block.tid) {
+            add_merged_state = false;
+            for edge in edges {
+                match edge.and_then(|edge| Some((edge, self.get_state_at_block_end(edge.source)?)))
+                {
+                    Some((edge, mut state)) => {
+                        for def in block.term.defs.iter() {
+                            self.handle_def(&mut state, def);
+                        }
+                        states.push((Some(edge), state));
+                    }
+                    None => add_merged_state = true,
+                }
+            }
+        }
+        if add_merged_state || states.is_empty() {
+            if let Some(state) = self.get_state_at_block_end(block) {
+                states.push((None, state));
+            }
+        }
+        states
+    }
+
+    /// Get the held locks that the function releases on some other path.
+    fn get_leaked_resources(&self, state: &LockState) -> BTreeSet<Tid> {
+        state
+            .held
+            .values()
+            .filter(|site| self.released_acquisition_sites.contains(site))
+            .cloned()
+            .collect()
+    }
+
+    /// The acquisition of a lock failed if the return value of the lock function is checked to be nonzero.
+    fn get_failed_resources(&self, check: &ValueCheck) -> BTreeSet<Tid> {
+        if !check.is_nonzero_check() {
+            return BTreeSet::new();
+        }
+        let block = match self.def_blocks.get(check.tid) {
+            Some(block) => block,
+            None => return BTreeSet::new(),
+        };
+        let mut state = match self.block_start_states.get(&block.tid) {
+            Some(state) => state.clone(),
+            None => return BTreeSet::new(),
+        };
+        for def in block.term.defs.iter() {
+            if &def.tid == check.tid {
+                break;
+            }
+            self.handle_def(&mut state, def);
+        }
+        state.eval(&check.value)
+    }
+
+    fn get_acquisition_site(&self, site: &Tid) -> Tid {
+        site.clone()
+    }
+}
+
+/// Generate the CWE warning for a lock acquired at the given callsite
+/// that may still be held at the given return instruction.
+fn generate_unreleased_lock_warning(
+    sub: &Term<Sub>,
+    return_jmp: &Term<Jmp>,
+    acquisition_site: &Tid,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Locking) The lock acquired at {} may still be held when {} returns at {}",
+            acquisition_site.address,
+            sub.term.display_name(),
+            return_jmp.tid.address
+        ),
+    )
+    .tids(vec![
+        format!("{}", return_jmp.tid),
+        format!("{}", acquisition_site),
+    ])
+    .addresses(vec![
+        return_jmp.tid.address.clone(),
+        acquisition_site.address.clone(),
+    ])
+    .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Generate the CWE warning for a call acquiring a lock that is already held.
+fn generate_double_lock_warning(double_lock: &DoubleLock) -> CweWarning {
+    let description = match &double_lock.nested_site {
+        Some(nested_site) => format!(
+            "(Improper Locking) The lock acquired at {} is acquired again at {} by the call at {} in {} while it is still held",
+            double_lock.held_site.address, nested_site.address, double_lock.call.address, double_lock.sub.term.display_name()
+        ),
+        None => format!(
+            "(Improper Locking) The lock acquired at {} is acquired again at {} in {} while it is still held",
+            double_lock.held_site.address, double_lock.call.address, double_lock.sub.term.display_name()
+        ),
+    };
+    let mut tids = vec![double_lock.call.clone(), double_lock.held_site.clone()];
+    tids.extend(double_lock.nested_site.iter().cloned());
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .tids(tids.iter().map(|tid| format!("{}", tid)).collect())
+        .addresses(tids.iter().map(|tid| tid.address.clone()).collect())
+        .symbols(vec![double_lock.sub.term.display_name().to_string()])
+}
+
+/// Generate the CWE warning for two global locks that are acquired in both orders.
+fn generate_lock_order_warning(order: &LockOrder, inverse_order: &LockOrder) -> CweWarning {
+    let mut symbols = vec![order.sub.term.display_name().to_string()];
+    if inverse_order.sub.tid != order.sub.tid {
+        symbols.push(inverse_order.sub.term.display_name().to_string());
+    }
+    let tids = [
+        &order.held_site,
+        &order.acquisition_site,
+        &inverse_order.held_site,
+        &inverse_order.acquisition_site,
+    ];
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Locking) The locks at {:#x} and {:#x} are acquired in inverse orders at {} in {} and at {} in {} (potential deadlock)",
+            order.held_lock,
+            order.acquired_lock,
+            order.acquisition_site.address,
+            order.sub.term.display_name(),
+            inverse_order.acquisition_site.address,
+            inverse_order.sub.term.display_name()
+        ),
+    )
+    .tids(tids.iter().map(|tid| format!("{}", tid)).collect())
+    .addresses(tids.iter().map(|tid| tid.address.clone()).collect())
+    .symbols(symbols)
+    .other(vec![vec![
+        "lock_addresses".to_string(),
+        format!("{:#x}", order.held_lock),
+        format!("{:#x}", order.acquired_lock),
+    ]])
+}
+
+/// Find the pairs of global locks that are acquired in both orders.
+/// Returns the first occurrence of each of the two orders for each pair,
+/// starting with the order in which the lock with the smaller address is held.
+fn find_inverse_lock_orders<'a, 'b>(
+    lock_orders: &'b [LockOrder<'a>],
+) -> Vec<(&'b LockOrder<'a>, &'b LockOrder<'a>)> {
+    let mut first_orders: BTreeMap<(u64, u64), &LockOrder> = BTreeMap::new();
+    for order in lock_orders {
+        first_orders
+            .entry((order.held_lock, order.acquired_lock))
+            .or_insert(order);
+    }
+    first_orders
+        .iter()
+        .filter(|((held_lock, acquired_lock), _)| held_lock < acquired_lock)
+        .filter_map(|((held_lock, acquired_lock), order)| {
+            let inverse_order = first_orders.get(&(*acquired_lock, *held_lock))?;
+            Some((*order, *inverse_order))
+        })
+        .collect()
+}
+
+/// Run the CWE check.
+/// We check the function exits for held locks, the lock calls for held locks
+/// and the acquisitions of global locks for inconsistent orders.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let analysis = LockAnalysis::new(analysis_results, pointer_inference, &config);
+    let mut cwe_warnings: Vec<CweWarning> =
+        find_leaks(project, &analysis, &config.program_exit_functions)
+            .into_iter()
+            .map(|leak| {
+                generate_unreleased_lock_warning(leak.sub, leak.return_jmp, &leak.acquisition_site)
+            })
+            .collect();
+    let mut reported_double_locks = HashSet::new();
+    let mut lock_orders = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let (double_locks, sub_lock_orders) = analysis.find_lock_misuse(sub);
+        for double_lock in double_locks {
+            if reported_double_locks
+                .insert((double_lock.held_site.clone(), double_lock.call.clone()))
+            {
+                cwe_warnings.push(generate_double_lock_warning(&double_lock));
+            }
+        }
+        lock_orders.extend(sub_lock_orders);
+    }
+    for (order, inverse_order) in find_inverse_lock_orders(&lock_orders) {
+        cwe_warnings.push(generate_lock_order_warning(order, inverse_order));
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use serde_json::json;
+
+    fn mock_symbol(name: &str) -> ExternSymbol {
+        ExternSymbol {
+            tid: Tid::new(name),
+            name: name.to_string(),
+            ..ExternSymbol::mock()
+        }
+    }
+
+    /// A block calling the target with the given lock address as parameter.
+    fn call_block(name: &str, lock: Option<i64>, target: &str, return_: &str) -> Term<Blk> {
+        let defs = lock
+            .map(|lock| {
+                vec![Def::assign(
+                    &format!("{}_arg", name),
+                    Variable::mock("RDI", 8),
+                    Expression::const_from_i64(lock),
+                )]
+            })
+            .unwrap_or_default();
+        Blk::mock_with_jmps(
+            name,
+            defs,
+            vec![Jmp::Call {
+                target: Tid::new(target),
+                return_: Some(Tid::new(return_)),
+            }],
+        )
+    }
+
+    fn return_block(name: &str) -> Term<Blk> {
+        Blk::mock_with_jmps(
+            name,
+            Vec::new(),
+            vec![Jmp::Return(Expression::const_from_i64(0))],
+        )
+    }
+
+    fn mock_sub(name: &str, blocks: Vec<Term<Blk>>) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        sub.term.blocks = blocks;
+        sub
+    }
+
+    fn mock_project() -> Project {
+        let zf = Variable::mock("ZF", 1);
+        let first = mock_sub(
+            "first",
+            vec![
+                call_block("first_0", Some(0x1000), "pthread_mutex_lock", "first_1"),
+                call_block("first_1", Some(0x2000), "pthread_mutex_lock", "first_2"),
+                call_block("first_2", Some(0x2000), "pthread_mutex_unlock", "first_3"),
+                call_block("first_3", Some(0x1000), "pthread_mutex_unlock", "first_4"),
+                return_block("first_4"),
+            ],
+        );
+        let second = mock_sub(
+            "second",
+            vec![
+                call_block("second_0", Some(0x2000), "pthread_mutex_lock", "second_1"),
+                call_block("second_1", Some(0x1000), "pthread_mutex_lock", "second_2"),
+                call_block("second_2", Some(0x1000), "pthread_mutex_unlock", "second_3"),
+                Blk::mock_with_jmps(
+                    "second_3",
+                    Vec::new(),
+                    vec![
+                        Jmp::CBranch {
+                            target: Tid::new("second_5"),
+                            condition: Expression::Var(zf.clone()),
+                        },
+                        Jmp::Branch(Tid::new("second_4")),
+                    ],
+                ),
+                call_block("second_4", Some(0x2000), "pthread_mutex_unlock", "second_5"),
+                return_block("second_5"),
+            ],
+        );
+        let helper = mock_sub(
+            "helper",
+            vec![
+                call_block("helper_0", Some(0x1000), "pthread_mutex_lock", "helper_1"),
+                call_block("helper_1", Some(0x1000), "pthread_mutex_unlock", "helper_2"),
+                return_block("helper_2"),
+            ],
+        );
+        let relock = mock_sub(
+            "relock",
+            vec![
+                call_block("relock_0", Some(0x1000), "pthread_mutex_lock", "relock_1"),
+                call_block("relock_1", None, "helper", "relock_2"),
+                return_block("relock_2"),
+            ],
+        );
+        let double_lock = mock_sub(
+            "double_lock",
+            vec![
+                call_block("double_0", Some(0x1000), "pthread_mutex_lock", "double_1"),
+                call_block("double_1", Some(0x1000), "pthread_mutex_lock", "double_2"),
+                call_block("double_2", Some(0x1000), "pthread_mutex_unlock", "double_3"),
+                return_block("double_3"),
+            ],
+        );
+        let try_lock = mock_sub(
+            "try_lock",
+            vec![
+                call_block("try_0", Some(0x3000), "pthread_mutex_trylock", "try_1"),
+                Blk::mock_with_jmps(
+                    "try_1",
+                    vec![Def::assign(
+                        "try_1_compare",
+                        zf.clone(),
+                        Expression::BinOp {
+                            op: BinOpType::IntEqual,
+                            lhs: Box::new(Expression::Var(Variable::mock("RAX", 8))),
+                            rhs: Box::new(Expression::const_from_i64(0)),
+                        },
+                    )],
+                    vec![
+                        Jmp::CBranch {
+                            target: Tid::new("try_3"),
+                            condition: Expression::Var(zf),
+                        },
+                        Jmp::Branch(Tid::new("try_2")),
+                    ],
+                ),
+                return_block("try_2"),
+                call_block("try_3", Some(0x3000), "pthread_mutex_unlock", "try_4"),
+                return_block("try_4"),
+            ],
+        );
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![
+            mock_symbol("pthread_mutex_lock"),
+            mock_symbol("pthread_mutex_trylock"),
+            mock_symbol("pthread_mutex_unlock"),
+        ];
+        project.program.term.subs = vec![first, second, helper, relock, double_lock, try_lock];
+        project
+    }
+
+    fn mock_config() -> serde_json::Value {
+        json!({
+            "lock_symbols": {"pthread_mutex_lock": 0},
+            "trylock_symbols": {"pthread_mutex_trylock": 0},
+            "unlock_symbols": {"pthread_mutex_unlock": 0},
+            "program_exit_functions": ["main"]
+        })
+    }
+
+    #[test]
+    fn state_merge() {
+        let rax = Variable::mock("RAX", 8);
+        let mut state = LockState::default();
+        state.set_register(&rax, vec![Tid::new("lock_1")].into_iter().collect());
+        state.held.insert(Lock::Global(0x1000), Tid::new("lock_2"));
+        state.held.insert(Lock::Global(0x2000), Tid::new("lock_3"));
+        let mut other = LockState::default();
+        other.held.insert(Lock::Global(0x1000), Tid::new("lock_1"));
+
+        assert!(state.merge_with(&other));
+        assert!(!state.merge_with(&other));
+        assert_eq!(
+            state.held,
+            vec![(Lock::Global(0x1000), Tid::new("lock_1"))]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            state.eval(&Expression::Var(rax).subpiece(ByteSize::new(0), ByteSize::new(4))),
+            vec![Tid::new("lock_1")].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn lock_misuse() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_sub_tids = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_sub_tids);
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let config: Config = parse_config(&mock_config()).unwrap();
+        let analysis = LockAnalysis::new(
+            &analysis_results,
+            analysis_results.pointer_inference().unwrap(),
+            &config,
+        );
+        let helper_summary = &analysis.summaries[&Tid::new("helper")];
+        assert_eq!(
+            helper_summary.acquired,
+            vec![(0x1000, (Tid::new("helper_0_jmp_0"), false))]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            analysis.summaries[&Tid::new("relock")].released,
+            vec![0x1000].into_iter().collect()
+        );
+
+        let (double_locks, _) = analysis.find_lock_misuse(&project.program.term.subs[3]);
+        assert_eq!(double_locks.len(), 1);
+        assert_eq!(double_locks[0].held_site, Tid::new("relock_0_jmp_0"));
+        assert_eq!(double_locks[0].call, Tid::new("relock_1_jmp_0"));
+        assert_eq!(
+            double_locks[0].nested_site,
+            Some(Tid::new("helper_0_jmp_0"))
+        );
+        let (double_locks, lock_orders) = analysis.find_lock_misuse(&project.program.term.subs[4]);
+        assert_eq!(double_locks.len(), 1);
+        assert_eq!(double_locks[0].call, Tid::new("double_1_jmp_0"));
+        assert_eq!(double_locks[0].nested_site, None);
+        assert!(lock_orders.is_empty());
+
+        let (_, warnings) = check_cwe(&analysis_results, &mock_config());
+        assert_eq!(warnings.len(), 4);
+        // The lock is not released on the path through the conditional jump.
+        assert_eq!(
+            warnings[0].tids,
+            vec!["second_5_jmp_0".to_string(), "second_0_jmp_0".to_string()]
+        );
+        // The failed acquisition of the lock by `pthread_mutex_trylock` is not reported.
+        assert!(warnings
+            .iter()
+            .all(|warning| !warning.tids.contains(&"try_0_jmp_0".to_string())));
+        // The locks are acquired in inverse orders in `first` and `second`.
+        assert_eq!(warnings[3].symbols, vec!["first", "second"]);
+        assert_eq!(
+            warnings[3].other,
+            vec![vec!["lock_addresses", "0x1000", "0x2000"]]
+        );
+    }
+}
@@ -0,0 +1,597 @@
+//! This module implements a check for CWE-672: Operation on a Resource after Expiration or Release.
+//!
+//! After a file descriptor or handle is closed (e.g. with `close` or `fclose`), it must not be used anymore.
+//! Using a closed `FILE` stream is undefined behaviour, comparable to a use after free ([CWE-416](super::cwe_416)).
+//! A closed file descriptor number may already be reused by another `open` or `socket` call,
+//! so that reads and writes through the stale descriptor end up in an unrelated file or connection.
+//!
+//! See <https://cwe.mitre.org/data/definitions/672.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We run the intraprocedural descriptor analysis of the [CWE-775 check](super::cwe_775) on all functions.
+//! It tracks the descriptors returned by the configured `acquisition_symbols`
+//! and the values of the parameter registers of the function
+//! through registers and through the stack frame of the function,
+//! and records on which paths they are released by calls to the configured `release_symbols`.
+//!
+//! A descriptor is used if it is passed as the configured parameter to one of the `use_symbols` (e.g. `read` or `fwrite`)
+//! or if it is passed to a function of the program that uses the corresponding parameter register.
+//! The parameter registers used by each function are propagated from callees to their callers until a fixpoint is reached.
+//! A warning is generated for each call using a descriptor that may already be released on a path to the call.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - A parameter register counts as used if it is used on some path through the function,
+//!   even if the path is not taken for the given arguments.
+//!
+//! ## False Negatives
+//!
+//! - Descriptors released in other functions than the one using them are not tracked,
+//!   unless the release happens in the caller before the descriptor is passed to the callee.
+//! - Descriptors stored outside of the stack frame of a function (e.g. in a struct on the heap) are not tracked.
+//! - Only register parameters are tracked, so parameters passed on the stack (e.g. on x86) are not checked.
+
+use super::cwe_775::{DescriptorState, DescriptorTracker};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE672",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions returning a new descriptor or handle.
+    acquisition_symbols: Vec<String>,
+    /// Extern functions releasing the descriptor passed as the parameter with the given index.
+    release_symbols: BTreeMap<String, usize>,
+    /// Extern functions that may keep the descriptors passed to them as parameters.
+    #[serde(default)]
+    escaping_symbols: Vec<String>,
+    /// Extern functions using the descriptor passed as the parameter with the given index.
+    use_symbols: BTreeMap<String, usize>,
+}
+
+/// A call to a use symbol, given by the call and the called symbol.
+type UseSite<'a> = (&'a Term<Jmp>, &'a ExternSymbol);
+
+/// The parameter registers that the functions of the program use as descriptors,
+/// mapped to a call to a use symbol using the descriptor.
+type HandleParameters<'a> = HashMap<&'a Tid, BTreeMap<String, UseSite<'a>>>;
+
+/// The calls of a function together with the descriptor states right before them.
+type CallStates<'a> = Vec<(&'a Term<Jmp>, DescriptorState)>;
+
+/// Get the physical register with the given name and the pointer size of the project.
+fn get_register(project: &Project, name: &str) -> Variable {
+    Variable {
+        name: name.to_string(),
+        size: project.get_pointer_bytesize(),
+        is_temp: false,
+    }
+}
+
+/// Get the descriptors used by the call together with the calls to use symbols using them.
+///
+/// For calls to functions of the program the use sites inside the callee are returned.
+fn get_used_descriptors<'a>(
+    project: &Project,
+    tracker: &DescriptorTracker,
+    use_symbols: &HashMap<&'a Tid, (&'a ExternSymbol, usize)>,
+    handle_parameters: &HandleParameters<'a>,
+    call: &'a Term<Jmp>,
+    state: &DescriptorState,
+) -> Vec<(BTreeSet<Tid>, UseSite<'a>)> {
+    let target = match &call.term {
+        Jmp::Call { target, .. } => target,
+        _ => return Vec::new(),
+    };
+    if let Some((symbol, index)) = use_symbols.get(target) {
+        return match symbol.parameters.get(*index) {
+            Some(parameter) => vec![(
+                tracker.eval_parameter(state, call, parameter),
+                (call, *symbol),
+            )],
+            None => Vec::new(),
+        };
+    }
+    match handle_parameters.get(target) {
+        Some(parameters) => parameters
+            .iter()
+            .map(|(register, use_site)| {
+                (
+                    state.eval(&Expression::Var(get_register(project, register))),
+                    *use_site,
+                )
+            })
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Compute the parameter registers that the functions of the program use as descriptors.
+///
+/// The computation is repeated until the uses in callees are propagated to all their callers.
+fn get_handle_parameters<'a>(
+    project: &'a Project,
+    tracker: &DescriptorTracker,
+    use_symbols: &HashMap<&'a Tid, (&'a ExternSymbol, usize)>,
+    call_states: &HashMap<&'a Tid, CallStates<'a>>,
+) -> HandleParameters<'a> {
+    let parameter_registers = project
+        .get_standard_calling_convention()
+        .map(|cconv| cconv.parameter_register.as_slice())
+        .unwrap_or_default();
+    let mut handle_parameters = HandleParameters::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for sub in project.program.term.subs.iter() {
+            let mut new_parameters = Vec::new();
+            for (call, state) in call_states.get(&sub.tid).into_iter().flatten() {
+                for (descriptors, use_site) in get_used_descriptors(
+                    project,
+                    tracker,
+                    use_symbols,
+                    &handle_parameters,
+                    call,
+                    state,
+                ) {
+                    for register in parameter_registers {
+                        let parameter = DescriptorTracker::get_parameter_descriptor(sub, register);
+                        if descriptors.contains(&parameter) {
+                            new_parameters.push((register.clone(), use_site));
+                        }
+                    }
+                }
+            }
+            let known_parameters = handle_parameters.entry(&sub.tid).or_default();
+            for (register, use_site) in new_parameters {
+                if let Entry::Vacant(entry) = known_parameters.entry(register) {
+                    entry.insert(use_site);
+                    changed = true;
+                }
+            }
+        }
+    }
+    handle_parameters
+}
+
+/// Generate the CWE warning for a call using descriptors that may be released by the given calls.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    use_site: UseSite,
+    releases: &BTreeSet<Tid>,
+    callee: Option<&Term<Sub>>,
+) -> CweWarning {
+    let (use_call, symbol) = use_site;
+    let release_addresses: Vec<String> = releases.iter().map(|tid| tid.address.clone()).collect();
+    let description = match callee {
+        Some(callee) => format!(
+            "(Use of Resource after Close) The handle passed to {} at {} in {} may already be closed at {}. It is used by {} at {}",
+            callee.term.display_name(),
+            call.tid.address,
+            sub.term.display_name(),
+            release_addresses.join(", "),
+            symbol.name,
+            use_call.tid.address
+        ),
+        None => format!(
+            "(Use of Resource after Close) The handle used by {} at {} in {} may already be closed at {}",
+            symbol.name,
+            call.tid.address,
+            sub.term.display_name(),
+            release_addresses.join(", ")
+        ),
+    };
+    let mut addresses = vec![call.tid.address.clone()];
+    let mut tids = vec![call.tid.to_string()];
+    let mut symbols = vec![sub.term.display_name().to_string()];
+    if let Some(callee) = callee {
+        addresses.push(use_call.tid.address.clone());
+        tids.push(use_call.tid.to_string());
+        symbols.push(callee.term.display_name().to_string());
+    }
+    addresses.extend(release_addresses);
+    tids.extend(releases.iter().map(|tid| tid.to_string()));
+    symbols.push(symbol.name.clone());
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(addresses)
+        .tids(tids)
+        .symbols(symbols)
+}
+
+/// Find the calls using descriptors that may already be released on a path to the call.
+fn find_uses_after_release<'a>(
+    project: &'a Project,
+    tracker: &DescriptorTracker<'a>,
+    use_symbols: &HashMap<&'a Tid, (&'a ExternSymbol, usize)>,
+) -> Vec<CweWarning> {
+    let subs: HashMap<&Tid, &Term<Sub>> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub))
+        .collect();
+    let call_states: HashMap<&Tid, CallStates> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, tracker.get_states_at_calls(sub)))
+        .collect();
+    let handle_parameters = get_handle_parameters(project, tracker, use_symbols, &call_states);
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (call, state) in call_states[&sub.tid].iter() {
+            for (descriptors, use_site) in get_used_descriptors(
+                project,
+                tracker,
+                use_symbols,
+                &handle_parameters,
+                call,
+                state,
+            ) {
+                let releases: BTreeSet<Tid> = descriptors
+                    .iter()
+                    .filter_map(|descriptor| state.get_releases(descriptor))
+                    .flatten()
+                    .cloned()
+                    .collect();
+                if releases.is_empty() {
+                    continue;
+                }
+                let callee = match &call.term {
+                    Jmp::Call { target, .. } => subs.get(target).copied(),
+                    _ => None,
+                };
+                warnings.push(generate_cwe_warning(sub, call, use_site, &releases, callee));
+            }
+        }
+    }
+    warnings
+}
+
+/// Run the CWE check.
+/// For each call to a use symbol or to a function using its parameters as descriptors
+/// we check whether the passed descriptors may already be released.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let use_symbols: HashMap<&Tid, (&ExternSymbol, usize)> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter_map(|symbol| {
+            config
+                .use_symbols
+                .get(&symbol.name)
+                .map(|index| (&symbol.tid, (symbol, *index)))
+        })
+        .collect();
+    if use_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let tracker = DescriptorTracker::with_parameters(
+        project,
+        pointer_inference,
+        &config.acquisition_symbols,
+        &config.release_symbols,
+        &config.escaping_symbols,
+    );
+    let warnings = find_uses_after_release(project, &tracker, &use_symbols);
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn mock_symbol(name: &str) -> ExternSymbol {
+        ExternSymbol {
+            tid: Tid::new(name),
+            name: name.to_string(),
+            ..ExternSymbol::mock()
+        }
+    }
+
+    #[test]
+    fn use_in_callee_after_close() {
+        // `func` closes the descriptor and then passes it to `log`, which writes to it.
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let mut func = Sub::mock("func");
+        func.term.blocks = vec![
+            Blk::mock_with("entry", Vec::new(), Jmp::mock_call("open", "release")),
+            Blk::mock_with(
+                "release",
+                vec![
+                    Def::assign("save", rbp.clone(), Expression::Var(rax.clone())),
+                    Def::assign("arg_1", rdi.clone(), Expression::Var(rax)),
+                ],
+                Jmp::mock_call("close", "use"),
+            ),
+            Blk::mock_with(
+                "use",
+                vec![Def::assign("arg_2", rdi, Expression::Var(rbp))],
+                Jmp::mock_call("log", "exit"),
+            ),
+            Blk::mock_with(
+                "exit",
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ];
+        let mut log = Sub::mock("log");
+        log.term.blocks = vec![
+            Blk::mock_with("log_entry", Vec::new(), Jmp::mock_call("write", "log_exit")),
+            Blk::mock_with(
+                "log_exit",
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ];
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![
+            mock_symbol("open"),
+            mock_symbol("close"),
+            mock_symbol("write"),
+        ];
+        project.program.term.subs = vec![func, log];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let tracker = DescriptorTracker::with_parameters(
+            &project,
+            &pointer_inference,
+            &["open".to_string()],
+            &vec![("close".to_string(), 0)].into_iter().collect(),
+            &[],
+        );
+        let write_symbol = &project.program.term.extern_symbols[2];
+        let use_symbols: HashMap<&Tid, (&ExternSymbol, usize)> =
+            vec![(&write_symbol.tid, (write_symbol, 0))]
+                .into_iter()
+                .collect();
+
+        let call_states: HashMap<&Tid, CallStates> = project
+            .program
+            .term
+            .subs
+            .iter()
+            .map(|sub| (&sub.tid, tracker.get_states_at_calls(sub)))
+            .collect();
+        let handle_parameters =
+            get_handle_parameters(&project, &tracker, &use_symbols, &call_states);
+        assert!(handle_parameters[&Tid::new("func")].is_empty());
+        let log_parameters = &handle_parameters[&Tid::new("log")];
+        assert_eq!(log_parameters.len(), 1);
+        assert_eq!(log_parameters["RDI"].0.tid, Tid::new("log_entry_jmp"));
+
+        let warnings = find_uses_after_release(&project, &tracker, &use_symbols);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "use_jmp".to_string(),
+                "log_entry_jmp".to_string(),
+                "release_jmp".to_string()
+            ]
+        );
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["func".to_string(), "log".to_string(), "write".to_string()]
+        );
+    }
+
+    /// Run the check on the project and return the TIDs of the calls using closed descriptors.
+    fn reported_uses(project: &Project, config: serde_json::Value) -> Vec<String> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| warning.tids[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn uses_after_close() {
+        let rdi = Variable::mock("RDI", 8);
+        let rbp = Variable::mock("RBP", 8);
+        // The descriptor is kept in the callee-saved register RBP.
+        let save =
+            |tid: &str| Def::assign(tid, rbp.clone(), Expression::Var(Variable::mock("RAX", 8)));
+        let restore = |tid: &str| Def::assign(tid, rdi.clone(), Expression::Var(rbp.clone()));
+        let ret = || Jmp::Return(Expression::const_from_i64(0));
+        let sub = |name: &str, blocks: Vec<Term<Blk>>| {
+            let mut sub = Sub::mock(name);
+            sub.term.blocks = blocks;
+            sub
+        };
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![
+            mock_symbol("open"),
+            mock_symbol("close"),
+            mock_symbol("write"),
+        ];
+        project.program.term.subs = vec![
+            // close(fd); write(fd, ...)
+            sub(
+                "closed",
+                vec![
+                    Blk::mock_with("closed_0", Vec::new(), Jmp::mock_call("open", "closed_1")),
+                    Blk::mock_with(
+                        "closed_1",
+                        vec![save("closed_save"), restore("closed_close_arg")],
+                        Jmp::mock_call("close", "closed_2"),
+                    ),
+                    Blk::mock_with(
+                        "closed_2",
+                        vec![restore("closed_write_arg")],
+                        Jmp::mock_call("write", "closed_3"),
+                    ),
+                    Blk::mock_with("closed_3", Vec::new(), ret()),
+                ],
+            ),
+            // write(fd, ...); close(fd)
+            sub(
+                "in_use",
+                vec![
+                    Blk::mock_with("in_use_0", Vec::new(), Jmp::mock_call("open", "in_use_1")),
+                    Blk::mock_with(
+                        "in_use_1",
+                        vec![save("in_use_save"), restore("in_use_write_arg")],
+                        Jmp::mock_call("write", "in_use_2"),
+                    ),
+                    Blk::mock_with(
+                        "in_use_2",
+                        vec![restore("in_use_close_arg")],
+                        Jmp::mock_call("close", "in_use_3"),
+                    ),
+                    Blk::mock_with("in_use_3", Vec::new(), ret()),
+                ],
+            ),
+            // close(fd); fd = open(...); write(fd, ...)
+            sub(
+                "reopened",
+                vec![
+                    Blk::mock_with(
+                        "reopened_0",
+                        Vec::new(),
+                        Jmp::mock_call("open", "reopened_1"),
+                    ),
+                    Blk::mock_with(
+                        "reopened_1",
+                        vec![save("reopened_save"), restore("reopened_close_arg")],
+                        Jmp::mock_call("close", "reopened_2"),
+                    ),
+                    Blk::mock_with(
+                        "reopened_2",
+                        Vec::new(),
+                        Jmp::mock_call("open", "reopened_3"),
+                    ),
+                    Blk::mock_with(
+                        "reopened_3",
+                        vec![save("reopened_resave"), restore("reopened_write_arg")],
+                        Jmp::mock_call("write", "reopened_4"),
+                    ),
+                    Blk::mock_with("reopened_4", Vec::new(), ret()),
+                ],
+            ),
+            // if (...) close(fd); write(fd, ...)
+            sub(
+                "maybe_closed",
+                vec![
+                    Blk::mock_with(
+                        "maybe_closed_0",
+                        Vec::new(),
+                        Jmp::mock_call("open", "maybe_closed_1"),
+                    ),
+                    Blk::mock_with_jmps(
+                        "maybe_closed_1",
+                        vec![save("maybe_closed_save")],
+                        vec![
+                            Jmp::CBranch {
+                                target: Tid::new("maybe_closed_3"),
+                                condition: Expression::Var(Variable::mock("ZF", 1)),
+                            },
+                            Jmp::Branch(Tid::new("maybe_closed_2")),
+                        ],
+                    ),
+                    Blk::mock_with(
+                        "maybe_closed_2",
+                        vec![restore("maybe_closed_close_arg")],
+                        Jmp::mock_call("close", "maybe_closed_3"),
+                    ),
+                    Blk::mock_with(
+                        "maybe_closed_3",
+                        vec![restore("maybe_closed_write_arg")],
+                        Jmp::mock_call("write", "maybe_closed_4"),
+                    ),
+                    Blk::mock_with("maybe_closed_4", Vec::new(), ret()),
+                ],
+            ),
+        ];
+
+        let config = json!({
+            "acquisition_symbols": ["open"],
+            "release_symbols": {"close": 0},
+            "use_symbols": {"write": 0}
+        });
+        assert_eq!(
+            reported_uses(&project, config),
+            vec!["closed_2_jmp", "maybe_closed_3_jmp"]
+        );
+        // Use symbols without the configured parameter are ignored.
+        let config = json!({
+            "acquisition_symbols": ["open"],
+            "release_symbols": {"close": 0},
+            "use_symbols": {"write": 2}
+        });
+        assert!(reported_uses(&project, config).is_empty());
+        // Without release symbols no descriptor is ever closed.
+        let config = json!({
+            "acquisition_symbols": ["open"],
+            "release_symbols": {},
+            "use_symbols": {"write": 0}
+        });
+        assert!(reported_uses(&project, config).is_empty());
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: Config = parse_config(&json!({
+            "acquisition_symbols": ["open"],
+            "release_symbols": {"close": 0},
+            "use_symbols": {"write": 0}
+        }))
+        .unwrap();
+        assert!(config.escaping_symbols.is_empty());
+        assert!(parse_config::<Config>(&json!({
+            "acquisition_symbols": ["open"],
+            "release_symbols": {"close": 0}
+        }))
+        .is_err());
+    }
+}
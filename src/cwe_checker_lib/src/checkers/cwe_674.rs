@@ -0,0 +1,402 @@
+//! This module implements a check for CWE-674: Uncontrolled Recursion.
+//!
+//! Each recursive call consumes stack memory.
+//! If the recursion depth is not bounded, an attacker may be able to exhaust the stack and crash the program,
+//! e.g. by passing deeply nested input to a recursive descent parser.
+//!
+//! See <https://cwe.mitre.org/data/definitions/674.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We compute the sets of mutually recursive functions, i.e. the cycles of the call graph of the program.
+//! The recursion of such a set of functions is considered to be bounded
+//! if there is a parameter register that serves as a recursion depth counter:
+//! At each recursive call the parameter has to be passed as its value at the start of the calling function
+//! plus a constant, where the constants all have the same sign and at least one of them is not zero.
+//! Furthermore, the parameter has to be compared in one of the functions (the base case of the recursion).
+//! The values of the parameters are computed symbolically, as in the [CWE-131 check](super::cwe_131).
+//!
+//! A warning is generated for each set of recursive functions without such a depth counter.
+//! If one of the functions or one of the functions called by them calls one of the `input_functions`
+//! configured in config.json, the recursion depth is likely to depend on external input
+//! and the warning mentions these functions.
+//!
+//! ## False Positives
+//!
+//! - Recursion over finite data structures (e.g. trees) is bounded by the size of the data structure
+//!   and is flagged nonetheless.
+//! - Recursion depths bounded by global variables or by fields of structs are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Recursion through indirect calls (e.g. function pointers) is not detected.
+//! - Any comparison of the depth counter counts as a bound, even if the bound is too large for the stack.
+
+use super::cwe_131::{SizeResolver, SymbolicSize, SymbolicTerm};
+use crate::analysis::pointer_inference::PointerInference;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE674",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions reading external input.
+    input_functions: Vec<String>,
+}
+
+/// A call from a function of a set of recursive functions to a function of the same set,
+/// together with the calling function.
+type RecursiveCall<'a> = (&'a Term<Sub>, &'a Term<Jmp>);
+
+/// Get the constant that is added to the parameter register at the start of the calling function
+/// to compute the value of the parameter register at the call.
+///
+/// Returns `None` if the value of the parameter at the call is not of this form.
+fn get_parameter_step(
+    resolver: &SizeResolver,
+    call: &Term<Jmp>,
+    parameter: &Variable,
+) -> Option<i64> {
+    match resolver.resolve_register(&call.tid, parameter, 0)? {
+        SymbolicSize {
+            term: Some(SymbolicTerm::Register(None, var)),
+            constant,
+        } if var.name == parameter.name => Some(constant),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the value of the parameter register at the start of the function
+/// is compared in the function.
+fn is_compared(resolver: &SizeResolver, sub: &Term<Sub>, parameter: &Variable) -> bool {
+    sub.term
+        .blocks
+        .iter()
+        .flat_map(|block| block.term.defs.iter())
+        .any(|def| match &def.term {
+            Def::Assign {
+                value:
+                    Expression::BinOp {
+                        op:
+                            BinOpType::IntEqual
+                            | BinOpType::IntNotEqual
+                            | BinOpType::IntLess
+                            | BinOpType::IntSLess
+                            | BinOpType::IntLessEqual
+                            | BinOpType::IntSLessEqual
+                            | BinOpType::IntSBorrow,
+                        lhs,
+                        rhs,
+                    },
+                ..
+            } => [lhs, rhs].iter().any(|operand| {
+                matches!(
+                    resolver.resolve_expression(&def.tid, operand, 0),
+                    Some(SymbolicSize {
+                        term: Some(SymbolicTerm::Register(None, var)),
+                        ..
+                    }) if var.name == parameter.name
+                )
+            }),
+            _ => false,
+        })
+}
+
+/// Find a parameter register bounding the recursion depth of the recursive functions,
+/// i.e. a parameter that is changed monotonically by the recursive calls and compared in one of the functions.
+fn find_depth_parameter<'b>(
+    resolvers: &HashMap<&Tid, SizeResolver>,
+    subs: &[&Term<Sub>],
+    recursive_calls: &[RecursiveCall],
+    parameters: &'b [Variable],
+) -> Option<&'b Variable> {
+    parameters.iter().find(|parameter| {
+        let steps: Option<Vec<i64>> = recursive_calls
+            .iter()
+            .map(|(sub, call)| get_parameter_step(&resolvers[&sub.tid], call, parameter))
+            .collect();
+        let is_monotonic = match steps {
+            Some(steps) => {
+                (steps.iter().all(|step| *step >= 0) || steps.iter().all(|step| *step <= 0))
+                    && steps.iter().any(|step| *step != 0)
+            }
+            None => false,
+        };
+        is_monotonic
+            && subs
+                .iter()
+                .any(|sub| is_compared(&resolvers[&sub.tid], sub, parameter))
+    })
+}
+
+/// Get the input functions called by the given functions or by the functions called by them.
+fn get_called_input_functions<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    subs: &[&'a Term<Sub>],
+    input_symbols: &HashMap<Tid, &'a ExternSymbol>,
+) -> BTreeSet<&'a str> {
+    let call_graph = analysis_results.call_graph();
+    let subs_by_tid: HashMap<&Tid, &Term<Sub>> = analysis_results
+        .project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub))
+        .collect();
+    let mut visited: HashSet<&Tid> = HashSet::new();
+    let mut worklist: Vec<&Tid> = subs.iter().map(|sub| &sub.tid).collect();
+    let mut input_functions = BTreeSet::new();
+    while let Some(sub_tid) = worklist.pop() {
+        if !visited.insert(sub_tid) {
+            continue;
+        }
+        if let Some(sub) = subs_by_tid.get(sub_tid) {
+            for (_, _, symbol) in get_callsites(sub, input_symbols) {
+                input_functions.insert(symbol.name.as_str());
+            }
+        }
+        worklist.extend(call_graph.get_callees(sub_tid));
+    }
+    input_functions
+}
+
+/// Generate the warning for a set of recursive functions without a bound on the recursion depth.
+fn generate_cwe_warning(
+    subs: &[&Term<Sub>],
+    recursive_calls: &[RecursiveCall],
+    input_functions: &BTreeSet<&str>,
+) -> CweWarning {
+    let mut sub_names: Vec<String> = subs
+        .iter()
+        .map(|sub| sub.term.display_name().to_string())
+        .collect();
+    sub_names.sort();
+    let mut description = if sub_names.len() == 1 {
+        format!(
+            "(Uncontrolled Recursion) The function {} calls itself without a parameter bounding the recursion depth.",
+            sub_names[0]
+        )
+    } else {
+        format!(
+            "(Uncontrolled Recursion) The functions {} call each other recursively without a parameter bounding the recursion depth.",
+            sub_names.join(", ")
+        )
+    };
+    if !input_functions.is_empty() {
+        description.push_str(&format!(
+            " The recursion depth may depend on input read by {}.",
+            input_functions
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    let mut warning = CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(
+            recursive_calls
+                .iter()
+                .map(|(_, call)| call.tid.address.clone())
+                .collect(),
+        )
+        .tids(
+            recursive_calls
+                .iter()
+                .map(|(_, call)| call.tid.to_string())
+                .collect(),
+        )
+        .symbols(sub_names);
+    if !input_functions.is_empty() {
+        warning = warning.other(vec![std::iter::once("input_functions".to_string())
+            .chain(input_functions.iter().map(|name| name.to_string()))
+            .collect()]);
+    }
+    warning
+}
+
+/// Get the calls between the given recursive functions.
+fn get_recursive_calls<'a>(
+    subs: &[&'a Term<Sub>],
+    component: &BTreeSet<Tid>,
+) -> Vec<RecursiveCall<'a>> {
+    subs.iter()
+        .flat_map(|sub| {
+            sub.term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+                .filter(move |jmp| matches!(&jmp.term, Jmp::Call { target, .. } if component.contains(target)))
+                .map(move |jmp| (*sub, jmp))
+        })
+        .collect()
+}
+
+/// Get the parameter registers of the standard calling convention.
+fn get_parameter_registers(project: &Project) -> Vec<Variable> {
+    let pointer_size = project.get_pointer_bytesize();
+    project
+        .get_standard_calling_convention()
+        .map(|cconv| {
+            cconv
+                .parameter_register
+                .iter()
+                .map(|name| Variable {
+                    name: name.clone(),
+                    size: pointer_size,
+                    is_temp: false,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Flag sets of recursive functions without a parameter bounding the recursion depth.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference: &PointerInference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let input_symbols = get_symbol_map(project, &config.input_functions);
+    let parameters = get_parameter_registers(project);
+    let subs_by_tid: HashMap<&Tid, &Term<Sub>> = project
+        .program
+        .term
+        .subs
+        .iter()
+        .map(|sub| (&sub.tid, sub))
+        .collect();
+
+    let mut warnings = Vec::new();
+    for component in analysis_results.call_graph().get_recursive_components() {
+        let subs: Vec<&Term<Sub>> = component
+            .iter()
+            .filter_map(|tid| subs_by_tid.get(tid).copied())
+            .collect();
+        let resolvers: HashMap<&Tid, SizeResolver> = subs
+            .iter()
+            .filter_map(|sub| {
+                let def_use_chains = analysis_results.def_use_chains(&sub.tid)?;
+                Some((
+                    &sub.tid,
+                    SizeResolver::new(sub, def_use_chains, pointer_inference),
+                ))
+            })
+            .collect();
+        if resolvers.len() < subs.len() {
+            continue;
+        }
+        let recursive_calls = get_recursive_calls(&subs, &component);
+        if find_depth_parameter(&resolvers, &subs, &recursive_calls, &parameters).is_none() {
+            let input_functions =
+                get_called_input_functions(analysis_results, &subs, &input_symbols);
+            warnings.push(generate_cwe_warning(
+                &subs,
+                &recursive_calls,
+                &input_functions,
+            ));
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::def_use::DefUseChains;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    fn def(name: &str, var: &str, value: Expression) -> Term<Def> {
+        Term {
+            tid: Tid::new(name),
+            term: Def::Assign {
+                var: Variable::mock(var, 8),
+                value,
+            },
+        }
+    }
+
+    /// A function calling itself with `RDI + step` as first parameter
+    /// and comparing `RDI` with zero if `compare` is set.
+    fn recursive_sub(step: i64, compare: bool) -> Term<Sub> {
+        let rdi = Expression::Var(Variable::mock("RDI", 8));
+        let mut block = Blk::mock();
+        block.tid = Tid::new("block");
+        if compare {
+            block.term.defs.push(def(
+                "compare",
+                "ZF",
+                Expression::BinOp {
+                    op: BinOpType::IntEqual,
+                    lhs: Box::new(rdi.clone()),
+                    rhs: Box::new(Expression::const_from_i64(0)),
+                },
+            ));
+        }
+        block.term.defs.push(def(
+            "step",
+            "RDI",
+            rdi.plus(Expression::const_from_i64(step)),
+        ));
+        block.term.jmps.push(Term {
+            tid: Tid::new("recursive_call"),
+            term: Jmp::Call {
+                target: Tid::new("func"),
+                return_: None,
+            },
+        });
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![block];
+        sub
+    }
+
+    #[test]
+    fn depth_parameters() {
+        let parameters = vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)];
+        let component: BTreeSet<Tid> = vec![Tid::new("func")].into_iter().collect();
+        for (step, compare, is_bounded) in
+            [(-1, true, true), (0, true, false), (-1, false, false)].iter()
+        {
+            let mut project = Project::mock_empty();
+            project.calling_conventions = vec![CallingConvention::mock()];
+            project.program.term.subs = vec![recursive_sub(*step, *compare)];
+            let runtime_memory_image = RuntimeMemoryImage::mock();
+            let graph = get_program_cfg(&project.program, HashSet::new());
+            let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+            let sub = &project.program.term.subs[0];
+            let def_use_chains = DefUseChains::new(&project, sub);
+            let resolvers: HashMap<&Tid, SizeResolver> = vec![(
+                &sub.tid,
+                SizeResolver::new(sub, &def_use_chains, &pointer_inference),
+            )]
+            .into_iter()
+            .collect();
+            let recursive_calls = get_recursive_calls(&[sub], &component);
+            assert_eq!(recursive_calls.len(), 1);
+            let depth_parameter =
+                find_depth_parameter(&resolvers, &[sub], &recursive_calls, &parameters);
+            assert_eq!(depth_parameter.is_some(), *is_bounded);
+        }
+    }
+}
@@ -19,6 +19,7 @@ False Negatives
 * None known
 */
 use crate::prelude::*;
+use crate::utils::parse_config;
 use std::collections::HashMap;
 
 use crate::{
@@ -37,10 +38,13 @@ pub static CWE_MODULE: crate::CweModule = crate::CweModule {
     name: "CWE676",
     version: VERSION,
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// struct containing dangerous symbols from config.json
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     symbols: Vec<String>,
 }
@@ -114,7 +118,7 @@ pub fn check_cwe(
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let prog: &Term<Program> = &project.program;
     let subfunctions: &Vec<Term<Sub>> = &prog.term.subs;
     let external_symbols: &Vec<ExternSymbol> = &prog.term.extern_symbols;
@@ -0,0 +1,454 @@
+//! This module implements a check for CWE-685: Function Call With Incorrect Number of Arguments.
+//!
+//! Variadic functions like `printf` cannot check how many arguments they were called with.
+//! They read as many arguments as the conversion specifiers of the format string demand.
+//! If the caller passes fewer arguments, the function reads whatever values happen to be
+//! in the corresponding parameter registers or stack slots, e.g. stale values of other computations
+//! or saved data of the calling function, which may leak sensitive information.
+//! If a missing argument is used as a pointer (e.g. for `%s` or `%n`),
+//! the function may even read from or write to arbitrary memory.
+//!
+//! See <https://cwe.mitre.org/data/definitions/685.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a function configured in the `format_string_index` map of config.json
+//! we use the [Pointer Inference analysis](crate::analysis::pointer_inference) to determine the format string.
+//! If it is a constant string in read-only memory, we parse its conversion specifiers
+//! and compute the parameter slots of all arguments the format string requires.
+//! Arguments that are larger than the pointer size (e.g. `%lld` on 32-bit architectures)
+//! occupy two slots, which are aligned to an even slot index on 32-bit architectures other than x86.
+//!
+//! Then we check whether each required slot is set by the caller:
+//! - A register slot is set if a definition of the register in the calling function
+//!   reaches the call along the [def-use chains](crate::analysis::def_use) of the function.
+//!   Registers only defined by preceding calls contain garbage.
+//!   If no definition of the register reaches the call and the call can be reached from the function entry
+//!   without passing other calls, the register may contain a forwarded parameter of the calling function
+//!   and is also considered to be set.
+//! - A stack slot is set if a store instruction of the calling function writes to it.
+//!
+//! A warning is generated for the first argument whose slots are not set.
+//!
+//! ## False Positives
+//!
+//! - Arguments set in registers by other means than assignments or loads (e.g. by a called function
+//!   that is known to leave the register unchanged) are reported as missing.
+//! - The calling conventions of the architectures are only approximated.
+//!   Deviations (e.g. for arguments passed in register pairs) may lead to wrongly computed parameter slots.
+//!
+//! ## False Negatives
+//!
+//! - Only constant format strings in read-only memory are checked.
+//! - Only the arguments before the first floating point argument are checked,
+//!   since floating point arguments may be passed in separate registers.
+//! - Stack slots written to on another path than the one leading to the call are considered to be set.
+//! - Values of parameter registers that are defined for other purposes are indistinguishable from arguments.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::analysis::string_abstraction::get_constant_string;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::format_string::{parse_format_string, FormatSpecifier};
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE685",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions taking a format string, mapped to the index of the format string parameter.
+    format_string_index: BTreeMap<String, usize>,
+}
+
+/// A memory store to the stack frame of a function,
+/// given by the offset of the written bytes relative to the stack frame and their number.
+type StackStore = (i64, u64);
+
+/// Returns `true` if the conversion character denotes a floating point argument.
+fn is_float_conversion(conversion: char) -> bool {
+    "aAeEfFgG".contains(conversion)
+}
+
+/// Get the size in bytes of the argument consumed by the specifier.
+/// Arguments without a specifier are width or precision arguments given by `*`, i.e. of type `int`.
+fn get_argument_size(specifier: Option<&FormatSpecifier>, pointer_size: ByteSize) -> u64 {
+    match specifier {
+        Some(specifier) if matches!(specifier.length_modifier.as_str(), "ll" | "j" | "q" | "L") => {
+            std::cmp::max(8, u64::from(pointer_size))
+        }
+        _ => u64::from(pointer_size),
+    }
+}
+
+/// Compute the parameter slots of the arguments required by the given conversion specifiers.
+///
+/// For each argument (in order) the indices of the parameter slots occupied by it are returned,
+/// where the first argument after the format string is located at slot `first_index`.
+/// The computation stops at the first floating point argument.
+/// If `align_pairs` is set, arguments occupying two slots start at an even slot index.
+fn get_argument_slots(
+    specifiers: &[FormatSpecifier],
+    pointer_size: ByteSize,
+    first_index: usize,
+    align_pairs: bool,
+) -> Vec<Vec<usize>> {
+    let argument_count = match specifiers.iter().map(|spec| spec.argument_index).max() {
+        Some(max_index) => max_index + 1,
+        None => return Vec::new(),
+    };
+    let mut argument_slots = Vec::new();
+    let mut next_slot = first_index;
+    for argument_index in 0..argument_count {
+        let specifier = specifiers
+            .iter()
+            .find(|spec| spec.argument_index == argument_index);
+        if matches!(specifier, Some(spec) if is_float_conversion(spec.conversion)) {
+            break;
+        }
+        if get_argument_size(specifier, pointer_size) > u64::from(pointer_size) {
+            if align_pairs && next_slot % 2 == 1 {
+                next_slot += 1;
+            }
+            argument_slots.push(vec![next_slot, next_slot + 1]);
+            next_slot += 2;
+        } else {
+            argument_slots.push(vec![next_slot]);
+            next_slot += 1;
+        }
+    }
+    argument_slots
+}
+
+/// Get the blocks of the function that are reachable from the function entry without passing a call.
+fn get_call_free_blocks(sub: &Term<Sub>) -> HashSet<&Tid> {
+    let mut reached = HashSet::new();
+    let mut worklist: Vec<&Term<Blk>> = sub.term.blocks.first().into_iter().collect();
+    while let Some(block) = worklist.pop() {
+        if !reached.insert(&block.tid) {
+            continue;
+        }
+        let contains_call = block.term.jmps.iter().any(|jmp| {
+            matches!(
+                jmp.term,
+                Jmp::Call { .. } | Jmp::CallInd { .. } | Jmp::CallOther { .. }
+            )
+        });
+        if contains_call {
+            continue;
+        }
+        for target in get_intraprocedural_jump_targets(block, sub) {
+            if let Some(target_block) = sub.term.blocks.iter().find(|blk| blk.tid == *target) {
+                worklist.push(target_block);
+            }
+        }
+    }
+    reached
+}
+
+/// Collect the stores of the function that write to its own stack frame.
+fn get_stack_stores(pointer_inference: &PointerInference, sub: &Term<Sub>) -> Vec<StackStore> {
+    let mut stack_stores = Vec::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            if let Def::Store { address, value } = &def.term {
+                if let Some(state) = pointer_inference.get_state_at_tid(&def.tid) {
+                    if let Some(offset) = get_stack_offset(&state, &state.eval(address)) {
+                        stack_stores.push((offset, u64::from(value.bytesize())));
+                    }
+                }
+            }
+        }
+    }
+    stack_stores
+}
+
+/// Get the exact offset into the stack frame of the current function that the pointer points to.
+fn get_stack_offset(state: &State, pointer: &Data) -> Option<i64> {
+    match pointer {
+        Data::Pointer(pointer) if pointer.targets().len() == 1 => {
+            let (id, offset) = pointer.targets().iter().next().unwrap();
+            if *id == state.stack_id {
+                offset.try_to_offset().ok()
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Check whether the register is set by the calling function before the call.
+fn is_register_set(
+    def_use_chains: &DefUseChains,
+    call_free_blocks: &HashSet<&Tid>,
+    block: &Term<Blk>,
+    call: &Term<Jmp>,
+    var: &Variable,
+) -> bool {
+    let mut definitions = def_use_chains.defs_reaching_call(&call.tid, var).peekable();
+    if definitions.peek().is_none() {
+        // The register may contain a parameter of the calling function.
+        return call_free_blocks.contains(&block.tid);
+    }
+    definitions.any(|def_tid| {
+        matches!(
+            def_use_chains.get_definition(def_tid),
+            Some(Definition::Def(_))
+        )
+    })
+}
+
+/// Check whether the stack slot at the given offset relative to the stack pointer at the call
+/// is written to by the calling function.
+/// Stack slots whose location is not exactly known are assumed to be set.
+fn is_stack_slot_set(
+    project: &Project,
+    state: &State,
+    stack_stores: &[StackStore],
+    offset: i64,
+    size: ByteSize,
+) -> bool {
+    let address =
+        state.eval(&Expression::Var(project.stack_pointer_register.clone()).plus_const(offset));
+    let slot_offset = match get_stack_offset(state, &address) {
+        Some(slot_offset) => slot_offset,
+        None => return true,
+    };
+    stack_stores.iter().any(|(store_offset, store_size)| {
+        *store_offset <= slot_offset
+            && slot_offset + u64::from(size) as i64 <= store_offset + *store_size as i64
+    })
+}
+
+/// The information about the calling function needed to check whether parameter slots are set.
+struct CallingFunction<'a> {
+    /// The def-use chains of the function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The blocks reachable from the function entry without passing a call.
+    call_free_blocks: HashSet<&'a Tid>,
+    /// The stores of the function to its own stack frame.
+    stack_stores: Vec<StackStore>,
+}
+
+/// Check the call to a format string function.
+/// Returns the index of the first required argument that is not passed by the caller
+/// and the number of arguments required by the format string.
+fn check_call(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    caller: &CallingFunction,
+    state: &State,
+    (block, call, symbol): (&Term<Blk>, &Term<Jmp>, &ExternSymbol),
+    format_string_index: usize,
+) -> Option<(usize, usize)> {
+    let format_string = state
+        .eval_parameter_arg(
+            &get_variadic_parameter(project, symbol, format_string_index),
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?;
+    let specifiers =
+        parse_format_string(get_constant_string(&format_string, runtime_memory_image)?);
+    let pointer_size = project.get_pointer_bytesize();
+    let align_pairs = u64::from(pointer_size) == 4
+        && !matches!(project.cpu_architecture.as_str(), "x86" | "x86_32");
+    let argument_slots = get_argument_slots(
+        &specifiers,
+        pointer_size,
+        format_string_index + 1,
+        align_pairs,
+    );
+    let is_slot_set = |slot: usize| match get_variadic_parameter(project, symbol, slot) {
+        Arg::Register(var) => is_register_set(
+            caller.def_use_chains,
+            &caller.call_free_blocks,
+            block,
+            call,
+            &var,
+        ),
+        Arg::Stack { offset, size } => {
+            is_stack_slot_set(project, state, &caller.stack_stores, offset, size)
+        }
+    };
+    let missing_argument = argument_slots
+        .iter()
+        .position(|slots| !slots.iter().all(|slot| is_slot_set(*slot)))?;
+    Some((missing_argument, argument_slots.len()))
+}
+
+/// Generate the CWE warning for a call with a missing argument.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    missing_argument: usize,
+    required_arguments: usize,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Function Call With Incorrect Number of Arguments) The format string of the call to {} at {} in {} requires at least {} arguments, but argument {} is not passed",
+            symbol.name,
+            call.tid.address,
+            sub.term.display_name(),
+            required_arguments,
+            missing_argument + 1
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![vec![
+        "missing_argument".to_string(),
+        (missing_argument + 1).to_string(),
+    ]])
+}
+
+/// Run the CWE check.
+/// For each call to a format string function with a constant format string
+/// we check whether all arguments required by the format string are passed.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbols: Vec<String> = config.format_string_index.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbols);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let callsites = get_callsites(sub, &symbol_map);
+        if callsites.is_empty() {
+            continue;
+        }
+        let caller = match analysis_results.def_use_chains(&sub.tid) {
+            Some(def_use_chains) => CallingFunction {
+                def_use_chains,
+                call_free_blocks: get_call_free_blocks(sub),
+                stack_stores: get_stack_stores(pointer_inference, sub),
+            },
+            None => continue,
+        };
+        for (block, call, symbol) in callsites {
+            if let Some(state) = pointer_inference.get_state_at_jmp_tid(&call.tid) {
+                if let Some((missing_argument, required_arguments)) = check_call(
+                    project,
+                    analysis_results.runtime_memory_image,
+                    &caller,
+                    state,
+                    (block, call, symbol),
+                    config.format_string_index[&symbol.name],
+                ) {
+                    cwe_warnings.push(generate_cwe_warning(
+                        sub,
+                        call,
+                        symbol,
+                        missing_argument,
+                        required_arguments,
+                    ));
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argument_slots() {
+        let slots = |format_string: &str, pointer_size: u64, align_pairs: bool| {
+            get_argument_slots(
+                &parse_format_string(format_string),
+                ByteSize::new(pointer_size),
+                1,
+                align_pairs,
+            )
+        };
+        assert_eq!(slots("%d %s\n", 8, false), vec![vec![1], vec![2]]);
+        assert_eq!(slots("%*d %lld", 8, false), vec![vec![1], vec![2], vec![3]]);
+        assert_eq!(slots("%d %lld", 4, false), vec![vec![1], vec![2, 3]]);
+        assert_eq!(slots("%d %lld", 4, true), vec![vec![1], vec![2, 3]]);
+        assert_eq!(slots("%lld", 4, true), vec![vec![2, 3]]);
+        assert_eq!(slots("%2$s %1$d", 8, false), vec![vec![1], vec![2]]);
+        // Floating point arguments and all following arguments are not checked.
+        assert_eq!(slots("%d %f %d", 8, false), vec![vec![1]]);
+        assert!(slots("no arguments", 8, false).is_empty());
+    }
+
+    #[test]
+    fn register_arguments() {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock();
+        cconv.parameter_register = vec!["RDI".to_string(), "RSI".to_string(), "RDX".to_string()];
+        project.calling_conventions = vec![cconv];
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let rdi = Variable::mock("RDI", 8);
+        let rsi = Variable::mock("RSI", 8);
+        let rdx = Variable::mock("RDX", 8);
+        let mock_block = |name: &str, defs: Vec<Term<Def>>, return_: Option<Tid>| Term {
+            tid: Tid::new(name),
+            term: Blk {
+                defs,
+                jmps: vec![Term {
+                    tid: Tid::new(format!("{}_call", name)),
+                    term: Jmp::Call {
+                        target: Tid::new("mock_symbol"),
+                        return_,
+                    },
+                }],
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            mock_block(
+                "first",
+                vec![
+                    Def::assign("def_rdi", rdi.clone(), Expression::const_from_i64(1)),
+                    Def::assign("def_rsi", rsi.clone(), Expression::const_from_i64(2)),
+                ],
+                Some(Tid::new("second")),
+            ),
+            mock_block(
+                "second",
+                vec![Def::assign("def_rdi_2", rdi, Expression::const_from_i64(3))],
+                None,
+            ),
+        ];
+        let chains = DefUseChains::new(&project, &sub);
+        let call_free_blocks = get_call_free_blocks(&sub);
+        let is_set = |block_index: usize, var: &Variable| {
+            let block = &sub.term.blocks[block_index];
+            is_register_set(&chains, &call_free_blocks, block, &block.term.jmps[0], var)
+        };
+        assert!(is_set(0, &rsi));
+        // RDX may contain a parameter of the function at the first call.
+        assert!(is_set(0, &rdx));
+        // At the second call RSI and RDX contain return values of the first call.
+        assert!(!is_set(1, &rsi));
+        assert!(!is_set(1, &rdx));
+    }
+}
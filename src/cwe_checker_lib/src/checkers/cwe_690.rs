@@ -0,0 +1,728 @@
+//! This module implements a check for CWE-690: Unchecked Return Value to NULL Pointer Dereference.
+//!
+//! Allocation functions like `malloc()` return NULL if the allocation fails.
+//! If the returned pointer is dereferenced without comparing it to NULL first,
+//! the program crashes in the error case.
+//! If the dereference happens at an offset that an attacker can influence (e.g. `buffer[index] = value`),
+//! the NULL pointer plus the offset may point to arbitrary memory, which turns the missing check into a write primitive.
+//!
+//! This check is a focused variant of the [CWE-476 check](super::cwe_476).
+//! In contrast to that check, it only considers the return values of allocation functions,
+//! only reports actual dereferences (and not e.g. the passing of the pointer to other functions)
+//! and only accepts comparisons of the pointer with zero as checks.
+//!
+//! See <https://cwe.mitre.org/data/definitions/690.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the configured allocation `symbols` we follow the returned pointer
+//! along the [def-use chains](crate::analysis::def_use) of the calling function.
+//! Pointer-sized values computed from the pointer (e.g. by pointer arithmetic) are followed as well.
+//! If the pointer is stored to a memory cell known to the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! (e.g. a local variable on the stack), values loaded from the same memory cell are followed as well.
+//!
+//! Then we search the control flow graph of the function starting at the return site of the call.
+//! Conditional jumps depending on a comparison of the pointer with zero are NULL checks, at which the search stops.
+//! A warning is generated for the first load or store instruction on the searched paths whose address is derived from the pointer.
+//!
+//! If `only_controllable_offsets` is set in config.json, only dereferences whose offset relative to the returned pointer
+//! is not a constant known to the Pointer Inference analysis are reported,
+//! since only these may be abused to access arbitrary memory.
+//!
+//! ## False Positives
+//!
+//! - Memory cells containing the pointer are not tracked flow-sensitively.
+//!   A value loaded from a memory cell that was overwritten after the pointer was stored to it is still followed.
+//! - NULL checks in called functions (e.g. wrapper functions terminating the program on NULL) are not recognized.
+//! - Variable offsets are not necessarily controlled by an attacker.
+//!
+//! ## False Negatives
+//!
+//! - The analysis is intraprocedural. Dereferences in other functions than the call are not checked.
+//! - Any comparison of the pointer with zero counts as a NULL check, regardless of which branch accesses the pointer.
+//! - Pointers stored to memory cells not exactly known to the Pointer Inference analysis are lost.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::def_use::DefUseChains;
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE690",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The allocation functions that may return NULL.
+    symbols: Vec<String>,
+    /// Only report dereferences whose offset relative to the returned pointer is not a known constant.
+    #[serde(default)]
+    only_controllable_offsets: bool,
+}
+
+/// A memory cell, given by the abstract object and the offset into it.
+type Cell = (AbstractIdentifier, i64);
+
+/// Get the memory cells accessed by the load and store instructions of the function,
+/// if the Pointer Inference analysis knows them exactly.
+fn get_memory_cells<'a>(
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+) -> HashMap<&'a Tid, Cell> {
+    let mut memory_cells = HashMap::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            let address = match &def.term {
+                Def::Load { address, .. } | Def::Store { address, .. } => address,
+                Def::Assign { .. } => continue,
+            };
+            if let Some(state) = pointer_inference.get_state_at_tid(&def.tid) {
+                if let Data::Pointer(pointer) = state.eval(address) {
+                    if let [(id, offset)] = pointer.targets().iter().collect::<Vec<_>>()[..] {
+                        if let Ok(offset) = offset.try_to_offset() {
+                            memory_cells.insert(&def.tid, (id.clone(), offset));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    memory_cells
+}
+
+/// The values derived from the return value of an allocation call inside the calling function.
+struct DerivedValues<'a> {
+    /// The def-use chains of the calling function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The allocation call.
+    call: &'a Term<Jmp>,
+    /// The register containing the return value of the allocation call.
+    return_register: &'a Variable,
+    /// The `Def` terms whose defined values are derived from the return value.
+    defs: HashSet<&'a Tid>,
+    /// The memory cells that derived values are stored to.
+    cells: HashSet<Cell>,
+}
+
+impl<'a> DerivedValues<'a> {
+    /// Compute the values derived from the return value of the call in the given function.
+    fn new(
+        sub: &'a Term<Sub>,
+        def_use_chains: &'a DefUseChains<'a>,
+        memory_cells: &HashMap<&'a Tid, Cell>,
+        call: &'a Term<Jmp>,
+        return_register: &'a Variable,
+        pointer_size: ByteSize,
+    ) -> DerivedValues<'a> {
+        let mut derived = DerivedValues {
+            def_use_chains,
+            call,
+            return_register,
+            defs: HashSet::new(),
+            cells: HashSet::new(),
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for def in sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.defs.iter())
+            {
+                match &def.term {
+                    Def::Assign { value, .. } => {
+                        if value.bytesize() == pointer_size
+                            && !derived.defs.contains(&def.tid)
+                            && derived.is_expression_derived(&def.tid, value)
+                        {
+                            derived.defs.insert(&def.tid);
+                            changed = true;
+                        }
+                    }
+                    Def::Load { .. } => {
+                        if !derived.defs.contains(&def.tid)
+                            && matches!(memory_cells.get(&def.tid), Some(cell) if derived.cells.contains(cell))
+                        {
+                            derived.defs.insert(&def.tid);
+                            changed = true;
+                        }
+                    }
+                    Def::Store { value, .. } => {
+                        if let Some(cell) = memory_cells.get(&def.tid) {
+                            if derived.is_expression_derived(&def.tid, value) {
+                                changed |= derived.cells.insert(cell.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        derived
+    }
+
+    /// Check whether the value of the register `var` used by the term with the given TID is derived from the return value.
+    fn is_derived(&self, use_tid: &Tid, var: &Variable) -> bool {
+        self.def_use_chains
+            .defs_reaching(use_tid, var)
+            .any(|def_tid| {
+                self.defs.contains(def_tid)
+                    || (*def_tid == self.call.tid && var == self.return_register)
+            })
+    }
+
+    /// Check whether the expression used by the term with the given TID depends on a value derived from the return value.
+    fn is_expression_derived(&self, use_tid: &Tid, expression: &Expression) -> bool {
+        expression
+            .input_vars()
+            .into_iter()
+            .any(|var| self.is_derived(use_tid, var))
+    }
+
+    /// Check whether the expression used by the term with the given TID compares a derived value with zero.
+    fn is_null_comparison(&self, use_tid: &Tid, expression: &Expression) -> bool {
+        let is_zero = |expression: &Expression| matches!(expression, Expression::Const(value) if value.is_zero());
+        match expression {
+            Expression::BinOp {
+                op: BinOpType::IntEqual | BinOpType::IntNotEqual,
+                lhs,
+                rhs,
+            } => {
+                (is_zero(rhs) && self.is_expression_derived(use_tid, lhs))
+                    || (is_zero(lhs) && self.is_expression_derived(use_tid, rhs))
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the `Def` terms of the function whose values are derived from a comparison of a derived value with zero.
+    fn get_null_comparisons(&self, sub: &'a Term<Sub>) -> HashSet<&'a Tid> {
+        let mut comparisons = HashSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for def in sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.defs.iter())
+            {
+                if let Def::Assign { value, .. } = &def.term {
+                    if !comparisons.contains(&def.tid)
+                        && (self.is_null_comparison(&def.tid, value)
+                            || self.depends_on(&def.tid, value, &comparisons))
+                    {
+                        comparisons.insert(&def.tid);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        comparisons
+    }
+
+    /// Check whether the expression used by the term with the given TID depends on one of the given definitions.
+    fn depends_on(&self, use_tid: &Tid, expression: &Expression, defs: &HashSet<&Tid>) -> bool {
+        expression.input_vars().into_iter().any(|var| {
+            self.def_use_chains
+                .defs_reaching(use_tid, var)
+                .any(|def_tid| defs.contains(def_tid))
+        })
+    }
+
+    /// Check whether the jump is a conditional jump depending on a comparison of a derived value with zero.
+    fn is_null_check(&self, jmp: &Term<Jmp>, null_comparisons: &HashSet<&Tid>) -> bool {
+        match &jmp.term {
+            Jmp::CBranch { condition, .. } => {
+                self.is_null_comparison(&jmp.tid, condition)
+                    || self.depends_on(&jmp.tid, condition, null_comparisons)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Get the dereferences of values derived from the return value
+/// that are reachable from the given return site of the call without passing a NULL check,
+/// together with the dereferenced addresses.
+fn get_unchecked_dereferences<'a>(
+    sub: &'a Term<Sub>,
+    derived: &DerivedValues<'a>,
+    return_site: &Tid,
+) -> Vec<(&'a Term<Def>, &'a Expression)> {
+    let null_comparisons = derived.get_null_comparisons(sub);
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut dereferences = Vec::new();
+    let mut visited = HashSet::new();
+    let mut worklist: VecDeque<&Term<Blk>> = blocks.get(return_site).cloned().into_iter().collect();
+    while let Some(block) = worklist.pop_front() {
+        if !visited.insert(&block.tid) {
+            continue;
+        }
+        for def in block.term.defs.iter() {
+            if let Def::Load { address, .. } | Def::Store { address, .. } = &def.term {
+                if derived.is_expression_derived(&def.tid, address) {
+                    dereferences.push((def, address));
+                }
+            }
+        }
+        if block
+            .term
+            .jmps
+            .iter()
+            .any(|jmp| derived.is_null_check(jmp, &null_comparisons))
+        {
+            continue;
+        }
+        for target in get_intraprocedural_jump_targets(block, sub) {
+            if let Some(target_block) = blocks.get(target) {
+                worklist.push_back(target_block);
+            }
+        }
+    }
+    dereferences
+}
+
+/// Check whether the offset of the dereferenced address relative to its target is not exactly known.
+fn has_variable_offset(
+    pointer_inference: &PointerInference,
+    def: &Term<Def>,
+    address: &Expression,
+) -> bool {
+    match pointer_inference
+        .get_state_at_tid(&def.tid)
+        .map(|state| state.eval(address))
+    {
+        Some(Data::Pointer(pointer)) => pointer
+            .targets()
+            .values()
+            .any(|offset| offset.try_to_offset().is_err()),
+        _ => false,
+    }
+}
+
+/// Generate the CWE warning for an unchecked dereference of the return value of an allocation call.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    dereference: &Term<Def>,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Unchecked Return Value to NULL Pointer Dereference) The return value of {} at {} in {} is dereferenced at {} without a NULL check",
+            symbol.name, call.tid.address, sub.term.display_name(), dereference.tid.address
+        ),
+    )
+    .tids(vec![format!("{}", call.tid), format!("{}", dereference.tid)])
+    .addresses(vec![
+        call.tid.address.clone(),
+        dereference.tid.address.clone(),
+    ])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .root_cause(&call.tid)
+}
+
+/// Run the CWE check.
+/// For each call to an allocation function we search for dereferences of the returned pointer
+/// that are not preceded by a NULL check.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbol_map = get_symbol_map(project, &config.symbols);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let callsites = get_callsites(sub, &symbol_map);
+        if callsites.is_empty() {
+            continue;
+        }
+        let def_use_chains = match analysis_results.def_use_chains(&sub.tid) {
+            Some(def_use_chains) => def_use_chains,
+            None => continue,
+        };
+        let memory_cells = get_memory_cells(pointer_inference, sub);
+        for (_block, call, symbol) in callsites {
+            let (return_register, return_site) = match (symbol.return_values.first(), &call.term) {
+                (
+                    Some(Arg::Register(return_register)),
+                    Jmp::Call {
+                        return_: Some(return_site),
+                        ..
+                    },
+                ) => (return_register, return_site),
+                _ => continue,
+            };
+            let derived = DerivedValues::new(
+                sub,
+                def_use_chains,
+                &memory_cells,
+                call,
+                return_register,
+                project.get_pointer_bytesize(),
+            );
+            if let Some((dereference, _address)) =
+                get_unchecked_dereferences(sub, &derived, return_site)
+                    .into_iter()
+                    .find(|(def, address)| {
+                        !config.only_controllable_offsets
+                            || has_variable_offset(pointer_inference, def, address)
+                    })
+            {
+                cwe_warnings.push(generate_cwe_warning(sub, call, symbol, dereference));
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    #[test]
+    fn unchecked_dereferences() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let rax = Variable::mock("RAX", 8);
+        let rcx = Variable::mock("RCX", 8);
+        let zf = Variable::mock("ZF", 1);
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "entry",
+                Vec::new(),
+                vec![Jmp::mock_call("mock_symbol", "branch")],
+            ),
+            Blk::mock_with_jmps(
+                "branch",
+                vec![Def::assign(
+                    "offset_pointer",
+                    rcx.clone(),
+                    Expression::Var(rax.clone()).plus_const(8),
+                )],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("unchecked"),
+                        condition: Expression::Var(Variable::mock("RDI", 8)),
+                    },
+                    Jmp::Branch(Tid::new("check")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "check",
+                vec![Def::assign(
+                    "compare",
+                    zf.clone(),
+                    Expression::BinOp {
+                        op: BinOpType::IntEqual,
+                        lhs: Box::new(Expression::Var(rax.clone())),
+                        rhs: Box::new(Expression::const_from_i64(0)),
+                    },
+                )],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("exit"),
+                        condition: Expression::Var(zf),
+                    },
+                    Jmp::Branch(Tid::new("checked")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "checked",
+                vec![Term {
+                    tid: Tid::new("checked_store"),
+                    term: Def::Store {
+                        address: Expression::Var(rax.clone()),
+                        value: Expression::const_from_i64(1),
+                    },
+                }],
+                vec![Jmp::Branch(Tid::new("exit"))],
+            ),
+            Blk::mock_with_jmps(
+                "unchecked",
+                vec![Term {
+                    tid: Tid::new("unchecked_load"),
+                    term: Def::Load {
+                        var: rax.clone(),
+                        address: Expression::Var(rcx),
+                    },
+                }],
+                vec![Jmp::Branch(Tid::new("exit"))],
+            ),
+            Blk::mock_with_jmps(
+                "exit",
+                Vec::new(),
+                vec![Jmp::Return(Expression::Var(rax.clone()))],
+            ),
+        ];
+        let chains = DefUseChains::new(&project, &sub);
+        let derived = DerivedValues::new(
+            &sub,
+            &chains,
+            &HashMap::new(),
+            &sub.term.blocks[0].term.jmps[0],
+            &rax,
+            ByteSize::new(8),
+        );
+        assert!(derived.defs.contains(&Tid::new("offset_pointer")));
+        // Comparison results are not pointer-sized and thus not derived values.
+        assert!(!derived.defs.contains(&Tid::new("compare")));
+        let dereferences: Vec<String> =
+            get_unchecked_dereferences(&sub, &derived, &Tid::new("branch"))
+                .into_iter()
+                .map(|(def, _address)| def.tid.to_string())
+                .collect();
+        assert_eq!(dereferences, vec!["unchecked_load".to_string()]);
+    }
+
+    fn store(tid: &str, address: Expression) -> Term<Def> {
+        Def::store(tid, address, Expression::const_from_i64(1))
+    }
+
+    /// A function calling `target` in its first block and executing the given blocks afterwards.
+    /// The call returns to the block `{name}_1`.
+    fn call_sub(
+        name: &str,
+        entry_defs: Vec<Term<Def>>,
+        target: &str,
+        blocks: Vec<Term<Blk>>,
+    ) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        sub.term.blocks = vec![Blk::mock_with_jmps(
+            &format!("{}_0", name),
+            entry_defs,
+            vec![Jmp::mock_call(target, &format!("{}_1", name))],
+        )];
+        sub.term.blocks.extend(blocks);
+        sub
+    }
+
+    /// Run the check on the project and return the TIDs of the reported dereferences.
+    fn reported_dereferences(project: &Project, config: serde_json::Value) -> Vec<String> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": ["malloc"],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| warning.tids[1].clone())
+            .collect()
+    }
+
+    #[test]
+    fn allocation_return_values() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        let mut malloc = ExternSymbol::mock();
+        malloc.tid = Tid::new("malloc");
+        malloc.name = "malloc".to_string();
+        project.program.term.extern_symbols = vec![malloc, ExternSymbol::mock()];
+        let rax = Variable::mock("RAX", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let rcx = Variable::mock("RCX", 8);
+        let zf = Variable::mock("ZF", 1);
+        let ret = |name: &str| {
+            Blk::mock_with_jmps(
+                name,
+                Vec::new(),
+                vec![Jmp::Return(Expression::Var(rax.clone()))],
+            )
+        };
+        project.program.term.subs = vec![
+            // *(malloc(16) + 8) = 1
+            call_sub(
+                "constant_offset",
+                Vec::new(),
+                "malloc",
+                vec![Blk::mock_with_jmps(
+                    "constant_offset_1",
+                    vec![store(
+                        "constant_offset_store",
+                        Expression::Var(rax.clone()).plus_const(8),
+                    )],
+                    vec![Jmp::Return(Expression::Var(rax.clone()))],
+                )],
+            ),
+            // The offset is the result of a comparison and thus not exactly known.
+            call_sub(
+                "variable_offset",
+                Vec::new(),
+                "malloc",
+                vec![Blk::mock_with_jmps(
+                    "variable_offset_1",
+                    vec![
+                        Def::assign(
+                            "variable_offset_compare",
+                            zf.clone(),
+                            Expression::BinOp {
+                                op: BinOpType::IntEqual,
+                                lhs: Box::new(Expression::Var(Variable::mock("RDI", 8))),
+                                rhs: Box::new(Expression::const_from_i64(0)),
+                            },
+                        ),
+                        Def::assign(
+                            "variable_offset_extend",
+                            rcx.clone(),
+                            Expression::Cast {
+                                op: CastOpType::IntZExt,
+                                size: ByteSize::new(8),
+                                arg: Box::new(Expression::Var(zf.clone())),
+                            },
+                        ),
+                        Def::assign(
+                            "variable_offset_add",
+                            rcx.clone(),
+                            Expression::BinOp {
+                                op: BinOpType::IntAdd,
+                                lhs: Box::new(Expression::Var(rax.clone())),
+                                rhs: Box::new(Expression::Var(rcx.clone())),
+                            },
+                        ),
+                        store("variable_offset_store", Expression::Var(rcx.clone())),
+                    ],
+                    vec![Jmp::Return(Expression::Var(rax.clone()))],
+                )],
+            ),
+            // The pointer is stored to a stack variable and loaded from it before the dereference.
+            call_sub(
+                "stack_variable",
+                vec![Def::assign(
+                    "stack_variable_frame",
+                    rbp.clone(),
+                    Expression::Var(Variable::mock("RSP", 8)).plus_const(-0x10),
+                )],
+                "malloc",
+                vec![Blk::mock_with_jmps(
+                    "stack_variable_1",
+                    vec![
+                        Def::store(
+                            "stack_variable_save",
+                            Expression::Var(rbp.clone()),
+                            Expression::Var(rax.clone()),
+                        ),
+                        Def::assign(
+                            "stack_variable_overwrite",
+                            rax.clone(),
+                            Expression::const_from_i64(0),
+                        ),
+                        Def::load(
+                            "stack_variable_restore",
+                            rcx.clone(),
+                            Expression::Var(rbp.clone()),
+                        ),
+                        store("stack_variable_store", Expression::Var(rcx.clone())),
+                    ],
+                    vec![Jmp::Return(Expression::Var(rax.clone()))],
+                )],
+            ),
+            // The pointer is compared with NULL before the dereference.
+            call_sub(
+                "checked",
+                Vec::new(),
+                "malloc",
+                vec![
+                    Blk::mock_with_jmps(
+                        "checked_1",
+                        vec![Def::assign(
+                            "checked_compare",
+                            zf.clone(),
+                            Expression::BinOp {
+                                op: BinOpType::IntEqual,
+                                lhs: Box::new(Expression::Var(rax.clone())),
+                                rhs: Box::new(Expression::const_from_i64(0)),
+                            },
+                        )],
+                        vec![
+                            Jmp::CBranch {
+                                target: Tid::new("checked_3"),
+                                condition: Expression::Var(zf.clone()),
+                            },
+                            Jmp::Branch(Tid::new("checked_2")),
+                        ],
+                    ),
+                    Blk::mock_with_jmps(
+                        "checked_2",
+                        vec![store("checked_store", Expression::Var(rax.clone()))],
+                        vec![Jmp::Branch(Tid::new("checked_3"))],
+                    ),
+                    ret("checked_3"),
+                ],
+            ),
+            // The return value of a function that is not an allocation function is dereferenced.
+            call_sub(
+                "no_allocation",
+                Vec::new(),
+                "mock_symbol",
+                vec![Blk::mock_with_jmps(
+                    "no_allocation_1",
+                    vec![store("no_allocation_store", Expression::Var(rax.clone()))],
+                    vec![Jmp::Return(Expression::Var(rax.clone()))],
+                )],
+            ),
+        ];
+
+        let config = json!({"symbols": ["malloc"]});
+        assert_eq!(
+            reported_dereferences(&project, config),
+            vec![
+                "constant_offset_store",
+                "variable_offset_store",
+                "stack_variable_store"
+            ]
+        );
+        let config = json!({"symbols": ["malloc"], "only_controllable_offsets": true});
+        assert_eq!(
+            reported_dereferences(&project, config),
+            vec!["variable_offset_store"]
+        );
+        let config = json!({"symbols": ["malloc", "mock_symbol"]});
+        assert_eq!(reported_dereferences(&project, config).len(), 4);
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: Config = parse_config(&json!({"symbols": ["malloc"]})).unwrap();
+        assert!(!config.only_controllable_offsets);
+        assert!(parse_config::<Config>(&json!({"only_controllable_offsets": true})).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+//! This module implements a check for CWE-732: Incorrect Permission Assignment for Critical Resource.
+//!
+//! Files, directories and shared memory objects created with world-writable permissions
+//! can be modified by every user of the system.
+//! Files with the setuid or setgid bit run with the privileges of their owner resp. group,
+//! so that granting these bits by accident may lead to privilege escalation.
+//! Calling `umask(0)` disables the permission mask of the process,
+//! so that all subsequently created files get exactly the permissions requested by the program.
+//!
+//! See <https://cwe.mitre.org/data/definitions/732.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the configured `mode_parameters` (e.g. `open`, `chmod` or `mkdir`)
+//! we evaluate the permission parameter with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! Its value is represented by an interval, so that modes computed by OR-ing or masking constants are handled as well.
+//! The bits set in all values of the interval are the permission bits that are granted for certain.
+//! A warning is generated if they contain the world-writable, the setuid or the setgid bit.
+//!
+//! Functions like `open` only use the permission parameter if they create a file.
+//! For the configured `flag_parameters` the permissions are only checked
+//! if the flags may contain one of the configured `create_flags` (e.g. `O_CREAT`).
+//!
+//! Additionally, a warning is generated for each call to one of the `umask_symbols` with the argument zero.
+//!
+//! ## False Positives
+//!
+//! - World-writable directories with the sticky bit (like `/tmp`) are intended to be shared between users.
+//! - The `create_flags` contain the values of `O_CREAT` on different architectures.
+//!   On some architectures they may coincide with other flags.
+//!
+//! ## False Negatives
+//!
+//! - Permissions that are only known to be dangerous on some paths are not reported.
+//! - Permissions that are restricted by the umask of the process at runtime are not taken into account,
+//!   i.e. calls are reported regardless of the umask.
+//! - Permissions set through other functions than the configured ones (e.g. `fchmod` on a descriptor from `open`) are only reported if these functions are configured.
+
+use crate::abstract_domain::{Interval, TryToInterval};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE732",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions creating files or changing their permissions,
+    /// mapped to the index of the parameter containing the permission bits.
+    mode_parameters: BTreeMap<String, usize>,
+    /// Extern functions that only use the permission bits if they create a file,
+    /// mapped to the index of the parameter containing the flags.
+    #[serde(default)]
+    flag_parameters: BTreeMap<String, usize>,
+    /// Flags requesting the creation of a file, e.g. `O_CREAT`.
+    #[serde(default)]
+    create_flags: Vec<u64>,
+    /// Extern functions setting the permission mask of the process, e.g. `umask`.
+    umask_symbols: Vec<String>,
+}
+
+/// The permission bit granting write access to all users.
+const WORLD_WRITABLE: u64 = 0o002;
+/// The permission bit running executables with the privileges of their owner.
+const SETUID: u64 = 0o4000;
+/// The permission bit running executables with the privileges of their group.
+const SETGID: u64 = 0o2000;
+
+/// Get the bits that are set in all values of the interval.
+///
+/// Returns `None` if the interval contains negative values.
+fn get_certain_bits(interval: &Interval) -> Option<u64> {
+    let start = interval.start.try_to_u64().ok()?;
+    let end = interval.end.try_to_u64().ok()?;
+    if start > end {
+        return None;
+    }
+    // All bits up to the highest bit in which start and end differ take both values in the interval.
+    let differing_bits = start ^ end;
+    if differing_bits == 0 {
+        return Some(start);
+    }
+    Some(start & !(u64::MAX >> differing_bits.leading_zeros()))
+}
+
+/// Get the names of the dangerous permission bits contained in the mode.
+fn get_permission_issues(mode: u64) -> Vec<&'static str> {
+    let mut issues = Vec::new();
+    if mode & WORLD_WRITABLE != 0 {
+        issues.push("world_writable");
+    }
+    if mode & SETUID != 0 {
+        issues.push("setuid");
+    }
+    if mode & SETGID != 0 {
+        issues.push("setgid");
+    }
+    issues
+}
+
+/// Evaluate the parameter with the given index at the call to the interval of its possible values.
+fn eval_parameter(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+    call: &Term<Jmp>,
+    (symbol, index): (&ExternSymbol, usize),
+) -> Option<Interval> {
+    let state = pointer_inference.get_state_at_jmp_tid(&call.tid)?;
+    let parameter = get_variadic_parameter(project, symbol, index);
+    match state
+        .eval_parameter_arg(
+            &parameter,
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?
+    {
+        Data::Value(value) => value.try_to_interval().ok(),
+        _ => None,
+    }
+}
+
+/// Generate the CWE warning for a call granting dangerous permissions.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    mode: u64,
+    issues: &[&str],
+) -> CweWarning {
+    let mut issue_list = vec!["permission_issues".to_string()];
+    issue_list.extend(issues.iter().map(|issue| issue.to_string()));
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Incorrect Permission Assignment for Critical Resource) {} calls {} at {} with permissions {:#o}, which are {}",
+            sub.term.display_name(),
+            symbol.name,
+            call.tid.address,
+            mode,
+            issues.join(", ").replace('_', "-")
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![
+        vec!["mode".to_string(), format!("{:#o}", mode)],
+        issue_list,
+    ])
+}
+
+/// Generate the CWE warning for a call disabling the permission mask of the process.
+fn generate_umask_warning(sub: &Term<Sub>, call: &Term<Jmp>, symbol: &ExternSymbol) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Incorrect Permission Assignment for Critical Resource) {} calls {}(0) at {}, which disables the permission mask of the process",
+            sub.term.display_name(), symbol.name, call.tid.address
+        ),
+    )
+    .tids(vec![format!("{}", call.tid)])
+    .addresses(vec![call.tid.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![vec!["umask_arg".to_string(), "0".to_string()]])
+}
+
+/// Run the CWE check.
+/// For each call to a configured function we check whether the permissions passed to it
+/// are world-writable or contain the setuid or setgid bit,
+/// and for each call to `umask` whether it disables the permission mask.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let mode_symbol_names: Vec<String> = config.mode_parameters.keys().cloned().collect();
+    let mode_symbols = get_symbol_map(project, &mode_symbol_names);
+    let umask_symbols = get_symbol_map(project, &config.umask_symbols);
+    let create_flags = config
+        .create_flags
+        .iter()
+        .fold(0, |flags, flag| flags | flag);
+    let eval = |call, symbol, index| {
+        eval_parameter(
+            project,
+            pointer_inference,
+            runtime_memory_image,
+            call,
+            (symbol, index),
+        )
+    };
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, call, symbol) in get_callsites(sub, &mode_symbols) {
+            if let Some(flags_index) = config.flag_parameters.get(&symbol.name) {
+                // Only flags that are known exactly can exclude the creation of a file.
+                let may_create = match eval(call, symbol, *flags_index) {
+                    Some(flags) if flags.start == flags.end => flags
+                        .start
+                        .try_to_u64()
+                        .map(|flags| flags & create_flags != 0)
+                        .unwrap_or(true),
+                    _ => true,
+                };
+                if !may_create {
+                    continue;
+                }
+            }
+            let mode = match eval(call, symbol, config.mode_parameters[&symbol.name])
+                .as_ref()
+                .and_then(get_certain_bits)
+            {
+                Some(mode) => mode,
+                None => continue,
+            };
+            let issues = get_permission_issues(mode);
+            if !issues.is_empty() {
+                cwe_warnings.push(generate_cwe_warning(sub, call, symbol, mode, &issues));
+            }
+        }
+        for (_block, call, symbol) in get_callsites(sub, &umask_symbols) {
+            let is_zero = matches!(
+                eval(call, symbol, 0),
+                Some(mask) if mask.start == mask.end && mask.start.is_zero()
+            );
+            if is_zero {
+                cwe_warnings.push(generate_umask_warning(sub, call, symbol));
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: u64, end: u64) -> Interval {
+        Interval::new(Bitvector::from_u64(start), Bitvector::from_u64(end))
+    }
+
+    #[test]
+    fn certain_bits() {
+        assert_eq!(get_certain_bits(&interval(0o666, 0o666)), Some(0o666));
+        // All values in [0o640, 0o647] grant read and write access to the owner and read access to the group.
+        assert_eq!(get_certain_bits(&interval(0o640, 0o647)), Some(0o640));
+        // The world-writable bit varies in [0o4775, 0o4777], but the setuid bit is set in all values.
+        assert_eq!(get_certain_bits(&interval(0o4775, 0o4777)), Some(0o4774));
+        assert_eq!(get_certain_bits(&interval(0, 0o7777)), Some(0));
+        let negative = Interval::new(Bitvector::from_i64(-1), Bitvector::from_i64(5));
+        assert_eq!(get_certain_bits(&negative), None);
+    }
+
+    #[test]
+    fn permission_issues() {
+        assert!(get_permission_issues(0o644).is_empty());
+        assert_eq!(get_permission_issues(0o666), vec!["world_writable"]);
+        assert_eq!(get_permission_issues(0o4755), vec!["setuid"]);
+        assert_eq!(
+            get_permission_issues(0o6777),
+            vec!["world_writable", "setuid", "setgid"]
+        );
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "chmod".to_string();
+        let call = Term {
+            tid: Tid::new("call_chmod"),
+            term: Jmp::Call {
+                target: Tid::new("chmod"),
+                return_: None,
+            },
+        };
+        let warning = generate_cwe_warning(
+            &Sub::mock("func"),
+            &call,
+            &symbol,
+            0o4755,
+            &get_permission_issues(0o4755),
+        );
+        assert!(warning
+            .description
+            .ends_with("permissions 0o4755, which are setuid"));
+    }
+}
@@ -0,0 +1,203 @@
+//! This module implements a check for CWE-761: Free of Pointer not at Start of Buffer.
+//!
+//! Deallocation functions like `free` expect the exact pointer returned by the corresponding allocation function.
+//! Passing a pointer into the middle of a heap object corrupts the metadata of the heap allocator,
+//! which may lead to crashes or arbitrary code execution.
+//!
+//! See <https://cwe.mitre.org/data/definitions/761.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The [Pointer Inference analysis](crate::analysis::pointer_inference) tracks the offsets of pointers
+//! relative to the start of the heap objects they point to.
+//! Since the states at the start of functions are derived from the states at their call sites,
+//! adjusted pointers passed as parameters to other functions keep their offsets in the callee.
+//! For each call to one of the deallocation functions configured in the `symbols` of the check
+//! we evaluate the pointer parameter.
+//! If it points to a heap object at an offset that may be nonzero, a warning is generated.
+//! The warning contains the possible offsets and the allocation site of the heap object.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - The offset intervals of pointers in loops may be overapproximated if the loop bound cannot be represented in the interval domain.
+//! - Objects allocated by the same call in a loop are represented by the same abstract object.
+//!
+//! ## False Negatives
+//!
+//! - Pointers whose offset is unknown to the Pointer Inference analysis are not checked.
+//! - Pointers to objects that the Pointer Inference analysis does not recognize as heap objects are not checked.
+//! - Custom deallocation functions that are not extern symbols are not checked.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToInterval};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE761",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration of the check.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions releasing the heap object pointed to by their first parameter.
+    symbols: Vec<String>,
+}
+
+/// Return the first heap object targeted by the given pointer at an offset that may be nonzero,
+/// together with the interval of possible offsets.
+///
+/// Targets with unknown offsets are ignored.
+fn get_offset_heap_target(state: &State, pointer: &Data) -> Option<(AbstractIdentifier, i64, i64)> {
+    let pointer = match pointer {
+        Data::Pointer(pointer) => pointer,
+        _ => return None,
+    };
+    for (id, offset) in pointer.targets() {
+        if !matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap))) {
+            continue;
+        }
+        if let Ok((offset_start, offset_end)) = offset.try_to_offset_interval() {
+            if offset_start != 0 || offset_end != 0 {
+                return Some((id.clone(), offset_start, offset_end));
+            }
+        }
+    }
+    None
+}
+
+/// Generate the CWE warning for a deallocation of the given heap object at a nonzero offset.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    callsite: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    object_id: &AbstractIdentifier,
+    offsets: (i64, i64),
+) -> CweWarning {
+    let allocation_site = object_id.get_tid();
+    let offsets = if offsets.0 == offsets.1 {
+        offsets.0.to_string()
+    } else {
+        format!("[{}, {}]", offsets.0, offsets.1)
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Free of Pointer not at Start of Buffer) Call to {} at {} in {} may release the heap object allocated at {} through a pointer at offset {}",
+            symbol.name, callsite.tid.address, sub.term.display_name(), allocation_site.address, offsets
+        ),
+    )
+    .tids(vec![format!("{}", callsite.tid), format!("{}", allocation_site)])
+    .addresses(vec![
+        callsite.tid.address.clone(),
+        allocation_site.address.clone(),
+    ])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+    .other(vec![vec!["offset".to_string(), offsets]])
+    .root_cause(object_id)
+}
+
+/// Run the CWE check.
+/// For each call to a deallocation function we check whether the pointer parameter
+/// may point to a heap object at a nonzero offset.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let symbol_map = get_symbol_map(project, &config.symbols);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_, callsite, symbol) in get_callsites(sub, &symbol_map) {
+            let (state, parameter) = match (
+                pointer_inference.get_state_at_jmp_tid(&callsite.tid),
+                symbol.parameters.first(),
+            ) {
+                (Some(state), Some(parameter)) => (state, parameter),
+                _ => continue,
+            };
+            let pointer = match state.eval_parameter_arg(
+                parameter,
+                &project.stack_pointer_register,
+                analysis_results.runtime_memory_image,
+            ) {
+                Ok(pointer) => pointer,
+                Err(_) => continue,
+            };
+            if let Some((object_id, offset_start, offset_end)) =
+                get_offset_heap_target(state, &pointer)
+            {
+                cwe_warnings.push(generate_cwe_warning(
+                    sub,
+                    callsite,
+                    symbol,
+                    &object_id,
+                    (offset_start, offset_end),
+                ));
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+    use crate::analysis::pointer_inference::ValueDomain;
+
+    #[test]
+    fn offset_heap_targets() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let heap_id = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        state.memory.add_abstract_object(
+            heap_id.clone(),
+            Bitvector::from_i64(0).into(),
+            ObjectType::Heap,
+            ByteSize::new(8),
+        );
+        let pointer_at =
+            |offset: ValueDomain| -> Data { PointerDomain::new(heap_id.clone(), offset).into() };
+
+        assert!(
+            get_offset_heap_target(&state, &pointer_at(Bitvector::from_i64(0).into())).is_none()
+        );
+        assert_eq!(
+            get_offset_heap_target(&state, &pointer_at(Bitvector::from_i64(4).into())),
+            Some((heap_id.clone(), 4, 4))
+        );
+        let possibly_zero = ValueDomain::new(Bitvector::from_i64(0), Bitvector::from_i64(8));
+        assert_eq!(
+            get_offset_heap_target(&state, &pointer_at(possibly_zero)),
+            Some((heap_id.clone(), 0, 8))
+        );
+        // Unknown offsets and pointers to other objects than heap objects are ignored.
+        assert!(get_offset_heap_target(
+            &state,
+            &pointer_at(ValueDomain::new_top(ByteSize::new(8)))
+        )
+        .is_none());
+        let stack_pointer: Data =
+            PointerDomain::new(state.stack_id.clone(), Bitvector::from_i64(-8).into()).into();
+        assert!(get_offset_heap_target(&state, &stack_pointer).is_none());
+    }
+}
@@ -0,0 +1,276 @@
+//! This module implements a check for CWE-770: Allocation of Resources Without Limits or Throttling.
+//!
+//! If untrusted input controls how often a loop allocates memory, an attacker can make the program
+//! allocate arbitrary amounts of memory in total, even if each single allocation is small.
+//! Similarly, stack allocations with `alloca` or variable-length arrays whose size is controlled by untrusted input
+//! can exhaust the stack or jump over its guard page.
+//! This complements the [CWE-789 check](super::cwe_789), which checks the size of single heap allocations.
+//!
+//! See <https://cwe.mitre.org/data/definitions/770.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each loop of a function (see [`get_loops`]) that calls one of the allocation or reallocation functions
+//! known to the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! we check whether the number of iterations of the loop is controlled by untrusted input
+//! as in the [CWE-606 check](super::cwe_606):
+//! The conditions of the jumps leaving the loop are traced backwards to the return values of the configured
+//! `return_value_sources` and to the memory written by the configured `parameter_sources`.
+//! If one of these sources is not checked against an upper bound, a warning is generated for the loop.
+//!
+//! Inlined stack allocations (`alloca` and variable-length arrays) are recognized
+//! as subtractions of a non-constant value from the stack pointer.
+//! The subtracted value is traced backwards to untrusted sources in the same way.
+//! A warning is generated if it depends on a source that is neither bounded according to the interval domain
+//! of the Pointer Inference analysis nor compared to a nonzero constant elsewhere in the function.
+//!
+//! ## False Positives
+//!
+//! - Memory allocated in a loop may also be freed in the same iteration of the loop.
+//! - Checks in other functions (e.g. in a parsing function) are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - The analysis is intraprocedural.
+//!   Allocations in functions called from the loop and loop bounds passed as parameters are not recognized.
+//! - Any comparison of an untrusted value with a nonzero constant outside of the loop counts as a check,
+//!   even if it is not an upper bound check.
+
+use super::cwe_606::{InputTracer, Sources};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_loops;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::utils::taint_sources::TaintSourceConfig;
+use crate::CweModule;
+use std::collections::HashSet;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE770",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<TaintSourceConfig>,
+    default_config: crate::utils::default_config::<TaintSourceConfig>,
+};
+
+/// Get the size of the stack allocation by the `Def` term,
+/// if it subtracts a non-constant value from the stack pointer.
+fn get_dynamic_stack_allocation<'a>(
+    def: &'a Term<Def>,
+    stack_pointer: &Variable,
+) -> Option<&'a Expression> {
+    match &def.term {
+        Def::Assign {
+            var,
+            value:
+                Expression::BinOp {
+                    op: BinOpType::IntSub,
+                    lhs,
+                    rhs,
+                },
+        } if var == stack_pointer
+            && matches!(lhs.as_ref(), Expression::Var(lhs_var) if lhs_var == stack_pointer)
+            && !matches!(rhs.as_ref(), Expression::Const(_)) =>
+        {
+            Some(rhs)
+        }
+        _ => None,
+    }
+}
+
+/// Generate the warning for a loop that allocates memory in each iteration
+/// and whose number of iterations is controlled by untrusted input.
+fn generate_loop_warning(
+    sub: &Term<Sub>,
+    allocations: &[(&Term<Jmp>, &ExternSymbol)],
+    loop_exits: &[(&Term<Jmp>, Sources)],
+) -> CweWarning {
+    let mut source_names: Vec<&str> = loop_exits
+        .iter()
+        .flat_map(|(_, sources)| sources.values().cloned())
+        .collect();
+    source_names.sort_unstable();
+    source_names.dedup();
+    let allocation_descriptions: Vec<String> = allocations
+        .iter()
+        .map(|(call, symbol)| format!("{} at {}", symbol.name, call.tid.address))
+        .collect();
+    let description = format!(
+        "(Allocation of Resources Without Limits) The loop calling {} in {} has a number of iterations controlled by input from {} without an upper bound.",
+        allocation_descriptions.join(", "),
+        sub.term.display_name(),
+        source_names.join(", ")
+    );
+    let jmps = allocations
+        .iter()
+        .map(|(call, _)| *call)
+        .chain(loop_exits.iter().map(|(jmp, _)| *jmp));
+    let mut symbols = vec![sub.term.display_name().to_string()];
+    symbols.extend(allocations.iter().map(|(_, symbol)| symbol.name.clone()));
+    symbols.dedup();
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(jmps.clone().map(|jmp| jmp.tid.address.clone()).collect())
+        .tids(jmps.map(|jmp| jmp.tid.to_string()).collect())
+        .symbols(symbols)
+        .other(vec![std::iter::once("sources".to_string())
+            .chain(source_names.iter().map(|name| name.to_string()))
+            .collect()])
+}
+
+/// Generate the warning for a stack allocation whose size is controlled by untrusted input.
+fn generate_stack_allocation_warning(
+    sub: &Term<Sub>,
+    def: &Term<Def>,
+    sources: &Sources,
+) -> CweWarning {
+    let mut source_names: Vec<&str> = sources.values().cloned().collect();
+    source_names.sort_unstable();
+    source_names.dedup();
+    let description = format!(
+        "(Allocation of Resources Without Limits) The size of the stack allocation at {} in {} is controlled by input from {} without an upper bound.",
+        def.tid.address,
+        sub.term.display_name(),
+        source_names.join(", ")
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![def.tid.address.clone()])
+        .tids(
+            std::iter::once(def.tid.to_string())
+                .chain(sources.keys().map(|tid| tid.to_string()))
+                .collect(),
+        )
+        .symbols(vec![sub.term.display_name().to_string()])
+        .other(vec![std::iter::once("sources".to_string())
+            .chain(source_names.iter().map(|name| name.to_string()))
+            .collect()])
+}
+
+/// Flag loops allocating memory whose number of iterations is controlled by untrusted input
+/// and stack allocations whose size is controlled by untrusted input.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: TaintSourceConfig = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let return_value_sources = config.get_return_value_sources(project);
+    let parameter_sources = config.get_parameter_sources(project);
+    if return_value_sources.is_empty() && parameter_sources.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let context = pointer_inference.get_context();
+    let allocation_symbol_names: Vec<String> = context
+        .allocation_symbols
+        .iter()
+        .chain(context.reallocation_symbols.iter())
+        .cloned()
+        .collect();
+    let allocation_symbols = get_symbol_map(project, &allocation_symbol_names);
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let allocations = get_callsites(sub, &allocation_symbols);
+        let loops = if allocations.is_empty() {
+            Vec::new()
+        } else {
+            get_loops(sub)
+        };
+        let stack_allocations: Vec<(&Term<Def>, &Expression)> = sub
+            .term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.defs.iter())
+            .filter_map(|def| {
+                get_dynamic_stack_allocation(def, &project.stack_pointer_register)
+                    .map(|size| (def, size))
+            })
+            .collect();
+        if loops.is_empty() && stack_allocations.is_empty() {
+            continue;
+        }
+        let tracer = InputTracer::new(
+            analysis_results,
+            sub,
+            return_value_sources.clone(),
+            &parameter_sources,
+            &config.parameter_sources,
+            false,
+        );
+
+        let mut reported_allocations = HashSet::new();
+        for loop_blocks in loops.iter() {
+            let loop_allocations: Vec<(&Term<Jmp>, &ExternSymbol)> = allocations
+                .iter()
+                .filter(|(block, call, _)| {
+                    loop_blocks.contains(&block.tid) && !reported_allocations.contains(&call.tid)
+                })
+                .map(|(_, call, symbol)| (*call, *symbol))
+                .collect();
+            if loop_allocations.is_empty() {
+                continue;
+            }
+            let loop_exits = tracer.get_unchecked_loop_exits(loop_blocks);
+            if !loop_exits.is_empty() {
+                reported_allocations.extend(loop_allocations.iter().map(|(call, _)| &call.tid));
+                warnings.push(generate_loop_warning(sub, &loop_allocations, &loop_exits));
+            }
+        }
+
+        if stack_allocations.is_empty() {
+            continue;
+        }
+        let checked_sources = tracer.get_checked_sources(&HashSet::new());
+        for (def, size) in stack_allocations {
+            if tracer.has_checked_upper_bound(&def.tid, size) {
+                continue;
+            }
+            let mut sources = tracer.get_sources(&def.tid, size);
+            sources.retain(|source_tid, _| !checked_sources.contains(source_tid));
+            if !sources.is_empty() {
+                warnings.push(generate_stack_allocation_warning(sub, def, &sources));
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dynamic_stack_allocations() {
+        let rsp = Variable::mock("RSP", 8);
+        let rax = Variable::mock("RAX", 8);
+        let allocation = Def::assign(
+            "alloca",
+            rsp.clone(),
+            Expression::Var(rsp.clone()).minus(Expression::Var(rax.clone())),
+        );
+        assert_eq!(
+            get_dynamic_stack_allocation(&allocation, &rsp),
+            Some(&Expression::Var(rax.clone()))
+        );
+        // The constant stack frame allocation in the function prologue is ignored.
+        let prologue = Def::assign(
+            "prologue",
+            rsp.clone(),
+            Expression::Var(rsp.clone()).minus_const(32),
+        );
+        assert_eq!(get_dynamic_stack_allocation(&prologue, &rsp), None);
+        // Subtractions from other registers are ignored.
+        let other = Def::assign(
+            "other",
+            rax.clone(),
+            Expression::Var(rsp.clone()).minus(Expression::Var(rax)),
+        );
+        assert_eq!(get_dynamic_stack_allocation(&other, &rsp), None);
+    }
+}
@@ -0,0 +1,968 @@
+//! This module implements a check for CWE-775: Missing Release of File Descriptor or Handle after Effective Lifetime.
+//!
+//! File descriptors and handles (e.g. of files, sockets or directories) that are not closed
+//! before the last reference to them is lost cannot be reused by the program.
+//! If this happens repeatedly (e.g. on an error path of a request handler),
+//! attackers may exhaust the descriptors available to the process, so that it cannot open new files or accept new connections.
+//!
+//! See <https://cwe.mitre.org/data/definitions/775.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each function we run a simple intraprocedural analysis
+//! that tracks the descriptors returned by calls to the configured `acquisition_symbols` (e.g. `open` or `fopen`).
+//! Descriptors are identified by the callsites of their acquisition
+//! and tracked through registers and through the stack frame of the function,
+//! whose memory cells are determined with the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//! A descriptor is released if it is passed to one of the configured `release_symbols` (e.g. `close` or `fclose`).
+//! A descriptor escapes (and is no longer tracked) if it is stored outside of the stack frame of the function,
+//! passed to one of the configured `escaping_symbols`, to an internal function or to an indirect call.
+//!
+//! The function exits are checked by the [resource lifetime engine](crate::analysis::resource_lifetime)
+//! that is shared with the [check for memory leaks](crate::checkers::cwe_401).
+//! A descriptor leaks at a function exit if it is acquired and not released or escaped on the path to the exit
+//! and not returned in a return register.
+//! Descriptors that are checked to be invalid on the path (e.g. by `fd < 0` or `file == NULL`) are not reported.
+//! Functions configured as `program_exit_functions` are not checked,
+//! since the operating system closes the descriptors of a program after it terminates.
+//!
+//! One warning is generated per function and acquisition site.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - Descriptors released through pointers that the Pointer Inference analysis lost track of are reported as leaked.
+//! - Ownership transfers to extern functions other than the configured `escaping_symbols` are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Descriptors acquired on only some of the paths reaching a point are no longer tracked after that point.
+//! - Descriptors returned through parameters (e.g. by `pipe` or `socketpair`) are not tracked.
+//! - Leaks on paths through blocks with several predecessors are only recognized
+//!   if the descriptor leaks on the merged path as well.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::analysis::resource_lifetime::{
+    find_leaks, get_incoming_edges, PathEdge, ResourceTracker, ValueCheck,
+};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE775",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions returning a new descriptor or handle.
+    acquisition_symbols: Vec<String>,
+    /// Extern functions releasing the descriptor passed as the parameter with the given index.
+    release_symbols: BTreeMap<String, usize>,
+    /// Extern functions that may keep the descriptors passed to them as parameters,
+    /// e.g. `fdopen`, whose returned handle owns the descriptor.
+    #[serde(default)]
+    escaping_symbols: Vec<String>,
+    /// Functions whose return terminates the program, e.g. `main`.
+    /// Descriptors still open at their exits are not reported.
+    #[serde(default)]
+    program_exit_functions: Vec<String>,
+}
+
+/// The descriptors known to the analysis at some point of a function.
+/// Descriptors are identified by the TIDs of the calls acquiring them.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub(crate) struct DescriptorState {
+    /// The descriptors that registers may contain.
+    registers: HashMap<Variable, BTreeSet<Tid>>,
+    /// The descriptors that memory cells in the stack frame of the function may contain.
+    /// The memory cells are given by the abstract object and the offset into it.
+    memory: HashMap<(AbstractIdentifier, i64), BTreeSet<Tid>>,
+    /// The descriptors that are acquired on all paths to this point
+    /// and neither released nor escaped on any of them.
+    open: BTreeSet<Tid>,
+    /// The descriptors that are released on some path to this point (after their last acquisition),
+    /// mapped to the calls releasing them.
+    released: BTreeMap<Tid, BTreeSet<Tid>>,
+}
+
+impl DescriptorState {
+    /// Get the descriptors that the value of the expression may be.
+    pub(crate) fn eval(&self, expression: &Expression) -> BTreeSet<Tid> {
+        match expression {
+            Expression::Var(var) => self.registers.get(var).cloned().unwrap_or_default(),
+            Expression::Cast { arg, .. } | Expression::Subpiece { arg, .. } => self.eval(arg),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Get the calls that may have released the descriptor on a path to this point.
+    pub(crate) fn get_releases(&self, descriptor: &Tid) -> Option<&BTreeSet<Tid>> {
+        self.released.get(descriptor)
+    }
+
+    /// Set the descriptors that the register may contain.
+    fn set_register(&mut self, var: &Variable, descriptors: BTreeSet<Tid>) {
+        if descriptors.is_empty() {
+            self.registers.remove(var);
+        } else {
+            self.registers.insert(var.clone(), descriptors);
+        }
+    }
+
+    /// Merge the state with the state of another path to the same point.
+    /// Returns whether the state changed.
+    fn merge_with(&mut self, other: &DescriptorState) -> bool {
+        let old_state = self.clone();
+        for (var, descriptors) in other.registers.iter() {
+            self.registers
+                .entry(var.clone())
+                .or_default()
+                .extend(descriptors.iter().cloned());
+        }
+        for (cell, descriptors) in other.memory.iter() {
+            self.memory
+                .entry(cell.clone())
+                .or_default()
+                .extend(descriptors.iter().cloned());
+        }
+        self.open = self.open.intersection(&other.open).cloned().collect();
+        for (descriptor, release_calls) in other.released.iter() {
+            self.released
+                .entry(descriptor.clone())
+                .or_default()
+                .extend(release_calls.iter().cloned());
+        }
+        *self != old_state
+    }
+}
+
+/// The target of a memory access relative to the stack frame of the function.
+enum AccessTarget {
+    /// The memory cell in the stack frame given by the abstract object and the offset into it.
+    StackCell(AbstractIdentifier, i64),
+    /// The target is unknown, e.g. because the Pointer Inference analysis did not reach the access.
+    Unknown,
+    /// The target may be outside of the stack frame.
+    Outside,
+}
+
+/// A call releasing a descriptor that may already have been released on a path to the call.
+pub(crate) struct DoubleRelease<'a> {
+    /// The call releasing the descriptor again.
+    pub call: &'a Term<Jmp>,
+    /// The extern symbol called.
+    pub symbol: &'a ExternSymbol,
+    /// The descriptor, identified by the call acquiring it.
+    pub descriptor: Tid,
+    /// The calls that may have released the descriptor before.
+    pub previous_releases: BTreeSet<Tid>,
+}
+
+/// The [`ResourceTracker`] for file descriptors and handles.
+pub(crate) struct DescriptorTracker<'a> {
+    /// The project.
+    project: &'a Project,
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+    /// The TIDs of all extern symbols.
+    extern_symbols: HashSet<&'a Tid>,
+    /// The extern symbols acquiring descriptors.
+    acquisition_symbols: HashMap<Tid, &'a ExternSymbol>,
+    /// The extern symbols writing two new descriptors to the array passed as the parameter with the given index.
+    array_acquisition_symbols: HashMap<&'a Tid, (&'a ExternSymbol, usize)>,
+    /// The extern symbols releasing descriptors, together with the index of the descriptor parameter.
+    release_symbols: HashMap<&'a Tid, (&'a ExternSymbol, usize)>,
+    /// The extern symbols that may keep the descriptors passed to them.
+    escaping_symbols: HashMap<Tid, &'a ExternSymbol>,
+    /// The standard calling convention of the project, if known.
+    calling_convention: Option<&'a CallingConvention>,
+    /// The descriptor states at the start of the analyzed blocks.
+    block_start_states: HashMap<&'a Tid, DescriptorState>,
+    /// Maps the TIDs of `Def` terms to the blocks containing them.
+    def_blocks: HashMap<&'a Tid, &'a Term<Blk>>,
+}
+
+impl<'a> DescriptorTracker<'a> {
+    /// Create the tracker and run the analysis on all functions that call one of the acquisition symbols.
+    pub(crate) fn new(
+        project: &'a Project,
+        pointer_inference: &'a PointerInference<'a>,
+        acquisition_symbols: &[String],
+        release_symbols: &BTreeMap<String, usize>,
+        escaping_symbols: &[String],
+    ) -> DescriptorTracker<'a> {
+        DescriptorTracker::create(
+            project,
+            pointer_inference,
+            acquisition_symbols,
+            &BTreeMap::new(),
+            release_symbols,
+            escaping_symbols,
+            false,
+        )
+    }
+
+    /// Create the tracker and run the analysis on all functions of the program.
+    /// The values of the parameter registers at the start of a function are tracked as descriptors as well.
+    /// They are identified by [`DescriptorTracker::get_parameter_descriptor`].
+    pub(crate) fn with_parameters(
+        project: &'a Project,
+        pointer_inference: &'a PointerInference<'a>,
+        acquisition_symbols: &[String],
+        release_symbols: &BTreeMap<String, usize>,
+        escaping_symbols: &[String],
+    ) -> DescriptorTracker<'a> {
+        DescriptorTracker::create(
+            project,
+            pointer_inference,
+            acquisition_symbols,
+            &BTreeMap::new(),
+            release_symbols,
+            escaping_symbols,
+            true,
+        )
+    }
+
+    /// Create the tracker and run the analysis on all functions that call one of the acquisition symbols
+    /// or one of the `array_acquisition_symbols`.
+    /// These write two new descriptors (e.g. the ends of a pipe) to the array passed as the parameter with the given index.
+    /// The descriptors are identified by [`DescriptorTracker::get_array_descriptor`].
+    pub(crate) fn with_descriptor_arrays(
+        project: &'a Project,
+        pointer_inference: &'a PointerInference<'a>,
+        acquisition_symbols: &[String],
+        array_acquisition_symbols: &BTreeMap<String, usize>,
+        release_symbols: &BTreeMap<String, usize>,
+        escaping_symbols: &[String],
+    ) -> DescriptorTracker<'a> {
+        DescriptorTracker::create(
+            project,
+            pointer_inference,
+            acquisition_symbols,
+            array_acquisition_symbols,
+            release_symbols,
+            escaping_symbols,
+            false,
+        )
+    }
+
+    /// Create the tracker and run the analysis.
+    /// If `track_parameters` is set, all functions are analyzed with their parameter registers as descriptors.
+    /// Else only functions calling one of the acquisition symbols are analyzed.
+    fn create(
+        project: &'a Project,
+        pointer_inference: &'a PointerInference<'a>,
+        acquisition_symbols: &[String],
+        array_acquisition_symbols: &BTreeMap<String, usize>,
+        release_symbols: &BTreeMap<String, usize>,
+        escaping_symbols: &[String],
+        track_parameters: bool,
+    ) -> DescriptorTracker<'a> {
+        let get_parameter_symbols = |symbols: &BTreeMap<String, usize>| {
+            project
+                .program
+                .term
+                .extern_symbols
+                .iter()
+                .filter_map(|symbol| {
+                    symbols
+                        .get(&symbol.name)
+                        .map(|index| (&symbol.tid, (symbol, *index)))
+                })
+                .collect()
+        };
+        let mut tracker = DescriptorTracker {
+            project,
+            pointer_inference,
+            extern_symbols: project
+                .program
+                .term
+                .extern_symbols
+                .iter()
+                .map(|symbol| &symbol.tid)
+                .collect(),
+            acquisition_symbols: get_symbol_map(project, acquisition_symbols),
+            array_acquisition_symbols: get_parameter_symbols(array_acquisition_symbols),
+            release_symbols: get_parameter_symbols(release_symbols),
+            escaping_symbols: get_symbol_map(project, escaping_symbols),
+            calling_convention: project.get_standard_calling_convention(),
+            block_start_states: HashMap::new(),
+            def_blocks: HashMap::new(),
+        };
+        for sub in project.program.term.subs.iter() {
+            let acquires_descriptors = sub.term.blocks.iter().any(|block| {
+                block.term.jmps.iter().any(|jmp| {
+                    matches!(&jmp.term, Jmp::Call { target, .. } if tracker.acquisition_symbols.contains_key(target) || tracker.array_acquisition_symbols.contains_key(target))
+                })
+            });
+            if track_parameters {
+                let state = tracker.get_parameter_state(sub);
+                tracker.analyze_sub(sub, state);
+            } else if acquires_descriptors {
+                tracker.analyze_sub(sub, DescriptorState::default());
+            }
+        }
+        tracker
+    }
+
+    /// Get the descriptor identifying the value of the parameter register at the start of the function.
+    pub(crate) fn get_parameter_descriptor(sub: &Term<Sub>, register: &str) -> Tid {
+        sub.tid
+            .clone()
+            .with_id_suffix(&format!("_param_{}", register))
+    }
+
+    /// Get the descriptor identifying the descriptor with the given index
+    /// in the array written by the call to an array acquisition symbol.
+    pub(crate) fn get_array_descriptor(call_tid: &Tid, index: usize) -> Tid {
+        call_tid.clone().with_id_suffix(&format!("_fd_{}", index))
+    }
+
+    /// Get the state at the start of the function, where each parameter register contains its own descriptor.
+    fn get_parameter_state(&self, sub: &Term<Sub>) -> DescriptorState {
+        let mut state = DescriptorState::default();
+        let parameter_registers = self
+            .calling_convention
+            .map(|cconv| cconv.parameter_register.as_slice())
+            .unwrap_or_default();
+        for register in parameter_registers {
+            let var = Variable {
+                name: register.clone(),
+                size: self.project.get_pointer_bytesize(),
+                is_temp: false,
+            };
+            state.set_register(
+                &var,
+                vec![Self::get_parameter_descriptor(sub, register)]
+                    .into_iter()
+                    .collect(),
+            );
+        }
+        state
+    }
+
+    /// Compute the descriptor states at the start of the blocks of the function.
+    fn analyze_sub(&mut self, sub: &'a Term<Sub>, initial_state: DescriptorState) {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, DescriptorState> = HashMap::new();
+        let mut worklist: Vec<&Tid> = Vec::new();
+        if let Some(first_block) = sub.term.blocks.first() {
+            block_states.insert(&first_block.tid, initial_state);
+            worklist.push(&first_block.tid);
+        }
+        let mut in_worklist: HashSet<&Tid> = worklist.iter().copied().collect();
+        while let Some(block_tid) = worklist.pop() {
+            in_worklist.remove(block_tid);
+            let block = blocks[block_tid];
+            let mut state = block_states[block_tid].clone();
+            for def in block.term.defs.iter() {
+                self.handle_def(&mut state, def);
+            }
+            for jmp in block.term.jmps.iter() {
+                let (successor, successor_state) = match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => (target, state.clone()),
+                    Jmp::Call {
+                        return_: Some(return_target),
+                        ..
+                    }
+                    | Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => {
+                        let mut state_after_call = state.clone();
+                        self.handle_call(&mut state_after_call, jmp);
+                        (return_target, state_after_call)
+                    }
+                    Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } => (return_target, state.clone()),
+                    _ => continue,
+                };
+                if !blocks.contains_key(successor) {
+                    continue;
+                }
+                let changed = match block_states.get_mut(successor) {
+                    Some(known_state) => known_state.merge_with(&successor_state),
+                    None => {
+                        block_states.insert(successor, successor_state);
+                        true
+                    }
+                };
+                if changed && in_worklist.insert(successor) {
+                    worklist.push(successor);
+                }
+            }
+        }
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                self.def_blocks.insert(&def.tid, block);
+            }
+        }
+        self.block_start_states.extend(block_states);
+    }
+
+    /// Determine the target of the memory access through the address right before the term with the given TID.
+    fn get_access_target(&self, tid: &Tid, address: &Expression) -> AccessTarget {
+        let pi_state = match self.pointer_inference.get_state_at_tid(tid) {
+            Some(pi_state) => pi_state,
+            None => return AccessTarget::Unknown,
+        };
+        match pi_state.eval(address) {
+            Data::Pointer(pointer) => match pointer.targets().iter().collect::<Vec<_>>()[..] {
+                [(id, offset)] if *id == pi_state.stack_id => {
+                    match offset.try_to_bitvec().map(|offset| offset.try_to_i64()) {
+                        Ok(Ok(offset)) => AccessTarget::StackCell(id.clone(), offset),
+                        _ => AccessTarget::Unknown,
+                    }
+                }
+                _ => AccessTarget::Outside,
+            },
+            _ => AccessTarget::Outside,
+        }
+    }
+
+    /// Get the descriptors that the memory cell targeted by the address may contain.
+    fn load(&self, state: &DescriptorState, tid: &Tid, address: &Expression) -> BTreeSet<Tid> {
+        match self.get_access_target(tid, address) {
+            AccessTarget::StackCell(id, offset) => {
+                state.memory.get(&(id, offset)).cloned().unwrap_or_default()
+            }
+            AccessTarget::Unknown | AccessTarget::Outside => BTreeSet::new(),
+        }
+    }
+
+    /// Update the state according to the effects of the `Def` term.
+    fn handle_def(&self, state: &mut DescriptorState, def: &Term<Def>) {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let descriptors = state.eval(value);
+                state.set_register(var, descriptors);
+            }
+            Def::Load { var, address } => {
+                let descriptors = self.load(state, &def.tid, address);
+                state.set_register(var, descriptors);
+            }
+            Def::Store { address, value } => {
+                let descriptors = state.eval(value);
+                match self.get_access_target(&def.tid, address) {
+                    AccessTarget::StackCell(id, offset) => {
+                        if descriptors.is_empty() {
+                            state.memory.remove(&(id, offset));
+                        } else {
+                            state.memory.insert((id, offset), descriptors);
+                        }
+                    }
+                    AccessTarget::Unknown => (),
+                    AccessTarget::Outside => {
+                        // The descriptors escape to memory outside of the stack frame.
+                        state.open = state.open.difference(&descriptors).cloned().collect();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the descriptors passed as the parameter to a call to the extern symbol.
+    pub(crate) fn eval_parameter(
+        &self,
+        state: &DescriptorState,
+        call: &Term<Jmp>,
+        parameter: &Arg,
+    ) -> BTreeSet<Tid> {
+        match parameter {
+            Arg::Register(var) => state.eval(&Expression::Var(var.clone())),
+            Arg::Stack { offset, .. } => {
+                let address = Expression::Var(self.project.stack_pointer_register.clone())
+                    .plus_const(*offset);
+                self.load(state, &call.tid, &address)
+            }
+        }
+    }
+
+    /// Get the release symbol called and the descriptors it releases, if the call is a call to a release symbol.
+    fn get_released_descriptors(
+        &self,
+        state: &DescriptorState,
+        call: &Term<Jmp>,
+    ) -> Option<(&'a ExternSymbol, BTreeSet<Tid>)> {
+        match &call.term {
+            Jmp::Call { target, .. } => {
+                let (symbol, index) = self.release_symbols.get(target)?;
+                let released = symbol
+                    .parameters
+                    .get(*index)
+                    .map(|parameter| self.eval_parameter(state, call, parameter))
+                    .unwrap_or_default();
+                Some((*symbol, released))
+            }
+            _ => None,
+        }
+    }
+
+    /// Update the state according to the effects of the call.
+    fn handle_call(&self, state: &mut DescriptorState, call: &Term<Jmp>) {
+        let mut escaped = BTreeSet::new();
+        let mut released = BTreeSet::new();
+        let mut acquisition_symbol = None;
+        let mut acquired_array = None;
+        match &call.term {
+            Jmp::Call { target, .. } if self.extern_symbols.contains(target) => {
+                if let Some((_, descriptors)) = self.get_released_descriptors(state, call) {
+                    released = descriptors;
+                }
+                if let Some(symbol) = self.escaping_symbols.get(target) {
+                    for parameter in symbol.parameters.iter() {
+                        escaped.append(&mut self.eval_parameter(state, call, parameter));
+                    }
+                }
+                acquisition_symbol = self.acquisition_symbols.get(target);
+                if let Some((symbol, index)) = self.array_acquisition_symbols.get(target) {
+                    if let Some(Arg::Register(var)) = symbol.parameters.get(*index) {
+                        acquired_array = match self
+                            .get_access_target(&call.tid, &Expression::Var(var.clone()))
+                        {
+                            AccessTarget::StackCell(id, offset) => Some((id, offset)),
+                            AccessTarget::Unknown | AccessTarget::Outside => None,
+                        };
+                    }
+                }
+            }
+            Jmp::Call { .. } | Jmp::CallInd { .. } => {
+                // The callee may take over the descriptors passed to it.
+                let parameter_registers = self
+                    .calling_convention
+                    .map(|cconv| cconv.parameter_register.as_slice())
+                    .unwrap_or_default();
+                for (var, descriptors) in state.registers.iter() {
+                    if parameter_registers.contains(&var.name) {
+                        escaped.extend(descriptors.iter().cloned());
+                    }
+                }
+            }
+            _ => (),
+        }
+        state.open = state
+            .open
+            .iter()
+            .filter(|descriptor| !released.contains(descriptor) && !escaped.contains(descriptor))
+            .cloned()
+            .collect();
+        for descriptor in released {
+            state
+                .released
+                .entry(descriptor)
+                .or_default()
+                .insert(call.tid.clone());
+        }
+        let callee_saved_registers = self
+            .calling_convention
+            .map(|cconv| cconv.callee_saved_register.as_slice())
+            .unwrap_or_default();
+        state
+            .registers
+            .retain(|var, _| callee_saved_registers.contains(&var.name));
+        if let Some(symbol) = acquisition_symbol {
+            if let Some(Arg::Register(return_register)) = symbol.return_values.first() {
+                state.set_register(
+                    return_register,
+                    vec![call.tid.clone()].into_iter().collect(),
+                );
+                state.open.insert(call.tid.clone());
+                state.released.remove(&call.tid);
+            }
+        }
+        if let Some((id, offset)) = acquired_array {
+            // The array contains two descriptors of type `int`.
+            for index in 0..2 {
+                let descriptor = Self::get_array_descriptor(&call.tid, index);
+                state.memory.insert(
+                    (id.clone(), offset + 4 * index as i64),
+                    vec![descriptor.clone()].into_iter().collect(),
+                );
+                state.open.insert(descriptor.clone());
+                state.released.remove(&descriptor);
+            }
+        }
+    }
+
+    /// Get the descriptor state at the end of the block (but before the effects of its jumps).
+    fn get_state_at_block_end(&self, block: &Term<Blk>) -> Option<DescriptorState> {
+        let mut state = self.block_start_states.get(&block.tid)?.clone();
+        for def in block.term.defs.iter() {
+            self.handle_def(&mut state, def);
+        }
+        Some(state)
+    }
+
+    /// Get the calls in the analyzed blocks of the function together with the descriptor states right before them.
+    pub(crate) fn get_states_at_calls(
+        &self,
+        sub: &'a Term<Sub>,
+    ) -> Vec<(&'a Term<Jmp>, DescriptorState)> {
+        let mut states = Vec::new();
+        for block in sub.term.blocks.iter() {
+            let state = match self.get_state_at_block_end(block) {
+                Some(state) => state,
+                None => continue,
+            };
+            for jmp in block.term.jmps.iter() {
+                if matches!(jmp.term, Jmp::Call { .. } | Jmp::CallInd { .. }) {
+                    states.push((jmp, state.clone()));
+                }
+            }
+        }
+        states
+    }
+
+    /// Get the calls in the function that release descriptors
+    /// which may already have been released on a path to the call.
+    pub(crate) fn find_double_releases(&self, sub: &'a Term<Sub>) -> Vec<DoubleRelease<'a>> {
+        let mut double_releases = Vec::new();
+        for (call, state) in self.get_states_at_calls(sub) {
+            let (symbol, released) = match self.get_released_descriptors(&state, call) {
+                Some(release) => release,
+                None => continue,
+            };
+            for descriptor in released {
+                if let Some(previous_releases) = state.get_releases(&descriptor) {
+                    double_releases.push(DoubleRelease {
+                        call,
+                        symbol,
+                        descriptor,
+                        previous_releases: previous_releases.clone(),
+                    });
+                }
+            }
+        }
+        double_releases
+    }
+}
+
+impl<'a> ResourceTracker<'a> for DescriptorTracker<'a> {
+    type Resource = Tid;
+    type State = DescriptorState;
+
+    /// Get the descriptor states at the return instruction for each incoming jump.
+    fn get_states_at_return(
+        &self,
+        sub: &'a Term<Sub>,
+        return_jmp: &'a Term<Jmp>,
+    ) -> Vec<(Option<PathEdge<'a>>, DescriptorState)> {
+        let block = match sub
+            .term
+            .blocks
+            .iter()
+            .find(|block| block.term.jmps.contains(return_jmp))
+        {
+            Some(block) => block,
+            None => return Vec::new(),
+        };
+        let incoming_edges = get_incoming_edges(sub);
+        let mut states = Vec::new();
+        let mut add_merged_state = true;
+        if let Some(edges) = incoming_edges.get(&block.tid) {
+            add_merged_state = false;
+            for edge in edges {
+                match edge.and_then(|edge| Some((edge, self.get_state_at_block_end(edge.source)?)))
+                {
+                    Some((edge, mut state)) => {
+                        for def in block.term.defs.iter() {
+                            self.handle_def(&mut state, def);
+                        }
+                        states.push((Some(edge), state));
+                    }
+                    None => add_merged_state = true,
+                }
+            }
+        }
+        if add_merged_state || states.is_empty() {
+            if let Some(state) = self.get_state_at_block_end(block) {
+                states.push((None, state));
+            }
+        }
+        states
+    }
+
+    fn get_leaked_resources(&self, state: &DescriptorState) -> BTreeSet<Tid> {
+        let return_registers = self
+            .calling_convention
+            .map(|cconv| cconv.return_register.as_slice())
+            .unwrap_or_default();
+        let returned: BTreeSet<&Tid> = state
+            .registers
+            .iter()
+            .filter(|(var, _)| return_registers.contains(&var.name))
+            .flat_map(|(_, descriptors)| descriptors.iter())
+            .collect();
+        state
+            .open
+            .iter()
+            .filter(|descriptor| !returned.contains(descriptor))
+            .cloned()
+            .collect()
+    }
+
+    /// The acquisition of a descriptor failed if it is checked to be negative (e.g. `fd < 0` or `fd == -1`)
+    /// or to be zero (e.g. `file == NULL`).
+    fn get_failed_resources(&self, check: &ValueCheck) -> BTreeSet<Tid> {
+        if !check.is_negative_check() && !check.is_zero_check() {
+            return BTreeSet::new();
+        }
+        let block = match self.def_blocks.get(check.tid) {
+            Some(block) => block,
+            None => return BTreeSet::new(),
+        };
+        let mut state = match self.block_start_states.get(&block.tid) {
+            Some(state) => state.clone(),
+            None => return BTreeSet::new(),
+        };
+        for def in block.term.defs.iter() {
+            if &def.tid == check.tid {
+                break;
+            }
+            self.handle_def(&mut state, def);
+        }
+        state.eval(&check.value)
+    }
+
+    fn get_acquisition_site(&self, descriptor: &Tid) -> Tid {
+        descriptor.clone()
+    }
+}
+
+/// Generate the CWE warning for a descriptor acquired at the given callsite
+/// that leaks at the given return instruction.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    return_jmp: &Term<Jmp>,
+    acquisition_site: &Tid,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Missing Release of File Descriptor or Handle after Effective Lifetime) The descriptor acquired at {} may leak when {} returns at {}",
+            acquisition_site.address, sub.term.display_name(), return_jmp.tid.address
+        ),
+    )
+    .tids(vec![format!("{}", return_jmp.tid), format!("{}", acquisition_site)])
+    .addresses(vec![
+        return_jmp.tid.address.clone(),
+        acquisition_site.address.clone(),
+    ])
+    .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Run the CWE check.
+/// For each return instruction we check whether descriptors are lost on a path to it without being released.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let tracker = DescriptorTracker::new(
+        project,
+        pointer_inference,
+        &config.acquisition_symbols,
+        &config.release_symbols,
+        &config.escaping_symbols,
+    );
+    let cwe_warnings = find_leaks(project, &tracker, &config.program_exit_functions)
+        .into_iter()
+        .map(|leak| generate_cwe_warning(leak.sub, leak.return_jmp, &leak.acquisition_site))
+        .collect();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    fn mock_symbol(name: &str) -> ExternSymbol {
+        ExternSymbol {
+            tid: Tid::new(name),
+            name: name.to_string(),
+            ..ExternSymbol::mock()
+        }
+    }
+
+    fn tids(names: &[&str]) -> BTreeSet<Tid> {
+        names.iter().map(|name| Tid::new(*name)).collect()
+    }
+
+    #[test]
+    fn state_merge() {
+        let rax = Variable::mock("RAX", 8);
+        let mut state = DescriptorState::default();
+        state.set_register(&rax, tids(&["open_1"]));
+        state.open = tids(&["open_1", "open_2"]);
+        let mut other = DescriptorState::default();
+        other.set_register(&rax, tids(&["open_2"]));
+        other.open = tids(&["open_2"]);
+
+        assert!(state.merge_with(&other));
+        assert!(!state.merge_with(&other));
+        assert_eq!(
+            state.eval(&Expression::Var(rax.clone()).subpiece(ByteSize::new(0), ByteSize::new(4))),
+            tids(&["open_1", "open_2"])
+        );
+        assert_eq!(state.open, tids(&["open_2"]));
+        state.set_register(&rax, BTreeSet::new());
+        assert!(state.registers.is_empty());
+    }
+
+    #[test]
+    fn leak_on_early_return() {
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let sf = Variable::mock("SF", 1);
+        let zf = Variable::mock("ZF", 1);
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps("entry", Vec::new(), vec![Jmp::mock_call("open", "check")]),
+            Blk::mock_with_jmps(
+                "check",
+                vec![
+                    Def::assign(
+                        "sign",
+                        sf.clone(),
+                        Expression::BinOp {
+                            op: BinOpType::IntSLess,
+                            lhs: Box::new(Expression::Var(rax.clone())),
+                            rhs: Box::new(Expression::const_from_i64(0)),
+                        },
+                    ),
+                    Def::assign("save", rbp.clone(), Expression::Var(rax.clone())),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("fail"),
+                        condition: Expression::Var(sf),
+                    },
+                    Jmp::Branch(Tid::new("use")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "use",
+                Vec::new(),
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("release"),
+                        condition: Expression::Var(zf),
+                    },
+                    Jmp::Branch(Tid::new("exit")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "release",
+                vec![Def::assign("arg", rdi, Expression::Var(rbp))],
+                vec![Jmp::mock_call("close", "exit")],
+            ),
+            Blk::mock_with_jmps("fail", Vec::new(), vec![Jmp::Branch(Tid::new("exit"))]),
+            Blk::mock_with_jmps(
+                "exit",
+                vec![Def::assign(
+                    "result",
+                    rax.clone(),
+                    Expression::const_from_i64(0),
+                )],
+                vec![Jmp::Return(Expression::const_from_i64(0))],
+            ),
+        ];
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![mock_symbol("open"), mock_symbol("close")];
+        project.program.term.subs = vec![sub];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let config = Config {
+            acquisition_symbols: vec!["open".to_string()],
+            release_symbols: vec![("close".to_string(), 0)].into_iter().collect(),
+            escaping_symbols: Vec::new(),
+            program_exit_functions: vec!["main".to_string()],
+        };
+        let tracker = DescriptorTracker::new(
+            &project,
+            &pointer_inference,
+            &config.acquisition_symbols,
+            &config.release_symbols,
+            &config.escaping_symbols,
+        );
+
+        // The descriptor is saved in the callee-saved register RBP across the call to `close`.
+        let exit_states = tracker.get_states_at_return(
+            &project.program.term.subs[0],
+            &project.program.term.subs[0].term.blocks[5].term.jmps[0],
+        );
+        assert_eq!(exit_states.len(), 3);
+        let leaks = find_leaks(&project, &tracker, &config.program_exit_functions);
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].acquisition_site, Tid::new("entry_jmp_0"));
+        // The path through the failure check is not reported.
+        let fail_state = exit_states
+            .iter()
+            .find(|(edge, _)| matches!(edge, Some(edge) if edge.source.tid == Tid::new("fail")))
+            .unwrap();
+        assert_eq!(
+            tracker.get_leaked_resources(&fail_state.1),
+            tids(&["entry_jmp_0"])
+        );
+        let failed = get_failed_resources_for_test(&tracker, &project.program.term.subs[0]);
+        assert_eq!(failed, tids(&["entry_jmp_0"]));
+
+        // Without a configured release symbol the descriptor also leaks after the call to `close`.
+        let config = Config {
+            release_symbols: BTreeMap::new(),
+            ..config
+        };
+        let tracker = DescriptorTracker::new(
+            &project,
+            &pointer_inference,
+            &config.acquisition_symbols,
+            &config.release_symbols,
+            &config.escaping_symbols,
+        );
+        let exit_block = &project.program.term.subs[0].term.blocks[5];
+        assert_eq!(
+            tracker.get_state_at_block_end(exit_block).unwrap().open,
+            tids(&["entry_jmp_0"])
+        );
+    }
+
+    /// Get the descriptors that are checked to be invalid in the block `check`.
+    fn get_failed_resources_for_test(
+        tracker: &DescriptorTracker,
+        sub: &Term<Sub>,
+    ) -> BTreeSet<Tid> {
+        let block = &sub.term.blocks[1];
+        let check =
+            crate::analysis::resource_lifetime::get_value_check(block, &block.term.jmps[0], None)
+                .unwrap();
+        tracker.get_failed_resources(&check)
+    }
+}
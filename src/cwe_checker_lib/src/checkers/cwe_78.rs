@@ -50,7 +50,10 @@ use crate::{
     },
     intermediate_representation::{ExternSymbol, Jmp, Project, Sub},
     prelude::*,
-    utils::log::{CweWarning, LogMessage},
+    utils::{
+        log::{CweWarning, LogMessage},
+        parse_config,
+    },
     AnalysisResults, CweModule,
 };
 
@@ -69,10 +72,13 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE78",
     version: "0.1",
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
 };
 
 /// The configuration struct
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// The names of the system call symbols
     system_symbols: Vec<String>,
@@ -85,19 +91,19 @@ pub struct Config {
 /// This check searches for system calls and sets their parameters as taint source if available.
 /// Then the fixpoint computation is executed and its result may generate cwe warnings if
 /// the parameters can be tracked back to user inputs
-pub fn check_cwe(
-    analysis_results: &AnalysisResults,
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
-    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let pointer_inference_results = analysis_results.pointer_inference().unwrap();
 
     let mut cwe_78_graph = analysis_results.control_flow_graph.clone();
     cwe_78_graph.reverse();
 
     let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
 
-    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
     let system_symbols =
         crate::utils::symbol_utils::get_symbol_map(project, &config.system_symbols[..]);
 
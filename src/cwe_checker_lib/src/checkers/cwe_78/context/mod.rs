@@ -7,12 +7,12 @@ use petgraph::graph::NodeIndex;
 
 use super::{state::State, BlockMaps, SymbolMaps, CWE_MODULE};
 use crate::{
-    abstract_domain::{AbstractDomain, DataDomain, IntervalDomain},
+    abstract_domain::{AbstractDomain, DataDomain},
     analysis::{
         forward_interprocedural_fixpoint::Context as PiContext, graph::Graph,
         interprocedural_fixpoint_generic::NodeValue,
         pointer_inference::PointerInference as PointerInferenceComputation,
-        pointer_inference::State as PointerInferenceState,
+        pointer_inference::State as PointerInferenceState, pointer_inference::ValueDomain,
     },
     checkers::cwe_476::Taint,
     intermediate_representation::*,
@@ -194,7 +194,7 @@ impl<'a> Context<'a> {
             &self.project.stack_pointer_register,
             self.runtime_memory_image,
         ) {
-            let temp_mem_taints: Vec<DataDomain<IntervalDomain>> =
+            let temp_mem_taints: Vec<DataDomain<ValueDomain>> =
                 self.add_temporary_callee_saved_register_taints_to_mem_taints(pi_state, state);
 
             if state.address_points_to_taint(address.clone(), pi_state) {
@@ -216,8 +216,8 @@ impl<'a> Context<'a> {
         &self,
         pi_state: &PointerInferenceState,
         state: &mut State,
-    ) -> Vec<DataDomain<IntervalDomain>> {
-        let mut temp_mem_taints: Vec<DataDomain<IntervalDomain>> = Vec::new();
+    ) -> Vec<DataDomain<ValueDomain>> {
+        let mut temp_mem_taints: Vec<DataDomain<ValueDomain>> = Vec::new();
         if let Some(standard_cconv) = self.project.get_standard_calling_convention() {
             for (var, _) in state
                 .get_callee_saved_register_taints(standard_cconv)
@@ -277,7 +277,12 @@ impl<'a> Context<'a> {
                     .is_some()
                 {
                     self.generate_cwe_warning(
-                        &new_state.get_current_sub().as_ref().unwrap().term.name,
+                        new_state
+                            .get_current_sub()
+                            .as_ref()
+                            .unwrap()
+                            .term
+                            .display_name(),
                     );
                 }
                 if let Some(NodeValue::Value(pi_state)) = self
@@ -22,7 +22,7 @@ use crate::{
     intermediate_representation::{Program, Sub, Term, Tid},
     utils::{
         log::{CweWarning, LogMessage},
-        symbol_utils::{find_symbol, get_calls_to_symbols},
+        symbol_utils::{find_symbols, get_calls_to_symbols},
     },
 };
 
@@ -33,6 +33,8 @@ pub static CWE_MODULE: crate::CweModule = crate::CweModule {
     name: "CWE782",
     version: VERSION,
     run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
 };
 
 /// check whether the ioctl symbol is called by any subroutine. If so, generate the cwe warning.
@@ -75,8 +77,8 @@ pub fn check_cwe(
     let project = analysis_results.project;
     let prog: &Term<Program> = &project.program;
     let mut warnings: Vec<CweWarning> = Vec::new();
-    if let Some((tid, name)) = find_symbol(prog, "ioctl") {
-        let symbol: &HashMap<&Tid, &str> = &[(tid, name)].iter().cloned().collect();
+    let symbol: &HashMap<&Tid, &str> = &find_symbols(prog, "ioctl").into_iter().collect();
+    if !symbol.is_empty() {
         prog.term
             .subs
             .iter()
@@ -0,0 +1,965 @@
+//! This module implements a check for CWE-789: Memory Allocation with Excessive Size Value.
+//!
+//! If the size of a memory allocation is controlled by untrusted input and not limited by the program,
+//! an attacker can make the program allocate excessive amounts of memory and exhaust the available memory.
+//! In contrast to [CWE-190](crate::checkers::cwe_190) no integer overflow is needed for this.
+//!
+//! See <https://cwe.mitre.org/data/definitions/789.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We reuse the intraprocedural taint analysis of the [CWE-129 check](crate::checkers::cwe_129)
+//! with the taint sources configured as `taint_sources` in config.json.
+//! For each call to one of the configured `allocation_symbols` we check whether one of its size parameters is tainted.
+//! A tainted size parameter is considered bounded
+//! - if the interval domain of the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//!   shows that it is at most `max_allocation_size` (e.g. because it was masked with a constant),
+//! - or if a comparison in a block dominating the call bounds it by at most `max_allocation_size`
+//!   on all paths to the call.
+//!
+//! The compared value is matched with the size parameter through the def-use chains of the function.
+//! Both are followed backwards across copies, casts and loads from the same memory cell to their original definitions.
+//! The comparison bounds the size parameter if all original definitions of the size parameter
+//! are also original definitions of the compared value.
+//! Signed upper bounds only count if the value is also checked to be non-negative,
+//! since negative values are converted to huge sizes.
+//!
+//! A warning is generated for each call with a tainted and unbounded size parameter.
+//!
+//! ## False Positives
+//!
+//! - Bounds checks in other functions (e.g. in the caller of the function containing the allocation) are not recognized.
+//! - Bounds checks on values that are computed from the size parameter in other ways than by copies and casts
+//!   are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - The taint analysis is intraprocedural.
+//!   Sizes passed as parameters or returned from internal functions are not tracked.
+//! - Memory cells overwritten between the bounds check and the allocation are not recognized.
+//! - Allocations inside wrapper functions are only found if the wrapper is configured as an allocation symbol.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToInterval};
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference, State as PointerInferenceState};
+use crate::checkers::cwe_129;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE789",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The maximal number of definitions that are followed backwards
+/// when resolving conditions and the origins of values.
+const MAX_RESOLUTION_DEPTH: usize = 8;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Allocation functions mapped to the indices of their size parameters.
+    allocation_symbols: BTreeMap<String, Vec<usize>>,
+    /// The maximal allocation size in bytes that is not considered excessive.
+    max_allocation_size: u64,
+    /// The taint sources of the intraprocedural taint analysis.
+    /// See the [CWE-129 check](crate::checkers::cwe_129) for the possible options.
+    taint_sources: cwe_129::Config,
+}
+
+/// The original definition of a value, determined by following copies and casts backwards.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+enum ValueOrigin {
+    /// The value was defined by the term with the given TID.
+    Definition(Tid),
+    /// The value was loaded from the memory cell given by the abstract object and the offset into it.
+    MemoryCell(AbstractIdentifier, i64),
+    /// The value of the register at the start of the function.
+    Input(Variable),
+}
+
+/// A fact about a value that holds if a branch condition has a certain value.
+/// The value is identified by its origins.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Fact {
+    /// The value is at most `bound`.
+    /// If `signed` is set, the bound only holds when interpreting the value as a signed integer.
+    UpperBound {
+        origins: BTreeSet<ValueOrigin>,
+        bound: u64,
+        signed: bool,
+    },
+    /// The value is non-negative when interpreted as a signed integer.
+    NonNegative { origins: BTreeSet<ValueOrigin> },
+}
+
+impl Fact {
+    /// Get the origins of the value that the fact is about.
+    fn get_origins(&self) -> &BTreeSet<ValueOrigin> {
+        match self {
+            Fact::UpperBound { origins, .. } | Fact::NonNegative { origins } => origins,
+        }
+    }
+
+    /// Return a fact that holds if either `self` or `other` holds, if the facts concern the same value.
+    fn disjunction(&self, other: &Fact) -> Option<Fact> {
+        match (self, other) {
+            (
+                Fact::UpperBound {
+                    origins,
+                    bound,
+                    signed,
+                },
+                Fact::UpperBound {
+                    origins: other_origins,
+                    bound: other_bound,
+                    signed: other_signed,
+                },
+            ) if origins == other_origins => Some(Fact::UpperBound {
+                origins: origins.clone(),
+                bound: std::cmp::max(*bound, *other_bound),
+                signed: *signed || *other_signed,
+            }),
+            (
+                Fact::NonNegative { origins },
+                Fact::NonNegative {
+                    origins: other_origins,
+                },
+            ) if origins == other_origins => Some(self.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// A comparison of a value with a constant, normalized so that the value is on the left side.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    /// Get the comparison that holds if `self` does not hold.
+    fn negate(self) -> Comparison {
+        use Comparison::*;
+        match self {
+            Less => GreaterEqual,
+            LessEqual => Greater,
+            Greater => LessEqual,
+            GreaterEqual => Less,
+            Equal => NotEqual,
+            NotEqual => Equal,
+        }
+    }
+
+    /// Get the comparison that holds if the operands of `self` are swapped.
+    fn mirror(self) -> Comparison {
+        use Comparison::*;
+        match self {
+            Less => Greater,
+            LessEqual => GreaterEqual,
+            Greater => Less,
+            GreaterEqual => LessEqual,
+            Equal | NotEqual => self,
+        }
+    }
+}
+
+/// The context for resolving the branch conditions and value origins of a function.
+struct Context<'a> {
+    /// The def-use chains of the function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+}
+
+impl<'a> Context<'a> {
+    /// Get the `Def` term defining the register right before the term with the given TID,
+    /// if there is exactly one such definition.
+    fn get_unique_definition(&self, use_tid: &Tid, var: &Variable) -> Option<&'a Term<Def>> {
+        let mut reaching_defs = self.def_use_chains.defs_reaching(use_tid, var);
+        match (reaching_defs.next(), reaching_defs.next()) {
+            (Some(def_tid), None) => match self.def_use_chains.get_definition(def_tid)? {
+                Definition::Def(def) => Some(def),
+                Definition::Call(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Get the original definitions of the value of the expression right before the term with the given TID.
+    /// Returns an empty set if the expression is not a (possibly casted) register.
+    fn get_value_origins(&self, expression: &Expression, use_tid: &Tid) -> BTreeSet<ValueOrigin> {
+        let mut origins = BTreeSet::new();
+        self.add_value_origins(expression, use_tid, MAX_RESOLUTION_DEPTH, &mut origins);
+        origins
+    }
+
+    /// Add the original definitions of the value of the expression right before the term with the given TID
+    /// to the set of origins.
+    fn add_value_origins(
+        &self,
+        expression: &Expression,
+        use_tid: &Tid,
+        depth: usize,
+        origins: &mut BTreeSet<ValueOrigin>,
+    ) {
+        let var = match strip_casts(expression) {
+            Expression::Var(var) => var,
+            _ => return,
+        };
+        let reaching_defs: Vec<&Tid> = self.def_use_chains.defs_reaching(use_tid, var).collect();
+        if reaching_defs.is_empty() {
+            origins.insert(ValueOrigin::Input(var.clone()));
+        }
+        for def_tid in reaching_defs {
+            match self.def_use_chains.get_definition(def_tid) {
+                Some(Definition::Def(def)) => match &def.term {
+                    Def::Assign { value, .. }
+                        if depth > 0 && matches!(strip_casts(value), Expression::Var(_)) =>
+                    {
+                        self.add_value_origins(value, &def.tid, depth - 1, origins)
+                    }
+                    Def::Load { address, .. } => {
+                        let origin = self
+                            .pointer_inference
+                            .get_unique_memory_cell_at_tid(&def.tid, address)
+                            .map(|(id, offset)| ValueOrigin::MemoryCell(id, offset))
+                            .unwrap_or_else(|| ValueOrigin::Definition(def.tid.clone()));
+                        origins.insert(origin);
+                    }
+                    _ => {
+                        origins.insert(ValueOrigin::Definition(def.tid.clone()));
+                    }
+                },
+                _ => {
+                    origins.insert(ValueOrigin::Definition(def_tid.clone()));
+                }
+            }
+        }
+    }
+
+    /// Get the facts that hold if the condition evaluated right before the term with the given TID
+    /// has the value given by `holds`.
+    fn get_facts(
+        &self,
+        condition: &Expression,
+        use_tid: &Tid,
+        holds: bool,
+        depth: usize,
+    ) -> Vec<Fact> {
+        use BinOpType::*;
+        match condition {
+            Expression::Var(var) if depth > 0 => match self.get_unique_definition(use_tid, var) {
+                Some(Term {
+                    tid,
+                    term: Def::Assign { value, .. },
+                }) => self.get_facts(value, tid, holds, depth - 1),
+                _ => Vec::new(),
+            },
+            Expression::UnOp {
+                op: UnOpType::BoolNegate,
+                arg,
+            } => self.get_facts(arg, use_tid, !holds, depth),
+            Expression::Cast {
+                op: CastOpType::IntZExt,
+                arg,
+                ..
+            } => self.get_facts(arg, use_tid, holds, depth),
+            Expression::BinOp {
+                op: op @ (BoolAnd | BoolOr),
+                lhs,
+                rhs,
+            } => {
+                let lhs_facts = self.get_facts(lhs, use_tid, holds, depth);
+                let rhs_facts = self.get_facts(rhs, use_tid, holds, depth);
+                if (*op == BoolAnd) == holds {
+                    // Both operands have the value of the condition.
+                    lhs_facts.into_iter().chain(rhs_facts).collect()
+                } else {
+                    // At least one of the operands has the value of the condition.
+                    lhs_facts
+                        .iter()
+                        .flat_map(|lhs_fact| {
+                            rhs_facts
+                                .iter()
+                                .filter_map(move |rhs_fact| lhs_fact.disjunction(rhs_fact))
+                        })
+                        .collect()
+                }
+            }
+            Expression::BinOp { op, lhs, rhs } => {
+                self.get_comparison_facts(*op, lhs, rhs, use_tid, holds, depth)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the facts that hold if the comparison evaluated right before the term with the given TID
+    /// has the value given by `holds`.
+    ///
+    /// Besides comparisons of values with constants
+    /// we recognize comparisons of boolean values with zero,
+    /// comparisons of differences `x - c` with zero (i.e. `x == c`)
+    /// and comparisons of the overflow flag with the sign flag of the subtraction `x - c` (i.e. `x < c`).
+    fn get_comparison_facts(
+        &self,
+        op: BinOpType,
+        lhs: &Expression,
+        rhs: &Expression,
+        use_tid: &Tid,
+        holds: bool,
+        depth: usize,
+    ) -> Vec<Fact> {
+        use BinOpType::*;
+        let (comparison, signed) = match op {
+            IntLess => (Comparison::Less, false),
+            IntLessEqual => (Comparison::LessEqual, false),
+            IntSLess => (Comparison::Less, true),
+            IntSLessEqual => (Comparison::LessEqual, true),
+            IntEqual => (Comparison::Equal, false),
+            IntNotEqual => (Comparison::NotEqual, false),
+            _ => return Vec::new(),
+        };
+        let comparison = if holds {
+            comparison
+        } else {
+            comparison.negate()
+        };
+        if matches!(comparison, Comparison::Equal | Comparison::NotEqual) {
+            let is_equal = comparison == Comparison::Equal;
+            if let Some(facts) = self.get_signed_less_facts(lhs, rhs, use_tid, !is_equal) {
+                return facts;
+            }
+            if let (value, Expression::Const(constant)) | (Expression::Const(constant), value) =
+                (lhs, rhs)
+            {
+                if constant.is_zero() && depth > 0 {
+                    let boolean_facts = self.get_facts(value, use_tid, !is_equal, depth);
+                    if !boolean_facts.is_empty() {
+                        return boolean_facts;
+                    }
+                    if let Expression::Var(var) = value {
+                        if let Some(Term {
+                            tid,
+                            term:
+                                Def::Assign {
+                                    value:
+                                        Expression::BinOp {
+                                            op: IntSub,
+                                            lhs,
+                                            rhs,
+                                        },
+                                    ..
+                                },
+                        }) = self.get_unique_definition(use_tid, var)
+                        {
+                            return self.get_comparison_facts(
+                                IntEqual,
+                                lhs,
+                                rhs,
+                                tid,
+                                is_equal,
+                                depth - 1,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        let (value, constant, comparison) = match (lhs, rhs) {
+            (value, Expression::Const(constant)) => (value, constant, comparison),
+            (Expression::Const(constant), value) => (value, constant, comparison.mirror()),
+            _ => return Vec::new(),
+        };
+        let origins = self.get_value_origins(value, use_tid);
+        if origins.is_empty() {
+            return Vec::new();
+        }
+        get_comparison_fact(origins, constant, comparison, signed)
+            .into_iter()
+            .collect()
+    }
+
+    /// If one side of the (in)equality is the overflow flag `SBORROW(x, c)` of a subtraction,
+    /// interpret the (in)equality as the result of the comparison of the overflow flag with the sign flag
+    /// and return the facts for `x < c` (if `less_holds` is set) or `x >= c`.
+    fn get_signed_less_facts(
+        &self,
+        lhs: &Expression,
+        rhs: &Expression,
+        use_tid: &Tid,
+        less_holds: bool,
+    ) -> Option<Vec<Fact>> {
+        for flag in [lhs, rhs] {
+            if let Expression::Var(var) = flag {
+                if let Some(Term {
+                    tid,
+                    term:
+                        Def::Assign {
+                            value:
+                                Expression::BinOp {
+                                    op: BinOpType::IntSBorrow,
+                                    lhs,
+                                    rhs,
+                                },
+                            ..
+                        },
+                }) = self.get_unique_definition(use_tid, var)
+                {
+                    return Some(self.get_comparison_facts(
+                        BinOpType::IntSLess,
+                        lhs,
+                        rhs,
+                        tid,
+                        less_holds,
+                        0,
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Collect the facts that hold on all paths to the block with the given TID
+    /// due to the conditional jumps in the blocks dominating it.
+    fn get_dominating_facts(
+        &self,
+        block_tid: &Tid,
+        blocks: &HashMap<&Tid, &Term<Blk>>,
+        dominators: &HashMap<Tid, Tid>,
+    ) -> Vec<Fact> {
+        let mut dominated_blocks = vec![block_tid];
+        let mut facts = Vec::new();
+        while let Some(dominator_tid) = dominators.get(*dominated_blocks.last().unwrap()) {
+            if dominated_blocks.contains(&dominator_tid) {
+                break;
+            }
+            if let Some(dominator) = blocks.get(dominator_tid) {
+                if let Some((condition, jmp_tid, holds)) =
+                    get_condition_on_path(dominator, &dominated_blocks)
+                {
+                    facts.extend(self.get_facts(condition, jmp_tid, holds, MAX_RESOLUTION_DEPTH));
+                }
+            }
+            dominated_blocks.push(dominator_tid);
+        }
+        facts
+    }
+}
+
+/// Remove casts and subpieces from the expression.
+fn strip_casts(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::Cast {
+            op: CastOpType::IntZExt | CastOpType::IntSExt,
+            arg,
+            ..
+        }
+        | Expression::Subpiece { arg, .. } => strip_casts(arg),
+        _ => expression,
+    }
+}
+
+/// Get the fact that holds for the value with the given origins if the comparison with the constant holds.
+fn get_comparison_fact(
+    origins: BTreeSet<ValueOrigin>,
+    constant: &Bitvector,
+    comparison: Comparison,
+    signed: bool,
+) -> Option<Fact> {
+    let upper_bound = |bound: u64, signed: bool| {
+        Some(Fact::UpperBound {
+            origins: origins.clone(),
+            bound,
+            signed,
+        })
+    };
+    if signed {
+        let constant = constant.try_to_i64().ok()?;
+        match comparison {
+            Comparison::Less if constant > 0 => upper_bound(constant as u64 - 1, true),
+            Comparison::LessEqual if constant >= 0 => upper_bound(constant as u64, true),
+            Comparison::Greater if constant >= -1 => Some(Fact::NonNegative { origins }),
+            Comparison::GreaterEqual if constant >= 0 => Some(Fact::NonNegative { origins }),
+            _ => None,
+        }
+    } else {
+        let constant = constant.try_to_u64().ok()?;
+        match comparison {
+            Comparison::Less if constant > 0 => upper_bound(constant - 1, false),
+            Comparison::LessEqual | Comparison::Equal => upper_bound(constant, false),
+            _ => None,
+        }
+    }
+}
+
+/// If the dominator block ends with a conditional jump and exactly one of its successors
+/// is contained in the given dominated blocks, return the condition of the jump,
+/// the TID of the jump and whether the condition holds on the path into the dominated blocks.
+fn get_condition_on_path<'b>(
+    dominator: &'b Term<Blk>,
+    dominated_blocks: &[&Tid],
+) -> Option<(&'b Expression, &'b Tid, bool)> {
+    let (condition, jmp_tid, target) =
+        dominator.term.jmps.iter().find_map(|jmp| match &jmp.term {
+            Jmp::CBranch { target, condition } => Some((condition, &jmp.tid, target)),
+            _ => None,
+        })?;
+    let fallthrough = dominator.term.jmps.iter().find_map(|jmp| match &jmp.term {
+        Jmp::Branch(target) => Some(target),
+        _ => None,
+    })?;
+    match (
+        dominated_blocks.contains(&target),
+        dominated_blocks.contains(&fallthrough),
+    ) {
+        (true, false) => Some((condition, jmp_tid, true)),
+        (false, true) => Some((condition, jmp_tid, false)),
+        _ => None,
+    }
+}
+
+/// Check whether the facts bound the value with the given origins by the maximal allocation size.
+fn is_bounded_by_facts(origins: &BTreeSet<ValueOrigin>, facts: &[Fact], max_size: u64) -> bool {
+    if origins.is_empty() {
+        return false;
+    }
+    let is_about_value = |fact: &&Fact| origins.is_subset(fact.get_origins());
+    let is_non_negative = facts
+        .iter()
+        .filter(is_about_value)
+        .any(|fact| matches!(fact, Fact::NonNegative { .. }));
+    facts.iter().filter(is_about_value).any(|fact| match fact {
+        Fact::UpperBound { bound, signed, .. } => {
+            *bound <= max_size && (!*signed || is_non_negative)
+        }
+        Fact::NonNegative { .. } => false,
+    })
+}
+
+/// Check whether the interval domain of the Pointer Inference analysis bounds the value
+/// by the maximal allocation size.
+fn is_bounded_by_interval(value: &Data, max_size: u64) -> bool {
+    match value {
+        Data::Value(value) => match value.try_to_interval() {
+            Ok(interval) => matches!(
+                (interval.start.try_to_i64(), interval.end.try_to_i64()),
+                (Ok(start), Ok(end)) if start >= 0 && end as u64 <= max_size
+            ),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+/// Get the expression evaluating to the parameter of the call,
+/// together with the TID of the term right before which its origins have to be determined.
+///
+/// For stack parameters this is the value of the last store to the parameter in the block of the call.
+fn get_parameter_expression<'b>(
+    parameter: &Arg,
+    block: &'b Term<Blk>,
+    call_tid: &'b Tid,
+    pi_state: &PointerInferenceState,
+    pointer_inference: &PointerInference,
+    stack_pointer: &Variable,
+) -> Option<(Expression, &'b Tid)> {
+    match parameter {
+        Arg::Register(var) => Some((Expression::Var(var.clone()), call_tid)),
+        Arg::Stack { offset, .. } => {
+            let cell = pi_state.get_unique_memory_cell(
+                &Expression::Var(stack_pointer.clone()).plus_const(*offset),
+            )?;
+            block
+                .term
+                .defs
+                .iter()
+                .rev()
+                .find_map(|def| match &def.term {
+                    Def::Store { address, value } => {
+                        if pointer_inference
+                            .get_unique_memory_cell_at_tid(&def.tid, address)
+                            .as_ref()
+                            == Some(&cell)
+                        {
+                            Some((value.clone(), &def.tid))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                })
+        }
+    }
+}
+
+/// Generate the CWE warning for a call with a tainted and unbounded size parameter.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    callsite: &Tid,
+    symbol: &ExternSymbol,
+    parameter_index: usize,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Memory Allocation with Excessive Size Value) Unbounded size parameter {} from untrusted input in call to {} at {} in {}",
+            parameter_index, symbol.name, callsite.address, sub.term.display_name()
+        ),
+    )
+    .tids(vec![format!("{}", callsite)])
+    .addresses(vec![callsite.address.clone()])
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+}
+
+/// Run the CWE check.
+/// For each call to an allocation function we check whether a size parameter is tainted
+/// and not bounded by the interval domain or by a dominating comparison.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let allocation_symbols: HashMap<&Tid, (&ExternSymbol, &Vec<usize>)> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter_map(|symbol| {
+            config
+                .allocation_symbols
+                .get(&symbol.name)
+                .map(|indices| (&symbol.tid, (symbol, indices)))
+        })
+        .collect();
+    let (taint_context, logs) =
+        cwe_129::Context::new(analysis_results, config.taint_sources.clone());
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let calls_allocation_symbol = sub.term.blocks.iter().any(|block| {
+            block.term.jmps.iter().any(|jmp| {
+                matches!(&jmp.term, Jmp::Call { target, .. } if allocation_symbols.contains_key(target))
+            })
+        });
+        if !calls_allocation_symbol {
+            continue;
+        }
+        let (def_use_chains, dominators) = match (
+            analysis_results.def_use_chains(&sub.tid),
+            analysis_results.dominators(&sub.tid),
+        ) {
+            (Some(def_use_chains), Some(dominators)) => (def_use_chains, dominators),
+            _ => continue,
+        };
+        let context = Context {
+            def_use_chains,
+            pointer_inference,
+        };
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let taint_states = taint_context.get_taint_states_at_calls(sub);
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                let (symbol, indices) = match &jmp.term {
+                    Jmp::Call { target, .. } => match allocation_symbols.get(target) {
+                        Some(allocation_symbol) => *allocation_symbol,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                let (taint_state, pi_state) = match (
+                    taint_states.get(&jmp.tid),
+                    pointer_inference.get_state_at_jmp_tid(&jmp.tid),
+                ) {
+                    (Some(taint_state), Some(pi_state)) => (taint_state, pi_state),
+                    _ => continue,
+                };
+                let mut dominating_facts = None;
+                for index in indices {
+                    let parameter = match symbol.parameters.get(*index) {
+                        Some(parameter) => parameter,
+                        None => continue,
+                    };
+                    let is_tainted = match parameter {
+                        Arg::Register(var) => {
+                            taint_state.eval(&Expression::Var(var.clone())).is_some()
+                        }
+                        Arg::Stack { offset, .. } => taint_state
+                            .load(
+                                &pi_state.eval(
+                                    &Expression::Var(project.stack_pointer_register.clone())
+                                        .plus_const(*offset),
+                                ),
+                            )
+                            .is_some(),
+                    };
+                    if !is_tainted {
+                        continue;
+                    }
+                    if let Ok(value) = pi_state.eval_parameter_arg(
+                        parameter,
+                        &project.stack_pointer_register,
+                        analysis_results.runtime_memory_image,
+                    ) {
+                        if is_bounded_by_interval(&value, config.max_allocation_size) {
+                            continue;
+                        }
+                    }
+                    let origins = get_parameter_expression(
+                        parameter,
+                        block,
+                        &jmp.tid,
+                        pi_state,
+                        pointer_inference,
+                        &project.stack_pointer_register,
+                    )
+                    .map(|(expression, use_tid)| context.get_value_origins(&expression, use_tid))
+                    .unwrap_or_default();
+                    let facts = dominating_facts.get_or_insert_with(|| {
+                        context.get_dominating_facts(&block.tid, &blocks, dominators)
+                    });
+                    if !is_bounded_by_facts(&origins, facts, config.max_allocation_size) {
+                        cwe_warnings.push(generate_cwe_warning(sub, &jmp.tid, symbol, *index));
+                    }
+                }
+            }
+        }
+    }
+    (logs, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use crate::utils::graph_utils::get_immediate_dominators;
+    use std::collections::HashSet;
+
+    fn input_origins(name: &str) -> BTreeSet<ValueOrigin> {
+        vec![ValueOrigin::Input(Variable::mock(name, 8))]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn comparison_facts() {
+        let origins = input_origins("RDI");
+        let constant = Bitvector::from_u64(0x100);
+        assert_eq!(
+            get_comparison_fact(origins.clone(), &constant, Comparison::Less, false),
+            Some(Fact::UpperBound {
+                origins: origins.clone(),
+                bound: 0xff,
+                signed: false
+            })
+        );
+        assert_eq!(
+            get_comparison_fact(origins.clone(), &constant, Comparison::Greater, false),
+            None
+        );
+        let signed_bound =
+            get_comparison_fact(origins.clone(), &constant, Comparison::LessEqual, true).unwrap();
+        let non_negative = get_comparison_fact(
+            origins.clone(),
+            &Bitvector::from_i64(0),
+            Comparison::GreaterEqual,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            non_negative,
+            Fact::NonNegative {
+                origins: origins.clone()
+            }
+        );
+        // Signed upper bounds only bound the value if it is also non-negative.
+        assert!(!is_bounded_by_facts(
+            &origins,
+            std::slice::from_ref(&signed_bound),
+            0x1000
+        ));
+        assert!(is_bounded_by_facts(
+            &origins,
+            &[signed_bound.clone(), non_negative],
+            0x1000
+        ));
+        // The bound has to be below the maximal allocation size and concern the same value.
+        let unsigned_bound = Fact::UpperBound {
+            origins: origins.clone(),
+            bound: 0x100,
+            signed: false,
+        };
+        assert!(!is_bounded_by_facts(
+            &origins,
+            std::slice::from_ref(&unsigned_bound),
+            0x10
+        ));
+        assert!(!is_bounded_by_facts(
+            &input_origins("RSI"),
+            std::slice::from_ref(&unsigned_bound),
+            0x1000
+        ));
+        assert!(!is_bounded_by_facts(
+            &BTreeSet::new(),
+            std::slice::from_ref(&unsigned_bound),
+            0x1000
+        ));
+        // A disjunction of bounds yields the weaker bound.
+        let weaker_bound = Fact::UpperBound {
+            origins: origins.clone(),
+            bound: 0x200,
+            signed: false,
+        };
+        assert_eq!(
+            unsigned_bound.disjunction(&weaker_bound),
+            Some(weaker_bound.clone())
+        );
+        assert_eq!(
+            signed_bound
+                .disjunction(&weaker_bound)
+                .map(|fact| match fact {
+                    Fact::UpperBound { signed, .. } => signed,
+                    Fact::NonNegative { .. } => false,
+                }),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn dominating_bounds() {
+        let rdi = Variable::mock("RDI", 8);
+        let rax = Variable::mock("RAX", 8);
+        let cf = Variable::mock("CF", 1);
+        let zf = Variable::mock("ZF", 1);
+        let difference = Variable::mock("$U1", 8);
+        let mut sub = Sub::mock("func");
+        // if (RAX < 0x1000 || RAX == 0x1000) { RDI = RAX; malloc(RDI) } else { malloc(RDI) }
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "entry",
+                vec![
+                    Def::assign(
+                        "def_cf",
+                        cf.clone(),
+                        Expression::BinOp {
+                            op: BinOpType::IntLess,
+                            lhs: Box::new(Expression::Var(rax.clone())),
+                            rhs: Box::new(Expression::const_from_i64(0x1000)),
+                        },
+                    ),
+                    Def::assign(
+                        "def_difference",
+                        difference.clone(),
+                        Expression::BinOp {
+                            op: BinOpType::IntSub,
+                            lhs: Box::new(Expression::Var(rax.clone())),
+                            rhs: Box::new(Expression::const_from_i64(0x1000)),
+                        },
+                    ),
+                    Def::assign(
+                        "def_zf",
+                        zf.clone(),
+                        Expression::BinOp {
+                            op: BinOpType::IntEqual,
+                            lhs: Box::new(Expression::Var(difference)),
+                            rhs: Box::new(Expression::const_from_i64(0)),
+                        },
+                    ),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("unbounded"),
+                        condition: Expression::UnOp {
+                            op: UnOpType::BoolNegate,
+                            arg: Box::new(Expression::BinOp {
+                                op: BinOpType::BoolOr,
+                                lhs: Box::new(Expression::Var(cf)),
+                                rhs: Box::new(Expression::Var(zf)),
+                            }),
+                        },
+                    },
+                    Jmp::Branch(Tid::new("bounded")),
+                ],
+            ),
+            Blk::mock_with_jmps(
+                "bounded",
+                vec![Def::assign(
+                    "copy",
+                    rdi.clone(),
+                    Expression::Var(rax.clone()),
+                )],
+                vec![Jmp::Call {
+                    target: Tid::new("mock_symbol"),
+                    return_: None,
+                }],
+            ),
+            Blk::mock_with_jmps(
+                "unbounded",
+                Vec::new(),
+                vec![Jmp::Call {
+                    target: Tid::new("mock_symbol"),
+                    return_: None,
+                }],
+            ),
+        ];
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        project.program.term.subs = vec![sub.clone()];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let def_use_chains = DefUseChains::new(&project, &sub);
+        let context = Context {
+            def_use_chains: &def_use_chains,
+            pointer_inference: &pointer_inference,
+        };
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let dominators = get_immediate_dominators(&sub);
+
+        let origins =
+            context.get_value_origins(&Expression::Var(rdi.clone()), &Tid::new("bounded_jmp_0"));
+        assert_eq!(origins, input_origins("RAX"));
+        let facts = context.get_dominating_facts(&Tid::new("bounded"), &blocks, &dominators);
+        assert_eq!(
+            facts,
+            vec![Fact::UpperBound {
+                origins: origins.clone(),
+                bound: 0x1000,
+                signed: false
+            }]
+        );
+        assert!(is_bounded_by_facts(&origins, &facts, 0x1000));
+        assert!(!is_bounded_by_facts(&origins, &facts, 0xfff));
+
+        let facts = context.get_dominating_facts(&Tid::new("unbounded"), &blocks, &dominators);
+        assert!(!is_bounded_by_facts(&origins, &facts, 0x1000));
+    }
+}
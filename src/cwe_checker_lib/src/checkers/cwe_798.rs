@@ -0,0 +1,789 @@
+//! This module implements a check for CWE-798: Use of Hard-coded Credentials.
+//!
+//! Passwords or keys embedded in the binary can be extracted by everyone with access to the binary.
+//! They cannot be changed without patching the binary, so that a leaked credential compromises all installations.
+//!
+//! See <https://cwe.mitre.org/data/definitions/798.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The check uses the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! to compute the values of the parameters of calls to extern functions.
+//! A parameter is a hard-coded credential if it points to constant data in a read-only section of the binary.
+//! Warnings are generated for
+//! - credential parameters of the `credential_sinks` configured in config.json
+//!   (e.g. the password parameter of `mysql_real_connect` or the key of `sqlite3_key`)
+//!   pointing to constant data.
+//!   Sinks with an `option_parameter` (e.g. `setsockopt` or `pam_set_item`) are only checked
+//!   if the option parameter is one of the configured `option_values`.
+//! - calls to the `comparison_functions` (e.g. `strcmp`) comparing user input with a constant string.
+//!
+//! User input is either
+//! - a buffer written to by one of the `input_buffer_sources` (e.g. `fgets` or `read`),
+//!   identified by its memory object and offset according to the Pointer Inference analysis.
+//!   Buffers of callers passed as parameters to the function containing the comparison are also recognized.
+//! - or the return value of one of the `input_return_sources` (e.g. `getpass`) in the same function,
+//!   followed through the def-use chains of the function.
+//!
+//! ## False Positives
+//!
+//! - User input compared with constant strings that are not secret (e.g. commands like `"quit"`) is flagged.
+//! - Constant credentials that are only placeholders (e.g. an empty default password) are flagged.
+//!
+//! ## False Negatives
+//!
+//! - Credentials that are decoded or decrypted at runtime are not detected.
+//! - Comparisons by inlined or custom comparison functions are not detected.
+//! - User input is not tracked through copies into other buffers.
+//! - Return values of input sources are only tracked in registers inside the calling function.
+
+use crate::abstract_domain::{AbstractIdentifier, AbstractLocation, TryToBitvec};
+use crate::analysis::def_use::Definition;
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::analysis::string_abstraction::get_constant_string;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE798",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The maximal number of callers and register copies that are followed when searching for user input.
+const MAX_TRACE_DEPTH: usize = 8;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions comparing the strings or buffers given by their first two parameters.
+    comparison_functions: Vec<String>,
+    /// Functions writing user input into a buffer, mapped to the index of the buffer parameter.
+    input_buffer_sources: BTreeMap<String, usize>,
+    /// Functions returning a pointer to user input.
+    input_return_sources: Vec<String>,
+    /// Functions taking credentials as parameters.
+    #[serde(default)]
+    credential_sinks: BTreeMap<String, CredentialSink>,
+}
+
+/// A function taking a credential as parameter.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialSink {
+    /// The index of the credential parameter.
+    parameter: usize,
+    /// If set, calls are only checked if the parameter with this index is one of the `option_values`.
+    #[serde(default)]
+    option_parameter: Option<usize>,
+    /// The option values selecting a credential, e.g. `TCP_MD5SIG` for `setsockopt`.
+    #[serde(default)]
+    option_values: Vec<u64>,
+}
+
+/// A hard-coded credential, given by its address and its content if it is a string.
+struct Credential<'a> {
+    /// The address of the credential.
+    address: u64,
+    /// The credential, if it is a null-terminated string.
+    string: Option<&'a str>,
+}
+
+/// The context for searching hard-coded credentials.
+struct Context<'a> {
+    /// The project.
+    project: &'a Project,
+    /// The analysis results.
+    analysis_results: &'a AnalysisResults<'a>,
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+    /// The memory cells of buffers filled with user input.
+    input_buffers: HashSet<(AbstractIdentifier, i64)>,
+    /// The input return source functions, indexed by their TIDs.
+    input_return_sources: HashSet<&'a Tid>,
+}
+
+impl<'a> Context<'a> {
+    /// Get the value of the parameter with the given index of the call to the extern symbol.
+    fn eval_parameter(&self, state: &State, symbol: &ExternSymbol, index: usize) -> Option<Data> {
+        state
+            .eval_parameter_arg(
+                &get_variadic_parameter(self.project, symbol, index),
+                &self.project.stack_pointer_register,
+                self.analysis_results.runtime_memory_image,
+            )
+            .ok()
+    }
+
+    /// Return the credential that the given value points to,
+    /// if it is an address in a read-only section of the binary.
+    fn get_credential(&self, value: &Data) -> Option<Credential<'a>> {
+        let runtime_memory_image = self.analysis_results.runtime_memory_image;
+        let address = value.try_to_bitvec().ok()?;
+        if runtime_memory_image.is_address_writeable(&address).ok()? {
+            return None;
+        }
+        Some(Credential {
+            address: address.try_to_u64().ok()?,
+            string: get_constant_string(value, runtime_memory_image),
+        })
+    }
+
+    /// Check whether the given value points to the start of a buffer filled with user input.
+    fn is_input_buffer(&self, value: &Data) -> bool {
+        match value {
+            Data::Pointer(pointer) => pointer.targets().iter().any(|(id, offset)| {
+                match offset
+                    .try_to_bitvec()
+                    .ok()
+                    .and_then(|offset| offset.try_to_i64().ok())
+                {
+                    Some(offset) => self.is_input_cell(id, offset, MAX_TRACE_DEPTH),
+                    None => false,
+                }
+            }),
+            _ => false,
+        }
+    }
+
+    /// Check whether the memory cell is the start of a buffer filled with user input.
+    ///
+    /// In callees the stack frames of callers are identified by the call instruction.
+    /// For these the corresponding memory cell of the stack frame of the caller is checked.
+    fn is_input_cell(&self, id: &AbstractIdentifier, offset: i64, depth: usize) -> bool {
+        if self.input_buffers.contains(&(id.clone(), offset)) {
+            return true;
+        }
+        let stack_location =
+            AbstractLocation::from_var(&self.project.stack_pointer_register).unwrap();
+        if depth == 0 || *id != AbstractIdentifier::new(id.get_tid().clone(), stack_location) {
+            return false;
+        }
+        let state = match self.pointer_inference.get_state_at_tid(id.get_tid()) {
+            Some(state) => state,
+            None => return false,
+        };
+        // Revert the offset adjustment applied to the caller stack frame at the call.
+        match state.get_register(&self.project.stack_pointer_register) {
+            Data::Pointer(stack_pointer) => match stack_pointer
+                .targets()
+                .get(&state.stack_id)
+                .and_then(|offset| offset.try_to_bitvec().ok())
+                .and_then(|offset| offset.try_to_i64().ok())
+            {
+                Some(stack_offset) => {
+                    self.is_input_cell(&state.stack_id, offset + stack_offset, depth - 1)
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Check whether the register `var` holds the return value of an input return source
+    /// at its use by the term with the given TID.
+    fn is_input_return_value(
+        &self,
+        sub: &Term<Sub>,
+        use_tid: &Tid,
+        var: &Variable,
+        depth: usize,
+    ) -> bool {
+        if depth == 0 {
+            return false;
+        }
+        let def_use_chains = match self.analysis_results.def_use_chains(&sub.tid) {
+            Some(def_use_chains) => def_use_chains,
+            None => return false,
+        };
+        let reaching_defs: Vec<&Tid> = def_use_chains.defs_reaching(use_tid, var).collect();
+        !reaching_defs.is_empty()
+            && reaching_defs.into_iter().all(|def_tid| {
+                match def_use_chains.get_definition(def_tid) {
+                    Some(Definition::Def(def)) => match &def.term {
+                        Def::Assign {
+                            value: Expression::Var(input),
+                            ..
+                        } => self.is_input_return_value(sub, &def.tid, input, depth - 1),
+                        _ => false,
+                    },
+                    Some(Definition::Call(call)) => matches!(
+                        &call.term,
+                        Jmp::Call { target, .. } if self.input_return_sources.contains(target)
+                    ),
+                    None => false,
+                }
+            })
+    }
+
+    /// Check whether the parameter with the given index of the call is user input.
+    fn is_user_input(
+        &self,
+        sub: &Term<Sub>,
+        call: &Term<Jmp>,
+        state: &State,
+        symbol: &ExternSymbol,
+        index: usize,
+    ) -> bool {
+        if let Some(value) = self.eval_parameter(state, symbol, index) {
+            if self.is_input_buffer(&value) {
+                return true;
+            }
+        }
+        match get_variadic_parameter(self.project, symbol, index) {
+            Arg::Register(var) => self.is_input_return_value(sub, &call.tid, &var, MAX_TRACE_DEPTH),
+            Arg::Stack { .. } => false,
+        }
+    }
+}
+
+/// Collect the memory cells of all buffers written to by calls to input buffer sources.
+fn collect_input_buffers(
+    project: &Project,
+    analysis_results: &AnalysisResults,
+    pointer_inference: &PointerInference,
+    config: &Config,
+) -> HashSet<(AbstractIdentifier, i64)> {
+    let source_names: Vec<String> = config.input_buffer_sources.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &source_names);
+    let mut input_buffers = HashSet::new();
+    for sub in project.program.term.subs.iter() {
+        for (_, call, symbol) in get_callsites(sub, &symbol_map) {
+            let state = match pointer_inference.get_state_at_tid(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            let parameter =
+                get_variadic_parameter(project, symbol, config.input_buffer_sources[&symbol.name]);
+            if let Ok(Data::Pointer(pointer)) = state.eval_parameter_arg(
+                &parameter,
+                &project.stack_pointer_register,
+                analysis_results.runtime_memory_image,
+            ) {
+                for (id, offset) in pointer.targets().iter() {
+                    if let Some(offset) = offset
+                        .try_to_bitvec()
+                        .ok()
+                        .and_then(|offset| offset.try_to_i64().ok())
+                    {
+                        input_buffers.insert((id.clone(), offset));
+                    }
+                }
+            }
+        }
+    }
+    input_buffers
+}
+
+/// Generate the warning for a hard-coded credential passed to the given call.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    credential: &Credential,
+    is_comparison: bool,
+) -> CweWarning {
+    let description = if is_comparison {
+        format!(
+            "(Use of Hard-coded Credentials) User input is compared with the constant at {:#x} by {} in {} at {}.",
+            credential.address, symbol.name, sub.term.display_name(), call.tid.address
+        )
+    } else {
+        format!(
+            "(Use of Hard-coded Credentials) The constant at {:#x} is passed as credential to {} in {} at {}.",
+            credential.address, symbol.name, sub.term.display_name(), call.tid.address
+        )
+    };
+    let mut other = vec![vec![
+        "credential_address".to_string(),
+        format!("{:#x}", credential.address),
+    ]];
+    if let Some(string) = credential.string {
+        other.push(vec!["credential".to_string(), string.to_string()]);
+    }
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![call.tid.address.clone()])
+        .tids(vec![call.tid.to_string()])
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            symbol.name.clone(),
+        ])
+        .other(other)
+}
+
+/// Flag hard-coded credentials passed to credential sinks or compared with user input.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let comparison_symbols = get_symbol_map(project, &config.comparison_functions);
+    let sink_names: Vec<String> = config.credential_sinks.keys().cloned().collect();
+    let sink_symbols = get_symbol_map(project, &sink_names);
+    if comparison_symbols.is_empty() && sink_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let context = Context {
+        project,
+        analysis_results,
+        pointer_inference,
+        input_buffers: collect_input_buffers(project, analysis_results, pointer_inference, &config),
+        input_return_sources: project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .filter(|symbol| config.input_return_sources.contains(&symbol.name))
+            .map(|symbol| &symbol.tid)
+            .collect(),
+    };
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_, call, symbol) in get_callsites(sub, &sink_symbols) {
+            let state = match pointer_inference.get_state_at_tid(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            let sink = &config.credential_sinks[&symbol.name];
+            if let Some(option_parameter) = sink.option_parameter {
+                let option = context
+                    .eval_parameter(&state, symbol, option_parameter)
+                    .and_then(|value| value.try_to_bitvec().ok())
+                    .and_then(|value| value.try_to_u64().ok());
+                match option {
+                    Some(option) if sink.option_values.contains(&option) => (),
+                    _ => continue,
+                }
+            }
+            if let Some(credential) = context
+                .eval_parameter(&state, symbol, sink.parameter)
+                .and_then(|value| context.get_credential(&value))
+            {
+                warnings.push(generate_cwe_warning(sub, call, symbol, &credential, false));
+            }
+        }
+        for (_, call, symbol) in get_callsites(sub, &comparison_symbols) {
+            let state = match pointer_inference.get_state_at_tid(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            for &(constant_index, input_index) in [(0, 1), (1, 0)].iter() {
+                if let Some(credential) = context
+                    .eval_parameter(&state, symbol, constant_index)
+                    .and_then(|value| context.get_credential(&value))
+                {
+                    if credential.string.is_some()
+                        && context.is_user_input(sub, call, &state, symbol, input_index)
+                    {
+                        warnings.push(generate_cwe_warning(sub, call, symbol, &credential, true));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    fn assign(tid: &str, var: &str, value: Expression) -> Term<Def> {
+        Def::assign(tid, Variable::mock(var, 8), value)
+    }
+
+    fn frame_pointer(tid: &str) -> Term<Def> {
+        assign(
+            tid,
+            "RBP",
+            Expression::Var(Variable::mock("RSP", 8)).plus_const(-0x40),
+        )
+    }
+
+    fn stack_buffer(tid: &str, var: &str) -> Term<Def> {
+        // The frame pointer is callee-saved, so that it is not changed by calls.
+        assign(tid, var, Expression::Var(Variable::mock("RBP", 8)))
+    }
+
+    fn constant_string(tid: &str, var: &str) -> Term<Def> {
+        // Points to the read-only string "Hello World" in the mock runtime memory image.
+        assign(tid, var, Expression::const_from_i64(0x3002))
+    }
+
+    /// Run the check with the Pointer Inference analysis on the project.
+    fn check_project(project: &Project, config: serde_json::Value) -> Vec<CweWarning> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        check_cwe(&analysis_results, &config).1
+    }
+
+    /// Return the TIDs of the calls the warnings were generated for.
+    fn call_tids(warnings: &[CweWarning]) -> Vec<&str> {
+        warnings
+            .iter()
+            .map(|warning| warning.tids[0].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn hardcoded_credentials() {
+        let mut project = Project::mock_empty();
+        let mut calling_convention = CallingConvention::mock();
+        calling_convention.parameter_register =
+            vec!["RDI".to_string(), "RSI".to_string(), "RDX".to_string()];
+        project.calling_conventions = vec![calling_convention];
+        project.program.term.extern_symbols = ["strcmp", "fgets", "getpass", "sqlite3_key"]
+            .iter()
+            .map(|name| ExternSymbol::mock_with(name, &["RDI", "RSI"]))
+            .collect();
+        project.program.term.subs = vec![
+            // strcmp(input, "Hello World") with input read by fgets
+            Sub::mock_with(
+                "input",
+                vec![
+                    Blk::mock_with(
+                        "input_0",
+                        vec![
+                            frame_pointer("input_frame"),
+                            stack_buffer("input_buffer", "RDI"),
+                        ],
+                        Jmp::mock_call("fgets", "input_1"),
+                    ),
+                    Blk::mock_with(
+                        "input_1",
+                        vec![
+                            stack_buffer("input_first", "RDI"),
+                            constant_string("input_second", "RSI"),
+                        ],
+                        Jmp::mock_call("strcmp", "input_2"),
+                    ),
+                    Blk::mock_with("input_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // strcmp(buffer, "Hello World") with an uninitialized buffer
+            Sub::mock_with(
+                "no_input",
+                vec![
+                    Blk::mock_with(
+                        "no_input_0",
+                        vec![
+                            frame_pointer("no_input_frame"),
+                            stack_buffer("no_input_first", "RDI"),
+                            constant_string("no_input_second", "RSI"),
+                        ],
+                        Jmp::mock_call("strcmp", "no_input_1"),
+                    ),
+                    Blk::mock_with("no_input_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // strcmp("Hello World", getpass(...))
+            Sub::mock_with(
+                "password",
+                vec![
+                    Blk::mock_with(
+                        "password_0",
+                        vec![],
+                        Jmp::mock_call("getpass", "password_1"),
+                    ),
+                    Blk::mock_with(
+                        "password_1",
+                        vec![
+                            constant_string("password_first", "RDI"),
+                            assign(
+                                "password_second",
+                                "RSI",
+                                Expression::Var(Variable::mock("RAX", 8)),
+                            ),
+                        ],
+                        Jmp::mock_call("strcmp", "password_2"),
+                    ),
+                    Blk::mock_with("password_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // sqlite3_key(db, "Hello World", 11)
+            Sub::mock_with(
+                "sink",
+                vec![
+                    Blk::mock_with(
+                        "sink_0",
+                        vec![constant_string("sink_key", "RSI")],
+                        Jmp::mock_call("sqlite3_key", "sink_1"),
+                    ),
+                    Blk::mock_with("sink_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // Reads input into a buffer and calls `compare` with the buffer as parameter.
+            Sub::mock_with(
+                "caller",
+                vec![
+                    Blk::mock_with(
+                        "caller_0",
+                        vec![
+                            frame_pointer("caller_frame"),
+                            stack_buffer("caller_buffer", "RDI"),
+                        ],
+                        Jmp::mock_call("fgets", "caller_1"),
+                    ),
+                    Blk::mock_with(
+                        "caller_1",
+                        vec![stack_buffer("caller_parameter", "RDI")],
+                        Jmp::mock_call("compare", "caller_2"),
+                    ),
+                    Blk::mock_with("caller_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // strcmp(parameter, "Hello World")
+            Sub::mock_with(
+                "compare",
+                vec![
+                    Blk::mock_with(
+                        "compare_0",
+                        vec![constant_string("compare_second", "RSI")],
+                        Jmp::mock_call("strcmp", "compare_1"),
+                    ),
+                    Blk::mock_with("compare_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+        let config = json!({
+            "comparison_functions": ["strcmp"],
+            "input_buffer_sources": {"fgets": 0},
+            "input_return_sources": ["getpass"],
+            "credential_sinks": {"sqlite3_key": {"parameter": 1}}
+        });
+
+        let warnings = check_project(&project, config);
+        assert_eq!(
+            call_tids(&warnings),
+            vec![
+                "input_1_jmp",
+                "password_1_jmp",
+                "sink_0_jmp",
+                "compare_0_jmp"
+            ]
+        );
+        assert_eq!(
+            warnings[2].other[1],
+            vec!["credential".to_string(), "Hello World".to_string()]
+        );
+    }
+
+    #[test]
+    fn credential_sink_options() {
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols =
+            vec![ExternSymbol::mock_with("setsockopt", &["RDI", "RSI"])];
+        let option = |tid: &str, value: i64| assign(tid, "RDI", Expression::const_from_i64(value));
+        project.program.term.subs = vec![
+            Sub::mock_with(
+                "matching",
+                vec![
+                    Blk::mock_with(
+                        "matching_0",
+                        vec![
+                            option("matching_option", 14),
+                            constant_string("matching_key", "RSI"),
+                        ],
+                        Jmp::mock_call("setsockopt", "matching_1"),
+                    ),
+                    Blk::mock_with("matching_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            Sub::mock_with(
+                "other_option",
+                vec![
+                    Blk::mock_with(
+                        "other_option_0",
+                        vec![
+                            option("other_option_option", 3),
+                            constant_string("other_option_key", "RSI"),
+                        ],
+                        Jmp::mock_call("setsockopt", "other_option_1"),
+                    ),
+                    Blk::mock_with("other_option_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // The option is a parameter of the function and thus unknown.
+            Sub::mock_with(
+                "unknown_option",
+                vec![
+                    Blk::mock_with(
+                        "unknown_option_0",
+                        vec![constant_string("unknown_option_key", "RSI")],
+                        Jmp::mock_call("setsockopt", "unknown_option_1"),
+                    ),
+                    Blk::mock_with("unknown_option_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+        let config = json!({
+            "comparison_functions": [],
+            "input_buffer_sources": {},
+            "input_return_sources": [],
+            "credential_sinks": {
+                "setsockopt": {"parameter": 1, "option_parameter": 0, "option_values": [14]}
+            }
+        });
+        assert_eq!(
+            call_tids(&check_project(&project, config)),
+            vec!["matching_0_jmp"]
+        );
+
+        // Without an option parameter all calls are checked.
+        let config = json!({
+            "comparison_functions": [],
+            "input_buffer_sources": {},
+            "input_return_sources": [],
+            "credential_sinks": {"setsockopt": {"parameter": 1}}
+        });
+        assert_eq!(
+            call_tids(&check_project(&project, config)),
+            vec![
+                "matching_0_jmp",
+                "other_option_0_jmp",
+                "unknown_option_0_jmp"
+            ]
+        );
+    }
+
+    #[test]
+    fn non_constant_credentials() {
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols = vec![
+            ExternSymbol::mock_with("sqlite3_key", &["RDI", "RSI"]),
+            ExternSymbol::mock_with("fgets", &["RDI", "RSI"]),
+            ExternSymbol::mock_with("strcmp", &["RDI", "RSI"]),
+        ];
+        let key = |tid: &str, address: i64| assign(tid, "RSI", Expression::const_from_i64(address));
+        project.program.term.subs = vec![
+            // The key is located in writeable memory.
+            Sub::mock_with(
+                "writeable",
+                vec![
+                    Blk::mock_with(
+                        "writeable_0",
+                        vec![key("writeable_key", 0x2000)],
+                        Jmp::mock_call("sqlite3_key", "writeable_1"),
+                    ),
+                    Blk::mock_with("writeable_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // The key is a stack buffer.
+            Sub::mock_with(
+                "stack",
+                vec![
+                    Blk::mock_with(
+                        "stack_0",
+                        vec![
+                            frame_pointer("stack_frame"),
+                            stack_buffer("stack_key", "RSI"),
+                        ],
+                        Jmp::mock_call("sqlite3_key", "stack_1"),
+                    ),
+                    Blk::mock_with("stack_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // Read-only data that is not a string is reported without its content.
+            Sub::mock_with(
+                "binary",
+                vec![
+                    Blk::mock_with(
+                        "binary_0",
+                        vec![key("binary_key", 0x1000)],
+                        Jmp::mock_call("sqlite3_key", "binary_1"),
+                    ),
+                    Blk::mock_with("binary_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // Comparisons of user input with read-only data that is not a string are not reported.
+            Sub::mock_with(
+                "compare_binary",
+                vec![
+                    Blk::mock_with(
+                        "compare_binary_0",
+                        vec![
+                            frame_pointer("compare_binary_frame"),
+                            stack_buffer("compare_binary_buffer", "RDI"),
+                        ],
+                        Jmp::mock_call("fgets", "compare_binary_1"),
+                    ),
+                    Blk::mock_with(
+                        "compare_binary_1",
+                        vec![
+                            stack_buffer("compare_binary_first", "RDI"),
+                            key("compare_binary_second", 0x1000),
+                        ],
+                        Jmp::mock_call("strcmp", "compare_binary_2"),
+                    ),
+                    Blk::mock_with("compare_binary_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+        let config = json!({
+            "comparison_functions": ["strcmp"],
+            "input_buffer_sources": {"fgets": 0},
+            "input_return_sources": [],
+            "credential_sinks": {"sqlite3_key": {"parameter": 1}}
+        });
+        assert_eq!(
+            call_tids(&check_project(&project, config)),
+            vec!["binary_0_jmp"]
+        );
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: Config = parse_config(&json!({
+            "comparison_functions": ["strcmp"],
+            "input_buffer_sources": {"fgets": 0},
+            "input_return_sources": []
+        }))
+        .unwrap();
+        assert!(config.credential_sinks.is_empty());
+        // The input sources are required.
+        assert!(parse_config::<Config>(&json!({
+            "comparison_functions": ["strcmp"],
+            "input_return_sources": []
+        }))
+        .is_err());
+        // Unknown fields of credential sinks are rejected.
+        assert!(parse_config::<Config>(&json!({
+            "comparison_functions": [],
+            "input_buffer_sources": {},
+            "input_return_sources": [],
+            "credential_sinks": {"setsockopt": {"parameter": 3, "option": 2}}
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,769 @@
+//! This module implements a check for CWE-835: Loop with Unreachable Exit Condition ('Infinite Loop').
+//!
+//! A loop whose exit condition can never be satisfied does not terminate.
+//! If an attacker can trigger such a loop, the program hangs and consumes CPU time (denial of service).
+//!
+//! See <https://cwe.mitre.org/data/definitions/835.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each loop of a function (see [`get_loops`]) we collect the edges leaving the loop.
+//! Returns, calls that do not return (e.g. to `exit`) and calls to the `exit_functions` configured in config.json
+//! also count as exits of the loop.
+//! A warning is generated if the loop has no exits at all.
+//!
+//! Exits through conditional jumps are considered unreachable
+//! if the [Pointer Inference analysis](crate::analysis::pointer_inference) computes a constant value
+//! for the condition that does not lead out of the loop,
+//! or if the condition does not change inside the loop.
+//! For the latter the registers used by the condition are traced backwards through their definitions inside the loop.
+//! Values loaded from memory only count as unchanged if the memory cell is not written inside the loop,
+//! neither directly nor by calls that may access it.
+//! A warning is generated if all exits of the loop are conditional jumps with unreachable exit conditions.
+//!
+//! ## False Positives
+//!
+//! - Loops that are meant to run forever (e.g. the main loop of a server) are flagged if they have no exits.
+//! - Memory may be modified by other threads or by signal handlers, e.g. a global flag terminating the loop.
+//! - Loops may be left through calls to internal functions that do not return, e.g. wrappers of `exit`.
+//!
+//! ## False Negatives
+//!
+//! - Exit conditions that change inside the loop but never become true (e.g. because of an overflowing counter) are not detected.
+//! - Loops left through indirect jumps are not checked.
+
+use crate::abstract_domain::{AbstractIdentifier, AbstractLocation, TryToBitvec};
+use crate::analysis::def_use::{DefUseChains, Definition};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::{get_intraprocedural_jump_targets, get_loops};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::CweModule;
+use std::collections::{HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE835",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The maximal number of definitions followed backwards when checking whether a value changes inside a loop.
+const MAX_TRACE_DEPTH: usize = 16;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions terminating the program or the thread. Calls to them count as exits of a loop.
+    exit_functions: Vec<String>,
+}
+
+/// An edge leaving a loop.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum LoopExit<'a> {
+    /// An exit that is taken whenever the block containing it is reached.
+    Unconditional,
+    /// A conditional jump leaving the loop if its condition has the given value.
+    Conditional {
+        /// The conditional jump.
+        jmp: &'a Term<Jmp>,
+        /// The condition of the jump.
+        condition: &'a Expression,
+        /// Whether the loop is left if the condition is true or if it is false.
+        exit_if: bool,
+    },
+}
+
+/// Collect the exits of the loop given by the set of its blocks.
+fn get_loop_exits<'a>(
+    sub: &'a Term<Sub>,
+    loop_blocks: &HashSet<Tid>,
+    exit_functions: &HashMap<Tid, &ExternSymbol>,
+) -> Vec<LoopExit<'a>> {
+    let mut exits = Vec::new();
+    for block in sub
+        .term
+        .blocks
+        .iter()
+        .filter(|block| loop_blocks.contains(&block.tid))
+    {
+        let cbranch = block.term.jmps.iter().find_map(|jmp| match &jmp.term {
+            Jmp::CBranch { target, condition } => Some((jmp, target, condition)),
+            _ => None,
+        });
+        for jmp in block.term.jmps.iter() {
+            match &jmp.term {
+                Jmp::CBranch { target, condition } if !loop_blocks.contains(target) => {
+                    exits.push(LoopExit::Conditional {
+                        jmp,
+                        condition,
+                        exit_if: true,
+                    })
+                }
+                Jmp::Branch(target) if !loop_blocks.contains(target) => match cbranch {
+                    Some((cbranch, cbranch_target, condition))
+                        if loop_blocks.contains(cbranch_target) =>
+                    {
+                        exits.push(LoopExit::Conditional {
+                            jmp: cbranch,
+                            condition,
+                            exit_if: false,
+                        })
+                    }
+                    _ => exits.push(LoopExit::Unconditional),
+                },
+                Jmp::Call { target, return_ } => {
+                    if exit_functions.contains_key(target)
+                        || return_
+                            .as_ref()
+                            .map(|return_| !loop_blocks.contains(return_))
+                            .unwrap_or(true)
+                    {
+                        exits.push(LoopExit::Unconditional);
+                    }
+                }
+                Jmp::CallInd { return_, .. } | Jmp::CallOther { return_, .. } => {
+                    if return_
+                        .as_ref()
+                        .map(|return_| !loop_blocks.contains(return_))
+                        .unwrap_or(true)
+                    {
+                        exits.push(LoopExit::Unconditional);
+                    }
+                }
+                Jmp::BranchInd(_) | Jmp::Return(_) => exits.push(LoopExit::Unconditional),
+                Jmp::Branch(_) | Jmp::CBranch { .. } => (),
+            }
+        }
+    }
+    exits
+}
+
+/// The context for checking whether values change inside a loop.
+struct LoopContext<'a> {
+    /// The TIDs of the terms of the loop.
+    loop_terms: HashSet<&'a Tid>,
+    /// The def-use chains of the function.
+    def_use_chains: &'a DefUseChains<'a>,
+    /// The results of the Pointer Inference analysis.
+    pointer_inference: &'a PointerInference<'a>,
+    /// The identifier of the stack frame of the function.
+    stack_id: AbstractIdentifier,
+    /// The memory written inside the loop, given by the written objects and the offsets if they are known.
+    written_memory: Vec<(AbstractIdentifier, Option<i64>)>,
+    /// Whether the loop may write to memory outside of the stack frame of the function
+    /// through calls or through stores to unknown addresses.
+    writes_unknown_memory: bool,
+}
+
+impl<'a> LoopContext<'a> {
+    /// Create the context for the loop given by the set of its blocks.
+    ///
+    /// Calls inside the loop are assumed to write to all memory outside of the stack frame of the function
+    /// and to the objects whose addresses are passed to them in parameter registers.
+    fn new(
+        project: &Project,
+        sub: &'a Term<Sub>,
+        loop_blocks: &HashSet<Tid>,
+        def_use_chains: &'a DefUseChains<'a>,
+        pointer_inference: &'a PointerInference<'a>,
+    ) -> LoopContext<'a> {
+        let mut context = LoopContext {
+            loop_terms: HashSet::new(),
+            def_use_chains,
+            pointer_inference,
+            stack_id: AbstractIdentifier::new(
+                sub.tid.clone(),
+                AbstractLocation::from_var(&project.stack_pointer_register).unwrap(),
+            ),
+            written_memory: Vec::new(),
+            writes_unknown_memory: false,
+        };
+        let parameter_registers: Vec<Variable> = project
+            .get_standard_calling_convention()
+            .map(|cconv| {
+                cconv
+                    .parameter_register
+                    .iter()
+                    .map(|name| Variable {
+                        name: name.clone(),
+                        size: project.get_pointer_bytesize(),
+                        is_temp: false,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        for block in sub
+            .term
+            .blocks
+            .iter()
+            .filter(|block| loop_blocks.contains(&block.tid))
+        {
+            for def in block.term.defs.iter() {
+                context.loop_terms.insert(&def.tid);
+                if let Def::Store { address, .. } = &def.term {
+                    context.add_store(&def.tid, address);
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                context.loop_terms.insert(&jmp.tid);
+                if let Jmp::Call { .. } | Jmp::CallInd { .. } | Jmp::CallOther { .. } = &jmp.term {
+                    context.writes_unknown_memory = true;
+                    match pointer_inference.get_state_at_tid(&jmp.tid) {
+                        Some(state) => {
+                            for parameter in parameter_registers.iter() {
+                                for id in state.get_register(parameter).referenced_ids() {
+                                    context.written_memory.push((id, None));
+                                }
+                            }
+                        }
+                        None => context
+                            .written_memory
+                            .push((context.stack_id.clone(), None)),
+                    }
+                }
+            }
+        }
+        context
+    }
+
+    /// Record the memory written by the store with the given TID and address.
+    fn add_store(&mut self, tid: &Tid, address: &Expression) {
+        match self
+            .pointer_inference
+            .get_state_at_tid(tid)
+            .map(|state| state.eval(address))
+        {
+            Some(Data::Pointer(pointer)) => {
+                for (id, offset) in pointer.targets().iter() {
+                    let offset = offset
+                        .try_to_bitvec()
+                        .ok()
+                        .and_then(|offset| offset.try_to_i64().ok());
+                    self.written_memory.push((id.clone(), offset));
+                }
+            }
+            _ => {
+                self.writes_unknown_memory = true;
+                self.written_memory.push((self.stack_id.clone(), None));
+            }
+        }
+    }
+
+    /// Returns `true` if the memory cell may be written inside the loop.
+    fn is_written(&self, cell: &(AbstractIdentifier, i64)) -> bool {
+        (self.writes_unknown_memory && cell.0 != self.stack_id)
+            || self.written_memory.iter().any(|(id, offset)| {
+                *id == cell.0 && offset.map(|offset| offset == cell.1).unwrap_or(true)
+            })
+    }
+
+    /// Returns `true` if the value of the register `var` at its use in the term with the given TID
+    /// is the same in each iteration of the loop.
+    ///
+    /// `path` contains the definitions currently traced.
+    /// Reaching one of them again means that the value depends on its value in the previous iteration.
+    fn is_unchanged(&self, use_tid: &Tid, var: &Variable, path: &mut Vec<&'a Tid>) -> bool {
+        for def_tid in self.def_use_chains.defs_reaching(use_tid, var) {
+            if !self.loop_terms.contains(def_tid) {
+                continue;
+            }
+            if path.len() >= MAX_TRACE_DEPTH || path.contains(&def_tid) {
+                return false;
+            }
+            path.push(def_tid);
+            let is_unchanged = match self.def_use_chains.get_definition(def_tid) {
+                Some(Definition::Def(Term {
+                    tid,
+                    term: Def::Assign { value, .. },
+                })) => self.is_expression_unchanged(tid, value, path),
+                Some(Definition::Def(Term {
+                    tid,
+                    term: Def::Load { address, .. },
+                })) => {
+                    self.is_expression_unchanged(tid, address, path)
+                        && self
+                            .pointer_inference
+                            .get_unique_memory_cell_at_tid(tid, address)
+                            .map(|cell| !self.is_written(&cell))
+                            .unwrap_or(false)
+                }
+                _ => false,
+            };
+            path.pop();
+            if !is_unchanged {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if the value of the expression evaluated by the term with the given TID
+    /// is the same in each iteration of the loop.
+    fn is_expression_unchanged(
+        &self,
+        tid: &Tid,
+        expression: &Expression,
+        path: &mut Vec<&'a Tid>,
+    ) -> bool {
+        expression
+            .input_vars()
+            .into_iter()
+            .all(|var| self.is_unchanged(tid, var, path))
+    }
+
+    /// Returns `true` if the loop is never left through the conditional jump.
+    fn is_unreachable_exit(&self, jmp: &Term<Jmp>, condition: &Expression, exit_if: bool) -> bool {
+        let constant_condition = self
+            .pointer_inference
+            .get_state_at_tid(&jmp.tid)
+            .and_then(|state| state.eval(condition).try_to_bitvec().ok());
+        if let Some(value) = constant_condition {
+            return value.is_zero() == exit_if;
+        }
+        self.is_expression_unchanged(&jmp.tid, condition, &mut Vec::new())
+    }
+}
+
+/// Get the first block of the loop that is reachable from outside the loop.
+fn get_loop_header<'a>(sub: &'a Term<Sub>, loop_blocks: &HashSet<Tid>) -> Option<&'a Term<Blk>> {
+    let entered_blocks: HashSet<&Tid> = sub
+        .term
+        .blocks
+        .iter()
+        .filter(|block| !loop_blocks.contains(&block.tid))
+        .flat_map(|block| get_intraprocedural_jump_targets(block, sub))
+        .collect();
+    let mut blocks = sub
+        .term
+        .blocks
+        .iter()
+        .filter(|block| loop_blocks.contains(&block.tid));
+    let first_block = blocks.clone().next();
+    blocks
+        .find(|block| entered_blocks.contains(&block.tid))
+        .or(first_block)
+}
+
+/// Generate the warning for a loop that is never left.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    header: &Term<Blk>,
+    exit_jumps: &[&Term<Jmp>],
+) -> CweWarning {
+    let description = if exit_jumps.is_empty() {
+        format!(
+            "(Infinite Loop) The loop at {} in {} has no exit.",
+            header.tid.address,
+            sub.term.display_name()
+        )
+    } else {
+        format!(
+            "(Infinite Loop) The exit conditions of the loop at {} in {} never lead out of the loop once it is entered.",
+            header.tid.address, sub.term.display_name()
+        )
+    };
+    let mut addresses = vec![header.tid.address.clone()];
+    addresses.extend(exit_jumps.iter().map(|jmp| jmp.tid.address.clone()));
+    let mut tids = vec![header.tid.to_string()];
+    tids.extend(exit_jumps.iter().map(|jmp| jmp.tid.to_string()));
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(addresses)
+        .tids(tids)
+        .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Flag loops without exits or whose exit conditions can never be satisfied.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let exit_functions = get_symbol_map(project, &config.exit_functions);
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let def_use_chains = match analysis_results.def_use_chains(&sub.tid) {
+            Some(def_use_chains) => def_use_chains,
+            None => continue,
+        };
+        for loop_blocks in get_loops(sub) {
+            let exits = get_loop_exits(sub, &loop_blocks, &exit_functions);
+            if exits.contains(&LoopExit::Unconditional) {
+                continue;
+            }
+            let context = LoopContext::new(
+                project,
+                sub,
+                &loop_blocks,
+                def_use_chains,
+                pointer_inference,
+            );
+            let mut exit_jumps = Vec::new();
+            let all_exits_unreachable = exits.iter().all(|exit| match exit {
+                LoopExit::Conditional {
+                    jmp,
+                    condition,
+                    exit_if,
+                } => {
+                    exit_jumps.push(*jmp);
+                    context.is_unreachable_exit(jmp, condition, *exit_if)
+                }
+                LoopExit::Unconditional => false,
+            });
+            if all_exits_unreachable {
+                if let Some(header) = get_loop_header(sub, &loop_blocks) {
+                    warnings.push(generate_cwe_warning(sub, header, &exit_jumps));
+                }
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use serde_json::json;
+
+    fn equal(lhs: &Variable, rhs: Expression) -> Expression {
+        Expression::BinOp {
+            op: BinOpType::IntEqual,
+            lhs: Box::new(Expression::Var(lhs.clone())),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    #[test]
+    fn loop_exits_and_unchanged_conditions() {
+        let project = Project::mock_empty();
+        let rax = Variable::mock("RAX", 8);
+        let rcx = Variable::mock("RCX", 8);
+        let counter_flag = Variable::mock("ZF", 1);
+        let limit_flag = Variable::mock("CF", 1);
+        // entry -> loop (self loop) -> exit
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                "entry",
+                vec![
+                    Def::assign("init_counter", rax.clone(), Expression::const_from_i64(0)),
+                    Def::assign("init_limit", rcx.clone(), Expression::const_from_i64(10)),
+                ],
+                vec![Jmp::Branch(Tid::new("loop"))],
+            ),
+            Blk::mock_with_jmps(
+                "loop",
+                vec![
+                    Def::assign(
+                        "increment",
+                        rax.clone(),
+                        Expression::Var(rax.clone()).plus_const(1),
+                    ),
+                    Def::assign(
+                        "compare_counter",
+                        counter_flag.clone(),
+                        equal(&rax, Expression::Var(rcx.clone())),
+                    ),
+                    Def::assign(
+                        "compare_limit",
+                        limit_flag.clone(),
+                        equal(&rcx, Expression::const_from_i64(0)),
+                    ),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("loop"),
+                        condition: Expression::Var(counter_flag.clone()),
+                    },
+                    Jmp::Branch(Tid::new("exit")),
+                ],
+            ),
+            Blk::mock_with_jmps("exit", Vec::new(), vec![Jmp::Return(Expression::Var(rax))]),
+        ];
+        let loop_blocks: HashSet<Tid> = vec![Tid::new("loop")].into_iter().collect();
+        let exits = get_loop_exits(&sub, &loop_blocks, &HashMap::new());
+        let cbranch = &sub.term.blocks[1].term.jmps[0];
+        assert_eq!(
+            exits,
+            vec![LoopExit::Conditional {
+                jmp: cbranch,
+                condition: &Expression::Var(counter_flag.clone()),
+                exit_if: false,
+            }]
+        );
+        assert_eq!(
+            get_loop_header(&sub, &loop_blocks).unwrap().tid,
+            Tid::new("loop")
+        );
+
+        let def_use_chains = DefUseChains::new(&project, &sub);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let context = LoopContext::new(
+            &project,
+            &sub,
+            &loop_blocks,
+            &def_use_chains,
+            &pointer_inference,
+        );
+        // The counter is incremented in each iteration.
+        assert!(!context.is_expression_unchanged(
+            &cbranch.tid,
+            &Expression::Var(counter_flag),
+            &mut Vec::new()
+        ));
+        // The limit is only set before the loop.
+        assert!(context.is_expression_unchanged(
+            &cbranch.tid,
+            &Expression::Var(limit_flag),
+            &mut Vec::new()
+        ));
+
+        // A call to an exit function leaves the loop.
+        let mut exit_symbol = ExternSymbol::mock();
+        exit_symbol.tid = Tid::new("exit");
+        let exit_functions: HashMap<Tid, &ExternSymbol> =
+            vec![(exit_symbol.tid.clone(), &exit_symbol)]
+                .into_iter()
+                .collect();
+        sub.term.blocks[1].term.jmps = vec![Term {
+            tid: Tid::new("call_exit"),
+            term: Jmp::Call {
+                target: Tid::new("exit"),
+                return_: Some(Tid::new("loop")),
+            },
+        }];
+        assert_eq!(
+            get_loop_exits(&sub, &loop_blocks, &exit_functions),
+            vec![LoopExit::Unconditional]
+        );
+        assert!(get_loop_exits(&sub, &loop_blocks, &HashMap::new()).is_empty());
+    }
+
+    /// A function consisting of an entry block executing the given Defs
+    /// and a loop block executing the given Defs and jumps.
+    /// Jumps of the loop to the block `exit` leave the loop.
+    fn loop_sub(
+        name: &str,
+        entry_defs: Vec<Term<Def>>,
+        loop_defs: Vec<Term<Def>>,
+        loop_jmps: Vec<Jmp>,
+    ) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        sub.term.blocks = vec![
+            Blk::mock_with_jmps(
+                &format!("{}_entry", name),
+                entry_defs,
+                vec![Jmp::Branch(Tid::new(format!("{}_loop", name)))],
+            ),
+            Blk::mock_with_jmps(&format!("{}_loop", name), loop_defs, loop_jmps),
+            Blk::mock_with_jmps(
+                &format!("{}_exit", name),
+                Vec::new(),
+                vec![Jmp::Return(Expression::const_from_i64(0))],
+            ),
+        ];
+        sub
+    }
+
+    /// Run the check on the project and return the names of the functions with warnings.
+    fn flagged_functions(project: &Project, config: serde_json::Value) -> Vec<String> {
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let graph = get_program_cfg(&project.program, extern_subs);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+            .with_pointer_inference_config(&json!({
+                "allocation_symbols": [],
+                "deallocation_symbols": []
+            }));
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        warnings
+            .into_iter()
+            .map(|warning| warning.symbols[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn infinite_loops() {
+        let rax = Variable::mock("RAX", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let flag = Variable::mock("ZF", 1);
+        let loop_while_flag = |name: &str| {
+            vec![
+                Jmp::CBranch {
+                    target: Tid::new(format!("{}_loop", name)),
+                    condition: Expression::Var(flag.clone()),
+                },
+                Jmp::Branch(Tid::new(format!("{}_exit", name))),
+            ]
+        };
+        let frame_pointer = |tid: &str| {
+            Def::assign(
+                tid,
+                rbp.clone(),
+                Expression::Var(Variable::mock("RSP", 8)).plus_const(-0x10),
+            )
+        };
+        let load_flag = |tid: &str| {
+            vec![
+                Def::load(tid, rax.clone(), Expression::Var(rbp.clone())),
+                Def::assign(
+                    &format!("{}_compare", tid),
+                    flag.clone(),
+                    equal(&rax, Expression::const_from_i64(0)),
+                ),
+            ]
+        };
+        let mut project = Project::mock_empty();
+        let mut exit_symbol = ExternSymbol::mock();
+        exit_symbol.tid = Tid::new("exit");
+        exit_symbol.name = "exit".to_string();
+        project.program.term.extern_symbols = vec![exit_symbol];
+        project.program.term.subs = vec![
+            // A loop without any exit.
+            loop_sub(
+                "no_exit",
+                Vec::new(),
+                Vec::new(),
+                vec![Jmp::Branch(Tid::new("no_exit_loop"))],
+            ),
+            // A loop left through a call to `exit`.
+            loop_sub(
+                "exit_call",
+                Vec::new(),
+                Vec::new(),
+                vec![Jmp::Call {
+                    target: Tid::new("exit"),
+                    return_: Some(Tid::new("exit_call_loop")),
+                }],
+            ),
+            // The condition is constant and never leads out of the loop.
+            loop_sub(
+                "constant",
+                vec![Def::assign(
+                    "constant_init",
+                    rax.clone(),
+                    Expression::const_from_i64(10),
+                )],
+                vec![Def::assign(
+                    "constant_compare",
+                    flag.clone(),
+                    equal(&rax, Expression::const_from_i64(10)),
+                )],
+                loop_while_flag("constant"),
+            ),
+            // The condition depends on a parameter that does not change inside the loop.
+            loop_sub(
+                "unchanged",
+                Vec::new(),
+                vec![Def::assign(
+                    "unchanged_compare",
+                    flag.clone(),
+                    equal(&rdi, Expression::const_from_i64(0)),
+                )],
+                loop_while_flag("unchanged"),
+            ),
+            // The condition depends on a counter incremented inside the loop.
+            loop_sub(
+                "counter",
+                vec![Def::assign(
+                    "counter_init",
+                    rax.clone(),
+                    Expression::const_from_i64(0),
+                )],
+                vec![
+                    Def::assign(
+                        "counter_increment",
+                        rax.clone(),
+                        Expression::Var(rax.clone()).plus_const(1),
+                    ),
+                    Def::assign(
+                        "counter_compare",
+                        flag.clone(),
+                        equal(&rax, Expression::const_from_i64(10)),
+                    ),
+                ],
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("counter_exit"),
+                        condition: Expression::Var(flag.clone()),
+                    },
+                    Jmp::Branch(Tid::new("counter_loop")),
+                ],
+            ),
+            // The condition is loaded from a stack variable that is not written inside the loop.
+            loop_sub(
+                "unwritten_memory",
+                vec![frame_pointer("unwritten_memory_frame")],
+                load_flag("unwritten_memory_load"),
+                loop_while_flag("unwritten_memory"),
+            ),
+            // The condition is loaded from a stack variable that is written inside the loop.
+            loop_sub(
+                "written_memory",
+                vec![frame_pointer("written_memory_frame")],
+                [
+                    vec![Def::store(
+                        "written_memory_store",
+                        Expression::Var(rbp.clone()),
+                        Expression::Var(rdi.clone()),
+                    )],
+                    load_flag("written_memory_load"),
+                ]
+                .concat(),
+                loop_while_flag("written_memory"),
+            ),
+        ];
+
+        let config = json!({"exit_functions": ["exit"]});
+        assert_eq!(
+            flagged_functions(&project, config),
+            vec!["no_exit", "constant", "unchanged", "unwritten_memory"]
+        );
+        // Without configured exit functions, calls to `exit` inside a loop do not leave it.
+        let config = json!({"exit_functions": []});
+        assert_eq!(
+            flagged_functions(&project, config),
+            vec![
+                "no_exit",
+                "exit_call",
+                "constant",
+                "unchanged",
+                "unwritten_memory"
+            ]
+        );
+    }
+
+    #[test]
+    fn config_parsing() {
+        assert!(parse_config::<Config>(&json!({"exit_functions": ["exit"]})).is_ok());
+        assert!(parse_config::<Config>(&json!({})).is_err());
+        assert!(parse_config::<Config>(&json!({
+            "exit_functions": [],
+            "loop_functions": []
+        }))
+        .is_err());
+    }
+}
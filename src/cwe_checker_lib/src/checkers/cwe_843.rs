@@ -0,0 +1,357 @@
+//! This module implements a check for CWE-843: Access of Resource Using Incompatible Type ('Type Confusion').
+//!
+//! If a memory location is written as a value of one type and later read as a value of another type,
+//! the program works with a value that it did not intend to create.
+//! In C this happens mostly with unions and with hand-rolled tagged types,
+//! where the tag that selects the active member is not checked (correctly) before the access.
+//! If an integer is reinterpreted as a pointer, an attacker controlling the integer controls the accessed memory.
+//!
+//! See <https://cwe.mitre.org/data/definitions/843.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! we collect all accesses to exactly known offsets of heap objects in the program.
+//! Since the analysis propagates the identifiers of heap objects into called functions,
+//! accesses in different functions and on different paths are collected for the same object.
+//! For each access we record its width and how the accessed value is used:
+//! - Stores record whether the stored value is a pointer or an integer.
+//! - Loads record whether the loaded value is dereferenced in the same basic block,
+//!   i.e. used (possibly after pointer arithmetic) as the address of another memory access or as the target of an indirect jump or call.
+//!
+//! A warning is generated for an offset of a heap object if
+//! - an integer narrower than a pointer is stored to it and a pointer-sized value loaded from it is dereferenced, or
+//! - a pointer is stored to it and a value narrower than a pointer is loaded from it.
+//!
+//! ## False Positives
+//!
+//! - Objects allocated by the same call (e.g. in an allocation wrapper function) are represented by the same abstract object,
+//!   even if they are used for different types.
+//! - Unions whose active member is selected by a tag are reported if the analysis does not distinguish the paths for different tags.
+//! - Memory reused for objects of different types by a custom allocator is not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Only heap objects are checked.
+//! - Accesses through pointers that may point to more than one object or to inexact offsets are not checked.
+//! - Loaded values that are dereferenced only in another basic block are not recognized as pointers.
+//! - Type confusions between types of the same width (e.g. `float` and `int`) are not detected.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+use std::collections::{BTreeMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE843",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// The ways in which an accessed value is used.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum AccessKind {
+    /// A store of a value that is not known to be a pointer.
+    IntegerStore,
+    /// A store of a pointer.
+    PointerStore,
+    /// A load of a value that is not dereferenced in the same block.
+    Load,
+    /// A load of a value that is used as a pointer in the same block.
+    DereferencedLoad,
+}
+
+/// An access to an exactly known offset of a heap object.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+struct Access {
+    /// The `Def` term of the access.
+    tid: Tid,
+    /// The name of the function containing the access.
+    sub_name: String,
+    /// The number of accessed bytes.
+    size: ByteSize,
+    /// How the accessed value is used.
+    kind: AccessKind,
+}
+
+/// Return the heap object and the offset into it that the given address points to,
+/// if the address points to exactly one heap object at an exactly known offset.
+fn get_heap_location(state: &State, address: &Expression) -> Option<(AbstractIdentifier, i64)> {
+    let pointer = match state.eval(address) {
+        Data::Pointer(pointer) if pointer.targets().len() == 1 => pointer,
+        _ => return None,
+    };
+    let (id, offset) = pointer.targets().iter().next().unwrap();
+    if !matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap))) {
+        return None;
+    }
+    Some((id.clone(), offset.try_to_offset().ok()?))
+}
+
+/// Check whether the value loaded into `var` by the `Def` at the given index of the block
+/// is used as an address of a memory access or as a jump target later in the block.
+/// Values derived from the loaded value by assignments (e.g. pointer arithmetic) are tracked as well.
+fn is_dereferenced_in_block(block: &Term<Blk>, load_index: usize, var: &Variable) -> bool {
+    let mut tracked_vars: HashSet<&Variable> = vec![var].into_iter().collect();
+    let uses_tracked_var = |expression: &Expression, tracked_vars: &HashSet<&Variable>| -> bool {
+        expression
+            .input_vars()
+            .iter()
+            .any(|input| tracked_vars.contains(input))
+    };
+    for def in block.term.defs.iter().skip(load_index + 1) {
+        match &def.term {
+            Def::Load { var, address } => {
+                if uses_tracked_var(address, &tracked_vars) {
+                    return true;
+                }
+                tracked_vars.remove(var);
+            }
+            Def::Store { address, .. } => {
+                if uses_tracked_var(address, &tracked_vars) {
+                    return true;
+                }
+            }
+            Def::Assign { var, value } => {
+                if uses_tracked_var(value, &tracked_vars) {
+                    tracked_vars.insert(var);
+                } else {
+                    tracked_vars.remove(var);
+                }
+            }
+        }
+        if tracked_vars.is_empty() {
+            return false;
+        }
+    }
+    block.term.jmps.iter().any(|jmp| match &jmp.term {
+        Jmp::BranchInd(target) | Jmp::CallInd { target, .. } => {
+            uses_tracked_var(target, &tracked_vars)
+        }
+        _ => false,
+    })
+}
+
+/// Return the conflicting pair of a store and a load if the given accesses to the same location
+/// use incompatible types.
+fn find_type_confusion(accesses: &[Access], pointer_size: ByteSize) -> Option<(&Access, &Access)> {
+    for store in accesses.iter() {
+        for load in accesses.iter() {
+            let is_confusion = match (store.kind, load.kind) {
+                (AccessKind::IntegerStore, AccessKind::DereferencedLoad) => {
+                    store.size < pointer_size && load.size == pointer_size
+                }
+                (AccessKind::PointerStore, AccessKind::Load | AccessKind::DereferencedLoad) => {
+                    store.size == pointer_size && load.size < pointer_size
+                }
+                _ => false,
+            };
+            if is_confusion {
+                return Some((store, load));
+            }
+        }
+    }
+    None
+}
+
+/// Generate the CWE warning for a type confusion between the given store and load.
+fn generate_cwe_warning(
+    object_id: &AbstractIdentifier,
+    offset: i64,
+    store: &Access,
+    load: &Access,
+) -> CweWarning {
+    let allocation_site = object_id.get_tid();
+    let (stored_type, loaded_type) = match store.kind {
+        AccessKind::PointerStore => ("pointer", "integer"),
+        _ => ("integer", "pointer"),
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Type Confusion) The heap object allocated at {} is written as a {}-byte {} at offset {} at {} in {} and read as a {}-byte {} at {} in {}",
+            allocation_site.address,
+            u64::from(store.size),
+            stored_type,
+            offset,
+            store.tid.address,
+            store.sub_name,
+            u64::from(load.size),
+            loaded_type,
+            load.tid.address,
+            load.sub_name
+        ),
+    )
+    .tids(vec![
+        format!("{}", store.tid),
+        format!("{}", load.tid),
+        format!("{}", allocation_site),
+    ])
+    .addresses(vec![
+        store.tid.address.clone(),
+        load.tid.address.clone(),
+        allocation_site.address.clone(),
+    ])
+    .symbols(vec![store.sub_name.clone(), load.sub_name.clone()])
+    .other(vec![vec!["offset".to_string(), offset.to_string()]])
+    .root_cause(object_id)
+}
+
+/// Run the CWE check.
+/// We collect the accesses to all exactly known offsets of heap objects
+/// and check the accesses to each offset for incompatible types.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut accesses: BTreeMap<(AbstractIdentifier, i64), Vec<Access>> = BTreeMap::new();
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for (index, def) in block.term.defs.iter().enumerate() {
+                let (address, size) = match &def.term {
+                    Def::Load { var, address } => (address, var.size),
+                    Def::Store { address, value } => (address, value.bytesize()),
+                    Def::Assign { .. } => continue,
+                };
+                let state = match pointer_inference.get_state_at_tid(&def.tid) {
+                    Some(state) => state,
+                    None => continue,
+                };
+                let location = match get_heap_location(&state, address) {
+                    Some(location) => location,
+                    None => continue,
+                };
+                let kind = match &def.term {
+                    Def::Store { value, .. } => match state.eval(value) {
+                        Data::Pointer(_) => AccessKind::PointerStore,
+                        _ => AccessKind::IntegerStore,
+                    },
+                    Def::Load { var, .. } if is_dereferenced_in_block(block, index, var) => {
+                        AccessKind::DereferencedLoad
+                    }
+                    _ => AccessKind::Load,
+                };
+                accesses.entry(location).or_default().push(Access {
+                    tid: def.tid.clone(),
+                    sub_name: sub.term.display_name().to_string(),
+                    size,
+                    kind,
+                });
+            }
+        }
+    }
+    let pointer_size = project.get_pointer_bytesize();
+    let cwe_warnings = accesses
+        .iter()
+        .filter_map(|((object_id, offset), accesses)| {
+            let (store, load) = find_type_confusion(accesses, pointer_size)?;
+            Some(generate_cwe_warning(object_id, *offset, store, load))
+        })
+        .collect();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(name: &str, size: u64, kind: AccessKind) -> Access {
+        Access {
+            tid: Tid::new(name),
+            sub_name: "func".to_string(),
+            size: ByteSize::new(size),
+            kind,
+        }
+    }
+
+    #[test]
+    fn dereferenced_loads() {
+        let mut block = Blk::mock();
+        block.term.defs = vec![
+            Def::load(
+                "load",
+                Variable::mock("RAX", 8),
+                Expression::Var(Variable::mock("RDI", 8)),
+            ),
+            Def::assign(
+                "add",
+                Variable::mock("RBX", 8),
+                Expression::Var(Variable::mock("RAX", 8)).plus_const(8),
+            ),
+            Def::assign(
+                "overwrite",
+                Variable::mock("RAX", 8),
+                Expression::Const(Bitvector::from_i64(0)),
+            ),
+            Def::store(
+                "store",
+                Expression::Var(Variable::mock("RBX", 8)),
+                Expression::Const(Bitvector::from_i64(0)),
+            ),
+        ];
+        assert!(is_dereferenced_in_block(
+            &block,
+            0,
+            &Variable::mock("RAX", 8)
+        ));
+        assert!(!is_dereferenced_in_block(
+            &block,
+            2,
+            &Variable::mock("RAX", 8)
+        ));
+        // Indirect calls dereference their target.
+        block.term.defs.truncate(1);
+        assert!(!is_dereferenced_in_block(
+            &block,
+            0,
+            &Variable::mock("RAX", 8)
+        ));
+        block.term.jmps = vec![Term {
+            tid: Tid::new("call"),
+            term: Jmp::CallInd {
+                target: Expression::Var(Variable::mock("RAX", 8)),
+                return_: None,
+            },
+        }];
+        assert!(is_dereferenced_in_block(
+            &block,
+            0,
+            &Variable::mock("RAX", 8)
+        ));
+    }
+
+    #[test]
+    fn type_confusions() {
+        let pointer_size = ByteSize::new(8);
+        let int_store = access("int_store", 4, AccessKind::IntegerStore);
+        let pointer_store = access("pointer_store", 8, AccessKind::PointerStore);
+        let dereferenced_load = access("dereferenced_load", 8, AccessKind::DereferencedLoad);
+        let narrow_load = access("narrow_load", 4, AccessKind::Load);
+
+        let accesses = vec![int_store.clone(), dereferenced_load.clone()];
+        assert_eq!(
+            find_type_confusion(&accesses, pointer_size),
+            Some((&int_store, &dereferenced_load))
+        );
+        let accesses = vec![pointer_store.clone(), narrow_load.clone()];
+        assert_eq!(
+            find_type_confusion(&accesses, pointer_size),
+            Some((&pointer_store, &narrow_load))
+        );
+        // Accesses with compatible types are not reported.
+        let accesses = vec![pointer_store, dereferenced_load];
+        assert!(find_type_confusion(&accesses, pointer_size).is_none());
+        let accesses = vec![int_store, narrow_load, access("load", 8, AccessKind::Load)];
+        assert!(find_type_confusion(&accesses, pointer_size).is_none());
+    }
+}
@@ -0,0 +1,312 @@
+//! This module implements a check for CWE-88: Improper Neutralization of Argument Delimiters in a Command ('Argument Injection').
+//!
+//! Programs executing other programs with `execve`-like functions pass the arguments as an array of strings.
+//! No shell is involved, so shell metacharacters are harmless (in contrast to [CWE-78](super::cwe_78)).
+//! But if an attacker controls one of the arguments, they may inject options into the command line,
+//! e.g. `--output=/etc/passwd`, changing the behaviour of the executed program.
+//!
+//! See <https://cwe.mitre.org/data/definitions/88.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the `exec_functions` configured in config.json
+//! the [Pointer Inference analysis](crate::analysis::pointer_inference) computes the argument array
+//! passed in the configured parameter.
+//! The elements of the array are the values stored to it in the calling function.
+//! Each element (except the name of the program) is traced backwards to untrusted input
+//! as in the [CWE-606 check](super::cwe_606):
+//! the return values and the memory written by the `taint_sources` configured in config.json
+//! (e.g. `getenv` or the buffer of `fgets`)
+//! and, in `main`, the command line arguments.
+//! A warning is generated for each call with untrusted elements in its argument array,
+//! unless an element `"--"` (marking the end of options) precedes all untrusted elements.
+//!
+//! ## False Positives
+//!
+//! - Untrusted arguments may be validated before they are passed to the call.
+//! - Argument arrays that are reused for several calls contain the elements stored for all calls.
+//!
+//! ## False Negatives
+//!
+//! - Argument arrays filled in other functions than the one calling the exec function are not checked.
+//! - Elements stored through pointers with unknown offsets into the argument array are not found.
+//! - Untrusted input is only traced within a function.
+
+use super::cwe_606::{InputTracer, Sources, FUNCTION_INPUT};
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::utils::taint_sources::TaintSourceConfig;
+use crate::CweModule;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE88",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Functions executing a program, mapped to the index of the parameter containing the argument array.
+    exec_functions: BTreeMap<String, usize>,
+    /// The untrusted input sources.
+    taint_sources: TaintSourceConfig,
+}
+
+/// An element of an argument array, given by its index, the store writing it and the stored value.
+type ArgumentElement<'a> = (u64, &'a Term<Def>, &'a Expression);
+
+/// Get the elements stored to the argument array starting at the given offset of the memory object.
+///
+/// The elements are sorted by their index.
+fn get_argument_elements<'a>(
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+    array: &(AbstractIdentifier, i64),
+    pointer_size: i64,
+) -> Vec<ArgumentElement<'a>> {
+    let mut elements = Vec::new();
+    for def in sub
+        .term
+        .blocks
+        .iter()
+        .flat_map(|block| block.term.defs.iter())
+    {
+        if let Def::Store { address, value } = &def.term {
+            if let Some((id, offset)) =
+                pointer_inference.get_unique_memory_cell_at_tid(&def.tid, address)
+            {
+                let distance = offset - array.1;
+                if id == array.0 && distance >= 0 && distance % pointer_size == 0 {
+                    elements.push(((distance / pointer_size) as u64, def, value));
+                }
+            }
+        }
+    }
+    elements.sort_by_key(|(index, _, _)| *index);
+    elements
+}
+
+/// Returns `true` if the stored value is a pointer to the string `"--"`, which marks the end of options.
+fn is_end_of_options(
+    analysis_results: &AnalysisResults,
+    store: &Term<Def>,
+    value: &Expression,
+) -> bool {
+    analysis_results
+        .pointer_inference()
+        .and_then(|pointer_inference| pointer_inference.get_state_at_tid(&store.tid))
+        .and_then(|state| state.eval(value).try_to_bitvec().ok())
+        .and_then(|address| {
+            analysis_results
+                .runtime_memory_image
+                .read_string_until_null_terminator(&address)
+                .ok()
+        })
+        .map(|string| string == "--")
+        .unwrap_or(false)
+}
+
+/// Get the indices of the untrusted elements of the argument array together with their sources.
+///
+/// The element at index zero (the name of the program) and elements after an end-of-options marker are ignored.
+fn get_untrusted_elements<'a>(
+    analysis_results: &AnalysisResults,
+    tracer: &InputTracer<'a>,
+    sub: &'a Term<Sub>,
+    elements: &[ArgumentElement<'a>],
+) -> Vec<(u64, Sources<'a>)> {
+    let mut untrusted_elements = Vec::new();
+    for (index, store, value) in elements.iter().filter(|(index, _, _)| *index > 0) {
+        if is_end_of_options(analysis_results, store, value) {
+            break;
+        }
+        let mut sources = tracer.get_sources(&store.tid, value);
+        if let Some(state) = analysis_results
+            .pointer_inference()
+            .and_then(|pointer_inference| pointer_inference.get_state_at_tid(&store.tid))
+        {
+            sources.extend(tracer.get_pointer_sources(&state.eval(value)));
+        }
+        if sub.term.name != "main" {
+            sources.remove(&sub.tid);
+        }
+        if !sources.is_empty() {
+            untrusted_elements.push((*index, sources));
+        }
+    }
+    untrusted_elements
+}
+
+/// Generate the warning for a call to an exec function with untrusted elements in its argument array.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    untrusted_elements: &[(u64, Sources)],
+) -> CweWarning {
+    let mut source_names: Vec<&str> = untrusted_elements
+        .iter()
+        .flat_map(|(_, sources)| sources.values().cloned())
+        .map(|name| {
+            if name == FUNCTION_INPUT {
+                "command line arguments"
+            } else {
+                name
+            }
+        })
+        .collect();
+    source_names.sort_unstable();
+    source_names.dedup();
+    let indices: Vec<String> = untrusted_elements
+        .iter()
+        .map(|(index, _)| index.to_string())
+        .collect();
+    let description = format!(
+        "(Argument Injection) The argument array passed to {} at {} in {} contains elements derived from {} (indices {}).",
+        symbol.name,
+        call.tid.address,
+        sub.term.display_name(),
+        source_names.join(", "),
+        indices.join(", ")
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![call.tid.address.clone()])
+        .tids(vec![call.tid.to_string()])
+        .symbols(vec![
+            sub.term.display_name().to_string(),
+            symbol.name.clone(),
+        ])
+        .other(vec![std::iter::once("sources".to_string())
+            .chain(source_names.iter().map(|name| name.to_string()))
+            .collect()])
+}
+
+/// Flag calls to exec functions whose argument arrays contain untrusted elements.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let exec_function_names: Vec<String> = config.exec_functions.keys().cloned().collect();
+    let exec_functions = get_symbol_map(project, &exec_function_names);
+    if exec_functions.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let return_value_sources = config.taint_sources.get_return_value_sources(project);
+    let parameter_sources = config.taint_sources.get_parameter_sources(project);
+    let pointer_size = u64::from(project.get_pointer_bytesize()) as i64;
+
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let exec_calls = get_callsites(sub, &exec_functions);
+        if exec_calls.is_empty() {
+            continue;
+        }
+        let tracer = InputTracer::new(
+            analysis_results,
+            sub,
+            return_value_sources.clone(),
+            &parameter_sources,
+            &config.taint_sources.parameter_sources,
+            true,
+        );
+        for (_, call, symbol) in exec_calls {
+            let argv = pointer_inference
+                .get_state_at_tid(&call.tid)
+                .and_then(|state| {
+                    state
+                        .eval_parameter_arg(
+                            &get_variadic_parameter(
+                                project,
+                                symbol,
+                                config.exec_functions[&symbol.name],
+                            ),
+                            &project.stack_pointer_register,
+                            analysis_results.runtime_memory_image,
+                        )
+                        .ok()
+                });
+            let arrays: Vec<(AbstractIdentifier, i64)> = match argv {
+                Some(Data::Pointer(pointer)) => pointer
+                    .targets()
+                    .iter()
+                    .filter_map(|(id, offset)| {
+                        Some((id.clone(), offset.try_to_bitvec().ok()?.try_to_i64().ok()?))
+                    })
+                    .collect(),
+                _ => continue,
+            };
+            let mut untrusted_elements = Vec::new();
+            for array in arrays.iter() {
+                let elements = get_argument_elements(pointer_inference, sub, array, pointer_size);
+                untrusted_elements.extend(get_untrusted_elements(
+                    analysis_results,
+                    &tracer,
+                    sub,
+                    &elements,
+                ));
+            }
+            if !untrusted_elements.is_empty() {
+                warnings.push(generate_cwe_warning(sub, call, symbol, &untrusted_elements));
+            }
+        }
+    }
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_sources() {
+        let sub = Sub::mock("main");
+        let call = Term {
+            tid: Tid::new("call_execvp"),
+            term: Jmp::Call {
+                target: Tid::new("execvp"),
+                return_: None,
+            },
+        };
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "execvp".to_string();
+        let getenv_call = Tid::new("call_getenv");
+        let untrusted_elements: Vec<(u64, Sources)> = vec![
+            (1, vec![(&sub.tid, FUNCTION_INPUT)].into_iter().collect()),
+            (
+                2,
+                vec![(&getenv_call, "getenv"), (&sub.tid, FUNCTION_INPUT)]
+                    .into_iter()
+                    .collect(),
+            ),
+        ];
+        let warning = generate_cwe_warning(&sub, &call, &symbol, &untrusted_elements);
+        assert!(warning
+            .description
+            .contains("derived from command line arguments, getenv (indices 1, 2)"));
+        assert_eq!(
+            warning.other,
+            vec![vec![
+                "sources".to_string(),
+                "command line arguments".to_string(),
+                "getenv".to_string()
+            ]]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! This module implements a check for CWE-908: Use of Uninitialized Resource.
+//!
+//! Memory returned by allocation functions like `malloc` is not initialized.
+//! Reading it before writing to it yields leftover data of previously freed memory objects,
+//! which may leak sensitive data (e.g. when a partially initialized struct is sent over the network)
+//! or lead to undefined behaviour.
+//!
+//! See <https://cwe.mitre.org/data/definitions/908.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The [Pointer Inference analysis](crate::analysis::pointer_inference) tracks for each heap object
+//! allocated by one of the `uninitialized_allocation_symbols` configured for it
+//! which bytes are initialized on all execution paths.
+//! Stores mark the written bytes as initialized.
+//! Stores to inexact offsets and calls to extern functions receiving a pointer to the object
+//! mark the whole object as initialized.
+//! For each `Load` instruction we check whether it reads from an exactly known offset of a heap object
+//! and none of the read bytes is initialized.
+//! If so, a warning is generated.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized.
+//! - Objects initialized through pointers not tracked by the Pointer Inference analysis
+//!   (e.g. by other threads) are not recognized as initialized.
+//!
+//! ## False Negatives
+//!
+//! - Partially initialized reads (e.g. reading a struct where only some fields are initialized) are not reported.
+//! - Uninitialized bytes passed to extern functions (e.g. uninitialized padding of a struct given to `send`)
+//!   are not reported.
+//! - Reads through pointers that may point to more than one object or to inexact offsets are not checked.
+//! - Memory objects are not tracked anymore after the Pointer Inference analysis lost track of them.
+
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE908",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// If the load of the given size from the given address only reads uninitialized bytes of a heap object,
+/// return the TID of the call that allocated the object.
+fn get_uninitialized_read(state: &State, address: &Expression, size: ByteSize) -> Option<Tid> {
+    let address = state.eval(address);
+    if !state.memory.is_uninitialized_read(&address, size) {
+        return None;
+    }
+    match address {
+        Data::Pointer(pointer) => pointer.ids().next().map(|id| id.get_tid().clone()),
+        _ => None,
+    }
+}
+
+/// Generate the CWE warning for a read of uninitialized memory allocated at the given callsite.
+fn generate_cwe_warning(sub: &Term<Sub>, load: &Term<Def>, allocation_site: &Tid) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Use of Uninitialized Resource) The heap memory allocated at {} may be read at {} in {} before it is initialized",
+            allocation_site.address, load.tid.address, sub.term.display_name()
+        ),
+    )
+    .tids(vec![format!("{}", load.tid), format!("{}", allocation_site)])
+    .addresses(vec![
+        load.tid.address.clone(),
+        allocation_site.address.clone(),
+    ])
+    .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Run the CWE check.
+/// For each load instruction we check whether it reads heap memory that is not initialized on all paths to the load.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for def in block.term.defs.iter() {
+                if let Def::Load { var, address } = &def.term {
+                    let allocation_site = pointer_inference
+                        .get_state_at_tid(&def.tid)
+                        .and_then(|state| get_uninitialized_read(&state, address, var.size));
+                    if let Some(allocation_site) = allocation_site {
+                        cwe_warnings.push(generate_cwe_warning(sub, def, &allocation_site));
+                    }
+                }
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+    use crate::analysis::pointer_inference::object::ObjectType;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    #[test]
+    fn uninitialized_heap_reads() {
+        let project = Project::mock_empty();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let rdi = Variable::mock("RDI", 8);
+        let heap_id = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        state.memory.add_abstract_object(
+            heap_id.clone(),
+            Bitvector::from_i64(0).into(),
+            ObjectType::Heap,
+            ByteSize::new(8),
+        );
+        state.set_register(
+            &rdi,
+            PointerDomain::new(heap_id.clone(), Bitvector::from_i64(0).into()).into(),
+        );
+        let field_address = Expression::Var(rdi.clone()).plus_const(8);
+        // Objects are treated as initialized unless allocated by a function returning uninitialized memory.
+        assert!(get_uninitialized_read(&state, &field_address, ByteSize::new(8)).is_none());
+
+        state.memory.mark_object_as_uninitialized(&heap_id);
+        state
+            .write_to_address(
+                &Expression::Var(rdi.clone()),
+                &Data::Value(Bitvector::from_i64(1).into()),
+                &RuntimeMemoryImage::mock(),
+            )
+            .unwrap();
+        assert!(get_uninitialized_read(&state, &Expression::Var(rdi), ByteSize::new(8)).is_none());
+        assert_eq!(
+            get_uninitialized_read(&state, &field_address, ByteSize::new(8)),
+            Some(Tid::new("malloc_call"))
+        );
+    }
+}
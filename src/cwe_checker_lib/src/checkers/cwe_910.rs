@@ -0,0 +1,324 @@
+//! This module implements a check for CWE-910: Use of Expired File Descriptor.
+//!
+//! After a `fork` the parent and the child process usually close the descriptors they do not need,
+//! e.g. the unused ends of a pipe, and move the remaining ones to the standard streams with `dup2`.
+//! If the program uses the value of a descriptor after it was closed in this shuffling,
+//! the operation fails or, if the descriptor number was already reused by another `open`, `pipe` or `dup` call,
+//! silently reads from or writes to an unrelated file.
+//! A typical example is reading from the read end of a pipe after it was moved to `stdin` with `dup2` and closed.
+//!
+//! See <https://cwe.mitre.org/data/definitions/910.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We run the intraprocedural descriptor analysis of the [CWE-775 check](super::cwe_775) on all functions calling one of the `fork_symbols`.
+//! It tracks the descriptors returned by the configured `acquisition_symbols`
+//! and the descriptors written by the `array_acquisition_symbols` (e.g. `pipe` or `socketpair`) to an array on the stack.
+//! Descriptors are released by the `release_symbols`,
+//! which include `dup2` for the descriptor that it replaces.
+//!
+//! A warning is generated for each call to one of the `use_symbols` (e.g. `read` or `write`)
+//! whose descriptor parameter may have been released on a path to the call after a call to a fork symbol.
+//! Calls to `exec*` functions only return if they fail.
+//! A child process continuing after a failed `exec*` call with the descriptors it closed before
+//! is recognized if the paths of the child and the parent merge again.
+//!
+//! ## False Positives
+//!
+//! - Paths that are infeasible at runtime are not recognized, e.g. if the parent and the child path merge
+//!   although the child always terminates.
+//!
+//! ## False Negatives
+//!
+//! - Descriptors released or used in other functions than the one calling the fork symbol are not tracked.
+//! - Descriptors closed by a successful `exec*` call (because of the `FD_CLOEXEC` flag) are not tracked,
+//!   since the code running afterwards is not part of the analyzed binary.
+//! - Only descriptor arrays passed to the array acquisition symbols in registers are tracked.
+//! - Descriptors stored outside of the stack frame of the function (e.g. in global variables) are not tracked.
+
+use super::cwe_775::DescriptorTracker;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_intraprocedural_jump_targets;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE910",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extern functions creating a child process, e.g. `fork`.
+    fork_symbols: Vec<String>,
+    /// Extern functions returning a new descriptor.
+    acquisition_symbols: Vec<String>,
+    /// Extern functions writing two new descriptors to the array passed as the parameter with the given index.
+    #[serde(default)]
+    array_acquisition_symbols: BTreeMap<String, usize>,
+    /// Extern functions releasing the descriptor passed as the parameter with the given index.
+    release_symbols: BTreeMap<String, usize>,
+    /// Extern functions that may keep the descriptors passed to them as parameters.
+    #[serde(default)]
+    escaping_symbols: Vec<String>,
+    /// Extern functions using the descriptor passed as the parameter with the given index.
+    use_symbols: BTreeMap<String, usize>,
+}
+
+/// Get the TIDs of the jumps in the blocks of the function that are reachable after a call to a fork symbol.
+fn get_jumps_after_fork<'a>(
+    sub: &'a Term<Sub>,
+    fork_symbols: &HashMap<Tid, &ExternSymbol>,
+) -> HashSet<&'a Tid> {
+    let mut worklist: Vec<&Tid> = sub
+        .term
+        .blocks
+        .iter()
+        .flat_map(|block| block.term.jmps.iter())
+        .filter_map(|jmp| match &jmp.term {
+            Jmp::Call {
+                target,
+                return_: Some(return_),
+            } if fork_symbols.contains_key(target) => Some(return_),
+            _ => None,
+        })
+        .collect();
+    let mut visited_blocks = HashSet::new();
+    let mut jumps = HashSet::new();
+    while let Some(block_tid) = worklist.pop() {
+        if !visited_blocks.insert(block_tid) {
+            continue;
+        }
+        if let Some(block) = sub.term.blocks.iter().find(|blk| blk.tid == *block_tid) {
+            jumps.extend(block.term.jmps.iter().map(|jmp| &jmp.tid));
+            worklist.extend(get_intraprocedural_jump_targets(block, sub));
+        }
+    }
+    jumps
+}
+
+/// Generate the CWE warning for a call using a descriptor that may be released by the given calls.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    releases: &BTreeSet<Tid>,
+) -> CweWarning {
+    let release_addresses: Vec<String> = releases.iter().map(|tid| tid.address.clone()).collect();
+    let mut addresses = vec![call.tid.address.clone()];
+    addresses.extend(release_addresses.iter().cloned());
+    let mut tids = vec![call.tid.to_string()];
+    tids.extend(releases.iter().map(|tid| tid.to_string()));
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Use of Expired File Descriptor) The descriptor used by {} at {} in {} may already be closed after fork at {}",
+            symbol.name,
+            call.tid.address,
+            sub.term.display_name(),
+            release_addresses.join(", ")
+        ),
+    )
+    .addresses(addresses)
+    .tids(tids)
+    .symbols(vec![sub.term.display_name().to_string(), symbol.name.clone()])
+}
+
+/// Find the calls to use symbols using descriptors that may be released after a call to a fork symbol.
+fn find_expired_uses<'a>(
+    project: &'a Project,
+    tracker: &DescriptorTracker<'a>,
+    fork_symbols: &HashMap<Tid, &ExternSymbol>,
+    use_symbols: &HashMap<&'a Tid, (&'a ExternSymbol, usize)>,
+) -> Vec<CweWarning> {
+    let mut warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let jumps_after_fork = get_jumps_after_fork(sub, fork_symbols);
+        if jumps_after_fork.is_empty() {
+            continue;
+        }
+        for (call, state) in tracker.get_states_at_calls(sub) {
+            let (symbol, parameter) = match &call.term {
+                Jmp::Call { target, .. } => match use_symbols.get(target) {
+                    Some((symbol, index)) => match symbol.parameters.get(*index) {
+                        Some(parameter) => (*symbol, parameter),
+                        None => continue,
+                    },
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let releases: BTreeSet<Tid> = tracker
+                .eval_parameter(&state, call, parameter)
+                .iter()
+                .filter_map(|descriptor| state.get_releases(descriptor))
+                .flatten()
+                .filter(|release| jumps_after_fork.contains(release))
+                .cloned()
+                .collect();
+            if !releases.is_empty() {
+                warnings.push(generate_cwe_warning(sub, call, symbol, &releases));
+            }
+        }
+    }
+    warnings
+}
+
+/// Run the CWE check.
+/// For each call to a use symbol in a function calling a fork symbol
+/// we check whether the descriptor passed to it may be released after the fork.
+pub fn check_cwe<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let config: Config = parse_config(cwe_params).unwrap();
+    let fork_symbols = get_symbol_map(project, &config.fork_symbols);
+    if fork_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let use_symbols: HashMap<&Tid, (&ExternSymbol, usize)> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter_map(|symbol| {
+            config
+                .use_symbols
+                .get(&symbol.name)
+                .map(|index| (&symbol.tid, (symbol, *index)))
+        })
+        .collect();
+    let tracker = DescriptorTracker::with_descriptor_arrays(
+        project,
+        pointer_inference,
+        &config.acquisition_symbols,
+        &config.array_acquisition_symbols,
+        &config.release_symbols,
+        &config.escaping_symbols,
+    );
+    let warnings = find_expired_uses(project, &tracker, &fork_symbols, &use_symbols);
+    (Vec::new(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::get_program_cfg;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    fn mock_symbol(name: &str) -> ExternSymbol {
+        ExternSymbol {
+            tid: Tid::new(name),
+            name: name.to_string(),
+            ..ExternSymbol::mock()
+        }
+    }
+
+    #[test]
+    fn use_after_close_after_fork() {
+        // `func` closes and uses a descriptor before the fork, which is not reported,
+        // and closes and uses another descriptor after the fork.
+        let rax = Variable::mock("RAX", 8);
+        let rdi = Variable::mock("RDI", 8);
+        let rbp = Variable::mock("RBP", 8);
+        let close_and_save = |name: &str, return_: &str| {
+            Blk::mock_with(
+                name,
+                vec![
+                    Def::assign(
+                        &format!("{}_save", name),
+                        rbp.clone(),
+                        Expression::Var(rax.clone()),
+                    ),
+                    Def::assign(
+                        &format!("{}_arg", name),
+                        rdi.clone(),
+                        Expression::Var(rax.clone()),
+                    ),
+                ],
+                Jmp::mock_call("close", return_),
+            )
+        };
+        let use_saved = |name: &str, return_: &str| {
+            Blk::mock_with(
+                name,
+                vec![Def::assign(
+                    &format!("{}_arg", name),
+                    rdi.clone(),
+                    Expression::Var(rbp.clone()),
+                )],
+                Jmp::mock_call("read", return_),
+            )
+        };
+        let mut func = Sub::mock("func");
+        func.term.blocks = vec![
+            Blk::mock_with("entry", Vec::new(), Jmp::mock_call("open", "close_first")),
+            close_and_save("close_first", "use_first"),
+            use_saved("use_first", "fork"),
+            Blk::mock_with("fork", Vec::new(), Jmp::mock_call("fork", "open_second")),
+            Blk::mock_with(
+                "open_second",
+                Vec::new(),
+                Jmp::mock_call("open", "close_second"),
+            ),
+            close_and_save("close_second", "use_second"),
+            use_saved("use_second", "exit"),
+            Blk::mock_with(
+                "exit",
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ];
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        project.program.term.extern_symbols = vec![
+            mock_symbol("open"),
+            mock_symbol("close"),
+            mock_symbol("read"),
+            mock_symbol("fork"),
+        ];
+        project.program.term.subs = vec![func];
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        let tracker = DescriptorTracker::with_descriptor_arrays(
+            &project,
+            &pointer_inference,
+            &["open".to_string()],
+            &BTreeMap::new(),
+            &vec![("close".to_string(), 0)].into_iter().collect(),
+            &[],
+        );
+        let fork_symbols = get_symbol_map(&project, &["fork".to_string()]);
+        let read_symbol = &project.program.term.extern_symbols[2];
+        let use_symbols: HashMap<&Tid, (&ExternSymbol, usize)> =
+            vec![(&read_symbol.tid, (read_symbol, 0))]
+                .into_iter()
+                .collect();
+
+        let jumps_after_fork = get_jumps_after_fork(&project.program.term.subs[0], &fork_symbols);
+        assert_eq!(jumps_after_fork.len(), 4);
+        assert!(!jumps_after_fork.contains(&Tid::new("fork_jmp")));
+
+        let warnings = find_expired_uses(&project, &tracker, &fork_symbols, &use_symbols);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["use_second_jmp".to_string(), "close_second_jmp".to_string()]
+        );
+    }
+}
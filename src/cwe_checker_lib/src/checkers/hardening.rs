@@ -0,0 +1,701 @@
+//! This module implements a check for missing exploit mitigations in ELF binaries.
+//!
+//! The check does not correspond to a specific CWE.
+//! It reports which of the following mitigations are present or absent in the binary:
+//! - A non-executable stack (NX), so that shellcode written to the stack cannot be executed.
+//! - Partial RELRO, which maps relocated data like the `.got` section read-only after relocation.
+//! - Full RELRO, which additionally resolves all symbols at load time,
+//!   so that the `.got.plt` section can be mapped read-only as well.
+//! - Position independent executables (PIE), which can be loaded at randomized addresses (ASLR).
+//! - Stack canaries, which detect overwritten return addresses before a function returns.
+//! - `_FORTIFY_SOURCE`, which replaces calls to unsafe libc functions by variants checking the size of their destination.
+//!
+//! For AArch64 binaries it additionally reports which of the following mitigations are present or absent
+//! in the binary and in its functions:
+//! - Pointer authentication (PAC), which signs return addresses before they are spilled to the stack.
+//! - Branch target identification (BTI), which restricts the targets of indirect branches to landing pads.
+//! - Memory tagging (MTE), which tags memory and pointers to detect spatial and temporal memory safety violations.
+//! - The shadow call stack, which keeps a copy of return addresses on a separate stack addressed by register `x18`.
+//!
+//! ## How the check works
+//!
+//! The properties of the binary are read from its ELF headers, see [`get_elf_security_properties`].
+//! The stack is non-executable if the binary contains a `PT_GNU_STACK` program header without the executable flag.
+//! RELRO is present if the binary contains a `PT_GNU_RELRO` program header,
+//! full RELRO if the dynamic section additionally requests `BIND_NOW`.
+//! The binary is position independent if its type is `ET_DYN`.
+//!
+//! Stack canaries and `_FORTIFY_SOURCE` are recognized by the symbols of the binary:
+//! Binaries with stack canaries reference one of the configured `stack_canary_symbols` (e.g. `__stack_chk_fail`),
+//! fortified binaries reference one of the configured `fortified_symbols` (e.g. `__memcpy_chk`).
+//! Functions like `memcpy` whose fortified variant `__memcpy_chk` is configured but not referenced
+//! are listed as unfortified functions in the warning.
+//!
+//! The AArch64 mitigations of the whole binary are read from the ELF notes:
+//! The `GNU_PROPERTY_AARCH64_FEATURE_1_AND` property of the GNU property note
+//! (generated by `-mbranch-protection`) marks the binary as compatible with BTI and PAC
+//! and the Android memtag note marks it as using memory tagging.
+//!
+//! For each function of an AArch64 binary we scan the intermediate representation
+//! for instruction patterns of the AArch64 mitigations.
+//! PAC, BTI and MTE instructions are lifted to `CallOther` jumps by Ghidra.
+//! They are recognized by the names of their user-defined P-Code operations
+//! configured in the `Hardening` section of the config.json.
+//! The shadow call stack is recognized by stores of the link register (`x30`) to addresses computed from
+//! the shadow call stack register (`x18`) and by loads of the link register from such addresses.
+//!
+//! A warning is generated for the binary if some mitigation is absent.
+//! Its `other` field lists the present and absent mitigations and the unfortified functions.
+//! Additionally, a warning is generated for each function that calls other functions
+//! but lacks pointer authentication or the shadow call stack although other functions of the binary use them.
+//! Functions not calling other functions do not spill the link register and thus do not need these mitigations.
+//! An info log message lists the mitigations used by the binary.
+//!
+//! The check is only run for ELF binaries.
+//!
+//! ## False Positives
+//!
+//! - Statically linked binaries whose symbol tables were stripped are reported for missing stack canaries and `_FORTIFY_SOURCE`.
+//! - Binaries without functions writing to stack buffers do not need stack canaries.
+//! - Ghidra may lift BTI landing pads as no-ops, in which case BTI is only detected through the GNU property note.
+//! - Functions may spill the link register without calling other functions, e.g. to obtain the return address.
+//!
+//! ## False Negatives
+//!
+//! - Stack canaries and fortified functions with custom names (e.g. in firmware without libc) are not recognized.
+//! - Binaries using stack canaries or `_FORTIFY_SOURCE` only for some functions are reported as using them.
+//! - Shadow call stack accesses through registers other than the configured shadow call stack register are not detected.
+//! - Functions with hand-written return address protection are not recognized.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::{
+    get_elf_security_properties, ElfSecurityProperties, GNU_PROPERTY_AARCH64_FEATURE_1_BTI,
+    GNU_PROPERTY_AARCH64_FEATURE_1_PAC,
+};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::CweModule;
+use goblin::elf::program_header::PF_X;
+use std::collections::{BTreeSet, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "Hardening",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The configuration of the check.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Symbols referenced by binaries using stack canaries, e.g. `__stack_chk_fail`.
+    stack_canary_symbols: Vec<String>,
+    /// Fortified variants of libc functions used by binaries compiled with `_FORTIFY_SOURCE`, e.g. `__memcpy_chk`.
+    /// Their names consist of the name of the unfortified function with the prefix `__` and the suffix `_chk`.
+    fortified_symbols: Vec<String>,
+    /// Names of the user-defined P-Code operations of pointer authentication instructions.
+    pointer_authentication_operations: Vec<String>,
+    /// Names of the user-defined P-Code operations of branch target identification instructions.
+    branch_target_identification_operations: Vec<String>,
+    /// Names of the user-defined P-Code operations of memory tagging instructions.
+    memory_tagging_operations: Vec<String>,
+    /// The name of the register holding the shadow call stack pointer.
+    shadow_call_stack_register: String,
+    /// The name of the link register holding the return address.
+    link_register: String,
+}
+
+/// The mitigations recognized by the check.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+enum Mitigation {
+    NonExecutableStack,
+    PartialRelro,
+    FullRelro,
+    PositionIndependentExecutable,
+    StackCanaries,
+    FortifySource,
+    PointerAuthentication,
+    BranchTargetIdentification,
+    MemoryTagging,
+    ShadowCallStack,
+}
+
+impl Mitigation {
+    /// Mitigations recognized in ELF binaries of all architectures.
+    const ELF: [Mitigation; 6] = [
+        Mitigation::NonExecutableStack,
+        Mitigation::PartialRelro,
+        Mitigation::FullRelro,
+        Mitigation::PositionIndependentExecutable,
+        Mitigation::StackCanaries,
+        Mitigation::FortifySource,
+    ];
+
+    /// Mitigations only recognized in AArch64 binaries.
+    /// They are also recognized in single functions.
+    const AARCH64: [Mitigation; 4] = [
+        Mitigation::PointerAuthentication,
+        Mitigation::BranchTargetIdentification,
+        Mitigation::MemoryTagging,
+        Mitigation::ShadowCallStack,
+    ];
+
+    /// Mitigations that are needed by all functions spilling the link register.
+    const RETURN_ADDRESS_PROTECTIONS: [Mitigation; 2] = [
+        Mitigation::PointerAuthentication,
+        Mitigation::ShadowCallStack,
+    ];
+
+    /// Get the mitigations that are recognized in binaries of the given architecture.
+    fn applicable(is_aarch64: bool) -> Vec<Mitigation> {
+        let mut mitigations = Mitigation::ELF.to_vec();
+        if is_aarch64 {
+            mitigations.extend(Mitigation::AARCH64);
+        }
+        mitigations
+    }
+}
+
+impl std::fmt::Display for Mitigation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Mitigation::NonExecutableStack => write!(formatter, "nx_stack"),
+            Mitigation::PartialRelro => write!(formatter, "partial_relro"),
+            Mitigation::FullRelro => write!(formatter, "full_relro"),
+            Mitigation::PositionIndependentExecutable => write!(formatter, "pie"),
+            Mitigation::StackCanaries => write!(formatter, "stack_canaries"),
+            Mitigation::FortifySource => write!(formatter, "fortify_source"),
+            Mitigation::PointerAuthentication => write!(formatter, "pointer_authentication"),
+            Mitigation::BranchTargetIdentification => {
+                write!(formatter, "branch_target_identification")
+            }
+            Mitigation::MemoryTagging => write!(formatter, "memory_tagging"),
+            Mitigation::ShadowCallStack => write!(formatter, "shadow_call_stack"),
+        }
+    }
+}
+
+/// Get the mitigations used by a binary with the given properties.
+fn get_binary_mitigations(
+    properties: &ElfSecurityProperties,
+    config: &Config,
+) -> BTreeSet<Mitigation> {
+    let mut mitigations = BTreeSet::new();
+    if matches!(properties.gnu_stack_flags, Some(flags) if flags & PF_X == 0) {
+        mitigations.insert(Mitigation::NonExecutableStack);
+    }
+    if properties.has_relro_segment {
+        mitigations.insert(Mitigation::PartialRelro);
+        if properties.binds_now {
+            mitigations.insert(Mitigation::FullRelro);
+        }
+    }
+    if properties.is_position_independent {
+        mitigations.insert(Mitigation::PositionIndependentExecutable);
+    }
+    let references_any = |symbols: &[String]| {
+        symbols
+            .iter()
+            .any(|symbol| properties.symbol_names.contains(symbol))
+    };
+    if references_any(&config.stack_canary_symbols) {
+        mitigations.insert(Mitigation::StackCanaries);
+    }
+    if references_any(&config.fortified_symbols) {
+        mitigations.insert(Mitigation::FortifySource);
+    }
+    if properties.aarch64_feature_flags & GNU_PROPERTY_AARCH64_FEATURE_1_BTI != 0 {
+        mitigations.insert(Mitigation::BranchTargetIdentification);
+    }
+    if properties.aarch64_feature_flags & GNU_PROPERTY_AARCH64_FEATURE_1_PAC != 0 {
+        mitigations.insert(Mitigation::PointerAuthentication);
+    }
+    if properties.uses_memory_tagging {
+        mitigations.insert(Mitigation::MemoryTagging);
+    }
+    mitigations
+}
+
+/// Get the functions referenced by the binary whose configured fortified variant is not referenced.
+fn get_unfortified_functions(properties: &ElfSecurityProperties, config: &Config) -> Vec<String> {
+    config
+        .fortified_symbols
+        .iter()
+        .filter(|symbol| !properties.symbol_names.contains(*symbol))
+        .filter_map(|symbol| {
+            symbol
+                .strip_prefix("__")
+                .and_then(|name| name.strip_suffix("_chk"))
+        })
+        .filter(|name| properties.symbol_names.contains(*name))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Check whether the name of a user-defined P-Code operation is contained in the given list of operation names.
+/// The comparison ignores case.
+fn is_operation_in_list(description: &str, operations: &[String]) -> bool {
+    operations
+        .iter()
+        .any(|operation| description.eq_ignore_ascii_case(operation))
+}
+
+/// Get the AArch64 mitigations used by the given function.
+/// Also return whether the function calls other functions.
+fn get_function_mitigations(sub: &Term<Sub>, config: &Config) -> (BTreeSet<Mitigation>, bool) {
+    let mut mitigations = BTreeSet::new();
+    let mut is_leaf_function = true;
+    for block in sub.term.blocks.iter() {
+        // Variables whose value was computed from the shadow call stack register in this block.
+        let mut shadow_stack_pointers: HashSet<&str> = HashSet::new();
+        shadow_stack_pointers.insert(&config.shadow_call_stack_register);
+        let is_shadow_stack_address = |address: &Expression, pointers: &HashSet<&str>| {
+            address
+                .input_vars()
+                .iter()
+                .any(|var| pointers.contains(var.name.as_str()))
+        };
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Store {
+                    address,
+                    value: Expression::Var(value),
+                } if value.name == config.link_register
+                    && is_shadow_stack_address(address, &shadow_stack_pointers) =>
+                {
+                    mitigations.insert(Mitigation::ShadowCallStack);
+                }
+                Def::Load { var, address }
+                    if var.name == config.link_register
+                        && is_shadow_stack_address(address, &shadow_stack_pointers) =>
+                {
+                    mitigations.insert(Mitigation::ShadowCallStack);
+                }
+                Def::Assign { var, value } if var.name != config.shadow_call_stack_register => {
+                    if is_shadow_stack_address(value, &shadow_stack_pointers) {
+                        shadow_stack_pointers.insert(&var.name);
+                    } else {
+                        shadow_stack_pointers.remove(var.name.as_str());
+                    }
+                }
+                Def::Load { var, .. } => {
+                    shadow_stack_pointers.remove(var.name.as_str());
+                }
+                _ => (),
+            }
+        }
+        for jmp in block.term.jmps.iter() {
+            match &jmp.term {
+                Jmp::Call { .. } | Jmp::CallInd { .. } => is_leaf_function = false,
+                Jmp::CallOther { description, .. } => {
+                    if is_operation_in_list(description, &config.pointer_authentication_operations)
+                    {
+                        mitigations.insert(Mitigation::PointerAuthentication);
+                    }
+                    if is_operation_in_list(
+                        description,
+                        &config.branch_target_identification_operations,
+                    ) {
+                        mitigations.insert(Mitigation::BranchTargetIdentification);
+                    }
+                    if is_operation_in_list(description, &config.memory_tagging_operations) {
+                        mitigations.insert(Mitigation::MemoryTagging);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    (mitigations, !is_leaf_function)
+}
+
+/// Generate the part of the `other` field of a warning
+/// listing which of the given mitigations are present and absent.
+fn list_mitigations(
+    mitigations: &[Mitigation],
+    present: &BTreeSet<Mitigation>,
+) -> Vec<Vec<String>> {
+    let (present, absent): (Vec<Mitigation>, Vec<Mitigation>) = mitigations
+        .iter()
+        .partition(|mitigation| present.contains(mitigation));
+    let mut present_list = vec!["present_mitigations".to_string()];
+    present_list.extend(present.iter().map(|mitigation| mitigation.to_string()));
+    let mut absent_list = vec!["absent_mitigations".to_string()];
+    absent_list.extend(absent.iter().map(|mitigation| mitigation.to_string()));
+    vec![present_list, absent_list]
+}
+
+/// Join the names of the given mitigations for use in a warning description.
+fn join_mitigations<'a>(mitigations: impl IntoIterator<Item = &'a Mitigation>) -> String {
+    mitigations
+        .into_iter()
+        .map(|mitigation| mitigation.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Generate the info log listing the used mitigations and the warnings for the binary and its functions
+/// given the mitigations applicable to the binary, the mitigations marked in its ELF headers,
+/// the mitigations used by each function and the unfortified functions referenced by the binary.
+fn generate_cwe_warnings(
+    applicable_mitigations: &[Mitigation],
+    binary_mitigations: &BTreeSet<Mitigation>,
+    function_mitigations: &[(&Term<Sub>, BTreeSet<Mitigation>, bool)],
+    unfortified_functions: &[String],
+) -> (LogMessage, Vec<CweWarning>) {
+    let mut used_mitigations = binary_mitigations.clone();
+    for (_, mitigations, _) in function_mitigations {
+        used_mitigations.extend(mitigations.iter().copied());
+    }
+    used_mitigations.retain(|mitigation| applicable_mitigations.contains(mitigation));
+    let mut cwe_warnings = Vec::new();
+    let absent: Vec<Mitigation> = applicable_mitigations
+        .iter()
+        .copied()
+        .filter(|mitigation| !used_mitigations.contains(mitigation))
+        .collect();
+    if !absent.is_empty() {
+        let mut other = list_mitigations(applicable_mitigations, &used_mitigations);
+        let mut unfortified_list = vec!["unfortified_functions".to_string()];
+        unfortified_list.extend(unfortified_functions.iter().cloned());
+        other.push(unfortified_list);
+        cwe_warnings.push(
+            CweWarning::new(
+                CWE_MODULE.name,
+                CWE_MODULE.version,
+                format!(
+                    "(Missing Hardening) The binary does not use the following mitigations: {}",
+                    join_mitigations(&absent)
+                ),
+            )
+            .other(other),
+        );
+    }
+    for (sub, mitigations, is_non_leaf) in function_mitigations {
+        if !is_non_leaf {
+            continue;
+        }
+        let missing: Vec<Mitigation> = Mitigation::RETURN_ADDRESS_PROTECTIONS
+            .iter()
+            .copied()
+            .filter(|mitigation| {
+                used_mitigations.contains(mitigation) && !mitigations.contains(mitigation)
+            })
+            .collect();
+        if !missing.is_empty() {
+            let mut other = vec![vec![
+                "function".to_string(),
+                sub.term.display_name().to_string(),
+            ]];
+            other.extend(list_mitigations(&Mitigation::AARCH64, mitigations));
+            cwe_warnings.push(
+                CweWarning::new(
+                    CWE_MODULE.name,
+                    CWE_MODULE.version,
+                    format!(
+                        "(Missing Hardening) Function {} at {} does not use {} although other parts of the binary do",
+                        sub.term.display_name(),
+                        sub.tid.address,
+                        join_mitigations(&missing)
+                    ),
+                )
+                .tids(vec![format!("{}", sub.tid)])
+                .addresses(vec![sub.tid.address.clone()])
+                .symbols(vec![sub.term.display_name().to_string()])
+                .other(other),
+            );
+        }
+    }
+    let info_log = LogMessage::new_info(format!(
+        "Mitigations used by the binary: {}",
+        if used_mitigations.is_empty() {
+            "none".to_string()
+        } else {
+            join_mitigations(&used_mitigations)
+        }
+    ))
+    .source(CWE_MODULE.name);
+    (info_log, cwe_warnings)
+}
+
+/// Run the check.
+///
+/// The check is only run for ELF binaries.
+/// For other binaries it returns without any warnings.
+/// The functions of the binary are only scanned for AArch64 binaries.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let properties = match get_elf_security_properties(analysis_results.binary) {
+        Ok(properties) => properties,
+        Err(err) => {
+            let info_log =
+                LogMessage::new_info(format!("Check skipped: {}", err)).source(CWE_MODULE.name);
+            return (vec![info_log], Vec::new());
+        }
+    };
+    let is_aarch64 = project.cpu_architecture.starts_with("AARCH64");
+    let function_mitigations: Vec<_> = if is_aarch64 {
+        project
+            .program
+            .term
+            .subs
+            .iter()
+            .map(|sub| {
+                let (mitigations, is_non_leaf) = get_function_mitigations(sub, &config);
+                (sub, mitigations, is_non_leaf)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let (info_log, cwe_warnings) = generate_cwe_warnings(
+        &Mitigation::applicable(is_aarch64),
+        &get_binary_mitigations(&properties, &config),
+        &function_mitigations,
+        &get_unfortified_functions(&properties, &config),
+    );
+    (vec![info_log], cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_config() -> Config {
+        Config {
+            stack_canary_symbols: vec!["__stack_chk_fail".to_string()],
+            fortified_symbols: vec!["__memcpy_chk".to_string(), "__strcpy_chk".to_string()],
+            pointer_authentication_operations: vec!["paciasp".to_string()],
+            branch_target_identification_operations: vec!["bti".to_string()],
+            memory_tagging_operations: vec!["irg".to_string()],
+            shadow_call_stack_register: "x18".to_string(),
+            link_register: "x30".to_string(),
+        }
+    }
+
+    fn mock_properties(symbol_names: &[&str]) -> ElfSecurityProperties {
+        ElfSecurityProperties {
+            gnu_stack_flags: Some(6),
+            has_relro_segment: true,
+            binds_now: true,
+            is_position_independent: true,
+            symbol_names: symbol_names.iter().map(|name| name.to_string()).collect(),
+            aarch64_feature_flags: 0,
+            uses_memory_tagging: false,
+        }
+    }
+
+    #[test]
+    fn fully_hardened_binary() {
+        let config = mock_config();
+        let properties = mock_properties(&["__stack_chk_fail", "__memcpy_chk", "strcpy"]);
+        let mitigations = get_binary_mitigations(&properties, &config);
+        assert_eq!(mitigations, Mitigation::ELF.iter().copied().collect());
+        let unfortified_functions = get_unfortified_functions(&properties, &config);
+        assert_eq!(unfortified_functions, vec!["strcpy".to_string()]);
+        let (_, warnings) = generate_cwe_warnings(
+            &Mitigation::applicable(false),
+            &mitigations,
+            &[],
+            &unfortified_functions,
+        );
+        assert!(warnings.is_empty());
+
+        // The AArch64 mitigations marked in the ELF notes.
+        let properties = ElfSecurityProperties {
+            aarch64_feature_flags: GNU_PROPERTY_AARCH64_FEATURE_1_BTI
+                | GNU_PROPERTY_AARCH64_FEATURE_1_PAC,
+            uses_memory_tagging: true,
+            ..properties
+        };
+        let mitigations = get_binary_mitigations(&properties, &config);
+        let (_, warnings) = generate_cwe_warnings(
+            &Mitigation::applicable(true),
+            &mitigations,
+            &[],
+            &unfortified_functions,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].other[1],
+            vec![
+                "absent_mitigations".to_string(),
+                "shadow_call_stack".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_mitigations() {
+        let config = mock_config();
+        let properties = ElfSecurityProperties {
+            // An executable stack.
+            gnu_stack_flags: Some(7),
+            binds_now: false,
+            is_position_independent: false,
+            ..mock_properties(&["__stack_chk_fail", "memcpy"])
+        };
+        let mitigations = get_binary_mitigations(&properties, &config);
+        assert_eq!(
+            mitigations,
+            vec![Mitigation::PartialRelro, Mitigation::StackCanaries]
+                .into_iter()
+                .collect()
+        );
+        // Binaries without `PT_GNU_STACK` header have an executable stack.
+        let properties = ElfSecurityProperties {
+            gnu_stack_flags: None,
+            ..properties
+        };
+        assert!(
+            !get_binary_mitigations(&properties, &config).contains(&Mitigation::NonExecutableStack)
+        );
+
+        let unfortified_functions = get_unfortified_functions(&properties, &config);
+        let (_, warnings) = generate_cwe_warnings(
+            &Mitigation::applicable(false),
+            &mitigations,
+            &[],
+            &unfortified_functions,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].other,
+            vec![
+                vec![
+                    "present_mitigations".to_string(),
+                    "partial_relro".to_string(),
+                    "stack_canaries".to_string()
+                ],
+                vec![
+                    "absent_mitigations".to_string(),
+                    "nx_stack".to_string(),
+                    "full_relro".to_string(),
+                    "pie".to_string(),
+                    "fortify_source".to_string()
+                ],
+                vec!["unfortified_functions".to_string(), "memcpy".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn function_mitigations() {
+        let config = mock_config();
+        let x18 = Variable::mock("x18", 8);
+        let x30 = Variable::mock("x30", 8);
+        let temp = Variable {
+            name: "$U1".to_string(),
+            size: ByteSize::new(8),
+            is_temp: true,
+        };
+        let call = Jmp::Call {
+            target: Tid::new("callee"),
+            return_: None,
+        };
+        let call_other = |description: &str| Jmp::CallOther {
+            description: description.to_string(),
+            return_: None,
+        };
+
+        // A function spilling the link register to the shadow call stack through a temporary register.
+        let mut sub = Sub::mock("scs_function");
+        sub.term.blocks = vec![Blk::mock_with_jmps(
+            "scs_block",
+            vec![
+                Def::assign("def_1", temp.clone(), Expression::Var(x18.clone())),
+                Term {
+                    tid: Tid::new("def_2"),
+                    term: Def::Store {
+                        address: Expression::Var(temp.clone()),
+                        value: Expression::Var(x30.clone()),
+                    },
+                },
+            ],
+            vec![call_other("PACIASP"), call.clone()],
+        )];
+        let (mitigations, is_non_leaf) = get_function_mitigations(&sub, &config);
+        assert!(is_non_leaf);
+        assert_eq!(
+            mitigations,
+            vec![
+                Mitigation::PointerAuthentication,
+                Mitigation::ShadowCallStack
+            ]
+            .into_iter()
+            .collect()
+        );
+
+        // Overwriting the temporary register ends the tracking of the shadow call stack pointer.
+        let mut unprotected_sub = Sub::mock("unprotected_function");
+        unprotected_sub.term.blocks = vec![Blk::mock_with_jmps(
+            "unprotected_block",
+            vec![
+                Def::assign("def_3", temp.clone(), Expression::Var(x18)),
+                Def::assign("def_4", temp.clone(), Expression::const_from_i64(0)),
+                Term {
+                    tid: Tid::new("def_5"),
+                    term: Def::Store {
+                        address: Expression::Var(temp),
+                        value: Expression::Var(x30),
+                    },
+                },
+            ],
+            vec![call_other("irg"), call],
+        )];
+        let (mitigations, is_non_leaf) = get_function_mitigations(&unprotected_sub, &config);
+        assert!(is_non_leaf);
+        assert_eq!(
+            mitigations,
+            vec![Mitigation::MemoryTagging].into_iter().collect()
+        );
+
+        // Leaf functions are not flagged for missing return address protection.
+        let leaf_sub = Sub::mock("leaf_function");
+        let (leaf_mitigations, is_non_leaf) = get_function_mitigations(&leaf_sub, &config);
+        assert!(!is_non_leaf);
+
+        let mut binary_mitigations: BTreeSet<Mitigation> =
+            Mitigation::ELF.iter().copied().collect();
+        binary_mitigations.insert(Mitigation::BranchTargetIdentification);
+        let function_mitigations = vec![
+            get_function_mitigations(&sub, &config),
+            get_function_mitigations(&unprotected_sub, &config),
+            (leaf_mitigations, false),
+        ];
+        let function_mitigations: Vec<_> = vec![&sub, &unprotected_sub, &leaf_sub]
+            .into_iter()
+            .zip(function_mitigations)
+            .map(|(sub, (mitigations, is_non_leaf))| (sub, mitigations, is_non_leaf))
+            .collect();
+        let (_, warnings) = generate_cwe_warnings(
+            &Mitigation::applicable(true),
+            &binary_mitigations,
+            &function_mitigations,
+            &[],
+        );
+        // All mitigations are used somewhere, only the unprotected function is flagged.
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["unprotected_function".to_string()]
+        );
+        assert_eq!(
+            warnings[0].other[2],
+            vec![
+                "absent_mitigations".to_string(),
+                "pointer_authentication".to_string(),
+                "branch_target_identification".to_string(),
+                "shadow_call_stack".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,438 @@
+//! This module implements an informational check for the memory-protection hygiene of processes handling secrets.
+//!
+//! Secrets like cryptographic keys should not outlive their use in memory.
+//! Otherwise they may end up in swap space or in core dumps
+//! or they may be leaked through other vulnerabilities (e.g. reads of uninitialized memory) long after their use.
+//! Processes handling secrets should therefore
+//! - lock the memory holding secrets into RAM (e.g. with `mlock`),
+//! - disable core dumps (e.g. with `prctl(PR_SET_DUMPABLE, 0)`),
+//! - and wipe secret buffers after use with functions that are not optimized away by the compiler
+//!   (e.g. `explicit_bzero`).
+//!
+//! The check does not correspond to a specific CWE.
+//! Related CWEs are CWE-226: Sensitive Information in Resource Not Removed Before Reuse
+//! and CWE-528: Exposure of Core Dump File to an Unauthorized Control Sphere.
+//!
+//! ## How the check works
+//!
+//! A binary is identified as handling secrets if it calls one of the crypto functions
+//! configured in the `secret_parameters` of the `SecretHygiene` section of config.json.
+//! For these binaries a warning is generated if
+//! - none of the `memory_locking_symbols` is imported,
+//! - or there is no call to `prctl` whose first two arguments are `PR_SET_DUMPABLE` and zero
+//!   according to the [Pointer Inference analysis](crate::analysis::pointer_inference).
+//!
+//! Furthermore, for each call to a crypto function the Pointer Inference analysis computes the memory objects
+//! that the secret parameter (e.g. the key) points to.
+//! If such an object is the stack frame of the function or a heap object allocated in the function,
+//! it has to be wiped by a call to one of the `wipe_symbols` in the same function.
+//! Otherwise a warning is generated, since the secret survives when the object is freed
+//! or when the function returns.
+//!
+//! ## False Positives
+//!
+//! - Secrets wiped by a callee of the function are not recognized as wiped.
+//! - Core dumps disabled by other means (e.g. `setrlimit` or the system configuration) are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Wiping functions are not checked for being called after the last use of the secret
+//!   or for wiping the whole secret.
+//! - Secrets passed into the function by the caller or stored in global memory are not checked.
+//! - Buffers wiped with `memset` are accepted if `memset` is configured as a wiping function,
+//!   although the compiler may optimize such calls away.
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::parse_config;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "SecretHygiene",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<Config>,
+    default_config: crate::utils::default_config::<Config>,
+};
+
+/// The value of the `PR_SET_DUMPABLE` option of `prctl`.
+const PR_SET_DUMPABLE: u64 = 4;
+
+/// The configuration struct.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Crypto functions, mapped to the index of the parameter pointing to the secret (e.g. the key).
+    secret_parameters: BTreeMap<String, usize>,
+    /// Functions locking memory into RAM.
+    #[serde(default)]
+    memory_locking_symbols: Vec<String>,
+    /// Functions wiping the buffer given as their first parameter.
+    wipe_symbols: Vec<String>,
+}
+
+/// A buffer holding a secret that is not wiped in the function owning it.
+struct UnwipedSecret<'a> {
+    /// The call to the crypto function using the secret.
+    call: &'a Term<Jmp>,
+    /// The name of the crypto function.
+    symbol: &'a str,
+    /// The memory object holding the secret.
+    object_id: AbstractIdentifier,
+}
+
+/// Return the memory objects that the given parameter of the call points to
+/// and that are owned by the function of the call,
+/// i.e. its stack frame and the heap objects allocated in the function.
+fn get_owned_targets(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    sub: &Term<Sub>,
+    symbol: &ExternSymbol,
+    parameter_index: usize,
+) -> Vec<AbstractIdentifier> {
+    let pointer = match state.eval_parameter_arg(
+        &get_variadic_parameter(project, symbol, parameter_index),
+        &project.stack_pointer_register,
+        runtime_memory_image,
+    ) {
+        Ok(Data::Pointer(pointer)) => pointer,
+        _ => return Vec::new(),
+    };
+    pointer
+        .targets()
+        .keys()
+        .filter(|id| {
+            **id == state.stack_id
+                || (matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap)))
+                    && is_term_in_sub(sub, id.get_tid()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Check whether the function contains a jump term with the given TID.
+pub(crate) fn is_term_in_sub(sub: &Term<Sub>, tid: &Tid) -> bool {
+    sub.term
+        .blocks
+        .iter()
+        .flat_map(|block| block.term.jmps.iter())
+        .any(|jmp| jmp.tid == *tid)
+}
+
+/// Check whether the call to `prctl` in the given state disables core dumps,
+/// i.e. whether its first argument is `PR_SET_DUMPABLE` and its second argument is zero.
+fn disables_core_dumps(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    symbol: &ExternSymbol,
+) -> bool {
+    let get_argument = |index: usize| {
+        state
+            .eval_parameter_arg(
+                &get_variadic_parameter(project, symbol, index),
+                &project.stack_pointer_register,
+                runtime_memory_image,
+            )
+            .ok()
+            .and_then(|value| value.try_to_bitvec().ok())
+            .and_then(|value| value.try_to_u64().ok())
+    };
+    get_argument(0) == Some(PR_SET_DUMPABLE) && get_argument(1) == Some(0)
+}
+
+/// Check the calls to crypto functions in the function for secrets that are not wiped in the function.
+fn get_unwiped_secrets<'a>(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    sub: &'a Term<Sub>,
+    secret_symbols: &HashMap<Tid, &'a ExternSymbol>,
+    wipe_symbols: &HashMap<Tid, &'a ExternSymbol>,
+    config: &Config,
+) -> Vec<UnwipedSecret<'a>> {
+    let runtime_memory_image = pointer_inference.get_context().runtime_memory_image;
+    let mut secrets = Vec::new();
+    for (_block, jmp, symbol) in get_callsites(sub, secret_symbols) {
+        if let Some(state) = pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+            let parameter_index = config.secret_parameters[&symbol.name];
+            for object_id in get_owned_targets(
+                project,
+                runtime_memory_image,
+                state,
+                sub,
+                symbol,
+                parameter_index,
+            ) {
+                secrets.push(UnwipedSecret {
+                    call: jmp,
+                    symbol: &symbol.name,
+                    object_id,
+                });
+            }
+        }
+    }
+    if secrets.is_empty() {
+        return secrets;
+    }
+    let mut wiped_objects = HashSet::new();
+    for (_block, jmp, symbol) in get_callsites(sub, wipe_symbols) {
+        if let Some(state) = pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+            wiped_objects.extend(get_owned_targets(
+                project,
+                runtime_memory_image,
+                state,
+                sub,
+                symbol,
+                0,
+            ));
+        }
+    }
+    let mut reported_objects = HashSet::new();
+    secrets
+        .into_iter()
+        .filter(|secret| {
+            !wiped_objects.contains(&secret.object_id)
+                && reported_objects.insert(secret.object_id.clone())
+        })
+        .collect()
+}
+
+/// Generate the warning for a secret buffer that is not wiped.
+fn generate_unwiped_secret_warning(sub: &Term<Sub>, secret: &UnwipedSecret) -> CweWarning {
+    let (location, mut tids, mut addresses) = if *secret.object_id.get_tid() == sub.tid {
+        ("on the stack".to_string(), Vec::new(), Vec::new())
+    } else {
+        let allocation_site = secret.object_id.get_tid();
+        (
+            format!(
+                "in the heap object allocated at {}",
+                allocation_site.address
+            ),
+            vec![format!("{}", allocation_site)],
+            vec![allocation_site.address.clone()],
+        )
+    };
+    tids.insert(0, format!("{}", secret.call.tid));
+    addresses.insert(0, secret.call.tid.address.clone());
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Secret Hygiene) The secret passed to {} at {} in {} is stored {} and not wiped before it goes out of scope",
+            secret.symbol, secret.call.tid.address, sub.term.display_name(), location
+        ),
+    )
+    .tids(tids)
+    .addresses(addresses)
+    .symbols(vec![sub.term.display_name().to_string(), secret.symbol.to_string()])
+    .root_cause(&secret.object_id)
+}
+
+/// Generate the warning for missing process-wide protections of secrets.
+fn generate_process_hygiene_warning(
+    crypto_symbols: &[String],
+    memory_locked: bool,
+    core_dumps_disabled: bool,
+) -> Option<CweWarning> {
+    let mut missing = Vec::new();
+    if !memory_locked {
+        missing.push("memory locking");
+    }
+    if !core_dumps_disabled {
+        missing.push("disabling of core dumps");
+    }
+    if missing.is_empty() {
+        return None;
+    }
+    let mut other = vec!["missing_protections".to_string()];
+    other.extend(
+        missing
+            .iter()
+            .map(|protection| protection.replace(' ', "_")),
+    );
+    Some(
+        CweWarning::new(
+            CWE_MODULE.name,
+            CWE_MODULE.version,
+            format!(
+                "(Secret Hygiene) The binary handles secrets (calls to {}) but does not use {}",
+                crypto_symbols.join(", "),
+                missing.join(" or ")
+            ),
+        )
+        .symbols(crypto_symbols.to_vec())
+        .other(vec![other]),
+    )
+}
+
+/// Run the check.
+///
+/// The check only generates warnings for binaries calling crypto functions.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = parse_config(cwe_params).unwrap();
+    let secret_symbol_names: Vec<String> = config.secret_parameters.keys().cloned().collect();
+    let secret_symbols = get_symbol_map(project, &secret_symbol_names);
+    let wipe_symbols = get_symbol_map(project, &config.wipe_symbols);
+    let prctl_symbols = get_symbol_map(project, &["prctl".to_string()]);
+
+    let mut called_crypto_symbols = BTreeSet::new();
+    let mut core_dumps_disabled = false;
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, _jmp, symbol) in get_callsites(sub, &secret_symbols) {
+            called_crypto_symbols.insert(symbol.name.clone());
+        }
+        for (_block, jmp, symbol) in get_callsites(sub, &prctl_symbols) {
+            if let Some(state) = pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                core_dumps_disabled |=
+                    disables_core_dumps(project, runtime_memory_image, state, symbol);
+            }
+        }
+        for secret in get_unwiped_secrets(
+            project,
+            pointer_inference,
+            sub,
+            &secret_symbols,
+            &wipe_symbols,
+            &config,
+        ) {
+            cwe_warnings.push(generate_unwiped_secret_warning(sub, &secret));
+        }
+    }
+    if called_crypto_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let memory_locked = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .any(|symbol| config.memory_locking_symbols.contains(&symbol.name));
+    let crypto_symbols: Vec<String> = called_crypto_symbols.into_iter().collect();
+    if let Some(warning) =
+        generate_process_hygiene_warning(&crypto_symbols, memory_locked, core_dumps_disabled)
+    {
+        cwe_warnings.insert(0, warning);
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+
+    #[test]
+    fn core_dump_disabling() {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock();
+        cconv.parameter_register = vec!["RDI".to_string(), "RSI".to_string()];
+        project.calling_conventions = vec![cconv];
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "prctl".to_string();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        state.set_register(&Variable::mock("RDI", 8), Bitvector::from_u64(4).into());
+        assert!(!disables_core_dumps(
+            &project,
+            &runtime_memory_image,
+            &state,
+            &symbol
+        ));
+        state.set_register(&Variable::mock("RSI", 8), Bitvector::from_u64(0).into());
+        assert!(disables_core_dumps(
+            &project,
+            &runtime_memory_image,
+            &state,
+            &symbol
+        ));
+        state.set_register(&Variable::mock("RDI", 8), Bitvector::from_u64(38).into());
+        assert!(!disables_core_dumps(
+            &project,
+            &runtime_memory_image,
+            &state,
+            &symbol
+        ));
+    }
+
+    #[test]
+    fn owned_secret_buffers() {
+        let project = Project::mock_empty();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let symbol = ExternSymbol::mock();
+        let mut sub = Sub::mock("func");
+        let mut block = Blk::mock();
+        block.term.jmps = vec![Term {
+            tid: Tid::new("malloc_call"),
+            term: Jmp::Call {
+                target: Tid::new("malloc"),
+                return_: None,
+            },
+        }];
+        sub.term.blocks = vec![block];
+        let mut state = State::new(&project.stack_pointer_register, sub.tid.clone());
+        let heap_id = |call: &str| {
+            AbstractIdentifier::new(
+                Tid::new(call),
+                AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+            )
+        };
+        for call in ["malloc_call", "other_malloc_call"].iter() {
+            state.memory.add_abstract_object(
+                heap_id(call),
+                Bitvector::from_i64(0).into(),
+                ObjectType::Heap,
+                ByteSize::new(8),
+            );
+        }
+        let rdi = Variable::mock("RDI", 8);
+        let set_target = |state: &mut State, id: AbstractIdentifier| {
+            state.set_register(
+                &rdi,
+                PointerDomain::new(id, Bitvector::from_i64(0).into()).into(),
+            );
+        };
+        let get_targets = |state: &State| {
+            get_owned_targets(&project, &runtime_memory_image, state, &sub, &symbol, 0)
+        };
+
+        set_target(&mut state, heap_id("malloc_call"));
+        assert_eq!(get_targets(&state), vec![heap_id("malloc_call")]);
+        // Heap objects allocated in other functions are not owned by the function.
+        set_target(&mut state, heap_id("other_malloc_call"));
+        assert!(get_targets(&state).is_empty());
+        let stack_id = state.stack_id.clone();
+        set_target(&mut state, stack_id.clone());
+        assert_eq!(get_targets(&state), vec![stack_id]);
+    }
+
+    #[test]
+    fn process_hygiene_warning() {
+        let crypto_symbols = vec!["AES_set_encrypt_key".to_string()];
+        assert!(generate_process_hygiene_warning(&crypto_symbols, true, true).is_none());
+        let warning = generate_process_hygiene_warning(&crypto_symbols, true, false).unwrap();
+        assert_eq!(
+            warning.other,
+            vec![vec![
+                "missing_protections".to_string(),
+                "disabling_of_core_dumps".to_string()
+            ]]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! This module implements a check for functions that do not restore the stack pointer on return.
+//!
+//! A function is expected to return with the stack pointer set to its value at the start of the function
+//! (after popping the return address on x86).
+//! Functions that provably return with a different stack pointer are either miscompiled or hand-patched code
+//! or they indicate errors in the modeling of the binary by the disassembler frontend.
+//! In both cases the results of other checks for these functions (and their callers) should be taken with a grain of salt.
+//!
+//! ## How the check works
+//!
+//! For each `Return` jump of a function we use the [Pointer Inference analysis](crate::analysis::pointer_inference)
+//! to compute the value of the stack pointer right before the return.
+//! If the stack pointer is an exactly known offset into the stack frame of the function
+//! and the offset differs from the expected offset, a warning is generated for the function.
+//!
+//! ## False Positives
+//!
+//! - On x86_32 functions using the `stdcall` calling convention remove their stack parameters on return.
+//!   These functions are flagged, since the number of removed parameters is not known to the check.
+//!
+//! ## False Negatives
+//!
+//! - Returns where the Pointer Inference analysis lost track of the stack pointer
+//!   or only knows an inexact offset are not checked.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::CweModule;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "StackIntegrity",
+    version: "0.1",
+    run: check_cwe,
+    validate_config: crate::utils::validate_config::<crate::utils::EmptyConfig>,
+    default_config: crate::utils::default_config::<crate::utils::EmptyConfig>,
+};
+
+/// Return the offset of the stack pointer relative to the expected offset on return
+/// if the stack pointer provably does not have the expected value in the given state.
+fn get_unexpected_stack_offset(project: &Project, state: &State) -> Option<i64> {
+    let pointer = match state.get_register(&project.stack_pointer_register) {
+        Data::Pointer(pointer) => pointer,
+        _ => return None,
+    };
+    if pointer.targets().len() != 1 {
+        return None;
+    }
+    let (id, offset) = pointer.targets().iter().next().unwrap();
+    if *id != state.stack_id {
+        return None;
+    }
+    let offset = offset.try_to_offset().ok()?;
+    let expected_offset = project
+        .get_stack_pointer_offset_on_return()
+        .try_to_i64()
+        .ok()?;
+    if offset == expected_offset {
+        None
+    } else {
+        Some(offset - expected_offset)
+    }
+}
+
+/// Generate the CWE warning for a function not restoring the stack pointer on the given returns.
+fn generate_cwe_warning(sub: &Term<Sub>, returns: &[(&Term<Jmp>, i64)]) -> CweWarning {
+    let return_locations: Vec<String> = returns
+        .iter()
+        .map(|(jmp, offset)| format!("{} (offset {})", jmp.tid.address, offset))
+        .collect();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Stack Pointer Not Restored) Function {} returns with an unexpected stack pointer at {}",
+            sub.term.display_name(),
+            return_locations.join(", ")
+        ),
+    )
+    .tids(
+        returns
+            .iter()
+            .map(|(jmp, _)| format!("{}", jmp.tid))
+            .collect(),
+    )
+    .addresses(
+        returns
+            .iter()
+            .map(|(jmp, _)| jmp.tid.address.clone())
+            .collect(),
+    )
+    .symbols(vec![sub.term.display_name().to_string()])
+}
+
+/// Run the check.
+///
+/// For each function we check whether the stack pointer provably differs from its expected value
+/// at one of the return instructions of the function.
+/// At most one warning is generated per function.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference().unwrap();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        let mut unexpected_returns = Vec::new();
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if !matches!(jmp.term, Jmp::Return(_)) {
+                    continue;
+                }
+                if let Some(offset) = pointer_inference
+                    .get_state_at_jmp_tid(&jmp.tid)
+                    .and_then(|state| get_unexpected_stack_offset(project, state))
+                {
+                    unexpected_returns.push((jmp, offset));
+                }
+            }
+        }
+        if !unexpected_returns.is_empty() {
+            cwe_warnings.push(generate_cwe_warning(sub, &unexpected_returns));
+        }
+    }
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+
+    #[test]
+    fn unexpected_stack_offsets() {
+        let project = Project::mock_empty();
+        let stack_register = &project.stack_pointer_register;
+        let mut state = State::new(stack_register, Tid::new("func"));
+        let stack_id = state.stack_id.clone();
+        let stack_pointer_at = |offset: i64| -> Data {
+            PointerDomain::new(stack_id.clone(), Bitvector::from_i64(offset).into()).into()
+        };
+
+        state.set_register(stack_register, stack_pointer_at(8));
+        assert_eq!(get_unexpected_stack_offset(&project, &state), None);
+        state.set_register(stack_register, stack_pointer_at(-8));
+        assert_eq!(get_unexpected_stack_offset(&project, &state), Some(-16));
+        state.set_register(stack_register, Data::new_top(ByteSize::new(8)));
+        assert_eq!(get_unexpected_stack_offset(&project, &state), None);
+    }
+}
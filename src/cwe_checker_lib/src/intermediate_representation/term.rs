@@ -1,9 +1,18 @@
 use super::{ByteSize, CastOpType, Expression, Variable};
 use crate::prelude::*;
 use crate::utils::log::LogMessage;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 mod builder;
+mod carry_chain;
+mod copy_loop;
+mod no_return;
+mod slicing;
+mod syscall;
+mod task_entry_points;
+pub use no_return::NoReturnConfig;
+pub use syscall::*;
+pub use task_entry_points::TaskEntryPointsConfig;
 
 /// A term identifier consisting of an ID string (which is required to be unique)
 /// and an address to indicate where the term is located.
@@ -294,6 +303,83 @@ impl Term<Blk> {
             Err(logs)
         }
     }
+
+    /// Split the block into a sequence of blocks containing at most `max_defs` `Def` terms each.
+    ///
+    /// Each block of the sequence ends with an unconditional branch to the next block,
+    /// so that the semantics of the original block are preserved.
+    /// The first block keeps the TID of the original block (so that jumps to the block remain valid)
+    /// and the last block keeps the jumps and the indirect jump targets of the original block.
+    /// The TIDs of the new blocks are derived from the TID of the original block.
+    ///
+    /// Blocks are only split between instructions, i.e. where the address of the `Def` TIDs changes,
+    /// since the `Def` terms of one instruction may use temporary registers that do not survive block boundaries.
+    /// Thus a single instruction with more than `max_defs` `Def` terms still ends up in one block.
+    pub fn split_into_blocks_with_max_defs(self, max_defs: usize) -> Vec<Term<Blk>> {
+        if max_defs == 0 || self.term.defs.len() <= max_defs {
+            return vec![self];
+        }
+        let Term { tid, term: block } = self;
+        let mut instructions: Vec<Vec<Term<Def>>> = Vec::new();
+        for def in block.defs {
+            match instructions.last_mut() {
+                Some(instruction) if instruction[0].tid.address == def.tid.address => {
+                    instruction.push(def)
+                }
+                _ => instructions.push(vec![def]),
+            }
+        }
+        let mut chunks: Vec<Vec<Term<Def>>> = Vec::new();
+        for instruction in instructions {
+            match chunks.last_mut() {
+                Some(chunk) if chunk.len() + instruction.len() <= max_defs => {
+                    chunk.extend(instruction)
+                }
+                _ => chunks.push(instruction),
+            }
+        }
+        let block_tids: Vec<Tid> = chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                if index == 0 {
+                    tid.clone()
+                } else {
+                    Tid {
+                        id: format!("{}_split_{}", tid.id, index),
+                        address: chunk[0].tid.address.clone(),
+                    }
+                }
+            })
+            .collect();
+        let mut blocks = Vec::new();
+        let mut jmps = Some(block.jmps);
+        let mut indirect_jmp_targets = Some(block.indirect_jmp_targets);
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let (jmps, indirect_jmp_targets) =
+                if let Some(next_block_tid) = block_tids.get(index + 1) {
+                    let branch = Term {
+                        tid: Tid {
+                            id: format!("{}_split_jmp_{}", tid.id, index + 1),
+                            address: chunk.last().unwrap().tid.address.clone(),
+                        },
+                        term: Jmp::Branch(next_block_tid.clone()),
+                    };
+                    (vec![branch], Vec::new())
+                } else {
+                    (jmps.take().unwrap(), indirect_jmp_targets.take().unwrap())
+                };
+            blocks.push(Term {
+                tid: block_tids[index].clone(),
+                term: Blk {
+                    defs: chunk,
+                    jmps,
+                    indirect_jmp_targets,
+                },
+            });
+        }
+        blocks
+    }
 }
 
 /// A `Sub` or subroutine represents a function with a given name and a list of basic blocks belonging to it.
@@ -308,6 +394,74 @@ pub struct Sub {
     /// The basic blocks belonging to the subroutine.
     /// The first block is also the entry point of the subroutine.
     pub blocks: Vec<Term<Blk>>,
+    /// The name qualified by the address of the subroutine (e.g. `init@00101000`)
+    /// if other subroutines of the program have the same name.
+    /// Set by the normalization of the project, see [`Sub::display_name`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+}
+
+impl Sub {
+    /// Return the name to use for the subroutine in CWE warnings and log messages.
+    ///
+    /// If the name is not unique in the program, it is qualified by the address of the subroutine,
+    /// so that the subroutines can be told apart.
+    /// Configurations referencing functions by name are matched against the unqualified `name` instead.
+    pub fn display_name(&self) -> &str {
+        self.qualified_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl Term<Sub> {
+    /// Return the TID of the block of the subroutine
+    /// that directly follows the given address (with respect to the block start addresses),
+    /// provided that the block is the target of some other jump inside the subroutine.
+    ///
+    /// Used to find fallthrough code after calls that are assumed to not return.
+    pub fn get_referenced_block_following_address(&self, address: &str) -> Option<Tid> {
+        let address = u64::from_str_radix(address, 16).ok()?;
+        let next_block = self
+            .term
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                let block_address = u64::from_str_radix(&block.tid.address, 16).ok()?;
+                if block_address > address {
+                    Some((block_address, block))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|(block_address, _)| *block_address)?
+            .1;
+        let is_referenced = self.term.blocks.iter().any(|block| {
+            block
+                .term
+                .indirect_jmp_targets
+                .contains(&next_block.tid.address)
+                || block.term.jmps.iter().any(|jmp| match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => *target == next_block.tid,
+                    Jmp::Call {
+                        return_: Some(target),
+                        ..
+                    }
+                    | Jmp::CallInd {
+                        return_: Some(target),
+                        ..
+                    }
+                    | Jmp::CallOther {
+                        return_: Some(target),
+                        ..
+                    } => *target == next_block.tid,
+                    _ => false,
+                })
+        });
+        if is_referenced {
+            Some(next_block.tid.clone())
+        } else {
+            None
+        }
+    }
 }
 
 /// A parameter or return argument of a function.
@@ -428,6 +582,9 @@ pub struct CallingConvention {
     /// A list of callee-saved register,
     /// i.e. the values of these registers should be the same after the call as they were before the call.
     pub callee_saved_register: Vec<String>,
+    /// The alignment of the stack pointer at calls in bytes, if known.
+    #[serde(default)]
+    pub stack_alignment: Option<u64>,
 }
 
 /// The `Project` struct is the main data structure representing a binary.
@@ -458,6 +615,51 @@ impl Project {
             .iter()
             .find(|cconv| cconv.name == "__stdcall")
     }
+
+    /// Return the offset of the stack pointer right before a return instruction
+    /// relative to its value at the start of the function.
+    ///
+    /// On x86 the return address is popped from the stack before the return,
+    /// so the offset equals the pointer size. On all other architectures the offset is zero.
+    pub fn get_stack_pointer_offset_on_return(&self) -> Bitvector {
+        let bitwidth = apint::BitWidth::from(self.get_pointer_bytesize());
+        match self.cpu_architecture.as_str() {
+            "x86" | "x86_32" | "x86_64" => {
+                Bitvector::from_u64(u64::from(self.get_pointer_bytesize()))
+                    .into_truncate(bitwidth)
+                    .unwrap()
+            }
+            _ => Bitvector::zero(bitwidth),
+        }
+    }
+}
+
+/// The configuration of the renaming of extern symbols that are aliases of standard functions,
+/// read from the `SymbolAliases` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolAliasesConfig {
+    /// Maps alias names of extern symbols to the canonical function names.
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// The configuration of the renaming of extern symbols imported by ordinal,
+/// read from the `OrdinalImports` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OrdinalImportsConfig {
+    /// Maps lowercase library names to maps from ordinals to the names of the imported functions.
+    pub ordinals: BTreeMap<String, BTreeMap<u64, String>>,
+}
+
+/// The configuration of the normalization of the intermediate representation,
+/// read from the `Normalization` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NormalizationConfig {
+    /// The maximum number of `Def` terms per basic block, see [`Project::split_oversized_blocks`].
+    /// A value of zero disables the splitting of basic blocks.
+    pub max_defs_per_block: usize,
 }
 
 impl Project {
@@ -547,6 +749,7 @@ impl Project {
                             indirect_jmp_targets: Vec::new(),
                         },
                     }],
+                    qualified_name: None,
                 },
             };
             self.program.term.subs.push(dummy_sub);
@@ -554,15 +757,334 @@ impl Project {
         log_messages
     }
 
+    /// Set the qualified names of functions whose name is not unique in the program
+    /// to their names with the function address appended,
+    /// so that CWE warnings referencing these functions can be told apart.
+    /// The names themselves are kept, so that configurations can still reference the functions by name.
+    /// Return a log message for each ambiguous function name.
+    ///
+    /// Duplicate names occur e.g. in firmware images combining several statically linked components
+    /// or for local (`static`) functions with the same name in different compilation units.
+    #[must_use]
+    fn disambiguate_duplicate_sub_names(&mut self) -> Vec<LogMessage> {
+        let mut name_counts: HashMap<String, usize> = HashMap::new();
+        for sub in self.program.term.subs.iter() {
+            *name_counts.entry(sub.term.name.clone()).or_insert(0) += 1;
+        }
+        let mut log_messages = Vec::new();
+        for (name, count) in name_counts.iter() {
+            if *count > 1 {
+                log_messages.push(LogMessage::new_debug(format!(
+                    "Function name {} is used by {} functions. Warnings qualify the name by the function addresses.",
+                    name, count
+                )));
+            }
+        }
+        for sub in self.program.term.subs.iter_mut() {
+            sub.term.qualified_name = (name_counts[&sub.term.name] > 1)
+                .then(|| format!("{}@{}", sub.term.name, sub.tid.address));
+        }
+        log_messages
+    }
+
+    /// Rename extern symbols that are aliases of other functions to the names of these functions.
+    /// The map `aliases` maps alias names to the canonical function names.
+    /// Return a log message for each renamed symbol.
+    ///
+    /// C libraries like musl or uClibc export internal names for standard functions
+    /// (e.g. `__libc_malloc` for `malloc` or `__GI_memcpy` for `memcpy`).
+    /// Statically linked or stripped-down firmware binaries often call these internal names directly.
+    /// By renaming them to the canonical names, all checks and analyses configured for the standard names
+    /// also apply to their aliases.
+    #[must_use]
+    pub fn canonicalize_symbol_aliases(
+        &mut self,
+        aliases: &BTreeMap<String, String>,
+    ) -> Vec<LogMessage> {
+        let mut log_messages = Vec::new();
+        for symbol in self.program.term.extern_symbols.iter_mut() {
+            if let Some(canonical_name) = aliases.get(&symbol.name) {
+                log_messages.push(LogMessage::new_debug(format!(
+                    "Extern symbol {} is analyzed as {}.",
+                    symbol.name, canonical_name
+                )));
+                symbol.name = canonical_name.clone();
+            }
+        }
+        log_messages
+    }
+
+    /// Rename extern symbols imported by ordinal to the names of the imported functions.
+    /// The map `ordinals` maps lowercase library names to maps from ordinals to function names.
+    /// Return a log message for each renamed symbol.
+    ///
+    /// Windows binaries can import functions by ordinal instead of by name.
+    /// The Ghidra plugin names such imports after the library and the ordinal, e.g. `ws2_32.dll_Ordinal_23`.
+    /// For well-known ordinals of common libraries the symbol is renamed to the function name (e.g. `socket`),
+    /// so that all checks configured for this function also apply to it.
+    /// Unknown ordinals keep their synthesized names, which are stable across analysis runs.
+    #[must_use]
+    pub fn resolve_ordinal_imports(
+        &mut self,
+        ordinals: &BTreeMap<String, BTreeMap<u64, String>>,
+    ) -> Vec<LogMessage> {
+        let mut log_messages = Vec::new();
+        for symbol in self.program.term.extern_symbols.iter_mut() {
+            let resolved_name =
+                symbol
+                    .name
+                    .rsplit_once("_Ordinal_")
+                    .and_then(|(library, ordinal)| {
+                        let ordinal: u64 = ordinal.parse().ok()?;
+                        ordinals.get(&library.to_lowercase())?.get(&ordinal)
+                    });
+            if let Some(function_name) = resolved_name {
+                log_messages.push(LogMessage::new_debug(format!(
+                    "Extern symbol {} is analyzed as {}.",
+                    symbol.name, function_name
+                )));
+                symbol.name = function_name.clone();
+            }
+        }
+        log_messages
+    }
+
     /// Run some normalization passes over the project.
     ///
     /// Passes:
     /// - Replace trivial expressions like `a XOR a` with their result.
     /// - Replace jumps to nonexisting TIDs with jumps to an artificial sink target in the CFG.
+    /// - Compute qualified names of functions whose names are not unique, see [`Sub::display_name`].
+    /// - Fuse additions of register pairs into double-width additions.
+    /// - Unroll inlined copy and zero loops with a known number of iterations.
     #[must_use]
     pub fn normalize(&mut self) -> Vec<LogMessage> {
         self.substitute_trivial_expressions();
-        self.remove_references_to_nonexisting_tids()
+        let mut log_messages = self.remove_references_to_nonexisting_tids();
+        log_messages.append(&mut self.disambiguate_duplicate_sub_names());
+        log_messages.append(&mut self.fuse_carry_chains());
+        log_messages.append(&mut self.unroll_copy_loops());
+        log_messages
+    }
+
+    /// Unroll loops of inlined `memcpy` and `memset` implementations
+    /// (e.g. `rep movsb` instructions on x86) if their number of iterations is a known constant,
+    /// so that the abstract interpretation can track the initialized bytes and check each access.
+    fn unroll_copy_loops(&mut self) -> Vec<LogMessage> {
+        let num_unrolled_loops: usize = self
+            .program
+            .term
+            .subs
+            .iter_mut()
+            .map(|sub| sub.unroll_copy_loops())
+            .sum();
+        if num_unrolled_loops > 0 {
+            vec![LogMessage::new_debug(format!(
+                "Unrolled {} inlined copy loops with known numbers of iterations.",
+                num_unrolled_loops
+            ))]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Replace the computation of the upper halves of additions of register pairs
+    /// (as used for 64-bit arithmetic on 32-bit architectures)
+    /// by the upper halves of the corresponding double-width additions,
+    /// so that abstract domains do not lose track of the results.
+    fn fuse_carry_chains(&mut self) -> Vec<LogMessage> {
+        let mut num_fused_additions = 0;
+        for sub in self.program.term.subs.iter_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                num_fused_additions += block.fuse_carry_chains();
+            }
+        }
+        if num_fused_additions > 0 {
+            vec![LogMessage::new_debug(format!(
+                "Fused {} additions of register pairs into double-width additions.",
+                num_fused_additions
+            ))]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Split basic blocks containing more than `max_defs_per_block` `Def` terms
+    /// into sequences of smaller blocks connected by unconditional branches.
+    ///
+    /// Huge blocks (e.g. from unrolled loops) make single applications of transfer functions very expensive
+    /// and cause memory spikes in the fixpoint computations.
+    /// Splitting them bounds the cost per block without changing the semantics of the program.
+    /// A `max_defs_per_block` value of zero disables the splitting.
+    #[must_use]
+    pub fn split_oversized_blocks(&mut self, max_defs_per_block: usize) -> Vec<LogMessage> {
+        if max_defs_per_block == 0 {
+            return Vec::new();
+        }
+        let mut num_split_blocks = 0;
+        for sub in self.program.term.subs.iter_mut() {
+            if sub
+                .term
+                .blocks
+                .iter()
+                .all(|block| block.term.defs.len() <= max_defs_per_block)
+            {
+                continue;
+            }
+            let blocks = std::mem::take(&mut sub.term.blocks);
+            for block in blocks {
+                let split_blocks = block.split_into_blocks_with_max_defs(max_defs_per_block);
+                if split_blocks.len() > 1 {
+                    num_split_blocks += 1;
+                }
+                sub.term.blocks.extend(split_blocks);
+            }
+        }
+        if num_split_blocks > 0 {
+            vec![LogMessage::new_info(format!(
+                "Split {} basic blocks with more than {} Def terms.",
+                num_split_blocks, max_defs_per_block
+            ))]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Check the `no_return` flags of extern symbols for consistency
+    /// with a list of functions known to never return and with the call sites of the symbols.
+    ///
+    /// Misclassified `no_return` flags silently change the control flow graph:
+    /// - If a returning function is marked as non-returning,
+    ///   the code after its call sites is missing from the analysis.
+    ///   We detect this if the fallthrough block after a call is referenced by other jumps in the same function.
+    /// - If a known non-returning function (e.g. `exit`) has call sites with return targets,
+    ///   the code after these call sites is analyzed although it is unreachable.
+    ///
+    /// A log message is generated for each inconsistent extern symbol.
+    /// If `auto_correct` is set, the `no_return` flags and the return targets of the affected calls are corrected.
+    /// Since the control flow graph is generated after this pass,
+    /// all affected functions get analyzed with the corrected flags.
+    #[must_use]
+    pub fn check_no_return_flags(
+        &mut self,
+        known_no_return_symbols: &[String],
+        auto_correct: bool,
+    ) -> Vec<LogMessage> {
+        let extern_tids: HashSet<Tid> = self
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        // Collect (call site, has return target, fallthrough candidate) triples for each extern symbol.
+        let mut callsites: HashMap<Tid, Vec<(Tid, bool, Option<Tid>)>> = HashMap::new();
+        for sub in self.program.term.subs.iter() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call { target, return_ } = &jmp.term {
+                        if extern_tids.contains(target) {
+                            let fallthrough = if return_.is_none() {
+                                sub.get_referenced_block_following_address(&jmp.tid.address)
+                            } else {
+                                None
+                            };
+                            callsites.entry(target.clone()).or_default().push((
+                                jmp.tid.clone(),
+                                return_.is_some(),
+                                fallthrough,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut logs = Vec::new();
+        let mut return_target_corrections: HashMap<Tid, Option<Tid>> = HashMap::new();
+        for symbol in self.program.term.extern_symbols.iter_mut() {
+            let symbol_callsites = match callsites.get(&symbol.tid) {
+                Some(symbol_callsites) => symbol_callsites,
+                None => continue,
+            };
+            let returning_calls: Vec<&Tid> = symbol_callsites
+                .iter()
+                .filter_map(|(call, has_return, _)| if *has_return { Some(call) } else { None })
+                .collect();
+            if known_no_return_symbols.contains(&symbol.name) {
+                if returning_calls.is_empty() {
+                    continue;
+                }
+                logs.push(
+                    LogMessage::new_info(format!(
+                        "Extern symbol {} is known to not return, but code after {} of its call sites is analyzed as reachable.",
+                        symbol.name,
+                        returning_calls.len()
+                    ))
+                    .location(returning_calls[0].clone()),
+                );
+                if auto_correct {
+                    symbol.no_return = true;
+                    for call in returning_calls {
+                        return_target_corrections.insert(call.clone(), None);
+                    }
+                }
+            } else if symbol.no_return {
+                let fallthrough_calls: Vec<(&Tid, &Tid)> = symbol_callsites
+                    .iter()
+                    .filter_map(|(call, _, fallthrough)| {
+                        fallthrough.as_ref().map(|block| (call, block))
+                    })
+                    .collect();
+                if !fallthrough_calls.is_empty() {
+                    logs.push(
+                        LogMessage::new_info(format!(
+                            "Extern symbol {} is marked as non-returning, but the code after {} of its call sites is referenced elsewhere. This code is excluded from the analysis.",
+                            symbol.name,
+                            fallthrough_calls.len()
+                        ))
+                        .location(fallthrough_calls[0].0.clone()),
+                    );
+                } else if !returning_calls.is_empty() {
+                    logs.push(
+                        LogMessage::new_info(format!(
+                            "Extern symbol {} is marked as non-returning, but {} of its call sites return.",
+                            symbol.name,
+                            returning_calls.len()
+                        ))
+                        .location(returning_calls[0].clone()),
+                    );
+                } else {
+                    continue;
+                }
+                if auto_correct {
+                    symbol.no_return = false;
+                    for (call, fallthrough_block) in fallthrough_calls {
+                        return_target_corrections
+                            .insert(call.clone(), Some(fallthrough_block.clone()));
+                    }
+                }
+            }
+        }
+
+        if !return_target_corrections.is_empty() {
+            for sub in self.program.term.subs.iter_mut() {
+                for block in sub.term.blocks.iter_mut() {
+                    for jmp in block.term.jmps.iter_mut() {
+                        if let Some(corrected_return) = return_target_corrections.get(&jmp.tid) {
+                            if let Jmp::Call { return_, .. } = &mut jmp.term {
+                                *return_ = corrected_return.clone();
+                            }
+                        }
+                    }
+                }
+            }
+            logs.push(LogMessage::new_info(format!(
+                "Corrected the return targets of {} calls to extern symbols with misclassified no-return flags.",
+                return_target_corrections.len()
+            )));
+        }
+        logs
     }
 }
 
@@ -583,6 +1105,57 @@ mod tests {
                 },
             }
         }
+
+        /// Create a block with the given defs ending with the given jump.
+        /// The TID of the jump is `{name}_jmp`.
+        pub fn mock_with(name: &str, defs: Vec<Term<Def>>, jmp: Jmp) -> Term<Blk> {
+            Term {
+                tid: Tid::new(name),
+                term: Blk {
+                    defs,
+                    jmps: vec![Term {
+                        tid: Tid::new(format!("{}_jmp", name)),
+                        term: jmp,
+                    }],
+                    indirect_jmp_targets: Vec::new(),
+                },
+            }
+        }
+
+        /// Create a block with the given defs and jumps.
+        /// The TIDs of the jumps are `{name}_jmp_{index}`.
+        pub fn mock_with_jmps(name: &str, defs: Vec<Term<Def>>, jmps: Vec<Jmp>) -> Term<Blk> {
+            Term {
+                tid: Tid::new(name),
+                term: Blk {
+                    defs,
+                    jmps: jmps
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, jmp)| Term {
+                            tid: Tid::new(format!("{}_jmp_{}", name, index)),
+                            term: jmp,
+                        })
+                        .collect(),
+                    indirect_jmp_targets: Vec::new(),
+                },
+            }
+        }
+    }
+
+    impl Jmp {
+        /// Create a call to the given target returning to the given block.
+        pub fn mock_call(target: &str, return_: &str) -> Jmp {
+            Jmp::Call {
+                target: Tid::new(target),
+                return_: Some(Tid::new(return_)),
+            }
+        }
+
+        /// Create a return jump.
+        pub fn mock_return() -> Jmp {
+            Jmp::Return(Expression::Var(Variable::mock("RSP", 8u64)))
+        }
     }
 
     impl Sub {
@@ -592,9 +1165,17 @@ mod tests {
                 term: Sub {
                     name: name.to_string(),
                     blocks: Vec::new(),
+                    qualified_name: None,
                 },
             }
         }
+
+        /// Create a function with the given blocks.
+        pub fn mock_with(name: impl ToString, blocks: Vec<Term<Blk>>) -> Term<Sub> {
+            let mut sub = Sub::mock(name);
+            sub.term.blocks = blocks;
+            sub
+        }
     }
 
     impl Program {
@@ -615,6 +1196,7 @@ mod tests {
                 parameter_register: vec!["RDI".to_string()],
                 return_register: vec!["RAX".to_string()],
                 callee_saved_register: vec!["RBP".to_string()],
+                stack_alignment: None,
             }
         }
     }
@@ -637,6 +1219,17 @@ mod tests {
                 no_return: false,
             }
         }
+
+        /// Create an extern symbol with the given name as TID
+        /// and with the given registers as parameters.
+        pub fn mock_with(name: &str, parameter_registers: &[&str]) -> ExternSymbol {
+            ExternSymbol {
+                tid: Tid::new(name),
+                name: name.to_string(),
+                parameters: parameter_registers.iter().map(Arg::mock_register).collect(),
+                ..ExternSymbol::mock()
+            }
+        }
     }
 
     impl Project {
@@ -653,6 +1246,331 @@ mod tests {
         }
     }
 
+    #[test]
+    fn symbol_alias_canonicalization() {
+        let mut project = Project::mock_empty();
+        let mut alias_symbol = ExternSymbol::mock();
+        alias_symbol.name = "__libc_malloc".to_string();
+        project.program.term.extern_symbols = vec![ExternSymbol::mock(), alias_symbol];
+        let aliases: BTreeMap<String, String> =
+            vec![("__libc_malloc".to_string(), "malloc".to_string())]
+                .into_iter()
+                .collect();
+        let logs = project.canonicalize_symbol_aliases(&aliases);
+        assert_eq!(logs.len(), 1);
+        let symbol_names: Vec<&str> = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(symbol_names, vec!["mock_symbol", "malloc"]);
+    }
+
+    #[test]
+    fn symbol_aliases_config() {
+        use crate::utils::parse_config;
+        let config: SymbolAliasesConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "aliases": {"__libc_malloc": "malloc"}
+        }))
+        .unwrap();
+        assert_eq!(config.aliases["__libc_malloc"], "malloc");
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<SymbolAliasesConfig>(&serde_json::json!({
+            "aliases": {"__libc_malloc": ["malloc"]}
+        }))
+        .is_err());
+        assert!(parse_config::<SymbolAliasesConfig>(&serde_json::json!({
+            "alias": {"__libc_malloc": "malloc"}
+        }))
+        .is_err());
+        assert!(parse_config::<SymbolAliasesConfig>(&serde_json::Value::Null).is_err());
+    }
+
+    #[test]
+    fn ordinal_import_resolution() {
+        let mut project = Project::mock_empty();
+        let mut socket_symbol = ExternSymbol::mock();
+        socket_symbol.name = "WS2_32.dll_Ordinal_23".to_string();
+        let mut unknown_symbol = ExternSymbol::mock();
+        unknown_symbol.name = "ws2_32.dll_Ordinal_999".to_string();
+        let mut other_library_symbol = ExternSymbol::mock();
+        other_library_symbol.name = "foo.dll_Ordinal_23".to_string();
+        project.program.term.extern_symbols =
+            vec![socket_symbol, unknown_symbol, other_library_symbol];
+        let ordinals: BTreeMap<String, BTreeMap<u64, String>> =
+            serde_json::from_str(r#"{"ws2_32.dll": {"23": "socket", "115": "WSAStartup"}}"#)
+                .unwrap();
+        let logs = project.resolve_ordinal_imports(&ordinals);
+        assert_eq!(logs.len(), 1);
+        let symbol_names: Vec<&str> = project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(
+            symbol_names,
+            vec!["socket", "ws2_32.dll_Ordinal_999", "foo.dll_Ordinal_23"]
+        );
+    }
+
+    #[test]
+    fn ordinal_imports_config() {
+        use crate::utils::parse_config;
+        let config: OrdinalImportsConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "ordinals": {"ws2_32.dll": {"23": "socket"}}
+        }))
+        .unwrap();
+        assert_eq!(config.ordinals["ws2_32.dll"][&23], "socket");
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<OrdinalImportsConfig>(&serde_json::json!({
+            "ordinals": {"ws2_32.dll": {"socket": "23"}}
+        }))
+        .is_err());
+        assert!(parse_config::<OrdinalImportsConfig>(&serde_json::json!({
+            "ordinal": {"ws2_32.dll": {"23": "socket"}}
+        }))
+        .is_err());
+        assert!(parse_config::<OrdinalImportsConfig>(&serde_json::Value::Null).is_err());
+    }
+
+    #[test]
+    fn no_return_flag_correction() {
+        let call_term = |id: &str, address: &str, target: &str, return_: Option<Tid>| Term {
+            tid: Tid {
+                id: id.to_string(),
+                address: address.to_string(),
+            },
+            term: Jmp::Call {
+                target: Tid::new(target),
+                return_,
+            },
+        };
+        let block = |address: &str, jmps: Vec<Term<Jmp>>| Term {
+            tid: Tid::blk_id_at_address(address),
+            term: Blk {
+                defs: Vec::new(),
+                jmps,
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        let mut sub = Sub::mock("sub");
+        sub.term.blocks = vec![
+            block(
+                "00001000",
+                vec![call_term(
+                    "call_exit",
+                    "00001000",
+                    "exit",
+                    Some(Tid::blk_id_at_address("00001010")),
+                )],
+            ),
+            block(
+                "00001010",
+                vec![call_term("call_fatal", "00001010", "fatal", None)],
+            ),
+            block("00001020", vec![]),
+            block(
+                "00001030",
+                vec![Term {
+                    tid: Tid::new("branch"),
+                    term: Jmp::Branch(Tid::blk_id_at_address("00001020")),
+                }],
+            ),
+        ];
+        let mut project = Project::mock_empty();
+        project.program.term.subs.push(sub);
+        for name in ["exit", "fatal"].iter() {
+            let mut symbol = ExternSymbol::mock();
+            symbol.tid = Tid::new(name);
+            symbol.name = name.to_string();
+            symbol.no_return = *name == "fatal";
+            project.program.term.extern_symbols.push(symbol);
+        }
+        let known_no_return_symbols = vec!["exit".to_string()];
+
+        let logs = project
+            .clone()
+            .check_no_return_flags(&known_no_return_symbols, false);
+        assert_eq!(logs.len(), 2);
+
+        let logs = project.check_no_return_flags(&known_no_return_symbols, true);
+        assert_eq!(logs.len(), 3);
+        let symbols = &project.program.term.extern_symbols;
+        assert!(symbols[0].no_return);
+        assert!(!symbols[1].no_return);
+        let blocks = &project.program.term.subs[0].term.blocks;
+        assert_eq!(
+            blocks[0].term.jmps[0].term,
+            Jmp::Call {
+                target: Tid::new("exit"),
+                return_: None
+            }
+        );
+        assert_eq!(
+            blocks[1].term.jmps[0].term,
+            Jmp::Call {
+                target: Tid::new("fatal"),
+                return_: Some(Tid::blk_id_at_address("00001020"))
+            }
+        );
+        assert!(project
+            .check_no_return_flags(&known_no_return_symbols, false)
+            .is_empty());
+    }
+
+    #[test]
+    fn split_oversized_blocks() {
+        let mut block = Blk::mock();
+        block.term.defs = (0..5)
+            .map(|index| Term {
+                tid: Tid {
+                    id: format!("def_{}", index),
+                    address: format!("0000100{}", index),
+                },
+                term: Def::Assign {
+                    var: Variable::mock("RAX", 8),
+                    value: Expression::const_from_i64(index),
+                },
+            })
+            .collect();
+        block.term.jmps = vec![Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RAX")),
+        }];
+        let mut sub = Sub::mock("sub");
+        sub.term.blocks.push(block);
+        let mut project = Project::mock_empty();
+        project.program.term.subs.push(sub);
+
+        assert!(project.split_oversized_blocks(5).is_empty());
+        assert_eq!(project.program.term.subs[0].term.blocks.len(), 1);
+        assert_eq!(project.split_oversized_blocks(2).len(), 1);
+        let blocks = &project.program.term.subs[0].term.blocks;
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].tid, Tid::new("block"));
+        assert_eq!(blocks[0].term.defs.len(), 2);
+        assert_eq!(blocks[1].tid.address, "00001002");
+        assert_eq!(
+            blocks[0].term.jmps[0].term,
+            Jmp::Branch(blocks[1].tid.clone())
+        );
+        assert_eq!(
+            blocks[1].term.jmps[0].term,
+            Jmp::Branch(blocks[2].tid.clone())
+        );
+        assert_eq!(blocks[2].term.defs.len(), 1);
+        assert_eq!(blocks[2].term.jmps[0].tid, Tid::new("return"));
+    }
+
+    #[test]
+    fn normalization_config() {
+        use crate::utils::parse_config;
+        let config: NormalizationConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "max_defs_per_block": 5000
+        }))
+        .unwrap();
+        assert_eq!(config.max_defs_per_block, 5000);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<NormalizationConfig>(&serde_json::json!({
+            "max_defs_per_block": "5000"
+        }))
+        .is_err());
+        assert!(parse_config::<NormalizationConfig>(&serde_json::json!({
+            "max_defs_per_block": -1
+        }))
+        .is_err());
+        assert!(parse_config::<NormalizationConfig>(&serde_json::json!({
+            "max_def_per_block": 5000
+        }))
+        .is_err());
+        assert!(parse_config::<NormalizationConfig>(&serde_json::Value::Null).is_err());
+    }
+
+    #[test]
+    fn split_blocks_at_instruction_boundaries() {
+        // Three instructions with one, three and two `Def` terms.
+        let addresses = [
+            "00001000", "00001004", "00001004", "00001004", "00001008", "00001008",
+        ];
+        let mut block = Blk::mock();
+        block.term.defs = addresses
+            .iter()
+            .enumerate()
+            .map(|(index, address)| Term {
+                tid: Tid {
+                    id: format!("def_{}", index),
+                    address: address.to_string(),
+                },
+                term: Def::Assign {
+                    var: Variable::mock("RAX", 8),
+                    value: Expression::const_from_i64(index as i64),
+                },
+            })
+            .collect();
+
+        // The limit falls into the middle of the second instruction.
+        let blocks = block.clone().split_into_blocks_with_max_defs(3);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].term.defs.len(), 1);
+        assert_eq!(blocks[1].tid.address, "00001004");
+        assert_eq!(blocks[1].term.defs.len(), 3);
+        assert_eq!(blocks[2].tid.address, "00001008");
+        let blocks = block.clone().split_into_blocks_with_max_defs(4);
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| block.term.defs.len())
+                .collect::<Vec<_>>(),
+            vec![4, 2]
+        );
+        // Instructions with more `Def` terms than the limit are not split.
+        let blocks = block.split_into_blocks_with_max_defs(2);
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| block.term.defs.len())
+                .collect::<Vec<_>>(),
+            vec![1, 3, 2]
+        );
+    }
+
+    #[test]
+    fn duplicate_sub_names() {
+        let mut project = Project::mock_empty();
+        let mut first_init = Sub::mock("init");
+        first_init.tid.address = "1000".to_string();
+        let mut second_init = Sub::mock("init");
+        second_init.tid = Tid::new("init_2");
+        second_init.tid.address = "2000".to_string();
+        project.program.term.subs = vec![first_init, second_init, Sub::mock("main")];
+
+        let logs = project.disambiguate_duplicate_sub_names();
+        assert_eq!(logs.len(), 1);
+        let names: Vec<(&str, &str)> = project
+            .program
+            .term
+            .subs
+            .iter()
+            .map(|sub| (sub.term.name.as_str(), sub.term.display_name()))
+            .collect();
+        // The names are kept for configurations referencing the functions by name.
+        assert_eq!(
+            names,
+            vec![
+                ("init", "init@1000"),
+                ("init", "init@2000"),
+                ("main", "main")
+            ]
+        );
+    }
+
     #[test]
     fn retarget_nonexisting_jumps() {
         let mut jmp_term = Term {
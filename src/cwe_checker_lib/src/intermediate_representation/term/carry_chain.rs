@@ -0,0 +1,353 @@
+//! Recognition of additions of double-width values that are split into additions of register pairs.
+//!
+//! On 32-bit architectures 64-bit values are held in register pairs.
+//! An addition of two such values is split into an addition of the lower halves
+//! and an addition of the upper halves that also adds the carry of the first addition, e.g.
+//! ```text
+//! CY = INT_CARRY(r0, r2)          // adds r0, r0, r2 (ARM)
+//! r0 = r0 + r2
+//! r1 = (r1 + r3) + ZEXT(CY)       // adc r1, r1, r3
+//! ```
+//! or on architectures without a carry flag (e.g. MIPS)
+//! ```text
+//! v0 = a0 + a2                    // addu v0, a0, a2
+//! v1 = ZEXT(v0 < a0)              // sltu v1, v0, a0
+//! a1 = a1 + a3                    // addu a1, a1, a3
+//! v1 = v1 + a1                    // addu v1, v1, a1
+//! ```
+//! Abstract domains lose track of the upper half of the result in these cases,
+//! because the carry is (abstractly) unknown in most cases.
+//! Thus we replace the computation of the upper half by the upper half of the double-width addition
+//! of the values pieced together from the register pairs.
+//! The operands of the lower half addition are saved in temporary variables when the carry is computed,
+//! since the registers holding them are usually overwritten before the upper half is computed.
+
+use super::*;
+use crate::intermediate_representation::BinOpType;
+
+impl Term<Blk> {
+    /// Replace the computation of the upper halves of additions of register pairs
+    /// by the upper halves of the corresponding double-width additions.
+    /// See the module-level documentation for more information.
+    ///
+    /// Only carry chains contained in the block are recognized.
+    /// Returns the number of replaced upper half computations.
+    pub fn fuse_carry_chains(&mut self) -> usize {
+        let mut num_fused_additions = 0;
+        // Variables holding the sum of two expressions, where the sum is still valid.
+        let mut sums: HashMap<Variable, (Expression, Expression)> = HashMap::new();
+        // Variables holding the carry of the addition of two (saved) expressions.
+        let mut carries: HashMap<Variable, (Expression, Expression)> = HashMap::new();
+        let mut new_defs = Vec::with_capacity(self.term.defs.len());
+        for mut def in std::mem::take(&mut self.term.defs) {
+            let (var, value) = match &mut def.term {
+                Def::Assign { var, value } => (var.clone(), value),
+                Def::Load { var, .. } => {
+                    invalidate_var(var, &mut sums, &mut carries);
+                    new_defs.push(def);
+                    continue;
+                }
+                Def::Store { .. } => {
+                    new_defs.push(def);
+                    continue;
+                }
+            };
+            let mut new_carry = None;
+            if let Some((lhs, rhs)) = get_carry_operands(value, &sums) {
+                if lhs.bytesize() == rhs.bytesize() {
+                    let (saved_lhs, save_lhs) = save_operand(&def.tid, "_carry_lhs", lhs);
+                    let (saved_rhs, save_rhs) = save_operand(&def.tid, "_carry_rhs", rhs);
+                    new_defs.push(save_lhs);
+                    new_defs.push(save_rhs);
+                    new_carry = Some((saved_lhs, saved_rhs));
+                }
+            } else if let Some(carry_source) = strip_zero_extension(value).as_var() {
+                new_carry = carries.get(carry_source).cloned();
+            } else if let Some(fused_value) = get_fused_upper_half(&var, value, &sums, &carries) {
+                *value = fused_value;
+                num_fused_additions += 1;
+            }
+            let new_sum = match value {
+                Expression::BinOp {
+                    op: BinOpType::IntAdd,
+                    lhs,
+                    rhs,
+                } if !lhs.input_vars().contains(&&var) && !rhs.input_vars().contains(&&var) => {
+                    Some((lhs.as_ref().clone(), rhs.as_ref().clone()))
+                }
+                _ => None,
+            };
+            invalidate_var(&var, &mut sums, &mut carries);
+            if let Some(carry) = new_carry {
+                carries.insert(var.clone(), carry);
+            }
+            if let Some(sum) = new_sum {
+                sums.insert(var, sum);
+            }
+            new_defs.push(def);
+        }
+        self.term.defs = new_defs;
+        num_fused_additions
+    }
+}
+
+impl Expression {
+    /// Return the variable if the expression is a variable.
+    pub(super) fn as_var(&self) -> Option<&Variable> {
+        match self {
+            Expression::Var(var) => Some(var),
+            _ => None,
+        }
+    }
+}
+
+/// Remove all sums and carries that are no longer valid after a value is assigned to `var`.
+fn invalidate_var(
+    var: &Variable,
+    sums: &mut HashMap<Variable, (Expression, Expression)>,
+    carries: &mut HashMap<Variable, (Expression, Expression)>,
+) {
+    sums.retain(|sum_var, (lhs, rhs)| {
+        sum_var != var && !lhs.input_vars().contains(&var) && !rhs.input_vars().contains(&var)
+    });
+    carries.remove(var);
+}
+
+/// Remove zero extensions from the expression.
+fn strip_zero_extension(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::Cast {
+            op: CastOpType::IntZExt,
+            arg,
+            ..
+        } => strip_zero_extension(arg),
+        _ => expression,
+    }
+}
+
+/// If the expression computes the carry of an addition, return the operands of the addition.
+///
+/// Recognized are `INT_CARRY(x, y)` and `s < x` (unsigned), where `s` is known to hold `x + y`,
+/// possibly zero-extended.
+fn get_carry_operands(
+    value: &Expression,
+    sums: &HashMap<Variable, (Expression, Expression)>,
+) -> Option<(Expression, Expression)> {
+    match strip_zero_extension(value) {
+        Expression::BinOp {
+            op: BinOpType::IntCarry,
+            lhs,
+            rhs,
+        } => Some((lhs.as_ref().clone(), rhs.as_ref().clone())),
+        Expression::BinOp {
+            op: BinOpType::IntLess,
+            lhs,
+            rhs,
+        } => {
+            let (sum_lhs, sum_rhs) = sums.get(lhs.as_var()?)?;
+            if sum_lhs == rhs.as_ref() {
+                Some((sum_lhs.clone(), sum_rhs.clone()))
+            } else if sum_rhs == rhs.as_ref() {
+                Some((sum_rhs.clone(), sum_lhs.clone()))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Generate a temporary variable and an assignment saving the value of `operand` in it.
+fn save_operand(tid: &Tid, suffix: &str, operand: Expression) -> (Expression, Term<Def>) {
+    let temp_var = Variable {
+        name: format!("${}{}", tid, suffix),
+        size: operand.bytesize(),
+        is_temp: true,
+    };
+    let save_def = Term {
+        tid: tid.clone().with_id_suffix(suffix),
+        term: Def::Assign {
+            var: temp_var.clone(),
+            value: operand,
+        },
+    };
+    (Expression::Var(temp_var), save_def)
+}
+
+/// If `value` computes the upper half of an addition of register pairs,
+/// return an expression computing the upper half of the corresponding double-width addition.
+///
+/// Recognized are `(a + b) + carry` and `t + carry`, where `t` is known to hold `a + b`,
+/// with the operands in any order.
+fn get_fused_upper_half(
+    var: &Variable,
+    value: &Expression,
+    sums: &HashMap<Variable, (Expression, Expression)>,
+    carries: &HashMap<Variable, (Expression, Expression)>,
+) -> Option<Expression> {
+    let (first, second) = match value {
+        Expression::BinOp {
+            op: BinOpType::IntAdd,
+            lhs,
+            rhs,
+        } => (lhs.as_ref(), rhs.as_ref()),
+        _ => return None,
+    };
+    let get_carry = |expr: &Expression| carries.get(strip_zero_extension(expr).as_var()?).cloned();
+    let get_sum = |expr: &Expression| match expr {
+        Expression::BinOp {
+            op: BinOpType::IntAdd,
+            lhs,
+            rhs,
+        } => Some((lhs.as_ref().clone(), rhs.as_ref().clone())),
+        Expression::Var(sum_var) => sums.get(sum_var).cloned(),
+        _ => None,
+    };
+    let ((lower_lhs, lower_rhs), (upper_lhs, upper_rhs)) =
+        match (get_carry(first), get_carry(second)) {
+            (Some(carry), None) => (carry, get_sum(second)?),
+            (None, Some(carry)) => (carry, get_sum(first)?),
+            _ => return None,
+        };
+    if upper_lhs.bytesize() != var.size || upper_rhs.bytesize() != var.size {
+        return None;
+    }
+    let lower_size = lower_lhs.bytesize();
+    let piece = |upper: Expression, lower: Expression| Expression::BinOp {
+        op: BinOpType::Piece,
+        lhs: Box::new(upper),
+        rhs: Box::new(lower),
+    };
+    Some(Expression::Subpiece {
+        low_byte: lower_size,
+        size: var.size,
+        arg: Box::new(piece(upper_lhs, lower_lhs).plus(piece(upper_rhs, lower_rhs))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(defs: Vec<Term<Def>>) -> Term<Blk> {
+        let mut block = Blk::mock();
+        block.term.defs = defs;
+        block
+    }
+
+    fn reg(name: &str, size: u64) -> Variable {
+        Variable::mock(name, size)
+    }
+
+    fn temp(name: &str) -> Variable {
+        Variable {
+            name: name.to_string(),
+            size: ByteSize::new(4),
+            is_temp: true,
+        }
+    }
+
+    fn r(name: &str) -> Expression {
+        Expression::Var(reg(name, 4))
+    }
+
+    fn zext(arg: Expression) -> Expression {
+        Expression::Cast {
+            op: CastOpType::IntZExt,
+            size: ByteSize::new(4),
+            arg: Box::new(arg),
+        }
+    }
+
+    fn bin_op(op: BinOpType, lhs: Expression, rhs: Expression) -> Expression {
+        Expression::BinOp {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    /// The expected replacement for the upper half computation of the carry chain generated by the def `carry`.
+    fn fused_upper_half(upper_lhs: &str, upper_rhs: &str) -> Expression {
+        let piece = |upper: &str, lower: &str| {
+            bin_op(BinOpType::Piece, r(upper), Expression::Var(temp(lower)))
+        };
+        Expression::Subpiece {
+            low_byte: ByteSize::new(4),
+            size: ByteSize::new(4),
+            arg: Box::new(
+                piece(upper_lhs, "$carry_carry_lhs").plus(piece(upper_rhs, "$carry_carry_rhs")),
+            ),
+        }
+    }
+
+    #[test]
+    fn fuse_carry_flag_chain() {
+        let mut block = block(vec![
+            Def::assign(
+                "carry",
+                reg("tmpCY", 1),
+                bin_op(BinOpType::IntCarry, r("r0"), r("r2")),
+            ),
+            Def::assign("add_low", reg("r0", 4), r("r0").plus(r("r2"))),
+            Def::assign("set_flag", reg("CY", 1), Expression::Var(reg("tmpCY", 1))),
+            Def::assign("add_high", reg("r1", 4), r("r1").plus(r("r3"))),
+            Def::assign(
+                "add_carry",
+                reg("r1", 4),
+                r("r1").plus(zext(Expression::Var(reg("CY", 1)))),
+            ),
+        ]);
+        // The sum `r1 + r3` is unknown at the last def, since `r1` was overwritten.
+        assert_eq!(block.clone().fuse_carry_chains(), 0);
+
+        block.term.defs.remove(4);
+        block.term.defs[3] = Def::assign(
+            "add_high",
+            reg("r1", 4),
+            r("r1")
+                .plus(r("r3"))
+                .plus(zext(Expression::Var(reg("CY", 1)))),
+        );
+        assert_eq!(block.fuse_carry_chains(), 1);
+        assert_eq!(block.term.defs.len(), 6);
+        assert_eq!(
+            block.term.defs[0],
+            Def::assign("carry_carry_lhs", temp("$carry_carry_lhs"), r("r0"))
+        );
+        assert_eq!(
+            block.term.defs[5].term,
+            Def::Assign {
+                var: reg("r1", 4),
+                value: fused_upper_half("r1", "r3"),
+            }
+        );
+    }
+
+    #[test]
+    fn fuse_set_less_than_chain() {
+        let mut block = block(vec![
+            Def::assign("add_low", reg("v0", 4), r("a0").plus(r("a2"))),
+            Def::assign(
+                "carry",
+                reg("v1", 4),
+                zext(bin_op(BinOpType::IntLess, r("v0"), r("a0"))),
+            ),
+            Def::assign("add_high", reg("a1", 4), r("a1").plus(r("a3"))),
+            Def::assign("add_carry", reg("v1", 4), r("v1").plus(r("a1"))),
+        ]);
+        // The sum `a1 + a3` is unknown at the last def, since `a1` was overwritten.
+        assert_eq!(block.clone().fuse_carry_chains(), 0);
+
+        block.term.defs[2] = Def::assign("add_high", reg("t0", 4), r("a1").plus(r("a3")));
+        block.term.defs[3] = Def::assign("add_carry", reg("v1", 4), r("t0").plus(r("v1")));
+        assert_eq!(block.fuse_carry_chains(), 1);
+        assert_eq!(
+            block.term.defs.last().unwrap().term,
+            Def::Assign {
+                var: reg("v1", 4),
+                value: fused_upper_half("a1", "a3"),
+            }
+        );
+    }
+}
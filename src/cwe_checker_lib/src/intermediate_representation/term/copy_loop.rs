@@ -0,0 +1,535 @@
+//! Recognition of small copy and zero loops that compilers expand inline for `memcpy` and `memset`.
+//!
+//! Compilers replace calls to `memcpy` and `memset` with small known sizes by inline code,
+//! e.g. by `rep movsb`/`rep stosq` instructions on x86 or by loops copying one word per iteration.
+//! In the intermediate representation these are loops of one or two blocks, e.g. for `rep stosq`
+//! ```text
+//! blk_head:  $U1 = RCX == 0
+//!            CBRANCH blk_exit, $U1
+//!            BRANCH blk_body
+//! blk_body:  RCX = RCX - 1
+//!            *RDI = RAX
+//!            RDI = RDI + 8 - 16 * ZEXT(DF)
+//!            BRANCH blk_head
+//! ```
+//! Fixpoint algorithms have to widen the pointer offsets in such loops.
+//! So the Pointer Inference analysis does not know which bytes of a memory object get initialized by the loop
+//! and it cannot check the accesses in the loop against the bounds of the target object.
+//!
+//! If the loop counter is set to a constant right before the loop,
+//! we unroll the loop completely, i.e. we replace it by the sequence of `Def` terms executed by the loop.
+//! Then each store of the loop is a separate `Def` with a known offset for the abstract interpretation.
+//! Since the unrolled defs are copies of the original defs, the semantics of the program do not change.
+//! The only exception is the direction flag `DF` of x86, which is assumed to be cleared in the unrolled defs
+//! (as required by the calling conventions) unless the function assigns a value to it.
+
+use super::*;
+use crate::intermediate_representation::{BinOpType, BitvectorExtended, UnOpType};
+
+/// The maximal number of iterations of loops that get unrolled.
+const MAX_UNROLLED_ITERATIONS: u64 = 256;
+
+/// A recognized copy or zero loop together with the defs of its unrolled version.
+struct CopyLoop {
+    /// The block containing the loop condition.
+    head: Tid,
+    /// The second block of loops consisting of two blocks.
+    body: Option<Tid>,
+    /// The jump leaving the loop.
+    exit: Term<Jmp>,
+    /// The defs executed by the loop.
+    unrolled_defs: Vec<Term<Def>>,
+}
+
+impl Term<Sub> {
+    /// Unroll inlined copy and zero loops whose number of iterations is a known constant.
+    /// See the module-level documentation for more information.
+    ///
+    /// Returns the number of unrolled loops.
+    pub fn unroll_copy_loops(&mut self) -> usize {
+        let assigns_direction_flag = self.term.blocks.iter().any(|block| {
+            block.term.defs.iter().any(|def| match &def.term {
+                Def::Assign { var, .. } | Def::Load { var, .. } => var.name == "DF",
+                Def::Store { .. } => false,
+            })
+        });
+        let block_tids: Vec<Tid> = self
+            .term
+            .blocks
+            .iter()
+            .map(|block| block.tid.clone())
+            .collect();
+        let mut num_unrolled_loops = 0;
+        for head in block_tids {
+            let mut copy_loop = match self.find_copy_loop(&head) {
+                Some(copy_loop) => copy_loop,
+                None => continue,
+            };
+            if !assigns_direction_flag {
+                for def in copy_loop.unrolled_defs.iter_mut() {
+                    clear_direction_flag(def);
+                }
+            }
+            if let Some(body) = &copy_loop.body {
+                self.term.blocks.retain(|block| block.tid != *body);
+            }
+            let head_block = self
+                .term
+                .blocks
+                .iter_mut()
+                .find(|block| block.tid == copy_loop.head)
+                .unwrap();
+            head_block.term.defs = copy_loop.unrolled_defs;
+            head_block.term.jmps = vec![copy_loop.exit];
+            num_unrolled_loops += 1;
+        }
+        num_unrolled_loops
+    }
+
+    /// Check whether the block with the given TID is the head of a copy or zero loop
+    /// with a known number of iterations and compute the defs executed by the loop.
+    ///
+    /// The loop must consist of either a single block or of the head block and a body block only reachable from the head.
+    /// The loop condition must compare a counter variable to zero,
+    /// the counter must be decremented exactly once per iteration and the loop must contain at least one store.
+    /// The loop must have a single predecessor block that assigns a constant to the counter.
+    fn find_copy_loop(&self, head_tid: &Tid) -> Option<CopyLoop> {
+        let blocks = &self.term.blocks;
+        if blocks.first()?.tid == *head_tid {
+            // The entry block of a function may be reached through calls.
+            return None;
+        }
+        let find_block = |tid: &Tid| blocks.iter().find(|block| block.tid == *tid);
+        let head = find_block(head_tid)?;
+        let (condition, conditional_target, fallthrough_jmp) = match &head.term.jmps[..] {
+            [Term {
+                term: Jmp::CBranch { target, condition },
+                ..
+            }, fallthrough @ Term {
+                term: Jmp::Branch(fallthrough_target),
+                ..
+            }] => (condition, target, (fallthrough, fallthrough_target)),
+            _ => return None,
+        };
+        let is_loop_target = |target: &Tid| {
+            if target == head_tid {
+                return Some(None);
+            }
+            let body = find_block(target)?;
+            match &body.term.jmps[..] {
+                [Term {
+                    term: Jmp::Branch(back_edge_target),
+                    ..
+                }] if back_edge_target == head_tid => Some(Some(body)),
+                _ => None,
+            }
+        };
+        let (body, continue_if_true, exit) = match (
+            is_loop_target(conditional_target),
+            is_loop_target(fallthrough_jmp.1),
+        ) {
+            (Some(body), None) => (
+                body,
+                true,
+                Term {
+                    tid: fallthrough_jmp.0.tid.clone(),
+                    term: Jmp::Branch(fallthrough_jmp.1.clone()),
+                },
+            ),
+            (None, Some(body)) => (
+                body,
+                false,
+                Term {
+                    tid: head.term.jmps[0].tid.clone(),
+                    term: Jmp::Branch(conditional_target.clone()),
+                },
+            ),
+            _ => return None,
+        };
+        let body_defs: &[Term<Def>] = body.map(|body| &body.term.defs[..]).unwrap_or(&[]);
+
+        // The loop must only be entered through the head block from a single predecessor.
+        let loop_tids: Vec<&Tid> = std::iter::once(head_tid)
+            .chain(body.map(|body| &body.tid))
+            .collect();
+        let mut predecessors = blocks
+            .iter()
+            .filter(|block| !loop_tids.contains(&&block.tid) && block_references(block, head_tid));
+        let predecessor = predecessors.next()?;
+        if predecessors.next().is_some()
+            || blocks
+                .iter()
+                .any(|block| block_calls_with_return_to(block, head_tid))
+        {
+            return None;
+        }
+        if let Some(body) = body {
+            if blocks
+                .iter()
+                .any(|block| block.tid != *head_tid && block_references(block, &body.tid))
+            {
+                return None;
+            }
+        }
+
+        // Identify the counter and the def computing the value of the loop condition.
+        let (counter, true_iff_zero, condition_def_index) = match parse_zero_test(condition) {
+            Some((counter, true_iff_zero)) => (counter, true_iff_zero, None),
+            None => {
+                let (condition_var, negated) = match condition {
+                    Expression::Var(var) => (var, false),
+                    Expression::UnOp {
+                        op: UnOpType::BoolNegate,
+                        arg,
+                    } => (arg.as_var()?, true),
+                    _ => return None,
+                };
+                let (index, value) = head.term.defs.iter().enumerate().rev().find_map(
+                    |(index, def)| match &def.term {
+                        Def::Assign { var, value } if *var == *condition_var => {
+                            Some((index, value))
+                        }
+                        _ => None,
+                    },
+                )?;
+                let (counter, true_iff_zero) = parse_zero_test(value)?;
+                (counter, true_iff_zero != negated, Some(index))
+            }
+        };
+        let loop_defs = || head.term.defs.iter().chain(body_defs.iter());
+        let mut counter_assignments = loop_defs().filter(|def| match &def.term {
+            Def::Assign { var, .. } | Def::Load { var, .. } => *var == *counter,
+            Def::Store { .. } => false,
+        });
+        let decrement = counter_assignments.next()?;
+        if counter_assignments.next().is_some()
+            || !is_decrement(decrement, counter)
+            || !loop_defs().any(|def| matches!(def.term, Def::Store { .. }))
+        {
+            return None;
+        }
+        let mut counter_value = predecessor
+            .term
+            .defs
+            .iter()
+            .rev()
+            .find_map(|def| match &def.term {
+                Def::Assign { var, value } if var == counter => Some(Some(value)),
+                Def::Load { var, .. } if var == counter => Some(None),
+                _ => None,
+            })??
+            .evaluate_constant()?
+            .try_to_u64()
+            .ok()?;
+
+        // Simulate the loop counter to compute the defs executed by the loop.
+        let mut unrolled_defs = Vec::new();
+        for iteration in 0..=MAX_UNROLLED_ITERATIONS {
+            let mut tested_value = None;
+            for (index, def) in head.term.defs.iter().enumerate() {
+                unrolled_defs.push(copy_for_iteration(def, iteration));
+                if def.tid == decrement.tid {
+                    counter_value = counter_value.checked_sub(1)?;
+                }
+                if Some(index) == condition_def_index {
+                    tested_value = Some(counter_value);
+                }
+            }
+            let condition_value = (tested_value.unwrap_or(counter_value) == 0) == true_iff_zero;
+            if condition_value != continue_if_true {
+                return Some(CopyLoop {
+                    head: head_tid.clone(),
+                    body: body.map(|body| body.tid.clone()),
+                    exit,
+                    unrolled_defs,
+                });
+            }
+            for def in body_defs {
+                unrolled_defs.push(copy_for_iteration(def, iteration));
+                if def.tid == decrement.tid {
+                    counter_value = counter_value.checked_sub(1)?;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Expression {
+    /// Evaluate the expression if it is a (possibly extended) constant.
+    fn evaluate_constant(&self) -> Option<Bitvector> {
+        match self {
+            Expression::Const(bitvec) => Some(bitvec.clone()),
+            Expression::Cast {
+                op: op @ (CastOpType::IntZExt | CastOpType::IntSExt),
+                size,
+                arg,
+            } => arg.evaluate_constant()?.cast(*op, *size).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Return whether the block contains a jump (or a return from a call) to the target block.
+fn block_references(block: &Term<Blk>, target: &Tid) -> bool {
+    block.term.indirect_jmp_targets.contains(&target.address)
+        || block.term.jmps.iter().any(|jmp| match &jmp.term {
+            Jmp::Branch(jump_target)
+            | Jmp::CBranch {
+                target: jump_target,
+                ..
+            } => jump_target == target,
+            Jmp::Call { return_, .. }
+            | Jmp::CallInd { return_, .. }
+            | Jmp::CallOther { return_, .. } => return_.as_ref() == Some(target),
+            Jmp::BranchInd(_) | Jmp::Return(_) => false,
+        })
+}
+
+/// Return whether the block contains a call returning to the target block.
+/// The called function may change the loop counter in this case.
+fn block_calls_with_return_to(block: &Term<Blk>, target: &Tid) -> bool {
+    block.term.jmps.iter().any(|jmp| match &jmp.term {
+        Jmp::Call { return_, .. }
+        | Jmp::CallInd { return_, .. }
+        | Jmp::CallOther { return_, .. } => return_.as_ref() == Some(target),
+        _ => false,
+    })
+}
+
+/// If the expression compares a variable to zero, return the variable
+/// and whether the expression is true if and only if the variable is zero.
+fn parse_zero_test(expression: &Expression) -> Option<(&Variable, bool)> {
+    match expression {
+        Expression::UnOp {
+            op: UnOpType::BoolNegate,
+            arg,
+        } => parse_zero_test(arg).map(|(var, true_iff_zero)| (var, !true_iff_zero)),
+        Expression::BinOp {
+            op: op @ (BinOpType::IntEqual | BinOpType::IntNotEqual),
+            lhs,
+            rhs,
+        } => {
+            let var = match (lhs.as_ref(), rhs.as_ref()) {
+                (Expression::Var(var), Expression::Const(zero))
+                | (Expression::Const(zero), Expression::Var(var))
+                    if zero.is_zero() =>
+                {
+                    var
+                }
+                _ => return None,
+            };
+            Some((var, *op == BinOpType::IntEqual))
+        }
+        _ => None,
+    }
+}
+
+/// Return whether the def decrements the counter by one.
+fn is_decrement(def: &Term<Def>, counter: &Variable) -> bool {
+    let value = match &def.term {
+        Def::Assign { var, value } if var == counter => value,
+        _ => return false,
+    };
+    match value {
+        Expression::BinOp { op, lhs, rhs } if lhs.as_var() == Some(counter) => {
+            match (op, rhs.as_ref()) {
+                (BinOpType::IntSub, Expression::Const(one)) => one.try_to_u64() == Ok(1),
+                (BinOpType::IntAdd, Expression::Const(minus_one)) => {
+                    minus_one.clone().into_bitnot().is_zero()
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Copy the def for the given iteration of an unrolled loop.
+/// The copies for all iterations except the first one get new unique TIDs.
+fn copy_for_iteration(def: &Term<Def>, iteration: u64) -> Term<Def> {
+    if iteration == 0 {
+        def.clone()
+    } else {
+        Term {
+            tid: def
+                .tid
+                .clone()
+                .with_id_suffix(&format!("_unrolled_{}", iteration)),
+            term: def.term.clone(),
+        }
+    }
+}
+
+/// Replace all reads of the direction flag `DF` in the def by zero.
+fn clear_direction_flag(def: &mut Term<Def>) {
+    match &mut def.term {
+        Def::Assign { value: expr, .. } | Def::Load { address: expr, .. } => {
+            expr.clear_direction_flag()
+        }
+        Def::Store { address, value } => {
+            address.clear_direction_flag();
+            value.clear_direction_flag();
+        }
+    }
+}
+
+impl Expression {
+    /// Replace all occurences of the direction flag `DF` in the expression by zero.
+    fn clear_direction_flag(&mut self) {
+        match self {
+            Expression::Var(var) if var.name == "DF" => {
+                *self = Expression::Const(Bitvector::zero(var.size.into()))
+            }
+            Expression::Var(_) | Expression::Const(_) | Expression::Unknown { .. } => (),
+            Expression::BinOp { lhs, rhs, .. } => {
+                lhs.clear_direction_flag();
+                rhs.clear_direction_flag();
+            }
+            Expression::UnOp { arg, .. }
+            | Expression::Cast { arg, .. }
+            | Expression::Subpiece { arg, .. } => arg.clear_direction_flag(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reg(name: &str, size: u64) -> Variable {
+        Variable::mock(name, size)
+    }
+
+    fn r(name: &str) -> Expression {
+        Expression::Var(reg(name, 8))
+    }
+
+    /// A function with a `rep stosq` instruction whose counter is initialized with `RCX = count`.
+    fn rep_stosq_sub(count: Expression) -> Term<Sub> {
+        let zero_test = Expression::BinOp {
+            op: BinOpType::IntEqual,
+            lhs: Box::new(r("RCX")),
+            rhs: Box::new(Expression::const_from_i64(0)),
+        };
+        let step = Expression::const_from_i64(8).minus(Expression::BinOp {
+            op: BinOpType::IntMult,
+            lhs: Box::new(Expression::const_from_i64(16)),
+            rhs: Box::new(Expression::Var(reg("DF", 1)).cast(CastOpType::IntZExt)),
+        });
+        Sub::mock_with(
+            "func",
+            vec![
+                Blk::mock_with_jmps(
+                    "entry",
+                    vec![Def::assign("init", reg("RCX", 8), count)],
+                    vec![Jmp::Branch(Tid::new("head"))],
+                ),
+                Blk::mock_with_jmps(
+                    "head",
+                    vec![Def::assign("test", reg("$U1", 1), zero_test)],
+                    vec![
+                        Jmp::CBranch {
+                            target: Tid::new("exit"),
+                            condition: Expression::Var(reg("$U1", 1)),
+                        },
+                        Jmp::Branch(Tid::new("body")),
+                    ],
+                ),
+                Blk::mock_with_jmps(
+                    "body",
+                    vec![
+                        Def::assign("decrement", reg("RCX", 8), r("RCX").minus_const(1)),
+                        Def::store("store", r("RDI"), r("RAX")),
+                        Def::assign("increment", reg("RDI", 8), r("RDI").plus(step)),
+                    ],
+                    vec![Jmp::Branch(Tid::new("head"))],
+                ),
+                Blk::mock_with_jmps("exit", Vec::new(), Vec::new()),
+            ],
+        )
+    }
+
+    #[test]
+    fn unroll_rep_prefix_loop() {
+        let mut sub = rep_stosq_sub(Expression::const_from_i64(2));
+        assert_eq!(sub.unroll_copy_loops(), 1);
+        assert_eq!(sub.term.blocks.len(), 3);
+        let head = &sub.term.blocks[1];
+        // Two iterations of the body and three evaluations of the loop condition.
+        assert_eq!(head.term.defs.len(), 3 + 2 * 3);
+        assert_eq!(head.term.defs[2].tid, Tid::new("store"));
+        assert_eq!(head.term.defs[6].tid, Tid::new("store_unrolled_1"));
+        assert_eq!(head.term.jmps, vec![Jmp::branch("head_jmp_0", "exit")]);
+        // The direction flag is cleared in the unrolled defs.
+        assert!(head.term.defs.iter().all(|def| match &def.term {
+            Def::Assign { value, .. } => !value.input_vars().contains(&&reg("DF", 1)),
+            _ => true,
+        }));
+
+        // Loops with unknown trip counts are not unrolled.
+        let mut sub = rep_stosq_sub(r("RDX"));
+        assert_eq!(sub.unroll_copy_loops(), 0);
+        assert_eq!(sub.term.blocks.len(), 4);
+        // Loops with too many iterations are not unrolled.
+        let mut sub = rep_stosq_sub(Expression::const_from_i64(1000));
+        assert_eq!(sub.unroll_copy_loops(), 0);
+    }
+
+    #[test]
+    fn unroll_word_copy_loop() {
+        let not_zero = Expression::BinOp {
+            op: BinOpType::IntNotEqual,
+            lhs: Box::new(r("RCX")),
+            rhs: Box::new(Expression::const_from_i64(0)),
+        };
+        let mut sub = Sub::mock_with(
+            "func",
+            vec![
+                Blk::mock_with_jmps(
+                    "entry",
+                    vec![Def::assign(
+                        "init",
+                        reg("RCX", 8),
+                        Expression::Const(Bitvector::from_i32(3)).cast(CastOpType::IntZExt),
+                    )],
+                    vec![Jmp::Branch(Tid::new("loop"))],
+                ),
+                Blk::mock_with_jmps(
+                    "loop",
+                    vec![
+                        Def::load("load", reg("RAX", 8), r("RSI")),
+                        Def::store("store", r("RDI"), r("RAX")),
+                        Def::assign("increment_src", reg("RSI", 8), r("RSI").plus_const(8)),
+                        Def::assign("increment_dest", reg("RDI", 8), r("RDI").plus_const(8)),
+                        Def::assign("decrement", reg("RCX", 8), r("RCX").minus_const(1)),
+                        Def::assign("test", reg("ZF", 1), not_zero.un_op(UnOpType::BoolNegate)),
+                    ],
+                    vec![
+                        Jmp::CBranch {
+                            target: Tid::new("loop"),
+                            condition: Expression::Var(reg("ZF", 1)).un_op(UnOpType::BoolNegate),
+                        },
+                        Jmp::Branch(Tid::new("exit")),
+                    ],
+                ),
+                Blk::mock_with_jmps("exit", Vec::new(), Vec::new()),
+            ],
+        );
+        let mut unrolled_sub = sub.clone();
+        assert_eq!(unrolled_sub.unroll_copy_loops(), 1);
+        let unrolled_block = &unrolled_sub.term.blocks[1];
+        assert_eq!(unrolled_block.term.defs.len(), 3 * 6);
+        assert_eq!(
+            unrolled_block.term.jmps,
+            vec![Jmp::branch("loop_jmp_1", "exit")]
+        );
+
+        // Loops reachable from several blocks are not unrolled.
+        sub.term.blocks[2]
+            .term
+            .jmps
+            .push(Jmp::branch("other_entry", "loop"));
+        assert_eq!(sub.unroll_copy_loops(), 0);
+    }
+}
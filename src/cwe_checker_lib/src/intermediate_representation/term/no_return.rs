@@ -0,0 +1,293 @@
+//! Inference of internal functions that never return.
+//!
+//! The disassembler only marks extern symbols as non-returning.
+//! Internal wrappers around non-returning functions (e.g. an error handler calling `abort`)
+//! are treated as returning functions, so that the code after their call sites is analyzed as reachable.
+//! Such bogus fallthrough paths pollute the results of all analyses.
+
+use super::*;
+
+/// The configuration of the handling of non-returning functions,
+/// read from the `NoReturn` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NoReturnConfig {
+    /// Extern functions known to never return.
+    pub symbols: Vec<String>,
+    /// Whether misclassified `no_return` flags of extern symbols get corrected before the analysis.
+    #[serde(default)]
+    pub auto_correct: bool,
+    /// Whether internal functions that never return get inferred before the analysis.
+    #[serde(default)]
+    pub infer_internal_functions: bool,
+}
+
+impl Project {
+    /// Infer which internal functions never return and remove the return targets of all calls to them.
+    /// Return a log message for each non-returning function whose call sites were corrected.
+    ///
+    /// A function never returns if no return instruction is reachable from its entry block,
+    /// i.e. if all paths end in calls to non-returning functions or in infinite loops.
+    /// Calls to extern symbols are non-returning if the `no_return` flag of the symbol is set.
+    /// The analysis is conservative: functions containing tail calls to returning functions,
+    /// indirect jumps without known targets or basic blocks without jumps (from control flow reconstruction errors)
+    /// are assumed to return.
+    /// Since the control flow graph is generated after this pass,
+    /// the analyses do not follow the removed return edges.
+    #[must_use]
+    pub fn infer_no_return_functions(&mut self) -> Vec<LogMessage> {
+        let extern_symbols: HashMap<&Tid, bool> = self
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| (&symbol.tid, symbol.no_return))
+            .collect();
+        let sub_tids: HashSet<&Tid> = self.program.term.subs.iter().map(|sub| &sub.tid).collect();
+        // Compute the least fixpoint of the set of internal functions that may return.
+        let mut returning_subs: HashSet<&Tid> = HashSet::new();
+        loop {
+            let mut changed = false;
+            for sub in self.program.term.subs.iter() {
+                if returning_subs.contains(&sub.tid) {
+                    continue;
+                }
+                let is_returning = |target: &Tid| match extern_symbols.get(target) {
+                    Some(no_return) => !no_return,
+                    None => returning_subs.contains(target) || !sub_tids.contains(target),
+                };
+                if may_return(sub, is_returning) {
+                    returning_subs.insert(&sub.tid);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let no_return_subs: HashMap<Tid, String> = self
+            .program
+            .term
+            .subs
+            .iter()
+            .filter(|sub| !returning_subs.contains(&sub.tid))
+            .map(|sub| (sub.tid.clone(), sub.term.display_name().to_string()))
+            .collect();
+
+        let mut corrected_callsites: BTreeMap<Tid, usize> = BTreeMap::new();
+        for sub in self.program.term.subs.iter_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                for jmp in block.term.jmps.iter_mut() {
+                    if let Jmp::Call { target, return_ } = &mut jmp.term {
+                        if return_.is_some() && no_return_subs.contains_key(target) {
+                            *return_ = None;
+                            *corrected_callsites.entry(target.clone()).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+        }
+        corrected_callsites
+            .into_iter()
+            .map(|(sub_tid, num_callsites)| {
+                LogMessage::new_info(format!(
+                    "Function {} never returns. Removed the return targets of {} of its call sites.",
+                    no_return_subs[&sub_tid], num_callsites
+                ))
+                .location(sub_tid)
+            })
+            .collect()
+    }
+}
+
+/// Check whether a return instruction of the function is reachable from its entry block.
+/// The closure `is_returning` decides whether the targets of direct calls return.
+fn may_return(sub: &Term<Sub>, is_returning: impl Fn(&Tid) -> bool) -> bool {
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut worklist: Vec<&Term<Blk>> = match sub.term.blocks.first() {
+        Some(entry_block) => vec![entry_block],
+        None => return true,
+    };
+    let mut visited: HashSet<&Tid> = HashSet::new();
+    while let Some(block) = worklist.pop() {
+        if !visited.insert(&block.tid) {
+            continue;
+        }
+        if block.term.jmps.is_empty() {
+            return true;
+        }
+        let mut successors: Vec<Tid> = Vec::new();
+        for jmp in block.term.jmps.iter() {
+            match &jmp.term {
+                Jmp::Return(_) => return true,
+                Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                    successors.push(target.clone())
+                }
+                Jmp::BranchInd(_) => {
+                    if block.term.indirect_jmp_targets.is_empty() {
+                        return true;
+                    }
+                    successors.extend(
+                        block
+                            .term
+                            .indirect_jmp_targets
+                            .iter()
+                            .map(|address| Tid::blk_id_at_address(address)),
+                    );
+                }
+                Jmp::Call { target, return_ } => {
+                    if is_returning(target) {
+                        match return_ {
+                            Some(return_target) => successors.push(return_target.clone()),
+                            // A tail call to a returning function.
+                            None => return true,
+                        }
+                    }
+                }
+                Jmp::CallInd { return_, .. } | Jmp::CallOther { return_, .. } => match return_ {
+                    Some(return_target) => successors.push(return_target.clone()),
+                    None => return true,
+                },
+            }
+        }
+        for successor in successors {
+            match blocks.get(&successor) {
+                Some(successor_block) => worklist.push(successor_block),
+                // A jump to another function.
+                None => return true,
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_return_inference() {
+        let mut project = Project::mock_empty();
+        let mut abort = ExternSymbol::mock_with("abort", &[]);
+        abort.no_return = true;
+        let puts = ExternSymbol::mock_with("puts", &["RDI"]);
+        project.program.term.extern_symbols = vec![abort, puts];
+        project.program.term.subs = vec![
+            // Calls a function that never returns.
+            Sub::mock_with(
+                "main",
+                vec![
+                    Blk::mock_with("main_0", vec![], Jmp::mock_call("fatal", "main_1")),
+                    Blk::mock_with("main_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+            // Wrapper around `abort`, called through another wrapper.
+            Sub::mock_with(
+                "fatal",
+                vec![
+                    Blk::mock_with("fatal_0", vec![], Jmp::mock_call("puts", "fatal_1")),
+                    Blk::mock_with("fatal_1", vec![], Jmp::mock_call("die", "fatal_2")),
+                    Blk::mock_with("fatal_2", vec![], Jmp::mock_return()),
+                ],
+            ),
+            Sub::mock_with(
+                "die",
+                vec![Blk::mock_with(
+                    "die_0",
+                    vec![],
+                    Jmp::mock_call("abort", "die_1"),
+                )],
+            ),
+            // Infinite loop.
+            Sub::mock_with(
+                "loop",
+                vec![Blk::mock_with(
+                    "loop_0",
+                    vec![],
+                    Jmp::Branch(Tid::new("loop_0")),
+                )],
+            ),
+            // Tail call to a returning function.
+            Sub::mock_with(
+                "tail",
+                vec![Blk::mock_with(
+                    "tail_0",
+                    vec![],
+                    Jmp::Call {
+                        target: Tid::new("puts"),
+                        return_: None,
+                    },
+                )],
+            ),
+            // Recursion with a base case.
+            Sub::mock_with(
+                "recursive",
+                vec![
+                    Blk::mock_with_jmps(
+                        "recursive_0",
+                        vec![],
+                        vec![
+                            Jmp::CBranch {
+                                target: Tid::new("recursive_1"),
+                                condition: Expression::const_from_i64(0),
+                            },
+                            Jmp::mock_call("recursive", "recursive_1"),
+                        ],
+                    ),
+                    Blk::mock_with("recursive_1", vec![], Jmp::mock_return()),
+                ],
+            ),
+        ];
+
+        let logs = project.infer_no_return_functions();
+        assert_eq!(logs.len(), 2);
+        let return_target = |sub_index: usize, block_index: usize| match &project.program.term.subs
+            [sub_index]
+            .term
+            .blocks[block_index]
+            .term
+            .jmps
+            .last()
+            .unwrap()
+            .term
+        {
+            Jmp::Call { return_, .. } => return_.clone(),
+            _ => panic!(),
+        };
+        assert_eq!(return_target(0, 0), None);
+        assert_eq!(return_target(1, 1), None);
+        assert_eq!(return_target(1, 0), Some(Tid::new("fatal_1")));
+        assert_eq!(return_target(5, 0), Some(Tid::new("recursive_1")));
+    }
+
+    #[test]
+    fn no_return_config() {
+        use crate::utils::parse_config;
+        let config: NoReturnConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "auto_correct": true,
+            "symbols": ["exit"]
+        }))
+        .unwrap();
+        assert_eq!(config.symbols, vec!["exit".to_string()]);
+        assert!(config.auto_correct);
+        assert!(!config.infer_internal_functions);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<NoReturnConfig>(&serde_json::json!({
+            "auto_correct": "true",
+            "symbols": ["exit"]
+        }))
+        .is_err());
+        assert!(parse_config::<NoReturnConfig>(&serde_json::json!({
+            "auto_corect": true,
+            "symbols": ["exit"]
+        }))
+        .is_err());
+        assert!(parse_config::<NoReturnConfig>(&serde_json::Value::Null).is_err());
+    }
+}
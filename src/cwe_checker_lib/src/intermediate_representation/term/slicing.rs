@@ -0,0 +1,112 @@
+//! Slicing of projects down to a set of functions.
+//!
+//! A sliced project can be analyzed on its own, e.g. to reproduce the analysis of a single function in a unit test.
+
+use super::*;
+use std::collections::BTreeSet;
+
+impl Project {
+    /// Remove all functions except the functions with the given TIDs from the program.
+    ///
+    /// Calls to removed functions are replaced by indirect calls to unknown targets,
+    /// so that the analyses handle them like calls to unknown functions.
+    /// Extern symbols are kept.
+    /// The entry points of the sliced program are the kept functions that are entry points of the original program
+    /// or that are not called by other kept functions.
+    /// If no such function exists (e.g. for mutually recursive functions), all kept functions are entry points.
+    pub fn slice_to_functions(&mut self, sub_tids: &BTreeSet<Tid>) {
+        let pointer_size = self.get_pointer_bytesize();
+        let program = &mut self.program.term;
+        program.subs.retain(|sub| sub_tids.contains(&sub.tid));
+        let mut called_subs = HashSet::new();
+        for sub in program.subs.iter_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                for jmp in block.term.jmps.iter_mut() {
+                    let (target, return_) = match &jmp.term {
+                        Jmp::Call { target, return_ } => (target, return_),
+                        _ => continue,
+                    };
+                    if sub_tids.contains(target) {
+                        if *target != sub.tid {
+                            called_subs.insert(target.clone());
+                        }
+                    } else if !program
+                        .extern_symbols
+                        .iter()
+                        .any(|symbol| symbol.tid == *target)
+                    {
+                        jmp.term = Jmp::CallInd {
+                            target: Expression::Unknown {
+                                description: format!("Call to removed function {}", target),
+                                size: pointer_size,
+                            },
+                            return_: return_.clone(),
+                        };
+                    }
+                }
+            }
+        }
+        let original_entry_points = std::mem::take(&mut program.entry_points);
+        program.entry_points = program
+            .subs
+            .iter()
+            .filter(|sub| {
+                original_entry_points.contains(&sub.tid) || !called_subs.contains(&sub.tid)
+            })
+            .map(|sub| sub.tid.clone())
+            .collect();
+        if program.entry_points.is_empty() {
+            program.entry_points = program.subs.iter().map(|sub| sub.tid.clone()).collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(target: &str) -> Term<Blk> {
+        let mut block = Blk::mock();
+        block.tid = Tid::new(format!("block_calling_{}", target));
+        block.term.jmps = vec![Term {
+            tid: Tid::new(format!("call_{}", target)),
+            term: Jmp::Call {
+                target: Tid::new(target),
+                return_: None,
+            },
+        }];
+        block
+    }
+
+    #[test]
+    fn slicing() {
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let mut main = Sub::mock("main");
+        main.term.blocks = vec![call("caller")];
+        let mut caller = Sub::mock("caller");
+        caller.term.blocks = vec![call("callee"), call("other"), call("mock_symbol")];
+        let callee = Sub::mock("callee");
+        let other = Sub::mock("other");
+        project.program.term.subs = vec![main, caller, callee, other];
+        project.program.term.entry_points = vec![Tid::new("main")];
+
+        let kept_subs: BTreeSet<Tid> = vec![Tid::new("caller"), Tid::new("callee")]
+            .into_iter()
+            .collect();
+        project.slice_to_functions(&kept_subs);
+        let program = &project.program.term;
+        assert_eq!(program.subs.len(), 2);
+        assert_eq!(program.entry_points, vec![Tid::new("caller")]);
+        assert_eq!(program.extern_symbols.len(), 1);
+        let jmps: Vec<&Jmp> = program.subs[0]
+            .term
+            .blocks
+            .iter()
+            .map(|block| &block.term.jmps[0].term)
+            .collect();
+        assert!(matches!(jmps[0], Jmp::Call { target, .. } if *target == Tid::new("callee")));
+        assert!(matches!(jmps[1], Jmp::CallInd { .. }));
+        assert!(matches!(jmps[2], Jmp::Call { target, .. } if *target == Tid::new("mock_symbol")));
+    }
+}
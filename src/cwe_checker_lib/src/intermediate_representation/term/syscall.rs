@@ -0,0 +1,355 @@
+//! Semantic models for direct syscalls.
+//!
+//! Statically linked binaries often perform syscalls directly instead of calling the corresponding libc functions.
+//! Such syscalls are represented by `CallOther` jumps in the intermediate representation,
+//! so that checks searching for calls to extern symbols like `read` or `execve` do not recognize them.
+//! With the help of a table of syscall numbers for the CPU architecture and operating system of the binary
+//! we replace syscall sites with a known syscall number by calls to artificial extern symbols
+//! named after the corresponding libc functions.
+
+use super::*;
+use crate::intermediate_representation::BitvectorExtended;
+
+/// The name of the artificial calling convention used by extern symbols representing syscalls.
+pub const SYSCALL_CALLING_CONVENTION: &str = "__syscall";
+
+/// The configuration of the syscall semantics,
+/// read from the `Syscalls` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SyscallsConfig {
+    /// The syscall models for all supported combinations of CPU architecture and operating system.
+    pub models: Vec<SyscallModel>,
+}
+
+/// The syscall semantics for a combination of CPU architecture and operating system.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SyscallModel {
+    /// The CPU architecture (as named in [`Project::cpu_architecture`]) that the model applies to.
+    pub cpu_architecture: String,
+    /// The operating system that the model applies to.
+    pub operating_system: String,
+    /// The description of the `CallOther` jumps representing the syscall instruction.
+    pub instruction: String,
+    /// The register containing the syscall number.
+    pub number_register: String,
+    /// The registers containing the syscall parameters in order.
+    pub parameter_register: Vec<String>,
+    /// The register containing the return value of the syscall.
+    pub return_register: String,
+    /// The known syscalls.
+    pub syscalls: Vec<Syscall>,
+}
+
+/// A syscall that gets modeled by an extern symbol.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Syscall {
+    /// The syscall number.
+    pub number: u64,
+    /// The name of the extern symbol representing the syscall.
+    pub symbol: String,
+    /// The number of parameters of the syscall.
+    pub parameters: usize,
+    /// Set to `true` if the syscall never returns.
+    #[serde(default)]
+    pub no_return: bool,
+}
+
+impl Project {
+    /// Replace syscall sites by calls to artificial extern symbols representing the syscalls,
+    /// using the model for the CPU architecture of the project and the given operating system.
+    ///
+    /// The syscall number has to be a constant that can be computed from the instructions of the basic block
+    /// containing the syscall.
+    /// Syscall sites where this is not the case or where the syscall number is unknown are not changed.
+    /// Return a log message for each syscall site that could not be replaced.
+    #[must_use]
+    pub fn replace_syscalls_by_extern_calls(
+        &mut self,
+        operating_system: &str,
+        models: &[SyscallModel],
+    ) -> Vec<LogMessage> {
+        let model = match models.iter().find(|model| {
+            model.cpu_architecture == self.cpu_architecture
+                && model.operating_system == operating_system
+        }) {
+            Some(model) => model,
+            None => return Vec::new(),
+        };
+        let pointer_bytesize = self.get_pointer_bytesize();
+        let register = |name: &str| Variable {
+            name: name.to_string(),
+            size: pointer_bytesize,
+            is_temp: false,
+        };
+        let number_register = register(&model.number_register);
+        // On x86 the stack pointer is adjusted for the return address pushed by the call instruction
+        // when returning from an extern call. Since syscall instructions do not push a return address,
+        // we add a corresponding adjustment of the stack pointer before the call.
+        let push_return_address = if self.cpu_architecture.starts_with("x86") {
+            let stack_pointer = Expression::Var(self.stack_pointer_register.clone());
+            Some(Def::Assign {
+                var: self.stack_pointer_register.clone(),
+                value: stack_pointer.plus_const(-(u64::from(self.get_pointer_bytesize()) as i64)),
+            })
+        } else {
+            None
+        };
+
+        let mut logs = Vec::new();
+        let mut used_syscalls: Vec<&Syscall> = Vec::new();
+        for sub in self.program.term.subs.iter_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                let (jmp_tid, return_) = match &block.term.jmps[..] {
+                    [Term {
+                        tid,
+                        term:
+                            Jmp::CallOther {
+                                description,
+                                return_,
+                            },
+                    }] if *description == model.instruction => (tid.clone(), return_.clone()),
+                    _ => continue,
+                };
+                let syscall = match eval_constant_in_block(
+                    &block.term.defs,
+                    block.term.defs.len(),
+                    &Expression::Var(number_register.clone()),
+                )
+                .and_then(|number| number.try_to_u64().ok())
+                {
+                    Some(number) => match model
+                        .syscalls
+                        .iter()
+                        .find(|syscall| syscall.number == number)
+                    {
+                        Some(syscall) => syscall,
+                        None => {
+                            logs.push(
+                                LogMessage::new_debug(format!("Unknown syscall number {}", number))
+                                    .location(jmp_tid),
+                            );
+                            continue;
+                        }
+                    },
+                    None => {
+                        logs.push(
+                            LogMessage::new_debug("Could not determine the syscall number")
+                                .location(jmp_tid),
+                        );
+                        continue;
+                    }
+                };
+                if let Some(push_return_address) = &push_return_address {
+                    block.term.defs.push(Term {
+                        tid: jmp_tid.clone().with_id_suffix("_push_return_address"),
+                        term: push_return_address.clone(),
+                    });
+                }
+                block.term.jmps[0].term = Jmp::Call {
+                    target: get_syscall_symbol_tid(&syscall.symbol),
+                    return_: if syscall.no_return { None } else { return_ },
+                };
+                if !used_syscalls.contains(&syscall) {
+                    used_syscalls.push(syscall);
+                }
+            }
+        }
+
+        if used_syscalls.is_empty() {
+            return logs;
+        }
+        for syscall in used_syscalls {
+            self.program.term.extern_symbols.push(ExternSymbol {
+                tid: get_syscall_symbol_tid(&syscall.symbol),
+                addresses: Vec::new(),
+                name: syscall.symbol.clone(),
+                calling_convention: Some(SYSCALL_CALLING_CONVENTION.to_string()),
+                parameters: model
+                    .parameter_register
+                    .iter()
+                    .take(syscall.parameters)
+                    .map(|name| Arg::Register(register(name)))
+                    .collect(),
+                return_values: vec![Arg::Register(register(&model.return_register))],
+                no_return: syscall.no_return,
+            });
+        }
+        if !self
+            .calling_conventions
+            .iter()
+            .any(|cconv| cconv.name == SYSCALL_CALLING_CONVENTION)
+        {
+            self.calling_conventions.push(CallingConvention {
+                name: SYSCALL_CALLING_CONVENTION.to_string(),
+                parameter_register: model.parameter_register.clone(),
+                return_register: vec![model.return_register.clone()],
+                // The kernel preserves all parameter registers not used for the return value.
+                callee_saved_register: model
+                    .parameter_register
+                    .iter()
+                    .filter(|name| **name != model.return_register)
+                    .cloned()
+                    .collect(),
+                stack_alignment: None,
+            });
+        }
+        logs
+    }
+}
+
+/// Get the TID of the artificial extern symbol representing the syscall with the given name.
+fn get_syscall_symbol_tid(symbol: &str) -> Tid {
+    Tid::new(format!("syscall_{}", symbol))
+}
+
+/// Try to compute the constant value of the expression
+/// when evaluated after the first `position` defs of the block were executed.
+///
+/// Only values computable from constants assigned in the block are recognized.
+pub(super) fn eval_constant_in_block(
+    defs: &[Term<Def>],
+    position: usize,
+    expression: &Expression,
+) -> Option<Bitvector> {
+    match expression {
+        Expression::Const(bitvec) => Some(bitvec.clone()),
+        Expression::Var(var) => {
+            let (index, def) =
+                defs[..position]
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find(|(_, def)| match &def.term {
+                        Def::Assign { var: target, .. } | Def::Load { var: target, .. } => {
+                            target == var
+                        }
+                        Def::Store { .. } => false,
+                    })?;
+            match &def.term {
+                Def::Assign { value, .. } => eval_constant_in_block(defs, index, value),
+                _ => None,
+            }
+        }
+        Expression::BinOp { op, lhs, rhs } => {
+            let lhs = eval_constant_in_block(defs, position, lhs)?;
+            let rhs = eval_constant_in_block(defs, position, rhs)?;
+            lhs.bin_op(*op, &rhs).ok()
+        }
+        Expression::Cast { op, size, arg } => eval_constant_in_block(defs, position, arg)?
+            .cast(*op, *size)
+            .ok(),
+        Expression::Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => Some(eval_constant_in_block(defs, position, arg)?.subpiece(*low_byte, *size)),
+        Expression::UnOp { .. } | Expression::Unknown { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_model() -> SyscallModel {
+        SyscallModel {
+            cpu_architecture: "x86_64".to_string(),
+            operating_system: "linux".to_string(),
+            instruction: "syscall".to_string(),
+            number_register: "RAX".to_string(),
+            parameter_register: vec!["RDI".to_string(), "RSI".to_string(), "RDX".to_string()],
+            return_register: "RAX".to_string(),
+            syscalls: vec![Syscall {
+                number: 59,
+                symbol: "execve".to_string(),
+                parameters: 3,
+                no_return: false,
+            }],
+        }
+    }
+
+    fn mock_syscall_block(name: &str, number_assignment: Expression) -> Term<Blk> {
+        let mut block = Blk::mock();
+        block.tid = Tid::new(name);
+        block.term.defs = vec![
+            Def::assign(
+                &format!("{}_eax", name),
+                Variable::mock("EAX", 4),
+                number_assignment,
+            ),
+            Def::assign(
+                &format!("{}_rax", name),
+                Variable::mock("RAX", 8),
+                Expression::Var(Variable::mock("EAX", 4)).cast(CastOpType::IntZExt),
+            ),
+        ];
+        block.term.jmps = vec![Term {
+            tid: Tid::new(format!("{}_syscall", name)),
+            term: Jmp::CallOther {
+                description: "syscall".to_string(),
+                return_: Some(Tid::new("return")),
+            },
+        }];
+        block
+    }
+
+    #[test]
+    fn syscall_replacement() {
+        let mut project = Project::mock_empty();
+        let mut sub = Sub::mock("main");
+        sub.term.blocks = vec![
+            mock_syscall_block("known", Expression::Const(Bitvector::from_u32(59))),
+            mock_syscall_block("unknown", Expression::Const(Bitvector::from_u32(1))),
+            mock_syscall_block("unresolved", Expression::Var(Variable::mock("ECX", 4))),
+        ];
+        project.program.term.subs.push(sub);
+
+        // No model for the operating system
+        assert!(project
+            .replace_syscalls_by_extern_calls("windows", &[mock_model()])
+            .is_empty());
+
+        let logs = project.replace_syscalls_by_extern_calls("linux", &[mock_model()]);
+        assert_eq!(logs.len(), 2);
+        let blocks = &project.program.term.subs[0].term.blocks;
+        assert_eq!(
+            blocks[0].term.jmps[0].term,
+            Jmp::Call {
+                target: Tid::new("syscall_execve"),
+                return_: Some(Tid::new("return")),
+            }
+        );
+        assert_eq!(blocks[0].term.defs.len(), 3);
+        assert!(matches!(blocks[1].term.jmps[0].term, Jmp::CallOther { .. }));
+        let symbol = &project.program.term.extern_symbols[0];
+        assert_eq!(symbol.name, "execve");
+        assert_eq!(symbol.parameters.len(), 3);
+        assert_eq!(
+            symbol
+                .get_calling_convention(&project)
+                .callee_saved_register,
+            vec!["RDI".to_string(), "RSI".to_string(), "RDX".to_string()]
+        );
+    }
+
+    #[test]
+    fn syscalls_config() {
+        use crate::utils::parse_config;
+        let mut model = serde_json::to_value(mock_model()).unwrap();
+        let config: SyscallsConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "models": [model]
+        }))
+        .unwrap();
+        assert_eq!(config.models, vec![mock_model()]);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        model["syscalls"][0]["parameter"] = serde_json::json!(3);
+        assert!(parse_config::<SyscallsConfig>(&serde_json::json!({ "models": [model] })).is_err());
+        model["syscalls"][0]["parameters"] = serde_json::json!("3");
+        assert!(parse_config::<SyscallsConfig>(&serde_json::json!({ "models": [model] })).is_err());
+        assert!(parse_config::<SyscallsConfig>(&serde_json::Value::Null).is_err());
+    }
+}
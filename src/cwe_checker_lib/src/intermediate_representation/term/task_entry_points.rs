@@ -0,0 +1,193 @@
+//! Entry points of tasks and threads.
+//!
+//! Firmware built on a real-time operating system (e.g. FreeRTOS or Zephyr) usually has only one entry point
+//! that initializes the hardware, starts the tasks of the firmware and then hands control to the scheduler.
+//! The functions executed by the tasks are only referenced through function pointers given to the task creation functions,
+//! so that the analyses cannot reach them from the entry points of the binary.
+//! Adding them to the entry points of the program lets the analyses start there.
+
+use super::syscall::eval_constant_in_block;
+use super::*;
+use crate::utils::symbol_utils::get_variadic_parameter;
+
+/// The configuration of the task entry points,
+/// read from the `TaskEntryPoints` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TaskEntryPointsConfig {
+    /// Maps the names of task creation functions
+    /// to the index of the parameter containing the address of the function executed by the new task.
+    pub task_creation_symbols: BTreeMap<String, usize>,
+}
+
+impl Project {
+    /// Add the functions started as tasks or threads by calls to the given extern symbols to the entry points of the program.
+    /// The functions are determined by [`get_task_entry_points`](Project::get_task_entry_points).
+    /// Return a log message for each added entry point.
+    #[must_use]
+    pub fn add_task_entry_points(
+        &mut self,
+        task_creation_symbols: &BTreeMap<String, usize>,
+    ) -> Vec<LogMessage> {
+        let new_entry_points: Vec<Tid> = self
+            .get_task_entry_points(task_creation_symbols)
+            .into_iter()
+            .filter(|tid| !self.program.term.entry_points.contains(tid))
+            .collect();
+        let logs = new_entry_points
+            .iter()
+            .map(|tid| {
+                LogMessage::new_debug(format!("Added task entry point {}", tid))
+                    .source("Task Entry Points")
+            })
+            .collect();
+        self.program.term.entry_points.extend(new_entry_points);
+        logs
+    }
+
+    /// Get the functions started as tasks or threads by calls to the given extern symbols.
+    /// The map `task_creation_symbols` maps the names of task creation functions
+    /// to the index of the parameter containing the address of the function executed by the new task.
+    ///
+    /// The address has to be a constant that can be computed from the instructions of the basic block
+    /// containing the call and it has to be passed in a register.
+    /// On ARM the lowest bit of the address (marking Thumb code) is ignored.
+    pub fn get_task_entry_points(
+        &self,
+        task_creation_symbols: &BTreeMap<String, usize>,
+    ) -> Vec<Tid> {
+        let entry_parameters: HashMap<&Tid, Variable> = self
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .filter_map(|symbol| {
+                let index = task_creation_symbols.get(&symbol.name)?;
+                match get_variadic_parameter(self, symbol, *index) {
+                    Arg::Register(var) => Some((&symbol.tid, var)),
+                    Arg::Stack { .. } => None,
+                }
+            })
+            .collect();
+        if entry_parameters.is_empty() {
+            return Vec::new();
+        }
+        let sub_address_map: HashMap<u64, &Tid> = self
+            .program
+            .term
+            .subs
+            .iter()
+            .filter_map(|sub| {
+                let address = u64::from_str_radix(&sub.tid.address, 16).ok()?;
+                Some((address, &sub.tid))
+            })
+            .collect();
+        let ignore_thumb_bit = self.cpu_architecture.starts_with("ARM");
+
+        let mut entry_points = Vec::new();
+        for sub in self.program.term.subs.iter() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    let entry_parameter = match &jmp.term {
+                        Jmp::Call { target, .. } => match entry_parameters.get(target) {
+                            Some(entry_parameter) => entry_parameter,
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+                    let entry_address = eval_constant_in_block(
+                        &block.term.defs,
+                        block.term.defs.len(),
+                        &Expression::Var(entry_parameter.clone()),
+                    )
+                    .and_then(|address| address.try_to_u64().ok())
+                    .map(|address| {
+                        if ignore_thumb_bit {
+                            address & !1
+                        } else {
+                            address
+                        }
+                    });
+                    if let Some(entry_tid) =
+                        entry_address.and_then(|address| sub_address_map.get(&address))
+                    {
+                        if !entry_points.contains(*entry_tid) {
+                            entry_points.push((*entry_tid).clone());
+                        }
+                    }
+                }
+            }
+        }
+        entry_points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_entry_points() {
+        let mut project = Project::mock_empty();
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "xTaskCreate".to_string();
+        project.program.term.extern_symbols.push(symbol);
+        let mut task = Sub::mock("task");
+        task.tid.address = "1000".to_string();
+        let mut main = Sub::mock("main");
+        let mut block = Blk::mock();
+        block.term.defs = vec![Def::assign(
+            "entry_assignment",
+            Variable::mock("RDI", 8),
+            Expression::Const(Bitvector::from_u64(0x1000)),
+        )];
+        block.term.jmps = vec![Term {
+            tid: Tid::new("create_task"),
+            term: Jmp::Call {
+                target: Tid::new("mock_symbol"),
+                return_: Some(Tid::new("return")),
+            },
+        }];
+        main.term.blocks = vec![block];
+        let task_tid = task.tid.clone();
+        project.program.term.subs = vec![main, task];
+
+        let task_creation_symbols: BTreeMap<String, usize> =
+            vec![("xTaskCreate".to_string(), 0)].into_iter().collect();
+        assert!(project.add_task_entry_points(&BTreeMap::new()).is_empty());
+        assert_eq!(
+            project.get_task_entry_points(&task_creation_symbols),
+            vec![task_tid.clone()]
+        );
+        assert_eq!(
+            project.add_task_entry_points(&task_creation_symbols).len(),
+            1
+        );
+        assert_eq!(project.program.term.entry_points, vec![task_tid]);
+        // Entry points are only added once.
+        assert!(project
+            .add_task_entry_points(&task_creation_symbols)
+            .is_empty());
+    }
+
+    #[test]
+    fn task_entry_points_config() {
+        use crate::utils::parse_config;
+        let config: TaskEntryPointsConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "task_creation_symbols": {"xTaskCreate": 0}
+        }))
+        .unwrap();
+        assert_eq!(config.task_creation_symbols["xTaskCreate"], 0);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<TaskEntryPointsConfig>(&serde_json::json!({
+            "task_creation_symbols": {"xTaskCreate": "pvTaskCode"}
+        }))
+        .is_err());
+        assert!(parse_config::<TaskEntryPointsConfig>(&serde_json::json!({
+            "task_creation_symbol": {"xTaskCreate": 0}
+        }))
+        .is_err());
+        assert!(parse_config::<TaskEntryPointsConfig>(&serde_json::Value::Null).is_err());
+    }
+}
@@ -49,11 +49,16 @@ You can find out more information about each check, including known false positi
 by reading the check-specific module documentation in the [`checkers`] module.
 */
 
+use crate::analysis::def_use::DefUseChains;
 use crate::analysis::graph::Graph;
 use crate::analysis::pointer_inference::PointerInference;
-use crate::intermediate_representation::Project;
+use crate::intermediate_representation::{Project, Tid};
 use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::graph_utils::{get_immediate_dominators, CallGraph};
 use crate::utils::log::{CweWarning, LogMessage};
+use anyhow::Error;
+use std::cell::OnceCell;
+use std::collections::HashMap;
 
 pub mod abstract_domain;
 pub mod analysis;
@@ -74,7 +79,7 @@ mod prelude {
 
 /// The generic function signature for the main function of a CWE module
 pub type CweModuleFn =
-    fn(&AnalysisResults, &serde_json::Value) -> (Vec<LogMessage>, Vec<CweWarning>);
+    for<'a> fn(&'a AnalysisResults<'a>, &serde_json::Value) -> (Vec<LogMessage>, Vec<CweWarning>);
 
 /// A structure containing general information about a CWE analysis module,
 /// including the function to be called to run the analysis.
@@ -86,6 +91,11 @@ pub struct CweModule {
     pub version: &'static str,
     /// The function that executes the check and returns CWE warnings found during the check.
     pub run: CweModuleFn,
+    /// The function that checks whether a configuration can be parsed into the configuration struct of the check.
+    pub validate_config: fn(&serde_json::Value) -> Result<(), Error>,
+    /// The function that returns the default values of all optional settings of the check.
+    /// These are used for all optional settings missing in the configuration of the check.
+    pub default_config: fn() -> serde_json::Value,
 }
 
 impl std::fmt::Display for CweModule {
@@ -98,25 +108,76 @@ impl std::fmt::Display for CweModule {
 /// Get a list of all known analysis modules.
 pub fn get_modules() -> Vec<&'static CweModule> {
     vec![
+        &crate::checkers::cwe_14::CWE_MODULE,
         &crate::checkers::cwe_78::CWE_MODULE,
+        &crate::checkers::cwe_88::CWE_MODULE,
+        &crate::checkers::cwe_120::CWE_MODULE,
+        &crate::checkers::cwe_121::CWE_MODULE,
+        &crate::checkers::cwe_122::CWE_MODULE,
+        &crate::checkers::cwe_129::CWE_MODULE,
+        &crate::checkers::cwe_131::CWE_MODULE,
+        &crate::checkers::cwe_134::CWE_MODULE,
         &crate::checkers::cwe_190::CWE_MODULE,
+        &crate::checkers::cwe_197::CWE_MODULE,
+        &crate::checkers::cwe_208::CWE_MODULE,
         &crate::checkers::cwe_215::CWE_MODULE,
         &crate::checkers::cwe_243::CWE_MODULE,
+        &crate::checkers::cwe_252::CWE_MODULE,
+        &crate::checkers::cwe_273::CWE_MODULE,
+        &crate::checkers::cwe_295::CWE_MODULE,
+        &crate::checkers::cwe_321::CWE_MODULE,
+        &crate::checkers::cwe_326::CWE_MODULE,
+        &crate::checkers::cwe_327::CWE_MODULE,
         &crate::checkers::cwe_332::CWE_MODULE,
+        &crate::checkers::cwe_337::CWE_MODULE,
+        &crate::checkers::cwe_362::CWE_MODULE,
         &crate::checkers::cwe_367::CWE_MODULE,
+        &crate::checkers::cwe_369::CWE_MODULE,
+        &crate::checkers::cwe_401::CWE_MODULE,
         &crate::checkers::cwe_426::CWE_MODULE,
+        &crate::checkers::cwe_457::CWE_MODULE,
         &crate::checkers::cwe_467::CWE_MODULE,
+        &crate::checkers::cwe_468::CWE_MODULE,
+        &crate::checkers::cwe_469::CWE_MODULE,
         &crate::checkers::cwe_476::CWE_MODULE,
+        &crate::checkers::cwe_477::CWE_MODULE,
+        &crate::checkers::cwe_479::CWE_MODULE,
+        &crate::checkers::cwe_532::CWE_MODULE,
         &crate::checkers::cwe_560::CWE_MODULE,
+        &crate::checkers::cwe_606::CWE_MODULE,
+        &crate::checkers::cwe_617::CWE_MODULE,
+        &crate::checkers::cwe_667::CWE_MODULE,
+        &crate::checkers::cwe_672::CWE_MODULE,
+        &crate::checkers::cwe_674::CWE_MODULE,
         &crate::checkers::cwe_676::CWE_MODULE,
+        &crate::checkers::cwe_685::CWE_MODULE,
+        &crate::checkers::cwe_690::CWE_MODULE,
+        &crate::checkers::cwe_732::CWE_MODULE,
+        &crate::checkers::cwe_761::CWE_MODULE,
+        &crate::checkers::cwe_770::CWE_MODULE,
+        &crate::checkers::cwe_775::CWE_MODULE,
         &crate::checkers::cwe_782::CWE_MODULE,
+        &crate::checkers::cwe_789::CWE_MODULE,
+        &crate::checkers::cwe_798::CWE_MODULE,
+        &crate::checkers::cwe_835::CWE_MODULE,
+        &crate::checkers::cwe_843::CWE_MODULE,
+        &crate::checkers::cwe_908::CWE_MODULE,
+        &crate::checkers::cwe_910::CWE_MODULE,
+        &crate::checkers::cwe_1335::CWE_MODULE,
+        &crate::checkers::cwe_1341::CWE_MODULE,
+        &crate::checkers::hardening::CWE_MODULE,
+        &crate::checkers::secret_hygiene::CWE_MODULE,
+        &crate::checkers::stack_integrity::CWE_MODULE,
         &crate::analysis::pointer_inference::CWE_MODULE,
     ]
 }
 
 /// A struct containing pointers to all known analysis results
 /// that may be needed as input for other analyses and CWE checks.
-#[derive(Clone, Copy)]
+///
+/// Expensive analyses are only computed on the first call to the corresponding getter
+/// and then cached for all subsequent calls.
+/// Analyses that need a configuration (like the pointer inference) are only available if configured.
 pub struct AnalysisResults<'a> {
     /// The content of the binary file
     pub binary: &'a [u8],
@@ -126,8 +187,16 @@ pub struct AnalysisResults<'a> {
     pub control_flow_graph: &'a Graph<'a>,
     /// A pointer to the project struct
     pub project: &'a Project,
+    /// The configuration of the pointer inference analysis if it should be available.
+    pointer_inference_config: Option<serde_json::Value>,
     /// The result of the pointer inference analysis if already computed.
-    pub pointer_inference: Option<&'a PointerInference<'a>>,
+    pointer_inference: OnceCell<Option<PointerInference<'a>>>,
+    /// The call graph of the program if already computed.
+    call_graph: OnceCell<CallGraph>,
+    /// The immediate dominators of the blocks of each function if already computed.
+    dominators: OnceCell<HashMap<Tid, HashMap<Tid, Tid>>>,
+    /// The def-use chains of the registers of each function if already computed.
+    def_use_chains: OnceCell<HashMap<Tid, DefUseChains<'a>>>,
 }
 
 impl<'a> AnalysisResults<'a> {
@@ -143,30 +212,91 @@ impl<'a> AnalysisResults<'a> {
             runtime_memory_image,
             control_flow_graph,
             project,
-            pointer_inference: None,
+            pointer_inference_config: None,
+            pointer_inference: OnceCell::new(),
+            call_graph: OnceCell::new(),
+            dominators: OnceCell::new(),
+            def_use_chains: OnceCell::new(),
+        }
+    }
+
+    /// Make the pointer inference analysis available with the given configuration.
+    /// The analysis is only computed on the first call to [`AnalysisResults::pointer_inference`].
+    pub fn with_pointer_inference_config(self, config: &serde_json::Value) -> AnalysisResults<'a> {
+        AnalysisResults {
+            pointer_inference_config: Some(config.clone()),
+            ..self
         }
     }
 
     /// Compute the pointer inference analysis.
     /// The result gets returned, but not saved to the `AnalysisResults` struct itself.
-    pub fn compute_pointer_inference(&'a self, config: &serde_json::Value) -> PointerInference<'a> {
+    pub fn compute_pointer_inference(&self, config: &serde_json::Value) -> PointerInference<'a> {
+        let mut config: crate::analysis::pointer_inference::Config =
+            crate::utils::parse_config(config).unwrap();
+        config.resolve_memory_pool_addresses(
+            self.binary,
+            self.project.program.term.address_base_offset,
+        );
         crate::analysis::pointer_inference::run(
             self.project,
             self.runtime_memory_image,
             self.control_flow_graph,
-            serde_json::from_value(config.clone()).unwrap(),
+            config,
             false,
         )
     }
 
-    /// Create a new `AnalysisResults` struct containing the given pointer inference analysis results.
-    pub fn set_pointer_inference<'b: 'a>(
-        self,
-        pi_results: Option<&'b PointerInference<'a>>,
-    ) -> AnalysisResults<'b> {
-        AnalysisResults {
-            pointer_inference: pi_results,
-            ..self
-        }
+    /// Get the results of the pointer inference analysis, computing them if necessary.
+    /// Returns `None` if the pointer inference analysis was not configured.
+    pub fn pointer_inference(&self) -> Option<&PointerInference<'a>> {
+        self.pointer_inference
+            .get_or_init(|| {
+                self.pointer_inference_config
+                    .as_ref()
+                    .map(|config| self.compute_pointer_inference(config))
+            })
+            .as_ref()
+    }
+
+    /// Get the call graph of the program, computing it if necessary.
+    pub fn call_graph(&self) -> &CallGraph {
+        self.call_graph
+            .get_or_init(|| CallGraph::new(&self.project.program))
+    }
+
+    /// Get the immediate dominators of the blocks of the given function, computing them if necessary.
+    /// Returns `None` if the function does not exist.
+    ///
+    /// See [`get_immediate_dominators`](crate::utils::graph_utils::get_immediate_dominators)
+    /// for the format of the result.
+    pub fn dominators(&self, sub_tid: &Tid) -> Option<&HashMap<Tid, Tid>> {
+        self.dominators
+            .get_or_init(|| {
+                self.project
+                    .program
+                    .term
+                    .subs
+                    .iter()
+                    .map(|sub| (sub.tid.clone(), get_immediate_dominators(sub)))
+                    .collect()
+            })
+            .get(sub_tid)
+    }
+
+    /// Get the def-use and use-def chains of the registers of the given function, computing them if necessary.
+    /// Returns `None` if the function does not exist.
+    pub fn def_use_chains(&self, sub_tid: &Tid) -> Option<&DefUseChains<'a>> {
+        self.def_use_chains
+            .get_or_init(|| {
+                self.project
+                    .program
+                    .term
+                    .subs
+                    .iter()
+                    .map(|sub| (sub.tid.clone(), DefUseChains::new(self.project, sub)))
+                    .collect()
+            })
+            .get(sub_tid)
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use super::{Expression, ExpressionType, RegisterProperties, Variable};
 use crate::intermediate_representation::Arg as IrArg;
@@ -364,6 +364,7 @@ impl From<Term<Sub>> for Term<IrSub> {
             term: IrSub {
                 name: sub.term.name,
                 blocks,
+                qualified_name: None,
             },
         }
     }
@@ -378,6 +379,8 @@ pub struct ExternSymbol {
     /// May be more than one, since we also identify thunk functions calling the extern symbol with the symbol itself.
     pub addresses: Vec<String>,
     /// The name of the extern symbol.
+    /// Symbols imported by ordinal are named after the lowercase library name and the ordinal,
+    /// e.g. `ws2_32.dll_Ordinal_23`.
     pub name: String,
     /// The calling convention used (as reported by Ghidra, i.e. this may not be correct).
     pub calling_convention: Option<String>,
@@ -476,19 +479,27 @@ impl Program {
 }
 
 /// A struct describing a calling convention.
+///
+/// Calling conventions are either exported by Ghidra
+/// or supplied by the user for CPU architectures where Ghidra's calling conventions are missing or wrong.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct CallingConvention {
     /// The name of the calling convention.
     #[serde(rename = "calling_convention")]
     pub name: String,
-    /// Possible parameter registers.
+    /// Possible parameter registers in the order of the arguments that they hold.
     parameter_register: Vec<String>,
     /// Possible return registers.
     return_register: Vec<String>,
     /// Callee-saved registers.
     unaffected_register: Vec<String>,
     /// Registers that may be overwritten by the call, i.e. caller-saved registers.
+    #[serde(default)]
     killed_by_call_register: Vec<String>,
+    /// The alignment of the stack pointer at calls in bytes.
+    /// Not exported by Ghidra, but may be given in user-supplied definitions.
+    #[serde(default)]
+    stack_alignment: Option<u64>,
 }
 
 impl From<CallingConvention> for IrCallingConvention {
@@ -498,6 +509,7 @@ impl From<CallingConvention> for IrCallingConvention {
             parameter_register: cconv.parameter_register,
             return_register: cconv.return_register,
             callee_saved_register: cconv.unaffected_register,
+            stack_alignment: cconv.stack_alignment,
         }
     }
 }
@@ -633,6 +645,50 @@ impl Project {
     }
 }
 
+impl Project {
+    /// Replace the calling conventions exported by Ghidra with the user-supplied definitions
+    /// for the CPU architecture of the project.
+    ///
+    /// The `definitions` map CPU architecture names to calling conventions.
+    /// A user-supplied calling convention replaces the calling convention with the same name exported by Ghidra.
+    /// Calling conventions without a counterpart exported by Ghidra are added.
+    /// This must be called before the project is converted to the internally used IR.
+    #[must_use]
+    pub fn override_calling_conventions(
+        &mut self,
+        definitions: &BTreeMap<String, Vec<CallingConvention>>,
+    ) -> Vec<LogMessage> {
+        let mut log_messages = Vec::new();
+        let user_conventions = match definitions.get(&self.cpu_architecture) {
+            Some(user_conventions) => user_conventions,
+            None => return log_messages,
+        };
+        for user_cconv in user_conventions {
+            match self
+                .register_calling_convention
+                .iter_mut()
+                .find(|cconv| cconv.name == user_cconv.name)
+            {
+                Some(cconv) => {
+                    *cconv = user_cconv.clone();
+                    log_messages.push(LogMessage::new_info(format!(
+                        "Replaced calling convention {} with user-supplied definition.",
+                        user_cconv.name
+                    )));
+                }
+                None => {
+                    self.register_calling_convention.push(user_cconv.clone());
+                    log_messages.push(LogMessage::new_info(format!(
+                        "Added user-supplied calling convention {}.",
+                        user_cconv.name
+                    )));
+                }
+            }
+        }
+        log_messages
+    }
+}
+
 impl Project {
     /// This function runs normalization passes to bring the project into a form
     /// that can be translated into the internally used intermediate representation.
@@ -869,3 +869,49 @@ fn from_project_to_ir_project() {
         expected_jmp
     );
 }
+
+#[test]
+fn override_calling_conventions() {
+    let mut project = Setup::new().project;
+    let definitions: BTreeMap<String, Vec<CallingConvention>> = serde_json::from_str(
+        r#"
+        {
+            "x86_64": [
+                {
+                    "calling_convention": "default",
+                    "parameter_register": ["RDI", "RSI"],
+                    "return_register": ["RAX"],
+                    "unaffected_register": ["RBX"],
+                    "stack_alignment": 16
+                },
+                {
+                    "calling_convention": "custom",
+                    "parameter_register": ["R8"],
+                    "return_register": ["R9"],
+                    "unaffected_register": []
+                }
+            ],
+            "ARM": []
+        }
+        "#,
+    )
+    .unwrap();
+    let logs = project.override_calling_conventions(&definitions);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(project.register_calling_convention.len(), 2);
+
+    let ir_project = project.into_ir_project(0x10000);
+    let default_cconv = &ir_project.calling_conventions[0];
+    assert_eq!(default_cconv.name, "default");
+    assert_eq!(default_cconv.parameter_register, vec!["RDI", "RSI"]);
+    assert_eq!(default_cconv.callee_saved_register, vec!["RBX"]);
+    assert_eq!(default_cconv.stack_alignment, Some(16));
+    assert_eq!(ir_project.calling_conventions[1].name, "custom");
+
+    // Definitions for other architectures are ignored.
+    let mut project = Setup::new().project;
+    project.cpu_architecture = "x86".to_string();
+    assert!(project
+        .override_calling_conventions(&definitions)
+        .is_empty());
+}
@@ -0,0 +1,143 @@
+//! Analysis profiles adapting the configuration to a class of binaries.
+//!
+//! The standard configuration targets binaries running on Linux or other POSIX systems.
+//! Binaries of other platforms (e.g. firmware running on a real-time operating system)
+//! use other functions for the same purposes, and some checks do not apply to them at all.
+//! A profile is a JSON object with the following fields:
+//! - `name`: The name of the profile.
+//! - `disabled_modules`: The checks that are not run by default when the profile is used.
+//! - `config`: Configuration entries that are merged into the configuration.
+//!   Objects are merged recursively. All other values replace the values in the configuration.
+//!
+//! Keys starting with `_comment` are ignored.
+
+use super::extern_semantics::remove_comments;
+use crate::prelude::*;
+use std::path::Path;
+
+/// The profiles bundled with the cwe_checker.
+const BUNDLED_PROFILES: [&str; 1] = [include_str!("../../../profiles/rtos.json")];
+
+/// An analysis profile.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AnalysisProfile {
+    /// The name of the profile.
+    pub name: String,
+    /// The checks that are not run by default when the profile is used.
+    #[serde(default)]
+    pub disabled_modules: Vec<String>,
+    /// Configuration entries that are merged into the configuration.
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+impl AnalysisProfile {
+    /// Parse the contents of a profile file.
+    pub fn parse(profile: &str) -> Result<AnalysisProfile, Error> {
+        let profile: serde_json::Value = serde_json::from_str(profile)?;
+        Ok(serde_json::from_value(remove_comments(profile))?)
+    }
+
+    /// Get the bundled profile with the given name or, if no such profile exists, read the profile file at the given path.
+    pub fn load(name_or_path: &str) -> Result<AnalysisProfile, Error> {
+        for profile in BUNDLED_PROFILES.iter() {
+            let profile = AnalysisProfile::parse(profile).expect("Invalid bundled profile");
+            if profile.name == name_or_path {
+                return Ok(profile);
+            }
+        }
+        let path = Path::new(name_or_path);
+        if !path.is_file() {
+            return Err(anyhow!(
+                "{} is neither a bundled profile nor a profile file",
+                name_or_path
+            ));
+        }
+        let profile = std::fs::read_to_string(path)
+            .map_err(|error| anyhow!("Could not read {}: {}", path.display(), error))?;
+        AnalysisProfile::parse(&profile).map_err(|error| anyhow!("{}: {}", path.display(), error))
+    }
+
+    /// Merge the configuration entries of the profile into the given configuration.
+    pub fn apply_to_config(&self, config: &mut serde_json::Value) {
+        merge_json(config, &self.config);
+    }
+
+    /// Check whether the check with the given name is disabled by the profile.
+    pub fn is_disabled(&self, module_name: &str) -> bool {
+        self.disabled_modules
+            .iter()
+            .any(|disabled_module| disabled_module == module_name)
+    }
+}
+
+/// Merge `update` into `target`.
+/// Objects are merged recursively. All other values of `update` replace the corresponding values of `target`.
+fn merge_json(target: &mut serde_json::Value, update: &serde_json::Value) {
+    match (target, update) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(update)) => {
+            for (key, value) in update {
+                merge_json(
+                    target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                    value,
+                );
+            }
+        }
+        (target, update) => *target = update.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bundled_profiles() {
+        let module_names: Vec<&str> = crate::get_modules()
+            .iter()
+            .map(|module| module.name)
+            .collect();
+        for profile in BUNDLED_PROFILES.iter() {
+            let profile = AnalysisProfile::parse(profile).unwrap();
+            for module in profile.disabled_modules.iter() {
+                assert!(module_names.contains(&module.as_str()), "{}", module);
+            }
+            assert_eq!(AnalysisProfile::load(&profile.name).unwrap(), profile);
+        }
+        assert!(AnalysisProfile::load("unknown_profile").is_err());
+    }
+
+    #[test]
+    fn config_merging() {
+        let profile = AnalysisProfile::parse(
+            &json!({
+                "_comment": "A profile for tests.",
+                "name": "mock",
+                "disabled_modules": ["CWE560"],
+                "config": {
+                    "Scoring": {"input_symbols": ["xQueueReceive"]},
+                    "New": {"_comment": "Added.", "value": 1}
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let mut config = json!({
+            "Scoring": {"_comment": "Kept.", "input_symbols": ["recv", "read"]},
+            "CWE560": {}
+        });
+        profile.apply_to_config(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "Scoring": {"_comment": "Kept.", "input_symbols": ["xQueueReceive"]},
+                "CWE560": {},
+                "New": {"value": 1}
+            })
+        );
+        assert!(profile.is_disabled("CWE560"));
+        assert!(!profile.is_disabled("CWE476"));
+    }
+}
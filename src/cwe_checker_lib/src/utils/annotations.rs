@@ -0,0 +1,217 @@
+//! Assertion annotations for regression tests of the analysis precision.
+//!
+//! Test binaries can state in which functions a check is expected to generate a warning
+//! by calling marker functions named `__cwe_checker_expect_warning_<CWE>`
+//! or `__cwe_checker_expect_no_warning_<CWE>`, e.g. `__cwe_checker_expect_warning_CWE416()`.
+//! The marker functions may either be defined in the binary itself or be extern symbols.
+//!
+//! Since the expected CWE is part of the name of the marker function,
+//! the markers can be recognized without evaluating parameters of the call,
+//! which works the same for all CPU architectures and calling conventions.
+
+use crate::intermediate_representation::*;
+use crate::utils::log::CweWarning;
+use std::collections::{HashMap, HashSet};
+
+/// Name prefix of marker functions annotating that a warning is expected in the calling function.
+pub const EXPECT_WARNING_PREFIX: &str = "__cwe_checker_expect_warning_";
+/// Name prefix of marker functions annotating that no warning is expected in the calling function.
+pub const EXPECT_NO_WARNING_PREFIX: &str = "__cwe_checker_expect_no_warning_";
+
+/// An assertion annotation found in the binary.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Annotation {
+    /// The name of the CWE that the annotation refers to, e.g. `CWE416`.
+    pub cwe: String,
+    /// Whether a warning is expected (`true`) or no warning is expected (`false`) in the annotated function.
+    pub expect_warning: bool,
+    /// The term ID of the annotated function.
+    pub sub_tid: Tid,
+    /// The name of the annotated function.
+    pub sub_name: String,
+    /// The term ID of the call to the marker function.
+    pub call_tid: Tid,
+}
+
+impl Annotation {
+    /// Generate the annotation corresponding to a call to the function with the given name
+    /// if the name is the name of a marker function.
+    fn from_marker_call(
+        marker_name: &str,
+        sub: &Term<Sub>,
+        call: &Term<Jmp>,
+    ) -> Option<Annotation> {
+        let (cwe, expect_warning) =
+            if let Some(cwe) = marker_name.strip_prefix(EXPECT_WARNING_PREFIX) {
+                (cwe, true)
+            } else if let Some(cwe) = marker_name.strip_prefix(EXPECT_NO_WARNING_PREFIX) {
+                (cwe, false)
+            } else {
+                return None;
+            };
+        if cwe.is_empty() {
+            return None;
+        }
+        Some(Annotation {
+            cwe: cwe.to_string(),
+            expect_warning,
+            sub_tid: sub.tid.clone(),
+            sub_name: sub.term.display_name().to_string(),
+            call_tid: call.tid.clone(),
+        })
+    }
+}
+
+/// Collect all assertion annotations contained in the program.
+pub fn get_annotations(project: &Project) -> Vec<Annotation> {
+    let program = &project.program.term;
+    let function_names: HashMap<&Tid, &str> = program
+        .extern_symbols
+        .iter()
+        .map(|symbol| (&symbol.tid, symbol.name.as_str()))
+        .chain(
+            program
+                .subs
+                .iter()
+                .map(|sub| (&sub.tid, sub.term.name.as_str())),
+        )
+        .collect();
+    let mut annotations = Vec::new();
+    for sub in program.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if let Some(annotation) = function_names
+                        .get(target)
+                        .and_then(|name| Annotation::from_marker_call(name, sub, jmp))
+                    {
+                        annotations.push(annotation);
+                    }
+                }
+            }
+        }
+    }
+    annotations
+}
+
+/// Check the given CWE warnings against the annotations
+/// and return a message for each annotation that is violated by the warnings.
+///
+/// A warning is considered to belong to a function
+/// if one of its term IDs or addresses belongs to a term of the function.
+/// The messages start with `[ANNOTATION FAILED]` followed by the name of the CWE in square brackets,
+/// so that test harnesses can easily filter for them.
+pub fn check_annotations(
+    project: &Project,
+    annotations: &[Annotation],
+    cwes: &[CweWarning],
+) -> Vec<String> {
+    let mut messages = Vec::new();
+    for annotation in annotations {
+        let locations = match project
+            .program
+            .term
+            .subs
+            .iter()
+            .find(|sub| sub.tid == annotation.sub_tid)
+        {
+            Some(sub) => get_locations_of_sub(sub),
+            None => continue,
+        };
+        let mut warnings_in_sub = cwes.iter().filter(|cwe| {
+            cwe.name == annotation.cwe
+                && (cwe.tids.iter().any(|tid| locations.contains(tid))
+                    || cwe
+                        .addresses
+                        .iter()
+                        .any(|address| locations.contains(address)))
+        });
+        match (annotation.expect_warning, warnings_in_sub.next()) {
+            (true, None) => messages.push(format!(
+                "[ANNOTATION FAILED][{}] Expected a warning in function {} (annotation at {})",
+                annotation.cwe, annotation.sub_name, annotation.call_tid.address
+            )),
+            (false, Some(warning)) => messages.push(format!(
+                "[ANNOTATION FAILED][{}] Unexpected warning in function {} (annotation at {}): {}",
+                annotation.cwe, annotation.sub_name, annotation.call_tid.address, warning
+            )),
+            _ => (),
+        }
+    }
+    messages
+}
+
+/// Get the string representations of the term IDs and the addresses of all terms contained in the function.
+fn get_locations_of_sub(sub: &Term<Sub>) -> HashSet<String> {
+    let mut locations = HashSet::new();
+    let mut add_tid = |tid: &Tid| {
+        locations.insert(format!("{}", tid));
+        if tid.address != "UNKNOWN" {
+            locations.insert(tid.address.clone());
+        }
+    };
+    for block in sub.term.blocks.iter() {
+        add_tid(&block.tid);
+        for def in block.term.defs.iter() {
+            add_tid(&def.tid);
+        }
+        for jmp in block.term.jmps.iter() {
+            add_tid(&jmp.tid);
+        }
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_sub_with_call(name: &str, call_address: &str, target: &str) -> Term<Sub> {
+        let mut call_tid = Tid::new(format!("call_{}", name));
+        call_tid.address = call_address.to_string();
+        let call = Term {
+            tid: call_tid,
+            term: Jmp::Call {
+                target: Tid::new(target),
+                return_: None,
+            },
+        };
+        let mut block = Blk::mock();
+        block.tid = Tid::new(format!("block_{}", name));
+        block.term.jmps.push(call);
+        let mut sub = Sub::mock(name);
+        sub.term.blocks.push(block);
+        sub
+    }
+
+    #[test]
+    fn annotation_checking() {
+        let mut project = Project::mock_empty();
+        let mut marker_symbol = ExternSymbol::mock();
+        marker_symbol.tid = Tid::new("expect_warning_symbol");
+        marker_symbol.name = "__cwe_checker_expect_warning_CWE416".to_string();
+        project.program.term.extern_symbols.push(marker_symbol);
+        let mut marker_sub = Sub::mock("__cwe_checker_expect_no_warning_CWE416");
+        marker_sub.tid = Tid::new("expect_no_warning_sub");
+        project.program.term.subs = vec![
+            mock_sub_with_call("bad", "0x10", "expect_warning_symbol"),
+            mock_sub_with_call("good", "0x20", "expect_no_warning_sub"),
+            marker_sub,
+        ];
+
+        let annotations = get_annotations(&project);
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].cwe, "CWE416");
+        assert!(annotations[0].expect_warning);
+        assert_eq!(annotations[1].sub_name, "good");
+        assert!(!annotations[1].expect_warning);
+
+        assert_eq!(check_annotations(&project, &annotations, &[]).len(), 1);
+        let warning_in_bad = CweWarning::new("CWE416", "0.1", "").addresses(vec!["0x10".into()]);
+        assert!(check_annotations(&project, &annotations, &[warning_in_bad]).is_empty());
+        let warning_in_good = CweWarning::new("CWE416", "0.1", "").tids(vec!["call_good".into()]);
+        let messages = check_annotations(&project, &annotations, &[warning_in_good]);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].starts_with("[ANNOTATION FAILED][CWE416] Unexpected warning"));
+    }
+}
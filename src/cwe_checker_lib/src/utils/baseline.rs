@@ -0,0 +1,156 @@
+//! Lifecycle metadata of findings and the versioned schema of saved reports.
+//!
+//! Each CWE warning is identified across runs by a stable finding ID
+//! computed from the name of the check together with the addresses and term IDs of the warning.
+//! When the warnings of a run are merged with a baseline (i.e. a report saved by an earlier run),
+//! the time when a finding was first seen and its free-form triage state are taken over from the baseline.
+//! This way the triage state of findings can be kept in a saved report under version control.
+//!
+//! Saved reports are JSON objects containing the schema version and the list of warnings.
+//! Reports saved before the schema was versioned are plain lists of warnings.
+
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use std::collections::HashMap;
+
+/// The version of the JSON report schema written by this version of the cwe_checker.
+///
+/// Version 1 added the lifecycle metadata (`finding_id`, `first_seen`, `last_seen` and `triage_state`) of warnings.
+/// Unversioned reports (plain lists of warnings) are treated as version 0.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// A saved report together with the version of its schema.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct VersionedReport {
+    /// The version of the report schema.
+    pub schema_version: u64,
+    /// The CWE warnings of the report.
+    pub warnings: Vec<CweWarning>,
+}
+
+/// Parse a saved JSON report.
+///
+/// Accepts both versioned reports and unversioned plain lists of warnings.
+/// Returns an error for reports with a newer schema version than [`SCHEMA_VERSION`].
+pub fn parse_report(json: &str) -> Result<Vec<CweWarning>, Error> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    if value.is_array() {
+        return Ok(serde_json::from_value(value)?);
+    }
+    let report: VersionedReport = serde_json::from_value(value)?;
+    if report.schema_version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Unsupported report schema version {} (supported up to version {})",
+            report.schema_version,
+            SCHEMA_VERSION
+        ));
+    }
+    Ok(report.warnings)
+}
+
+/// Compute the stable finding ID of a warning.
+///
+/// The ID consists of the name of the check and a 64-bit FNV-1a hash
+/// of the addresses and term IDs of the warning.
+/// Other fields like the version of the check or the description are ignored,
+/// so that changes to the checks do not change the IDs of their findings.
+pub fn get_finding_id(cwe: &CweWarning) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    // Null bytes separate the fields and the two lists,
+    // so that different lists of addresses and term IDs cannot yield the same byte sequence.
+    let addresses = cwe.addresses.iter().map(String::as_bytes);
+    let tids = cwe.tids.iter().map(String::as_bytes);
+    for field in addresses.chain(std::iter::once(&[][..])).chain(tids) {
+        for byte in field.iter().chain(std::iter::once(&0)) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{}-{:016x}", cwe.name, hash)
+}
+
+/// Set the finding ID of each warning.
+pub fn assign_finding_ids(cwes: &mut [CweWarning]) {
+    for cwe in cwes.iter_mut() {
+        cwe.finding_id = Some(get_finding_id(cwe));
+    }
+}
+
+/// Merge the lifecycle metadata of the findings in the baseline into the current warnings.
+///
+/// All current warnings are marked as last seen at `timestamp` (in seconds since the Unix epoch).
+/// For findings contained in the baseline the first-seen timestamp is taken over from the baseline,
+/// for new findings it is set to `timestamp`.
+/// The triage state of a finding is taken over from the baseline unless the current warning already has one.
+/// Findings of the baseline that are not contained in the current warnings are dropped.
+pub fn merge_with_baseline(cwes: &mut [CweWarning], baseline: &[CweWarning], timestamp: u64) {
+    let baseline_findings: HashMap<String, &CweWarning> = baseline
+        .iter()
+        .map(|cwe| (get_finding_id(cwe), cwe))
+        .collect();
+    for cwe in cwes.iter_mut() {
+        let finding_id = get_finding_id(cwe);
+        let baseline_finding = baseline_findings.get(&finding_id);
+        cwe.first_seen = baseline_finding
+            .and_then(|finding| finding.first_seen.or(finding.last_seen))
+            .or(Some(timestamp));
+        cwe.last_seen = Some(timestamp);
+        if cwe.triage_state.is_none() {
+            cwe.triage_state = baseline_finding.and_then(|finding| finding.triage_state.clone());
+        }
+        cwe.finding_id = Some(finding_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline_merge() {
+        let mut triaged = CweWarning::new("CWE476", "0.3", "a").addresses(vec!["00101234".into()]);
+        triaged.first_seen = Some(100);
+        triaged.last_seen = Some(200);
+        triaged.triage_state = Some("false_positive".to_string());
+        let baseline = vec![
+            triaged,
+            CweWarning::new("CWE416", "0.3", "b").addresses(vec!["00105678".into()]),
+        ];
+        let mut current = vec![
+            CweWarning::new("CWE476", "0.4", "changed").addresses(vec!["00101234".into()]),
+            CweWarning::new("CWE190", "0.1", "c").addresses(vec!["00109abc".into()]),
+        ];
+        merge_with_baseline(&mut current, &baseline, 300);
+        assert_eq!(current[0].finding_id, Some(get_finding_id(&baseline[0])));
+        assert_eq!(current[0].first_seen, Some(100));
+        assert_eq!(current[0].last_seen, Some(300));
+        assert_eq!(current[0].triage_state.as_deref(), Some("false_positive"));
+        assert_eq!(current[1].first_seen, Some(300));
+        assert_eq!(current[1].triage_state, None);
+        assert!(current[1]
+            .finding_id
+            .as_ref()
+            .unwrap()
+            .starts_with("CWE190-"));
+
+        // The lifecycle metadata round-trips through saved reports of both schema versions.
+        let versioned = serde_json::to_string(&VersionedReport {
+            schema_version: SCHEMA_VERSION,
+            warnings: current.clone(),
+        })
+        .unwrap();
+        assert_eq!(parse_report(&versioned).unwrap(), current);
+        let unversioned = serde_json::to_string(&current).unwrap();
+        assert_eq!(parse_report(&unversioned).unwrap(), current);
+        assert!(parse_report(r#"{"schema_version": 99, "warnings": []}"#).is_err());
+    }
+
+    #[test]
+    fn finding_ids_ignore_fields_other_than_locations() {
+        let cwe = CweWarning::new("CWE476", "0.3", "a").addresses(vec!["0010".into()]);
+        let changed = CweWarning::new("CWE476", "0.4", "b").addresses(vec!["0010".into()]);
+        assert_eq!(get_finding_id(&cwe), get_finding_id(&changed));
+        let moved = CweWarning::new("CWE476", "0.3", "a").tids(vec!["0010".into()]);
+        assert_ne!(get_finding_id(&cwe), get_finding_id(&moved));
+    }
+}
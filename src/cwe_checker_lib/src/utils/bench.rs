@@ -0,0 +1,222 @@
+//! Regression comparison of the analysis results for a corpus of binaries.
+//!
+//! The reports of a run over the corpus are compared with the reports of an earlier run (the baseline),
+//! so that the effects of changes to the analyses can be validated on many binaries at once.
+//! The report of each binary is saved as `<binary name>.json` in the report directory of a run.
+//! The runtimes of the analyses of the binaries are saved in the file [`RUNTIMES_FILE`] in the same directory,
+//! so that the report directory of a run can be used as the baseline of a later run.
+
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use crate::utils::report::{diff_reports, ReportDiff};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The name of the file in a report directory that maps binary names to the runtimes of their analyses in seconds.
+pub const RUNTIMES_FILE: &str = "runtimes.json";
+
+/// Get the path of the report of the binary with the given name in the given report directory.
+pub fn get_report_path(report_directory: &Path, binary_name: &str) -> PathBuf {
+    report_directory.join(format!("{}.json", binary_name))
+}
+
+/// Read the runtimes saved in the given report directory.
+/// Returns an empty map if the directory contains no runtimes file.
+pub fn read_runtimes(report_directory: &Path) -> Result<BTreeMap<String, f64>, Error> {
+    let path = report_directory.join(RUNTIMES_FILE);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Write the runtimes into the given report directory.
+pub fn write_runtimes(
+    report_directory: &Path,
+    runtimes: &BTreeMap<String, f64>,
+) -> Result<(), Error> {
+    std::fs::write(
+        report_directory.join(RUNTIMES_FILE),
+        serde_json::to_string_pretty(runtimes)?,
+    )?;
+    Ok(())
+}
+
+/// The comparison of the analysis of one binary with its baseline.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct BinaryComparison {
+    /// The name of the binary.
+    pub binary: String,
+    /// The differences between the baseline report and the new report.
+    /// `None` if there is no baseline report for the binary or the analysis failed.
+    pub diff: Option<ReportDiff>,
+    /// The runtime of the analysis in seconds.
+    pub runtime: f64,
+    /// The runtime of the analysis of the baseline in seconds, if known.
+    pub baseline_runtime: Option<f64>,
+    /// The error message if the analysis of the binary failed.
+    pub error: Option<String>,
+}
+
+impl BinaryComparison {
+    /// Compare the new report of a binary with its baseline report.
+    pub fn new(
+        binary: &str,
+        baseline_report: Option<&[CweWarning]>,
+        new_report: &[CweWarning],
+        runtime: f64,
+        baseline_runtime: Option<f64>,
+    ) -> BinaryComparison {
+        BinaryComparison {
+            binary: binary.to_string(),
+            diff: baseline_report.map(|baseline| diff_reports(baseline, new_report)),
+            runtime,
+            baseline_runtime,
+            error: None,
+        }
+    }
+
+    /// Record a failed analysis of a binary.
+    pub fn failed(
+        binary: &str,
+        error: String,
+        runtime: f64,
+        baseline_runtime: Option<f64>,
+    ) -> BinaryComparison {
+        BinaryComparison {
+            binary: binary.to_string(),
+            diff: None,
+            runtime,
+            baseline_runtime,
+            error: Some(error),
+        }
+    }
+
+    /// Get the difference between the runtime and the baseline runtime in seconds, if the baseline runtime is known.
+    pub fn runtime_delta(&self) -> Option<f64> {
+        self.baseline_runtime
+            .map(|baseline_runtime| self.runtime - baseline_runtime)
+    }
+}
+
+impl std::fmt::Display for BinaryComparison {
+    /// Print one line with the number of added and removed findings and the runtime (delta).
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}: ", self.binary)?;
+        match (&self.error, &self.diff) {
+            (Some(error), _) => write!(formatter, "analysis failed ({})", error)?,
+            (None, Some(diff)) => write!(
+                formatter,
+                "{} added, {} removed",
+                diff.added.len(),
+                diff.removed.len()
+            )?,
+            (None, None) => write!(formatter, "no baseline report")?,
+        }
+        write!(formatter, ", runtime {:.1}s", self.runtime)?;
+        if let Some(delta) = self.runtime_delta() {
+            write!(formatter, " ({:+.1}s)", delta)?;
+        }
+        Ok(())
+    }
+}
+
+/// The comparison of the analyses of all binaries of a corpus with their baselines.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct BenchSummary {
+    /// The comparisons for the individual binaries.
+    pub binaries: Vec<BinaryComparison>,
+}
+
+impl std::fmt::Display for BenchSummary {
+    /// Print the added and removed findings of each binary followed by one summary line per binary
+    /// and the totals over the corpus.
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for comparison in self.binaries.iter() {
+            if let Some(diff) = &comparison.diff {
+                for cwe in diff.added.iter() {
+                    writeln!(formatter, "[{}] + {}", comparison.binary, cwe)?;
+                }
+                for cwe in diff.removed.iter() {
+                    writeln!(formatter, "[{}] - {}", comparison.binary, cwe)?;
+                }
+            }
+        }
+        for comparison in self.binaries.iter() {
+            writeln!(formatter, "{}", comparison)?;
+        }
+        let diffs = self
+            .binaries
+            .iter()
+            .filter_map(|comparison| comparison.diff.as_ref());
+        let (added, removed) = diffs.fold((0, 0), |(added, removed), diff| {
+            (added + diff.added.len(), removed + diff.removed.len())
+        });
+        let failed = self
+            .binaries
+            .iter()
+            .filter(|comparison| comparison.error.is_some())
+            .count();
+        let runtime: f64 = self
+            .binaries
+            .iter()
+            .map(|comparison| comparison.runtime)
+            .sum();
+        write!(
+            formatter,
+            "Total: {} binaries ({} failed), {} added, {} removed, runtime {:.1}s",
+            self.binaries.len(),
+            failed,
+            added,
+            removed,
+            runtime
+        )?;
+        let deltas: Vec<f64> = self
+            .binaries
+            .iter()
+            .filter_map(|comparison| comparison.runtime_delta())
+            .collect();
+        if !deltas.is_empty() {
+            write!(formatter, " ({:+.1}s)", deltas.iter().sum::<f64>())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_comparison() {
+        let baseline = vec![
+            CweWarning::new("CWE476", "0.3", "a").addresses(vec!["00101234".into()]),
+            CweWarning::new("CWE416", "0.3", "b").addresses(vec!["00105678".into()]),
+        ];
+        let new = vec![
+            CweWarning::new("CWE476", "0.3", "a").addresses(vec!["00101234".into()]),
+            CweWarning::new("CWE908", "0.1", "c").addresses(vec!["00109abc".into()]),
+        ];
+        let comparison = BinaryComparison::new("foo", Some(&baseline), &new, 12.5, Some(10.0));
+        assert_eq!(comparison.runtime_delta(), Some(2.5));
+        assert_eq!(
+            format!("{}", comparison),
+            "foo: 1 added, 1 removed, runtime 12.5s (+2.5s)"
+        );
+        let unknown = BinaryComparison::new("bar", None, &new, 1.0, None);
+        assert_eq!(
+            format!("{}", unknown),
+            "bar: no baseline report, runtime 1.0s"
+        );
+        let failed = BinaryComparison::failed("baz", "exit code 101".into(), 2.0, Some(3.0));
+
+        let summary = BenchSummary {
+            binaries: vec![comparison, unknown, failed],
+        };
+        let output = format!("{}", summary);
+        assert!(output.starts_with("[foo] + "));
+        assert!(output.contains("baz: analysis failed (exit code 101), runtime 2.0s (-1.0s)\n"));
+        assert!(output
+            .ends_with("Total: 3 binaries (1 failed), 1 added, 1 removed, runtime 15.5s (+1.5s)"));
+    }
+}
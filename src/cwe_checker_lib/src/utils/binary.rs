@@ -6,12 +6,18 @@ use crate::prelude::*;
 use goblin::elf;
 use goblin::pe;
 use goblin::Object;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryInto;
 
 /// A representation of the runtime image of a binary after being loaded into memory by the loader.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct RuntimeMemoryImage {
     memory_segments: Vec<MemorySegment>,
     is_little_endian: bool,
+    /// Values of writeable global memory that are known after the global initialization phase of the program,
+    /// i.e. after all constructors have run.
+    /// Maps addresses to the values stored there.
+    initialized_global_values: BTreeMap<u64, Bitvector>,
 }
 
 /// A continuous segment in the memory image.
@@ -90,6 +96,7 @@ impl RuntimeMemoryImage {
                 Ok(RuntimeMemoryImage {
                     memory_segments,
                     is_little_endian: elf_file.header.endianness().unwrap().is_little(),
+                    initialized_global_values: BTreeMap::new(),
                 })
             }
             Object::PE(pe_file) => {
@@ -106,6 +113,7 @@ impl RuntimeMemoryImage {
                 let mut memory_image = RuntimeMemoryImage {
                     memory_segments,
                     is_little_endian: true,
+                    initialized_global_values: BTreeMap::new(),
                 };
                 memory_image.add_global_memory_offset(pe_file.image_base as u64);
                 Ok(memory_image)
@@ -127,6 +135,20 @@ impl RuntimeMemoryImage {
         for segment in self.memory_segments.iter_mut() {
             segment.base_address += offset;
         }
+        self.initialized_global_values = self
+            .initialized_global_values
+            .iter()
+            .map(|(address, value)| (address + offset, value.clone()))
+            .collect();
+    }
+
+    /// Add a value of writeable global memory that is known after the global initialization phase of the program.
+    ///
+    /// Reads of exactly this value (i.e. same address and size) from the memory image will return it,
+    /// even though the containing segment is writeable.
+    /// Thus the value should only be added if it is not expected to change after the initialization phase.
+    pub fn add_initialized_global_value(&mut self, address: u64, value: Bitvector) {
+        self.initialized_global_values.insert(address, value);
     }
 
     /// Read the contents of the memory image at the given address
@@ -136,6 +158,7 @@ impl RuntimeMemoryImage {
     /// i.e. values are interpreted with the endianness of the CPU architecture.
     /// If the address points to a writeable segment, the returned value is a `Ok(None)` value,
     /// since the data may change during program execution.
+    /// The exception are values added through [`add_initialized_global_value`](RuntimeMemoryImage::add_initialized_global_value).
     ///
     /// Returns an error if the address is not contained in the global data address range.
     pub fn read(&self, address: &Bitvector, size: ByteSize) -> Result<Option<Bitvector>, Error> {
@@ -146,8 +169,13 @@ impl RuntimeMemoryImage {
                 && address <= segment.base_address + segment.bytes.len() as u64 - u64::from(size)
             {
                 if segment.write_flag {
-                    // The segment is writeable, thus we do not know the content at runtime.
-                    return Ok(None);
+                    // The segment is writeable, thus we do not know the content at runtime
+                    // unless the value was set during the global initialization phase.
+                    return Ok(self
+                        .initialized_global_values
+                        .get(&address)
+                        .filter(|value| ByteSize::from(value.width()) == size)
+                        .cloned());
                 }
                 let index = (address - segment.base_address) as usize;
                 let mut bytes = segment.bytes[index..index + u64::from(size) as usize].to_vec();
@@ -167,16 +195,39 @@ impl RuntimeMemoryImage {
         Err(anyhow!("Address is not a valid global memory address."))
     }
 
+    /// Read the bytes of the memory image as they are after loading the binary,
+    /// i.e. before any code of the program was executed.
+    ///
+    /// In contrast to [`read`](RuntimeMemoryImage::read) this also returns the contents of writeable segments.
+    /// Returns an error if the given range is not fully contained in one memory segment.
+    pub fn read_loaded_bytes(&self, address: u64, length: u64) -> Result<&[u8], Error> {
+        for segment in self.memory_segments.iter() {
+            if address >= segment.base_address
+                && address - segment.base_address + length <= segment.bytes.len() as u64
+            {
+                let index = (address - segment.base_address) as usize;
+                return Ok(&segment.bytes[index..index + length as usize]);
+            }
+        }
+        Err(anyhow!("Address is not a valid global memory address."))
+    }
+
     /// Read the contents of memory from a given address onwards until a null byte is reached and checks whether the
     /// content is a valid UTF8 string.
     pub fn read_string_until_null_terminator(&self, address: &Bitvector) -> Result<&str, Error> {
         let address = address.try_to_u64().unwrap();
         for segment in self.memory_segments.iter() {
             if address >= segment.base_address
-                && address <= segment.base_address + segment.bytes.len() as u64
+                && address < segment.base_address + segment.bytes.len() as u64
             {
                 let index = (address - segment.base_address) as usize;
-                let c_str = std::ffi::CStr::from_bytes_with_nul(&segment.bytes[index..])?;
+                let null_terminator_index = segment.bytes[index..]
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .ok_or_else(|| anyhow!("String is not null-terminated."))?;
+                let c_str = std::ffi::CStr::from_bytes_with_nul(
+                    &segment.bytes[index..=index + null_terminator_index],
+                )?;
                 return Ok(c_str.to_str()?);
             }
         }
@@ -270,6 +321,256 @@ impl RuntimeMemoryImage {
     }
 }
 
+/// Return the address of the symbol with the given name from the symbol table of an ELF binary.
+///
+/// Returns an error if the binary is not an ELF file or does not contain the symbol.
+pub fn get_elf_symbol_address(binary: &[u8], symbol_name: &str) -> Result<u64, Error> {
+    match Object::parse(binary)? {
+        Object::Elf(elf_file) => {
+            let symbol_tables = [
+                (&elf_file.syms, &elf_file.strtab),
+                (&elf_file.dynsyms, &elf_file.dynstrtab),
+            ];
+            for (symbol_table, string_table) in symbol_tables.iter() {
+                for symbol in symbol_table.iter() {
+                    if let Some(Ok(name)) = string_table.get(symbol.st_name) {
+                        if name == symbol_name && symbol.st_value != 0 {
+                            return Ok(symbol.st_value);
+                        }
+                    }
+                }
+            }
+            Err(anyhow!("Symbol {} not found.", symbol_name))
+        }
+        _ => Err(anyhow!("Symbol lookup is only supported for ELF files.")),
+    }
+}
+
+/// Return the names, addresses and sizes of all data objects in the symbol tables of an ELF binary.
+///
+/// Returns an error if the binary is not an ELF file.
+pub fn get_elf_object_symbols(binary: &[u8]) -> Result<Vec<(String, u64, u64)>, Error> {
+    match Object::parse(binary)? {
+        Object::Elf(elf_file) => {
+            let symbol_tables = [
+                (&elf_file.syms, &elf_file.strtab),
+                (&elf_file.dynsyms, &elf_file.dynstrtab),
+            ];
+            let mut objects = Vec::new();
+            for (symbol_table, string_table) in symbol_tables.iter() {
+                for symbol in symbol_table.iter() {
+                    if symbol.st_type() != elf::sym::STT_OBJECT || symbol.st_value == 0 {
+                        continue;
+                    }
+                    if let Some(Ok(name)) = string_table.get(symbol.st_name) {
+                        objects.push((name.to_string(), symbol.st_value, symbol.st_size));
+                    }
+                }
+            }
+            Ok(objects)
+        }
+        _ => Err(anyhow!("Symbol lookup is only supported for ELF files.")),
+    }
+}
+
+/// The type of the GNU property note.
+const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+/// The property type of the AArch64 feature bits in the GNU property note.
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+/// The feature bit marking the binary as compatible with branch target identification.
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 1;
+/// The feature bit marking the binary as compatible with pointer authentication.
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 2;
+/// The type of the Android memtag note.
+const NT_ANDROID_TYPE_MEMTAG: u32 = 4;
+/// The mask of the memory tagging mode in the Android memtag note.
+/// The bits above the mode mark whether heap and stack tagging are enabled.
+const NT_MEMTAG_MASK: u32 = 0b1111;
+
+/// The properties of an ELF binary that determine which exploit mitigations it uses.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ElfSecurityProperties {
+    /// The flags of the `PT_GNU_STACK` program header, if the binary contains one.
+    pub gnu_stack_flags: Option<u32>,
+    /// Whether the binary contains a `PT_GNU_RELRO` program header.
+    pub has_relro_segment: bool,
+    /// Whether the dynamic linker resolves all symbols when loading the binary (`BIND_NOW`).
+    pub binds_now: bool,
+    /// Whether the binary is position independent, i.e. of type `ET_DYN`.
+    pub is_position_independent: bool,
+    /// The names of all symbols in the symbol tables of the binary.
+    pub symbol_names: BTreeSet<String>,
+    /// The AArch64 feature bits of the GNU property note (generated by `-mbranch-protection`).
+    /// Zero if the binary contains no such property.
+    pub aarch64_feature_flags: u32,
+    /// Whether the Android memtag note of the binary enables memory tagging.
+    pub uses_memory_tagging: bool,
+}
+
+/// Read a 32-bit integer with the given endianness from the start of the byte slice.
+fn read_u32(bytes: &[u8], little_endian: bool) -> Option<u32> {
+    let bytes: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    if little_endian {
+        Some(u32::from_le_bytes(bytes))
+    } else {
+        Some(u32::from_be_bytes(bytes))
+    }
+}
+
+/// Get the AArch64 feature bits contained in the content of a GNU property note.
+///
+/// The content is an array of properties, each consisting of its type, the size of its data and the data itself.
+/// Properties are padded to the given alignment (8 bytes on 64-bit targets).
+fn parse_gnu_property_note(desc: &[u8], little_endian: bool, alignment: usize) -> u32 {
+    let mut feature_flags = 0;
+    let mut offset = 0;
+    while let (Some(property_type), Some(data_size)) = (
+        desc.get(offset..)
+            .and_then(|bytes| read_u32(bytes, little_endian)),
+        desc.get(offset + 4..)
+            .and_then(|bytes| read_u32(bytes, little_endian)),
+    ) {
+        let data_start = offset + 8;
+        let data_size = data_size as usize;
+        if property_type == GNU_PROPERTY_AARCH64_FEATURE_1_AND {
+            if let Some(features) = desc
+                .get(data_start..data_start + data_size)
+                .and_then(|data| read_u32(data, little_endian))
+            {
+                feature_flags |= features;
+            }
+        }
+        offset = data_start + data_size.div_ceil(alignment) * alignment;
+    }
+    feature_flags
+}
+
+/// Return the properties of an ELF binary that determine which exploit mitigations it uses.
+///
+/// Returns an error if the binary is not an ELF file.
+pub fn get_elf_security_properties(binary: &[u8]) -> Result<ElfSecurityProperties, Error> {
+    match Object::parse(binary)? {
+        Object::Elf(elf_file) => {
+            let mut properties = ElfSecurityProperties {
+                is_position_independent: elf_file.header.e_type == elf::header::ET_DYN,
+                ..Default::default()
+            };
+            for header in elf_file.program_headers.iter() {
+                match header.p_type {
+                    elf::program_header::PT_GNU_STACK => {
+                        properties.gnu_stack_flags = Some(header.p_flags)
+                    }
+                    elf::program_header::PT_GNU_RELRO => properties.has_relro_segment = true,
+                    _ => (),
+                }
+            }
+            if let Some(dynamic) = &elf_file.dynamic {
+                properties.binds_now = dynamic.dyns.iter().any(|entry| match entry.d_tag {
+                    elf::dynamic::DT_BIND_NOW => true,
+                    elf::dynamic::DT_FLAGS => entry.d_val & elf::dynamic::DF_BIND_NOW != 0,
+                    elf::dynamic::DT_FLAGS_1 => entry.d_val & elf::dynamic::DF_1_NOW != 0,
+                    _ => false,
+                });
+            }
+            let symbol_tables = [
+                (&elf_file.syms, &elf_file.strtab),
+                (&elf_file.dynsyms, &elf_file.dynstrtab),
+            ];
+            for (symbol_table, string_table) in symbol_tables.iter() {
+                for symbol in symbol_table.iter() {
+                    if let Some(Ok(name)) = string_table.get(symbol.st_name) {
+                        if !name.is_empty() {
+                            properties.symbol_names.insert(name.to_string());
+                        }
+                    }
+                }
+            }
+            let alignment = if elf_file.is_64 { 8 } else { 4 };
+            for note in elf_file.iter_note_headers(binary).into_iter().flatten() {
+                let note = match note {
+                    Ok(note) => note,
+                    Err(_) => continue,
+                };
+                match (note.name, note.n_type) {
+                    ("GNU", NT_GNU_PROPERTY_TYPE_0) => {
+                        properties.aarch64_feature_flags |=
+                            parse_gnu_property_note(note.desc, elf_file.little_endian, alignment)
+                    }
+                    ("Android", NT_ANDROID_TYPE_MEMTAG) => {
+                        if let Some(mode) = read_u32(note.desc, elf_file.little_endian) {
+                            properties.uses_memory_tagging |= mode & NT_MEMTAG_MASK != 0;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            Ok(properties)
+        }
+        _ => Err(anyhow!(
+            "Security properties are only supported for ELF files."
+        )),
+    }
+}
+
+/// Return the function addresses contained in the `.init_array` and `.fini_array` sections of an ELF binary
+/// in the order in which they are executed by the loader.
+///
+/// Entries that are only filled in by relative relocations at load time are resolved using the relocation addends.
+/// Returns an error if the binary is not an ELF file.
+pub fn get_elf_init_and_fini_arrays(binary: &[u8]) -> Result<(Vec<u64>, Vec<u64>), Error> {
+    let elf_file = match Object::parse(binary)? {
+        Object::Elf(elf_file) => elf_file,
+        _ => {
+            return Err(anyhow!(
+                "Constructor lookup is only supported for ELF files."
+            ))
+        }
+    };
+    let pointer_size: usize = if elf_file.is_64 { 8 } else { 4 };
+    let relocation_addends: BTreeMap<u64, i64> = elf_file
+        .dynrelas
+        .iter()
+        .filter_map(|reloc| Some((reloc.r_offset, reloc.r_addend?)))
+        .collect();
+    let mut init_array = Vec::new();
+    let mut fini_array = Vec::new();
+    for section_header in elf_file.section_headers.iter() {
+        let function_array = match elf_file.shdr_strtab.get(section_header.sh_name) {
+            Some(Ok(".init_array")) => &mut init_array,
+            Some(Ok(".fini_array")) => &mut fini_array,
+            _ => continue,
+        };
+        let start = section_header.sh_offset as usize;
+        let end = start + section_header.sh_size as usize;
+        let section_bytes = binary
+            .get(start..end)
+            .ok_or_else(|| anyhow!("Section out of bounds of the binary."))?;
+        for (index, entry) in section_bytes.chunks_exact(pointer_size).enumerate() {
+            let mut value: u64 = 0;
+            if elf_file.little_endian {
+                for byte in entry.iter().rev() {
+                    value = (value << 8) | u64::from(*byte);
+                }
+            } else {
+                for byte in entry.iter() {
+                    value = (value << 8) | u64::from(*byte);
+                }
+            }
+            if value == 0 {
+                let entry_address = section_header.sh_addr + (index * pointer_size) as u64;
+                if let Some(addend) = relocation_addends.get(&entry_address) {
+                    value = *addend as u64;
+                }
+            }
+            let is_terminator = value == 0 || (pointer_size == 4 && value == 0xffff_ffff);
+            if !is_terminator && value != u64::MAX {
+                function_array.push(value);
+            }
+        }
+    }
+    Ok((init_array, fini_array))
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -296,7 +597,7 @@ pub mod tests {
                     MemorySegment {
                         bytes: [
                             0x01, 0x02, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x57, 0x6f, 0x72, 0x6c,
-                            0x64, 0x00,
+                            0x64, 0x00, 0x41, 0x42, 0x00,
                         ]
                         .to_vec(),
                         base_address: 0x3000,
@@ -306,6 +607,7 @@ pub mod tests {
                     },
                 ],
                 is_little_endian: true,
+                initialized_global_values: BTreeMap::new(),
             }
         }
     }
@@ -325,6 +627,26 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn read_initialized_global_value() {
+        let mut mem_image = RuntimeMemoryImage::mock();
+        let address = Bitvector::from_u32(0x2000);
+        assert_eq!(mem_image.read(&address, ByteSize::new(4)).unwrap(), None);
+        mem_image.add_initialized_global_value(0x2000, Bitvector::from_u32(42));
+        assert_eq!(
+            mem_image.read(&address, ByteSize::new(4)).unwrap(),
+            Some(Bitvector::from_u32(42))
+        );
+        // Reads with a different size are not answered by the initialized value.
+        assert_eq!(mem_image.read(&address, ByteSize::new(8)).unwrap(), None);
+        mem_image.add_global_memory_offset(0x1000);
+        let address = Bitvector::from_u32(0x3000);
+        assert_eq!(
+            mem_image.read(&address, ByteSize::new(4)).unwrap(),
+            Some(Bitvector::from_u32(42))
+        );
+    }
+
     #[test]
     fn ro_data_pointer() {
         let mem_image = RuntimeMemoryImage::mock();
@@ -334,6 +656,34 @@ pub mod tests {
         assert_eq!(&slice[index..], &[0xb2u8, 0xb3, 0xb4]);
     }
 
+    #[test]
+    fn gnu_property_note() {
+        // An unrelated property followed by the AArch64 feature property with BTI and PAC set.
+        let mut desc = vec![
+            0x02, 0x00, 0x00, 0xc0, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        desc.extend_from_slice(&[
+            0x00, 0x00, 0x00, 0xc0, 0x04, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ]);
+        assert_eq!(
+            parse_gnu_property_note(&desc, true, 8),
+            GNU_PROPERTY_AARCH64_FEATURE_1_BTI | GNU_PROPERTY_AARCH64_FEATURE_1_PAC
+        );
+        // Only BTI, big endian.
+        let desc = [
+            0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        assert_eq!(
+            parse_gnu_property_note(&desc, false, 8),
+            GNU_PROPERTY_AARCH64_FEATURE_1_BTI
+        );
+        // Truncated notes are ignored.
+        assert_eq!(parse_gnu_property_note(&desc[..10], false, 8), 0);
+    }
+
     #[test]
     fn test_read_string_until_null_terminator() {
         let mem_image = RuntimeMemoryImage::mock();
@@ -347,5 +697,13 @@ pub mod tests {
                 .read_string_until_null_terminator(&address)
                 .unwrap(),
         );
+        // Strings followed by other data in the same segment are also read correctly.
+        let address = Bitvector::from_u32(0x300e);
+        assert_eq!(
+            "AB",
+            mem_image
+                .read_string_until_null_terminator(&address)
+                .unwrap()
+        );
     }
 }
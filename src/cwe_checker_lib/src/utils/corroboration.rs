@@ -0,0 +1,187 @@
+//! Merging of CWE warnings corroborated by independent analyses.
+//!
+//! Each check is assigned the type of evidence its warnings are based on,
+//! e.g. the taint analysis of the CWE-129 check or the interval-based bounds checks of the Memory check.
+//! If warnings based on different types of evidence implicate the same instruction,
+//! they are merged into one finding with high confidence.
+//! The first of these warnings becomes the finding and the other warnings are listed as related to it.
+
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The configuration of the corroboration policy.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct Config {
+    /// Maps warning names (e.g. `CWE129`) to the type of evidence the warnings are based on.
+    /// Warnings of checks not contained in the map are never merged.
+    pub evidence_types: BTreeMap<String, String>,
+}
+
+/// Merge warnings based on different types of evidence that implicate the same instruction.
+///
+/// The instruction of a warning is identified by its first term ID, or by its first address if it has no term IDs.
+/// Merged findings get the confidence `high` and list the names of the corroborating warnings in `other`.
+/// The order of the warnings is preserved, merged findings take the place of their first warning.
+pub fn corroborate_warnings(cwes: Vec<CweWarning>, config: &Config) -> Vec<CweWarning> {
+    let location = |cwe: &CweWarning| cwe.tids.first().or_else(|| cwe.addresses.first()).cloned();
+    let mut evidence_at_location: HashMap<String, BTreeSet<&str>> = HashMap::new();
+    for cwe in cwes.iter() {
+        if let (Some(location), Some(evidence_type)) =
+            (location(cwe), config.evidence_types.get(&cwe.name))
+        {
+            evidence_at_location
+                .entry(location)
+                .or_default()
+                .insert(evidence_type);
+        }
+    }
+    let corroborated_locations: BTreeSet<String> = evidence_at_location
+        .into_iter()
+        .filter(|(_, evidence_types)| evidence_types.len() > 1)
+        .map(|(location, _)| location)
+        .collect();
+
+    let mut corroborated_cwes = Vec::new();
+    let mut findings: HashMap<String, usize> = HashMap::new();
+    for cwe in cwes {
+        let location = match location(&cwe) {
+            Some(location)
+                if corroborated_locations.contains(&location)
+                    && config.evidence_types.contains_key(&cwe.name) =>
+            {
+                location
+            }
+            _ => {
+                corroborated_cwes.push(cwe);
+                continue;
+            }
+        };
+        match findings.get(&location) {
+            Some(index) => corroborated_cwes[*index].related.push(cwe),
+            None => {
+                findings.insert(location, corroborated_cwes.len());
+                corroborated_cwes.push(cwe);
+            }
+        }
+    }
+    for index in findings.into_values() {
+        mark_as_corroborated(&mut corroborated_cwes[index]);
+    }
+    corroborated_cwes
+}
+
+/// Set the confidence of the finding to `high`
+/// and list the names of the related warnings as corroborating evidence.
+fn mark_as_corroborated(finding: &mut CweWarning) {
+    finding
+        .other
+        .retain(|entry| entry.first().map(String::as_str) != Some("confidence"));
+    finding
+        .other
+        .push(vec!["confidence".to_string(), "high".to_string()]);
+    let mut corroborated_by = vec!["corroborated_by".to_string()];
+    corroborated_by.extend(finding.related.iter().map(|cwe| cwe.name.clone()));
+    finding.other.push(corroborated_by);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corroboration() {
+        let warning =
+            |name: &str, tid: &str| CweWarning::new(name, "0.1", tid).tids(vec![tid.into()]);
+        let config = Config {
+            evidence_types: [
+                ("CWE129", "taint"),
+                ("CWE787", "bounds"),
+                ("CWE125", "bounds"),
+            ]
+            .iter()
+            .map(|(name, evidence)| (name.to_string(), evidence.to_string()))
+            .collect(),
+        };
+        let cwes = vec![
+            warning("CWE476", "store"),
+            warning("CWE129", "store"),
+            warning("CWE125", "load"),
+            warning("CWE787", "store")
+                .other(vec![vec!["confidence".to_string(), "low".to_string()]]),
+            warning("CWE129", "other_store"),
+        ];
+        let corroborated = corroborate_warnings(cwes, &config);
+        assert_eq!(corroborated.len(), 4);
+        assert_eq!(corroborated[0].name, "CWE476");
+        assert!(corroborated[0].related.is_empty());
+        let finding = &corroborated[1];
+        assert_eq!(finding.name, "CWE129");
+        assert_eq!(finding.related.len(), 1);
+        assert_eq!(finding.related[0].name, "CWE787");
+        assert_eq!(
+            finding.other,
+            vec![
+                vec!["confidence".to_string(), "high".to_string()],
+                vec!["corroborated_by".to_string(), "CWE787".to_string()]
+            ]
+        );
+        assert!(corroborated[2].other.is_empty());
+    }
+
+    #[test]
+    fn corroboration_edge_cases() {
+        let config = Config {
+            evidence_types: [
+                ("CWE129", "taint"),
+                ("CWE787", "bounds"),
+                ("CWE125", "bounds"),
+            ]
+            .iter()
+            .map(|(name, evidence)| (name.to_string(), evidence.to_string()))
+            .collect(),
+        };
+        let names = |cwes: &[CweWarning]| -> Vec<String> {
+            cwes.iter().map(|cwe| cwe.name.clone()).collect()
+        };
+
+        // Warnings with the same type of evidence do not corroborate each other.
+        let cwes = vec![
+            CweWarning::new("CWE787", "0.1", "a").tids(vec!["access".into()]),
+            CweWarning::new("CWE125", "0.1", "b").tids(vec!["access".into()]),
+        ];
+        let corroborated = corroborate_warnings(cwes, &config);
+        assert_eq!(names(&corroborated), vec!["CWE787", "CWE125"]);
+        assert!(corroborated.iter().all(|cwe| cwe.other.is_empty()));
+
+        // Warnings without term IDs are identified by their addresses.
+        // Warnings without any location are never merged.
+        let cwes = vec![
+            CweWarning::new("CWE787", "0.1", "a").addresses(vec!["0x10".into()]),
+            CweWarning::new("CWE129", "0.1", "b").addresses(vec!["0x10".into()]),
+            CweWarning::new("CWE125", "0.1", "c").addresses(vec!["0x10".into()]),
+            CweWarning::new("CWE787", "0.1", "d"),
+            CweWarning::new("CWE129", "0.1", "e"),
+        ];
+        let corroborated = corroborate_warnings(cwes, &config);
+        assert_eq!(names(&corroborated), vec!["CWE787", "CWE787", "CWE129"]);
+        assert_eq!(names(&corroborated[0].related), vec!["CWE129", "CWE125"]);
+        assert_eq!(
+            corroborated[0].other[1],
+            vec![
+                "corroborated_by".to_string(),
+                "CWE129".to_string(),
+                "CWE125".to_string()
+            ]
+        );
+
+        // Without evidence types nothing is merged.
+        let cwes = vec![
+            CweWarning::new("CWE787", "0.1", "a").tids(vec!["access".into()]),
+            CweWarning::new("CWE129", "0.1", "b").tids(vec!["access".into()]),
+        ];
+        let corroborated = corroborate_warnings(cwes, &Config::default());
+        assert_eq!(names(&corroborated), vec!["CWE787", "CWE129"]);
+        assert!(corroborated[0].related.is_empty());
+    }
+}
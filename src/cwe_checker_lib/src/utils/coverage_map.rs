@@ -0,0 +1,205 @@
+//! Per-function summaries of the program intended to guide the selection of fuzzing targets.
+//!
+//! For each function the coverage map contains
+//! - whether the function can be reached from functions calling input functions (e.g. `recv`) in the call graph,
+//! - the number of direct calls to dangerous sink functions (e.g. `memcpy` or `system`),
+//! - the number of terms whose behavior the static analyses cannot resolve,
+//!   i.e. indirect jumps and calls, calls to unknown instructions and assignments of unknown values.
+//!
+//! Functions reachable from input that call many sinks are promising fuzzing targets,
+//! especially if they contain a lot of behavior unresolved by the static analyses.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::get_call_graph_distances;
+use std::collections::HashMap;
+
+/// The configuration of the coverage map,
+/// read from the `CoverageMap` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Names of extern functions that read input into the program.
+    pub input_symbols: Vec<String>,
+    /// Names of extern functions that are dangerous if called with attacker-controlled arguments.
+    pub sink_symbols: Vec<String>,
+}
+
+/// The coverage map entry of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FunctionCoverage {
+    /// The name of the function.
+    pub name: String,
+    /// The address of the function.
+    pub address: String,
+    /// Whether the function is reachable in the call graph from a function calling an input function.
+    pub reachable_from_input: bool,
+    /// The number of direct calls to sink functions.
+    pub sink_calls: usize,
+    /// The number of terms in the function with behavior unresolved by the static analyses.
+    pub unresolved_behavior_score: usize,
+}
+
+/// Generate the coverage map entries for all functions of the program.
+pub fn generate_coverage_map(project: &Project, config: &Config) -> Vec<FunctionCoverage> {
+    let program = &project.program;
+    let symbol_names: HashMap<&Tid, &str> = program
+        .term
+        .extern_symbols
+        .iter()
+        .map(|symbol| (&symbol.tid, symbol.name.as_str()))
+        .collect();
+    let count_calls_to = |sub: &Term<Sub>, names: &[String]| {
+        sub.term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+            .filter(|jmp| match &jmp.term {
+                Jmp::Call { target, .. } => symbol_names
+                    .get(target)
+                    .map(|name| names.iter().any(|symbol| symbol == name))
+                    .unwrap_or(false),
+                _ => false,
+            })
+            .count()
+    };
+    let input_subs: Vec<Tid> = program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| count_calls_to(sub, &config.input_symbols) > 0)
+        .map(|sub| sub.tid.clone())
+        .collect();
+    let input_distances = get_call_graph_distances(program, &input_subs, true);
+
+    program
+        .term
+        .subs
+        .iter()
+        .map(|sub| FunctionCoverage {
+            name: sub.term.name.clone(),
+            address: sub.tid.address.clone(),
+            reachable_from_input: input_distances.contains_key(&sub.tid),
+            sink_calls: count_calls_to(sub, &config.sink_symbols),
+            unresolved_behavior_score: count_unresolved_terms(sub),
+        })
+        .collect()
+}
+
+/// Count the indirect jumps and calls, calls to unknown instructions
+/// and assignments of unknown values in the function.
+fn count_unresolved_terms(sub: &Term<Sub>) -> usize {
+    let mut count = 0;
+    for block in sub.term.blocks.iter() {
+        count += block
+            .term
+            .defs
+            .iter()
+            .filter(|def| match &def.term {
+                Def::Assign { value, .. } => matches!(value, Expression::Unknown { .. }),
+                Def::Load { address, .. } => matches!(address, Expression::Unknown { .. }),
+                Def::Store { address, value } => {
+                    matches!(address, Expression::Unknown { .. })
+                        || matches!(value, Expression::Unknown { .. })
+                }
+            })
+            .count();
+        count += block
+            .term
+            .jmps
+            .iter()
+            .filter(|jmp| {
+                matches!(
+                    jmp.term,
+                    Jmp::BranchInd(_) | Jmp::CallInd { .. } | Jmp::CallOther { .. }
+                )
+            })
+            .count();
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_sub(name: &str, jmps: Vec<Term<Jmp>>) -> Term<Sub> {
+        let mut block = Blk::mock();
+        block.tid = Tid::new(format!("block_{}", name));
+        block.term.jmps = jmps;
+        let mut sub = Sub::mock(name);
+        sub.term.blocks.push(block);
+        sub
+    }
+
+    #[test]
+    fn coverage_map() {
+        let mut project = Project::mock_empty();
+        let mut recv = ExternSymbol::mock();
+        recv.tid = Tid::new("recv");
+        recv.name = "recv".to_string();
+        let mut memcpy = ExternSymbol::mock();
+        memcpy.tid = Tid::new("memcpy");
+        memcpy.name = "memcpy".to_string();
+        project.program.term.extern_symbols = vec![recv, memcpy];
+        let indirect_call = Term {
+            tid: Tid::new("indirect_call"),
+            term: Jmp::CallInd {
+                target: Expression::var("RAX"),
+                return_: None,
+            },
+        };
+        project.program.term.subs = vec![
+            mock_sub(
+                "reader",
+                vec![
+                    Jmp::call("call_recv", "recv", None),
+                    Jmp::call("call_parser", "parser", None),
+                ],
+            ),
+            mock_sub(
+                "parser",
+                vec![
+                    Jmp::call("call_memcpy_1", "memcpy", None),
+                    Jmp::call("call_memcpy_2", "memcpy", None),
+                ],
+            ),
+            mock_sub("other", vec![indirect_call]),
+        ];
+        let config = Config {
+            input_symbols: vec!["recv".to_string()],
+            sink_symbols: vec!["memcpy".to_string()],
+        };
+
+        let coverage_map = generate_coverage_map(&project, &config);
+        assert!(coverage_map[0].reachable_from_input);
+        assert!(coverage_map[1].reachable_from_input);
+        assert_eq!(coverage_map[1].sink_calls, 2);
+        assert!(!coverage_map[2].reachable_from_input);
+        assert_eq!(coverage_map[2].unresolved_behavior_score, 1);
+    }
+
+    #[test]
+    fn config_parsing() {
+        use crate::utils::parse_config;
+        let config: Config = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "input_symbols": ["recv"],
+            "sink_symbols": ["memcpy"]
+        }))
+        .unwrap();
+        assert_eq!(config.input_symbols, vec!["recv".to_string()]);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "input_symbols": "recv",
+            "sink_symbols": ["memcpy"]
+        }))
+        .is_err());
+        assert!(parse_config::<Config>(&serde_json::json!({"input_symbols": ["recv"]})).is_err());
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "input_symbols": ["recv"],
+            "sink_symbol": ["memcpy"]
+        }))
+        .is_err());
+    }
+}
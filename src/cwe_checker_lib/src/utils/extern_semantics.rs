@@ -0,0 +1,766 @@
+//! Models of the semantics of extern functions.
+//!
+//! Many checks and analyses need to know what extern functions do,
+//! e.g. which functions allocate memory, which functions return untrusted input or which functions never return.
+//! This knowledge is kept in versioned model files instead of the configuration of the individual checks,
+//! so that models of large APIs (e.g. of OpenSSL or zlib) can be maintained and shared independently of the checks.
+//!
+//! A model file is a JSON object with the following fields:
+//! - `format_version`: The version of the file format. Must be equal to [`FORMAT_VERSION`].
+//! - `name` and `version`: The name and the version of the model, e.g. `libc` and `1.0.0`.
+//! - `functions`: The names of the modelled functions mapped to their [semantics](FunctionSemantics).
+//!
+//! Keys starting with `_comment` are ignored and unknown keys are rejected.
+//!
+//! The models are applied in layers.
+//! The models bundled with the cwe_checker form the bottom layers,
+//! followed by the model files in the `extern_semantics` folder of the configuration directory (in alphabetical order)
+//! and the model files given on the command line.
+//! The entry of a function in a later layer completely replaces the entry of the same function in earlier layers,
+//! so an empty entry removes all known semantics of a function.
+//!
+//! The resulting [`KnowledgeBase`] is added to the configuration of the checks and analyses before they are run.
+//! Entries in the configuration file take precedence over the entries generated from the models.
+
+use crate::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The version of the file format of model files supported by this version of the cwe_checker.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// The model files bundled with the cwe_checker.
+const BUNDLED_MODELS: [&str; 3] = [
+    include_str!("../../../extern_semantics/libc.json"),
+    include_str!("../../../extern_semantics/libc_extensions.json"),
+    include_str!("../../../extern_semantics/rtos.json"),
+];
+
+/// A function allocating a new memory object.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Allocation {
+    /// The indices of the size parameters. The size of the object is the product of the parameters.
+    pub size_parameters: Vec<usize>,
+    /// Set to true if the returned memory is initialized (e.g. with zeros).
+    pub initialized: bool,
+    /// Set to true if the object is allocated on the stack of the caller instead of on the heap.
+    pub stack: bool,
+}
+
+/// A function moving the memory object that the first parameter points to into a newly allocated object.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Reallocation {
+    /// The indices of the size parameters. The size of the new object is the product of the parameters.
+    pub size_parameters: Vec<usize>,
+}
+
+/// A function copying the memory pointed to by the second parameter into the buffer pointed to by the first parameter.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct BufferCopy {
+    /// The index of the parameter containing the number of copied bytes.
+    /// Functions without a length parameter copy a zero-terminated string.
+    pub length_parameter: Option<usize>,
+    /// Set to true if the string is appended to the string in the destination buffer (e.g. `strcat`).
+    pub append: bool,
+}
+
+/// A function writing a string formatted according to its format string parameter into a buffer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormattedOutput {
+    /// The index of the parameter pointing to the destination buffer.
+    pub destination_parameter: usize,
+    /// The index of the parameter containing the size of the destination buffer.
+    /// Functions without a size parameter do not truncate the formatted string.
+    pub size_parameter: Option<usize>,
+}
+
+/// The ways in which a function introduces untrusted input into the program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Taint {
+    /// Set to true if the function returns an untrusted value.
+    pub return_value: bool,
+    /// Set to true if the function returns a pointer to an untrusted string.
+    pub return_string: bool,
+    /// The indices of the parameters pointing to memory that the function writes untrusted input to.
+    pub parameters: Vec<usize>,
+    /// The index of the string parameter of an integer parsing function.
+    /// The return value is untrusted if the parsed string is untrusted.
+    pub parsed_parameter: Option<usize>,
+}
+
+/// A function starting a new task or thread.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TaskCreation {
+    /// The index of the parameter containing the address of the function executed by the new task.
+    pub entry_parameter: usize,
+}
+
+/// The semantics of an extern function.
+/// Semantics that are not set are unknown.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FunctionSemantics {
+    /// Set to true if the function never returns.
+    pub no_return: bool,
+    /// Set if the function allocates a new memory object.
+    pub allocation: Option<Allocation>,
+    /// Set if the function reallocates the memory object that its first parameter points to.
+    pub reallocation: Option<Reallocation>,
+    /// Set to true if the function frees the memory object that its first parameter points to.
+    pub deallocation: bool,
+    /// Set to true if the function returns a new descriptor or handle that has to be released.
+    pub handle_acquisition: bool,
+    /// The index of the parameter containing the descriptor or handle released by the function.
+    pub handle_release_parameter: Option<usize>,
+    /// Set if the function starts a new task or thread.
+    pub task_creation: Option<TaskCreation>,
+    /// Set if the function copies a buffer or a string.
+    pub copy: Option<BufferCopy>,
+    /// Set if the function introduces untrusted input.
+    pub taint: Option<Taint>,
+    /// The index of the format string parameter of functions taking a format string.
+    pub format_string_parameter: Option<usize>,
+    /// Set if the function writes the formatted string into a buffer.
+    pub formatted_output: Option<FormattedOutput>,
+    /// The (signed) interval containing all possible return values of the function.
+    pub return_value_interval: Option<(i64, i64)>,
+}
+
+impl FunctionSemantics {
+    /// Check the semantics for contradictions.
+    fn validate(&self) -> Result<(), Error> {
+        if matches!(&self.allocation, Some(allocation) if allocation.size_parameters.is_empty())
+            || matches!(&self.reallocation, Some(reallocation) if reallocation.size_parameters.is_empty())
+        {
+            return Err(anyhow!("Allocations need at least one size parameter"));
+        }
+        if self.deallocation && (self.allocation.is_some() || self.reallocation.is_some()) {
+            return Err(anyhow!(
+                "A function cannot both allocate and deallocate memory"
+            ));
+        }
+        if matches!(&self.copy, Some(copy) if copy.append && copy.length_parameter.is_some()) {
+            return Err(anyhow!(
+                "Appending copies with a length parameter are not supported"
+            ));
+        }
+        if self.formatted_output.is_some() && self.format_string_parameter.is_none() {
+            return Err(anyhow!("Formatted output needs a format string parameter"));
+        }
+        if let Some((lower_bound, upper_bound)) = self.return_value_interval {
+            if lower_bound > upper_bound {
+                return Err(anyhow!("The return value interval is empty"));
+            }
+        }
+        if self.handle_acquisition && self.handle_release_parameter.is_some() {
+            return Err(anyhow!(
+                "A function cannot both acquire and release a handle"
+            ));
+        }
+        let returns_value = self.allocation.is_some()
+            || self.reallocation.is_some()
+            || self.handle_acquisition
+            || self.return_value_interval.is_some()
+            || matches!(&self.taint, Some(taint) if taint.return_value || taint.return_string);
+        if self.no_return && returns_value {
+            return Err(anyhow!("A no-return function cannot have a return value"));
+        }
+        Ok(())
+    }
+}
+
+/// A model file containing the semantics of a set of extern functions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Model {
+    /// The version of the file format.
+    pub format_version: u64,
+    /// The name of the model.
+    pub name: String,
+    /// The version of the model.
+    pub version: String,
+    /// The modelled functions mapped to their semantics.
+    pub functions: BTreeMap<String, FunctionSemantics>,
+}
+
+impl Model {
+    /// Parse and validate the contents of a model file.
+    pub fn parse(model: &str) -> Result<Model, Error> {
+        let model: serde_json::Value = serde_json::from_str(model)?;
+        let model: Model = serde_json::from_value(remove_comments(model))?;
+        if model.format_version != FORMAT_VERSION {
+            return Err(anyhow!(
+                "Unsupported format version {} of model {} (expected version {})",
+                model.format_version,
+                model.name,
+                FORMAT_VERSION
+            ));
+        }
+        if model.name.is_empty() || model.version.is_empty() {
+            return Err(anyhow!(
+                "The name and the version of a model must not be empty"
+            ));
+        }
+        for (function, semantics) in model.functions.iter() {
+            semantics.validate().map_err(|error| {
+                anyhow!(
+                    "Invalid semantics of {} in model {}: {}",
+                    function,
+                    model.name,
+                    error
+                )
+            })?;
+        }
+        Ok(model)
+    }
+
+    /// Read, parse and validate a model file.
+    pub fn from_file(path: &Path) -> Result<Model, Error> {
+        let model = std::fs::read_to_string(path)
+            .map_err(|error| anyhow!("Could not read {}: {}", path.display(), error))?;
+        Model::parse(&model).map_err(|error| anyhow!("{}: {}", path.display(), error))
+    }
+}
+
+/// Remove all keys starting with `_comment` from the JSON objects contained in the given value.
+pub(crate) fn remove_comments(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(entries) => serde_json::Value::Object(
+            entries
+                .into_iter()
+                .filter(|(key, _)| !key.starts_with("_comment"))
+                .map(|(key, value)| (key, remove_comments(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// The semantics of extern functions resulting from layering several models.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct KnowledgeBase {
+    /// The names and versions of the applied models, in the order in which they were applied.
+    layers: Vec<(String, String)>,
+    /// The functions mapped to their semantics.
+    functions: BTreeMap<String, FunctionSemantics>,
+}
+
+impl KnowledgeBase {
+    /// Get the knowledge base consisting of the models bundled with the cwe_checker.
+    pub fn bundled() -> KnowledgeBase {
+        let mut knowledge_base = KnowledgeBase::default();
+        for model in BUNDLED_MODELS.iter() {
+            knowledge_base.add_layer(Model::parse(model).expect("Invalid bundled model"));
+        }
+        knowledge_base
+    }
+
+    /// Get the knowledge base consisting of the bundled models,
+    /// the models in the configuration directory and the model files at the given paths (in this order).
+    pub fn load(model_paths: &[String]) -> Result<KnowledgeBase, Error> {
+        let mut knowledge_base = KnowledgeBase::bundled();
+        let mut paths = get_user_model_paths();
+        paths.extend(model_paths.iter().map(PathBuf::from));
+        for path in paths {
+            knowledge_base.add_layer(Model::from_file(&path)?);
+        }
+        Ok(knowledge_base)
+    }
+
+    /// Add a model as a new layer on top of the knowledge base.
+    /// Its entries replace the entries of the same functions in earlier layers.
+    pub fn add_layer(&mut self, model: Model) {
+        self.layers.push((model.name, model.version));
+        self.functions.extend(model.functions);
+    }
+
+    /// Get the names and versions of the applied models.
+    pub fn get_layers(&self) -> &[(String, String)] {
+        &self.layers
+    }
+
+    /// Get the semantics of the function with the given name, if the function is modelled.
+    pub fn get(&self, function: &str) -> Option<&FunctionSemantics> {
+        self.functions.get(function)
+    }
+
+    /// Get the names of all functions whose semantics satisfy the given predicate.
+    fn get_functions(&self, predicate: impl Fn(&FunctionSemantics) -> bool) -> Vec<String> {
+        self.functions
+            .iter()
+            .filter(|(_, semantics)| predicate(semantics))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Get all functions for which the given function returns a value, mapped to that value.
+    fn get_function_map<T: Serialize>(
+        &self,
+        value: impl Fn(&FunctionSemantics) -> Option<T>,
+    ) -> Vec<(String, serde_json::Value)> {
+        self.functions
+            .iter()
+            .filter_map(|(name, semantics)| {
+                value(semantics).map(|value| (name.clone(), serde_json::to_value(value).unwrap()))
+            })
+            .collect()
+    }
+
+    /// Get the size parameters of all allocation and reallocation functions.
+    /// If `include_stack` is false, functions allocating on the stack are ignored.
+    fn get_size_parameters(&self, include_stack: bool) -> Vec<(String, serde_json::Value)> {
+        self.get_function_map(
+            |semantics| match (&semantics.allocation, &semantics.reallocation) {
+                (Some(allocation), _) if include_stack || !allocation.stack => {
+                    Some(allocation.size_parameters.clone())
+                }
+                (_, Some(reallocation)) => Some(reallocation.size_parameters.clone()),
+                _ => None,
+            },
+        )
+    }
+
+    /// Add the semantics of the knowledge base to the configuration of the checks and analyses
+    /// that use semantics of extern functions.
+    /// Existing entries of the configuration are kept.
+    pub fn apply_to_config(&self, config: &mut serde_json::Value) {
+        let is_heap_allocation = |semantics: &FunctionSemantics| matches!(&semantics.allocation, Some(allocation) if !allocation.stack);
+        add_list_entries(
+            &mut config["NoReturn"]["symbols"],
+            self.get_functions(|semantics| semantics.no_return),
+        );
+
+        let memory_config = &mut config["Memory"];
+        add_list_entries(
+            &mut memory_config["allocation_symbols"],
+            self.get_functions(is_heap_allocation),
+        );
+        add_list_entries(
+            &mut memory_config["uninitialized_allocation_symbols"],
+            self.get_functions(|semantics| {
+                is_heap_allocation(semantics)
+                    && matches!(&semantics.allocation, Some(allocation) if !allocation.initialized)
+            }),
+        );
+        add_list_entries(
+            &mut memory_config["deallocation_symbols"],
+            self.get_functions(|semantics| semantics.deallocation),
+        );
+        add_list_entries(
+            &mut memory_config["reallocation_symbols"],
+            self.get_functions(|semantics| semantics.reallocation.is_some()),
+        );
+        add_map_entries(
+            &mut memory_config["allocation_size_parameters"],
+            self.get_size_parameters(false),
+        );
+        add_map_entries(
+            &mut memory_config["return_value_intervals"],
+            self.get_function_map(|semantics| semantics.return_value_interval),
+        );
+        add_map_entries(
+            &mut memory_config["string_formatting_symbols"],
+            self.get_function_map(|semantics| {
+                let formatted_output = semantics.formatted_output.as_ref()?;
+                Some(serde_json::json!({
+                    "destination_parameter": formatted_output.destination_parameter,
+                    "size_parameter": formatted_output.size_parameter,
+                    "format_string_parameter": semantics.format_string_parameter?
+                }))
+            }),
+        );
+
+        add_list_entries(
+            &mut config["CWE476"]["symbols"],
+            self.get_functions(|semantics| {
+                is_heap_allocation(semantics) || semantics.reallocation.is_some()
+            }),
+        );
+        add_list_entries(
+            &mut config["CWE690"]["symbols"],
+            self.get_functions(|semantics| {
+                is_heap_allocation(semantics) || semantics.reallocation.is_some()
+            }),
+        );
+        add_list_entries(
+            &mut config["CWE775"]["acquisition_symbols"],
+            self.get_functions(|semantics| semantics.handle_acquisition),
+        );
+        add_map_entries(
+            &mut config["CWE775"]["release_symbols"],
+            self.get_function_map(|semantics| semantics.handle_release_parameter),
+        );
+        add_list_entries(
+            &mut config["CWE401"]["escaping_symbols"],
+            self.get_functions(|semantics| semantics.task_creation.is_some()),
+        );
+        add_map_entries(
+            &mut config["TaskEntryPoints"]["task_creation_symbols"],
+            self.get_function_map(|semantics| {
+                semantics
+                    .task_creation
+                    .as_ref()
+                    .map(|task_creation| task_creation.entry_parameter)
+            }),
+        );
+        add_map_entries(
+            &mut config["CWE362"]["thread_creation_symbols"],
+            self.get_function_map(|semantics| {
+                semantics
+                    .task_creation
+                    .as_ref()
+                    .map(|task_creation| task_creation.entry_parameter)
+            }),
+        );
+
+        add_list_entries(
+            &mut config["CWE120"]["symbols"],
+            self.get_functions(|semantics| {
+                matches!(&semantics.copy, Some(copy) if copy.length_parameter.is_none())
+            }),
+        );
+        add_list_entries(
+            &mut config["CWE120"]["append_symbols"],
+            self.get_functions(|semantics| matches!(&semantics.copy, Some(copy) if copy.append)),
+        );
+        add_map_entries(
+            &mut config["CWE120"]["copy_functions"],
+            self.get_function_map(|semantics| {
+                semantics
+                    .copy
+                    .as_ref()
+                    .and_then(|copy| copy.length_parameter)
+            }),
+        );
+
+        add_list_entries(
+            &mut config["CWE761"]["symbols"],
+            self.get_functions(|semantics| {
+                semantics.deallocation || semantics.reallocation.is_some()
+            }),
+        );
+
+        self.apply_taint_sources(&mut config["CWE129"]);
+        self.apply_taint_sources(&mut config["CWE789"]["taint_sources"]);
+        add_map_entries(
+            &mut config["CWE789"]["allocation_symbols"],
+            self.get_size_parameters(true),
+        );
+
+        let cwe_134_config = &mut config["CWE134"];
+        add_map_entries(
+            &mut cwe_134_config["format_string_index"],
+            self.get_function_map(|semantics| semantics.format_string_parameter),
+        );
+        add_map_entries(
+            &mut cwe_134_config["parameter_sources"],
+            self.get_tainted_parameters(),
+        );
+        add_list_entries(
+            &mut cwe_134_config["return_value_sources"],
+            self.get_functions(
+                |semantics| matches!(&semantics.taint, Some(taint) if taint.return_string),
+            ),
+        );
+        add_map_entries(
+            &mut config["CWE685"]["format_string_index"],
+            self.get_function_map(|semantics| semantics.format_string_parameter),
+        );
+    }
+
+    /// Get the functions writing untrusted input to memory pointed to by their parameters,
+    /// mapped to the indices of these parameters.
+    fn get_tainted_parameters(&self) -> Vec<(String, serde_json::Value)> {
+        self.get_function_map(|semantics| {
+            semantics
+                .taint
+                .as_ref()
+                .filter(|taint| !taint.parameters.is_empty())
+                .map(|taint| taint.parameters.clone())
+        })
+    }
+
+    /// Add the taint sources of the knowledge base to a taint source configuration
+    /// in the format of the CWE-129 check.
+    fn apply_taint_sources(&self, config: &mut serde_json::Value) {
+        add_list_entries(
+            &mut config["return_value_sources"],
+            self.get_functions(
+                |semantics| matches!(&semantics.taint, Some(taint) if taint.return_value),
+            ),
+        );
+        add_map_entries(
+            &mut config["parameter_sources"],
+            self.get_tainted_parameters(),
+        );
+        add_map_entries(
+            &mut config["parsing_functions"],
+            self.get_function_map(|semantics| {
+                semantics
+                    .taint
+                    .as_ref()
+                    .and_then(|taint| taint.parsed_parameter)
+            }),
+        );
+    }
+}
+
+/// Get the paths to the model files in the `extern_semantics` folder of the configuration directory,
+/// sorted alphabetically.
+pub fn get_user_model_paths() -> Vec<PathBuf> {
+    let model_dir = match directories::ProjectDirs::from("", "", "cwe_checker") {
+        Some(project_dirs) => project_dirs.config_dir().join("extern_semantics"),
+        None => return Vec::new(),
+    };
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(model_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map(|extension| extension == "json") == Some(true))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+}
+
+/// Append the given names to a JSON array, skipping names that are already contained in it.
+/// A `null` value is replaced by an array. Values that are neither arrays nor `null` are left unchanged.
+fn add_list_entries(list: &mut serde_json::Value, names: Vec<String>) {
+    if list.is_null() {
+        *list = serde_json::Value::Array(Vec::new());
+    }
+    if let serde_json::Value::Array(entries) = list {
+        for name in names {
+            let name = serde_json::Value::String(name);
+            if !entries.contains(&name) {
+                entries.push(name);
+            }
+        }
+    }
+}
+
+/// Add the given entries to a JSON object, skipping keys that are already contained in it.
+/// A `null` value is replaced by an object. Values that are neither objects nor `null` are left unchanged.
+fn add_map_entries(map: &mut serde_json::Value, new_entries: Vec<(String, serde_json::Value)>) {
+    if map.is_null() {
+        *map = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(entries) = map {
+        for (key, value) in new_entries {
+            entries.entry(key).or_insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mock_model(functions: serde_json::Value) -> Model {
+        Model::parse(
+            &json!({
+                "_comment": "A model for tests.",
+                "format_version": FORMAT_VERSION,
+                "name": "mock",
+                "version": "0.1",
+                "functions": functions
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn model_validation() {
+        let model = mock_model(json!({
+            "xalloc": {"_comment": "Allocates.", "allocation": {"size_parameters": [1]}}
+        }));
+        assert_eq!(
+            model.functions["xalloc"].allocation,
+            Some(Allocation {
+                size_parameters: vec![1],
+                initialized: false,
+                stack: false,
+            })
+        );
+        let parse = |model: serde_json::Value| Model::parse(&model.to_string());
+        let valid_model =
+            json!({"format_version": 1, "name": "mock", "version": "0.1", "functions": {}});
+        assert!(parse(valid_model.clone()).is_ok());
+        let mut model = valid_model.clone();
+        model["format_version"] = json!(2);
+        assert!(parse(model).is_err());
+        let mut model = valid_model.clone();
+        model["name"] = json!("");
+        assert!(parse(model).is_err());
+        let invalid_semantics = vec![
+            json!({"allocates": true}),
+            json!({"allocation": {"size_parameters": []}}),
+            json!({"allocation": {"size_parameters": [0]}, "deallocation": true}),
+            json!({"no_return": true, "taint": {"return_value": true}}),
+            json!({"return_value_interval": [1, 0]}),
+            json!({"handle_acquisition": true, "handle_release_parameter": 0}),
+            json!({"no_return": true, "handle_acquisition": true}),
+            json!({"copy": {"length_parameter": 2, "append": true}}),
+            json!({"formatted_output": {"destination_parameter": 0}}),
+        ];
+        for semantics in invalid_semantics {
+            let mut model = valid_model.clone();
+            model["functions"]["func"] = semantics.clone();
+            assert!(parse(model).is_err(), "{} accepted", semantics);
+        }
+    }
+
+    #[test]
+    fn bundled_models() {
+        let knowledge_base = KnowledgeBase::bundled();
+        assert_eq!(knowledge_base.get_layers().len(), BUNDLED_MODELS.len());
+        assert!(knowledge_base.get("exit").unwrap().no_return);
+        assert!(knowledge_base.get("free").unwrap().deallocation);
+        assert!(knowledge_base.get("vPortFree").unwrap().deallocation);
+        assert!(knowledge_base.get("main").is_none());
+    }
+
+    #[test]
+    fn layering() {
+        let mut knowledge_base = KnowledgeBase::bundled();
+        knowledge_base.add_layer(mock_model(json!({
+            "exit": {},
+            "SSL_read": {"taint": {"parameters": [1]}}
+        })));
+        assert_eq!(
+            knowledge_base.get_layers().last(),
+            Some(&("mock".to_string(), "0.1".to_string()))
+        );
+        assert!(!knowledge_base.get("exit").unwrap().no_return);
+        assert!(knowledge_base.get("abort").unwrap().no_return);
+        assert_eq!(
+            knowledge_base
+                .get("SSL_read")
+                .unwrap()
+                .taint
+                .as_ref()
+                .unwrap()
+                .parameters,
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn config_generation() {
+        let mut knowledge_base = KnowledgeBase::default();
+        knowledge_base.add_layer(mock_model(json!({
+            "exit": {"no_return": true},
+            "malloc": {"allocation": {"size_parameters": [0]}},
+            "calloc": {"allocation": {"size_parameters": [0, 1], "initialized": true}},
+            "alloca": {"allocation": {"size_parameters": [0], "stack": true}},
+            "free": {"deallocation": true},
+            "xQueueGenericCreate": {"handle_acquisition": true},
+            "vQueueDelete": {"handle_release_parameter": 0},
+            "xTaskCreate": {"task_creation": {"entry_parameter": 0}},
+            "strcpy": {"copy": {}},
+            "strcat": {"copy": {"append": true}},
+            "snprintf": {
+                "format_string_parameter": 2,
+                "formatted_output": {"destination_parameter": 0, "size_parameter": 1}
+            },
+            "memcpy": {"copy": {"length_parameter": 2}},
+            "recv": {"taint": {"parameters": [1]}},
+            "atoi": {"taint": {"return_value": true}}
+        })));
+        let mut config = json!({
+            "NoReturn": {"auto_correct": true, "symbols": ["fatal"]},
+            "Memory": {"allocation_size_parameters": {"malloc": [1]}},
+            "CWE129": {"parameter_sources": {"recv": [2]}}
+        });
+        knowledge_base.apply_to_config(&mut config);
+        assert_eq!(
+            config["NoReturn"],
+            json!({"auto_correct": true, "symbols": ["fatal", "exit"]})
+        );
+        assert_eq!(
+            config["Memory"]["allocation_symbols"],
+            json!(["calloc", "malloc"])
+        );
+        assert_eq!(
+            config["Memory"]["uninitialized_allocation_symbols"],
+            json!(["malloc"])
+        );
+        assert_eq!(
+            config["Memory"]["allocation_size_parameters"],
+            json!({"calloc": [0, 1], "malloc": [1]})
+        );
+        assert_eq!(config["Memory"]["deallocation_symbols"], json!(["free"]));
+        assert_eq!(config["CWE120"]["symbols"], json!(["strcat", "strcpy"]));
+        assert_eq!(config["CWE120"]["append_symbols"], json!(["strcat"]));
+        assert_eq!(
+            config["Memory"]["string_formatting_symbols"],
+            json!({"snprintf": {
+                "destination_parameter": 0,
+                "size_parameter": 1,
+                "format_string_parameter": 2
+            }})
+        );
+        assert_eq!(
+            config["CWE685"]["format_string_index"],
+            json!({"snprintf": 2})
+        );
+        assert_eq!(config["CWE761"]["symbols"], json!(["free"]));
+        assert_eq!(config["CWE476"]["symbols"], json!(["calloc", "malloc"]));
+        assert_eq!(config["CWE690"]["symbols"], json!(["calloc", "malloc"]));
+        assert_eq!(
+            config["CWE775"],
+            json!({
+                "acquisition_symbols": ["xQueueGenericCreate"],
+                "release_symbols": {"vQueueDelete": 0}
+            })
+        );
+        assert_eq!(config["CWE401"]["escaping_symbols"], json!(["xTaskCreate"]));
+        assert_eq!(
+            config["TaskEntryPoints"]["task_creation_symbols"],
+            json!({"xTaskCreate": 0})
+        );
+        assert_eq!(
+            config["CWE362"]["thread_creation_symbols"],
+            json!({"xTaskCreate": 0})
+        );
+        assert_eq!(config["CWE120"]["copy_functions"], json!({"memcpy": 2}));
+        assert_eq!(
+            config["CWE129"],
+            json!({
+                "return_value_sources": ["atoi"],
+                "parameter_sources": {"recv": [2]},
+                "parsing_functions": {}
+            })
+        );
+        assert_eq!(
+            config["CWE789"]["taint_sources"]["parameter_sources"],
+            json!({"recv": [1]})
+        );
+        assert_eq!(
+            config["CWE789"]["allocation_symbols"],
+            json!({"alloca": [0], "calloc": [0, 1], "malloc": [0]})
+        );
+    }
+
+    #[test]
+    fn bundled_models_yield_valid_config() {
+        let mut config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../config.json")).unwrap();
+        KnowledgeBase::bundled().apply_to_config(&mut config);
+        for module in crate::get_modules() {
+            if let Err(error) = (module.validate_config)(&config[module.name]) {
+                panic!("Invalid configuration of {}: {}", module.name, error);
+            }
+        }
+        crate::utils::parse_config::<crate::analysis::pointer_inference::Config>(&config["Memory"])
+            .unwrap();
+    }
+}
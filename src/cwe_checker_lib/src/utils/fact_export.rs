@@ -0,0 +1,233 @@
+//! Export of the analysis results as relational facts for external query engines.
+//!
+//! The facts are a set of tables, each written as a CSV file with a header row.
+//! Terms are referenced by their term IDs, so that the tables can be joined on these columns.
+//! The following tables are generated:
+//! - `functions`: The functions of the program (`tid`, `name`, `address`).
+//! - `extern_functions`: The extern symbols called by the program (`tid`, `name`).
+//! - `calls`: The direct calls of the call graph (`callsite_tid`, `callsite_address`, `caller_tid`, `callee_tid`).
+//! - `dataflow`: The intraprocedural def-use edges of register values
+//!   (`function_tid`, `def_tid`, `use_tid`, `register`), see [`DefUseChains`].
+//! - `findings`: The CWE warnings (`finding_id`, `check`, `version`, `description`).
+//! - `finding_addresses` and `finding_tids`: The locations of the CWE warnings
+//!   (`finding_id` and `address` resp. `tid`).
+
+use crate::analysis::def_use::DefUseChains;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::baseline::get_finding_id;
+use crate::utils::log::CweWarning;
+use std::path::Path;
+
+/// A table of facts with named columns.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FactTable {
+    /// The name of the table. It is also the name of the CSV file (without extension).
+    pub name: &'static str,
+    /// The names of the columns.
+    pub columns: Vec<&'static str>,
+    /// The rows of the table. Each row has one value per column.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl FactTable {
+    /// Create an empty table.
+    fn new(name: &'static str, columns: Vec<&'static str>) -> FactTable {
+        FactTable {
+            name,
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Render the table as CSV with a header row.
+    /// Values containing commas, quotes or line breaks are quoted.
+    pub fn to_csv(&self) -> String {
+        let columns = self.columns.iter().map(|column| column.to_string());
+        std::iter::once(columns.collect::<Vec<_>>())
+            .chain(self.rows.iter().cloned())
+            .map(|row| {
+                let values: Vec<String> = row.iter().map(|value| escape_csv_value(value)).collect();
+                values.join(",") + "\n"
+            })
+            .collect()
+    }
+}
+
+/// Quote a CSV value if necessary.
+fn escape_csv_value(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Generate the fact tables for the given project and CWE warnings.
+pub fn generate_facts(project: &Project, cwes: &[CweWarning]) -> Vec<FactTable> {
+    let program = &project.program.term;
+    let mut functions = FactTable::new("functions", vec!["tid", "name", "address"]);
+    let mut extern_functions = FactTable::new("extern_functions", vec!["tid", "name"]);
+    let mut calls = FactTable::new(
+        "calls",
+        vec![
+            "callsite_tid",
+            "callsite_address",
+            "caller_tid",
+            "callee_tid",
+        ],
+    );
+    let mut dataflow = FactTable::new(
+        "dataflow",
+        vec!["function_tid", "def_tid", "use_tid", "register"],
+    );
+    for symbol in program.extern_symbols.iter() {
+        extern_functions
+            .rows
+            .push(vec![symbol.tid.to_string(), symbol.name.clone()]);
+    }
+    for sub in program.subs.iter() {
+        functions.rows.push(vec![
+            sub.tid.to_string(),
+            sub.term.name.clone(),
+            sub.tid.address.clone(),
+        ]);
+        let chains = DefUseChains::new(project, sub);
+        for block in sub.term.blocks.iter() {
+            let def_tids = block.term.defs.iter().map(|def| &def.tid);
+            for def_tid in def_tids.chain(block.term.jmps.iter().map(|jmp| &jmp.tid)) {
+                for (use_tid, register) in chains.uses_of(def_tid) {
+                    dataflow.rows.push(vec![
+                        sub.tid.to_string(),
+                        def_tid.to_string(),
+                        use_tid.to_string(),
+                        register.name.clone(),
+                    ]);
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    calls.rows.push(vec![
+                        jmp.tid.to_string(),
+                        jmp.tid.address.clone(),
+                        sub.tid.to_string(),
+                        target.to_string(),
+                    ]);
+                }
+            }
+        }
+    }
+
+    let mut findings = FactTable::new(
+        "findings",
+        vec!["finding_id", "check", "version", "description"],
+    );
+    let mut finding_addresses = FactTable::new("finding_addresses", vec!["finding_id", "address"]);
+    let mut finding_tids = FactTable::new("finding_tids", vec!["finding_id", "tid"]);
+    for cwe in cwes {
+        let finding_id = cwe
+            .finding_id
+            .clone()
+            .unwrap_or_else(|| get_finding_id(cwe));
+        findings.rows.push(vec![
+            finding_id.clone(),
+            cwe.name.clone(),
+            cwe.version.clone(),
+            cwe.description.clone(),
+        ]);
+        for address in cwe.addresses.iter() {
+            finding_addresses
+                .rows
+                .push(vec![finding_id.clone(), address.clone()]);
+        }
+        for tid in cwe.tids.iter() {
+            finding_tids
+                .rows
+                .push(vec![finding_id.clone(), tid.clone()]);
+        }
+    }
+
+    vec![
+        functions,
+        extern_functions,
+        calls,
+        dataflow,
+        findings,
+        finding_addresses,
+        finding_tids,
+    ]
+}
+
+/// Write each fact table as a CSV file into the given directory.
+/// The directory is created if it does not exist.
+pub fn write_facts(directory: &Path, tables: &[FactTable]) -> Result<(), Error> {
+    std::fs::create_dir_all(directory)?;
+    for table in tables {
+        std::fs::write(
+            directory.join(format!("{}.csv", table.name)),
+            table.to_csv(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_rendering() {
+        let mut table = FactTable::new("findings", vec!["finding_id", "description"]);
+        table.rows.push(vec![
+            "a".to_string(),
+            "Call to \"gets\", unchecked".to_string(),
+        ]);
+        assert_eq!(
+            table.to_csv(),
+            "finding_id,description\na,\"Call to \"\"gets\"\", unchecked\"\n"
+        );
+    }
+
+    #[test]
+    fn fact_generation() {
+        let mut project = Project::mock_empty();
+        let rax = Variable::mock("RAX", 8);
+        let mut block = Blk::mock();
+        block.term.defs = vec![
+            Def::assign("def_1", rax.clone(), Expression::const_from_i64(1)),
+            Def::assign("def_2", rax.clone(), Expression::Var(rax.clone())),
+        ];
+        block.term.jmps = vec![Term {
+            tid: Tid::new("call"),
+            term: Jmp::Call {
+                target: Tid::new("mock_symbol"),
+                return_: None,
+            },
+        }];
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![block];
+        project.program.term.subs = vec![sub];
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        let cwe = CweWarning::new("CWE676", "0.1", "Call to mock_symbol")
+            .addresses(vec!["UNKNOWN".to_string()])
+            .tids(vec!["call".to_string()]);
+
+        let tables = generate_facts(&project, std::slice::from_ref(&cwe));
+        let get_table = |name: &str| tables.iter().find(|table| table.name == name).unwrap();
+        assert_eq!(
+            get_table("calls").rows,
+            vec![vec!["call", "UNKNOWN", "func", "mock_symbol"]]
+        );
+        assert!(get_table("dataflow").rows.contains(&vec![
+            "func".to_string(),
+            "def_1".into(),
+            "def_2".into(),
+            "RAX".into()
+        ]));
+        assert_eq!(get_table("findings").rows[0][0], get_finding_id(&cwe));
+        assert_eq!(
+            get_table("finding_tids").rows,
+            vec![vec![get_finding_id(&cwe), "call".to_string()]]
+        );
+    }
+}
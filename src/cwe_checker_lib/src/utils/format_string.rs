@@ -0,0 +1,307 @@
+//! Parsers for the conversion specifiers of `printf`-style and `scanf`-style format strings.
+//!
+//! The parsers determine for each conversion specifier which of the variadic arguments it consumes,
+//! so that checks can map specifiers to the actual argument locations at a call site.
+
+/// A conversion specifier of a format string.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct FormatSpecifier {
+    /// The conversion character, e.g. `s` for `%s` or `n` for `%n`.
+    pub conversion: char,
+    /// The length modifier, e.g. `l` for `%ld`. Empty if no length modifier is given.
+    pub length_modifier: String,
+    /// The field width if it is given as a number, e.g. `10` for `%10s`.
+    pub width: Option<usize>,
+    /// The precision if it is given as a number, e.g. `5` for `%.5s`.
+    /// Always `None` for `scanf`-style format strings.
+    pub precision: Option<usize>,
+    /// The index of the variadic argument consumed by the specifier,
+    /// where index 0 denotes the first argument after the format string.
+    pub argument_index: usize,
+    /// The byte offset of the specifier in the format string.
+    pub offset: usize,
+    /// The length of the specifier in bytes, including the leading `%`.
+    pub length: usize,
+}
+
+impl FormatSpecifier {
+    /// Returns `true` if the specifier writes to the memory its argument points to (i.e. `%n`).
+    pub fn is_write(&self) -> bool {
+        self.conversion == 'n'
+    }
+}
+
+/// Parse a format string and return its conversion specifiers in order.
+///
+/// Width and precision arguments given by `*` consume variadic arguments
+/// but are not returned as specifiers.
+/// Positional arguments like `%2$s` are supported.
+/// Parsing stops at the first malformed specifier.
+pub fn parse_format_string(format_string: &str) -> Vec<FormatSpecifier> {
+    let bytes = format_string.as_bytes();
+    let mut specifiers = Vec::new();
+    let mut next_argument = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] != b'%' {
+            pos += 1;
+            continue;
+        }
+        let offset = pos;
+        pos += 1;
+        if bytes.get(pos) == Some(&b'%') {
+            pos += 1;
+            continue;
+        }
+        // Positional argument, e.g. `%2$s`.
+        let mut position = None;
+        let digits_end = skip_digits(bytes, pos);
+        if digits_end > pos && bytes.get(digits_end) == Some(&b'$') {
+            position = format_string[pos..digits_end]
+                .parse::<usize>()
+                .ok()
+                .filter(|position| *position > 0);
+            pos = digits_end + 1;
+        }
+        // Flags
+        while pos < bytes.len() && b"-+ #0'".contains(&bytes[pos]) {
+            pos += 1;
+        }
+        // Width
+        let mut width = None;
+        if bytes.get(pos) == Some(&b'*') {
+            pos = skip_star_argument(bytes, pos + 1, &mut next_argument);
+        } else {
+            let width_end = skip_digits(bytes, pos);
+            width = format_string[pos..width_end].parse::<usize>().ok();
+            pos = width_end;
+        }
+        // Precision
+        let mut precision = None;
+        if bytes.get(pos) == Some(&b'.') {
+            pos += 1;
+            if bytes.get(pos) == Some(&b'*') {
+                pos = skip_star_argument(bytes, pos + 1, &mut next_argument);
+            } else {
+                let precision_end = skip_digits(bytes, pos);
+                // A missing number denotes a precision of zero.
+                precision = Some(
+                    format_string[pos..precision_end]
+                        .parse::<usize>()
+                        .unwrap_or(0),
+                );
+                pos = precision_end;
+            }
+        }
+        // Length modifier
+        let modifier_start = pos;
+        while pos < bytes.len() && b"hlLqjzt".contains(&bytes[pos]) {
+            pos += 1;
+        }
+        let length_modifier = format_string[modifier_start..pos].to_string();
+        // Conversion
+        let conversion = match bytes.get(pos) {
+            Some(conversion) if conversion.is_ascii_alphabetic() => *conversion as char,
+            _ => break,
+        };
+        pos += 1;
+        let argument_index = match position {
+            Some(position) => position - 1,
+            None => {
+                next_argument += 1;
+                next_argument - 1
+            }
+        };
+        specifiers.push(FormatSpecifier {
+            conversion,
+            length_modifier,
+            width,
+            precision,
+            argument_index,
+            offset,
+            length: pos - offset,
+        });
+    }
+    specifiers
+}
+
+/// Parse a `scanf`-style format string and return its conversion specifiers in order.
+///
+/// Specifiers with suppressed assignment (e.g. `%*d`) consume no argument and are not returned.
+/// Scansets like `%[a-z]` are returned with `[` as conversion character.
+/// Positional arguments like `%2$s` are supported.
+/// Parsing stops at the first malformed specifier.
+pub fn parse_scanf_format_string(format_string: &str) -> Vec<FormatSpecifier> {
+    let bytes = format_string.as_bytes();
+    let mut specifiers = Vec::new();
+    let mut next_argument = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] != b'%' {
+            pos += 1;
+            continue;
+        }
+        let offset = pos;
+        pos += 1;
+        if bytes.get(pos) == Some(&b'%') {
+            pos += 1;
+            continue;
+        }
+        // Positional argument, e.g. `%2$s`.
+        let mut position = None;
+        let digits_end = skip_digits(bytes, pos);
+        if digits_end > pos && bytes.get(digits_end) == Some(&b'$') {
+            position = format_string[pos..digits_end]
+                .parse::<usize>()
+                .ok()
+                .filter(|position| *position > 0);
+            pos = digits_end + 1;
+        }
+        // Assignment suppression
+        let is_suppressed = bytes.get(pos) == Some(&b'*');
+        if is_suppressed {
+            pos += 1;
+        }
+        // Width
+        let width_end = skip_digits(bytes, pos);
+        let width = format_string[pos..width_end].parse::<usize>().ok();
+        pos = width_end;
+        // Length modifier, including the `m` modifier for dynamically allocated strings.
+        let modifier_start = pos;
+        while pos < bytes.len() && b"hlLqjztm".contains(&bytes[pos]) {
+            pos += 1;
+        }
+        let length_modifier = format_string[modifier_start..pos].to_string();
+        // Conversion
+        let conversion = match bytes.get(pos) {
+            Some(b'[') => {
+                // A `]` directly after the opening `[` or `[^` belongs to the scanset.
+                let mut scanset_end = pos + 1;
+                if bytes.get(scanset_end) == Some(&b'^') {
+                    scanset_end += 1;
+                }
+                if bytes.get(scanset_end) == Some(&b']') {
+                    scanset_end += 1;
+                }
+                match bytes[scanset_end.min(bytes.len())..]
+                    .iter()
+                    .position(|byte| *byte == b']')
+                {
+                    Some(index) => pos = scanset_end + index,
+                    None => break,
+                }
+                '['
+            }
+            Some(conversion) if conversion.is_ascii_alphabetic() => *conversion as char,
+            _ => break,
+        };
+        pos += 1;
+        if is_suppressed {
+            continue;
+        }
+        let argument_index = match position {
+            Some(position) => position - 1,
+            None => {
+                next_argument += 1;
+                next_argument - 1
+            }
+        };
+        specifiers.push(FormatSpecifier {
+            conversion,
+            length_modifier,
+            width,
+            precision: None,
+            argument_index,
+            offset,
+            length: pos - offset,
+        });
+    }
+    specifiers
+}
+
+/// Return the position of the first non-digit at or after `pos`.
+fn skip_digits(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Skip the optional position (e.g. `2$`) of a `*` width or precision starting at `pos`.
+/// If no position is given, the `*` consumes the next argument.
+fn skip_star_argument(bytes: &[u8], pos: usize, next_argument: &mut usize) -> usize {
+    let digits_end = skip_digits(bytes, pos);
+    if digits_end > pos && bytes.get(digits_end) == Some(&b'$') {
+        digits_end + 1
+    } else {
+        *next_argument += 1;
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_string_parsing() {
+        let specifiers = parse_format_string("%d%% %-*.*s %lu%n %2$hhn");
+        let summary: Vec<(char, &str, usize)> = specifiers
+            .iter()
+            .map(|spec| {
+                (
+                    spec.conversion,
+                    spec.length_modifier.as_str(),
+                    spec.argument_index,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('d', "", 0),
+                ('s', "", 3),
+                ('u', "l", 4),
+                ('n', "", 5),
+                ('n', "hh", 1)
+            ]
+        );
+        assert!(specifiers[3].is_write());
+        assert!(!specifiers[1].is_write());
+        assert_eq!(specifiers[3].offset, 15);
+        assert_eq!(parse_format_string("100%").len(), 0);
+        assert_eq!(parse_format_string("%10s")[0].width, Some(10));
+        assert_eq!(specifiers[1].length, 6);
+        assert_eq!(specifiers[1].precision, None);
+        assert_eq!(parse_format_string("%.5s")[0].precision, Some(5));
+        assert_eq!(parse_format_string("%.s")[0].precision, Some(0));
+    }
+
+    #[test]
+    fn scanf_format_string_parsing() {
+        let specifiers = parse_scanf_format_string("%d %*s %15s %[^]a-z] %ms%%%c");
+        let summary: Vec<(char, &str, Option<usize>, usize)> = specifiers
+            .iter()
+            .map(|spec| {
+                (
+                    spec.conversion,
+                    spec.length_modifier.as_str(),
+                    spec.width,
+                    spec.argument_index,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('d', "", None, 0),
+                ('s', "", Some(15), 1),
+                ('[', "", None, 2),
+                ('s', "m", None, 3),
+                ('c', "", None, 4)
+            ]
+        );
+        assert_eq!(specifiers[2].offset, 12);
+        assert_eq!(parse_scanf_format_string("%[abc").len(), 0);
+    }
+}
@@ -1,11 +1,11 @@
 //! Helper functions for common tasks utilizing the control flow graph of the binary.
 
 use crate::analysis::graph::*;
-use crate::intermediate_representation::Jmp;
+use crate::intermediate_representation::{Blk, Jmp, Program, Sub, Term};
 use crate::prelude::*;
-use petgraph::graph::NodeIndex;
-use petgraph::visit::EdgeRef;
-use std::collections::HashSet;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::{EdgeRef, NodeFiltered};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 
 /// Check whether a call to the `sink_symbol` is reachable from the given `source_node`
 /// through a path of intraprocedural edges in the control flow graph.
@@ -59,3 +59,409 @@ pub fn is_sink_call_reachable_from_source_call(
     }
     None
 }
+
+/// Compute the loops of the function, each given by the set of its blocks.
+///
+/// Loops are identified with the strongly connected components of the intraprocedural control flow graph.
+/// To find nested loops, the loop headers (i.e. blocks with predecessors outside of the loop)
+/// are removed from each loop and the remaining blocks are searched for loops again.
+/// So a nested loop is returned in addition to the loop containing it.
+pub fn get_loops(sub: &Term<Sub>) -> Vec<HashSet<Tid>> {
+    let mut graph: DiGraph<&Tid, ()> = DiGraph::new();
+    let mut block_nodes: HashMap<&Tid, NodeIndex> = HashMap::new();
+    for block in sub.term.blocks.iter() {
+        block_nodes.insert(&block.tid, graph.add_node(&block.tid));
+    }
+    for block in sub.term.blocks.iter() {
+        for target in get_intraprocedural_jump_targets(block, sub) {
+            if let Some(target_node) = block_nodes.get(target) {
+                graph.add_edge(block_nodes[&block.tid], *target_node, ());
+            }
+        }
+    }
+
+    let mut loops = Vec::new();
+    let mut worklist: Vec<HashSet<NodeIndex>> = vec![graph.node_indices().collect()];
+    while let Some(nodes) = worklist.pop() {
+        let subgraph = NodeFiltered::from_fn(&graph, |node| nodes.contains(&node));
+        for component in petgraph::algo::tarjan_scc(&subgraph) {
+            let is_loop = component.len() > 1 || graph.contains_edge(component[0], component[0]);
+            if !is_loop {
+                continue;
+            }
+            let component: HashSet<NodeIndex> = component.into_iter().collect();
+            loops.push(component.iter().map(|node| graph[*node].clone()).collect());
+            let mut headers: HashSet<NodeIndex> = component
+                .iter()
+                .filter(|node| {
+                    graph
+                        .neighbors_directed(**node, petgraph::Incoming)
+                        .any(|pred| !component.contains(&pred))
+                })
+                .copied()
+                .collect();
+            if headers.is_empty() {
+                // The loop is only reachable through the function start.
+                headers.insert(*component.iter().min().unwrap());
+            }
+            let inner_nodes: HashSet<NodeIndex> = component.difference(&headers).copied().collect();
+            if !inner_nodes.is_empty() {
+                worklist.push(inner_nodes);
+            }
+        }
+    }
+    loops
+}
+
+/// Compute the loop nesting depth of each block of the function,
+/// i.e. the number of [loops](get_loops) containing the block.
+/// Blocks not contained in any loop have a loop depth of zero.
+pub fn get_loop_depths(sub: &Term<Sub>) -> HashMap<Tid, usize> {
+    let mut loop_depths: HashMap<Tid, usize> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (block.tid.clone(), 0))
+        .collect();
+    for loop_blocks in get_loops(sub) {
+        for block_tid in loop_blocks {
+            *loop_depths.get_mut(&block_tid).unwrap() += 1;
+        }
+    }
+    loop_depths
+}
+
+/// Get the targets of all jumps of the block that do not leave the function.
+pub fn get_intraprocedural_jump_targets<'a>(
+    block: &'a Term<Blk>,
+    sub: &'a Term<Sub>,
+) -> Vec<&'a Tid> {
+    let mut targets = Vec::new();
+    for jmp in block.term.jmps.iter() {
+        match &jmp.term {
+            Jmp::Branch(target) | Jmp::CBranch { target, .. } => targets.push(target),
+            Jmp::Call {
+                return_: Some(return_),
+                ..
+            }
+            | Jmp::CallInd {
+                return_: Some(return_),
+                ..
+            }
+            | Jmp::CallOther {
+                return_: Some(return_),
+                ..
+            } => targets.push(return_),
+            Jmp::BranchInd(_) => {
+                for target_block in sub.term.blocks.iter() {
+                    if block
+                        .term
+                        .indirect_jmp_targets
+                        .contains(&target_block.tid.address)
+                    {
+                        targets.push(&target_block.tid);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    targets
+}
+
+/// The call graph of a program.
+///
+/// Only direct calls between functions of the program are contained in the call graph,
+/// i.e. calls to extern symbols and indirect calls are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CallGraph {
+    /// Maps each function to the functions it calls.
+    callees: HashMap<Tid, BTreeSet<Tid>>,
+    /// Maps each function to the functions calling it.
+    callers: HashMap<Tid, BTreeSet<Tid>>,
+}
+
+impl CallGraph {
+    /// Generate the call graph of the given program.
+    pub fn new(program: &Term<Program>) -> CallGraph {
+        let sub_tids: HashSet<&Tid> = program.term.subs.iter().map(|sub| &sub.tid).collect();
+        let mut call_graph = CallGraph::default();
+        for sub in program.term.subs.iter() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call { target, .. } = &jmp.term {
+                        if sub_tids.contains(target) {
+                            call_graph
+                                .callees
+                                .entry(sub.tid.clone())
+                                .or_default()
+                                .insert(target.clone());
+                            call_graph
+                                .callers
+                                .entry(target.clone())
+                                .or_default()
+                                .insert(sub.tid.clone());
+                        }
+                    }
+                }
+            }
+        }
+        call_graph
+    }
+
+    /// Get the functions called by the given function.
+    pub fn get_callees<'a>(&'a self, sub_tid: &Tid) -> impl Iterator<Item = &'a Tid> {
+        self.callees.get(sub_tid).into_iter().flatten()
+    }
+
+    /// Get the functions calling the given function.
+    pub fn get_callers<'a>(&'a self, sub_tid: &Tid) -> impl Iterator<Item = &'a Tid> {
+        self.callers.get(sub_tid).into_iter().flatten()
+    }
+
+    /// Get the sets of mutually recursive functions,
+    /// i.e. the strongly connected components of the call graph that contain a cycle.
+    ///
+    /// A single function is only contained in the result if it calls itself.
+    pub fn get_recursive_components(&self) -> Vec<BTreeSet<Tid>> {
+        let mut graph: DiGraph<&Tid, ()> = DiGraph::new();
+        let mut nodes: HashMap<&Tid, NodeIndex> = HashMap::new();
+        for (caller, callees) in self.callees.iter() {
+            for callee in callees.iter() {
+                let caller_node = *nodes
+                    .entry(caller)
+                    .or_insert_with(|| graph.add_node(caller));
+                let callee_node = *nodes
+                    .entry(callee)
+                    .or_insert_with(|| graph.add_node(callee));
+                graph.add_edge(caller_node, callee_node, ());
+            }
+        }
+        petgraph::algo::tarjan_scc(&graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || graph.contains_edge(component[0], component[0])
+            })
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|node| graph[node].clone())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Compute the distances of functions to the given start functions in the call graph of the program,
+/// i.e. the minimal number of calls necessary to reach the function.
+///
+/// If `directed` is set to `false`, calls can also be followed in reverse direction,
+/// i.e. the distance is the minimal number of calls and returns necessary to reach the function.
+/// Functions not reachable from the start functions are not contained in the result.
+pub fn get_call_graph_distances(
+    program: &Term<Program>,
+    start_subs: &[Tid],
+    directed: bool,
+) -> HashMap<Tid, usize> {
+    let sub_tids: HashSet<&Tid> = program.term.subs.iter().map(|sub| &sub.tid).collect();
+    let call_graph = CallGraph::new(program);
+
+    let mut distances: HashMap<Tid, usize> = HashMap::new();
+    let mut queue: VecDeque<(&Tid, usize)> = VecDeque::new();
+    for start in start_subs.iter().filter(|tid| sub_tids.contains(tid)) {
+        if distances.insert(start.clone(), 0).is_none() {
+            queue.push_back((start, 0));
+        }
+    }
+    while let Some((sub_tid, distance)) = queue.pop_front() {
+        let mut neighbors: Vec<&Tid> = call_graph.get_callees(sub_tid).collect();
+        if !directed {
+            neighbors.extend(call_graph.get_callers(sub_tid));
+        }
+        for neighbor in neighbors {
+            if !distances.contains_key(neighbor) {
+                distances.insert(neighbor.clone(), distance + 1);
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Compute the immediate dominator of each block of the function
+/// with respect to the intraprocedural control flow graph.
+///
+/// The first block of the function is assumed to be the function entry.
+/// The entry block and blocks not reachable from it are not contained in the result.
+pub fn get_immediate_dominators(sub: &Term<Sub>) -> HashMap<Tid, Tid> {
+    let entry_block = match sub.term.blocks.first() {
+        Some(block) => block,
+        None => return HashMap::new(),
+    };
+    let mut graph: DiGraph<&Tid, ()> = DiGraph::new();
+    let mut block_nodes: HashMap<&Tid, NodeIndex> = HashMap::new();
+    for block in sub.term.blocks.iter() {
+        block_nodes.insert(&block.tid, graph.add_node(&block.tid));
+    }
+    for block in sub.term.blocks.iter() {
+        for target in get_intraprocedural_jump_targets(block, sub) {
+            if let Some(target_node) = block_nodes.get(target) {
+                graph.add_edge(block_nodes[&block.tid], *target_node, ());
+            }
+        }
+    }
+    let dominators = petgraph::algo::dominators::simple_fast(&graph, block_nodes[&entry_block.tid]);
+    graph
+        .node_indices()
+        .filter_map(|node| {
+            dominators
+                .immediate_dominator(node)
+                .map(|dominator| (graph[node].clone(), graph[dominator].clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::Expression;
+
+    fn mock_block(name: &str, jmps: Vec<Term<Jmp>>) -> Term<Blk> {
+        let mut block = Blk::mock();
+        block.tid = Tid::new(name);
+        block.term.jmps = jmps;
+        block
+    }
+
+    fn cbranch(target: &str) -> Term<Jmp> {
+        Term {
+            tid: Tid::new(format!("cbranch_{}", target)),
+            term: Jmp::CBranch {
+                target: Tid::new(target),
+                condition: Expression::var("ZF"),
+            },
+        }
+    }
+
+    #[test]
+    fn loop_depths() {
+        // entry -> outer <-> inner (self loop) -> exit
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            mock_block("entry", vec![Jmp::branch("entry_jmp", "outer")]),
+            mock_block(
+                "outer",
+                vec![cbranch("exit"), Jmp::branch("outer_jmp", "inner")],
+            ),
+            mock_block(
+                "inner",
+                vec![cbranch("inner"), Jmp::branch("inner_jmp", "outer")],
+            ),
+            mock_block("exit", Vec::new()),
+        ];
+        let loop_depths = get_loop_depths(&sub);
+        assert_eq!(loop_depths[&Tid::new("entry")], 0);
+        assert_eq!(loop_depths[&Tid::new("outer")], 1);
+        assert_eq!(loop_depths[&Tid::new("inner")], 2);
+        assert_eq!(loop_depths[&Tid::new("exit")], 0);
+
+        let loops = get_loops(&sub);
+        assert_eq!(loops.len(), 2);
+        assert!(loops.contains(
+            &vec![Tid::new("outer"), Tid::new("inner")]
+                .into_iter()
+                .collect()
+        ));
+        assert!(loops.contains(&vec![Tid::new("inner")].into_iter().collect()));
+    }
+
+    #[test]
+    fn call_graph_distances() {
+        let mut program = Program::mock_empty();
+        for (caller, callee) in [("main", "a"), ("a", "b"), ("c", "b")] {
+            let mut sub = Sub::mock(caller);
+            sub.term.blocks = vec![mock_block(
+                &format!("{}_block", caller),
+                vec![Jmp::call(&format!("{}_call", caller), callee, None)],
+            )];
+            program.subs.push(sub);
+        }
+        program.subs.push(Sub::mock("b"));
+        let program = Term {
+            tid: Tid::new("program"),
+            term: program,
+        };
+        let distances = get_call_graph_distances(&program, &[Tid::new("main")], true);
+        assert_eq!(distances[&Tid::new("b")], 2);
+        assert!(!distances.contains_key(&Tid::new("c")));
+        let distances = get_call_graph_distances(&program, &[Tid::new("main")], false);
+        assert_eq!(distances[&Tid::new("c")], 3);
+
+        let call_graph = CallGraph::new(&program);
+        let callers: Vec<&Tid> = call_graph.get_callers(&Tid::new("b")).collect();
+        assert_eq!(callers, vec![&Tid::new("a"), &Tid::new("c")]);
+        assert_eq!(call_graph.get_callees(&Tid::new("b")).count(), 0);
+        assert!(call_graph.get_recursive_components().is_empty());
+    }
+
+    #[test]
+    fn recursive_components() {
+        let mut program = Program::mock_empty();
+        for (caller, callees) in [
+            ("main", vec!["a", "c"]),
+            ("a", vec!["b"]),
+            ("b", vec!["a"]),
+            ("c", vec!["c"]),
+        ]
+        .iter()
+        {
+            let mut sub = Sub::mock(caller);
+            sub.term.blocks = callees
+                .iter()
+                .map(|callee| {
+                    mock_block(
+                        &format!("{}_{}_block", caller, callee),
+                        vec![Jmp::call(
+                            &format!("{}_{}_call", caller, callee),
+                            callee,
+                            None,
+                        )],
+                    )
+                })
+                .collect();
+            program.subs.push(sub);
+        }
+        let program = Term {
+            tid: Tid::new("program"),
+            term: program,
+        };
+        let mut components = CallGraph::new(&program).get_recursive_components();
+        components.sort();
+        let expected: Vec<BTreeSet<Tid>> = vec![
+            vec![Tid::new("a"), Tid::new("b")].into_iter().collect(),
+            vec![Tid::new("c")].into_iter().collect(),
+        ];
+        assert_eq!(components, expected);
+    }
+
+    #[test]
+    fn immediate_dominators() {
+        // entry -> (left | right) -> exit, unreachable -> exit
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![
+            mock_block(
+                "entry",
+                vec![cbranch("left"), Jmp::branch("entry_jmp", "right")],
+            ),
+            mock_block("left", vec![Jmp::branch("left_jmp", "exit")]),
+            mock_block("right", vec![Jmp::branch("right_jmp", "exit")]),
+            mock_block("exit", Vec::new()),
+            mock_block("unreachable", vec![Jmp::branch("unreachable_jmp", "exit")]),
+        ];
+        let dominators = get_immediate_dominators(&sub);
+        assert_eq!(dominators[&Tid::new("left")], Tid::new("entry"));
+        assert_eq!(dominators[&Tid::new("exit")], Tid::new("entry"));
+        assert!(!dominators.contains_key(&Tid::new("entry")));
+        assert!(!dominators.contains_key(&Tid::new("unreachable")));
+    }
+}
@@ -0,0 +1,293 @@
+//! Taint contracts for inter-process communication between binaries.
+//!
+//! Firmware often consists of several binaries communicating over named channels,
+//! e.g. sockets, message queues or shared configuration stores.
+//! A contract declares that the data sent by a producer function (e.g. `write_to_socket("cfg", data)`)
+//! is received by a consumer function (e.g. `read_from_socket("cfg", buffer)`) on the same channel.
+//!
+//! The binaries of a multi-binary session are analyzed one after another.
+//! For each binary, the calls to producer and consumer functions with constant channel names are collected
+//! and added to a session file shared by all analysis runs of the session.
+//! Consumer calls on channels that another binary of the session produces are then treated as taint sources
+//! by the CWE-129 check, whose warnings name the producer calls as their origins.
+//! Note that only producers of binaries analyzed earlier in the session are known,
+//! so binaries should be analyzed in the order of the data flow.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::State;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map, get_variadic_parameter};
+use crate::AnalysisResults;
+use std::collections::BTreeMap;
+
+/// An extern function sending or receiving data over a named channel.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Endpoint {
+    /// The name of the function.
+    pub symbol: String,
+    /// The index of the parameter containing the name of the channel.
+    pub channel_parameter: usize,
+    /// The index of the parameter pointing to the sent or received data.
+    pub data_parameter: usize,
+}
+
+/// A contract declaring that the data sent by the producer function
+/// is received by the consumer function on the same channel.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Contract {
+    /// The function sending the data.
+    pub producer: Endpoint,
+    /// The function receiving the data.
+    pub consumer: Endpoint,
+}
+
+/// The configuration of the IPC contracts,
+/// read from the `IpcContracts` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The contracts between producer and consumer functions.
+    pub contracts: Vec<Contract>,
+}
+
+/// A call to a producer or consumer function with a constant channel name.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct EndpointSite {
+    /// The name of the binary containing the call.
+    pub binary: String,
+    /// The name of the channel.
+    pub channel: String,
+    /// The name of the called function.
+    pub symbol: String,
+    /// The address of the call.
+    pub address: String,
+    /// `true` for calls to producer functions, `false` for calls to consumer functions.
+    pub is_producer: bool,
+}
+
+impl std::fmt::Display for EndpointSite {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{}: {}(\"{}\") at {}",
+            self.binary, self.symbol, self.channel, self.address
+        )
+    }
+}
+
+/// A consumer function receiving untrusted data on a channel produced by other binaries of the session.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct IpcSource {
+    /// The consumer function.
+    pub consumer: Endpoint,
+    /// The name of the channel.
+    pub channel: String,
+    /// The producer calls in other binaries sending data on the channel.
+    pub origins: Vec<String>,
+}
+
+/// The endpoint sites of all binaries analyzed in a multi-binary session.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct Session {
+    /// The endpoint sites of the analyzed binaries.
+    pub endpoint_sites: Vec<EndpointSite>,
+}
+
+impl Session {
+    /// Add the endpoint sites of a binary to the session.
+    /// Sites found in a previous analysis of the binary are replaced.
+    pub fn add_binary(&mut self, binary: &str, endpoint_sites: Vec<EndpointSite>) {
+        self.endpoint_sites.retain(|site| site.binary != binary);
+        self.endpoint_sites.extend(endpoint_sites);
+    }
+
+    /// Get the consumer calls of the given binary that receive data produced by other binaries of the session.
+    pub fn get_ipc_sources(&self, binary: &str, config: &Config) -> Vec<IpcSource> {
+        let mut sources = Vec::new();
+        for contract in config.contracts.iter() {
+            let mut origins_of_channel: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+            for site in self.endpoint_sites.iter().filter(|site| {
+                site.is_producer && site.binary != binary && site.symbol == contract.producer.symbol
+            }) {
+                origins_of_channel
+                    .entry(&site.channel)
+                    .or_default()
+                    .push(site.to_string());
+            }
+            for (channel, origins) in origins_of_channel {
+                let is_consumed = self.endpoint_sites.iter().any(|site| {
+                    !site.is_producer
+                        && site.binary == binary
+                        && site.symbol == contract.consumer.symbol
+                        && site.channel == channel
+                });
+                if is_consumed {
+                    sources.push(IpcSource {
+                        consumer: contract.consumer.clone(),
+                        channel: channel.to_string(),
+                        origins,
+                    });
+                }
+            }
+        }
+        sources
+    }
+}
+
+/// Get the channel name of a call to an endpoint function
+/// if the channel parameter is a constant string in the memory image of the binary.
+pub fn get_channel_name(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    symbol: &ExternSymbol,
+    endpoint: &Endpoint,
+) -> Option<String> {
+    let channel_address = state
+        .eval_parameter_arg(
+            &get_variadic_parameter(project, symbol, endpoint.channel_parameter),
+            &project.stack_pointer_register,
+            runtime_memory_image,
+        )
+        .ok()?
+        .try_to_bitvec()
+        .ok()?;
+    runtime_memory_image
+        .read_string_until_null_terminator(&channel_address)
+        .ok()
+        .map(|channel| channel.to_string())
+}
+
+/// Find all calls to producer and consumer functions of the contracts with constant channel names.
+pub fn find_endpoint_sites(
+    analysis_results: &AnalysisResults,
+    config: &Config,
+    binary: &str,
+) -> Vec<EndpointSite> {
+    let project = analysis_results.project;
+    let pointer_inference = match analysis_results.pointer_inference() {
+        Some(pointer_inference) => pointer_inference,
+        None => return Vec::new(),
+    };
+    let mut endpoints: BTreeMap<&str, (&Endpoint, bool)> = BTreeMap::new();
+    for contract in config.contracts.iter() {
+        endpoints.insert(&contract.producer.symbol, (&contract.producer, true));
+        endpoints.insert(&contract.consumer.symbol, (&contract.consumer, false));
+    }
+    let symbols: Vec<String> = endpoints.keys().map(|symbol| symbol.to_string()).collect();
+    let symbol_map = get_symbol_map(project, &symbols);
+    let mut endpoint_sites = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for (_block, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            let (endpoint, is_producer) = endpoints[symbol.name.as_str()];
+            if let Some(channel) =
+                pointer_inference
+                    .get_state_at_jmp_tid(&jmp.tid)
+                    .and_then(|state| {
+                        get_channel_name(
+                            project,
+                            analysis_results.runtime_memory_image,
+                            state,
+                            symbol,
+                            endpoint,
+                        )
+                    })
+            {
+                endpoint_sites.push(EndpointSite {
+                    binary: binary.to_string(),
+                    channel,
+                    symbol: symbol.name.clone(),
+                    address: jmp.tid.address.clone(),
+                    is_producer,
+                });
+            }
+        }
+    }
+    endpoint_sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipc_sources() {
+        let endpoint = |symbol: &str| Endpoint {
+            symbol: symbol.to_string(),
+            channel_parameter: 0,
+            data_parameter: 1,
+        };
+        let config = Config {
+            contracts: vec![Contract {
+                producer: endpoint("write_to_socket"),
+                consumer: endpoint("read_from_socket"),
+            }],
+        };
+        let site = |binary: &str, channel: &str, is_producer: bool| EndpointSite {
+            binary: binary.to_string(),
+            channel: channel.to_string(),
+            symbol: if is_producer {
+                "write_to_socket".to_string()
+            } else {
+                "read_from_socket".to_string()
+            },
+            address: "00101234".to_string(),
+            is_producer,
+        };
+        let mut session = Session::default();
+        session.add_binary(
+            "daemon",
+            vec![site("daemon", "cfg", true), site("daemon", "status", false)],
+        );
+        session.add_binary(
+            "webui",
+            vec![site("webui", "cfg", false), site("webui", "log", false)],
+        );
+        let sources = session.get_ipc_sources("webui", &config);
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].channel, "cfg");
+        assert_eq!(sources[0].consumer, endpoint("read_from_socket"));
+        assert_eq!(
+            sources[0].origins,
+            vec!["daemon: write_to_socket(\"cfg\") at 00101234".to_string()]
+        );
+        // The daemon does not consume any channel produced by another binary.
+        assert!(session.get_ipc_sources("daemon", &config).is_empty());
+        // Re-analyzing a binary replaces its endpoint sites.
+        session.add_binary("daemon", Vec::new());
+        assert!(session.get_ipc_sources("webui", &config).is_empty());
+    }
+
+    #[test]
+    fn config_parsing() {
+        use crate::utils::parse_config;
+        let config: Config = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "contracts": [{
+                "producer": {"symbol": "write_to_socket", "channel_parameter": 0, "data_parameter": 1},
+                "consumer": {"symbol": "read_from_socket", "channel_parameter": 0, "data_parameter": 1}
+            }]
+        }))
+        .unwrap();
+        assert_eq!(config.contracts[0].consumer.symbol, "read_from_socket");
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "contracts": [{
+                "producer": {"symbol": "write_to_socket", "channel": 0, "data_parameter": 1},
+                "consumer": {"symbol": "read_from_socket", "channel_parameter": 0, "data_parameter": 1}
+            }]
+        }))
+        .is_err());
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "contracts": [{
+                "producer": {"symbol": "write_to_socket", "channel_parameter": 0, "data_parameter": 1}
+            }]
+        }))
+        .is_err());
+        assert!(parse_config::<Config>(&serde_json::json!({"contract": []})).is_err());
+    }
+}
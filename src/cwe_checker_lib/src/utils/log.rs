@@ -1,6 +1,8 @@
 //! Structs and functions for generating log messages and CWE warnings.
 
 use crate::prelude::*;
+use crate::utils::report::{render_report, ReportFormat};
+use std::collections::BTreeMap;
 use std::thread::JoinHandle;
 
 /// A CWE warning message.
@@ -24,6 +26,34 @@ pub struct CweWarning {
     /// Should contain all essential information necessary to understand the warning,
     /// including the address in the binary for which the warning was generated.
     pub description: String,
+    /// An identifier of the root cause of the warning,
+    /// e.g. the memory object or the taint source that the warning originates from.
+    /// Warnings of the same check with the same root cause get grouped by [`correlate_warnings`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_cause: Option<String>,
+    /// Warnings sharing the root cause of this warning.
+    /// Only filled by [`correlate_warnings`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<CweWarning>,
+    /// A crude estimate (between 0 and 100) of how likely the warning is reachable by an attacker.
+    /// Only filled by [`score_warnings`](crate::utils::scoring::score_warnings).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<u64>,
+    /// The stable ID of the finding, see [`get_finding_id`](crate::utils::baseline::get_finding_id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finding_id: Option<String>,
+    /// The time (in seconds since the Unix epoch) of the first run that generated the warning.
+    /// Only filled when the warnings are merged with a baseline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<u64>,
+    /// The time (in seconds since the Unix epoch) of the last run that generated the warning.
+    /// Only filled when the warnings are merged with a baseline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<u64>,
+    /// A free-form triage state set by the user, e.g. `confirmed` or `false_positive`.
+    /// It is taken over from the baseline when the warnings are merged with one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triage_state: Option<String>,
 }
 
 impl CweWarning {
@@ -41,6 +71,13 @@ impl CweWarning {
             symbols: Vec::new(),
             other: Vec::new(),
             description: description.to_string(),
+            root_cause: None,
+            related: Vec::new(),
+            score: None,
+            finding_id: None,
+            first_seen: None,
+            last_seen: None,
+            triage_state: None,
         }
     }
 
@@ -67,18 +104,62 @@ impl CweWarning {
         self.other = other;
         self
     }
+
+    /// Sets the root cause field of the CweWarning
+    pub fn root_cause(mut self, root_cause: impl ToString) -> CweWarning {
+        self.root_cause = Some(root_cause.to_string());
+        self
+    }
 }
 
 impl std::fmt::Display for CweWarning {
+    /// Print the warning followed by its related warnings, each on an indented line.
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             formatter,
             "[{}] ({}) {}",
             self.name, self.version, self.description
-        )
+        )?;
+        if let Some(score) = self.score {
+            write!(formatter, " (score: {})", score)?;
+        }
+        if let Some(triage_state) = &self.triage_state {
+            write!(formatter, " (triage state: {})", triage_state)?;
+        }
+        for related_warning in self.related.iter() {
+            write!(formatter, "\n    related: {}", related_warning)?;
+        }
+        Ok(())
     }
 }
 
+/// Group CWE warnings of the same check that share the same root cause.
+///
+/// For each group the warning with the smallest address becomes the parent finding
+/// and the other warnings of the group are moved into its `related` field.
+/// Warnings without a root cause are returned unchanged.
+pub fn correlate_warnings(cwes: Vec<CweWarning>) -> Vec<CweWarning> {
+    let mut groups: BTreeMap<(String, String), Vec<CweWarning>> = BTreeMap::new();
+    let mut correlated_cwes = Vec::new();
+    for cwe in cwes {
+        match cwe.root_cause.clone() {
+            Some(root_cause) => groups
+                .entry((cwe.name.clone(), root_cause))
+                .or_default()
+                .push(cwe),
+            None => correlated_cwes.push(cwe),
+        }
+    }
+    for mut group in groups.into_values() {
+        group.sort_by(|cwe1, cwe2| cwe1.addresses.cmp(&cwe2.addresses));
+        let mut group = group.into_iter();
+        let mut parent = group.next().unwrap();
+        parent.related.extend(group);
+        correlated_cwes.push(parent);
+    }
+    correlated_cwes
+}
+
 /// A generic log message.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
 pub struct LogMessage {
@@ -179,15 +260,12 @@ pub fn print_all_messages(
     for log in logs {
         println!("{}", log);
     }
-    let output: String = if emit_json {
-        serde_json::to_string_pretty(&cwes).unwrap()
+    let format = if emit_json {
+        ReportFormat::Json
     } else {
-        cwes.iter()
-            .map(|cwe| format!("{}", cwe))
-            .collect::<Vec<String>>()
-            .join("\n")
-            + "\n"
+        ReportFormat::Text
     };
+    let output = render_report(&cwes, format);
     if let Some(file_path) = out_path {
         std::fs::write(file_path, output).unwrap();
     } else {
@@ -202,7 +280,7 @@ pub enum LogThreadMsg {
     /// A normal log message.
     Log(LogMessage),
     /// A CWE warning
-    Cwe(CweWarning),
+    Cwe(Box<CweWarning>),
     /// If the log collector thread receives this signal,
     /// it should stop receiving new messages
     /// and instead terminate and return the collected messages prior to receiving the termination signal.
@@ -285,3 +363,36 @@ impl LogThread {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_correlation() {
+        let warning = |name: &str, address: &str, root_cause: Option<&str>| {
+            let mut warning = CweWarning::new(name, "0.1", address).addresses(vec![address.into()]);
+            warning.root_cause = root_cause.map(|root_cause| root_cause.to_string());
+            warning
+        };
+        let cwes = vec![
+            warning("CWE416", "0x30", Some("object")),
+            warning("CWE416", "0x10", Some("object")),
+            warning("CWE415", "0x20", Some("object")),
+            warning("CWE416", "0x40", None),
+        ];
+        let correlated = correlate_warnings(cwes);
+        assert_eq!(correlated.len(), 3);
+        assert_eq!(correlated[0].addresses, vec!["0x40".to_string()]);
+        let parent = correlated
+            .iter()
+            .find(|cwe| cwe.name == "CWE416" && !cwe.related.is_empty())
+            .unwrap();
+        assert_eq!(parent.addresses, vec!["0x10".to_string()]);
+        assert_eq!(parent.related.len(), 1);
+        assert_eq!(
+            format!("{}", parent),
+            "[CWE416] (0.1) 0x10\n    related: [CWE416] (0.1) 0x30"
+        );
+    }
+}
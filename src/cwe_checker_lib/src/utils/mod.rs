@@ -1,11 +1,27 @@
 //! This module contains various utility modules and helper functions.
 
+pub mod analysis_profile;
+pub mod annotations;
+pub mod baseline;
+pub mod bench;
 pub mod binary;
+pub mod corroboration;
+pub mod coverage_map;
+pub mod extern_semantics;
+pub mod fact_export;
+pub mod format_string;
 pub mod graph_utils;
+pub mod ipc_contracts;
 pub mod log;
+pub mod report;
+pub mod safe_wrappers;
+pub mod scoring;
 pub mod symbol_utils;
+pub mod taint_sources;
+pub mod test_case_extract;
 
 use crate::prelude::*;
+use serde::de::DeserializeOwned;
 
 /// Get the contents of a configuration file.
 pub fn read_config_file(filename: &str) -> serde_json::Value {
@@ -18,6 +34,61 @@ pub fn read_config_file(filename: &str) -> serde_json::Value {
     serde_json::from_str(&config_file).unwrap()
 }
 
+/// Parse the configuration of a check or an analysis into its configuration struct.
+///
+/// Keys starting with `_comment` are ignored.
+/// Optional settings (marked with `#[serde(default)]`) that are missing in the configuration get their default values,
+/// while missing required settings are an error.
+/// A missing configuration (`null`) is treated like an empty configuration.
+/// Unknown settings are rejected if the configuration struct is marked with `deny_unknown_fields`.
+pub fn parse_config<T: DeserializeOwned>(config: &serde_json::Value) -> Result<T, Error> {
+    let config = match config {
+        serde_json::Value::Null => serde_json::Value::Object(serde_json::Map::new()),
+        serde_json::Value::Object(settings) => serde_json::Value::Object(
+            settings
+                .iter()
+                .filter(|(key, _)| !key.starts_with("_comment"))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ),
+        _ => return Err(anyhow!("Configuration is not a JSON object")),
+    };
+    Ok(serde_json::from_value(config)?)
+}
+
+/// Check whether the configuration can be parsed into the configuration struct `T`.
+pub fn validate_config<T: DeserializeOwned>(config: &serde_json::Value) -> Result<(), Error> {
+    parse_config::<T>(config).map(|_| ())
+}
+
+/// Get the default values of all optional settings of the configuration struct `T`.
+///
+/// Required settings are omitted, since their values always come from the configuration itself.
+/// A setting is required if the default configuration without it cannot be parsed.
+pub fn default_config<T: Default + Serialize + DeserializeOwned>() -> serde_json::Value {
+    let mut defaults = serde_json::to_value(T::default()).unwrap();
+    if let serde_json::Value::Object(settings) = &mut defaults {
+        let required_settings: Vec<String> = settings
+            .keys()
+            .filter(|key| {
+                let mut remaining_settings = settings.clone();
+                remaining_settings.remove(*key);
+                serde_json::from_value::<T>(serde_json::Value::Object(remaining_settings)).is_err()
+            })
+            .cloned()
+            .collect();
+        for key in required_settings {
+            settings.remove(&key);
+        }
+    }
+    defaults
+}
+
+/// The configuration struct of checks without any settings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct EmptyConfig {}
+
 /// Get the folder path to a Ghidra plugin bundled with the cwe_checker.
 pub fn get_ghidra_plugin_path(plugin_name: &str) -> std::path::PathBuf {
     let project_dirs = directories::ProjectDirs::from("", "", "cwe_checker")
@@ -44,3 +115,91 @@ pub fn get_binary_base_address(binary: &[u8]) -> Result<u64, Error> {
         _ => Err(anyhow!("Binary type not yet supported")),
     }
 }
+
+/// Get the name of the operating system that the binary was compiled for.
+///
+/// ELF binaries are assumed to be Linux binaries unless the ELF header indicates FreeBSD.
+pub fn get_binary_operating_system(binary: &[u8]) -> Result<&'static str, Error> {
+    use goblin::Object;
+    match Object::parse(binary)? {
+        Object::Elf(elf_file) => match elf_file.header.e_ident[goblin::elf::header::EI_OSABI] {
+            9 => Ok("freebsd"), // The OS/ABI value for FreeBSD
+
+            _ => Ok("linux"),
+        },
+        Object::PE(_) => Ok("windows"),
+        _ => Err(anyhow!("Binary type not yet supported")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+    #[serde(deny_unknown_fields)]
+    struct TestConfig {
+        symbols: Vec<String>,
+        #[serde(default)]
+        max_steps: u64,
+    }
+
+    #[test]
+    fn config_parsing() {
+        let config: TestConfig = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "symbols": ["malloc"]
+        }))
+        .unwrap();
+        assert_eq!(config.symbols, vec!["malloc".to_string()]);
+        assert_eq!(config.max_steps, 0);
+        // Required settings must not be missing.
+        assert!(validate_config::<TestConfig>(&serde_json::Value::Null).is_err());
+        assert!(validate_config::<TestConfig>(&serde_json::json!({"max_steps": 5})).is_err());
+        assert!(validate_config::<TestConfig>(&serde_json::json!({"max_step": 5})).is_err());
+        assert!(validate_config::<TestConfig>(&serde_json::json!({"max_steps": "5"})).is_err());
+        assert!(validate_config::<TestConfig>(&serde_json::json!(["malloc"])).is_err());
+        assert_eq!(
+            default_config::<TestConfig>(),
+            serde_json::json!({"max_steps": 0})
+        );
+    }
+
+    #[test]
+    fn bundled_config_is_valid() {
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../config.json")).unwrap();
+        for module in crate::get_modules() {
+            if let Err(error) = (module.validate_config)(&config[module.name]) {
+                panic!("Invalid configuration of {}: {}", module.name, error);
+            }
+        }
+        parse_config::<crate::intermediate_representation::NoReturnConfig>(&config["NoReturn"])
+            .unwrap();
+        parse_config::<crate::intermediate_representation::SymbolAliasesConfig>(
+            &config["SymbolAliases"],
+        )
+        .unwrap();
+        parse_config::<crate::intermediate_representation::OrdinalImportsConfig>(
+            &config["OrdinalImports"],
+        )
+        .unwrap();
+        parse_config::<crate::intermediate_representation::NormalizationConfig>(
+            &config["Normalization"],
+        )
+        .unwrap();
+        parse_config::<crate::intermediate_representation::SyscallsConfig>(&config["Syscalls"])
+            .unwrap();
+        parse_config::<crate::intermediate_representation::TaskEntryPointsConfig>(
+            &config["TaskEntryPoints"],
+        )
+        .unwrap();
+        parse_config::<safe_wrappers::Config>(&config["SafeWrappers"]).unwrap();
+        parse_config::<ipc_contracts::Config>(&config["IpcContracts"]).unwrap();
+        parse_config::<coverage_map::Config>(&config["CoverageMap"]).unwrap();
+        parse_config::<crate::analysis::concrete_interpreter::InterpreterConfig>(
+            &config["GlobalInitialization"],
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,317 @@
+//! Rendering and comparison of saved reports.
+//!
+//! A report is the list of CWE warnings generated by a run of the cwe_checker,
+//! as written by the `--json` output mode together with the [version of the report schema](crate::utils::baseline).
+//! Saved reports can be re-rendered in other output formats
+//! or compared with each other without analyzing the binary again.
+
+use crate::prelude::*;
+use crate::utils::baseline::{get_finding_id, SCHEMA_VERSION};
+use crate::utils::log::CweWarning;
+use std::collections::BTreeSet;
+
+/// The output formats for reports.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ReportFormat {
+    /// One line per warning, as printed by the cwe_checker by default.
+    Text,
+    /// The JSON representation of the warnings.
+    Json,
+    /// The Static Analysis Results Interchange Format (SARIF) version 2.1.0.
+    Sarif,
+    /// A standalone HTML page containing a table of the warnings.
+    Html,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<ReportFormat, Error> {
+        match format {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(anyhow!("Unknown report format: {}", format)),
+        }
+    }
+}
+
+/// Render the warnings in the given output format.
+pub fn render_report(cwes: &[CweWarning], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text => {
+            cwes.iter()
+                .map(|cwe| format!("{}", cwe))
+                .collect::<Vec<String>>()
+                .join("\n")
+                + "\n"
+        }
+        ReportFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "warnings": cwes,
+        }))
+        .unwrap(),
+        ReportFormat::Sarif => serde_json::to_string_pretty(&to_sarif(cwes)).unwrap(),
+        ReportFormat::Html => to_html(cwes),
+    }
+}
+
+/// Convert the warnings to a SARIF log with one rule per check.
+///
+/// The addresses of a warning are given as absolute addresses of the result location.
+/// Term IDs, symbols and additional information of a warning are stored as properties of the result.
+fn to_sarif(cwes: &[CweWarning]) -> serde_json::Value {
+    let rules: BTreeSet<(&str, &str)> = cwes
+        .iter()
+        .map(|cwe| (cwe.name.as_str(), cwe.version.as_str()))
+        .collect();
+    let rules: Vec<serde_json::Value> = rules
+        .into_iter()
+        .map(|(name, version)| {
+            serde_json::json!({
+                "id": name,
+                "properties": { "version": version },
+            })
+        })
+        .collect();
+    let results: Vec<serde_json::Value> = cwes
+        .iter()
+        .map(|cwe| {
+            let locations: Vec<serde_json::Value> = cwe
+                .addresses
+                .iter()
+                .filter_map(|address| {
+                    u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+                })
+                .map(|address| {
+                    serde_json::json!({
+                        "physicalLocation": { "address": { "absoluteAddress": address } }
+                    })
+                })
+                .collect();
+            let mut result = serde_json::json!({
+                "ruleId": cwe.name,
+                "level": "warning",
+                "message": { "text": cwe.description },
+                "locations": locations,
+                "properties": {
+                    "tids": cwe.tids,
+                    "symbols": cwe.symbols,
+                    "other": cwe.other,
+                },
+            });
+            if let Some(score) = cwe.score {
+                result["rank"] = serde_json::json!(score);
+            }
+            result["partialFingerprints"] = serde_json::json!({ "findingId": get_finding_id(cwe) });
+            if let Some(triage_state) = &cwe.triage_state {
+                result["properties"]["triage_state"] = serde_json::json!(triage_state);
+            }
+            result
+        })
+        .collect();
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cwe_checker",
+                    "informationUri": "https://github.com/fkie-cad/cwe_checker",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Render the warnings as a standalone HTML page containing a table of the warnings.
+fn to_html(cwes: &[CweWarning]) -> String {
+    let mut rows = String::new();
+    for cwe in cwes {
+        rows += &format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&cwe.name),
+            escape_html(&cwe.addresses.join(", ")),
+            escape_html(&cwe.symbols.join(", ")),
+            cwe.score.map(|score| score.to_string()).unwrap_or_default(),
+            escape_html(&cwe.description)
+        );
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>cwe_checker report</title></head>\n<body>\n\
+         <h1>cwe_checker report</h1>\n<p>{} warnings</p>\n<table border=\"1\">\n\
+         <tr><th>Check</th><th>Addresses</th><th>Symbols</th><th>Score</th><th>Description</th></tr>\n\
+         {}</table>\n</body>\n</html>\n",
+        cwes.len(),
+        rows
+    )
+}
+
+/// Escape the characters of the text that have a special meaning in HTML.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The differences between two reports.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ReportDiff {
+    /// Warnings only contained in the new report.
+    pub added: Vec<CweWarning>,
+    /// Warnings only contained in the old report.
+    pub removed: Vec<CweWarning>,
+}
+
+/// Compare two reports.
+///
+/// Warnings are identified by their [finding IDs](get_finding_id),
+/// so that changes to the checks do not show up as changed warnings.
+/// The warnings in the diff keep their lifecycle metadata, e.g. their triage state.
+pub fn diff_reports(old: &[CweWarning], new: &[CweWarning]) -> ReportDiff {
+    let key = get_finding_id;
+    let old_keys: BTreeSet<_> = old.iter().map(key).collect();
+    let new_keys: BTreeSet<_> = new.iter().map(key).collect();
+    ReportDiff {
+        added: new
+            .iter()
+            .filter(|cwe| !old_keys.contains(&key(cwe)))
+            .cloned()
+            .collect(),
+        removed: old
+            .iter()
+            .filter(|cwe| !new_keys.contains(&key(cwe)))
+            .cloned()
+            .collect(),
+    }
+}
+
+impl std::fmt::Display for ReportDiff {
+    /// Print the added warnings prefixed with `+` and the removed warnings prefixed with `-`.
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for cwe in self.added.iter() {
+            writeln!(formatter, "+ {}", cwe)?;
+        }
+        for cwe in self.removed.iter() {
+            writeln!(formatter, "- {}", cwe)?;
+        }
+        write!(
+            formatter,
+            "{} added, {} removed",
+            self.added.len(),
+            self.removed.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_rendering_and_diff() {
+        let old = vec![
+            CweWarning::new("CWE476", "0.3", "a").addresses(vec!["00101234".into()]),
+            CweWarning::new("CWE416", "0.3", "b <&>").addresses(vec!["00105678".into()]),
+        ];
+        let sarif = to_sarif(&old);
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"][0]["id"],
+            "CWE416"
+        );
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["address"]
+                ["absoluteAddress"],
+            0x101234
+        );
+        assert!(render_report(&old, ReportFormat::Html).contains("b &lt;&amp;&gt;"));
+        assert_eq!(
+            render_report(&old[..1], ReportFormat::Text),
+            "[CWE476] (0.3) a\n"
+        );
+
+        let new = vec![
+            CweWarning::new("CWE476", "0.4", "changed").addresses(vec!["00101234".into()]),
+            CweWarning::new("CWE190", "0.1", "c").addresses(vec!["00109abc".into()]),
+        ];
+        let diff = diff_reports(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "CWE190");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "CWE416");
+    }
+
+    #[test]
+    fn report_formats() {
+        assert_eq!(
+            "sarif".parse::<ReportFormat>().unwrap(),
+            ReportFormat::Sarif
+        );
+        assert!("xml".parse::<ReportFormat>().is_err());
+        assert!("Text".parse::<ReportFormat>().is_err());
+
+        let mut scored = CweWarning::new("CWE476", "0.3", "a")
+            .addresses(vec!["00101234".into(), "UNKNOWN".into()])
+            .tids(vec!["instr_00101234_1".into()]);
+        scored.score = Some(42);
+        scored.triage_state = Some("confirmed".to_string());
+        let cwes = vec![
+            scored,
+            CweWarning::new("CWE476", "0.3", "b"),
+            CweWarning::new("CWE190", "0.1", "c"),
+        ];
+
+        let sarif = to_sarif(&cwes);
+        let rules = sarif["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap();
+        // One rule per check, not per warning.
+        assert_eq!(rules.len(), 2);
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        // Addresses that cannot be parsed are not listed as locations.
+        assert_eq!(results[0]["locations"].as_array().unwrap().len(), 1);
+        assert_eq!(results[0]["rank"], 42);
+        assert_eq!(results[0]["properties"]["triage_state"], "confirmed");
+        assert_eq!(results[0]["properties"]["tids"][0], "instr_00101234_1");
+        assert!(results[1].get("rank").is_none());
+        assert!(results[1]["properties"].get("triage_state").is_none());
+        assert!(results[1]["locations"].as_array().unwrap().is_empty());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_report(&cwes, ReportFormat::Json)).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        let warnings: Vec<CweWarning> = serde_json::from_value(json["warnings"].clone()).unwrap();
+        assert_eq!(warnings, cwes);
+
+        let html = render_report(&cwes, ReportFormat::Html);
+        assert!(html.contains("<p>3 warnings</p>"));
+        assert!(html.contains("<td>42</td>"));
+        assert!(render_report(&[], ReportFormat::Html).contains("<p>0 warnings</p>"));
+        assert_eq!(render_report(&[], ReportFormat::Text), "\n");
+    }
+
+    #[test]
+    fn report_diff_output() {
+        let cwes = vec![
+            CweWarning::new("CWE476", "0.3", "a").addresses(vec!["00101234".into()]),
+            CweWarning::new("CWE416", "0.3", "b").addresses(vec!["00105678".into()]),
+        ];
+        let diff = diff_reports(&cwes, &cwes);
+        assert_eq!(diff, ReportDiff::default());
+        assert_eq!(diff.to_string(), "0 added, 0 removed");
+
+        let diff = diff_reports(&cwes[..1], &cwes[1..]);
+        assert_eq!(
+            diff.to_string(),
+            "+ [CWE416] (0.3) b\n- [CWE476] (0.3) a\n1 added, 1 removed"
+        );
+        // Warnings at different addresses are different findings.
+        let moved = vec![CweWarning::new("CWE476", "0.3", "a").addresses(vec!["00109999".into()])];
+        let diff = diff_reports(&cwes[..1], &moved);
+        assert_eq!((diff.added.len(), diff.removed.len()), (1, 1));
+    }
+}
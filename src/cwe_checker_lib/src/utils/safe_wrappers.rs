@@ -0,0 +1,370 @@
+//! Vetted safe wrappers and their contracts.
+//!
+//! Projects often wrap dangerous functions in audited wrappers, e.g. a `safe_strcpy` that checks the buffer size.
+//! Warnings generated inside the body of such a wrapper are false positives if the wrapper is only called
+//! in accordance with its documented contract.
+//! Functions configured as safe wrappers in the `SafeWrappers` section of the configuration file are handled as follows:
+//! - Warnings whose first address lies inside the body of a wrapper are suppressed.
+//! - At each call to a wrapper the contract of the wrapper is checked using the results of the
+//!   [Pointer Inference analysis](crate::analysis::pointer_inference).
+//!   A contract declares maximum values of parameters (e.g. of a length parameter)
+//!   and pairs of buffer and size parameters where the size must not exceed the size of the buffer.
+//!   If the contract may be violated, a warning is generated at the call site,
+//!   i.e. the reporting is shifted from the wrapper to the caller.
+//!
+//! Parameters of the wrappers are located according to the standard calling convention of the binary.
+
+use crate::abstract_domain::{AbstractDomain, TryToInterval};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::CweWarning;
+use crate::utils::symbol_utils::get_calling_convention_parameter;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The version number of the contract checks, used as the version of the generated warnings.
+const VERSION: &str = "0.1";
+
+/// A parameter of a wrapper together with its documented maximum value.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MaxValue {
+    /// The index of the parameter.
+    pub parameter: usize,
+    /// The maximum (unsigned) value of the parameter.
+    pub max_value: u64,
+}
+
+/// A buffer parameter of a wrapper together with the parameter containing the size of the buffer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BufferSize {
+    /// The index of the parameter pointing to the buffer.
+    pub buffer_parameter: usize,
+    /// The index of the parameter that must not exceed the size of the buffer.
+    pub size_parameter: usize,
+}
+
+/// A vetted safe wrapper function together with its contract.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Wrapper {
+    /// The name of the wrapper function.
+    pub name: String,
+    /// The name of the check under which contract violations are reported, e.g. `CWE120`.
+    pub cwe: String,
+    /// Parameters with documented maximum values.
+    #[serde(default)]
+    pub max_values: Vec<MaxValue>,
+    /// Buffer parameters whose size must not be exceeded by the corresponding size parameter.
+    #[serde(default)]
+    pub buffer_sizes: Vec<BufferSize>,
+}
+
+/// The configuration of the safe wrappers,
+/// read from the `SafeWrappers` section of *config.json*.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The vetted safe wrapper functions.
+    pub wrappers: Vec<Wrapper>,
+}
+
+/// Get the functions of the project that are configured as safe wrappers,
+/// mapped to the configuration of the wrapper.
+fn get_wrapper_subs<'a>(
+    project: &'a Project,
+    config: &'a Config,
+) -> BTreeMap<&'a Tid, (&'a Term<Sub>, &'a Wrapper)> {
+    project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter_map(|sub| {
+            config
+                .wrappers
+                .iter()
+                .find(|wrapper| wrapper.name == sub.term.name)
+                .map(|wrapper| (&sub.tid, (sub, wrapper)))
+        })
+        .collect()
+}
+
+/// Remove all warnings whose first address lies inside the body of a safe wrapper.
+pub fn suppress_warnings_in_wrappers(
+    project: &Project,
+    cwes: Vec<CweWarning>,
+    config: &Config,
+) -> Vec<CweWarning> {
+    let mut wrapper_addresses: BTreeSet<&str> = BTreeSet::new();
+    for (sub, _wrapper) in get_wrapper_subs(project, config).values() {
+        for block in sub.term.blocks.iter() {
+            wrapper_addresses.insert(&block.tid.address);
+            wrapper_addresses.extend(block.term.defs.iter().map(|def| def.tid.address.as_str()));
+            wrapper_addresses.extend(block.term.jmps.iter().map(|jmp| jmp.tid.address.as_str()));
+        }
+    }
+    cwes.into_iter()
+        .filter(|cwe| match cwe.addresses.first() {
+            Some(address) => !wrapper_addresses.contains(address.as_str()),
+            None => true,
+        })
+        .collect()
+}
+
+/// Get the largest possible number of bytes between the target of the pointer and the end of the pointed-to buffer.
+///
+/// For dynamic stack allocations in the current stack frame the end of the buffer is the end of the allocation.
+/// For other targets in the stack frame of the current function the end of the buffer is the stack frame base.
+/// For other memory objects of known size it is the end of the object.
+/// Returns `None` if the pointer target is not unique or the size of the buffer is unknown.
+fn get_buffer_size(state: &State, pointer: &Data) -> Option<i64> {
+    let pointer_domain = match pointer {
+        Data::Pointer(pointer_domain) if pointer_domain.targets().len() == 1 => pointer_domain,
+        _ => return None,
+    };
+    let (id, offset) = pointer_domain.targets().iter().next().unwrap();
+    if *id == state.stack_id {
+        if let Some((_, allocation)) = state.get_stack_allocation(offset) {
+            let (min_offset, _) = offset.try_to_offset_interval().ok()?;
+            return Some(allocation.end - min_offset);
+        }
+        return state
+            .get_stack_space_until_frame_base(pointer)
+            .map(|size| size as i64);
+    }
+    if !matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap))) {
+        return None;
+    }
+    let object_size = state.memory.get_object_size(id)?;
+    if object_size.is_top() {
+        return None;
+    }
+    let (_, max_object_size) = object_size.try_to_offset_interval().ok()?;
+    let (min_offset, _) = offset.try_to_offset_interval().ok()?;
+    if min_offset < 0 {
+        return None;
+    }
+    Some(max_object_size - min_offset)
+}
+
+/// Check the contract of the wrapper at a call to it.
+/// Returns the descriptions of all possibly violated parts of the contract.
+fn check_contract(
+    project: &Project,
+    runtime_memory_image: &RuntimeMemoryImage,
+    state: &State,
+    wrapper: &Wrapper,
+) -> Vec<String> {
+    let calling_convention = match project.get_standard_calling_convention() {
+        Some(calling_convention) => calling_convention,
+        None => return Vec::new(),
+    };
+    let eval_parameter = |index: usize| {
+        state
+            .eval_parameter_arg(
+                &get_calling_convention_parameter(project, calling_convention, index),
+                &project.stack_pointer_register,
+                runtime_memory_image,
+            )
+            .ok()
+    };
+    // The largest possible value of a parameter. Unbounded values are not reported.
+    let get_max_value = |index: usize| match eval_parameter(index)? {
+        Data::Value(value) if !value.is_top() => {
+            value.try_to_offset_interval().ok().map(|(_, max)| max)
+        }
+        _ => None,
+    };
+    let mut violations = Vec::new();
+    for bound in wrapper.max_values.iter() {
+        if let Some(max_value) = get_max_value(bound.parameter) {
+            if max_value < 0 || max_value as u64 > bound.max_value {
+                violations.push(format!(
+                    "parameter {} may be {} (documented maximum {})",
+                    bound.parameter, max_value, bound.max_value
+                ));
+            }
+        }
+    }
+    for buffer_size in wrapper.buffer_sizes.iter() {
+        let size = get_max_value(buffer_size.size_parameter);
+        let buffer = eval_parameter(buffer_size.buffer_parameter)
+            .and_then(|pointer| get_buffer_size(state, &pointer));
+        if let (Some(size), Some(buffer)) = (size, buffer) {
+            if size > buffer {
+                violations.push(format!(
+                    "parameter {} may be {} but the buffer of parameter {} has size {}",
+                    buffer_size.size_parameter, size, buffer_size.buffer_parameter, buffer
+                ));
+            }
+        }
+    }
+    violations
+}
+
+/// Check the contracts of the safe wrappers at all calls to them
+/// and generate a warning at each call that may violate the contract of the called wrapper.
+pub fn check_wrapper_contracts(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+    config: &Config,
+) -> Vec<CweWarning> {
+    let wrapper_subs = get_wrapper_subs(project, config);
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                let wrapper = match &jmp.term {
+                    Jmp::Call { target, .. } => match wrapper_subs.get(target) {
+                        Some((_sub, wrapper)) => wrapper,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                let state = match pointer_inference.get_state_at_jmp_tid(&jmp.tid) {
+                    Some(state) => state,
+                    None => continue,
+                };
+                let violations = check_contract(project, runtime_memory_image, state, wrapper);
+                if !violations.is_empty() {
+                    cwe_warnings.push(
+                        CweWarning::new(
+                            &wrapper.cwe,
+                            VERSION,
+                            format!(
+                                "(Safe Wrapper Contract Violation) Call to {} at {} in {} may violate the contract of the wrapper: {}",
+                                wrapper.name,
+                                jmp.tid.address,
+                                sub.term.display_name(),
+                                violations.join(", ")
+                            ),
+                        )
+                        .tids(vec![format!("{}", jmp.tid)])
+                        .addresses(vec![jmp.tid.address.clone()])
+                        .symbols(vec![wrapper.name.clone()])
+                        .other(vec![vec!["safe_wrapper".to_string(), wrapper.name.clone()]]),
+                    );
+                }
+            }
+        }
+    }
+    cwe_warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::*;
+
+    fn mock_wrapper() -> Wrapper {
+        Wrapper {
+            name: "safe_copy".to_string(),
+            cwe: "CWE120".to_string(),
+            max_values: vec![MaxValue {
+                parameter: 1,
+                max_value: 0x100,
+            }],
+            buffer_sizes: vec![BufferSize {
+                buffer_parameter: 0,
+                size_parameter: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn warnings_in_wrappers() {
+        let mut project = Project::mock_empty();
+        let mut wrapper_sub = Sub::mock("safe_copy");
+        let mut block = Blk::mock();
+        block.term.defs = vec![Def::assign(
+            "copy_loop",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0),
+        )];
+        block.term.defs[0].tid.address = "00001000".to_string();
+        wrapper_sub.term.blocks = vec![block];
+        project.program.term.subs = vec![wrapper_sub, Sub::mock("main")];
+        let config = Config {
+            wrappers: vec![mock_wrapper()],
+        };
+        let cwes = vec![
+            CweWarning::new("CWE120", "0.1", "inside").addresses(vec!["00001000".into()]),
+            CweWarning::new("CWE120", "0.1", "outside").addresses(vec!["00002000".into()]),
+        ];
+        let cwes = suppress_warnings_in_wrappers(&project, cwes, &config);
+        assert_eq!(cwes.len(), 1);
+        assert_eq!(cwes[0].description, "outside");
+    }
+
+    #[test]
+    fn contract_checks() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention {
+            name: "__stdcall".to_string(),
+            parameter_register: vec!["RDI".to_string(), "RSI".to_string()],
+            return_register: vec!["RAX".to_string()],
+            callee_saved_register: vec!["RBP".to_string()],
+            stack_alignment: None,
+        }];
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let stack_buffer: Data =
+            PointerDomain::new(state.stack_id.clone(), Bitvector::from_i64(-0x20).into()).into();
+        state.set_register(&Variable::mock("RDI", 8), stack_buffer);
+        let wrapper = mock_wrapper();
+
+        state.set_register(
+            &Variable::mock("RSI", 8),
+            Data::Value(Bitvector::from_i64(0x10).into()),
+        );
+        assert!(check_contract(&project, &runtime_memory_image, &state, &wrapper).is_empty());
+
+        state.set_register(
+            &Variable::mock("RSI", 8),
+            Data::Value(Bitvector::from_i64(0x40).into()),
+        );
+        let violations = check_contract(&project, &runtime_memory_image, &state, &wrapper);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("has size 32"));
+
+        state.set_register(
+            &Variable::mock("RSI", 8),
+            Data::Value(Bitvector::from_i64(0x200).into()),
+        );
+        assert_eq!(
+            check_contract(&project, &runtime_memory_image, &state, &wrapper).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn config_parsing() {
+        use crate::utils::parse_config;
+        let config: Config = parse_config(&serde_json::json!({
+            "_comment": "The comment is ignored.",
+            "wrappers": [{
+                "name": "safe_copy",
+                "cwe": "CWE120",
+                "max_values": [{"parameter": 1, "max_value": 256}],
+                "buffer_sizes": [{"buffer_parameter": 0, "size_parameter": 1}]
+            }]
+        }))
+        .unwrap();
+        assert_eq!(config.wrappers, vec![mock_wrapper()]);
+        // Invalid or missing settings are errors instead of being silently ignored.
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "wrappers": [{"name": "safe_copy", "cwe": "CWE120", "max_value": []}]
+        }))
+        .is_err());
+        assert!(parse_config::<Config>(&serde_json::json!({
+            "wrappers": [{"name": "safe_copy"}]
+        }))
+        .is_err());
+        assert!(parse_config::<Config>(&serde_json::json!({"wrapper": []})).is_err());
+    }
+}
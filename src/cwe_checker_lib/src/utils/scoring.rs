@@ -0,0 +1,259 @@
+//! Crude scoring of CWE warnings to help with the triage of large numbers of warnings.
+//!
+//! The score of a warning is a number between 0 and 100 computed from
+//! - the call graph distance of the function containing the warning to the entry points of the binary,
+//! - the call graph distance of the function to functions calling input functions (e.g. `recv`),
+//! - the loop nesting depth of the block containing the warning.
+//!
+//! Warnings in functions close to entry points and to input handling code
+//! and warnings inside (nested) loops get higher scores.
+//! Note that the score is only a heuristic and not a measure of the severity of a warning.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::{get_call_graph_distances, get_loop_depths};
+use crate::utils::log::CweWarning;
+use std::collections::{HashMap, HashSet};
+
+/// The weight of the distance to the entry points in the score.
+const ENTRY_POINT_WEIGHT: f64 = 0.4;
+/// The weight of the distance to input functions in the score.
+const INPUT_WEIGHT: f64 = 0.4;
+/// The weight of the loop depth in the score.
+const LOOP_WEIGHT: f64 = 0.2;
+
+/// The configuration of the warning scores.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct Config {
+    /// Names of extern functions that read input into the program.
+    pub input_symbols: Vec<String>,
+}
+
+/// Fill the `score` field of the given warnings.
+///
+/// Warnings whose location cannot be mapped to a basic block of the program do not get a score.
+pub fn score_warnings(project: &Project, cwes: &mut [CweWarning], config: &Config) {
+    let program = &project.program;
+    let entry_distances = get_call_graph_distances(program, &program.term.entry_points, true);
+    let input_symbol_tids: HashSet<&Tid> = program
+        .term
+        .extern_symbols
+        .iter()
+        .filter(|symbol| config.input_symbols.contains(&symbol.name))
+        .map(|symbol| &symbol.tid)
+        .collect();
+    let input_subs: Vec<Tid> = program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| calls_any_of(sub, &input_symbol_tids))
+        .map(|sub| sub.tid.clone())
+        .collect();
+    let input_distances = get_call_graph_distances(program, &input_subs, false);
+
+    // Map term IDs and addresses to the (function, block) pair containing the corresponding term.
+    let mut locations: HashMap<String, (&Tid, &Tid)> = HashMap::new();
+    for sub in program.term.subs.iter() {
+        for block in sub.term.blocks.iter() {
+            let tids = std::iter::once(&block.tid)
+                .chain(block.term.defs.iter().map(|def| &def.tid))
+                .chain(block.term.jmps.iter().map(|jmp| &jmp.tid));
+            for tid in tids {
+                locations.insert(format!("{}", tid), (&sub.tid, &block.tid));
+                if tid.address != "UNKNOWN" {
+                    locations
+                        .entry(tid.address.clone())
+                        .or_insert((&sub.tid, &block.tid));
+                }
+            }
+        }
+    }
+
+    let mut loop_depths: HashMap<&Tid, HashMap<Tid, usize>> = HashMap::new();
+    for cwe in cwes.iter_mut() {
+        let (sub_tid, block_tid) = match cwe
+            .tids
+            .iter()
+            .chain(cwe.addresses.iter())
+            .find_map(|location| locations.get(location))
+        {
+            Some(location) => *location,
+            None => continue,
+        };
+        let loop_depth = loop_depths
+            .entry(sub_tid)
+            .or_insert_with(|| {
+                let sub = program
+                    .term
+                    .subs
+                    .iter()
+                    .find(|sub| sub.tid == *sub_tid)
+                    .unwrap();
+                get_loop_depths(sub)
+            })
+            .get(block_tid)
+            .copied()
+            .unwrap_or(0);
+        cwe.score = Some(compute_score(
+            entry_distances.get(sub_tid).copied(),
+            input_distances.get(sub_tid).copied(),
+            loop_depth,
+        ));
+    }
+}
+
+/// Sort the warnings by descending score and remove warnings with a score less than `min_score`.
+/// Warnings without a score are sorted last and are only kept if `min_score` is zero.
+pub fn filter_and_sort_by_score(cwes: &mut Vec<CweWarning>, min_score: u64) {
+    cwes.retain(|cwe| cwe.score.unwrap_or(0) >= min_score);
+    cwes.sort_by_key(|cwe| std::cmp::Reverse(cwe.score));
+}
+
+/// Returns `true` if the function contains a direct call to one of the given targets.
+fn calls_any_of(sub: &Term<Sub>, targets: &HashSet<&Tid>) -> bool {
+    sub.term.blocks.iter().any(|block| {
+        block.term.jmps.iter().any(|jmp| match &jmp.term {
+            Jmp::Call { target, .. } => targets.contains(target),
+            _ => false,
+        })
+    })
+}
+
+/// Compute the score from the call graph distances of the function to entry points and input functions
+/// (`None` if unreachable) and the loop depth of the block.
+fn compute_score(
+    entry_distance: Option<usize>,
+    input_distance: Option<usize>,
+    loop_depth: usize,
+) -> u64 {
+    let proximity =
+        |distance: Option<usize>| distance.map(|d| 1.0 / (1.0 + d as f64)).unwrap_or(0.0);
+    let loop_factor = loop_depth as f64 / (1.0 + loop_depth as f64);
+    let score = ENTRY_POINT_WEIGHT * proximity(entry_distance)
+        + INPUT_WEIGHT * proximity(input_distance)
+        + LOOP_WEIGHT * loop_factor;
+    (100.0 * score).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores() {
+        assert_eq!(compute_score(Some(0), Some(0), 0), 80);
+        assert_eq!(compute_score(Some(1), None, 1), 30);
+        assert_eq!(compute_score(None, None, 0), 0);
+
+        let mut cwes = vec![
+            CweWarning::new("CWE1", "0.1", "a"),
+            CweWarning::new("CWE2", "0.1", "b"),
+            CweWarning::new("CWE3", "0.1", "c"),
+        ];
+        cwes[0].score = Some(10);
+        cwes[1].score = Some(50);
+        filter_and_sort_by_score(&mut cwes, 0);
+        assert_eq!(cwes[0].name, "CWE2");
+        assert_eq!(cwes[2].name, "CWE3");
+        filter_and_sort_by_score(&mut cwes, 20);
+        assert_eq!(cwes.len(), 1);
+    }
+
+    #[test]
+    fn scores_by_location() {
+        // `main` calls `handler`, which calls `recv` in a loop.
+        let mut main = Sub::mock("main");
+        let mut main_block = Blk::mock_with(
+            "main_0",
+            vec![Def::assign(
+                "main_def",
+                Variable::mock("RAX", 8),
+                Expression::const_from_i64(0),
+            )],
+            Jmp::mock_call("handler", "main_1"),
+        );
+        main_block.term.defs[0].tid.address = "0x10".to_string();
+        main.term.blocks = vec![
+            main_block,
+            Blk::mock_with(
+                "main_1",
+                Vec::new(),
+                Jmp::Return(Expression::const_from_i64(0)),
+            ),
+        ];
+        let mut handler = Sub::mock("handler");
+        handler.term.blocks = vec![
+            Blk::mock_with(
+                "handler_0",
+                Vec::new(),
+                Jmp::Branch(Tid::new("handler_loop")),
+            ),
+            Blk::mock_with(
+                "handler_loop",
+                Vec::new(),
+                Jmp::mock_call("recv", "handler_check"),
+            ),
+            Blk::mock_with_jmps(
+                "handler_check",
+                Vec::new(),
+                vec![
+                    Jmp::CBranch {
+                        target: Tid::new("handler_loop"),
+                        condition: Expression::Var(Variable::mock("ZF", 1)),
+                    },
+                    Jmp::Return(Expression::const_from_i64(0)),
+                ],
+            ),
+        ];
+        let mut unreachable = Sub::mock("unreachable");
+        unreachable.term.blocks = vec![Blk::mock_with(
+            "unreachable_0",
+            Vec::new(),
+            Jmp::Return(Expression::const_from_i64(0)),
+        )];
+        let mut project = Project::mock_empty();
+        let mut recv = ExternSymbol::mock();
+        recv.tid = Tid::new("recv");
+        recv.name = "recv".to_string();
+        project.program.term.extern_symbols = vec![recv];
+        project.program.term.subs = vec![main, handler, unreachable];
+        project.program.term.entry_points = vec![Tid::new("main")];
+
+        let warning = |tids: &[&str], addresses: &[&str]| {
+            CweWarning::new("CWE1", "0.1", "warning")
+                .tids(tids.iter().map(|tid| tid.to_string()).collect())
+                .addresses(
+                    addresses
+                        .iter()
+                        .map(|address| address.to_string())
+                        .collect(),
+                )
+        };
+        let mut cwes = vec![
+            // Only the address of the warning is known.
+            warning(&[], &["0x10"]),
+            warning(&["handler_loop_jmp"], &[]),
+            warning(&["handler_0_jmp"], &[]),
+            warning(&["unreachable_0_jmp"], &[]),
+            warning(&["unknown_tid"], &["0x20"]),
+        ];
+        let config = Config {
+            input_symbols: vec!["recv".to_string()],
+        };
+        score_warnings(&project, &mut cwes, &config);
+        let scores: Vec<Option<u64>> = cwes.iter().map(|cwe| cwe.score).collect();
+        assert_eq!(scores, vec![Some(60), Some(70), Some(60), Some(0), None]);
+
+        // Without input symbols only the entry point distance and the loop depth count.
+        score_warnings(&project, &mut cwes, &Config::default());
+        let scores: Vec<Option<u64>> = cwes.iter().map(|cwe| cwe.score).collect();
+        assert_eq!(scores, vec![Some(40), Some(30), Some(20), Some(0), None]);
+
+        filter_and_sort_by_score(&mut cwes, 0);
+        assert_eq!(cwes.len(), 5);
+        assert_eq!(cwes[0].score, Some(40));
+        assert_eq!(cwes[4].score, None);
+        filter_and_sort_by_score(&mut cwes, 1);
+        assert_eq!(cwes.len(), 3);
+    }
+}
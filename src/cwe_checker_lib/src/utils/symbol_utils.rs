@@ -5,16 +5,17 @@ use std::collections::HashMap;
 
 use crate::intermediate_representation::*;
 
-/// Find the extern symbol object for a symbol name and return the symbol tid and name.
-pub fn find_symbol<'a>(prog: &'a Term<Program>, name: &str) -> Option<(&'a Tid, &'a str)> {
-    let mut symbol: Option<(&'a Tid, &'a str)> = None;
-    prog.term.extern_symbols.iter().for_each(|sym| {
-        if name == sym.name {
-            symbol = Some((&sym.tid, &sym.name));
-        }
-    });
-
-    symbol
+/// Find all extern symbol objects with the given symbol name and return their tids and names.
+///
+/// Binaries combining several statically linked components may contain more than one symbol with the same name.
+/// The symbols are distinguished by their tids.
+pub fn find_symbols<'a>(prog: &'a Term<Program>, name: &str) -> Vec<(&'a Tid, &'a str)> {
+    prog.term
+        .extern_symbols
+        .iter()
+        .filter(|sym| name == sym.name)
+        .map(|sym| (&sym.tid, sym.name.as_str()))
+        .collect()
 }
 
 /// Match direct calls' target tids in the program's subroutines
@@ -31,7 +32,7 @@ pub fn get_calls_to_symbols<'a, 'b>(
             if let Jmp::Call { target: dst, .. } = &jmp.term {
                 if symbols.contains_key(dst) {
                     calls.push((
-                        sub.term.name.as_str(),
+                        sub.term.display_name(),
                         &jmp.tid,
                         symbols.get(dst).clone().unwrap(),
                     ));
@@ -44,29 +45,19 @@ pub fn get_calls_to_symbols<'a, 'b>(
 
 /// Get a map from TIDs to the corresponding extern symbol struct.
 /// Only symbols with names contained in `symbols_to_find` are contained in the map.
+/// If several symbols share the same name, all of them are contained in the map.
 pub fn get_symbol_map<'a>(
     project: &'a Project,
     symbols_to_find: &[String],
 ) -> HashMap<Tid, &'a ExternSymbol> {
-    let mut tid_map = HashMap::new();
-    for symbol_name in symbols_to_find {
-        if let Some((tid, symbol)) = project
-            .program
-            .term
-            .extern_symbols
-            .iter()
-            .find_map(|symbol| {
-                if symbol.name == *symbol_name {
-                    Some((symbol.tid.clone(), symbol))
-                } else {
-                    None
-                }
-            })
-        {
-            tid_map.insert(tid, symbol);
-        }
-    }
-    tid_map
+    project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter(|symbol| symbols_to_find.contains(&symbol.name))
+        .map(|symbol| (symbol.tid.clone(), symbol))
+        .collect()
 }
 
 /// Find calls to TIDs contained as keys in the given symbol map.
@@ -88,3 +79,88 @@ pub fn get_callsites<'a>(
     }
     callsites
 }
+
+/// Get the location of the parameter with the given index of a call to the extern symbol,
+/// including variadic parameters not contained in the parameter list of the symbol.
+///
+/// Variadic parameters are assumed to be passed in the parameter registers of the calling convention
+/// and then on the stack in pointer-sized slots.
+/// The offset of the first stack slot relative to the stack pointer at the call
+/// accounts for the return address on x86 and for the register save area on 32-bit MIPS.
+pub fn get_variadic_parameter(project: &Project, symbol: &ExternSymbol, index: usize) -> Arg {
+    if let Some(parameter) = symbol.parameters.get(index) {
+        return parameter.clone();
+    }
+    get_calling_convention_parameter(project, symbol.get_calling_convention(project), index)
+}
+
+/// Get the location of the parameter with the given index according to the calling convention.
+///
+/// Parameters are assumed to be pointer-sized and passed in the parameter registers of the calling convention
+/// and then on the stack, as described for [`get_variadic_parameter`].
+pub fn get_calling_convention_parameter(
+    project: &Project,
+    calling_convention: &CallingConvention,
+    index: usize,
+) -> Arg {
+    let pointer_size = project.get_pointer_bytesize();
+    let parameter_register = &calling_convention.parameter_register;
+    if let Some(register) = parameter_register.get(index) {
+        return Arg::Register(Variable {
+            name: register.clone(),
+            size: pointer_size,
+            is_temp: false,
+        });
+    }
+    let first_stack_slot_offset = match project.cpu_architecture.as_str() {
+        "x86" | "x86_32" | "x86_64" => u64::from(pointer_size),
+        "MIPS_32" | "MIPS" => 16,
+        _ => 0,
+    };
+    let stack_index = (index - parameter_register.len()) as u64;
+    Arg::Stack {
+        offset: (first_stack_slot_offset + stack_index * u64::from(pointer_size)) as i64,
+        size: pointer_size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_symbol_names() {
+        let mut project = Project::mock_empty();
+        let mut symbol = ExternSymbol::mock();
+        symbol.name = "init".to_string();
+        let mut other_symbol = symbol.clone();
+        other_symbol.tid = Tid::new("other_init");
+        project.program.term.extern_symbols = vec![symbol, other_symbol];
+
+        let symbols = find_symbols(&project.program, "init");
+        assert_eq!(symbols.len(), 2);
+        let symbol_map = get_symbol_map(&project, &["init".to_string()]);
+        assert_eq!(symbol_map.len(), 2);
+        assert!(symbol_map.contains_key(&Tid::new("other_init")));
+        assert!(find_symbols(&project.program, "main").is_empty());
+    }
+
+    #[test]
+    fn variadic_parameters() {
+        let mut project = Project::mock_empty();
+        project.calling_conventions = vec![CallingConvention::mock()];
+        let symbol = ExternSymbol::mock();
+        assert_eq!(
+            get_variadic_parameter(&project, &symbol, 0),
+            symbol.parameters[0]
+        );
+        // The only parameter register is already used by the first parameter.
+        assert_eq!(
+            get_variadic_parameter(&project, &symbol, 2),
+            Arg::Stack {
+                offset: 16,
+                size: ByteSize::new(8)
+            }
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! The configuration of untrusted input sources shared by the checks
+//! that trace values back to untrusted input, e.g. the CWE-606 check.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::get_symbol_map;
+use std::collections::{BTreeMap, HashMap};
+
+/// The extern functions returning or writing untrusted input.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TaintSourceConfig {
+    /// Functions whose return values are untrusted.
+    pub return_value_sources: Vec<String>,
+    /// Functions writing untrusted input to the memory pointed to by one of their parameters.
+    pub parameter_sources: BTreeMap<String, ParameterSource>,
+}
+
+/// A parameter pointing to memory that is filled with untrusted input.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ParameterSource {
+    /// The index of the parameter pointing to the untrusted memory.
+    pub parameter: usize,
+    /// The index of the parameter containing the number of written bytes.
+    /// If not given, a value of pointer size is written.
+    #[serde(default)]
+    pub size_parameter: Option<usize>,
+}
+
+impl TaintSourceConfig {
+    /// Get the extern symbols of the configured return value sources contained in the project,
+    /// indexed by their TIDs.
+    pub fn get_return_value_sources<'a>(
+        &self,
+        project: &'a Project,
+    ) -> HashMap<Tid, &'a ExternSymbol> {
+        get_symbol_map(project, &self.return_value_sources)
+    }
+
+    /// Get the extern symbols of the configured parameter sources contained in the project,
+    /// indexed by their TIDs.
+    pub fn get_parameter_sources<'a>(
+        &self,
+        project: &'a Project,
+    ) -> HashMap<Tid, &'a ExternSymbol> {
+        let names: Vec<String> = self.parameter_sources.keys().cloned().collect();
+        get_symbol_map(project, &names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_symbols() {
+        let config: TaintSourceConfig = serde_json::from_value(serde_json::json!({
+            "return_value_sources": ["atoi"],
+            "parameter_sources": {
+                "recv": {"parameter": 1, "size_parameter": 2},
+                "mock_symbol": {"parameter": 0}
+            }
+        }))
+        .unwrap();
+        assert_eq!(config.parameter_sources["mock_symbol"].size_parameter, None);
+
+        let mut project = Project::mock_empty();
+        project.program.term.extern_symbols = vec![ExternSymbol::mock()];
+        assert!(config.get_return_value_sources(&project).is_empty());
+        let parameter_sources = config.get_parameter_sources(&project);
+        assert_eq!(parameter_sources.len(), 1);
+        assert_eq!(
+            parameter_sources[&Tid::new("mock_symbol")].name,
+            "mock_symbol"
+        );
+    }
+}
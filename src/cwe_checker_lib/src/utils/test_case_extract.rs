@@ -0,0 +1,328 @@
+//! Self-contained extracts of single findings for reproducing them in unit tests.
+//!
+//! An extract contains the expected CWE warning, the intermediate representation of the functions affected by it,
+//! the runtime memory image of the binary and the configuration of the check that generated the warning.
+//! The affected functions are the functions containing the addresses or terms referenced by the warning.
+//! If a warning references several functions, a common caller of them and the functions connecting it to them
+//! in the call graph are added, so that the analyses can follow the values passed between the functions.
+//! All other functions are removed from the program (see [`Project::slice_to_functions`]).
+//!
+//! Checks that need the raw bytes of the binary (e.g. to parse its headers) cannot be reproduced from an extract.
+
+use super::baseline::get_finding_id;
+use super::binary::RuntimeMemoryImage;
+use super::graph_utils::CallGraph;
+use super::log::CweWarning;
+use crate::analysis::graph;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::AnalysisResults;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+/// A self-contained extract of a finding.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TestCaseExtract {
+    /// The name of the module that generated the warning.
+    pub module: String,
+    /// The names of the functions contained in the extract.
+    pub functions: Vec<String>,
+    /// The configuration of the module and of the Pointer Inference analysis,
+    /// as JSON object with the keys `module` and `Memory`.
+    pub config: serde_json::Value,
+    /// The warning that the analysis of the extract is expected to generate.
+    pub expected_warning: CweWarning,
+    /// The project sliced down to the functions of the extract.
+    pub project: Project,
+    /// The runtime memory image of the binary.
+    pub runtime_memory_image: RuntimeMemoryImage,
+}
+
+impl TestCaseExtract {
+    /// Generate the extract of the given warning that was generated by the module with the given name.
+    /// The configuration of the module and of the Pointer Inference analysis are taken from the given configuration.
+    ///
+    /// Returns `None` if the warning does not reference any function of the project.
+    pub fn new(
+        project: &Project,
+        runtime_memory_image: &RuntimeMemoryImage,
+        warning: &CweWarning,
+        module: &str,
+        config: &serde_json::Value,
+    ) -> Option<TestCaseExtract> {
+        let affected_functions = get_affected_functions(project, warning);
+        if affected_functions.is_empty() {
+            return None;
+        }
+        let call_graph = CallGraph::new(&project.program);
+        let sub_tids = add_connecting_functions(&call_graph, affected_functions);
+        let mut sliced_project = project.clone();
+        sliced_project.slice_to_functions(&sub_tids);
+        let functions = sliced_project
+            .program
+            .term
+            .subs
+            .iter()
+            .map(|sub| sub.term.name.clone())
+            .collect();
+        Some(TestCaseExtract {
+            module: module.to_string(),
+            functions,
+            config: serde_json::json!({
+                "module": config[module].clone(),
+                "Memory": config["Memory"].clone(),
+            }),
+            expected_warning: warning.clone(),
+            project: sliced_project,
+            runtime_memory_image: runtime_memory_image.clone(),
+        })
+    }
+
+    /// Parse an extract saved as JSON.
+    pub fn parse(extract: &str) -> Result<TestCaseExtract, Error> {
+        Ok(serde_json::from_str(extract)?)
+    }
+
+    /// Run the module of the extract on the sliced project and return the generated warnings.
+    pub fn run(&self) -> Result<Vec<CweWarning>, Error> {
+        let module = crate::get_modules()
+            .into_iter()
+            .find(|module| module.name == self.module)
+            .ok_or_else(|| anyhow!("Unknown module {}", self.module))?;
+        let extern_sub_tids = self
+            .project
+            .program
+            .term
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.tid.clone())
+            .collect();
+        let control_flow_graph = graph::get_program_cfg(&self.project.program, extern_sub_tids);
+        // The Pointer Inference analysis is only computed if the module requests it.
+        let analysis_results = AnalysisResults::new(
+            &[],
+            &self.runtime_memory_image,
+            &control_flow_graph,
+            &self.project,
+        )
+        .with_pointer_inference_config(&self.config["Memory"]);
+        let (_logs, warnings) = (module.run)(&analysis_results, &self.config["module"]);
+        Ok(warnings)
+    }
+
+    /// Check whether running the module on the extract generates the expected warning.
+    /// Warnings are compared by their finding IDs, i.e. by their names, addresses and term IDs.
+    pub fn reproduces_expected_warning(&self) -> Result<bool, Error> {
+        let expected_id = get_finding_id(&self.expected_warning);
+        Ok(self
+            .run()?
+            .iter()
+            .any(|warning| get_finding_id(warning) == expected_id))
+    }
+}
+
+/// Get the functions containing terms whose addresses or TIDs are referenced by the warning.
+fn get_affected_functions(project: &Project, warning: &CweWarning) -> BTreeSet<Tid> {
+    let addresses: HashSet<&str> = warning.addresses.iter().map(String::as_str).collect();
+    let tids: HashSet<&str> = warning.tids.iter().map(String::as_str).collect();
+    let is_referenced = |tid: &Tid| {
+        addresses.contains(tid.address.as_str()) || tids.contains(tid.to_string().as_str())
+    };
+    project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter(|sub| {
+            is_referenced(&sub.tid)
+                || sub.term.blocks.iter().any(|block| {
+                    is_referenced(&block.tid)
+                        || block.term.defs.iter().any(|def| is_referenced(&def.tid))
+                        || block.term.jmps.iter().any(|jmp| is_referenced(&jmp.tid))
+                })
+        })
+        .map(|sub| sub.tid.clone())
+        .collect()
+}
+
+/// If more than one function is given, add the closest common caller of the functions
+/// and the functions on the shortest call paths from it to the given functions.
+/// Functions without a common caller are returned unchanged.
+fn add_connecting_functions(call_graph: &CallGraph, functions: BTreeSet<Tid>) -> BTreeSet<Tid> {
+    if functions.len() < 2 {
+        return functions;
+    }
+    // For each function map its transitive callers to their distance and to the next function on the path to it.
+    let caller_paths: Vec<BTreeMap<Tid, (usize, Option<Tid>)>> = functions
+        .iter()
+        .map(|function| get_caller_paths(call_graph, function))
+        .collect();
+    let common_caller = caller_paths[0]
+        .keys()
+        .filter(|caller| caller_paths.iter().all(|paths| paths.contains_key(caller)))
+        .min_by_key(|caller| {
+            caller_paths
+                .iter()
+                .map(|paths| paths[*caller].0)
+                .sum::<usize>()
+        });
+    let common_caller = match common_caller {
+        Some(common_caller) => common_caller.clone(),
+        None => return functions,
+    };
+    let mut connected_functions = functions;
+    for paths in caller_paths.iter() {
+        let mut current = Some(common_caller.clone());
+        while let Some(function) = current {
+            current = paths[&function].1.clone();
+            connected_functions.insert(function);
+        }
+    }
+    connected_functions
+}
+
+/// Compute the transitive callers of the given function (including the function itself)
+/// with their distance to the function in the call graph
+/// and the next function on a shortest call path from the caller to the function.
+fn get_caller_paths(call_graph: &CallGraph, function: &Tid) -> BTreeMap<Tid, (usize, Option<Tid>)> {
+    let mut paths = BTreeMap::new();
+    paths.insert(function.clone(), (0, None));
+    let mut worklist = VecDeque::from(vec![function.clone()]);
+    while let Some(callee) = worklist.pop_front() {
+        let distance = paths[&callee].0;
+        for caller in call_graph.get_callers(&callee) {
+            if !paths.contains_key(caller) {
+                paths.insert(caller.clone(), (distance + 1, Some(callee.clone())));
+                worklist.push_back(caller.clone());
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(source: &str, target: &str) -> Term<Blk> {
+        let mut block = Term {
+            tid: Tid::new(format!("block_{}_{}", source, target)),
+            term: Blk {
+                defs: Vec::new(),
+                jmps: vec![Term {
+                    tid: Tid::new(format!("call_{}_{}", source, target)),
+                    term: Jmp::Call {
+                        target: Tid::new(target),
+                        return_: None,
+                    },
+                }],
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        block.term.jmps[0].tid.address = format!("{}_{}", source, target);
+        block
+    }
+
+    fn sub(name: &str, callees: &[&str]) -> Term<Sub> {
+        Term {
+            tid: Tid::new(name),
+            term: Sub {
+                name: name.to_string(),
+                blocks: callees.iter().map(|callee| call(name, callee)).collect(),
+                qualified_name: None,
+            },
+        }
+    }
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let mut strcpy = ExternSymbol::mock();
+        strcpy.tid = Tid::new("strcpy");
+        strcpy.name = "strcpy".to_string();
+        project.program.term.extern_symbols = vec![strcpy];
+        project.program.term.subs = vec![
+            sub("main", &["init", "handler"]),
+            sub("init", &[]),
+            sub("handler", &["parse", "respond"]),
+            sub("parse", &["strcpy"]),
+            sub("respond", &["strcpy"]),
+            sub("unrelated", &[]),
+        ];
+        project.program.term.entry_points = vec![Tid::new("main")];
+        project
+    }
+
+    #[test]
+    fn function_selection() {
+        let project = mock_project();
+        let call_graph = CallGraph::new(&project.program);
+        let mut warning = CweWarning::new("CWE676", "0.1", "description");
+        warning.addresses = vec!["parse_strcpy".to_string()];
+        warning.tids = vec!["call_respond_strcpy".to_string()];
+        let affected_functions = get_affected_functions(&project, &warning);
+        assert_eq!(
+            affected_functions,
+            vec![Tid::new("parse"), Tid::new("respond")]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            add_connecting_functions(&call_graph, affected_functions),
+            vec![Tid::new("handler"), Tid::new("parse"), Tid::new("respond")]
+                .into_iter()
+                .collect()
+        );
+        let unconnected: BTreeSet<Tid> = vec![Tid::new("parse"), Tid::new("unrelated")]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            add_connecting_functions(&call_graph, unconnected.clone()),
+            unconnected
+        );
+    }
+
+    #[test]
+    fn reproduction() {
+        let project = mock_project();
+        let config = json!({
+            "CWE676": {"symbols": ["strcpy"]},
+            "Memory": {}
+        });
+        let mut warning = CweWarning::new("CWE676", "0.1", "description");
+        warning.addresses = vec!["parse_strcpy".to_string()];
+        warning.tids = vec!["call_parse_strcpy".to_string()];
+        warning.symbols = vec!["parse".to_string(), "strcpy".to_string()];
+        let extract = TestCaseExtract::new(
+            &project,
+            &RuntimeMemoryImage::mock(),
+            &warning,
+            "CWE676",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(extract.functions, vec!["parse".to_string()]);
+        assert_eq!(
+            extract.project.program.term.entry_points,
+            vec![Tid::new("parse")]
+        );
+        assert_eq!(extract.config["module"], config["CWE676"]);
+
+        let extract = TestCaseExtract::parse(&serde_json::to_string(&extract).unwrap()).unwrap();
+        assert_eq!(extract.run().unwrap().len(), 1);
+        assert!(extract.reproduces_expected_warning().unwrap());
+
+        let mut unknown_module = extract.clone();
+        unknown_module.module = "CWE0".to_string();
+        assert!(unknown_module.run().is_err());
+        warning.addresses = vec!["unknown_address".to_string()];
+        warning.tids = Vec::new();
+        assert!(TestCaseExtract::new(
+            &project,
+            &RuntimeMemoryImage::mock(),
+            &warning,
+            "CWE676",
+            &config
+        )
+        .is_none());
+    }
+}
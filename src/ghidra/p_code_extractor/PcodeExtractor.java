@@ -73,8 +73,9 @@ public class PcodeExtractor extends GhidraScript {
     protected Term<Program> iterateFunctions(SimpleBlockModel simpleBM, Listing listing, Term<Program> program) {
         FunctionIterator functions = HelperFunctions.funcMan.getFunctions(true);
         for (Function func : functions) {
-            if(ExternSymbolCreator.externalSymbolMap.containsKey(func.getName())) {
-                ArrayList<String> addresses = ExternSymbolCreator.externalSymbolMap.get(func.getName()).getAddresses();
+            String externalName = ExternSymbolCreator.getExternalSymbolName(func);
+            if(ExternSymbolCreator.externalSymbolMap.containsKey(externalName)) {
+                ArrayList<String> addresses = ExternSymbolCreator.externalSymbolMap.get(externalName).getAddresses();
                 if(!addresses.stream().anyMatch(addr -> addr.equals(func.getEntryPoint().toString()))) {
                     Term<Sub> currentSub = TermCreator.createSubTerm(func);
                     currentSub.getTerm().setBlocks(iterateBlocks(currentSub, simpleBM, listing));
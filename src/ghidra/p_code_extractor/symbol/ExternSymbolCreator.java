@@ -14,6 +14,7 @@ import ghidra.program.model.address.Address;
 import ghidra.program.model.listing.Function;
 import ghidra.program.model.listing.FunctionManager;
 import ghidra.program.model.listing.Parameter;
+import ghidra.program.model.symbol.ExternalLocation;
 import ghidra.program.model.pcode.Varnode;
 import ghidra.program.model.symbol.SymbolTable;
 
@@ -78,14 +79,35 @@ public class ExternSymbolCreator {
      * Either adds a function to a given symbol name or creates a new entry in the symbol map
      */
     public static void addToSymbolMap(HashMap<String, ArrayList<Function>> symbolMap, Function func) {
-        if(symbolMap.containsKey(func.getName())) {
-            symbolMap.get(func.getName()).add(func);
+        String name = getExternalSymbolName(func);
+        if(symbolMap.containsKey(name)) {
+            symbolMap.get(name).add(func);
         } else {
-            symbolMap.put(func.getName(), new ArrayList<Function>(){{add(func);}});
+            symbolMap.put(name, new ArrayList<Function>(){{add(func);}});
         }
     }
 
 
+    /**
+     * 
+     * @param func: External function or thunk function of an external function
+     * @return: name of the external symbol
+     * 
+     * Imports by ordinal (e.g. in PE files) have no name and Ghidra labels them as Ordinal_N,
+     * which is not unique across libraries. Their names are qualified by the lowercase library name instead,
+     * e.g. ws2_32.dll_Ordinal_23. Well-known ordinals are mapped to the real function names by the cwe_checker.
+     */
+    public static String getExternalSymbolName(Function func) {
+        Function external = func.isThunk() ? func.getThunkedFunction(true) : func;
+        ExternalLocation location = external.getExternalLocation();
+        String name = external.getName();
+        if(location != null && location.getLibraryName() != null && name.matches("Ordinal_\\d+")) {
+            return String.format("%s_%s", location.getLibraryName().toLowerCase(), name);
+        }
+        return func.getName();
+    }
+
+
     /**
      * @param symbolMap: External symbol map
      * 
@@ -122,7 +144,7 @@ public class ExternSymbolCreator {
      */
     public static Tid updateExternalSymbolLocations(Address flow, String targetAddress, FunctionManager funcMan) {
         Function external = funcMan.getFunctionAt(flow);
-        ExternSymbol symbol = externalSymbolMap.get(external.getName());
+        ExternSymbol symbol = externalSymbolMap.get(getExternalSymbolName(external));
         symbol.getAddresses().add(targetAddress);
         if(symbol.getTid().getId().startsWith("sub_EXTERNAL")) {
             Tid targetTid = new Tid(String.format("sub_%s", targetAddress), targetAddress);
@@ -0,0 +1,42 @@
+#include <stdio.h>
+#include <string.h>
+
+void copy_too_long(){
+        char buf[8];
+        strcpy(buf, "This string does not fit into the buffer");
+        puts(buf);
+}
+
+char source[256];
+
+void copy_bounded_length(int flag){
+        char buf[16];
+        size_t len = 8;
+        if (flag) {
+                len = 256;
+        }
+        memcpy(buf, source, len);
+        puts(buf);
+}
+
+void copy_fits(){
+        char buf[32];
+        strcpy(buf, "short");
+        puts(buf);
+}
+
+void append_to_formatted(){
+        char buf[16];
+        sprintf(buf, "id %d:", 42);
+        strcat(buf, "this suffix does not fit");
+        puts(buf);
+}
+
+int main(int argc, char *argv[argc])
+{
+        copy_too_long();
+        copy_bounded_length(argc);
+        copy_fits();
+        append_to_formatted();
+        return 0;
+}
@@ -0,0 +1,33 @@
+#include <stdio.h>
+
+void read_unbounded(){
+        char buffer[32];
+        scanf("%s", buffer);
+        printf("%s\n", buffer);
+}
+
+void read_bounded(){
+        char buffer[32];
+        scanf("%31s", buffer);
+        printf("%s\n", buffer);
+}
+
+void copy_unbounded(char *input){
+        char buffer[16];
+        int i = 0;
+        while (input[i] != 0) {
+                buffer[i] = input[i];
+                i++;
+        }
+        buffer[i] = 0;
+        puts(buffer);
+}
+
+int main(int argc, char *argv[argc]){
+        read_unbounded();
+        read_bounded();
+        if (argc > 1) {
+                copy_unbounded(argv[1]);
+        }
+        return 0;
+}
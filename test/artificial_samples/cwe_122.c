@@ -0,0 +1,29 @@
+#include <stdlib.h>
+
+void write_past_end(){
+        int *buffer = malloc(4 * sizeof(int));
+        if (buffer == NULL) {
+                return;
+        }
+        for (int i = 0; i <= 4; i++) {
+                buffer[i] = i;
+        }
+        free(buffer);
+}
+
+void write_in_bounds(){
+        int *buffer = malloc(4 * sizeof(int));
+        if (buffer == NULL) {
+                return;
+        }
+        for (int i = 0; i < 4; i++) {
+                buffer[i] = i;
+        }
+        free(buffer);
+}
+
+int main(){
+        write_past_end();
+        write_in_bounds();
+        return 0;
+}
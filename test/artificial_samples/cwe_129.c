@@ -0,0 +1,25 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+int unchecked_index(char *input){
+        int values[16] = {0};
+        int index = atoi(input);
+        values[index] = 1;
+        return values[0];
+}
+
+int constant_index(){
+        int values[16] = {0};
+        int index = 8;
+        values[index] = 1;
+        return values[0];
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 1) {
+                unchecked_index(argv[1]);
+        }
+        constant_index();
+        return 0;
+}
@@ -0,0 +1,45 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+char *duplicate(const char *string)
+{
+  char *copy = malloc(strlen(string));
+  if (copy == NULL) {
+    return NULL;
+  }
+  strcpy(copy, string);
+  return copy;
+}
+
+char *duplicate_correctly(const char *string)
+{
+  char *copy = malloc(strlen(string) + 1);
+  if (copy == NULL) {
+    return NULL;
+  }
+  strcpy(copy, string);
+  return copy;
+}
+
+long *copy_records(const long *records, size_t count)
+{
+  long *buffer = malloc(count * sizeof(long));
+  if (buffer == NULL) {
+    return NULL;
+  }
+  memcpy(buffer, records, count * sizeof(long) + sizeof(long));
+  return buffer;
+}
+
+int main(int argc, char **argv)
+{
+  long records[4] = {1, 2, 3, 4};
+  if (argc < 2) {
+    return 1;
+  }
+  puts(duplicate(argv[1]));
+  puts(duplicate_correctly(argv[1]));
+  printf("%ld\n", copy_records(records, 3)[0]);
+  return 0;
+}
@@ -0,0 +1,19 @@
+#include <stdio.h>
+
+unsigned int get_field_mask(void)
+{
+  unsigned int field_width = 40;
+  return (1u << field_width) - 1;
+}
+
+unsigned int get_byte_mask(void)
+{
+  unsigned int field_width = 8;
+  return (1u << field_width) - 1;
+}
+
+int main(int argc, char **argv)
+{
+  printf("%x %x\n", get_field_mask(), get_byte_mask());
+  return 0;
+}
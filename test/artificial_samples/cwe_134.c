@@ -0,0 +1,32 @@
+#include <stdio.h>
+
+char writable_format[] = "%s%n\n";
+
+int print_writable_format(char *input){
+        int count = 0;
+        printf(writable_format, input, &count);
+        return count;
+}
+
+int print_constant_format(char *input){
+        int count = 0;
+        printf("%s%n\n", input, &count);
+        return count;
+}
+
+void print_user_input(void){
+        char buffer[64];
+        if (fgets(buffer, sizeof(buffer), stdin) != NULL) {
+                printf(buffer);
+        }
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 1) {
+                print_writable_format(argv[1]);
+                print_constant_format(argv[1]);
+        }
+        print_user_input();
+        return 0;
+}
@@ -0,0 +1,46 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <unistd.h>
+
+int read_header(const char *path, char *buffer){
+        int fd = open(path, O_RDONLY);
+        if (fd < 0)
+                return -1;
+        if (read(fd, buffer, 16) != 16)
+                close(fd);
+        close(fd);
+        return 0;
+}
+
+int reset_after_close(const char *path){
+        int fd = open(path, O_RDONLY);
+        if (fd < 0)
+                return -1;
+        close(fd);
+        fd = -1;
+        if (fd >= 0)
+                close(fd);
+        return 0;
+}
+
+int count_readable(int argc, char *argv[argc]){
+        int count = 0;
+        for (int i = 1; i < argc; i++) {
+                FILE *file = fopen(argv[i], "r");
+                if (file == NULL)
+                        continue;
+                fclose(file);
+                count++;
+        }
+        return count;
+}
+
+int main(int argc, char *argv[argc])
+{
+        char buffer[16];
+        if (argc > 1) {
+                read_header(argv[1], buffer);
+                reset_after_close(argv[1]);
+        }
+        return count_readable(argc, argv);
+}
@@ -0,0 +1,43 @@
+#define _DEFAULT_SOURCE
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+int authenticate(const char *password){
+        return strlen(password) > 8;
+}
+
+int login_heap(void){
+        char *password = getpass("Password: ");
+        char *copy = malloc(64);
+        strncpy(copy, password, 63);
+        explicit_bzero(password, strlen(password));
+        int ok = authenticate(copy);
+        memset(copy, 0, strlen(copy));
+        free(copy);
+        return ok;
+}
+
+int login_stack(void){
+        char buffer[64];
+        char *password = getpass("Password: ");
+        strncpy(buffer, password, 63);
+        explicit_bzero(password, strlen(password));
+        return authenticate(buffer);
+}
+
+int login_wiped(void){
+        char buffer[64];
+        char *password = getpass("Password: ");
+        strncpy(buffer, password, 63);
+        explicit_bzero(password, strlen(password));
+        int ok = authenticate(buffer);
+        explicit_bzero(buffer, sizeof(buffer));
+        return ok;
+}
+
+int main(void)
+{
+        return login_heap() && login_stack() && login_wiped();
+}
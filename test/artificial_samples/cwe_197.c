@@ -0,0 +1,15 @@
+#include <stdlib.h>
+#include <string.h>
+
+void truncated_size(unsigned long num_elems, unsigned long elem_size){
+        unsigned int size = num_elems * elem_size; // the 64-bit product is truncated here
+        char* buf = malloc(size);
+        memset(buf, 0, num_elems * elem_size);
+        free(buf);
+}
+
+int main(int argc, char *argv[argc])
+{
+        truncated_size(strtoul(argv[1], NULL, 10), strtoul(argv[2], NULL, 10));
+        return 0;
+}
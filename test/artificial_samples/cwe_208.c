@@ -0,0 +1,35 @@
+#include <stdio.h>
+#include <string.h>
+
+char secret_password[] = "correct horse battery staple";
+
+int check_password_strcmp(char *input){
+        return strcmp(input, secret_password) == 0;
+}
+
+int check_password_loop(char *input){
+        for (int i = 0; i < sizeof(secret_password); i++) {
+                if (input[i] != secret_password[i]) {
+                        return 0;
+                }
+        }
+        return 1;
+}
+
+int check_password_constant_time(char *input){
+        unsigned char difference = 0;
+        for (int i = 0; i < sizeof(secret_password); i++) {
+                difference |= input[i] ^ secret_password[i];
+        }
+        return difference == 0;
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 1) {
+                check_password_strcmp(argv[1]);
+                check_password_loop(argv[1]);
+                check_password_constant_time(argv[1]);
+        }
+        return 0;
+}
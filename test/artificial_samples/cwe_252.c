@@ -0,0 +1,24 @@
+#define _GNU_SOURCE
+
+#include <stdlib.h>
+#include <unistd.h>
+#include <stdio.h>
+
+void run_unchecked(){
+  setuid(getuid());
+  system("/usr/bin/env echo privileges may not have been dropped");
+}
+
+void run_checked(){
+  if (setuid(getuid()) != 0) {
+    exit(1);
+  }
+  system("/usr/bin/env echo privileges dropped");
+}
+
+int main(int argc, char **argv)
+{
+  run_unchecked();
+  run_checked();
+  return 0;
+}
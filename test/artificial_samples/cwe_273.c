@@ -0,0 +1,30 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/types.h>
+#include <unistd.h>
+
+void drop_privileges_wrong_order(uid_t uid, gid_t gid)
+{
+  if (setuid(uid) != 0) {
+    exit(1);
+  }
+  if (setgid(gid) != 0) {
+    exit(1);
+  }
+}
+
+void drop_privileges_unchecked(uid_t uid)
+{
+  setuid(uid);
+  FILE *file = fopen("/etc/shadow", "r");
+  if (file != NULL) {
+    fclose(file);
+  }
+}
+
+int main(void)
+{
+  drop_privileges_wrong_order(getuid(), getgid());
+  drop_privileges_unchecked(getuid());
+  return 0;
+}
@@ -0,0 +1,26 @@
+#include <stdio.h>
+#include <openssl/ssl.h>
+
+SSL_CTX *create_insecure_context(void)
+{
+  SSL_CTX *ctx = SSL_CTX_new(TLS_client_method());
+  SSL_CTX_set_verify(ctx, SSL_VERIFY_NONE, NULL);
+  return ctx;
+}
+
+SSL_CTX *create_context(void)
+{
+  SSL_CTX *ctx = SSL_CTX_new(TLS_client_method());
+  SSL_CTX_set_verify(ctx, SSL_VERIFY_PEER, NULL);
+  return ctx;
+}
+
+int main(void)
+{
+  SSL_CTX *insecure_ctx = create_insecure_context();
+  SSL_CTX *ctx = create_context();
+  printf("%p %p\n", (void *)insecure_ctx, (void *)ctx);
+  SSL_CTX_free(insecure_ctx);
+  SSL_CTX_free(ctx);
+  return 0;
+}
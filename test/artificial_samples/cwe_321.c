@@ -0,0 +1,36 @@
+#include <stdio.h>
+#include <openssl/aes.h>
+#include <openssl/evp.h>
+
+static const unsigned char aes_key[16] = {
+  0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c
+};
+
+unsigned char cbc_key[32] = "0123456789abcdef0123456789abcdef";
+unsigned char iv[16];
+
+void encrypt_block(const unsigned char *in, unsigned char *out)
+{
+  AES_KEY key;
+  AES_set_encrypt_key(aes_key, 128, &key);
+  AES_encrypt(in, out, &key);
+}
+
+int encrypt_message(const unsigned char *in, int in_len, unsigned char *out)
+{
+  int len = 0;
+  EVP_CIPHER_CTX *ctx = EVP_CIPHER_CTX_new();
+  EVP_EncryptInit_ex(ctx, EVP_aes_256_cbc(), NULL, cbc_key, iv);
+  EVP_EncryptUpdate(ctx, out, &len, in, in_len);
+  EVP_CIPHER_CTX_free(ctx);
+  return len;
+}
+
+int main(void)
+{
+  unsigned char block[16] = "attack at dawn!";
+  unsigned char out[64];
+  encrypt_block(block, out);
+  printf("%d\n", encrypt_message(block, sizeof(block), out));
+  return 0;
+}
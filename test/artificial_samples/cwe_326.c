@@ -0,0 +1,64 @@
+#define OPENSSL_SUPPRESS_DEPRECATED
+#include <openssl/bn.h>
+#include <openssl/dh.h>
+#include <openssl/ec.h>
+#include <openssl/obj_mac.h>
+#include <openssl/rsa.h>
+
+RSA *generate_rsa_key(int bits)
+{
+  RSA *rsa = RSA_new();
+  BIGNUM *exponent = BN_new();
+  BN_set_word(exponent, RSA_F4);
+  RSA_generate_key_ex(rsa, bits, exponent, NULL);
+  BN_free(exponent);
+  return rsa;
+}
+
+RSA *generate_weak_rsa_key(void)
+{
+  RSA *rsa = RSA_new();
+  BIGNUM *exponent = BN_new();
+  BN_set_word(exponent, RSA_F4);
+  RSA_generate_key_ex(rsa, 1024, exponent, NULL);
+  BN_free(exponent);
+  return rsa;
+}
+
+DH *generate_weak_dh_parameters(void)
+{
+  DH *dh = DH_new();
+  DH_generate_parameters_ex(dh, 512, DH_GENERATOR_2, NULL);
+  return dh;
+}
+
+EC_KEY *generate_weak_ec_key(void)
+{
+  EC_KEY *key = EC_KEY_new_by_curve_name(NID_secp160r1);
+  EC_KEY_generate_key(key);
+  return key;
+}
+
+EC_KEY *generate_ec_key(void)
+{
+  EC_KEY *key = EC_KEY_new_by_curve_name(NID_X9_62_prime256v1);
+  EC_KEY_generate_key(key);
+  return key;
+}
+
+int main(int argc, char **argv)
+{
+  // Key sizes that are not known exactly are not flagged.
+  RSA *rsa = generate_rsa_key(argc * 1024);
+  RSA *weak_rsa = generate_weak_rsa_key();
+  DH *weak_dh = generate_weak_dh_parameters();
+  EC_KEY *weak_ec_key = generate_weak_ec_key();
+  EC_KEY *ec_key = generate_ec_key();
+
+  RSA_free(rsa);
+  RSA_free(weak_rsa);
+  DH_free(weak_dh);
+  EC_KEY_free(weak_ec_key);
+  EC_KEY_free(ec_key);
+  return 0;
+}
@@ -0,0 +1,59 @@
+#define OPENSSL_SUPPRESS_DEPRECATED
+#include <string.h>
+#include <openssl/md5.h>
+#include <openssl/obj_mac.h>
+#include <openssl/rc4.h>
+#include <openssl/rsa.h>
+#include <openssl/sha.h>
+
+void hash_password(const char *password, unsigned char *digest)
+{
+  MD5_CTX ctx;
+  MD5_Init(&ctx);
+  MD5_Update(&ctx, password, strlen(password));
+  MD5_Final(digest, &ctx);
+}
+
+void encrypt_message(const unsigned char *key, const unsigned char *plaintext, unsigned char *ciphertext, size_t length)
+{
+  RC4_KEY rc4_key;
+  RC4_set_key(&rc4_key, 16, key);
+  RC4(&rc4_key, length, plaintext, ciphertext);
+}
+
+void digest_message(const char *message, unsigned char *digest)
+{
+  SHA_CTX ctx;
+  SHA1_Init(&ctx);
+  SHA1_Update(&ctx, message, strlen(message));
+  SHA1_Final(digest, &ctx);
+}
+
+int sign_message(RSA *rsa, const char *message, unsigned char *signature, unsigned int *signature_length)
+{
+  unsigned char digest[SHA_DIGEST_LENGTH];
+  digest_message(message, digest);
+  return RSA_sign(NID_sha1, digest, SHA_DIGEST_LENGTH, signature, signature_length, rsa);
+}
+
+void checksum(const char *data, unsigned char *digest)
+{
+  // SHA-1 checksums outside of signatures are not flagged.
+  SHA1((const unsigned char *)data, strlen(data), digest);
+}
+
+int main(int argc, char **argv)
+{
+  unsigned char digest[SHA_DIGEST_LENGTH];
+  unsigned char ciphertext[64];
+  unsigned char signature[512];
+  unsigned int signature_length;
+  RSA *rsa = RSA_new();
+
+  hash_password(argv[0], digest);
+  encrypt_message(digest, (const unsigned char *)argv[0], ciphertext, 16);
+  checksum(argv[0], digest);
+  sign_message(rsa, argv[0], signature, &signature_length);
+  RSA_free(rsa);
+  return 0;
+}
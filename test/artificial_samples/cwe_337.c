@@ -0,0 +1,23 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <time.h>
+#include <unistd.h>
+
+unsigned int get_seed(void)
+{
+  return time(NULL) ^ getpid();
+}
+
+void seed_with(unsigned int seed)
+{
+  srandom(seed);
+}
+
+int main(void)
+{
+  srand(time(NULL));
+  printf("%d\n", rand());
+  seed_with(get_seed());
+  printf("%ld\n", random());
+  return 0;
+}
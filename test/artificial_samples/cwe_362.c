@@ -0,0 +1,32 @@
+#include <pthread.h>
+#include <stdio.h>
+
+pthread_mutex_t counter_lock = PTHREAD_MUTEX_INITIALIZER;
+int counter = 0;
+int protected_counter = 0;
+
+void *worker(void *arg){
+        for (int i = 0; i < 1000; i++){
+                // Racy increment of the global counter.
+                counter++;
+                pthread_mutex_lock(&counter_lock);
+                protected_counter++;
+                pthread_mutex_unlock(&counter_lock);
+        }
+        return NULL;
+}
+
+int main(void){
+        pthread_t thread;
+        if (pthread_create(&thread, NULL, worker, NULL) != 0)
+                return 1;
+        for (int i = 0; i < 1000; i++){
+                counter++;
+                pthread_mutex_lock(&counter_lock);
+                protected_counter++;
+                pthread_mutex_unlock(&counter_lock);
+        }
+        pthread_join(thread, NULL);
+        printf("%d %d\n", counter, protected_counter);
+        return 0;
+}
@@ -0,0 +1,24 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+int divide_unchecked(int dividend, int divisor){
+        return dividend / divisor;
+}
+
+int divide_checked(int dividend, int divisor){
+        if (divisor == 0) {
+                return 0;
+        }
+        return dividend / divisor;
+}
+
+int main(int argc, char **argv){
+        if (argc < 2) {
+                return 1;
+        }
+        int divisor = atoi(argv[1]);
+        printf("%d\n", 100 % divisor);
+        printf("%d\n", divide_unchecked(100, argc - 2));
+        printf("%d\n", divide_checked(100, argc - 2));
+        return 0;
+}
@@ -0,0 +1,43 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+struct buffer {
+        char *data;
+        size_t length;
+};
+
+int leak_on_error_path(const char *input){
+        char *copy = malloc(64);
+        if (copy == NULL)
+                return -1;
+        if (strlen(input) >= 64)
+                return -1;
+        strcpy(copy, input);
+        puts(copy);
+        free(copy);
+        return 0;
+}
+
+struct buffer *return_allocation(size_t length){
+        struct buffer *buf = malloc(sizeof(struct buffer));
+        if (buf == NULL)
+                return NULL;
+        buf->data = malloc(length);
+        buf->length = length;
+        return buf;
+}
+
+void free_buffer(struct buffer *buf){
+        free(buf->data);
+        free(buf);
+}
+
+int main(int argc, char **argv){
+        struct buffer *buf = return_allocation(16);
+        if (buf != NULL)
+                free_buffer(buf);
+        if (argc > 1)
+                leak_on_error_path(argv[1]);
+        return 0;
+}
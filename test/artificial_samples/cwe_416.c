@@ -2,8 +2,12 @@
 #include <stdlib.h>
 #include <string.h>
 
+#include "cwe_checker_annotations.h"
+
 #define BUFSIZE1 512
 
+CWE_CHECKER_DEFINE_MARKERS(CWE416)
+
 int main(int argc, char **argv) {
         char *buf1R1;
         char *buf2R1;
@@ -12,4 +16,5 @@ int main(int argc, char **argv) {
         free(buf1R1);
         free(buf2R1);
         memset(buf1R1, 0x42, BUFSIZE1);
+        CWE_CHECKER_EXPECT_WARNING(CWE416);
 }
@@ -0,0 +1,23 @@
+#include <stdlib.h>
+#include <string.h>
+
+#include "cwe_checker_annotations.h"
+
+CWE_CHECKER_DEFINE_MARKERS(CWE416)
+
+struct message {
+        size_t length;
+        char *data;
+};
+
+int main(int argc, char **argv) {
+        struct message *message = (struct message *) malloc(sizeof(struct message));
+        message->length = strlen(argv[0]);
+        size_t length = message->length;
+        free(message);
+        char *copy = (char *) malloc(16);
+        copy[length] = 0;
+        CWE_CHECKER_EXPECT_WARNING(CWE416);
+        free(copy);
+        return 0;
+}
@@ -1,15 +1,23 @@
-#include <stdlib.h>
 #include <stdio.h>
 
-void uninitalized_variable(){
-        int a; // never initialized
-        int b = 7;
-        int c = a + b;
-        printf("a is %d, b is %d, c is %d\n", a , b, c);
+int read_maybe_uninitialized(int flag){
+        int value;
+        if (flag) {
+                value = 1;
+        }
+        return value;
 }
 
-int main(int argc, char *argv[argc])
-{
-        uninitalized_variable();
+int read_initialized(int flag){
+        int value = 0;
+        if (flag) {
+                value = 1;
+        }
+        return value;
+}
+
+int main(int argc, char **argv){
+        printf("%d\n", read_maybe_uninitialized(argc > 1));
+        printf("%d\n", read_initialized(argc > 1));
         return 0;
 }
@@ -0,0 +1,24 @@
+#include <stdio.h>
+
+int sum_doubly_scaled(int *values, int count){
+        int sum = 0;
+        for (int i = 0; i < count; i++)
+                sum += *(values + i * sizeof(int));
+        return sum;
+}
+
+int sum_with_header(int *values, int count){
+        int sum = 0;
+        for (int i = 1; i < count; i++)
+                sum += values[i];
+        int header = *(int *)((char *)values + count);
+        return sum + header;
+}
+
+int main(void)
+{
+        int values[16] = {0};
+        printf("%d\n", sum_doubly_scaled(values, 4));
+        printf("%d\n", sum_with_header(values, 4));
+        return 0;
+}
@@ -0,0 +1,22 @@
+#include <stdlib.h>
+#include <string.h>
+
+void copy_prefix(const char *source, char *end){
+        char *buffer = malloc(64);
+        memcpy(buffer, source, end - buffer);
+        free(buffer);
+}
+
+size_t prefix_length(const char *source){
+        const char *end = source;
+        while (*end != '\0' && *end != ':')
+                end++;
+        return end - source;
+}
+
+int main(void)
+{
+        char header[] = "name:value";
+        copy_prefix(header, header + 4);
+        return prefix_length(header);
+}
@@ -0,0 +1,16 @@
+#define _DEFAULT_SOURCE
+#include <netdb.h>
+#include <stdio.h>
+#include <unistd.h>
+
+int main(int argc, char *argv[argc])
+{
+        if (argc < 2)
+                return 1;
+        struct hostent *host = gethostbyname(argv[1]);
+        if (host == NULL)
+                return 1;
+        usleep(1000);
+        puts(host->h_name);
+        return 0;
+}
@@ -0,0 +1,43 @@
+#define _POSIX_C_SOURCE 200809L
+
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+volatile sig_atomic_t reload_requested = 0;
+
+void log_message(const char *message){
+        char *copy = malloc(strlen(message) + 1);
+        strcpy(copy, message);
+        write(STDERR_FILENO, copy, strlen(copy));
+        free(copy);
+}
+
+void interrupt_handler(int signum){
+        printf("Interrupted by signal %d\n", signum);
+        _exit(1);
+}
+
+void terminate_handler(int signum){
+        log_message("Terminating\n");
+        _exit(1);
+}
+
+void hangup_handler(int signum){
+        // Only async-signal-safe operations.
+        reload_requested = 1;
+        write(STDERR_FILENO, "Reloading\n", 10);
+}
+
+int main(void){
+        struct sigaction action;
+        memset(&action, 0, sizeof(action));
+        action.sa_handler = terminate_handler;
+        sigaction(SIGTERM, &action, NULL);
+        signal(SIGINT, interrupt_handler);
+        signal(SIGHUP, hangup_handler);
+        pause();
+        return reload_requested;
+}
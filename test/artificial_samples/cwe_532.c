@@ -0,0 +1,33 @@
+#include <stdio.h>
+#include <string.h>
+#include <syslog.h>
+#include <unistd.h>
+
+int login(const char *user){
+        char *password = getpass("Password: ");
+        syslog(LOG_DEBUG, "login of %s with password %s", user, password);
+        int ok = strlen(password) > 8;
+        memset(password, 0, strlen(password));
+        return ok;
+}
+
+void report_failure(const char *user){
+        char *password = getpass("Password: ");
+        fprintf(stderr, "wrong password for %s: %s\n", user, password);
+        memset(password, 0, strlen(password));
+}
+
+void log_user(const char *user){
+        syslog(LOG_INFO, "user %s logged in", user);
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc < 2)
+                return 1;
+        if (login(argv[1]))
+                log_user(argv[1]);
+        else
+                report_failure(argv[1]);
+        return 0;
+}
@@ -0,0 +1,34 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <unistd.h>
+
+int main(int argc, char **argv)
+{
+  unsigned int num_items = 0;
+  int count;
+  int limit;
+
+  if (argc < 3) {
+    return 1;
+  }
+  count = atoi(argv[1]);
+  for (int i = 0; i < count; i++) {
+    putchar('*');
+  }
+
+  limit = atoi(argv[2]);
+  if (limit > 100) {
+    return 1;
+  }
+  for (int i = 0; i < limit; i++) {
+    putchar('#');
+  }
+
+  if (read(0, &num_items, sizeof(num_items)) != sizeof(num_items)) {
+    return 1;
+  }
+  for (unsigned int i = 0; i < num_items; i++) {
+    putchar('.');
+  }
+  return 0;
+}
@@ -0,0 +1,34 @@
+#include <assert.h>
+#include <stdlib.h>
+#include <unistd.h>
+
+#define MAX_LEVEL 10
+
+static void parse_header(const unsigned char *header)
+{
+  assert(header[0] == 0x7f);
+}
+
+static void check_config(int level)
+{
+  assert(level < MAX_LEVEL);
+}
+
+int main(int argc, char **argv)
+{
+  unsigned char header[16];
+  int level;
+
+  check_config(MAX_LEVEL - 1);
+  if (argc < 2) {
+    return 1;
+  }
+  level = atoi(argv[1]);
+  assert(level < MAX_LEVEL);
+
+  if (read(0, header, sizeof(header)) != sizeof(header)) {
+    return 1;
+  }
+  parse_header(header);
+  return 0;
+}
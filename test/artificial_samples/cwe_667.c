@@ -0,0 +1,50 @@
+#include <pthread.h>
+#include <stdio.h>
+
+pthread_mutex_t table_lock = PTHREAD_MUTEX_INITIALIZER;
+pthread_mutex_t log_lock = PTHREAD_MUTEX_INITIALIZER;
+int table[16];
+
+void log_message(const char *message){
+        pthread_mutex_lock(&log_lock);
+        puts(message);
+        pthread_mutex_unlock(&log_lock);
+}
+
+int update_table(int index, int value){
+        pthread_mutex_lock(&table_lock);
+        // The lock is not released on the error path.
+        if (index < 0 || index >= 16)
+                return -1;
+        table[index] = value;
+        pthread_mutex_unlock(&table_lock);
+        return 0;
+}
+
+void print_table(void){
+        pthread_mutex_lock(&log_lock);
+        // The lock is acquired again by log_message.
+        log_message("table:");
+        // The locks are acquired in the inverse order in clear_table.
+        pthread_mutex_lock(&table_lock);
+        for (int i = 0; i < 16; i++)
+                printf("%d\n", table[i]);
+        pthread_mutex_unlock(&table_lock);
+        pthread_mutex_unlock(&log_lock);
+}
+
+void clear_table(void){
+        pthread_mutex_lock(&table_lock);
+        for (int i = 0; i < 16; i++)
+                table[i] = 0;
+        log_message("table cleared");
+        pthread_mutex_unlock(&table_lock);
+}
+
+int main(int argc, char *argv[argc])
+{
+        update_table(argc, 1);
+        print_table();
+        clear_table();
+        return 0;
+}
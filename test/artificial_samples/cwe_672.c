@@ -0,0 +1,45 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <unistd.h>
+
+void log_line(FILE *file, const char *line){
+        fputs(line, file);
+}
+
+int write_after_close(const char *path, const char *footer){
+        FILE *file = fopen(path, "w");
+        if (file == NULL)
+                return -1;
+        log_line(file, "header\n");
+        fclose(file);
+        log_line(file, footer);
+        return 0;
+}
+
+int read_after_close(const char *path){
+        char buffer[16];
+        int fd = open(path, O_RDONLY);
+        if (fd < 0)
+                return -1;
+        close(fd);
+        return read(fd, buffer, sizeof(buffer));
+}
+
+int write_before_close(const char *path){
+        int fd = open(path, O_WRONLY);
+        if (fd < 0)
+                return -1;
+        write(fd, "x", 1);
+        close(fd);
+        return 0;
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 2) {
+                write_after_close(argv[1], argv[2]);
+                read_after_close(argv[1]);
+                write_before_close(argv[1]);
+        }
+        return 0;
+}
@@ -0,0 +1,32 @@
+#include <stdio.h>
+
+/* Skips a nested list like "((()))" read from stdin. The nesting depth is controlled by the input. */
+int parse_list(void)
+{
+  int c;
+  while ((c = getchar()) != EOF) {
+    if (c == '(') {
+      if (parse_list() != 0) {
+        return -1;
+      }
+    } else if (c == ')') {
+      return 0;
+    }
+  }
+  return -1;
+}
+
+/* The recursion depth is bounded by the depth parameter. */
+int count_down(int depth)
+{
+  if (depth <= 0) {
+    return 0;
+  }
+  return count_down(depth - 1) + 1;
+}
+
+int main(void)
+{
+  printf("%d\n", count_down(10));
+  return parse_list();
+}
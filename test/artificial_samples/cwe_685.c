@@ -0,0 +1,19 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+void print_missing_argument(void){
+        int value = rand();
+        printf("%d %d\n", value);
+}
+
+void print_all_arguments(void){
+        int value = rand();
+        printf("%d %d\n", value, value + 1);
+}
+
+int main(void)
+{
+        print_missing_argument();
+        print_all_arguments();
+        return 0;
+}
@@ -0,0 +1,23 @@
+#include <stdlib.h>
+
+void write_unchecked(size_t index){
+        char *buffer = malloc(64);
+        buffer[index] = 'A';
+        free(buffer);
+}
+
+void write_checked(size_t index){
+        char *buffer = malloc(64);
+        if (buffer == NULL) {
+                return;
+        }
+        buffer[index] = 'A';
+        free(buffer);
+}
+
+int main(int argc, char *argv[argc])
+{
+        write_unchecked(argc);
+        write_checked(argc);
+        return 0;
+}
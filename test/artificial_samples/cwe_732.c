@@ -0,0 +1,18 @@
+#include <fcntl.h>
+#include <sys/stat.h>
+#include <sys/types.h>
+#include <unistd.h>
+
+int main(void){
+        // Disables the permission mask of the process.
+        umask(0);
+        // Creates a world-writable file.
+        int fd = open("/tmp/cwe_732_log", O_CREAT | O_WRONLY, S_IRUSR | S_IWUSR | S_IRGRP | S_IWGRP | S_IROTH | S_IWOTH);
+        if (fd >= 0)
+                close(fd);
+        // Sets the setuid bit.
+        chmod("/tmp/cwe_732_tool", S_IRWXU | S_ISUID);
+        // Creates a private directory, which is not reported.
+        mkdir("/tmp/cwe_732_private", S_IRWXU);
+        return 0;
+}
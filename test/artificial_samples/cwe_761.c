@@ -0,0 +1,31 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+void release_token(char *token){
+        free(token);
+}
+
+void skip_whitespace_and_free(char *input){
+        char *token = input;
+        while (*token == ' ')
+                token++;
+        puts(token);
+        free(input);
+}
+
+int main(int argc, char **argv){
+        char *line = malloc(32);
+        if (line == NULL)
+                return 1;
+        strcpy(line, "key: value");
+        puts(line + 5);
+        release_token(line + 5);
+
+        char *copy = malloc(32);
+        if (copy == NULL)
+                return 1;
+        strcpy(copy, "  text");
+        skip_whitespace_and_free(copy);
+        return 0;
+}
@@ -0,0 +1,58 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+struct node {
+        struct node *next;
+        int value;
+};
+
+struct node *build_list(const char *count_string){
+        struct node *list = NULL;
+        int count = atoi(count_string);
+        for (int i = 0; i < count; i++) {
+                struct node *node = malloc(sizeof(struct node));
+                if (node == NULL)
+                        break;
+                node->value = i;
+                node->next = list;
+                list = node;
+        }
+        return list;
+}
+
+struct node *build_bounded_list(const char *count_string){
+        struct node *list = NULL;
+        int count = atoi(count_string);
+        if (count > 64)
+                count = 64;
+        for (int i = 0; i < count; i++) {
+                struct node *node = malloc(sizeof(struct node));
+                if (node == NULL)
+                        break;
+                node->value = i;
+                node->next = list;
+                list = node;
+        }
+        return list;
+}
+
+int sum_squares(const char *count_string){
+        int count = atoi(count_string);
+        int squares[count];
+        int sum = 0;
+        for (int i = 0; i < count; i++)
+                squares[i] = i * i;
+        for (int i = 0; i < count; i++)
+                sum += squares[i];
+        return sum;
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 1) {
+                build_list(argv[1]);
+                build_bounded_list(argv[1]);
+                printf("%d\n", sum_squares(argv[1]));
+        }
+        return 0;
+}
@@ -0,0 +1,41 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <unistd.h>
+
+int leak_on_error_path(const char *path){
+        char buffer[64];
+        int fd = open(path, O_RDONLY);
+        if (fd < 0)
+                return -1;
+        if (read(fd, buffer, sizeof(buffer)) <= 0)
+                return -1;
+        close(fd);
+        return buffer[0];
+}
+
+int return_descriptor(const char *path){
+        return open(path, O_RDONLY);
+}
+
+int count_lines(const char *path){
+        int lines = 0;
+        int c;
+        FILE *file = fopen(path, "r");
+        if (file == NULL)
+                return -1;
+        while ((c = fgetc(file)) != EOF)
+                if (c == '\n')
+                        lines++;
+        fclose(file);
+        return lines;
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 1) {
+                leak_on_error_path(argv[1]);
+                close(return_descriptor(argv[1]));
+                count_lines(argv[1]);
+        }
+        return 0;
+}
@@ -0,0 +1,32 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define MAX_LENGTH 4096
+
+char *unbounded_allocation(char *input){
+        unsigned long length = strtoul(input, NULL, 10);
+        char *buffer = malloc(length);
+        if (buffer != NULL)
+                memset(buffer, 0, length);
+        return buffer;
+}
+
+char *bounded_allocation(char *input){
+        unsigned long length = strtoul(input, NULL, 10);
+        if (length > MAX_LENGTH)
+                return NULL;
+        char *buffer = malloc(length);
+        if (buffer != NULL)
+                memset(buffer, 0, length);
+        return buffer;
+}
+
+int main(int argc, char *argv[argc])
+{
+        if (argc > 1) {
+                free(unbounded_allocation(argv[1]));
+                free(bounded_allocation(argv[1]));
+        }
+        return 0;
+}
@@ -0,0 +1,28 @@
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+int check_password(char *password)
+{
+  return strcmp(password, "s3cr3t_p4ssw0rd") == 0;
+}
+
+int main(void)
+{
+  char user[64];
+  char password[64];
+
+  if (fgets(user, sizeof(user), stdin) == NULL) {
+    return 1;
+  }
+  if (strcmp(user, "admin\n") == 0) {
+    puts("Hello admin");
+  }
+  if (read(0, password, sizeof(password) - 1) <= 0) {
+    return 1;
+  }
+  if (check_password(password)) {
+    puts("Access granted");
+  }
+  return 0;
+}
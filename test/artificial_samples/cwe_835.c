@@ -0,0 +1,37 @@
+#include <stdio.h>
+
+void print_forever(void)
+{
+  for (;;) {
+    putchar('.');
+  }
+}
+
+int count_items(int num_items)
+{
+  int index = 0;
+  int count = 0;
+  /* The loop counter is never incremented. */
+  while (index < num_items) {
+    count++;
+  }
+  return count;
+}
+
+int sum_items(int num_items)
+{
+  int sum = 0;
+  for (int index = 0; index < num_items; index++) {
+    sum += index;
+  }
+  return sum;
+}
+
+int main(int argc, char **argv)
+{
+  if (argc > 2) {
+    print_forever();
+  }
+  printf("%d %d\n", count_items(argc), sum_items(argc));
+  return 0;
+}
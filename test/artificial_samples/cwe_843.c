@@ -0,0 +1,36 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+struct value {
+        int is_string;
+        union {
+                int number;
+                char *string;
+        } content;
+};
+
+struct value *new_number(int number){
+        struct value *value = malloc(sizeof(struct value));
+        value->is_string = 0;
+        value->content.number = number;
+        return value;
+}
+
+void print_string(struct value *value){
+        // The tag is not checked before the string member is accessed.
+        puts(value->content.string);
+        putchar(value->content.string[0]);
+}
+
+int print_number(struct value *value){
+        return printf("%d\n", value->content.number);
+}
+
+int main(int argc, char *argv[argc])
+{
+        struct value *number = new_number(argc);
+        print_number(number);
+        print_string(number);
+        free(number);
+        return 0;
+}
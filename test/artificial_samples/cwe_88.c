@@ -0,0 +1,51 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+void list_directory(void)
+{
+  char directory[64];
+  char *args[3];
+
+  if (fgets(directory, sizeof(directory), stdin) == NULL) {
+    return;
+  }
+  directory[strcspn(directory, "\n")] = '\0';
+  args[0] = "/bin/ls";
+  args[1] = directory;
+  args[2] = NULL;
+  execv("/bin/ls", args);
+}
+
+void list_directory_safely(void)
+{
+  char directory[64];
+  char *args[4];
+
+  if (fgets(directory, sizeof(directory), stdin) == NULL) {
+    return;
+  }
+  directory[strcspn(directory, "\n")] = '\0';
+  args[0] = "/bin/ls";
+  args[1] = "--";
+  args[2] = directory;
+  args[3] = NULL;
+  execv("/bin/ls", args);
+}
+
+int main(int argc, char **argv)
+{
+  char *args[3];
+
+  if (argc < 2) {
+    list_directory();
+    list_directory_safely();
+    return 1;
+  }
+  args[0] = "file";
+  args[1] = argv[1];
+  args[2] = NULL;
+  execvp("file", args);
+  return 1;
+}
@@ -0,0 +1,38 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+struct message {
+        int type;
+        int length;
+};
+
+int read_uninitialized_field(){
+        struct message *msg = malloc(sizeof(struct message));
+        msg->type = 1;
+        int length = msg->length;
+        free(msg);
+        return length;
+}
+
+int read_initialized_field(){
+        struct message *msg = malloc(sizeof(struct message));
+        msg->type = 1;
+        msg->length = 0;
+        int length = msg->length;
+        free(msg);
+        return length;
+}
+
+int read_calloc_field(){
+        struct message *msg = calloc(1, sizeof(struct message));
+        int length = msg->length;
+        free(msg);
+        return length;
+}
+
+int main(){
+        printf("%d\n", read_uninitialized_field());
+        printf("%d\n", read_initialized_field());
+        printf("%d\n", read_calloc_field());
+        return 0;
+}
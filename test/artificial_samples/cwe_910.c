@@ -0,0 +1,32 @@
+#include <stdio.h>
+#include <sys/types.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main(void){
+        int fds[2];
+        char buffer[64];
+        if (pipe(fds) != 0)
+                return 1;
+        pid_t pid = fork();
+        if (pid < 0)
+                return 1;
+        if (pid == 0){
+                dup2(fds[1], STDOUT_FILENO);
+                close(fds[0]);
+                close(fds[1]);
+                execlp("echo", "echo", "hello", NULL);
+                _exit(127);
+        }
+        dup2(fds[0], STDIN_FILENO);
+        close(fds[0]);
+        close(fds[1]);
+        // The read end of the pipe was moved to stdin and closed, but is still used.
+        ssize_t length = read(fds[0], buffer, sizeof(buffer) - 1);
+        waitpid(pid, NULL, 0);
+        if (length > 0){
+                buffer[length] = 0;
+                printf("%s", buffer);
+        }
+        return 0;
+}
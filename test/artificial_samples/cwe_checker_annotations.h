@@ -0,0 +1,22 @@
+/*
+ * Assertion annotations for the acceptance tests of the cwe_checker.
+ *
+ * A call to CWE_CHECKER_EXPECT_WARNING(CWE416) inside a function states that the cwe_checker
+ * should generate a CWE416 warning for that function.
+ * A call to CWE_CHECKER_EXPECT_NO_WARNING(CWE416) states that it should not generate one.
+ * The annotations are checked if the cwe_checker is run with the --check-annotations flag.
+ *
+ * Before using an annotation for a CWE, the marker functions for it have to be defined
+ * via CWE_CHECKER_DEFINE_MARKERS(CWE416) at file scope.
+ */
+#ifndef CWE_CHECKER_ANNOTATIONS_H
+#define CWE_CHECKER_ANNOTATIONS_H
+
+#define CWE_CHECKER_DEFINE_MARKERS(cwe) \
+    __attribute__((noinline)) void __cwe_checker_expect_warning_##cwe(void) { __asm__ volatile(""); } \
+    __attribute__((noinline)) void __cwe_checker_expect_no_warning_##cwe(void) { __asm__ volatile(""); }
+
+#define CWE_CHECKER_EXPECT_WARNING(cwe) __cwe_checker_expect_warning_##cwe()
+#define CWE_CHECKER_EXPECT_NO_WARNING(cwe) __cwe_checker_expect_no_warning_##cwe()
+
+#endif
@@ -40,6 +40,9 @@ impl CweTestCase {
 
     /// Run the test case and print to the shell, whether the test case succeeded or not.
     /// Returns stdout + stderr of the test execution on failure.
+    ///
+    /// The test case fails if the number of warnings does not match the expected number of occurences
+    /// or if an assertion annotation in the test binary for the CWE of the search string is violated.
     pub fn run_test(
         &self,
         search_string: &str,
@@ -55,15 +58,24 @@ impl CweTestCase {
             .arg("--partial")
             .arg(self.check_name)
             .arg("--quiet")
+            .arg("--check-annotations")
             .output()
             .unwrap();
         if output.status.success() {
-            let num_cwes = String::from_utf8(output.stdout)
-                .unwrap()
+            let stdout = String::from_utf8(output.stdout).unwrap();
+            let num_cwes = stdout
                 .lines()
                 .filter(|line| line.starts_with(search_string))
                 .count();
-            if num_cwes == num_expected_occurences {
+            // Only annotations for the CWE of the test case are relevant for it.
+            let failed_annotations: Vec<&str> = stdout
+                .lines()
+                .filter(|line| line.starts_with(&format!("[ANNOTATION FAILED]{}", search_string)))
+                .collect();
+            if !failed_annotations.is_empty() {
+                println!("{} \t {}", filepath, "[FAILED]".red());
+                Err(failed_annotations.join("\n"))
+            } else if num_cwes == num_expected_occurences {
                 println!("{} \t {}", filepath, "[OK]".green());
                 Ok(())
             } else {
@@ -168,6 +180,24 @@ pub fn print_errors(error_log: Vec<(String, String)>) {
 mod tests {
     use super::*;
 
+    #[test]
+    #[ignore]
+    fn cwe_14() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_14", "CWE14");
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE14]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
     #[test]
     #[ignore]
     fn cwe_78() {
@@ -202,18 +232,30 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_190() {
+    fn cwe_88() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_190", "CWE190");
-
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        let tests = linux_test_cases("cwe_88", "CWE88");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE88]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
-        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+    #[test]
+    #[ignore]
+    fn cwe_120() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_120", "CWE120");
 
         for test_case in tests {
             let num_expected_occurences = 3;
-            if let Err(error) = test_case.run_test("[CWE190]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE120]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -225,13 +267,13 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_215() {
+    fn cwe_121() {
         let mut error_log = Vec::new();
-        let tests = linux_test_cases("cwe_476", "CWE215"); // We use the test binaries of another check here.
+        let tests = all_test_cases("cwe_121", "CWE121");
 
         for test_case in tests {
-            let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE215]", num_expected_occurences) {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE121]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -243,16 +285,13 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_243() {
+    fn cwe_122() {
         let mut error_log = Vec::new();
-        let mut tests = linux_test_cases("cwe_243", "CWE243");
-
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        let tests = all_test_cases("cwe_122", "CWE122");
 
         for test_case in tests {
             let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE243]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE122]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -264,18 +303,13 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_332() {
+    fn cwe_129() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_332", "CWE332");
-
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
-
-        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+        let tests = all_test_cases("cwe_129", "CWE129");
 
         for test_case in tests {
             let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE332]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE129]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -287,18 +321,30 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_367() {
+    fn cwe_131() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_367", "CWE367");
-
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        let tests = linux_test_cases("cwe_131", "CWE131");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE131]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
-        mark_skipped(&mut tests, "x86", "mingw32-gcc"); // Symbol names are prefixed with an underscore in the Ghidra output.
+    #[test]
+    #[ignore]
+    fn cwe_134() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_134", "CWE134");
 
         for test_case in tests {
-            let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE367]", num_expected_occurences) {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE134]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -310,23 +356,18 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_415() {
+    fn cwe_190() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_415", "Memory");
+        let mut tests = all_test_cases("cwe_190", "CWE190");
 
         mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
         mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
 
-        // The analysis loses track of the stack pointer offset in the main() function
-        // because of a "INT_AND ESP 0xfffffff0" instruction.
-        // We would need knowledge about alignment guarantees for the stack pointer at the start of main() to fix this.
-        mark_skipped(&mut tests, "x86", "gcc");
-
         mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
 
         for test_case in tests {
-            let num_expected_occurences = 2;
-            if let Err(error) = test_case.run_test("[CWE415]", num_expected_occurences) {
+            let num_expected_occurences = 3;
+            if let Err(error) = test_case.run_test("[CWE190]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -338,23 +379,21 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_416() {
+    fn cwe_197() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_416", "Memory");
-
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        let mut tests = all_test_cases("cwe_197", "CWE197");
 
-        // The analysis loses track of the stack pointer offset in the main() function
-        // because of a "INT_AND ESP 0xfffffff0" instruction.
-        // We would need knowledge about alignment guarantees for the stack pointer at the start of main() to fix this.
-        mark_skipped(&mut tests, "x86", "gcc");
-
-        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+        // The truncation only exists on architectures where `unsigned long` has 64 bits.
+        mark_architecture_skipped(&mut tests, "arm");
+        mark_architecture_skipped(&mut tests, "mips");
+        mark_architecture_skipped(&mut tests, "mipsel");
+        mark_architecture_skipped(&mut tests, "ppc");
+        mark_architecture_skipped(&mut tests, "x86");
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // `unsigned long` has 32 bits on Windows.
 
         for test_case in tests {
             let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE416]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE197]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -366,18 +405,31 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_426() {
+    fn cwe_208() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_426", "CWE426");
+        let tests = all_test_cases("cwe_208", "CWE208");
 
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE208]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
-        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+    #[test]
+    #[ignore]
+    fn cwe_215() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_476", "CWE215"); // We use the test binaries of another check here.
 
         for test_case in tests {
             let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE426]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE215]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -389,27 +441,16 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_467() {
+    fn cwe_243() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_467", "CWE467");
-
-        // Only one instance is found.
-        // Other instance cannot be found, since the constant is not defined in the basic block of the call instruction.
-        mark_skipped(&mut tests, "aarch64", "clang");
-        mark_skipped(&mut tests, "arm", "clang");
-        mark_skipped(&mut tests, "mips", "clang");
-        mark_skipped(&mut tests, "mipsel", "clang");
-        mark_skipped(&mut tests, "mips64", "clang");
-        mark_skipped(&mut tests, "mips64el", "clang");
+        let mut tests = linux_test_cases("cwe_243", "CWE243");
 
         mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
         mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
 
-        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
-
         for test_case in tests {
-            let num_expected_occurences = 2;
-            if let Err(error) = test_case.run_test("[CWE467]", num_expected_occurences) {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE243]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -421,18 +462,48 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_476() {
+    fn cwe_252() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_476", "CWE476");
+        let tests = linux_test_cases("cwe_252", "CWE252");
 
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE252]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
-        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+    #[test]
+    #[ignore]
+    fn cwe_273() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_273", "CWE273");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE273]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
+    #[test]
+    #[ignore]
+    fn cwe_295() {
+        let mut error_log = Vec::new();
+        // The sample needs the OpenSSL headers and library, which are only installed for x64.
+        let tests = new_test_cases("cwe_295", &["x64"], COMPILERS, "CWE295");
         for test_case in tests {
             let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE476]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE295]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -444,16 +515,49 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_560() {
+    fn cwe_321() {
         let mut error_log = Vec::new();
-        let mut tests = linux_test_cases("cwe_560", "CWE560");
+        // The sample needs the OpenSSL headers and library, which are only installed for x64.
+        let tests = new_test_cases("cwe_321", &["x64"], COMPILERS, "CWE321");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE321]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
-        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
-        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+    #[test]
+    #[ignore]
+    fn cwe_326() {
+        let mut error_log = Vec::new();
+        // The sample needs the OpenSSL headers and library, which are only installed for x64.
+        let tests = new_test_cases("cwe_326", &["x64"], COMPILERS, "CWE326");
+        for test_case in tests {
+            let num_expected_occurences = 3;
+            if let Err(error) = test_case.run_test("[CWE326]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
 
+    #[test]
+    #[ignore]
+    fn cwe_327() {
+        let mut error_log = Vec::new();
+        // The sample needs the OpenSSL headers and library, which are only installed for x64.
+        let tests = new_test_cases("cwe_327", &["x64"], COMPILERS, "CWE327");
         for test_case in tests {
-            let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE560]", num_expected_occurences) {
+            let num_expected_occurences = 3;
+            if let Err(error) = test_case.run_test("[CWE327]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }
@@ -465,26 +569,19 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_676() {
+    fn cwe_332() {
         let mut error_log = Vec::new();
-        let mut tests = all_test_cases("cwe_676", "CWE676");
+        let mut tests = all_test_cases("cwe_332", "CWE332");
 
         mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
         mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
         mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
 
         for test_case in tests {
-            if test_case.architecture == "aarch64" && test_case.compiler == "clang" {
-                // For some reason clang adds an extra `memcpy` here, which is also in the list of dangerous functions.
-                let num_expected_occurences = 2;
-                if let Err(error) = test_case.run_test("[CWE676]", num_expected_occurences) {
-                    error_log.push((test_case.get_filepath(), error));
-                }
-            } else {
-                let num_expected_occurences = 1;
-                if let Err(error) = test_case.run_test("[CWE676]", num_expected_occurences) {
-                    error_log.push((test_case.get_filepath(), error));
-                }
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE332]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
             }
         }
         if !error_log.is_empty() {
@@ -495,12 +592,784 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn cwe_782() {
+    fn cwe_337() {
         let mut error_log = Vec::new();
-        let tests = new_test_cases("cwe_782", &["x64"], COMPILERS, "CWE782");
+        let tests = linux_test_cases("cwe_337", "CWE337");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE337]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_362() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_362", "CWE362");
+
         for test_case in tests {
             let num_expected_occurences = 1;
-            if let Err(error) = test_case.run_test("[CWE782]", num_expected_occurences) {
+            if let Err(error) = test_case.run_test("[CWE362]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_367() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_367", "CWE367");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        mark_skipped(&mut tests, "x86", "mingw32-gcc"); // Symbol names are prefixed with an underscore in the Ghidra output.
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE367]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_369() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_369", "CWE369");
+
+        mark_architecture_skipped(&mut tests, "arm"); // Integer division is a call to a library function.
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "x86"); // Parameters are passed on the stack.
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE369]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_401() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_401", "CWE401");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE401]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_415() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_415", "Memory");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        // The analysis loses track of the stack pointer offset in the main() function
+        // because of a "INT_AND ESP 0xfffffff0" instruction.
+        // We would need knowledge about alignment guarantees for the stack pointer at the start of main() to fix this.
+        mark_skipped(&mut tests, "x86", "gcc");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE415]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_416() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_416", "Memory");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        // The analysis loses track of the stack pointer offset in the main() function
+        // because of a "INT_AND ESP 0xfffffff0" instruction.
+        // We would need knowledge about alignment guarantees for the stack pointer at the start of main() to fix this.
+        mark_skipped(&mut tests, "x86", "gcc");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE416]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_416_stale_length() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_416_stale_length", "Memory");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        // The analysis loses track of the stack pointer offset in the main() function
+        // because of a "INT_AND ESP 0xfffffff0" instruction.
+        mark_skipped(&mut tests, "x86", "gcc");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE416]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_426() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_426", "CWE426");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE426]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_457() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_457", "CWE457");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE457]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_467() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_467", "CWE467");
+
+        // Only one instance is found.
+        // Other instance cannot be found, since the constant is not defined in the basic block of the call instruction.
+        mark_skipped(&mut tests, "aarch64", "clang");
+        mark_skipped(&mut tests, "arm", "clang");
+        mark_skipped(&mut tests, "mips", "clang");
+        mark_skipped(&mut tests, "mipsel", "clang");
+        mark_skipped(&mut tests, "mips64", "clang");
+        mark_skipped(&mut tests, "mips64el", "clang");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE467]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_468() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_468", "CWE468");
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE468]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_469() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_469", "CWE469");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE469]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_476() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_476", "CWE476");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE476]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_477() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_477", "CWE477");
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE477]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_479() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_479", "CWE479");
+
+        for test_case in tests {
+            let num_expected_occurences = match test_case.architecture {
+                // The handler registered with sigaction is not found,
+                // since MIPS stores it at a different offset of the struct
+                // and x86 passes the pointer to the struct on the stack.
+                "mips" | "mipsel" | "mips64" | "mips64el" | "x86" => 1,
+                _ => 3,
+            };
+            if let Err(error) = test_case.run_test("[CWE479]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_532() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_532", "CWE532");
+
+        for test_case in tests {
+            if test_case.architecture == "x86" {
+                // The stream parameter of `fprintf` is passed on the stack, so only the `syslog` call is found.
+                let num_expected_occurences = 1;
+                if let Err(error) = test_case.run_test("[CWE532]", num_expected_occurences) {
+                    error_log.push((test_case.get_filepath(), error));
+                }
+            } else {
+                let num_expected_occurences = 2;
+                if let Err(error) = test_case.run_test("[CWE532]", num_expected_occurences) {
+                    error_log.push((test_case.get_filepath(), error));
+                }
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_560() {
+        let mut error_log = Vec::new();
+        let mut tests = linux_test_cases("cwe_560", "CWE560");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE560]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_606() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_606", "CWE606");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE606]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_617() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_617", "CWE617");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE617]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_667() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_667", "CWE667");
+
+        for test_case in tests {
+            let num_expected_occurences = 3;
+            if let Err(error) = test_case.run_test("[CWE667]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_672() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_672", "CWE672");
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE672]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_674() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_674", "CWE674");
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE674]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_676() {
+        let mut error_log = Vec::new();
+        let mut tests = all_test_cases("cwe_676", "CWE676");
+
+        mark_architecture_skipped(&mut tests, "ppc64"); // Ghidra generates mangled function names here for some reason.
+        mark_architecture_skipped(&mut tests, "ppc64le"); // Ghidra generates mangled function names here for some reason.
+        mark_compiler_skipped(&mut tests, "mingw32-gcc"); // TODO: Check reason for failure!
+
+        for test_case in tests {
+            if test_case.architecture == "aarch64" && test_case.compiler == "clang" {
+                // For some reason clang adds an extra `memcpy` here, which is also in the list of dangerous functions.
+                let num_expected_occurences = 2;
+                if let Err(error) = test_case.run_test("[CWE676]", num_expected_occurences) {
+                    error_log.push((test_case.get_filepath(), error));
+                }
+            } else {
+                let num_expected_occurences = 1;
+                if let Err(error) = test_case.run_test("[CWE676]", num_expected_occurences) {
+                    error_log.push((test_case.get_filepath(), error));
+                }
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_685() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_685", "CWE685");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE685]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_690() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_690", "CWE690");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE690]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_732() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_732", "CWE732");
+
+        for test_case in tests {
+            let num_expected_occurences = 3;
+            if let Err(error) = test_case.run_test("[CWE732]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_761() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_761", "CWE761");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE761]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_770() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_770", "CWE770");
+
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE770]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_775() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_775", "CWE775");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE775]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_782() {
+        let mut error_log = Vec::new();
+        let tests = new_test_cases("cwe_782", &["x64"], COMPILERS, "CWE782");
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE782]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_789() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_789", "CWE789");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE789]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_798() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_798", "CWE798");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE798]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_835() {
+        let mut error_log = Vec::new();
+        let tests = linux_test_cases("cwe_835", "CWE835");
+        for test_case in tests {
+            let num_expected_occurences = 2;
+            if let Err(error) = test_case.run_test("[CWE835]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_843() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_843", "CWE843");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE843]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_908() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_908", "CWE908");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE908]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_910() {
+        let mut error_log = Vec::new();
+        let mut tests = linux_test_cases("cwe_910", "CWE910");
+
+        mark_architecture_skipped(&mut tests, "x86"); // The pipe array is passed on the stack, which is not tracked.
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE910]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_1335() {
+        let mut error_log = Vec::new();
+        // The other architectures mask the shift amount of their shift instructions.
+        let tests = new_test_cases("cwe_1335", &["arm"], COMPILERS, "CWE1335");
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE1335]", num_expected_occurences) {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_1341() {
+        let mut error_log = Vec::new();
+        let tests = all_test_cases("cwe_1341", "CWE1341");
+
+        for test_case in tests {
+            let num_expected_occurences = 1;
+            if let Err(error) = test_case.run_test("[CWE1341]", num_expected_occurences) {
                 error_log.push((test_case.get_filepath(), error));
             }
         }